@@ -0,0 +1,174 @@
+//! API key items.
+//!
+//! An API key is an item of kind `ApiKey`: the secret is its password, its
+//! expiry is the item's `expires_at`, and what a list needs to show without
+//! decrypting anything (provider, key id, scopes and the last characters of
+//! the secret) is kept in `ApiKeyDetails`. Presets fill in what is known about
+//! common providers.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Credential, CredentialSummary};
+
+/// Characters of the secret shown in lists
+const PREVIEW_CHARS: usize = 4;
+
+/// Days before expiry that items are reminded of unless configured otherwise
+pub const DEFAULT_EXPIRY_REMINDER_DAYS: u32 = 14;
+
+/// What is known about a provider's keys
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiKeyPreset {
+    /// Stored as `ApiKeyDetails::provider`
+    pub id: &'static str,
+    pub name: &'static str,
+    /// Site given to new items
+    pub site: &'static str,
+    /// What the provider calls the public half of the key, if it has one
+    pub key_id_label: Option<&'static str>,
+    pub secret_label: &'static str,
+    /// Scopes the provider offers, for suggestions
+    pub scopes: &'static [&'static str],
+    /// Suggested lifetime of new keys in days (None = keys do not expire)
+    pub suggested_lifetime_days: Option<u32>,
+}
+
+pub const PRESETS: &[ApiKeyPreset] = &[
+    ApiKeyPreset {
+        id: "aws",
+        name: "AWS",
+        site: "aws.amazon.com",
+        key_id_label: Some("Access key ID"),
+        secret_label: "Secret access key",
+        scopes: &[],
+        // Access keys do not expire, but AWS recommends rotating them
+        suggested_lifetime_days: Some(90),
+    },
+    ApiKeyPreset {
+        id: "github",
+        name: "GitHub",
+        site: "github.com",
+        key_id_label: None,
+        secret_label: "Personal access token",
+        scopes: &[
+            "repo",
+            "workflow",
+            "write:packages",
+            "read:packages",
+            "read:org",
+            "gist",
+            "user",
+        ],
+        suggested_lifetime_days: Some(90),
+    },
+    ApiKeyPreset {
+        id: "stripe",
+        name: "Stripe",
+        site: "stripe.com",
+        key_id_label: Some("Publishable key"),
+        secret_label: "Secret key",
+        scopes: &[],
+        suggested_lifetime_days: None,
+    },
+];
+
+/// Preset for a provider id, e.g. `github`
+pub fn preset(provider: &str) -> Option<&'static ApiKeyPreset> {
+    PRESETS
+        .iter()
+        .find(|preset| preset.id.eq_ignore_ascii_case(provider))
+}
+
+/// Preset id for a provider name as `secret_scan` reports it, e.g. `GitHub`
+pub fn provider_id(name: &str) -> String {
+    PRESETS
+        .iter()
+        .find(|preset| preset.name.eq_ignore_ascii_case(name))
+        .map_or_else(|| name.to_lowercase(), |preset| preset.id.to_string())
+}
+
+/// Unencrypted details of an API key item
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct ApiKeyDetails {
+    /// A preset id or any other name
+    pub provider: String,
+    /// The public identifier that goes with the secret (e.g. an AWS access key ID)
+    #[serde(default)]
+    pub key_id: Option<String>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// The secret masked to its last characters, e.g. `••••9f3a`; kept up to
+    /// date by the vault
+    #[serde(default)]
+    pub secret_preview: String,
+}
+
+/// An API key as entered by the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyInput {
+    /// A preset id or any other name
+    pub provider: String,
+    /// Defaults to the preset's site, or the provider
+    #[serde(default)]
+    pub site: Option<String>,
+    /// The account the key belongs to
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub key_id: Option<String>,
+    pub secret: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl ApiKeyInput {
+    pub fn site(&self) -> String {
+        self.site
+            .clone()
+            .filter(|site| !site.trim().is_empty())
+            .or_else(|| preset(&self.provider).map(|preset| preset.site.to_string()))
+            .unwrap_or_else(|| self.provider.clone())
+    }
+
+    pub fn details(&self) -> ApiKeyDetails {
+        ApiKeyDetails {
+            provider: self.provider.trim().to_string(),
+            key_id: self.key_id.clone().filter(|key_id| !key_id.is_empty()),
+            scopes: self.scopes.clone(),
+            secret_preview: secret_preview(&self.secret),
+        }
+    }
+}
+
+/// `secret` masked to its last characters; short secrets are masked entirely
+pub fn secret_preview(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    // Showing 4 of 8 characters would give away too much
+    if chars.len() < PREVIEW_CHARS * 3 {
+        return "••••".to_string();
+    }
+    let tail: String = chars[chars.len() - PREVIEW_CHARS..].iter().collect();
+    format!("••••{}", tail)
+}
+
+/// Items that expire within `within_days` of `now` or already have, soonest first
+pub fn expiring(
+    credentials: Vec<Credential>,
+    within_days: u32,
+    now: DateTime<Utc>,
+) -> Vec<CredentialSummary> {
+    let cutoff = now + Duration::days(i64::from(within_days));
+    let mut expiring: Vec<Credential> = credentials
+        .into_iter()
+        .filter(|credential| credential.expires_at.is_some_and(|at| at <= cutoff))
+        .collect();
+    expiring.sort_by_key(|credential| credential.expires_at);
+    expiring.into_iter().map(CredentialSummary::from).collect()
+}
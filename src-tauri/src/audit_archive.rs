@@ -0,0 +1,81 @@
+//! Archiving old audit entries instead of keeping them in the audit table.
+//!
+//! Every unlock, edit and reveal adds an audit entry, so the table only grows.
+//! `archive_audit_log` rolls entries older than a cutoff into segments of up to
+//! `AUDIT_ARCHIVE_SEGMENT_ENTRIES`: the entries as JSON, deflated, then
+//! encrypted with the vault key. A segment records the time span it covers in
+//! the clear, so `search_audit_archives` only opens the segments overlapping
+//! the range asked for. The audit table keeps the recent entries the audit log
+//! view and the scheduler read.
+
+use std::io::{Read, Write};
+
+use chrono::{DateTime, Utc};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::models::AuditLogEntry;
+
+/// Most entries rolled into one segment
+pub const AUDIT_ARCHIVE_SEGMENT_ENTRIES: usize = 1000;
+/// Age from which `archive_audit_log` archives entries unless told otherwise
+pub const DEFAULT_AUDIT_ARCHIVE_AGE_DAYS: u32 = 90;
+
+/// A segment as stored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredAuditArchive {
+    pub id: i64,
+    /// Time of the oldest and the newest entry in it
+    pub first_at: DateTime<Utc>,
+    pub last_at: DateTime<Utc>,
+    pub entries: usize,
+    /// Encrypted output of `pack`
+    pub data_enc: String,
+}
+
+/// Result of `archive_audit_log`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditArchiveReport {
+    /// Entries moved out of the audit table
+    pub archived: usize,
+    /// Segments written
+    pub segments: usize,
+}
+
+/// Time range for `search_audit_archives`; an open end is unbounded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditArchiveRange {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl AuditArchiveRange {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.from.is_none_or(|from| at >= from) && self.to.is_none_or(|to| at <= to)
+    }
+
+    /// Whether a segment spanning `first_at` to `last_at` may hold entries in range
+    pub fn overlaps(&self, first_at: DateTime<Utc>, last_at: DateTime<Utc>) -> bool {
+        self.from.is_none_or(|from| last_at >= from) && self.to.is_none_or(|to| first_at <= to)
+    }
+}
+
+/// Entries as deflated JSON, ready to be encrypted
+pub fn pack(entries: &[AuditLogEntry]) -> AppResult<Vec<u8>> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&serde_json::to_vec(entries)?)?;
+    Ok(encoder.finish()?)
+}
+
+/// The entries `pack` was given
+pub fn unpack(packed: &[u8]) -> AppResult<Vec<AuditLogEntry>> {
+    let mut json = Vec::new();
+    DeflateDecoder::new(packed)
+        .read_to_end(&mut json)
+        .map_err(|e| AppError::Other(format!("Audit archive is damaged: {}", e)))?;
+    Ok(serde_json::from_slice(&json)?)
+}
@@ -0,0 +1,172 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::AppResult;
+use crate::i18n::Message;
+use crate::models::AuditLogEntry;
+
+/// Format tag written into every export
+pub const AUDIT_EXPORT_FORMAT: &str = "secret-plan-audit/1";
+
+/// Context label for deriving the signing key from the vault key
+pub const SIGNING_KEY_CONTEXT: &[u8] = b"secret-plan audit signing v1";
+
+/// Chain hash preceding the first entry
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// An audit entry with the hash linking it to all previous entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainedAuditEntry {
+    #[serde(flatten)]
+    pub entry: AuditLogEntry,
+    /// Hex SHA-256 over the previous hash and this entry
+    pub hash: String,
+}
+
+/// File contents of a signed audit export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedAuditExport {
+    pub format: String,
+    pub exported_at: DateTime<Utc>,
+    /// Oldest first
+    pub entries: Vec<ChainedAuditEntry>,
+    /// Hash of the last entry, or the genesis hash for an empty log
+    pub chain_head: String,
+    /// Base64 Ed25519 public key of the exporting vault
+    pub public_key: String,
+    /// Base64 Ed25519 signature over `signing_payload`
+    pub signature: String,
+}
+
+/// Result of checking a signed audit export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditVerification {
+    /// Chain is intact and signed by the trusted key
+    pub valid: bool,
+    /// The trusted key was the currently unlocked vault's own
+    pub signed_by_this_vault: bool,
+    pub entry_count: usize,
+    pub exported_at: DateTime<Utc>,
+    /// Why verification failed, if it did
+    pub problem: Option<Message>,
+}
+
+/// Derives the vault's signing key from a 32-byte subkey
+pub fn signing_key(seed: &[u8; 32]) -> SigningKey {
+    SigningKey::from_bytes(seed)
+}
+
+fn link_hash(previous: &str, entry: &AuditLogEntry) -> AppResult<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(previous.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(serde_json::to_vec(entry)?);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn signing_payload(format: &str, exported_at: DateTime<Utc>, chain_head: &str) -> Vec<u8> {
+    format!("{}\n{}\n{}", format, exported_at.to_rfc3339(), chain_head).into_bytes()
+}
+
+/// Chains `entries` (oldest first) and signs the result with `sign`, which
+/// holds the private half of `public_key`
+pub fn build(
+    entries: Vec<AuditLogEntry>,
+    public_key: &VerifyingKey,
+    sign: impl FnOnce(&[u8]) -> AppResult<Signature>,
+    now: DateTime<Utc>,
+) -> AppResult<SignedAuditExport> {
+    let mut head = GENESIS_HASH.to_string();
+    let mut chained = Vec::with_capacity(entries.len());
+    for entry in entries {
+        head = link_hash(&head, &entry)?;
+        chained.push(ChainedAuditEntry {
+            entry,
+            hash: head.clone(),
+        });
+    }
+
+    let signature = sign(&signing_payload(AUDIT_EXPORT_FORMAT, now, &head))?;
+    Ok(SignedAuditExport {
+        format: AUDIT_EXPORT_FORMAT.to_string(),
+        exported_at: now,
+        entries: chained,
+        chain_head: head,
+        public_key: encode_public_key(public_key),
+        signature: BASE64.encode(signature.to_bytes()),
+    })
+}
+
+/// Decodes a base64 Ed25519 public key, as shown by `encode_public_key`
+pub fn decode_public_key(encoded: &str) -> Option<VerifyingKey> {
+    BASE64
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+}
+
+/// Base64 form of a public key, for the user to keep alongside exports
+pub fn encode_public_key(key: &VerifyingKey) -> String {
+    BASE64.encode(key.as_bytes())
+}
+
+/// Checks the hash chain and signature of an export against `trusted_key`.
+/// The key embedded in the file is never trusted on its own: anyone editing
+/// the file could re-sign it with a key of their own.
+/// `is_vault_key` tells whether `trusted_key` is the unlocked vault's.
+pub fn verify(
+    export: &SignedAuditExport,
+    trusted_key: Option<&VerifyingKey>,
+    is_vault_key: bool,
+) -> AuditVerification {
+    let problem = check(export, trusted_key).err();
+    AuditVerification {
+        valid: problem.is_none(),
+        signed_by_this_vault: problem.is_none() && is_vault_key,
+        entry_count: export.entries.len(),
+        exported_at: export.exported_at,
+        problem,
+    }
+}
+
+fn check(export: &SignedAuditExport, trusted_key: Option<&VerifyingKey>) -> Result<(), Message> {
+    if export.format != AUDIT_EXPORT_FORMAT {
+        return Err(
+            Message::new("audit_verify.unsupported_format").with_param("format", &export.format)
+        );
+    }
+
+    let mut head = GENESIS_HASH.to_string();
+    for (index, chained) in export.entries.iter().enumerate() {
+        head =
+            link_hash(&head, &chained.entry).map_err(|_| Message::new("audit_verify.malformed"))?;
+        if head != chained.hash {
+            return Err(Message::new("audit_verify.chain_broken").with_param("entry", index + 1));
+        }
+    }
+    if head != export.chain_head {
+        return Err(
+            Message::new("audit_verify.chain_broken").with_param("entry", export.entries.len())
+        );
+    }
+
+    let trusted_key = trusted_key.ok_or_else(|| Message::new("audit_verify.no_trusted_key"))?;
+    if decode_public_key(&export.public_key).as_ref() != Some(trusted_key) {
+        return Err(Message::new("audit_verify.untrusted_key"));
+    }
+    let signature = BASE64
+        .decode(&export.signature)
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        .ok_or_else(|| Message::new("audit_verify.malformed"))?;
+    trusted_key
+        .verify(
+            &signing_payload(&export.format, export.exported_at, &export.chain_head),
+            &signature,
+        )
+        .map_err(|_| Message::new("audit_verify.bad_signature"))
+}
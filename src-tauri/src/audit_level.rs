@@ -0,0 +1,117 @@
+//! How much the audit log records.
+//!
+//! Every audit action has the level from which it is recorded; see
+//! `AuditLevel::of`. The vault's `AppSettings::audit_level` is held in an
+//! `AuditFilter` shared by `VaultManager` and the repositories, so entries the
+//! repositories write together with an item change are filtered the same way.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+/// Actions recorded even at the minimal level: access to the vault's keys and
+/// secrets as a whole, and changes to who or what can reach them
+const MINIMAL_ACTIONS: &[&str] = &[
+    "audit.backed_up",
+    "audit.backup_restored",
+    "audit.bulk_session_started",
+    "audit.credential_qr_exported",
+    "audit.device_added",
+    "audit.device_revoked",
+    "audit.emergency_access_approved",
+    "audit.emergency_access_granted",
+    "audit.emergency_access_rejected",
+    "audit.emergency_access_requested",
+    "audit.emergency_contact_added",
+    "audit.emergency_contact_revoked",
+    "audit.emergency_key_released",
+    "audit.exported_audit_log",
+    "audit.exported_bundle",
+    "audit.exported_kdbx",
+    "audit.exported_plaintext",
+    "audit.feature_disabled",
+    "audit.feature_enabled",
+    "audit.high_security_disabled",
+    "audit.high_security_enabled",
+    "audit.high_security_revealed",
+    "audit.high_security_revealed_all",
+    "audit.moved_to_collection",
+    "audit.moved_to_personal",
+    "audit.panic_lock",
+    "audit.recovery_code_used",
+    "audit.recovery_skipped",
+    "audit.settings_updated",
+    "audit.share_created",
+    "audit.share_revoked",
+    "audit.storage_profile_changed",
+    "audit.travel_mode_disabled",
+    "audit.travel_mode_enabled",
+    "audit.vault_compacted",
+    "audit.vault_created",
+];
+
+/// Actions recorded only at the verbose level: reading, not changing
+const VERBOSE_ACTIONS: &[&str] = &["audit.credentials_listed", "audit.credentials_searched"];
+
+/// How much the audit log records, from least to most
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditLevel {
+    /// Only security-relevant events, no item activity
+    Minimal = 1,
+    /// Security events and item changes
+    #[default]
+    Standard = 2,
+    /// Also searches and item listings
+    Verbose = 3,
+}
+
+impl AuditLevel {
+    /// The lowest level that records the action with message key `key`
+    pub fn of(key: &str) -> Self {
+        if MINIMAL_ACTIONS.contains(&key) {
+            AuditLevel::Minimal
+        } else if VERBOSE_ACTIONS.contains(&key) {
+            AuditLevel::Verbose
+        } else {
+            AuditLevel::Standard
+        }
+    }
+
+    pub fn records(self, key: &str) -> bool {
+        Self::of(key) <= self
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => AuditLevel::Minimal,
+            3 => AuditLevel::Verbose,
+            _ => AuditLevel::Standard,
+        }
+    }
+}
+
+/// The audit level in effect, shared between the vault and its repositories
+#[derive(Debug, Clone)]
+pub struct AuditFilter(Arc<AtomicU8>);
+
+impl Default for AuditFilter {
+    fn default() -> Self {
+        Self(Arc::new(AtomicU8::new(AuditLevel::default() as u8)))
+    }
+}
+
+impl AuditFilter {
+    pub fn level(&self) -> AuditLevel {
+        AuditLevel::from_u8(self.0.load(Ordering::SeqCst))
+    }
+
+    pub fn set_level(&self, level: AuditLevel) {
+        self.0.store(level as u8, Ordering::SeqCst);
+    }
+
+    pub fn records(&self, key: &str) -> bool {
+        self.level().records(key)
+    }
+}
@@ -0,0 +1,521 @@
+//! Auto-type into desktop applications.
+//!
+//! When the auto-type shortcut fires, the title of the focused window is
+//! matched against the vault's sites and the user picks (or confirms) an item
+//! in the app. Only then are its fields typed into the window, as described by
+//! the item's sequence, e.g. `{USERNAME}{TAB}{PASSWORD}{ENTER}`.
+//!
+//! `SystemKeyboard` reads the focused window and sends keystrokes with
+//! `SendInput` on Windows, System Events (`osascript`) on macOS and `xdotool`
+//! on Linux (X11 only; Wayland does not let applications inject input). Typed
+//! text is handed to the helper tools on stdin, never on the command line.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::models::{Credential, Secret};
+use crate::site_match;
+use crate::totp::TotpConfig;
+
+/// Sequence used for items without one of their own
+pub const DEFAULT_SEQUENCE: &str = "{USERNAME}{TAB}{PASSWORD}{ENTER}";
+
+/// Longest pause a sequence may ask for
+pub const MAX_DELAY_MS: u64 = 10_000;
+
+/// Time given to the previously focused window to get the focus back after the
+/// app's confirmation is minimized
+pub const FOCUS_RETURN_DELAY: Duration = Duration::from_millis(300);
+
+/// Window title words shorter than this never match a site on their own
+const MIN_TITLE_MATCH_LENGTH: usize = 3;
+
+/// Part of an auto-type sequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SequenceToken {
+    Username,
+    Password,
+    /// The current code of the item's TOTP setup
+    Totp,
+    Tab,
+    Enter,
+    /// `{DELAY 500}`: wait, in milliseconds
+    Delay(u64),
+    Text(String),
+}
+
+/// What is sent to the focused window
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Keystroke {
+    Text(String),
+    Tab,
+    Enter,
+    Delay(Duration),
+}
+
+/// An item that can be typed into the focused window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutotypeCandidate {
+    pub uuid: String,
+    pub site: String,
+    pub username: String,
+}
+
+/// Auto-type waiting for the user to confirm, created when the shortcut fires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutotypeRequest {
+    pub id: String,
+    pub window_title: String,
+    /// Items matching the window; when empty the user can still pick any item
+    pub candidates: Vec<AutotypeCandidate>,
+}
+
+/// Parses a sequence. Placeholders are case-insensitive; `{{}` and `{}}` type
+/// literal braces.
+pub fn parse_sequence(sequence: &str) -> AppResult<Vec<SequenceToken>> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut rest = sequence;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        // `{}}` is a literal closing brace, so look for the end after it
+        let end = match after.strip_prefix('}') {
+            Some(tail) => tail.find('}').map(|i| i + 1),
+            None => after.find('}'),
+        }
+        .ok_or_else(|| invalid_sequence("a placeholder is not closed"))?;
+        let placeholder = &after[..end];
+        rest = &after[end + 1..];
+
+        let token = match placeholder.to_ascii_uppercase().as_str() {
+            "{" => None,
+            "}" => None,
+            "USERNAME" => Some(SequenceToken::Username),
+            "PASSWORD" => Some(SequenceToken::Password),
+            "TOTP" => Some(SequenceToken::Totp),
+            "TAB" => Some(SequenceToken::Tab),
+            "ENTER" => Some(SequenceToken::Enter),
+            upper => match upper.strip_prefix("DELAY ") {
+                Some(ms) => {
+                    let ms: u64 = ms
+                        .trim()
+                        .parse()
+                        .map_err(|_| invalid_sequence("DELAY needs a number of milliseconds"))?;
+                    if ms > MAX_DELAY_MS {
+                        return Err(invalid_sequence("DELAY is limited to 10 seconds"));
+                    }
+                    Some(SequenceToken::Delay(ms))
+                }
+                None => {
+                    return Err(invalid_sequence(&format!(
+                        "unknown placeholder {{{}}}",
+                        placeholder
+                    )))
+                }
+            },
+        };
+        match token {
+            Some(token) => {
+                if !text.is_empty() {
+                    tokens.push(SequenceToken::Text(std::mem::take(&mut text)));
+                }
+                tokens.push(token);
+            }
+            None => text.push_str(placeholder),
+        }
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        tokens.push(SequenceToken::Text(text));
+    }
+    if tokens.is_empty() {
+        return Err(invalid_sequence("it is empty"));
+    }
+    Ok(tokens)
+}
+
+fn invalid_sequence(reason: &str) -> AppError {
+    AppError::InvalidInput(format!("Invalid auto-type sequence: {}", reason))
+}
+
+/// The keystrokes typing `credential` with its sequence
+pub fn keystrokes(
+    credential: &Credential,
+    secret: &Secret,
+    unix_time: i64,
+) -> AppResult<Vec<Keystroke>> {
+    let sequence = secret
+        .autotype_sequence
+        .as_deref()
+        .unwrap_or(DEFAULT_SEQUENCE);
+    let mut keys: Vec<Keystroke> = Vec::new();
+    for token in parse_sequence(sequence)? {
+        let text = match token {
+            SequenceToken::Username => credential.username.clone(),
+            SequenceToken::Password => secret.password.clone(),
+            SequenceToken::Totp => {
+                let totp = secret.totp.as_deref().ok_or_else(|| {
+                    AppError::InvalidInput(
+                        "The sequence types a TOTP code, but the item has no TOTP setup"
+                            .to_string(),
+                    )
+                })?;
+                TotpConfig::parse(totp)?.code_at(unix_time)
+            }
+            SequenceToken::Text(text) => text,
+            SequenceToken::Tab => {
+                keys.push(Keystroke::Tab);
+                continue;
+            }
+            SequenceToken::Enter => {
+                keys.push(Keystroke::Enter);
+                continue;
+            }
+            SequenceToken::Delay(ms) => {
+                keys.push(Keystroke::Delay(Duration::from_millis(ms)));
+                continue;
+            }
+        };
+        // Adjacent text is sent in one go
+        match keys.last_mut() {
+            Some(Keystroke::Text(previous)) => previous.push_str(&text),
+            _ => keys.push(Keystroke::Text(text)),
+        }
+    }
+    Ok(keys)
+}
+
+/// Items whose site appears in `window_title`, e.g. "Sign in - Example -
+/// Firefox" for `https://www.example.com`. Items named by host match their
+/// host, registrable domain or bare name; other items ("Steam") match their name.
+/// Only the closest kind of match is returned, host over domain over name.
+pub fn matching_credentials<'a>(
+    window_title: &str,
+    credentials: &'a [Credential],
+) -> Vec<&'a Credential> {
+    let title = window_title.to_lowercase();
+    let words: Vec<&str> = title
+        .split(|c: char| !c.is_alphanumeric() && c != '-')
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    // Only the closest kind of match is offered: a title naming a host does not
+    // also suggest other sites that share its bare name
+    let scored: Vec<(u8, &Credential)> = credentials
+        .iter()
+        .filter_map(|credential| {
+            let score = match site_match::host_of(&credential.site) {
+                Some(host) => {
+                    let domain = site_match::registrable_domain(&host);
+                    let name = domain.split('.').next().unwrap_or("");
+                    if title.contains(&host) {
+                        2
+                    } else if title.contains(&domain) {
+                        1
+                    } else if name.len() >= MIN_TITLE_MATCH_LENGTH && words.contains(&name) {
+                        0
+                    } else {
+                        return None;
+                    }
+                }
+                None => {
+                    let site = credential.site.trim().to_lowercase();
+                    if site.chars().count() >= MIN_TITLE_MATCH_LENGTH && title.contains(&site) {
+                        0
+                    } else {
+                        return None;
+                    }
+                }
+            };
+            Some((score, credential))
+        })
+        .collect();
+    let best = scored.iter().map(|(score, _)| *score).max();
+    scored
+        .into_iter()
+        .filter(|(score, _)| Some(*score) == best)
+        .map(|(_, credential)| credential)
+        .collect()
+}
+
+/// Access to the focused window
+pub trait AutotypeTarget: Send + Sync {
+    /// Title of the window that has the keyboard focus, None if unknown
+    fn focused_window_title(&self) -> Option<String>;
+    /// Sends `keys` to the focused window
+    fn send(&self, keys: &[Keystroke]) -> AppResult<()>;
+}
+
+/// The operating system's keyboard
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemKeyboard;
+
+impl AutotypeTarget for SystemKeyboard {
+    fn focused_window_title(&self) -> Option<String> {
+        platform::focused_window_title()
+    }
+
+    fn send(&self, keys: &[Keystroke]) -> AppResult<()> {
+        for key in keys {
+            match key {
+                Keystroke::Delay(delay) => std::thread::sleep(*delay),
+                key => platform::send(key)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::Keystroke;
+    use crate::error::{AppError, AppResult};
+
+    const INPUT_KEYBOARD: u32 = 1;
+    const KEYEVENTF_KEYUP: u32 = 0x0002;
+    const KEYEVENTF_UNICODE: u32 = 0x0004;
+    const VK_TAB: u16 = 0x09;
+    const VK_RETURN: u16 = 0x0D;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct KeyboardInput {
+        vk: u16,
+        scan: u16,
+        flags: u32,
+        time: u32,
+        extra_info: usize,
+    }
+
+    // INPUT is a union sized by its largest member, MOUSEINPUT, which is 8
+    // bytes longer than KEYBDINPUT
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Input {
+        kind: u32,
+        keyboard: KeyboardInput,
+        padding: [u8; 8],
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn SendInput(count: u32, inputs: *const Input, size: i32) -> u32;
+        fn GetForegroundWindow() -> *mut std::ffi::c_void;
+        fn GetWindowTextW(window: *mut std::ffi::c_void, text: *mut u16, max: i32) -> i32;
+    }
+
+    fn input(vk: u16, scan: u16, flags: u32) -> Input {
+        Input {
+            kind: INPUT_KEYBOARD,
+            keyboard: KeyboardInput {
+                vk,
+                scan,
+                flags,
+                time: 0,
+                extra_info: 0,
+            },
+            padding: [0; 8],
+        }
+    }
+
+    pub fn focused_window_title() -> Option<String> {
+        let mut buffer = [0u16; 512];
+        // SAFETY: the window handle may be null, which GetWindowTextW handles, and
+        // the buffer length passed matches the buffer
+        let length = unsafe {
+            GetWindowTextW(
+                GetForegroundWindow(),
+                buffer.as_mut_ptr(),
+                buffer.len() as i32,
+            )
+        };
+        (length > 0).then(|| String::from_utf16_lossy(&buffer[..length as usize]))
+    }
+
+    pub fn send(key: &Keystroke) -> AppResult<()> {
+        let inputs: Vec<Input> = match key {
+            Keystroke::Text(text) => text
+                .encode_utf16()
+                .flat_map(|unit| {
+                    [
+                        input(0, unit, KEYEVENTF_UNICODE),
+                        input(0, unit, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP),
+                    ]
+                })
+                .collect(),
+            Keystroke::Tab => vec![input(VK_TAB, 0, 0), input(VK_TAB, 0, KEYEVENTF_KEYUP)],
+            Keystroke::Enter => vec![input(VK_RETURN, 0, 0), input(VK_RETURN, 0, KEYEVENTF_KEYUP)],
+            Keystroke::Delay(_) => return Ok(()),
+        };
+        // SAFETY: `inputs` is a live array of correctly sized INPUT structures
+        let sent = unsafe {
+            SendInput(
+                inputs.len() as u32,
+                inputs.as_ptr(),
+                std::mem::size_of::<Input>() as i32,
+            )
+        };
+        if sent as usize != inputs.len() {
+            return Err(AppError::Other(
+                "Keystrokes were blocked by another application".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    use super::Keystroke;
+    use crate::error::{AppError, AppResult};
+
+    /// Not looked up on PATH: the script it runs holds the text being typed
+    const OSASCRIPT: &str = "/usr/bin/osascript";
+
+    /// Runs an AppleScript passed on stdin, returning its output
+    fn osascript(script: &str) -> AppResult<String> {
+        let mut child = Command::new(OSASCRIPT)
+            .arg("-")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        child
+            .stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(script.as_bytes())?;
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(AppError::Other(
+                "System Events refused auto-type; allow the app under Accessibility".to_string(),
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    pub fn focused_window_title() -> Option<String> {
+        osascript(
+            "tell application \"System Events\" to get name of front window of \
+             (first application process whose frontmost is true)",
+        )
+        .ok()
+        .filter(|title| !title.is_empty())
+    }
+
+    pub fn send(key: &Keystroke) -> AppResult<()> {
+        let command = match key {
+            Keystroke::Text(text) => format!(
+                "keystroke \"{}\"",
+                text.replace('\\', "\\\\").replace('"', "\\\"")
+            ),
+            Keystroke::Tab => "key code 48".to_string(),
+            Keystroke::Enter => "key code 36".to_string(),
+            Keystroke::Delay(_) => return Ok(()),
+        };
+        osascript(&format!(
+            "tell application \"System Events\" to {}",
+            command
+        ))
+        .map(|_| ())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::io::Write;
+    use std::path::Path;
+    use std::process::{Command, Stdio};
+
+    use super::Keystroke;
+    use crate::error::{AppError, AppResult};
+
+    /// Where xdotool is looked for. It is run by absolute path, as in `idle`, so
+    /// nothing earlier on PATH is handed the secret.
+    const XDOTOOL_PATHS: &[&str] = &["/usr/bin/xdotool", "/bin/xdotool"];
+
+    fn xdotool() -> AppResult<Command> {
+        XDOTOOL_PATHS
+            .iter()
+            .find(|path| Path::new(path).is_file())
+            .map(Command::new)
+            .ok_or_else(|| {
+                AppError::Other(format!(
+                    "Auto-type needs xdotool in {}",
+                    XDOTOOL_PATHS.join(" or ")
+                ))
+            })
+    }
+
+    fn xdotool_failed(e: std::io::Error) -> AppError {
+        AppError::Other(format!("Could not run xdotool: {}", e))
+    }
+
+    pub fn focused_window_title() -> Option<String> {
+        let output = xdotool()
+            .ok()?
+            .args(["getactivewindow", "getwindowname"])
+            .output()
+            .ok()?;
+        let title = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !title.is_empty()).then_some(title)
+    }
+
+    pub fn send(key: &Keystroke) -> AppResult<()> {
+        let status = match key {
+            Keystroke::Text(text) => {
+                let mut child = xdotool()?
+                    .args(["type", "--clearmodifiers", "--file", "-"])
+                    .stdin(Stdio::piped())
+                    .spawn()
+                    .map_err(xdotool_failed)?;
+                child
+                    .stdin
+                    .take()
+                    .expect("stdin is piped")
+                    .write_all(text.as_bytes())?;
+                child.wait()?
+            }
+            Keystroke::Tab | Keystroke::Enter => {
+                let name = if *key == Keystroke::Tab {
+                    "Tab"
+                } else {
+                    "Return"
+                };
+                xdotool()?
+                    .args(["key", "--clearmodifiers", name])
+                    .status()
+                    .map_err(xdotool_failed)?
+            }
+            Keystroke::Delay(_) => return Ok(()),
+        };
+        if !status.success() {
+            return Err(AppError::Other(
+                "xdotool could not type into the focused window".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+mod platform {
+    use super::Keystroke;
+    use crate::error::{AppError, AppResult};
+
+    pub fn focused_window_title() -> Option<String> {
+        None
+    }
+
+    pub fn send(_key: &Keystroke) -> AppResult<()> {
+        Err(AppError::Other(
+            "Auto-type is not supported on this platform".to_string(),
+        ))
+    }
+}
@@ -0,0 +1,181 @@
+//! Automatic encrypted backups.
+//!
+//! A backup is a snapshot of the whole database, encrypted as one container
+//! with the vault key, next to the master password hash the key was derived
+//! from. Nothing in it is readable without the master password of the vault
+//! at the time of the backup, so it can sit in a synced folder; `restore`
+//! turns it back into a database file.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::crypto::CryptoService;
+use crate::error::{AppError, AppResult};
+use crate::export;
+use crate::traits::MaintenanceRepository;
+
+/// Format tag of every backup file, also the associated data of its contents
+pub const BACKUP_FORMAT: &str = "secret-plan-backup/1";
+
+/// Prefix/suffix of backup files; the middle part is the UTC creation time
+const BACKUP_PREFIX: &str = "secret-plan-backup-";
+const BACKUP_SUFFIX: &str = ".spbackup";
+const BACKUP_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// A backup file as written to disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackupFile {
+    format: String,
+    created_at: DateTime<Utc>,
+    /// Argon2 hash of the master password, holding the salt and parameters the
+    /// key is derived with
+    master_password_hash: String,
+    /// The database file, encrypted with the vault key
+    database: String,
+}
+
+/// Outcome of a backup run, sent to the frontend as an event payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupOutcome {
+    /// Path of the newly written backup
+    pub path: String,
+    /// Old backups removed by the retention policy
+    pub removed: Vec<String>,
+}
+
+/// A backup about to be written. Taken under the state lock with
+/// `VaultManager::backup_job`, and run after the lock is released, since
+/// copying the database can take a while.
+pub struct BackupJob {
+    pub dir: PathBuf,
+    pub retention: usize,
+    pub(crate) repo: Arc<dyn MaintenanceRepository>,
+    pub(crate) crypto: Arc<Mutex<CryptoService>>,
+}
+
+impl BackupJob {
+    /// Writes the backup, readable only by the current user where supported,
+    /// and applies retention. Fails if the vault was locked in the meantime.
+    pub fn run(&self) -> AppResult<BackupOutcome> {
+        let snapshot = Zeroizing::new(self.repo.snapshot()?);
+        let file = {
+            // Hash and key are read together, so a master password change
+            // cannot slip in between them
+            let crypto = self.crypto.lock().unwrap();
+            BackupFile {
+                format: BACKUP_FORMAT.to_string(),
+                created_at: Utc::now(),
+                master_password_hash: crypto.master_password_hash()?.to_string(),
+                database: crypto.encrypt(&snapshot, BACKUP_FORMAT.as_bytes())?,
+            }
+        };
+
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(backup_file_name(file.created_at));
+        let write = || -> AppResult<()> {
+            let mut out = export::create_private_file(&path)?;
+            serde_json::to_writer(&mut out, &file)?;
+            out.flush()?;
+            out.sync_all()?;
+            Ok(())
+        };
+        write().inspect_err(|_| {
+            let _ = std::fs::remove_file(&path);
+        })?;
+
+        let removed = prune(&self.dir, self.retention.max(1))?;
+        Ok(BackupOutcome {
+            path: path.display().to_string(),
+            removed: removed.iter().map(|p| p.display().to_string()).collect(),
+        })
+    }
+}
+
+/// Decrypts the backup at `backup` with the master password it was made under
+/// and writes the database it holds to `target`, readable only by the current
+/// user where supported. A wrong password fails with `AppError::AuthFailed`.
+pub fn restore(backup: &Path, master_password: &str, target: &Path) -> AppResult<()> {
+    let file: BackupFile = serde_json::from_slice(&std::fs::read(backup)?)
+        .map_err(|e| AppError::Import(format!("Damaged backup: {}", e)))?;
+    if file.format != BACKUP_FORMAT {
+        return Err(AppError::Import(format!(
+            "Unsupported backup format: {}",
+            file.format
+        )));
+    }
+    let key = CryptoService::derive_unlock_key(
+        master_password,
+        &file.master_password_hash,
+        &AtomicBool::new(false),
+    )?;
+    let database = Zeroizing::new(
+        key.decrypt(&file.database, BACKUP_FORMAT.as_bytes())
+            .map_err(|_| AppError::Import("Damaged backup: it does not decrypt".to_string()))?,
+    );
+    export::write_export_file(target, &database)
+}
+
+/// File name for a backup taken at `time`
+pub fn backup_file_name(time: DateTime<Utc>) -> String {
+    format!(
+        "{}{}{}",
+        BACKUP_PREFIX,
+        time.format(BACKUP_TIME_FORMAT),
+        BACKUP_SUFFIX
+    )
+}
+
+/// Parses the creation time out of a backup file name
+fn backup_time(path: &Path) -> Option<DateTime<Utc>> {
+    let name = path.file_name()?.to_str()?;
+    let stamp = name
+        .strip_prefix(BACKUP_PREFIX)?
+        .strip_suffix(BACKUP_SUFFIX)?;
+    NaiveDateTime::parse_from_str(stamp, BACKUP_TIME_FORMAT)
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+/// Lists backups in `dir`, oldest first. Unrelated files are ignored.
+pub fn list_backups(dir: &Path) -> AppResult<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups: Vec<(DateTime<Utc>, PathBuf)> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter_map(|path| backup_time(&path).map(|time| (time, path)))
+        .collect();
+    backups.sort();
+    Ok(backups.into_iter().map(|(_, path)| path).collect())
+}
+
+/// When the newest backup in `dir` was made, None if there is none
+pub fn latest_backup(dir: &Path) -> AppResult<Option<DateTime<Utc>>> {
+    Ok(list_backups(dir)?.last().and_then(|p| backup_time(p)))
+}
+
+/// Whether the newest backup in `dir` is older than `interval_hours`
+pub fn is_due(dir: &Path, interval_hours: u32, now: DateTime<Utc>) -> AppResult<bool> {
+    Ok(match latest_backup(dir)? {
+        Some(time) => now - time >= Duration::hours(interval_hours as i64),
+        None => true,
+    })
+}
+
+/// Deletes the oldest backups so that at most `keep` remain
+pub fn prune(dir: &Path, keep: usize) -> AppResult<Vec<PathBuf>> {
+    let backups = list_backups(dir)?;
+    let excess = backups.len().saturating_sub(keep);
+    let mut removed = Vec::new();
+    for path in backups.into_iter().take(excess) {
+        std::fs::remove_file(&path)?;
+        removed.push(path);
+    }
+    Ok(removed)
+}
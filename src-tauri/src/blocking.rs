@@ -0,0 +1,95 @@
+//! Runs a synchronous repository on tokio's blocking thread pool so that
+//! database calls made from async commands do not stall the runtime.
+
+use std::sync::Arc;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{AuditLogEntry, BreachState, Credential};
+use crate::traits::{
+    AsyncAuditLogger, AsyncCredentialRepository, AuditLogger, CredentialRepository, RepoFuture,
+};
+use crate::vault::CredentialFilter;
+
+/// Async facade over a synchronous repository (e.g. `SqliteRepository`)
+pub struct BlockingRepository<R: ?Sized> {
+    inner: Arc<R>,
+}
+
+impl<R: ?Sized> BlockingRepository<R> {
+    pub fn new(inner: Arc<R>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<R: ?Sized> Clone for BlockingRepository<R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<R: ?Sized + Send + Sync + 'static> BlockingRepository<R> {
+    /// Runs `call` on the blocking pool with a handle to the wrapped repository
+    fn run<T, F>(&self, call: F) -> RepoFuture<'static, T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&R) -> AppResult<T> + Send + 'static,
+    {
+        let inner = self.inner.clone();
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || call(&inner))
+                .await
+                .map_err(|e| AppError::Other(format!("Repository task failed: {}", e)))?
+        })
+    }
+}
+
+impl<R> AsyncCredentialRepository for BlockingRepository<R>
+where
+    R: CredentialRepository + ?Sized + 'static,
+{
+    fn add_credential(&self, credential: Credential, strength: u8) -> RepoFuture<'_, ()> {
+        self.run(move |repo| repo.add_credential(&credential, strength))
+    }
+
+    fn update_credential(&self, credential: Credential) -> RepoFuture<'_, ()> {
+        self.run(move |repo| repo.update_credential(&credential))
+    }
+
+    fn delete_credential(&self, uuid: String) -> RepoFuture<'_, String> {
+        self.run(move |repo| repo.delete_credential(&uuid))
+    }
+
+    fn get_credential(&self, uuid: String) -> RepoFuture<'_, Credential> {
+        self.run(move |repo| repo.get_credential(&uuid))
+    }
+
+    fn list_credentials(
+        &self,
+        filter: Option<CredentialFilter>,
+    ) -> RepoFuture<'_, Vec<Credential>> {
+        self.run(move |repo| repo.list_credentials(filter))
+    }
+
+    fn update_breach_state(&self, uuid: String, state: BreachState) -> RepoFuture<'_, ()> {
+        self.run(move |repo| repo.update_breach_state(&uuid, state))
+    }
+
+    fn credential_exists(&self, uuid: String) -> RepoFuture<'_, bool> {
+        self.run(move |repo| repo.credential_exists(&uuid))
+    }
+}
+
+impl<R> AsyncAuditLogger for BlockingRepository<R>
+where
+    R: AuditLogger + ?Sized + 'static,
+{
+    fn add_log(&self, action: String, item_uuid: Option<String>) -> RepoFuture<'_, i64> {
+        self.run(move |repo| repo.add_log(&action, item_uuid.as_deref()))
+    }
+
+    fn get_logs(&self, limit: Option<i64>) -> RepoFuture<'_, Vec<AuditLogEntry>> {
+        self.run(move |repo| repo.get_logs(limit))
+    }
+}
@@ -0,0 +1,107 @@
+//! Personal lists of compromised passwords.
+//!
+//! HIBP only knows breaches that made it into its public corpus. A list from a
+//! corporate incident can be imported here instead: one password per line, or
+//! HIBP-style SHA-1 hashes (`<40 hex digits>[:count]`). Entries are stored as
+//! HMACs of their SHA-1 under a key derived from the vault key, so the database
+//! does not hold the list in a form that can be cracked or read offline.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+use crate::error::{AppError, AppResult};
+
+/// Context for `CryptoService::mac_with_subkey`
+pub const BREACH_LIST_KEY_CONTEXT: &[u8] = b"secret-plan breach list v1";
+
+/// Largest list file read; about ten million hashes
+const MAX_LIST_BYTES: u64 = 512 * 1024 * 1024;
+const MAX_SOURCE_BYTES: usize = 128;
+
+/// An imported list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BreachListSource {
+    /// Name given at import, e.g. `2024 VPN incident`
+    pub source: String,
+    pub entries: u64,
+    pub imported_at: DateTime<Utc>,
+}
+
+/// What an import added
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreachListImport {
+    pub source: String,
+    /// Distinct entries read from the file
+    pub read: usize,
+    /// Entries the source did not have yet
+    pub added: usize,
+}
+
+/// A vault item whose password is on an imported list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalBreachMatch {
+    pub uuid: String,
+    pub site: String,
+    pub username: String,
+    pub sources: Vec<String>,
+}
+
+/// Uppercase SHA-1 hex of a password
+pub fn sha1_hex(password: &str) -> String {
+    Sha1::digest(password.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect()
+}
+
+/// The SHA-1 of every distinct entry in a list; lines that are a 40-digit hex
+/// hash (optionally followed by `:count`) are taken as hashes, anything else as
+/// a password
+pub fn parse_entries(text: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    text.lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let hash = line.split(':').next().unwrap_or_default().trim();
+            if hash.len() == 40 && hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                hash.to_ascii_uppercase()
+            } else {
+                sha1_hex(line)
+            }
+        })
+        .filter(|hash| seen.insert(hash.clone()))
+        .collect()
+}
+
+/// Reads and parses a list file
+pub fn read_entries(path: &Path) -> AppResult<Vec<String>> {
+    if std::fs::metadata(path)?.len() > MAX_LIST_BYTES {
+        return Err(AppError::InvalidInput(format!(
+            "Breach lists are limited to {} MB",
+            MAX_LIST_BYTES / (1024 * 1024)
+        )));
+    }
+    let bytes = std::fs::read(path)?;
+    Ok(parse_entries(&String::from_utf8_lossy(&bytes)))
+}
+
+pub fn validate_source(source: &str) -> AppResult<String> {
+    let source = source.trim();
+    if source.is_empty() || source.len() > MAX_SOURCE_BYTES {
+        return Err(AppError::InvalidInput(format!(
+            "A breach list needs a name of at most {} bytes",
+            MAX_SOURCE_BYTES
+        )));
+    }
+    Ok(source.to_string())
+}
+
+/// What is stored for an entry, given the HMAC of its SHA-1 hex under the
+/// `BREACH_LIST_KEY_CONTEXT` subkey
+pub fn fingerprint(mac: &[u8; 32]) -> String {
+    mac.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
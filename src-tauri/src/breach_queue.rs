@@ -0,0 +1,127 @@
+//! Coordination of concurrent breach checks.
+//!
+//! Saving an item can start a check in the background while the user asks for
+//! the same item, or another item with the same password, to be checked. The
+//! queue makes concurrent lookups of one password hash share a single request,
+//! and lets one breach state update run at a time so a slower check cannot
+//! interleave with a newer one.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{OnceCell, Semaphore};
+use tokio::task::JoinSet;
+
+use crate::error::{AppError, AppResult};
+use crate::hibp::HibpService;
+use crate::models::BreachState;
+
+/// Outcome of a lookup, kept so every waiting caller gets a copy
+#[derive(Debug, Clone)]
+enum Lookup {
+    Found(BreachState),
+    Network(String),
+    Failed(String),
+}
+
+impl From<AppResult<BreachState>> for Lookup {
+    fn from(result: AppResult<BreachState>) -> Self {
+        match result {
+            Ok(state) => Lookup::Found(state),
+            Err(AppError::Network(detail)) => Lookup::Network(detail),
+            Err(e) => Lookup::Failed(e.to_string()),
+        }
+    }
+}
+
+impl From<Lookup> for AppResult<BreachState> {
+    fn from(lookup: Lookup) -> Self {
+        match lookup {
+            Lookup::Found(state) => Ok(state),
+            Lookup::Network(detail) => Err(AppError::Network(detail)),
+            Lookup::Failed(detail) => Err(AppError::Other(detail)),
+        }
+    }
+}
+
+/// Breach checks in flight for the open vault
+#[derive(Debug, Default)]
+pub struct BreachCheckQueue {
+    // Lookups by password hash; removed once they complete
+    in_flight: Mutex<HashMap<String, Arc<OnceCell<Lookup>>>>,
+    updates: tokio::sync::Mutex<()>,
+}
+
+impl BreachCheckQueue {
+    /// Looks up `password_hash` with `service`, joining a lookup of the same hash
+    /// that is already running instead of sending another request
+    pub async fn lookup(
+        &self,
+        service: &HibpService,
+        password_hash: &str,
+    ) -> AppResult<BreachState> {
+        let cell = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(password_hash.to_string())
+            .or_default()
+            .clone();
+        let lookup = cell
+            .get_or_init(|| async { Lookup::from(service.check_password(password_hash).await) })
+            .await
+            .clone();
+
+        // The first caller to finish retires the entry; later checks look up afresh
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight
+            .get(password_hash)
+            .is_some_and(|current| Arc::ptr_eq(current, &cell))
+        {
+            in_flight.remove(password_hash);
+        }
+        lookup.into()
+    }
+
+    /// Looks up many password hashes with at most `concurrency` requests in
+    /// flight, each through [`Self::lookup`]. Lookups not yet started when
+    /// `cancel` is set finish as `None`.
+    pub fn lookup_many(
+        self: &Arc<Self>,
+        service: &HibpService,
+        password_hashes: Vec<String>,
+        concurrency: usize,
+        cancel: Arc<AtomicBool>,
+    ) -> JoinSet<Option<(String, AppResult<BreachState>)>> {
+        let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+        for hash in password_hashes {
+            let queue = self.clone();
+            let service = service.clone();
+            let semaphore = semaphore.clone();
+            let cancel = cancel.clone();
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await.ok()?;
+                if cancel.load(Ordering::SeqCst) {
+                    return None;
+                }
+                let result = queue.lookup(&service, &hash).await;
+                Some((hash, result))
+            });
+        }
+        tasks
+    }
+
+    /// Runs `update` once no other breach state update is running
+    pub async fn record<F: Future>(&self, update: F) -> F::Output {
+        let _turn = self.updates.lock().await;
+        update.await
+    }
+
+    /// Number of password hashes being looked up
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().unwrap().len()
+    }
+}
@@ -0,0 +1,103 @@
+//! Messages from the browser extension.
+//!
+//! The extension talks to a native messaging host, which forwards messages to
+//! the app using the browser's framing: a 32-bit length in native byte order
+//! followed by that many bytes of JSON. A captured login is never written to
+//! the vault directly: it is kept, encrypted, as a pending save until the user
+//! approves or discards it in the desktop UI.
+
+use std::io::{ErrorKind, Read, Write};
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::site_match::LookalikeWarning;
+
+/// Largest message accepted from the extension
+pub const MAX_MESSAGE_BYTES: usize = 1024 * 1024;
+
+/// Pending saves kept at most; the oldest are dropped first
+pub const MAX_PENDING_SAVES: usize = 50;
+
+/// A message sent by the extension
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BridgeMessage {
+    /// The user submitted a login form
+    LoginCaptured {
+        url: String,
+        username: String,
+        password: String,
+    },
+}
+
+/// The app's reply to a `BridgeMessage`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BridgeResponse {
+    /// Waiting for the user in the desktop UI
+    PendingSaveCreated { id: String },
+    /// The vault already holds this password for the site
+    AlreadySaved,
+}
+
+/// A captured login waiting for the user's decision, without its password
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingSave {
+    pub id: String,
+    pub url: String,
+    /// Host of `url`, used as the new item's site
+    pub site: String,
+    pub username: String,
+    /// The item whose password approving replaces; None adds a new item
+    pub existing_uuid: Option<String>,
+    /// Set when the page only resembles a saved site
+    pub warning: Option<LookalikeWarning>,
+    pub captured_at: DateTime<Utc>,
+}
+
+/// A pending save as stored, in the vault's encrypted meta data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingSaveRecord {
+    #[serde(flatten)]
+    pub save: PendingSave,
+    pub password: String,
+}
+
+/// Reads one framed message; None once the stream is closed
+pub fn read_message<T: DeserializeOwned>(reader: &mut impl Read) -> AppResult<Option<T>> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let length = u32::from_ne_bytes(length) as usize;
+    if length > MAX_MESSAGE_BYTES {
+        return Err(AppError::InvalidInput(format!(
+            "Browser message of {} bytes exceeds the {} byte limit",
+            length, MAX_MESSAGE_BYTES
+        )));
+    }
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+/// Writes one framed message
+pub fn write_message<T: Serialize>(writer: &mut impl Write, message: &T) -> AppResult<()> {
+    let body = serde_json::to_vec(message)?;
+    if body.len() > MAX_MESSAGE_BYTES {
+        return Err(AppError::InvalidInput(format!(
+            "Browser message of {} bytes exceeds the {} byte limit",
+            body.len(),
+            MAX_MESSAGE_BYTES
+        )));
+    }
+    writer.write_all(&(body.len() as u32).to_ne_bytes())?;
+    writer.write_all(&body)?;
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,45 @@
+//! Short-lived elevation for operations that decrypt every secret.
+//!
+//! Reports such as reuse detection or a breach check of the whole vault read
+//! every password at once. They run only inside a bulk session, which the user
+//! opens with the master password and a stated purpose. The session is held in
+//! memory by `VaultManager`, is never handed to the frontend, ends after
+//! `BULK_SESSION_MINUTES` and is dropped when the vault locks.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+/// How long a bulk session lasts
+pub const BULK_SESSION_MINUTES: i64 = 5;
+const MAX_PURPOSE_LEN: usize = 200;
+
+/// An open bulk session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BulkSession {
+    /// Why the user opened it, as recorded in the audit log
+    pub purpose: String,
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl BulkSession {
+    pub fn start(purpose: &str, now: DateTime<Utc>) -> AppResult<Self> {
+        let purpose = purpose.trim();
+        if purpose.is_empty() || purpose.chars().count() > MAX_PURPOSE_LEN {
+            return Err(AppError::InvalidInput(format!(
+                "Describe the purpose of the bulk session in 1 to {MAX_PURPOSE_LEN} characters"
+            )));
+        }
+        Ok(Self {
+            purpose: purpose.to_string(),
+            started_at: now,
+            expires_at: now + Duration::minutes(BULK_SESSION_MINUTES),
+        })
+    }
+
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        now < self.expires_at
+    }
+}
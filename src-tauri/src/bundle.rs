@@ -0,0 +1,254 @@
+//! Portable encrypted vault bundles.
+//!
+//! A bundle is a zip container: a plaintext header with the key derivation
+//! parameters, one encrypted entry per item, and an encrypted index written
+//! last. Every entry is encrypted on its own under a key derived from the
+//! bundle password, with the entry's name as associated data, so entries cannot
+//! be swapped or renamed. Entries are written and read one at a time; a large
+//! vault never has to be held in memory whole.
+//!
+//! Items are the only entry kind so far. Attachments and icons will be further
+//! kinds in the same container once the vault stores them.
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::crypto::CryptoService;
+use crate::error::{AppError, AppResult, CryptoError};
+use crate::export;
+use crate::models::{Credential, Secret};
+
+/// Format tag in the header of every bundle
+pub const BUNDLE_FORMAT: &str = "secret-plan-bundle/1";
+const HEADER_ENTRY: &str = "bundle.json";
+const INDEX_ENTRY: &str = "index.enc";
+const SALT_BYTES: usize = 16;
+
+/// Argon2id parameters of a bundle key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleKdf {
+    pub memory_kb: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for BundleKdf {
+    fn default() -> Self {
+        // Same cost as the vault's own default Argon2 settings
+        Self {
+            memory_kb: 65536,
+            iterations: 3,
+            parallelism: 4,
+        }
+    }
+}
+
+/// The plaintext part of a bundle: what is needed to derive its key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BundleHeader {
+    format: String,
+    created_at: DateTime<Utc>,
+    /// Base64
+    salt: String,
+    kdf: BundleKdf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    Item,
+}
+
+/// An entry as listed in the bundle's index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// Name of the entry in the container
+    pub name: String,
+    pub kind: EntryKind,
+    pub uuid: String,
+    pub site: String,
+}
+
+/// An item as stored in a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundledItem {
+    /// The item as it was in the vault, without its vault-encrypted secret
+    pub credential: Credential,
+    pub secret: Secret,
+}
+
+/// Rejects empty bundle passwords before any work is done
+pub fn validate_password(password: &str) -> AppResult<()> {
+    if password.is_empty() {
+        return Err(AppError::Export(
+            "A password is required for bundle exports".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn bundle_key(password: &str, salt: &[u8], kdf: BundleKdf) -> AppResult<[u8; 32]> {
+    let params = Params::new(kdf.memory_kb, kdf.iterations, kdf.parallelism, Some(32))
+        .map_err(|e| CryptoError::KeyDerivation(format!("Invalid bundle KDF parameters: {}", e)))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| CryptoError::KeyDerivation(format!("Bundle key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+/// Associated data binding an entry's contents to its name
+fn entry_aad(name: &str) -> Vec<u8> {
+    format!("{BUNDLE_FORMAT}:{name}").into_bytes()
+}
+
+fn zip_error(e: zip::result::ZipError) -> AppError {
+    AppError::Export(format!("Could not write bundle: {}", e))
+}
+
+fn damaged(detail: impl std::fmt::Display) -> AppError {
+    AppError::Import(format!("Damaged bundle: {}", detail))
+}
+
+/// Writes a bundle entry by entry
+pub struct BundleWriter {
+    zip: ZipWriter<File>,
+    key: [u8; 32],
+    index: Vec<IndexEntry>,
+}
+
+impl BundleWriter {
+    /// Creates the bundle file at `path`, readable only by the current user where
+    /// supported, and writes its header
+    pub fn create(path: &Path, password: &str, kdf: BundleKdf) -> AppResult<Self> {
+        validate_password(password)?;
+        let mut salt = [0u8; SALT_BYTES];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let key = bundle_key(password, &salt, kdf)?;
+
+        let file = export::create_private_file(path)?;
+        let mut writer = Self {
+            zip: ZipWriter::new(file),
+            key,
+            index: Vec::new(),
+        };
+        let header = BundleHeader {
+            format: BUNDLE_FORMAT.to_string(),
+            created_at: Utc::now(),
+            salt: BASE64.encode(salt),
+            kdf,
+        };
+        writer.write_entry(HEADER_ENTRY, &serde_json::to_vec_pretty(&header)?)?;
+        Ok(writer)
+    }
+
+    fn write_entry(&mut self, name: &str, contents: &[u8]) -> AppResult<()> {
+        self.zip
+            .start_file(name, SimpleFileOptions::default())
+            .map_err(zip_error)?;
+        self.zip.write_all(contents)?;
+        Ok(())
+    }
+
+    fn write_encrypted(&mut self, name: &str, plaintext: &[u8]) -> AppResult<()> {
+        let container = CryptoService::encrypt_with_key(&self.key, plaintext, &entry_aad(name))?;
+        self.write_entry(name, container.as_bytes())
+    }
+
+    /// Adds an item; its secret is encrypted and written right away
+    pub fn add_item(&mut self, credential: &Credential, secret: &Secret) -> AppResult<()> {
+        let name = format!("items/{:06}.enc", self.index.len());
+        let item = BundledItem {
+            credential: Credential {
+                secret_enc: String::new(),
+                ..credential.clone()
+            },
+            secret: secret.clone(),
+        };
+        self.write_encrypted(&name, &serde_json::to_vec(&item)?)?;
+        self.index.push(IndexEntry {
+            name,
+            kind: EntryKind::Item,
+            uuid: credential.uuid.clone(),
+            site: credential.site.clone(),
+        });
+        Ok(())
+    }
+
+    /// Writes the index and closes the container, returning the number of entries
+    pub fn finish(mut self) -> AppResult<usize> {
+        let index = serde_json::to_vec(&self.index)?;
+        self.write_encrypted(INDEX_ENTRY, &index)?;
+        self.zip.finish().map_err(zip_error)?;
+        Ok(self.index.len())
+    }
+}
+
+/// Reads a bundle entry by entry
+pub struct BundleReader {
+    archive: ZipArchive<File>,
+    key: [u8; 32],
+    index: Vec<IndexEntry>,
+}
+
+impl BundleReader {
+    /// Opens the bundle at `path`. A wrong password fails with `AppError::AuthFailed`.
+    pub fn open(path: &Path, password: &str) -> AppResult<Self> {
+        let mut archive = ZipArchive::new(File::open(path)?).map_err(damaged)?;
+        let header: BundleHeader =
+            serde_json::from_slice(&read_entry(&mut archive, HEADER_ENTRY)?).map_err(damaged)?;
+        if header.format != BUNDLE_FORMAT {
+            return Err(AppError::Import(format!(
+                "Unsupported bundle format: {}",
+                header.format
+            )));
+        }
+        let salt = BASE64.decode(&header.salt).map_err(damaged)?;
+        let mut reader = Self {
+            archive,
+            key: bundle_key(password, &salt, header.kdf)?,
+            index: Vec::new(),
+        };
+        let index = reader
+            .read_encrypted(INDEX_ENTRY)
+            .map_err(|_| AppError::AuthFailed)?;
+        reader.index = serde_json::from_slice(&index).map_err(damaged)?;
+        Ok(reader)
+    }
+
+    /// The bundle's entries, in the order they were written
+    pub fn index(&self) -> &[IndexEntry] {
+        &self.index
+    }
+
+    fn read_encrypted(&mut self, name: &str) -> AppResult<Vec<u8>> {
+        let container = String::from_utf8(read_entry(&mut self.archive, name)?).map_err(damaged)?;
+        CryptoService::decrypt_with_key(&self.key, &container, &entry_aad(name))
+    }
+
+    /// Decrypts the item entry `name`
+    pub fn read_item(&mut self, name: &str) -> AppResult<BundledItem> {
+        let plaintext = self
+            .read_encrypted(name)
+            .map_err(|_| damaged(format!("{} does not decrypt", name)))?;
+        serde_json::from_slice(&plaintext).map_err(damaged)
+    }
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> AppResult<Vec<u8>> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|_| damaged(format!("{} is missing", name)))?;
+    let mut contents = Vec::new();
+    entry.read_to_end(&mut contents)?;
+    Ok(contents)
+}
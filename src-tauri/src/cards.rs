@@ -0,0 +1,230 @@
+//! Credit card items.
+//!
+//! A card is an item of kind `CreditCard`: the card number is its password, the
+//! security code a `cvv` custom field and the cardholder its username. Numbers
+//! are checked here (Luhn checksum and the brand's lengths) rather than in the
+//! frontend, and what lists show (brand, masked number, expiry) is kept in
+//! `CardDetails`. The expiry also sets the item's `expires_at`, so cards are
+//! part of the expiry reminders.
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+/// Card networks recognised from the number's prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CardBrand {
+    Visa,
+    Mastercard,
+    Amex,
+    Discover,
+    Jcb,
+    DinersClub,
+    UnionPay,
+    Maestro,
+    Unknown,
+}
+
+impl CardBrand {
+    /// Number lengths the network issues
+    fn lengths(&self) -> &'static [usize] {
+        match self {
+            CardBrand::Visa => &[13, 16, 19],
+            CardBrand::Mastercard => &[16],
+            CardBrand::Amex => &[15],
+            CardBrand::Discover | CardBrand::Jcb | CardBrand::UnionPay => &[16, 17, 18, 19],
+            CardBrand::DinersClub => &[14, 16, 17, 18, 19],
+            CardBrand::Maestro | CardBrand::Unknown => &[12, 13, 14, 15, 16, 17, 18, 19],
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CardBrand::Visa => "Visa",
+            CardBrand::Mastercard => "Mastercard",
+            CardBrand::Amex => "American Express",
+            CardBrand::Discover => "Discover",
+            CardBrand::Jcb => "JCB",
+            CardBrand::DinersClub => "Diners Club",
+            CardBrand::UnionPay => "UnionPay",
+            CardBrand::Maestro => "Maestro",
+            CardBrand::Unknown => "Card",
+        }
+    }
+}
+
+/// Month and year printed on the card
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CardExpiry {
+    pub month: u32,
+    pub year: i32,
+}
+
+impl CardExpiry {
+    pub fn new(month: u32, year: i32) -> AppResult<Self> {
+        // Two-digit years as printed on cards
+        let year = if (0..100).contains(&year) {
+            2000 + year
+        } else {
+            year
+        };
+        if !(1..=12).contains(&month) || !(2000..=2100).contains(&year) {
+            return Err(AppError::InvalidInput(
+                "Card expiry must be a month from 1 to 12 and a year from 2000 to 2100".to_string(),
+            ));
+        }
+        Ok(Self { month, year })
+    }
+
+    /// Cards are valid through the last day of their expiry month
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        let (year, month) = match self.month {
+            12 => (self.year + 1, 1),
+            month => (self.year, month + 1),
+        };
+        let first_of_next = NaiveDate::from_ymd_opt(year, month, 1)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .expect("validated month and year");
+        Utc.from_utc_datetime(&first_of_next) - chrono::Duration::seconds(1)
+    }
+}
+
+/// Unencrypted details of a card item
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CardDetails {
+    pub brand: CardBrand,
+    /// The number masked to its last four digits, e.g. `•••• 4242`
+    pub masked_number: String,
+    pub expiry: CardExpiry,
+}
+
+/// Result of checking a card number, for feedback while it is typed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardNumberCheck {
+    pub brand: CardBrand,
+    /// Passes the Luhn checksum and has a length the brand issues
+    pub valid: bool,
+    pub masked_number: String,
+}
+
+/// The digits of a card number typed with spaces or dashes
+pub fn normalize_number(number: &str) -> AppResult<String> {
+    let digits: String = number.chars().filter(|c| !matches!(c, ' ' | '-')).collect();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(AppError::InvalidInput(
+            "A card number may only contain digits, spaces and dashes".to_string(),
+        ));
+    }
+    Ok(digits)
+}
+
+/// Luhn (mod 10) checksum of a string of digits
+pub fn luhn_valid(digits: &str) -> bool {
+    let sum: u32 = digits
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(index, byte)| {
+            let digit = u32::from(byte - b'0');
+            match index % 2 {
+                0 => digit,
+                _ if digit * 2 > 9 => digit * 2 - 9,
+                _ => digit * 2,
+            }
+        })
+        .sum();
+    digits.len() >= 2 && sum.is_multiple_of(10)
+}
+
+/// The network a number belongs to, from its issuer identification prefix
+pub fn detect_brand(digits: &str) -> CardBrand {
+    let prefix =
+        |len: usize| -> u32 { digits.get(..len).and_then(|p| p.parse().ok()).unwrap_or(0) };
+    match (prefix(1), prefix(2), prefix(3), prefix(4), prefix(6)) {
+        (4, ..) => CardBrand::Visa,
+        (_, 34 | 37, ..) => CardBrand::Amex,
+        (_, 51..=55, ..) | (_, _, _, 2221..=2720, _) => CardBrand::Mastercard,
+        (_, 65, ..) | (_, _, 644..=649, ..) | (_, _, _, 6011, _) => CardBrand::Discover,
+        (_, _, _, _, 622126..=622925) => CardBrand::Discover,
+        (_, _, _, 3528..=3589, _) => CardBrand::Jcb,
+        (_, 36 | 38 | 39, ..) | (_, _, 300..=305, ..) => CardBrand::DinersClub,
+        (_, 62, ..) => CardBrand::UnionPay,
+        (_, 50 | 56..=69, ..) => CardBrand::Maestro,
+        _ => CardBrand::Unknown,
+    }
+}
+
+/// The last four digits behind a mask
+pub fn mask_number(digits: &str) -> String {
+    let last4 = &digits[digits.len().saturating_sub(4)..];
+    format!("•••• {}", last4)
+}
+
+/// Checks a card number without storing anything
+pub fn check_number(number: &str) -> AppResult<CardNumberCheck> {
+    let digits = normalize_number(number)?;
+    let brand = detect_brand(&digits);
+    Ok(CardNumberCheck {
+        brand,
+        valid: luhn_valid(&digits) && brand.lengths().contains(&digits.len()),
+        masked_number: mask_number(&digits),
+    })
+}
+
+/// A card as entered by the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardInput {
+    /// Name of the item; defaults to the brand, e.g. `Visa •••• 4242`
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub cardholder: String,
+    pub number: String,
+    pub expiry_month: u32,
+    pub expiry_year: i32,
+    #[serde(default)]
+    pub cvv: Option<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl CardInput {
+    /// The normalized number and the details lists show. Rejects numbers that
+    /// fail the checksum or have a length the brand does not issue.
+    pub fn validate(&self) -> AppResult<(String, CardDetails)> {
+        let check = check_number(&self.number)?;
+        if !check.valid {
+            return Err(AppError::InvalidInput(format!(
+                "Not a valid {} number",
+                check.brand.name()
+            )));
+        }
+        if let Some(cvv) = self.cvv.as_deref().filter(|cvv| !cvv.is_empty()) {
+            let expected = if check.brand == CardBrand::Amex { 4 } else { 3 };
+            if cvv.len() != expected || !cvv.chars().all(|c| c.is_ascii_digit()) {
+                return Err(AppError::InvalidInput(format!(
+                    "The security code must be {} digits",
+                    expected
+                )));
+            }
+        }
+        let details = CardDetails {
+            brand: check.brand,
+            masked_number: check.masked_number,
+            expiry: CardExpiry::new(self.expiry_month, self.expiry_year)?,
+        };
+        Ok((normalize_number(&self.number)?, details))
+    }
+
+    /// The item's name
+    pub fn site(&self, details: &CardDetails) -> String {
+        self.name
+            .clone()
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| format!("{} {}", details.brand.name(), details.masked_number))
+    }
+}
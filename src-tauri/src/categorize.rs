@@ -0,0 +1,164 @@
+//! Guessing a category for imported items from their site.
+//!
+//! An import from another password manager often arrives without folders, or
+//! with folders nobody kept up. For each row the importer suggests a tag from a
+//! short list of well-known domains, and failing that from words in the host
+//! name such as "bank" or "mail". Suggestions are shown with the import preview
+//! and only applied when the import asks for it, to rows that have no tags of
+//! their own.
+
+use serde::{Deserialize, Serialize};
+
+use crate::site_match;
+
+/// What an item is probably for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    Banking,
+    Social,
+    Email,
+    Dev,
+}
+
+impl Category {
+    /// The tag an item of this category is given
+    pub fn tag(self) -> &'static str {
+        match self {
+            Category::Banking => "banking",
+            Category::Social => "social",
+            Category::Email => "email",
+            Category::Dev => "dev",
+        }
+    }
+}
+
+/// Known domains; a host matches a domain or any subdomain of it. More
+/// specific entries come first, so `mail.google.com` is not taken for Google.
+const KNOWN_DOMAINS: &[(&str, Category)] = &[
+    ("mail.google.com", Category::Email),
+    ("mail.yahoo.com", Category::Email),
+    ("aws.amazon.com", Category::Dev),
+    ("cloud.google.com", Category::Dev),
+    ("portal.azure.com", Category::Dev),
+    ("gmail.com", Category::Email),
+    ("outlook.com", Category::Email),
+    ("hotmail.com", Category::Email),
+    ("live.com", Category::Email),
+    ("proton.me", Category::Email),
+    ("protonmail.com", Category::Email),
+    ("fastmail.com", Category::Email),
+    ("tutanota.com", Category::Email),
+    ("zoho.com", Category::Email),
+    ("aol.com", Category::Email),
+    ("gmx.com", Category::Email),
+    ("gmx.net", Category::Email),
+    ("facebook.com", Category::Social),
+    ("instagram.com", Category::Social),
+    ("twitter.com", Category::Social),
+    ("x.com", Category::Social),
+    ("threads.net", Category::Social),
+    ("bsky.app", Category::Social),
+    ("mastodon.social", Category::Social),
+    ("linkedin.com", Category::Social),
+    ("reddit.com", Category::Social),
+    ("tiktok.com", Category::Social),
+    ("pinterest.com", Category::Social),
+    ("snapchat.com", Category::Social),
+    ("tumblr.com", Category::Social),
+    ("discord.com", Category::Social),
+    ("chase.com", Category::Banking),
+    ("bankofamerica.com", Category::Banking),
+    ("wellsfargo.com", Category::Banking),
+    ("citi.com", Category::Banking),
+    ("capitalone.com", Category::Banking),
+    ("usbank.com", Category::Banking),
+    ("ally.com", Category::Banking),
+    ("americanexpress.com", Category::Banking),
+    ("discover.com", Category::Banking),
+    ("schwab.com", Category::Banking),
+    ("fidelity.com", Category::Banking),
+    ("vanguard.com", Category::Banking),
+    ("hsbc.com", Category::Banking),
+    ("barclays.co.uk", Category::Banking),
+    ("monzo.com", Category::Banking),
+    ("revolut.com", Category::Banking),
+    ("wise.com", Category::Banking),
+    ("paypal.com", Category::Banking),
+    ("github.com", Category::Dev),
+    ("gitlab.com", Category::Dev),
+    ("bitbucket.org", Category::Dev),
+    ("atlassian.net", Category::Dev),
+    ("stackoverflow.com", Category::Dev),
+    ("npmjs.com", Category::Dev),
+    ("crates.io", Category::Dev),
+    ("pypi.org", Category::Dev),
+    ("docker.com", Category::Dev),
+    ("heroku.com", Category::Dev),
+    ("vercel.com", Category::Dev),
+    ("netlify.com", Category::Dev),
+    ("digitalocean.com", Category::Dev),
+    ("jetbrains.com", Category::Dev),
+];
+
+/// Words in a host name that give its category away: (word, whole label
+/// only, category). Parts matched anywhere in a label catch names like
+/// `firstbankonline.com`.
+const HOST_WORDS: &[(&str, bool, Category)] = &[
+    ("bank", false, Category::Banking),
+    ("credit", false, Category::Banking),
+    ("invest", false, Category::Banking),
+    ("mail", true, Category::Email),
+    ("webmail", true, Category::Email),
+    ("imap", true, Category::Email),
+    ("smtp", true, Category::Email),
+    ("git", true, Category::Dev),
+    ("gitea", true, Category::Dev),
+    ("jira", true, Category::Dev),
+    ("jenkins", true, Category::Dev),
+    ("ci", true, Category::Dev),
+    ("dev", true, Category::Dev),
+    ("staging", true, Category::Dev),
+    ("registry", true, Category::Dev),
+    ("forum", false, Category::Social),
+    ("community", true, Category::Social),
+];
+
+/// The category `site` probably belongs to, or None if nothing gives it away
+pub fn classify(site: &str) -> Option<Category> {
+    let host = site_match::host_of(site)?;
+    if let Some((_, category)) = KNOWN_DOMAINS.iter().find(|(domain, _)| {
+        host == *domain
+            || host
+                .strip_suffix(domain)
+                .is_some_and(|sub| sub.ends_with('.'))
+    }) {
+        return Some(*category);
+    }
+
+    // Leave out the public suffix, so `example.dev` is not taken for a dev tool
+    let domain = site_match::registrable_domain(&host);
+    let suffix_len = domain.find('.').map_or(0, |dot| domain.len() - dot);
+    let labels = &host[..host.len() - suffix_len];
+    labels.split('.').find_map(|label| {
+        HOST_WORDS
+            .iter()
+            .find(|(word, whole, _)| {
+                if *whole {
+                    label == *word
+                } else {
+                    label.contains(word)
+                }
+            })
+            .map(|(_, _, category)| *category)
+    })
+}
+
+/// The tag suggested for an item on `site` that has `tags`, None if it is
+/// already tagged or nothing gives its category away
+pub fn suggest_tag(site: &str, tags: &[String]) -> Option<&'static str> {
+    if !tags.is_empty() {
+        return None;
+    }
+    classify(site).map(Category::tag)
+}
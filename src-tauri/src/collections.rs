@@ -0,0 +1,124 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+
+use crate::crypto::CryptoService;
+use crate::error::{AppError, AppResult, CryptoError};
+
+/// Context label for deriving the vault's member key pair from the vault key
+pub const MEMBER_KEY_CONTEXT: &[u8] = b"secret-plan member x25519 v1";
+
+/// Context label mixed into every key-encryption key
+const WRAP_CONTEXT: &[u8] = b"secret-plan collection key wrap v1";
+
+/// A shared group of credentials whose secrets are encrypted with a collection key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Collection {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub members: Vec<CollectionMember>,
+}
+
+/// A member of a collection, identified by their X25519 public key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionMember {
+    /// Base64 X25519 public key
+    pub public_key: String,
+    /// Display name chosen by whoever added the member
+    pub label: String,
+    pub added_at: DateTime<Utc>,
+    /// The collection key, readable only with the member's private key
+    pub wrapped_key: WrappedKey,
+}
+
+/// A collection key encrypted for one member (ephemeral X25519 + AES-256-GCM)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WrappedKey {
+    /// Base64 ephemeral X25519 public key
+    pub ephemeral_public_key: String,
+    /// Encrypted container as produced by `CryptoService::encrypt_with_key`
+    pub ciphertext: String,
+}
+
+/// Generates a random collection key
+pub fn generate_collection_key() -> [u8; 32] {
+    CryptoService::generate_key()
+}
+
+/// Member key pair for the vault, from a subkey of the vault key
+pub fn member_secret(seed: [u8; 32]) -> StaticSecret {
+    StaticSecret::from(seed)
+}
+
+/// Encodes a public key for storage and display
+pub fn encode_public_key(key: &PublicKey) -> String {
+    BASE64.encode(key.as_bytes())
+}
+
+/// Parses a Base64 X25519 public key
+pub fn decode_public_key(encoded: &str) -> AppResult<PublicKey> {
+    BASE64
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .map(PublicKey::from)
+        .ok_or_else(|| AppError::InvalidInput(format!("Invalid member public key: {}", encoded)))
+}
+
+fn key_encryption_key(
+    shared_secret: &[u8; 32],
+    ephemeral: &PublicKey,
+    recipient: &PublicKey,
+) -> AppResult<[u8; 32]> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(shared_secret)
+        .map_err(|e| CryptoError::KeyDerivation(e.to_string()))?;
+    mac.update(WRAP_CONTEXT);
+    mac.update(ephemeral.as_bytes());
+    mac.update(recipient.as_bytes());
+    Ok(mac.finalize().into_bytes().into())
+}
+
+/// Encrypts `collection_key` so only the holder of `recipient`'s private key can read it.
+/// The collection id is bound as associated data.
+pub fn wrap_key(
+    collection_key: &[u8; 32],
+    recipient: &PublicKey,
+    collection_id: &str,
+) -> AppResult<WrappedKey> {
+    let ephemeral = EphemeralSecret::random_from_rng(OsRng);
+    let ephemeral_public = PublicKey::from(&ephemeral);
+    let shared = ephemeral.diffie_hellman(recipient);
+    let kek = key_encryption_key(shared.as_bytes(), &ephemeral_public, recipient)?;
+    Ok(WrappedKey {
+        ephemeral_public_key: encode_public_key(&ephemeral_public),
+        ciphertext: CryptoService::encrypt_with_key(
+            &kek,
+            collection_key,
+            collection_id.as_bytes(),
+        )?,
+    })
+}
+
+/// Recovers a collection key wrapped for `secret`'s public key
+pub fn unwrap_key(
+    wrapped: &WrappedKey,
+    secret: &StaticSecret,
+    collection_id: &str,
+) -> AppResult<[u8; 32]> {
+    let ephemeral_public = decode_public_key(&wrapped.ephemeral_public_key)?;
+    let shared = secret.diffie_hellman(&ephemeral_public);
+    let kek = key_encryption_key(
+        shared.as_bytes(),
+        &ephemeral_public,
+        &PublicKey::from(secret),
+    )?;
+    let key = CryptoService::decrypt_with_key(&kek, &wrapped.ciphertext, collection_id.as_bytes())?;
+    <[u8; 32]>::try_from(key.as_slice()).map_err(|_| {
+        CryptoError::InvalidFormat("Collection key must be 32 bytes".to_string()).into()
+    })
+}
@@ -0,0 +1,84 @@
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppResult;
+
+/// Bytes written per step when overwriting a file
+const WIPE_CHUNK: usize = 1024 * 1024;
+
+/// Phase of a compaction run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompactStage {
+    /// Rebuilding the database without free pages
+    Vacuuming,
+    /// Overwriting the replaced database file
+    Wiping,
+    /// Moving the compacted database into place
+    Restoring,
+}
+
+/// Progress of a compaction run, sent to the frontend as an event payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactProgress {
+    pub stage: CompactStage,
+    /// Units finished in this stage (bytes while wiping, otherwise 0 or 1)
+    pub done: u64,
+    pub total: u64,
+}
+
+impl CompactProgress {
+    pub fn step(stage: CompactStage, done: bool) -> Self {
+        Self {
+            stage,
+            done: u64::from(done),
+            total: 1,
+        }
+    }
+}
+
+/// Receives progress updates; implemented for closures
+pub trait ProgressSink {
+    fn report(&self, progress: &CompactProgress);
+}
+
+impl<F: Fn(&CompactProgress)> ProgressSink for F {
+    fn report(&self, progress: &CompactProgress) {
+        self(progress)
+    }
+}
+
+/// Outcome of `compact_vault`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactReport {
+    pub size_before: u64,
+    pub size_after: u64,
+    /// Free pages (possibly holding deleted data) that were dropped
+    pub freed_pages: u64,
+    /// Whether the old file was overwritten before being replaced
+    pub wiped: bool,
+}
+
+/// Overwrites `file` with zeros in place and syncs it to disk. The file may
+/// already be unlinked; its blocks are overwritten all the same.
+/// On SSDs and copy-on-write filesystems old blocks may still survive elsewhere.
+pub fn overwrite_file(file: &mut File, progress: &dyn ProgressSink) -> AppResult<()> {
+    let total = file.metadata()?.len();
+    file.seek(SeekFrom::Start(0))?;
+    let zeros = vec![0u8; WIPE_CHUNK];
+    let mut done = 0;
+    while done < total {
+        let len = (total - done).min(WIPE_CHUNK as u64) as usize;
+        file.write_all(&zeros[..len])?;
+        done += len as u64;
+        progress.report(&CompactProgress {
+            stage: CompactStage::Wiping,
+            done,
+            total,
+        });
+    }
+    file.sync_all()?;
+    Ok(())
+}
@@ -6,11 +6,23 @@ use argon2::{
     password_hash::SaltString, Argon2, Params, PasswordHasher, PasswordVerifier, Version,
 };
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ed25519_dalek::{Signature, VerifyingKey};
+use hmac::{Hmac, Mac};
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
-
+use sha2::Sha256;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use tracing::debug;
+use x25519_dalek::PublicKey;
+use zeroize::Zeroizing;
+
+use crate::collections::WrappedKey;
 use crate::error::{AppError, AppResult, CryptoError};
+use crate::hardening::{self, MitigationState};
 use crate::models::AppSettings;
+use crate::session::EphemeralSession;
+use crate::subkeys;
 
 /// Encrypted container format (used for secrets in vault_items)
 #[derive(Debug, Serialize, Deserialize)]
@@ -21,10 +33,32 @@ struct EncryptedContainer {
     ciphertext: String,
 }
 
+/// A master key derived by `CryptoService::derive_unlock_key`, to be handed to
+/// `CryptoService::unlock_with_key`
+pub struct DerivedKey(Key<Aes256Gcm>);
+
+impl DerivedKey {
+    /// Decrypts a container made with the key this was derived as, e.g. a
+    /// backup, without unlocking a service
+    pub(crate) fn decrypt(
+        &self,
+        encrypted_container: &str,
+        associated_data: &[u8],
+    ) -> AppResult<Vec<u8>> {
+        let (nonce_bytes, ciphertext) = unpack(encrypted_container)?;
+        open(&self.0, &ciphertext, associated_data, &nonce_bytes)
+    }
+}
+
 /// Handles all cryptographic operations
 pub struct CryptoService {
     /// Encryption key derived from master password
     master_key: Option<Key<Aes256Gcm>>,
+    /// Holds the key instead of `master_key` in an ephemeral session. Shared
+    /// only weakly with `SubkeyCipher`s, so dropping it ends the session.
+    session: Option<Arc<Mutex<EphemeralSession>>>,
+    /// Whether the page holding `master_key` is kept out of swap
+    key_memory: MitigationState,
     /// Stored Argon2 hash of the master password for verification
     master_password_hash: Option<String>,
     /// Application settings for KDF parameters
@@ -37,6 +71,8 @@ impl CryptoService {
     pub fn new(settings: AppSettings) -> Self {
         Self {
             master_key: None,
+            session: None,
+            key_memory: MitigationState::Inactive,
             master_password_hash: None, // Will be loaded or created during unlock/init
             settings,
             settings_repo: None,
@@ -76,21 +112,32 @@ impl CryptoService {
         Ok((*Key::<Aes256Gcm>::from_slice(&key_bytes), password_hash))
     }
 
-    /// Verifies the master password against the stored hash and derives the key if successful.
-    fn verify_password_and_derive_key(
-        &self,
+    /// Verifies the master password against the stored hash and derives the key if
+    /// successful. Needs no service, so it can run on a thread of its own; `cancel`
+    /// is checked between the two Argon2 runs.
+    pub fn derive_unlock_key(
         master_password: &str,
         stored_hash: &str,
-    ) -> AppResult<Key<Aes256Gcm>> {
-        let argon2 = self.get_argon2_instance()?;
+        cancel: &AtomicBool,
+    ) -> AppResult<DerivedKey> {
+        let started = std::time::Instant::now();
         let parsed_hash = argon2::PasswordHash::new(stored_hash).map_err(|e| {
             CryptoError::KeyDerivation(format!("Invalid stored hash format: {}", e))
         })?;
+        // The key is derived with the parameters the vault was created with, which
+        // calibration may have set to something other than the current settings
+        let params = Params::try_from(&parsed_hash).map_err(|e| {
+            CryptoError::KeyDerivation(format!("Invalid parameters in stored hash: {}", e))
+        })?;
+        let argon2 = Argon2::new(argon2::Algorithm::Argon2id, Version::V0x13, params.clone());
 
         // Verify the password
         argon2
             .verify_password(master_password.as_bytes(), &parsed_hash)
             .map_err(|_| AppError::AuthFailed)?; // Use AuthFailed for incorrect password
+        if cancel.load(Ordering::SeqCst) {
+            return Err(AppError::Cancelled);
+        }
 
         // If verification succeeded, *re-derive* the key using the salt from the stored hash
         let salt = parsed_hash
@@ -105,64 +152,181 @@ impl CryptoService {
             )
             .map_err(|e| CryptoError::KeyDerivation(format!("Key re-derivation failed: {}", e)))?;
 
-        Ok(*Key::<Aes256Gcm>::from_slice(&key_bytes))
+        debug!(
+            memory_kb = params.m_cost(),
+            iterations = params.t_cost(),
+            parallelism = params.p_cost(),
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "master key derived"
+        );
+        Ok(DerivedKey(*Key::<Aes256Gcm>::from_slice(&key_bytes)))
     }
 
     /// Unlocks the CryptoService with the given master password.
-    /// This now involves loading the stored hash (if available) and verifying.
+    /// Loads the stored hash (if available) and verifies against it; a vault without
+    /// a master password is never created here, see `initialize`.
     pub fn unlock(&mut self, master_password: &str) -> AppResult<()> {
-        // Load the master_password_hash from the repository if available
+        let stored_hash = self.load_hash_for_unlock()?;
+        let key = Self::derive_unlock_key(master_password, &stored_hash, &AtomicBool::new(false))?;
+        self.unlock_with_key(&stored_hash, key)
+    }
+
+    /// Loads the stored hash (if available) for an unlock whose key is derived
+    /// separately with `derive_unlock_key`
+    pub fn load_hash_for_unlock(&mut self) -> AppResult<String> {
         if let Some(repo) = &self.settings_repo {
             self.master_password_hash = repo.get_master_password_hash()?;
         }
+        self.master_password_hash
+            .clone()
+            .ok_or(AppError::VaultNotCreated)
+    }
 
-        let (key, hash_to_store) = match &self.master_password_hash {
-            Some(stored_hash) => {
-                // Verify existing password and derive key
-                let key = self.verify_password_and_derive_key(master_password, stored_hash)?;
-                (key, stored_hash.clone()) // Keep the existing hash
-            }
-            None => {
-                // First time unlock / vault creation: derive key and hash
-                let (key, new_hash) = self.derive_key_and_hash(master_password)?;
-                // Store the new hash in the repository
-                if let Some(repo) = &self.settings_repo {
-                    repo.save_master_password_hash(&new_hash)?;
-                }
-                (key, new_hash)
-            }
-        };
-
-        self.master_key = Some(key);
-        self.master_password_hash = Some(hash_to_store);
+    /// Finishes an unlock with a key derived from `stored_hash`. Fails if the
+    /// master password changed since the hash was loaded.
+    pub fn unlock_with_key(&mut self, stored_hash: &str, key: DerivedKey) -> AppResult<()> {
+        if self.master_password_hash.as_deref() != Some(stored_hash) {
+            return Err(AppError::Conflict(
+                "The master password changed during unlock; try again".to_string(),
+            ));
+        }
+        self.set_master_key(Some(key.0));
         Ok(())
     }
 
+    /// Derives the key of a vault being created without storing the hash, which
+    /// the caller persists together with the first settings. Fails if the vault
+    /// already has a master password.
+    pub fn initialize(&mut self, master_password: &str) -> AppResult<String> {
+        if let Some(repo) = &self.settings_repo {
+            self.master_password_hash = repo.get_master_password_hash()?;
+        }
+        if self.master_password_hash.is_some() {
+            return Err(AppError::Conflict(
+                "The vault already has a master password".to_string(),
+            ));
+        }
+        let (key, hash) = self.derive_key_and_hash(master_password)?;
+        self.set_master_key(Some(key));
+        self.master_password_hash = Some(hash.clone());
+        Ok(hash)
+    }
+
+    /// Forgets a key from `initialize` whose hash could not be stored
+    pub fn abandon_initialization(&mut self) {
+        self.set_master_key(None);
+        self.master_password_hash = None;
+    }
+
+    /// Re-checks the master password against the loaded hash without touching the key.
+    /// Used to gate sensitive operations while the vault is already unlocked.
+    pub fn verify_master_password(&self, master_password: &str) -> AppResult<()> {
+        let stored_hash = self
+            .master_password_hash
+            .as_ref()
+            .ok_or(AppError::VaultLocked)?;
+        let argon2 = self.get_argon2_instance()?;
+        let parsed_hash = argon2::PasswordHash::new(stored_hash).map_err(|e| {
+            CryptoError::KeyDerivation(format!("Invalid stored hash format: {}", e))
+        })?;
+        argon2
+            .verify_password(master_password.as_bytes(), &parsed_hash)
+            .map_err(|_| AppError::AuthFailed)
+    }
+
     /// Locks the CryptoService by removing the derived key
     pub fn lock(&mut self) {
-        self.master_key = None;
+        self.set_master_key(None);
+        // Ends the session process and the key with it
+        self.session = None;
         // Keep master_password_hash loaded
     }
 
     /// Checks if the CryptoService is unlocked
     pub fn is_unlocked(&self) -> bool {
-        self.master_key.is_some()
+        self.master_key.is_some() || self.session.is_some()
+    }
+
+    /// Moves the unlocked key into an ephemeral session process, see `session`.
+    /// Does nothing if it is there already.
+    pub fn start_ephemeral_session(&mut self) -> AppResult<()> {
+        if self.session.is_some() {
+            return Ok(());
+        }
+        let key = Zeroizing::new(<[u8; 32]>::from(*self.get_key()?));
+        let session = EphemeralSession::start(&key)?;
+        self.session = Some(Arc::new(Mutex::new(session)));
+        self.set_master_key(None);
+        Ok(())
+    }
+
+    /// Replaces the key, locking the memory of the new one and unlocking the old
+    fn set_master_key(&mut self, key: Option<Key<Aes256Gcm>>) {
+        if let Some(old) = self.master_key.as_mut() {
+            hardening::wipe(old.as_mut_slice());
+            if self.key_memory == MitigationState::Active {
+                hardening::unlock_memory(old.as_slice());
+            }
+        }
+        self.master_key = key;
+        self.key_memory = match &self.master_key {
+            Some(key) => hardening::lock_memory(key.as_slice()),
+            None => MitigationState::Inactive,
+        };
+    }
+
+    /// Whether the key in this process is kept out of swap
+    pub fn key_memory_state(&self) -> MitigationState {
+        self.key_memory
+    }
+
+    /// Whether the key is held by an ephemeral session process
+    pub fn in_ephemeral_session(&self) -> bool {
+        self.session.is_some()
+    }
+
+    /// The hash the key of an unlocked vault was derived with, for backups that
+    /// are opened with the master password alone
+    pub(crate) fn master_password_hash(&self) -> AppResult<&str> {
+        if !self.is_unlocked() {
+            return Err(AppError::VaultLocked);
+        }
+        self.master_password_hash
+            .as_deref()
+            .ok_or(AppError::VaultLocked)
+    }
+
+    /// Whether a master password hash was loaded, which `unlock` does and `lock` keeps
+    pub fn has_loaded_hash(&self) -> bool {
+        self.master_password_hash.is_some()
     }
 
     /// Encrypts plaintext data using AES-256-GCM, returning JSON container.
     pub fn encrypt(&self, plaintext: &[u8], associated_data: &[u8]) -> AppResult<String> {
         let (nonce_bytes, ciphertext) = self.encrypt_raw(plaintext, associated_data)?;
+        pack(&nonce_bytes, &ciphertext)
+    }
 
-        // Package the nonce and ciphertext in our container format
-        let container = EncryptedContainer {
-            nonce: BASE64.encode(nonce_bytes),
-            ciphertext: BASE64.encode(ciphertext),
-        };
+    /// Generates a random 32-byte key for use with `encrypt_with_key`
+    pub fn generate_key() -> [u8; 32] {
+        let mut key = [0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        key
+    }
 
-        // Serialize and return the container
-        serde_json::to_string(&container).map_err(|e| {
-            CryptoError::Encryption(format!("Failed to serialize container: {}", e)).into()
-        })
+    /// Like `encrypt`, but with an explicit 32-byte key instead of the master key
+    /// (e.g. a shared collection key).
+    pub fn encrypt_with_key(
+        key: &[u8; 32],
+        plaintext: &[u8],
+        associated_data: &[u8],
+    ) -> AppResult<String> {
+        let (nonce_bytes, ciphertext) = seal(
+            Key::<Aes256Gcm>::from_slice(key),
+            plaintext,
+            associated_data,
+        )?;
+        pack(&nonce_bytes, &ciphertext)
     }
 
     /// Encrypts plaintext data, returning raw nonce and ciphertext.
@@ -182,47 +346,40 @@ impl CryptoService {
         plaintext: &[u8],
         associated_data: &[u8],
     ) -> AppResult<([u8; 12], Vec<u8>)> {
-        let key = self.get_key()?;
-
-        // Generate a random 96-bit (12-byte) nonce
-        let mut nonce_bytes = [0u8; 12];
-        OsRng.fill_bytes(&mut nonce_bytes);
-        let nonce = Nonce::from_slice(&nonce_bytes);
-
-        // Create cipher instance
-        let cipher = Aes256Gcm::new(key);
-
-        // Encrypt the plaintext with associated data
-        let ciphertext = cipher
-            .encrypt(
-                nonce,
-                Payload {
-                    msg: plaintext,
-                    aad: associated_data,
-                },
-            )
-            .map_err(|e| CryptoError::Encryption(format!("Encryption failed: {:?}", e)))?;
-
-        Ok((nonce_bytes, ciphertext))
+        match &self.session {
+            Some(session) => session.lock().unwrap().seal(plaintext, associated_data),
+            None => seal(self.get_key()?, plaintext, associated_data),
+        }
     }
 
     /// Decrypts ciphertext from a JSON container using AES-256-GCM.
     pub fn decrypt(&self, encrypted_container: &str, associated_data: &[u8]) -> AppResult<Vec<u8>> {
-        // Parse the container
-        let container: EncryptedContainer = serde_json::from_str(encrypted_container)
-            .map_err(|e| CryptoError::InvalidFormat(format!("Invalid container format: {}", e)))?;
-
-        // Decode base64 components
-        let nonce_bytes = BASE64
-            .decode(&container.nonce)
-            .map_err(|e| CryptoError::InvalidFormat(format!("Invalid nonce encoding: {}", e)))?;
+        let (nonce_bytes, ciphertext) = unpack(encrypted_container)?;
+        self.decrypt_raw(&ciphertext, associated_data, &nonce_bytes)
+    }
 
-        let ciphertext = BASE64.decode(&container.ciphertext).map_err(|e| {
-            CryptoError::InvalidFormat(format!("Invalid ciphertext encoding: {}", e))
-        })?;
+    /// Like `decrypt`, but with an explicit 32-byte key instead of the master key.
+    pub fn decrypt_with_key(
+        key: &[u8; 32],
+        encrypted_container: &str,
+        associated_data: &[u8],
+    ) -> AppResult<Vec<u8>> {
+        let (nonce_bytes, ciphertext) = unpack(encrypted_container)?;
+        open(
+            Key::<Aes256Gcm>::from_slice(key),
+            &ciphertext,
+            associated_data,
+            &nonce_bytes,
+        )
+    }
 
-        // Decrypt using the raw decryption method
-        self.decrypt_raw(&ciphertext, associated_data, &nonce_bytes)
+    /// The nonce of a JSON container, read without decrypting it
+    pub fn container_nonce(encrypted_container: &str) -> AppResult<Vec<u8>> {
+        let container: EncryptedContainer = serde_json::from_str(encrypted_container)
+            .map_err(|e| CryptoError::InvalidFormat(format!("Invalid container format: {}", e)))?;
+        BASE64.decode(&container.nonce).map_err(|e| {
+            CryptoError::InvalidFormat(format!("Invalid nonce encoding: {}", e)).into()
+        })
     }
 
     /// Decrypts raw ciphertext using AES-256-GCM with a provided nonce.
@@ -243,28 +400,13 @@ impl CryptoService {
         associated_data: &[u8],
         nonce_bytes: &[u8],
     ) -> AppResult<Vec<u8>> {
-        let key = self.get_key()?;
-
-        if nonce_bytes.len() != 12 {
-            return Err(CryptoError::InvalidFormat("Nonce must be 12 bytes".to_string()).into());
+        match &self.session {
+            Some(session) => session
+                .lock()
+                .unwrap()
+                .open(ciphertext, associated_data, nonce_bytes),
+            None => open(self.get_key()?, ciphertext, associated_data, nonce_bytes),
         }
-        let nonce = Nonce::from_slice(nonce_bytes);
-
-        // Create cipher instance
-        let cipher = Aes256Gcm::new(key);
-
-        // Decrypt the ciphertext with associated data
-        let plaintext = cipher
-            .decrypt(
-                nonce,
-                Payload {
-                    msg: ciphertext,
-                    aad: associated_data,
-                },
-            )
-            .map_err(|e| CryptoError::Decryption(format!("Decryption failed: {:?}", e)))?;
-
-        Ok(plaintext)
     }
 
     /// Helper to get the master key or return an error if locked
@@ -272,6 +414,178 @@ impl CryptoService {
         self.master_key.as_ref().ok_or(AppError::VaultLocked)
     }
 
+    /// Raw master key, for wrapping it for emergency contacts. Refused in an
+    /// ephemeral session, whose point is that the key is not in this process.
+    pub(crate) fn master_key_bytes(&self) -> AppResult<[u8; 32]> {
+        if self.session.is_some() {
+            return Err(AppError::InvalidInput(
+                "The vault key cannot be used directly in an ephemeral session".to_string(),
+            ));
+        }
+        Ok((*self.get_key()?).into())
+    }
+
+    /// Runs `local` with the key in this process, or `remote` against the
+    /// ephemeral session worker holding it
+    fn with_key<T>(
+        &self,
+        local: impl FnOnce(&Key<Aes256Gcm>) -> AppResult<T>,
+        remote: impl FnOnce(&mut EphemeralSession) -> AppResult<T>,
+    ) -> AppResult<T> {
+        match &self.session {
+            Some(session) => remote(&mut session.lock().unwrap()),
+            None => local(self.get_key()?),
+        }
+    }
+
+    /// A cipher for the subkey bound to `context`, for code that encrypts
+    /// outside this service, such as a storage backend. Different `context`
+    /// labels yield independent keys.
+    pub fn subkey_cipher(&self, context: &[u8]) -> AppResult<SubkeyCipher> {
+        let holder = match &self.session {
+            Some(session) => SubkeyHolder::Session(Arc::downgrade(session)),
+            None => SubkeyHolder::Local(Zeroizing::new(subkey(self.get_key()?, context)?)),
+        };
+        Ok(SubkeyCipher {
+            context: context.to_vec(),
+            holder,
+        })
+    }
+
+    /// HMAC-SHA256 of each message under the subkey bound to `context`
+    pub fn mac_with_subkey(
+        &self,
+        context: &[u8],
+        messages: &[Vec<u8>],
+    ) -> AppResult<Vec<[u8; 32]>> {
+        self.with_key(
+            |key| subkeys::mac(key, context, messages),
+            |session| session.mac(context, messages),
+        )
+    }
+
+    /// Signs `message` with the vault's audit signing key
+    pub fn sign_audit(&self, message: &[u8]) -> AppResult<Signature> {
+        self.with_key(
+            |key| subkeys::sign(key, message),
+            |session| session.sign(message),
+        )
+    }
+
+    /// Public half of the vault's audit signing key
+    pub fn audit_public_key(&self) -> AppResult<VerifyingKey> {
+        self.with_key(
+            subkeys::signing_public_key,
+            EphemeralSession::signing_public_key,
+        )
+    }
+
+    /// Public half of the vault's member key for shared collections
+    pub fn member_public_key(&self) -> AppResult<PublicKey> {
+        self.with_key(
+            subkeys::member_public_key,
+            EphemeralSession::member_public_key,
+        )
+    }
+
+    /// A fresh collection key, wrapped for the vault's member key
+    pub fn new_collection_key(&self, collection_id: &str) -> AppResult<WrappedKey> {
+        self.with_key(
+            |key| subkeys::new_collection_key(key, collection_id),
+            |session| session.new_collection_key(collection_id),
+        )
+    }
+
+    /// The collection key in `wrapped`, wrapped again for `recipient`
+    pub fn rewrap_collection_key(
+        &self,
+        wrapped: &WrappedKey,
+        collection_id: &str,
+        recipient: &PublicKey,
+    ) -> AppResult<WrappedKey> {
+        self.with_key(
+            |key| subkeys::rewrap_collection_key(key, wrapped, collection_id, recipient),
+            |session| session.rewrap_collection_key(wrapped, collection_id, recipient),
+        )
+    }
+
+    /// Like `encrypt`, with the collection key in `wrapped`
+    pub fn encrypt_for_collection(
+        &self,
+        wrapped: &WrappedKey,
+        collection_id: &str,
+        plaintext: &[u8],
+        associated_data: &[u8],
+    ) -> AppResult<String> {
+        self.with_key(
+            |key| subkeys::seal_collection(key, wrapped, collection_id, plaintext, associated_data),
+            |session| session.seal_collection(wrapped, collection_id, plaintext, associated_data),
+        )
+    }
+
+    /// Like `decrypt`, with the collection key in `wrapped`
+    pub fn decrypt_for_collection(
+        &self,
+        wrapped: &WrappedKey,
+        collection_id: &str,
+        encrypted_container: &str,
+        associated_data: &[u8],
+    ) -> AppResult<Vec<u8>> {
+        self.with_key(
+            |key| {
+                subkeys::open_collection(
+                    key,
+                    wrapped,
+                    collection_id,
+                    encrypted_container,
+                    associated_data,
+                )
+            },
+            |session| {
+                session.open_collection(
+                    wrapped,
+                    collection_id,
+                    encrypted_container,
+                    associated_data,
+                )
+            },
+        )
+    }
+
+    /// Moves a container from the collection key in `from` to the one in `to`
+    /// without the plaintext leaving the key holder. None if it is under `to`
+    /// already.
+    pub fn recrypt_for_collection(
+        &self,
+        from: &WrappedKey,
+        to: &WrappedKey,
+        collection_id: &str,
+        encrypted_container: &str,
+        associated_data: &[u8],
+    ) -> AppResult<Option<String>> {
+        self.with_key(
+            |key| {
+                subkeys::recrypt_collection(
+                    key,
+                    from,
+                    to,
+                    collection_id,
+                    encrypted_container,
+                    associated_data,
+                )
+            },
+            |session| {
+                session.recrypt_collection(
+                    from,
+                    to,
+                    collection_id,
+                    encrypted_container,
+                    associated_data,
+                )
+            },
+        )
+    }
+
     /// Updates the key derivation parameters
     pub fn update_kdf_settings(&mut self, settings: AppSettings) {
         // TODO: Consider if changing KDF settings should require re-hashing the master password
@@ -279,7 +593,7 @@ impl CryptoService {
     }
 
     /// Helper to configure Argon2 instance based on settings
-    fn get_argon2_instance(&self) -> AppResult<Argon2> {
+    fn get_argon2_instance(&self) -> AppResult<Argon2<'_>> {
         let params = Params::new(
             self.settings.argon2_memory_kb,
             self.settings.argon2_iterations,
@@ -297,3 +611,187 @@ impl CryptoService {
         ))
     }
 }
+
+/// Encrypts and decrypts with the subkey for one context, see
+/// `CryptoService::subkey_cipher`. In an ephemeral session the subkey stays in
+/// the worker, and the cipher fails with `AppError::VaultLocked` once the
+/// session has ended.
+#[derive(Clone)]
+pub struct SubkeyCipher {
+    context: Vec<u8>,
+    holder: SubkeyHolder,
+}
+
+#[derive(Clone)]
+enum SubkeyHolder {
+    /// The subkey itself, when the vault key is in this process anyway
+    Local(Zeroizing<[u8; 32]>),
+    Session(Weak<Mutex<EphemeralSession>>),
+}
+
+impl SubkeyCipher {
+    fn session(session: &Weak<Mutex<EphemeralSession>>) -> AppResult<Arc<Mutex<EphemeralSession>>> {
+        session.upgrade().ok_or(AppError::VaultLocked)
+    }
+
+    /// Like `CryptoService::encrypt`, with the subkey
+    pub fn encrypt(&self, plaintext: &[u8], associated_data: &[u8]) -> AppResult<String> {
+        match &self.holder {
+            SubkeyHolder::Local(key) => {
+                CryptoService::encrypt_with_key(key, plaintext, associated_data)
+            }
+            SubkeyHolder::Session(session) => Self::session(session)?
+                .lock()
+                .unwrap()
+                .seal_with_subkey(&self.context, plaintext, associated_data),
+        }
+    }
+
+    /// Like `CryptoService::decrypt`, with the subkey
+    pub fn decrypt(&self, encrypted_container: &str, associated_data: &[u8]) -> AppResult<Vec<u8>> {
+        match &self.holder {
+            SubkeyHolder::Local(key) => {
+                CryptoService::decrypt_with_key(key, encrypted_container, associated_data)
+            }
+            SubkeyHolder::Session(session) => Self::session(session)?
+                .lock()
+                .unwrap()
+                .open_with_subkey(&self.context, encrypted_container, associated_data),
+        }
+    }
+}
+
+/// Argon2 parameters chosen by `calibrate_kdf`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfCalibration {
+    pub memory_kb: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    /// Expected unlock time on this machine with these parameters
+    pub estimated_ms: u32,
+}
+
+/// Most iterations calibration picks, so a slow first measurement cannot make
+/// unlocking take minutes on a faster machine later
+pub const MAX_CALIBRATED_ITERATIONS: u32 = 20;
+
+/// Times one Argon2 pass with the memory and parallelism of `settings` and picks
+/// the iteration count closest to `target_ms`, never fewer than `settings` has
+pub fn calibrate_kdf(settings: &AppSettings, target_ms: u32) -> AppResult<KdfCalibration> {
+    let params = Params::new(
+        settings.argon2_memory_kb,
+        1,
+        settings.argon2_parallelism,
+        Some(32),
+    )
+    .map_err(|e| CryptoError::KeyDerivation(format!("Failed to build Argon2 parameters: {}", e)))?;
+    let argon2 = Argon2::new(argon2::Algorithm::Argon2id, Version::V0x13, params);
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let mut output = [0u8; 32];
+    let started = std::time::Instant::now();
+    argon2
+        .hash_password_into(b"calibration", &salt, &mut output)
+        .map_err(|e| CryptoError::KeyDerivation(format!("Calibration failed: {}", e)))?;
+    let pass_ms = (started.elapsed().as_millis() as u32).max(1);
+
+    let iterations = (target_ms / pass_ms).clamp(
+        settings.argon2_iterations,
+        MAX_CALIBRATED_ITERATIONS.max(settings.argon2_iterations),
+    );
+    Ok(KdfCalibration {
+        memory_kb: settings.argon2_memory_kb,
+        iterations,
+        parallelism: settings.argon2_parallelism,
+        estimated_ms: pass_ms.saturating_mul(iterations),
+    })
+}
+
+/// HMAC-SHA256 of `context` under `key`, the derivation behind `subkeys`
+pub(crate) fn subkey(key: &Key<Aes256Gcm>, context: &[u8]) -> AppResult<[u8; 32]> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key.as_slice())
+        .map_err(|e| CryptoError::KeyDerivation(e.to_string()))?;
+    mac.update(context);
+    Ok(mac.finalize().into_bytes().into())
+}
+
+/// Serializes a nonce and ciphertext as an `EncryptedContainer`
+fn pack(nonce_bytes: &[u8], ciphertext: &[u8]) -> AppResult<String> {
+    let container = EncryptedContainer {
+        nonce: BASE64.encode(nonce_bytes),
+        ciphertext: BASE64.encode(ciphertext),
+    };
+    serde_json::to_string(&container).map_err(|e| {
+        CryptoError::Encryption(format!("Failed to serialize container: {}", e)).into()
+    })
+}
+
+/// The nonce and ciphertext of an `EncryptedContainer`
+fn unpack(encrypted_container: &str) -> AppResult<(Vec<u8>, Vec<u8>)> {
+    let container: EncryptedContainer = serde_json::from_str(encrypted_container)
+        .map_err(|e| CryptoError::InvalidFormat(format!("Invalid container format: {}", e)))?;
+    let nonce_bytes = BASE64
+        .decode(&container.nonce)
+        .map_err(|e| CryptoError::InvalidFormat(format!("Invalid nonce encoding: {}", e)))?;
+    let ciphertext = BASE64
+        .decode(&container.ciphertext)
+        .map_err(|e| CryptoError::InvalidFormat(format!("Invalid ciphertext encoding: {}", e)))?;
+    Ok((nonce_bytes, ciphertext))
+}
+
+/// AES-256-GCM encryption with a fresh random nonce
+pub(crate) fn seal(
+    key: &Key<Aes256Gcm>,
+    plaintext: &[u8],
+    associated_data: &[u8],
+) -> AppResult<([u8; 12], Vec<u8>)> {
+    // Generate a random 96-bit (12-byte) nonce
+    let mut nonce_bytes = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce_bytes);
+    let nonce = Nonce::from_slice(&nonce_bytes);
+
+    // Create cipher instance
+    let cipher = Aes256Gcm::new(key);
+
+    // Encrypt the plaintext with associated data
+    let ciphertext = cipher
+        .encrypt(
+            nonce,
+            Payload {
+                msg: plaintext,
+                aad: associated_data,
+            },
+        )
+        .map_err(|e| CryptoError::Encryption(format!("Encryption failed: {:?}", e)))?;
+
+    Ok((nonce_bytes, ciphertext))
+}
+
+/// AES-256-GCM decryption with the nonce stored next to the ciphertext
+pub(crate) fn open(
+    key: &Key<Aes256Gcm>,
+    ciphertext: &[u8],
+    associated_data: &[u8],
+    nonce_bytes: &[u8],
+) -> AppResult<Vec<u8>> {
+    if nonce_bytes.len() != 12 {
+        return Err(CryptoError::InvalidFormat("Nonce must be 12 bytes".to_string()).into());
+    }
+    let nonce = Nonce::from_slice(nonce_bytes);
+
+    // Create cipher instance
+    let cipher = Aes256Gcm::new(key);
+
+    // Decrypt the ciphertext with associated data
+    let plaintext = cipher
+        .decrypt(
+            nonce,
+            Payload {
+                msg: ciphertext,
+                aad: associated_data,
+            },
+        )
+        .map_err(|e| CryptoError::Decryption(format!("Decryption failed: {:?}", e)))?;
+
+    Ok(plaintext)
+}
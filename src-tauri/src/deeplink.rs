@@ -0,0 +1,212 @@
+//! Links that open the app.
+//!
+//! The app is registered for `otpauth://` (TOTP setups from websites' QR code
+//! links) and its own `secretplan://` scheme, through `Info.plist` on macOS,
+//! the `.desktop` file on Linux and the NSIS installer hooks on Windows. Links
+//! arrive as command line arguments, or as open events on macOS.
+//!
+//! Nothing happens when a link arrives: it is queued (in memory only, since a
+//! TOTP link carries its secret) and the UI asks the user to confirm it once
+//! the vault is unlocked.
+//!
+//! - `otpauth://totp/Example:alice@example.com?secret=...&issuer=Example` adds
+//!   the TOTP setup to an existing item or to a new one
+//! - `secretplan://credential/<uuid>` opens an item
+
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{Credential, CredentialSummary};
+use crate::site_match;
+use crate::totp::TotpConfig;
+
+/// The app's own URL scheme
+pub const APP_SCHEME: &str = "secretplan";
+
+/// Links kept waiting for confirmation at most; later ones are dropped
+pub const MAX_PENDING_DEEP_LINKS: usize = 10;
+
+/// A link the app can act on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeepLink {
+    /// A TOTP setup; `uri` is the whole link, which is what items store
+    Totp {
+        uri: String,
+        issuer: Option<String>,
+        account: Option<String>,
+    },
+    OpenCredential {
+        uuid: String,
+    },
+}
+
+/// A received link waiting for the user
+#[derive(Debug, Clone)]
+pub struct PendingDeepLink {
+    pub id: String,
+    pub link: DeepLink,
+    pub received_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeepLinkKind {
+    Totp,
+    OpenCredential,
+}
+
+/// A pending link as shown to the user, without the TOTP secret
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeepLinkRequest {
+    pub id: String,
+    pub kind: DeepLinkKind,
+    pub issuer: Option<String>,
+    pub account: Option<String>,
+    /// The item an `open_credential` link points to
+    pub credential_uuid: Option<String>,
+    /// Items a TOTP setup probably belongs to, best first
+    pub candidates: Vec<CredentialSummary>,
+    pub received_at: DateTime<Utc>,
+}
+
+/// Whether `arg` looks like a link for the app, e.g. among command line arguments
+pub fn is_deep_link(arg: &str) -> bool {
+    let scheme = arg.split_once("://").map_or("", |(scheme, _)| scheme);
+    scheme.eq_ignore_ascii_case("otpauth") || scheme.eq_ignore_ascii_case(APP_SCHEME)
+}
+
+/// Parses a link. Errors never include the link itself, which may hold a secret.
+pub fn parse(uri: &str) -> AppResult<DeepLink> {
+    let uri = uri.trim();
+    let url = Url::parse(uri).map_err(|_| AppError::InvalidInput("Malformed link".to_string()))?;
+    match url.scheme() {
+        "otpauth" => {
+            // Also checks the type is totp and the secret is usable
+            TotpConfig::parse(uri)?;
+            let label = url
+                .path_segments()
+                .and_then(|mut segments| segments.next())
+                .map(percent_decode)
+                .unwrap_or_default();
+            let (label_issuer, account) = match label.split_once(':') {
+                Some((issuer, account)) => (Some(issuer.trim().to_string()), account.trim()),
+                None => (None, label.trim()),
+            };
+            let issuer = url
+                .query_pairs()
+                .find(|(name, _)| name == "issuer")
+                .map(|(_, value)| value.trim().to_string())
+                .or(label_issuer)
+                .filter(|issuer| !issuer.is_empty());
+            Ok(DeepLink::Totp {
+                uri: uri.to_string(),
+                issuer,
+                account: Some(account.to_string()).filter(|account| !account.is_empty()),
+            })
+        }
+        scheme if scheme == APP_SCHEME => {
+            let uuid = match (url.host_str(), url.path_segments()) {
+                (Some("credential"), Some(mut segments)) => segments.next().unwrap_or(""),
+                _ => "",
+            };
+            let uuid = Uuid::parse_str(uuid).map_err(|_| {
+                AppError::InvalidInput(format!(
+                    "Unsupported {} link; expected {}://credential/<id>",
+                    APP_SCHEME, APP_SCHEME
+                ))
+            })?;
+            Ok(DeepLink::OpenCredential {
+                uuid: uuid.to_string(),
+            })
+        }
+        scheme => Err(AppError::InvalidInput(format!(
+            "Unsupported link type: {}",
+            scheme
+        ))),
+    }
+}
+
+/// Queues the links among `args` for confirmation and returns how many were
+/// added. Other arguments and malformed links are skipped. Only the scheme is
+/// logged, never the link.
+pub fn enqueue<I: IntoIterator<Item = String>>(
+    queue: &mut Vec<PendingDeepLink>,
+    args: I,
+    now: DateTime<Utc>,
+) -> usize {
+    let mut added = 0;
+    for arg in args.into_iter().filter(|arg| is_deep_link(arg)) {
+        let scheme = arg.split_once("://").map_or("", |(scheme, _)| scheme);
+        if queue.len() >= MAX_PENDING_DEEP_LINKS {
+            warn!(
+                scheme,
+                "too many links waiting for confirmation; link dropped"
+            );
+            continue;
+        }
+        match parse(&arg) {
+            Ok(link) => {
+                info!(scheme, "link received");
+                queue.push(PendingDeepLink {
+                    id: Uuid::new_v4().to_string(),
+                    link,
+                    received_at: now,
+                });
+                added += 1;
+            }
+            Err(_) => warn!(scheme, "malformed link ignored"),
+        }
+    }
+    added
+}
+
+fn percent_decode(text: &str) -> String {
+    // Reuse the URL parser's decoding by treating the text as a query value
+    Url::parse(&format!(
+        "x:?v={}",
+        text.replace('+', "%2B").replace('&', "%26")
+    ))
+    .ok()
+    .and_then(|url| {
+        url.query_pairs()
+            .next()
+            .map(|(_, value)| value.into_owned())
+    })
+    .unwrap_or_else(|| text.to_string())
+}
+
+/// Items a TOTP setup for `issuer` and `account` probably belongs to: same
+/// username and a site naming the issuer first, then either one alone
+pub fn totp_candidates<'a>(
+    issuer: Option<&str>,
+    account: Option<&str>,
+    credentials: &'a [Credential],
+) -> Vec<&'a Credential> {
+    let issuer = issuer.map(str::to_lowercase);
+    let mut scored: Vec<(u8, &Credential)> = credentials
+        .iter()
+        .filter_map(|credential| {
+            let site_matches = issuer.as_deref().is_some_and(|issuer| {
+                let site = credential.site.to_lowercase();
+                let name = site_match::host_of(&credential.site)
+                    .map(|host| site_match::registrable_domain(&host))
+                    .and_then(|domain| domain.split('.').next().map(str::to_string));
+                site.contains(issuer)
+                    || name.is_some_and(|name| issuer.contains(&name) && name.len() >= 3)
+            });
+            let account_matches =
+                account.is_some_and(|account| credential.username.eq_ignore_ascii_case(account));
+            let score = u8::from(site_matches) * 2 + u8::from(account_matches);
+            (score > 0).then_some((score, credential))
+        })
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored
+        .into_iter()
+        .map(|(_, credential)| credential)
+        .collect()
+}
@@ -0,0 +1,136 @@
+//! Devices that have opened the vault.
+//!
+//! Each installation generates an ID and an Ed25519 keypair on first run and
+//! keeps them in a file next to the vault, never in the database, so a synced
+//! vault file does not carry one device's secret key to another. Unlocking
+//! records the device in the vault; a revoked device is refused from then on.
+//! Revocation is enforced by the app, not by cryptography: if the device itself
+//! is lost, the master password should be changed as well.
+
+use std::path::Path;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::export;
+
+/// Name of the identity file, stored next to the vault database
+pub const IDENTITY_FILE: &str = "device.json";
+
+/// This installation's identity
+pub struct DeviceIdentity {
+    pub id: String,
+    pub name: String,
+    pub platform: String,
+    signing_key: SigningKey,
+}
+
+/// On-disk form of `DeviceIdentity`; name and platform are looked up on every start
+#[derive(Serialize, Deserialize)]
+struct StoredIdentity {
+    id: String,
+    secret_key: String,
+}
+
+impl DeviceIdentity {
+    fn new(id: String, seed: &[u8; 32]) -> Self {
+        Self {
+            id,
+            name: host_name(),
+            platform: std::env::consts::OS.to_string(),
+            signing_key: SigningKey::from_bytes(seed),
+        }
+    }
+
+    /// A fresh identity for the machine the app is running on
+    pub fn generate() -> Self {
+        let mut seed = [0u8; 32];
+        OsRng.fill_bytes(&mut seed);
+        Self::new(Uuid::new_v4().to_string(), &seed)
+    }
+
+    /// Reads the identity at `path`, creating it on first run
+    pub fn load_or_create(path: &Path) -> AppResult<Self> {
+        match std::fs::read(path) {
+            Ok(bytes) => {
+                let stored: StoredIdentity = serde_json::from_slice(&bytes)?;
+                let seed: [u8; 32] = BASE64
+                    .decode(&stored.secret_key)
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| {
+                        AppError::Other(format!("Device identity {} is corrupt", path.display()))
+                    })?;
+                Ok(Self::new(stored.id, &seed))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let identity = Self::generate();
+                let stored = StoredIdentity {
+                    id: identity.id.clone(),
+                    secret_key: BASE64.encode(identity.signing_key.to_bytes()),
+                };
+                export::write_export_file(path, &serde_json::to_vec_pretty(&stored)?)?;
+                Ok(identity)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Overrides the name taken from the host name
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Base64 Ed25519 public key
+    pub fn public_key(&self) -> String {
+        BASE64.encode(self.signing_key.verifying_key().as_bytes())
+    }
+}
+
+/// A device as recorded in the vault
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Device {
+    pub id: String,
+    pub name: String,
+    /// Operating system, e.g. "linux", "macos", "windows"
+    pub platform: String,
+    /// Base64 Ed25519 public key
+    pub public_key: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// Set once the device may no longer unlock the vault
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl Device {
+    /// The record for `identity`, seen for the first time at `now`
+    pub fn new(identity: &DeviceIdentity, now: DateTime<Utc>) -> Self {
+        Self {
+            id: identity.id.clone(),
+            name: identity.name.clone(),
+            platform: identity.platform.clone(),
+            public_key: identity.public_key(),
+            first_seen: now,
+            last_seen: now,
+            revoked_at: None,
+        }
+    }
+}
+
+/// Best guess at a human-readable name for this machine
+fn host_name() -> String {
+    ["HOSTNAME", "COMPUTERNAME"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .chain(std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .find(|name| !name.is_empty())
+        .unwrap_or_else(|| "Unnamed device".to_string())
+}
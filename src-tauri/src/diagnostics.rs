@@ -0,0 +1,128 @@
+//! Diagnostics bundles for bug reports.
+//!
+//! A bundle is a zip a user can attach to an issue. It holds what is needed to
+//! reproduce a problem (versions, the database's health, settings and recent
+//! logs) and nothing that identifies the user or their vault: no credentials,
+//! no paths or URLs from the settings, and only logs that went through the
+//! scrubber in `logging`.
+
+use std::io::Write;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::error::{AppError, AppResult};
+use crate::export;
+use crate::logging::{LogEntry, REDACTED};
+use crate::models::{AppSettings, ProxySettings};
+use crate::nonce_audit::NonceAuditReport;
+
+/// Most log events included in a bundle
+pub const DIAGNOSTICS_LOG_LIMIT: usize = 500;
+
+/// Everything that goes into a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticsBundle {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub created_at: DateTime<Utc>,
+    /// None when the storage backend has no local database
+    pub schema_version: Option<u32>,
+    /// SQLite's integrity check output, `["ok"]` for a healthy database
+    pub integrity: Option<Vec<String>>,
+    /// Nonce reuse check; None while the vault is locked
+    pub nonce_audit: Option<NonceAuditReport>,
+    /// Sanitized settings; None while the vault is locked, since they are encrypted
+    pub settings: Option<AppSettings>,
+    /// Scrubbed log events, oldest first
+    pub logs: Vec<LogEntry>,
+}
+
+/// Summary returned to the frontend after a bundle is written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticsReport {
+    pub path: String,
+    /// Names of the files inside the zip
+    pub files: Vec<String>,
+    pub integrity_ok: Option<bool>,
+    /// False if a nonce was reused; None while the vault is locked
+    pub nonces_ok: Option<bool>,
+}
+
+/// `settings` without anything that points at the user: paths, URLs, addresses,
+/// API keys and sync details
+pub fn sanitize_settings(settings: &AppSettings) -> AppSettings {
+    let mut sanitized = settings.clone();
+    let redact = |value: &mut Option<String>| {
+        if value.is_some() {
+            *value = Some(REDACTED.to_string());
+        }
+    };
+    redact(&mut sanitized.backup_dir);
+    redact(&mut sanitized.share_relay_url);
+    redact(&mut sanitized.update_manifest_url);
+    redact(&mut sanitized.breach_api.base_url);
+    redact(&mut sanitized.breach_api.ca_cert_path);
+    redact(&mut sanitized.breach_api.client_cert_path);
+    redact(&mut sanitized.breach_api.client_key_path);
+    redact(&mut sanitized.email_monitor.api_key);
+    redact(&mut sanitized.email_monitor.base_url);
+    for email in &mut sanitized.email_monitor.emails {
+        *email = REDACTED.to_string();
+    }
+    if let Some(config) = sanitized.sync_config.as_mut() {
+        for value in config.values_mut() {
+            *value = REDACTED.to_string();
+        }
+    }
+    if let ProxySettings::Manual { url } = &mut sanitized.network.proxy {
+        *url = REDACTED.to_string();
+    }
+    sanitized
+}
+
+/// Writes `bundle` as a zip at `path`, returning the names of the files inside
+pub fn write_bundle(path: &Path, bundle: &DiagnosticsBundle) -> AppResult<Vec<String>> {
+    let summary = serde_json::json!({
+        "app_version": bundle.app_version,
+        "os": bundle.os,
+        "arch": bundle.arch,
+        "created_at": bundle.created_at,
+        "schema_version": bundle.schema_version,
+        "integrity": bundle.integrity,
+        // Counts only: the locations name items
+        "nonce_audit": bundle.nonce_audit.as_ref().map(|audit| serde_json::json!({
+            "containers_scanned": audit.containers_scanned,
+            "reused": audit.reused.len(),
+            "unreadable": audit.unreadable.len(),
+        })),
+    });
+    let mut files = vec![("summary.json", serde_json::to_vec_pretty(&summary)?)];
+    if let Some(settings) = &bundle.settings {
+        files.push(("settings.json", serde_json::to_vec_pretty(settings)?));
+    }
+    let log_text: String = bundle
+        .logs
+        .iter()
+        .map(|entry| format!("{}\n", entry))
+        .collect();
+    files.push(("logs.txt", log_text.into_bytes()));
+
+    let mut zip = ZipWriter::new(export::create_private_file(path)?);
+    let options = SimpleFileOptions::default();
+    for (name, contents) in &files {
+        zip.start_file(*name, options).map_err(zip_error)?;
+        zip.write_all(contents)?;
+    }
+    zip.finish().map_err(zip_error)?;
+
+    Ok(files.iter().map(|(name, _)| name.to_string()).collect())
+}
+
+fn zip_error(e: zip::result::ZipError) -> AppError {
+    AppError::Export(format!("Could not write diagnostics bundle: {}", e))
+}
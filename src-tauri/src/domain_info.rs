@@ -0,0 +1,247 @@
+//! What the app knows about well-known sites: their password rules and where
+//! to change a password.
+//!
+//! A small dataset is built in, after Apple's password manager resources.
+//! Rules are written in the same `password-rules` syntax, e.g.
+//! `minlength: 8; maxlength: 20; required: lower; required: digit; allowed: [-_.];`,
+//! and turned into `GeneratorConstraints` so generated passwords are accepted.
+//! Sites without a change-password page on record get the standard
+//! `/.well-known/change-password` URL, which browsers also use.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::generator::{CharacterClass, GeneratorConstraints, LOWER, NUMBERS, SYMBOLS, UPPER};
+use crate::site_match;
+
+/// (domain, password rules, change-password page), by registrable domain
+const KNOWN_DOMAINS: &[(&str, Option<&str>, Option<&str>)] = &[
+    (
+        "amazon.com",
+        None,
+        Some("https://www.amazon.com/ap/cnep"),
+    ),
+    (
+        "americanexpress.com",
+        Some("minlength: 8; maxlength: 20; max-consecutive: 4; required: lower, upper; required: digit; allowed: [%&_?#=];"),
+        None,
+    ),
+    (
+        "apple.com",
+        Some("minlength: 8; maxlength: 63; required: lower; required: upper; required: digit; allowed: ascii-printable;"),
+        Some("https://account.apple.com/account/manage/section/security"),
+    ),
+    (
+        "bankofamerica.com",
+        Some("minlength: 8; maxlength: 20; max-consecutive: 3; required: lower; required: upper; required: digit; allowed: [-@#*()+={}/?~;,._];"),
+        None,
+    ),
+    (
+        "chase.com",
+        Some("minlength: 8; maxlength: 32; max-consecutive: 2; required: lower, upper; required: digit; required: [!#$%+/=@~];"),
+        None,
+    ),
+    (
+        "ea.com",
+        Some("minlength: 8; maxlength: 64; required: lower; required: upper; required: digit; allowed: special;"),
+        None,
+    ),
+    (
+        "facebook.com",
+        None,
+        Some("https://www.facebook.com/settings?tab=security"),
+    ),
+    (
+        "github.com",
+        None,
+        Some("https://github.com/settings/security"),
+    ),
+    (
+        "google.com",
+        None,
+        Some("https://myaccount.google.com/signinoptions/password"),
+    ),
+    (
+        "live.com",
+        None,
+        Some("https://account.live.com/password/Change"),
+    ),
+    (
+        "microsoft.com",
+        None,
+        Some("https://account.live.com/password/Change"),
+    ),
+    (
+        "paypal.com",
+        Some("minlength: 8; maxlength: 20; max-consecutive: 3; required: lower, upper; required: digit, [!@#$%^&*()];"),
+        None,
+    ),
+    (
+        "twitter.com",
+        None,
+        Some("https://x.com/settings/password"),
+    ),
+    (
+        "wellsfargo.com",
+        Some("minlength: 8; maxlength: 32; required: lower; required: upper; required: digit; allowed: [-!@#$%^*()_+={}|:;,.?];"),
+        None,
+    ),
+    (
+        "x.com",
+        None,
+        Some("https://x.com/settings/password"),
+    ),
+];
+
+/// What is known about a site, returned by `get_domain_info`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DomainInfo {
+    /// The registrable domain the site was matched by
+    pub domain: String,
+    /// Whether the dataset has an entry for it
+    pub known: bool,
+    /// The site's rules as written in the dataset
+    pub password_rules: Option<String>,
+    /// Shortest password the site accepts
+    pub min_length: Option<usize>,
+    /// The rules as generator constraints
+    pub constraints: Option<GeneratorConstraints>,
+    /// Page to change the password on
+    pub change_password_url: String,
+}
+
+/// Password rules parsed from the `password-rules` syntax
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PasswordRules {
+    pub min_length: Option<usize>,
+    pub constraints: GeneratorConstraints,
+}
+
+/// Looks up the site of an item or a URL
+pub fn lookup(site: &str) -> AppResult<DomainInfo> {
+    let host = site_match::host_of(site)
+        .ok_or_else(|| AppError::InvalidInput(format!("Not a domain: {}", site)))?;
+    let domain = site_match::registrable_domain(&host);
+    let entry = KNOWN_DOMAINS.iter().find(|(known, _, _)| *known == domain);
+    let rules = entry
+        .and_then(|(_, rules, _)| *rules)
+        .map(parse_rules)
+        .transpose()?;
+    Ok(DomainInfo {
+        known: entry.is_some(),
+        password_rules: entry.and_then(|(_, rules, _)| rules.map(str::to_string)),
+        min_length: rules.as_ref().and_then(|rules| rules.min_length),
+        constraints: rules.map(|rules| rules.constraints),
+        change_password_url: entry
+            .and_then(|(_, _, url)| *url)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("https://{}/.well-known/change-password", domain)),
+        domain,
+    })
+}
+
+/// Splits `text` at `separator`, except inside a `[...]` character set
+fn split_outside_sets(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_set = false;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            '[' => in_set = true,
+            ']' => in_set = false,
+            c if c == separator && !in_set => {
+                parts.push(&text[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// Characters a `password-rules` class stands for: a name such as `lower`, or
+/// a set such as `[-_.]`
+fn class_characters(class: &str) -> AppResult<String> {
+    Ok(match class {
+        "lower" => LOWER.to_string(),
+        "upper" => UPPER.to_string(),
+        "digit" => NUMBERS.to_string(),
+        "special" => SYMBOLS.to_string(),
+        "ascii-printable" | "unicode" => [UPPER, LOWER, NUMBERS, SYMBOLS].concat(),
+        set if set.starts_with('[') && set.ends_with(']') && set.len() > 2 => {
+            set[1..set.len() - 1].to_string()
+        }
+        other => {
+            return Err(AppError::InvalidInput(format!(
+                "Unknown character class in password rules: {}",
+                other
+            )))
+        }
+    })
+}
+
+/// Parses rules in the `password-rules` syntax. A password has to take a
+/// character from every `required` class; `max-consecutive` and other
+/// properties the generator has no use for are ignored. A group such as
+/// `required: lower, upper` asks for either, which requiring both satisfies.
+pub fn parse_rules(rules: &str) -> AppResult<PasswordRules> {
+    let invalid = |property: &str| {
+        AppError::InvalidInput(format!("Invalid password rules property: {}", property))
+    };
+    let mut parsed = PasswordRules::default();
+    // None until a `required` or `allowed` property names what may be used
+    let mut permitted: Option<String> = None;
+    for property in split_outside_sets(rules, ';') {
+        let property = property.trim();
+        if property.is_empty() {
+            continue;
+        }
+        let (name, value) = property.split_once(':').ok_or_else(|| invalid(property))?;
+        let value = value.trim();
+        let parse_length = || value.parse::<usize>().map_err(|_| invalid(property));
+        match name.trim() {
+            "minlength" => parsed.min_length = Some(parse_length()?),
+            "maxlength" => parsed.constraints.max_length = Some(parse_length()?),
+            name @ ("required" | "allowed") => {
+                for class in split_outside_sets(value, ',') {
+                    let characters = class_characters(class.trim())?;
+                    permitted
+                        .get_or_insert_with(String::new)
+                        .push_str(&characters);
+                    if name == "required" {
+                        for required in classes_of(&characters) {
+                            if !parsed.constraints.required_classes.contains(&required) {
+                                parsed.constraints.required_classes.push(required);
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(permitted) = permitted {
+        parsed.constraints.forbidden_characters = [UPPER, LOWER, NUMBERS, SYMBOLS]
+            .concat()
+            .chars()
+            .filter(|c| !permitted.contains(*c))
+            .collect();
+    }
+    Ok(parsed)
+}
+
+/// The generator's classes `characters` draw from
+fn classes_of(characters: &str) -> Vec<CharacterClass> {
+    [
+        (CharacterClass::Uppercase, UPPER),
+        (CharacterClass::Lowercase, LOWER),
+        (CharacterClass::Numbers, NUMBERS),
+        (CharacterClass::Symbols, SYMBOLS),
+    ]
+    .into_iter()
+    .filter(|(_, class)| characters.chars().any(|c| class.contains(c)))
+    .map(|(class, _)| class)
+    .collect()
+}
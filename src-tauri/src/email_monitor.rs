@@ -0,0 +1,237 @@
+//! Breach monitoring for the user's email addresses.
+//!
+//! The addresses live in the encrypted settings. On a schedule they are looked
+//! up in the Have I Been Pwned breached-account API, which needs an API key.
+//! Breaches not seen before for an address become notifications, each linked
+//! to the credentials that log in with that address. What has been seen and the
+//! notifications themselves are stored encrypted, like the settings.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::hibp::RetryPolicy;
+use crate::models::{Credential, NetFeature};
+use crate::net::{self, classify_service_error, validate_service_url, Network};
+
+/// Endpoint used when no other is configured
+pub const DEFAULT_ACCOUNT_API_BASE_URL: &str = "https://haveibeenpwned.com/api/v3";
+
+const SERVICE: &str = "Breached account API";
+
+/// A breach as reported by the breached-account API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AccountBreach {
+    /// Stable identifier, e.g. "Adobe"
+    pub name: String,
+    pub title: String,
+    #[serde(default)]
+    pub domain: String,
+    pub breach_date: Option<NaiveDate>,
+    pub added_date: DateTime<Utc>,
+    /// What leaked, e.g. "Email addresses", "Passwords"
+    #[serde(default)]
+    pub data_classes: Vec<String>,
+}
+
+/// A breach affecting one of the monitored addresses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreachNotification {
+    pub id: String,
+    pub email: String,
+    pub breach: AccountBreach,
+    /// Credentials logging in with `email`, whose passwords should be changed
+    pub credential_uuids: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub dismissed_at: Option<DateTime<Utc>>,
+}
+
+/// What the monitor remembers between runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailMonitorState {
+    pub last_checked_at: Option<DateTime<Utc>>,
+    /// Breach names already reported, per lowercased address
+    pub seen: BTreeMap<String, BTreeSet<String>>,
+    pub notifications: Vec<BreachNotification>,
+}
+
+impl EmailMonitorState {
+    /// Whether a scheduled run is due; an interval of 0 turns scheduling off
+    pub fn is_due(&self, interval_hours: u32, now: DateTime<Utc>) -> bool {
+        interval_hours > 0
+            && self
+                .last_checked_at
+                .is_none_or(|last| now - last >= Duration::hours(interval_hours as i64))
+    }
+
+    /// Records the breaches found for `email`, returning notifications for
+    /// those not reported before
+    pub fn record(
+        &mut self,
+        email: &str,
+        breaches: Vec<AccountBreach>,
+        credentials: &[Credential],
+        now: DateTime<Utc>,
+    ) -> Vec<BreachNotification> {
+        let seen = self.seen.entry(email.to_lowercase()).or_default();
+        let mut new = Vec::new();
+        for breach in breaches {
+            if !seen.insert(breach.name.clone()) {
+                continue;
+            }
+            new.push(BreachNotification {
+                id: Uuid::new_v4().to_string(),
+                email: email.to_string(),
+                credential_uuids: matching_credentials(email, &breach, credentials),
+                breach,
+                created_at: now,
+                dismissed_at: None,
+            });
+        }
+        self.notifications.extend(new.iter().cloned());
+        new
+    }
+}
+
+/// Credentials that use `email` as their username. Where the breach names a
+/// domain, items for that site whose username is the address's local part
+/// count too, since many sites let users log in either way.
+fn matching_credentials(
+    email: &str,
+    breach: &AccountBreach,
+    credentials: &[Credential],
+) -> Vec<String> {
+    let local_part = email.split('@').next().unwrap_or(email);
+    let domain = breach.domain.to_ascii_lowercase();
+    credentials
+        .iter()
+        .filter(|credential| {
+            credential.username.eq_ignore_ascii_case(email)
+                || (!domain.is_empty()
+                    && credential.site.to_ascii_lowercase().contains(&domain)
+                    && credential.username.eq_ignore_ascii_case(local_part))
+        })
+        .map(|credential| credential.uuid.clone())
+        .collect()
+}
+
+/// Checks that `email` looks like an address worth sending to the API
+pub fn validate_email(email: &str) -> AppResult<()> {
+    let valid = email.split_once('@').is_some_and(|(local, domain)| {
+        !local.is_empty() && domain.contains('.') && !domain.starts_with('.')
+    }) && !email.chars().any(|c| c.is_whitespace() || c.is_control());
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput(format!(
+            "Not an email address: {}",
+            email
+        )))
+    }
+}
+
+/// Client for the breached-account API
+#[derive(Debug, Clone)]
+pub struct AccountBreachClient {
+    base_url: String,
+    api_key: String,
+    retry_policy: RetryPolicy,
+    network: Network,
+}
+
+impl AccountBreachClient {
+    /// Validates the base URL (https, or http on loopback)
+    pub fn new(base_url: Option<&str>, api_key: &str) -> AppResult<Self> {
+        if api_key.trim().is_empty() {
+            return Err(AppError::InvalidInput(format!(
+                "{} needs an API key",
+                SERVICE
+            )));
+        }
+        Ok(Self {
+            base_url: validate_service_url(
+                base_url.unwrap_or(DEFAULT_ACCOUNT_API_BASE_URL),
+                SERVICE,
+            )?,
+            api_key: api_key.trim().to_string(),
+            retry_policy: RetryPolicy::default(),
+            network: Network::default(),
+        })
+    }
+
+    /// Replaces the retry policy used for rate-limited and failing requests
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sends requests through the app's proxy and offline settings
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Every breach `email` appears in; empty if none. Fails with
+    /// `AppError::Network` when the API is unreachable.
+    pub async fn breaches_for(&self, email: &str) -> AppResult<Vec<AccountBreach>> {
+        let mut url = Url::parse(&self.base_url)
+            .map_err(|e| AppError::Other(format!("Invalid {} URL: {}", SERVICE, e)))?;
+        url.path_segments_mut()
+            .map_err(|_| AppError::Other(format!("Invalid {} URL", SERVICE)))?
+            .push("breachedaccount")
+            .push(email);
+        url.query_pairs_mut()
+            .append_pair("truncateResponse", "false");
+
+        let client = net::build_client(
+            self.network
+                .feature_client_builder(NetFeature::Hibp, SERVICE)?,
+        )?;
+        let mut attempt = 0;
+        let response = loop {
+            let response = client
+                .get(url.clone())
+                .header("hibp-api-key", &self.api_key)
+                .send()
+                .await
+                .map_err(|e| classify_service_error(e, SERVICE))?;
+            let status = response.status();
+            let retryable = status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+            if !retryable || attempt >= self.retry_policy.max_retries {
+                break response;
+            }
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok())
+                .map(std::time::Duration::from_secs);
+            tokio::time::sleep(self.retry_policy.delay_for(attempt, retry_after)).await;
+            attempt += 1;
+        };
+
+        // The address is not logged: it identifies the user
+        debug!(status = %response.status(), attempts = attempt + 1, "breached account lookup finished");
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(Vec::new()),
+            StatusCode::UNAUTHORIZED => {
+                Err(AppError::Other(format!("{} rejected the API key", SERVICE)))
+            }
+            status if status.is_success() => response
+                .json()
+                .await
+                .map_err(|e| AppError::Other(format!("Invalid {} response: {}", SERVICE, e))),
+            status => Err(AppError::Other(format!(
+                "{} returned error: {}",
+                SERVICE, status
+            ))),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Emergency access: a trusted contact can recover the vault key after a
+//! waiting period during which the owner can veto the request.
+//!
+//! ```text
+//! Active --request--> RecoveryRequested --approve / wait elapsed--> RecoveryGranted
+//!   ^                        |
+//!   +--------reject----------+          any state --revoke--> Revoked
+//! ```
+//!
+//! The waiting period is enforced by this app, not by cryptography. The key
+//! is wrapped for the contact when they are added, and the wrapped copy sits in
+//! the database from then on; `release_emergency_key` only decides when the app
+//! hands it out, going by the local clock. So the wait and the veto protect
+//! against a contact who asks through the app, not against one who also gets a
+//! copy of the database file or can set the device's clock: they can unwrap
+//! the key at once. Add as contacts only people trusted with the vault as it is
+//! today, and revoke a contact (which deletes the wrapped key) rather than rely
+//! on a veto if that trust is gone.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::collections::WrappedKey;
+use crate::error::{AppError, AppResult};
+
+/// Allowed waiting period between a request and automatic access, in days
+pub const MIN_WAIT_DAYS: u32 = 1;
+pub const MAX_WAIT_DAYS: u32 = 90;
+
+/// Where an emergency contact is in the recovery process
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmergencyState {
+    /// Designated; no recovery in progress
+    Active,
+    /// The contact asked for access; the owner can still veto
+    RecoveryRequested,
+    /// The owner approved or the waiting period passed; the key can be released
+    RecoveryGranted,
+    /// The owner withdrew access; the wrapped key is gone
+    Revoked,
+}
+
+impl EmergencyState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EmergencyState::Active => "active",
+            EmergencyState::RecoveryRequested => "recovery_requested",
+            EmergencyState::RecoveryGranted => "recovery_granted",
+            EmergencyState::Revoked => "revoked",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "active" => Some(EmergencyState::Active),
+            "recovery_requested" => Some(EmergencyState::RecoveryRequested),
+            "recovery_granted" => Some(EmergencyState::RecoveryGranted),
+            "revoked" => Some(EmergencyState::Revoked),
+            _ => None,
+        }
+    }
+}
+
+/// A trusted contact holding a wrapped copy of the vault key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyContact {
+    pub id: String,
+    pub label: String,
+    /// Base64 X25519 public key of the contact
+    pub public_key: String,
+    /// Days between a recovery request and automatic access. Enforced by the
+    /// app only; the module documentation says what it does not protect against.
+    pub wait_days: u32,
+    pub state: EmergencyState,
+    pub created_at: DateTime<Utc>,
+    /// When the pending or last granted request was made
+    pub requested_at: Option<DateTime<Utc>>,
+    pub state_changed_at: DateTime<Utc>,
+    /// The vault key wrapped for the contact (None once revoked)
+    #[serde(skip_serializing)]
+    pub wrapped_key: Option<WrappedKey>,
+}
+
+impl EmergencyContact {
+    /// A new, active contact
+    pub fn new(
+        label: &str,
+        public_key: String,
+        wait_days: u32,
+        now: DateTime<Utc>,
+    ) -> AppResult<Self> {
+        if !(MIN_WAIT_DAYS..=MAX_WAIT_DAYS).contains(&wait_days) {
+            return Err(AppError::InvalidInput(format!(
+                "Waiting period must be between {} and {} days",
+                MIN_WAIT_DAYS, MAX_WAIT_DAYS
+            )));
+        }
+        let label = label.trim();
+        if label.is_empty() {
+            return Err(AppError::InvalidInput(
+                "Emergency contact name must not be empty".to_string(),
+            ));
+        }
+        Ok(Self {
+            id: Uuid::new_v4().to_string(),
+            label: label.to_string(),
+            public_key,
+            wait_days,
+            state: EmergencyState::Active,
+            created_at: now,
+            requested_at: None,
+            state_changed_at: now,
+            wrapped_key: None,
+        })
+    }
+
+    /// When a pending request turns into access without the owner's approval
+    pub fn grants_at(&self) -> Option<DateTime<Utc>> {
+        match self.state {
+            EmergencyState::RecoveryRequested => self
+                .requested_at
+                .map(|at| at + Duration::days(i64::from(self.wait_days))),
+            _ => None,
+        }
+    }
+
+    fn transition(
+        &mut self,
+        from: EmergencyState,
+        to: EmergencyState,
+        now: DateTime<Utc>,
+    ) -> AppResult<()> {
+        if self.state != from {
+            return Err(AppError::InvalidInput(format!(
+                "Emergency access is {}, expected {}",
+                self.state.as_str(),
+                from.as_str()
+            )));
+        }
+        self.state = to;
+        self.state_changed_at = now;
+        Ok(())
+    }
+
+    /// The contact asks for access, starting the waiting period
+    pub fn request_recovery(&mut self, now: DateTime<Utc>) -> AppResult<()> {
+        self.transition(
+            EmergencyState::Active,
+            EmergencyState::RecoveryRequested,
+            now,
+        )?;
+        self.requested_at = Some(now);
+        Ok(())
+    }
+
+    /// The owner grants access before the waiting period ends
+    pub fn approve(&mut self, now: DateTime<Utc>) -> AppResult<()> {
+        self.transition(
+            EmergencyState::RecoveryRequested,
+            EmergencyState::RecoveryGranted,
+            now,
+        )
+    }
+
+    /// The owner vetoes a pending request
+    pub fn reject(&mut self, now: DateTime<Utc>) -> AppResult<()> {
+        self.transition(
+            EmergencyState::RecoveryRequested,
+            EmergencyState::Active,
+            now,
+        )?;
+        self.requested_at = None;
+        Ok(())
+    }
+
+    /// The owner withdraws emergency access for good
+    pub fn revoke(&mut self, now: DateTime<Utc>) {
+        self.state = EmergencyState::Revoked;
+        self.state_changed_at = now;
+        self.wrapped_key = None;
+    }
+
+    /// Grants a pending request whose waiting period has passed.
+    /// Returns whether the state changed.
+    pub fn refresh(&mut self, now: DateTime<Utc>) -> bool {
+        match self.grants_at() {
+            Some(at) if now >= at => {
+                self.state = EmergencyState::RecoveryGranted;
+                self.state_changed_at = at;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The wrapped vault key, available only once access was granted
+    pub fn release(&self) -> AppResult<&WrappedKey> {
+        if self.state != EmergencyState::RecoveryGranted {
+            return Err(AppError::InvalidInput(format!(
+                "Emergency access is {}, expected {}",
+                self.state.as_str(),
+                EmergencyState::RecoveryGranted.as_str()
+            )));
+        }
+        self.wrapped_key
+            .as_ref()
+            .ok_or_else(|| AppError::Other("Emergency key is missing".to_string()))
+    }
+}
@@ -1,5 +1,14 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Serialize;
 use thiserror::Error;
 
+use crate::i18n::Message;
+use crate::password_policy::PolicyViolation;
+use crate::query::QueryError;
+use crate::validation::ValidationError;
+
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("Cryptography error: {0}")]
@@ -17,12 +26,51 @@ pub enum AppError {
     #[error("Vault is locked")]
     VaultLocked,
 
+    #[error("No vault has been created yet")]
+    VaultNotCreated,
+
     #[error("Authentication failed")]
     AuthFailed,
 
+    #[error("Master password required for this item")]
+    ReauthRequired,
+
+    #[error("This operation reads every secret and needs a bulk session")]
+    BulkSessionRequired,
+
+    #[error("The operation was cancelled")]
+    Cancelled,
+
+    #[error("An interrupted operation could not be recovered: {0}")]
+    RecoveryFailed(String),
+
     #[error("Item not found: {0}")]
     NotFound(String),
 
+    #[error("Conflicting change: {0}")]
+    Conflict(String),
+
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("Invalid input: {0}")]
+    Validation(#[from] ValidationError),
+
+    #[error("Invalid search: {0}")]
+    Query(#[from] QueryError),
+
+    #[error("Password refused: {0}")]
+    PasswordPolicy(#[from] PolicyViolation),
+
+    #[error("Export error: {0}")]
+    Export(String),
+
+    #[error("Import error: {0}")]
+    Import(String),
+
+    #[error("Network error: {0}")]
+    Network(String),
+
     #[error("Sync error: {0}")]
     Sync(String),
 
@@ -49,3 +97,103 @@ pub enum CryptoError {
 }
 
 pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    /// The translatable form of this error
+    pub fn message(&self) -> Message {
+        let detail = |key: &str, detail: &dyn std::fmt::Display| {
+            Message::new(key).with_param("detail", detail)
+        };
+        match self {
+            AppError::Crypto(e) => detail("error.crypto", e),
+            AppError::Database(e) => detail("error.database", e),
+            AppError::Serialization(e) => detail("error.serialization", e),
+            AppError::Io(e) => detail("error.io", e),
+            AppError::VaultLocked => Message::new("error.vault_locked"),
+            AppError::VaultNotCreated => Message::new("error.vault_not_created"),
+            AppError::AuthFailed => Message::new("error.auth_failed"),
+            AppError::ReauthRequired => Message::new("error.reauth_required"),
+            AppError::BulkSessionRequired => Message::new("error.bulk_session_required"),
+            AppError::Cancelled => Message::new("error.cancelled"),
+            AppError::RecoveryFailed(d) => detail("error.recovery_failed", d),
+            AppError::NotFound(id) => Message::new("error.not_found").with_param("id", id),
+            AppError::Conflict(d) => detail("error.conflict", d),
+            AppError::InvalidInput(d) => detail("error.invalid_input", d),
+            AppError::Validation(e) => e.message(),
+            AppError::Query(e) => e.message(),
+            AppError::PasswordPolicy(e) => e.message(),
+            AppError::Export(d) => detail("error.export", d),
+            AppError::Import(d) => detail("error.import", d),
+            AppError::Network(d) => detail("error.network", d),
+            AppError::Sync(d) => detail("error.sync", d),
+            AppError::Other(d) => detail("error.other", d),
+        }
+    }
+}
+
+/// Error returned by Tauri commands.
+///
+/// Carries the message key and params for the frontend to translate, the text
+/// rendered in the current locale, and the underlying error if there is one.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandError {
+    pub key: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, String>,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cause: Option<Message>,
+}
+
+impl CommandError {
+    /// A failure described by `key`, caused by `error` (rendered as `{reason}`)
+    pub fn context(key: &str, error: AppError) -> Self {
+        let cause = error.message();
+        let message = Message::new(key).with_param("reason", cause.text());
+        Self {
+            cause: Some(cause),
+            ..Self::from(message)
+        }
+    }
+
+    pub fn vault_not_initialized() -> Self {
+        Message::new("error.vault_not_initialized").into()
+    }
+}
+
+impl From<Message> for CommandError {
+    fn from(message: Message) -> Self {
+        Self {
+            message: message.text(),
+            key: message.key,
+            params: message.params,
+            cause: None,
+        }
+    }
+}
+
+impl From<AppError> for CommandError {
+    fn from(error: AppError) -> Self {
+        error.message().into()
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// Adds command error context to vault results
+pub trait ResultExt<T> {
+    /// Wraps the error as `CommandError::context(key, error)`
+    fn context(self, key: &str) -> Result<T, CommandError>;
+}
+
+impl<T, E: Into<AppError>> ResultExt<T> for Result<T, E> {
+    fn context(self, key: &str) -> Result<T, CommandError> {
+        self.map_err(|e| CommandError::context(key, e.into()))
+    }
+}
@@ -0,0 +1,334 @@
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::models::{Credential, Secret};
+use crate::validation::normalize_tag;
+
+/// Formats supported for migrating away from the app
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// CSV using Bitwarden's import column layout
+    BitwardenCsv,
+    /// Generic JSON document containing every field
+    Json,
+    /// Password-protected KeePass 4.x database
+    Kdbx,
+    /// Password-protected bundle of this app, see `bundle`
+    Bundle,
+}
+
+impl ExportFormat {
+    /// Short label used in audit entries
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::BitwardenCsv => "CSV",
+            ExportFormat::Json => "JSON",
+            ExportFormat::Kdbx => "KDBX",
+            ExportFormat::Bundle => "bundle",
+        }
+    }
+}
+
+/// Which credentials an export starts from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum ExportSelection {
+    /// Every credential in the vault
+    #[default]
+    All,
+    /// Credentials whose folder (first tag) matches
+    Folder(String),
+    /// Credentials carrying the given tag
+    Tag(String),
+}
+
+impl ExportSelection {
+    /// Checks whether a credential falls inside this selection. Tags and
+    /// folders are compared in their stored form, so "Work" selects "work".
+    pub fn matches(&self, credential: &Credential) -> bool {
+        match self {
+            ExportSelection::All => true,
+            ExportSelection::Folder(folder) => {
+                folder_of(credential).is_some_and(|first| same_tag(first, folder))
+            }
+            ExportSelection::Tag(tag) => credential.tags.iter().any(|t| same_tag(t, tag)),
+        }
+    }
+}
+
+/// Which credentials an export should contain: a selection, minus the items
+/// the user chose to keep out of the file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct ExportScope {
+    #[serde(default)]
+    pub selection: ExportSelection,
+    /// Leave out items flagged high-security
+    #[serde(default)]
+    pub exclude_high_security: bool,
+    /// Leave out items carrying any of these tags
+    #[serde(default)]
+    pub exclude_tags: Vec<String>,
+    /// Leave out items in any of these folders (first tag)
+    #[serde(default)]
+    pub exclude_folders: Vec<String>,
+}
+
+impl From<ExportSelection> for ExportScope {
+    fn from(selection: ExportSelection) -> Self {
+        Self {
+            selection,
+            ..Self::default()
+        }
+    }
+}
+
+impl ExportScope {
+    /// Checks whether a credential falls inside this scope
+    pub fn matches(&self, credential: &Credential) -> bool {
+        self.selection.matches(credential) && !self.excludes(credential)
+    }
+
+    /// Whether an item in the selection is left out. Tags and folders are
+    /// compared in their stored form, like `ExportSelection::matches`.
+    pub fn excludes(&self, credential: &Credential) -> bool {
+        (self.exclude_high_security && credential.high_security)
+            || credential.tags.iter().any(|tag| {
+                self.exclude_tags
+                    .iter()
+                    .any(|excluded| same_tag(tag, excluded))
+            })
+            || folder_of(credential).is_some_and(|folder| {
+                self.exclude_folders
+                    .iter()
+                    .any(|excluded| same_tag(folder, excluded))
+            })
+    }
+
+    /// The credentials of `credentials` in this scope, and how many of the
+    /// selected ones the exclusions left out
+    pub fn apply(&self, credentials: Vec<Credential>) -> (Vec<Credential>, usize) {
+        let (selected, _): (Vec<_>, Vec<_>) = credentials
+            .into_iter()
+            .partition(|credential| self.selection.matches(credential));
+        let (excluded, included): (Vec<_>, Vec<_>) = selected
+            .into_iter()
+            .partition(|credential| self.excludes(credential));
+        (included, excluded.len())
+    }
+
+    /// Compact, untranslated description for audit entries, such as
+    /// `tag:work -high_security -folder:Banking`
+    pub fn describe(&self) -> String {
+        let mut parts = vec![match &self.selection {
+            ExportSelection::All => "all".to_string(),
+            ExportSelection::Folder(folder) => format!("folder:{folder}"),
+            ExportSelection::Tag(tag) => format!("tag:{tag}"),
+        }];
+        if self.exclude_high_security {
+            parts.push("-high_security".to_string());
+        }
+        parts.extend(self.exclude_tags.iter().map(|tag| format!("-tag:{tag}")));
+        parts.extend(
+            self.exclude_folders
+                .iter()
+                .map(|folder| format!("-folder:{folder}")),
+        );
+        parts.join(" ")
+    }
+}
+
+/// Summary of a completed export returned to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportReport {
+    /// Where the file was written
+    pub path: String,
+    /// Format of the written file
+    pub format: ExportFormat,
+    /// Number of credentials written
+    pub exported: usize,
+    /// Credentials in the selection that the scope's exclusions left out
+    #[serde(default)]
+    pub excluded: usize,
+    /// Warnings the UI must show to the user
+    pub warnings: Vec<String>,
+}
+
+/// Bitwarden has a single folder per item; the first tag plays that role
+fn folder_of(credential: &Credential) -> Option<&str> {
+    credential.tags.first().map(String::as_str)
+}
+
+/// Whether two tags are the same once normalized; items written before tags
+/// were normalized may still hold other spellings
+fn same_tag(a: &str, b: &str) -> bool {
+    normalize_tag(a) == normalize_tag(b)
+}
+
+/// Generic JSON export document
+#[derive(Serialize)]
+struct JsonExport<'a> {
+    exported_at: DateTime<Utc>,
+    items: Vec<JsonExportItem<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonExportItem<'a> {
+    uuid: &'a str,
+    site: &'a str,
+    username: &'a str,
+    password: &'a str,
+    notes: Option<&'a str>,
+    totp: Option<&'a str>,
+    custom_fields: BTreeMap<&'a str, &'a str>,
+    tags: &'a [String],
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// Renders decrypted credentials into the requested plaintext format
+pub fn render(format: ExportFormat, entries: &[(Credential, Secret)]) -> AppResult<Vec<u8>> {
+    match format {
+        ExportFormat::BitwardenCsv => render_bitwarden_csv(entries),
+        ExportFormat::Json => render_json(entries),
+        ExportFormat::Kdbx => Err(AppError::Export(
+            "KDBX is an encrypted format; use the KDBX export instead".to_string(),
+        )),
+        ExportFormat::Bundle => Err(AppError::Export(
+            "Bundles are encrypted; use the bundle export instead".to_string(),
+        )),
+    }
+}
+
+fn render_json(entries: &[(Credential, Secret)]) -> AppResult<Vec<u8>> {
+    let document = JsonExport {
+        exported_at: Utc::now(),
+        items: entries
+            .iter()
+            .map(|(credential, secret)| JsonExportItem {
+                uuid: &credential.uuid,
+                site: &credential.site,
+                username: &credential.username,
+                password: &secret.password,
+                notes: secret.notes.as_deref(),
+                totp: secret.totp.as_deref(),
+                custom_fields: secret
+                    .custom_fields
+                    .iter()
+                    .map(|(k, v)| (k.as_str(), v.as_str()))
+                    .collect(),
+                tags: &credential.tags,
+                created_at: credential.created_at,
+                updated_at: credential.updated_at,
+                expires_at: credential.expires_at,
+            })
+            .collect(),
+    };
+    serde_json::to_vec_pretty(&document).map_err(AppError::Serialization)
+}
+
+fn render_bitwarden_csv(entries: &[(Credential, Secret)]) -> AppResult<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record([
+            "folder",
+            "favorite",
+            "type",
+            "name",
+            "notes",
+            "fields",
+            "reprompt",
+            "login_uri",
+            "login_username",
+            "login_password",
+            "login_totp",
+        ])
+        .map_err(|e| AppError::Export(format!("Failed to write CSV header: {}", e)))?;
+
+    for (credential, secret) in entries {
+        // Bitwarden expects custom fields as "name: value" lines
+        let fields = secret
+            .custom_fields
+            .iter()
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .map(|(k, v)| format!("{}: {}", k, v))
+            .collect::<Vec<_>>()
+            .join("\n");
+        writer
+            .write_record([
+                folder_of(credential).unwrap_or(""),
+                "",
+                "login",
+                &credential.site,
+                secret.notes.as_deref().unwrap_or(""),
+                &fields,
+                "0",
+                &credential.site,
+                &credential.username,
+                &secret.password,
+                secret.totp.as_deref().unwrap_or(""),
+            ])
+            .map_err(|e| AppError::Export(format!("Failed to write CSV row: {}", e)))?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|e| AppError::Export(format!("Failed to flush CSV: {}", e)))
+}
+
+/// Warnings shown alongside a plaintext export
+pub fn plaintext_warnings(format: ExportFormat, entries: &[(Credential, Secret)]) -> Vec<String> {
+    let mut warnings = vec![
+        "The exported file is NOT encrypted. Anyone with access to it can read every password."
+            .to_string(),
+        "Delete the file securely once the migration is complete.".to_string(),
+    ];
+    if format == ExportFormat::BitwardenCsv {
+        let multi_tagged = entries.iter().filter(|(c, _)| c.tags.len() > 1).count();
+        if multi_tagged > 0 {
+            warnings.push(format!(
+                "{} credentials have more than one tag; only the first is kept as the CSV folder.",
+                multi_tagged
+            ));
+        }
+    }
+    warnings
+}
+
+/// Creates or truncates `path` for writing, readable only by the current user
+/// where supported. The mode is given when the file is created, so what is
+/// written to it is never readable by others, not even for a moment. Used for
+/// every file holding vault contents outside the vault.
+pub fn create_private_file(path: &Path) -> AppResult<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let file = options.open(path)?;
+    // A file that already existed keeps its mode; narrow it before writing
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(file)
+}
+
+/// Writes an export file through `create_private_file`
+pub fn write_export_file(path: &Path, contents: &[u8]) -> AppResult<()> {
+    let mut file = create_private_file(path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    Ok(())
+}
@@ -0,0 +1,113 @@
+//! Per-vault feature flags.
+//!
+//! Features that are risky or unfinished can ship switched off and be turned on
+//! per vault. Flags that differ from their default are kept as an encrypted
+//! metadata entry. Experimental features exist only in builds made with the
+//! `experimental` Cargo feature; elsewhere they are listed as unavailable and
+//! always off.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+/// Metadata key holding the flags changed from their defaults
+pub const FEATURES_META_KEY: &str = "features";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    /// Accept logins captured by the browser extension
+    BrowserBridge,
+    /// Serve SSH keys to `ssh` through an agent socket
+    SshAgent,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 2] = [Feature::BrowserBridge, Feature::SshAgent];
+
+    /// Name used in commands and storage
+    pub fn name(self) -> &'static str {
+        match self {
+            Feature::BrowserBridge => "browser_bridge",
+            Feature::SshAgent => "ssh_agent",
+        }
+    }
+
+    pub fn from_name(name: &str) -> AppResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|feature| feature.name() == name)
+            .ok_or_else(|| AppError::NotFound(format!("feature {name}")))
+    }
+
+    pub fn default_enabled(self) -> bool {
+        match self {
+            Feature::BrowserBridge => true,
+            Feature::SshAgent => false,
+        }
+    }
+
+    pub fn experimental(self) -> bool {
+        matches!(self, Feature::SshAgent)
+    }
+
+    /// Whether this build includes the feature at all
+    pub fn available(self) -> bool {
+        !self.experimental() || cfg!(feature = "experimental")
+    }
+}
+
+/// A feature as shown in the settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureState {
+    pub feature: Feature,
+    pub enabled: bool,
+    pub default_enabled: bool,
+    pub experimental: bool,
+    /// False when the build leaves the feature out; it is then always off
+    pub available: bool,
+}
+
+/// Flags changed from their defaults, by feature name. Names of features this
+/// version does not know are kept, so a newer version's choices survive.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureFlags(BTreeMap<String, bool>);
+
+impl FeatureFlags {
+    pub fn is_enabled(&self, feature: Feature) -> bool {
+        feature.available()
+            && self
+                .0
+                .get(feature.name())
+                .copied()
+                .unwrap_or_else(|| feature.default_enabled())
+    }
+
+    /// Turns `feature` on or off; an unavailable feature can only be turned off
+    pub fn set(&mut self, feature: Feature, enabled: bool) -> AppResult<()> {
+        if enabled && !feature.available() {
+            return Err(AppError::InvalidInput(format!(
+                "The feature {} is not included in this build",
+                feature.name()
+            )));
+        }
+        if enabled == feature.default_enabled() {
+            self.0.remove(feature.name());
+        } else {
+            self.0.insert(feature.name().to_string(), enabled);
+        }
+        Ok(())
+    }
+
+    pub fn state(&self, feature: Feature) -> FeatureState {
+        FeatureState {
+            feature,
+            enabled: self.is_enabled(feature),
+            default_enabled: feature.default_enabled(),
+            experimental: feature.experimental(),
+            available: feature.available(),
+        }
+    }
+}
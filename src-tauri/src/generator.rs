@@ -0,0 +1,225 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+pub const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+pub const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+pub const NUMBERS: &str = "0123456789";
+pub const SYMBOLS: &str = "!@#$%^&*()-_=+[]{}|;:,.<>?";
+/// Similar looking characters
+const SIMILAR: &str = "Il1O0";
+/// Passwords drawn before giving up on one that contains every required class
+const MAX_CONSTRAINED_ATTEMPTS: usize = 1000;
+
+/// Options for random password generation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneratorOptions {
+    pub length: usize,
+    pub use_uppercase: bool,
+    pub use_lowercase: bool,
+    pub use_numbers: bool,
+    pub use_symbols: bool,
+    pub exclude_similar: bool,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        Self {
+            length: 20,
+            use_uppercase: true,
+            use_lowercase: true,
+            use_numbers: true,
+            use_symbols: true,
+            exclude_similar: true,
+        }
+    }
+}
+
+/// A kind of character a site can insist on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CharacterClass {
+    Uppercase,
+    Lowercase,
+    Numbers,
+    Symbols,
+}
+
+impl CharacterClass {
+    fn contains(self, c: char) -> bool {
+        match self {
+            CharacterClass::Uppercase => UPPER.contains(c),
+            CharacterClass::Lowercase => LOWER.contains(c),
+            CharacterClass::Numbers => NUMBERS.contains(c),
+            CharacterClass::Symbols => SYMBOLS.contains(c),
+        }
+    }
+}
+
+/// Password rules of a particular site, stored on its item so that
+/// regenerating the password always produces one the site accepts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneratorConstraints {
+    /// Longest password the site accepts
+    pub max_length: Option<usize>,
+    /// Characters the site rejects, typically some of the symbols
+    pub forbidden_characters: String,
+    /// Classes every password must contain at least one character of
+    pub required_classes: Vec<CharacterClass>,
+}
+
+impl GeneratorConstraints {
+    /// Fails if no password could ever satisfy the constraints
+    pub fn check(&self) -> AppResult<()> {
+        let options = self.apply(&GeneratorOptions::default());
+        if options.length < self.required_classes.len() {
+            return Err(AppError::InvalidInput(format!(
+                "A maximum length of {} cannot fit {} required character types",
+                options.length,
+                self.required_classes.len()
+            )));
+        }
+        self.charset(&options).map(|_| ())
+    }
+
+    /// `options` adjusted to the constraints: capped in length, with every required class enabled
+    fn apply(&self, options: &GeneratorOptions) -> GeneratorOptions {
+        let mut options = options.clone();
+        if let Some(max_length) = self.max_length {
+            options.length = options.length.min(max_length);
+        }
+        for class in &self.required_classes {
+            match class {
+                CharacterClass::Uppercase => options.use_uppercase = true,
+                CharacterClass::Lowercase => options.use_lowercase = true,
+                CharacterClass::Numbers => options.use_numbers = true,
+                CharacterClass::Symbols => options.use_symbols = true,
+            }
+        }
+        options
+    }
+
+    /// Characters to draw from once forbidden ones are removed
+    fn charset(&self, options: &GeneratorOptions) -> AppResult<Vec<char>> {
+        let mut charset = charset(options)?;
+        charset.retain(|c| !self.forbidden_characters.contains(*c));
+        if charset.is_empty() {
+            return Err(AppError::InvalidInput(
+                "Every allowed character is forbidden by the site".to_string(),
+            ));
+        }
+        if let Some(class) = self
+            .required_classes
+            .iter()
+            .find(|class| !charset.iter().any(|c| class.contains(*c)))
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Every {:?} character is forbidden, but the site requires one",
+                class
+            )));
+        }
+        Ok(charset)
+    }
+
+    fn satisfied_by(&self, password: &str) -> bool {
+        self.required_classes
+            .iter()
+            .all(|class| password.chars().any(|c| class.contains(c)))
+    }
+}
+
+/// Generates a password from `options` that also meets a site's `constraints`.
+/// Passwords missing a required class are discarded and drawn again, so every
+/// acceptable password stays equally likely.
+pub fn generate_constrained(
+    options: &GeneratorOptions,
+    constraints: &GeneratorConstraints,
+) -> AppResult<String> {
+    constraints.check()?;
+    let options = constraints.apply(options);
+    if options.length < constraints.required_classes.len().max(1) {
+        return Err(AppError::InvalidInput(
+            "Password length is too short for the required character types".to_string(),
+        ));
+    }
+
+    let charset = constraints.charset(&options)?;
+    let mut rng = rand::thread_rng();
+    for _ in 0..MAX_CONSTRAINED_ATTEMPTS {
+        let password: String = (0..options.length)
+            .map(|_| charset[rng.gen_range(0..charset.len())])
+            .collect();
+        if constraints.satisfied_by(&password) {
+            return Ok(password);
+        }
+    }
+    Err(AppError::InvalidInput(
+        "Could not generate a password meeting the site's requirements".to_string(),
+    ))
+}
+
+/// Entropy of a password from `generate_constrained`, in bits. Discarding
+/// passwords without a required class removes a small share of the space,
+/// so this is a slight overestimate when classes are required.
+pub fn constrained_entropy_bits(
+    options: &GeneratorOptions,
+    constraints: &GeneratorConstraints,
+) -> AppResult<f64> {
+    let options = constraints.apply(options);
+    let charset = constraints.charset(&options)?;
+    Ok(options.length as f64 * (charset.len() as f64).log2())
+}
+
+/// Generates a random password from the selected character sets
+pub fn generate(options: &GeneratorOptions) -> AppResult<String> {
+    if options.length < 1 {
+        return Err(AppError::InvalidInput(
+            "Password length must be at least 1".to_string(),
+        ));
+    }
+
+    let charset = charset(options)?;
+    let mut rng = rand::thread_rng();
+    Ok((0..options.length)
+        .map(|_| charset[rng.gen_range(0..charset.len())])
+        .collect())
+}
+
+/// Exact entropy of a password from `generate`, in bits. Every character is
+/// drawn uniformly from the charset, so this is length * log2(charset size).
+pub fn entropy_bits(options: &GeneratorOptions) -> AppResult<f64> {
+    let charset = charset(options)?;
+    Ok(options.length as f64 * (charset.len() as f64).log2())
+}
+
+/// Characters `generate` draws from
+fn charset(options: &GeneratorOptions) -> AppResult<Vec<char>> {
+    // Build the charset
+    let mut charset = String::new();
+    if options.use_uppercase {
+        charset.push_str(UPPER);
+    }
+    if options.use_lowercase {
+        charset.push_str(LOWER);
+    }
+    if options.use_numbers {
+        charset.push_str(NUMBERS);
+    }
+    if options.use_symbols {
+        charset.push_str(SYMBOLS);
+    }
+    if charset.is_empty() {
+        return Err(AppError::InvalidInput(
+            "At least one character type must be selected".to_string(),
+        ));
+    }
+
+    // Remove similar characters if requested
+    if options.exclude_similar {
+        charset.retain(|c| !SIMILAR.contains(c));
+    }
+
+    Ok(charset.chars().collect())
+}
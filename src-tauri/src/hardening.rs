@@ -0,0 +1,509 @@
+//! Process hardening against reading secrets out of memory.
+//!
+//! On unlock the process turns on what the platform offers, once:
+//!
+//! | Mitigation        | Linux                   | macOS                     | Windows                        |
+//! |-------------------|-------------------------|---------------------------|--------------------------------|
+//! | no core dumps     | `RLIMIT_CORE` 0         | `RLIMIT_CORE` 0           | no heap in error reports       |
+//! | no debugger       | `PR_SET_DUMPABLE` 0     | `PT_DENY_ATTACH`, release | -                              |
+//! | no code injection | -                       | -                         | extension points disabled      |
+//!
+//! The page holding the vault key is also locked into memory (`mlock`,
+//! `VirtualLock`) so it is never written to swap, and zeroed when the vault is
+//! locked. Copies made while the key is derived are not covered. Every step
+//! is best effort: a failure is reported by `get_hardening_status` and never
+//! keeps the vault from opening.
+//!
+//! The ephemeral session worker, see `session`, is confined further by
+//! `sandbox_worker` once it holds the key: on Linux (x86_64 and aarch64) a
+//! seccomp filter allows only the system calls it needs to answer requests on
+//! its pipes. Elsewhere it runs with the process mitigations alone.
+
+use std::sync::atomic::{compiler_fence, Ordering};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Whether a mitigation is in effect
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MitigationState {
+    Active,
+    /// The platform refused, e.g. over the memory lock limit
+    Failed,
+    /// Not available on this platform
+    Unsupported,
+    /// Not applied yet, or nothing to protect, e.g. while the vault is locked
+    #[default]
+    Inactive,
+}
+
+/// What `harden_process` achieved
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessHardening {
+    pub core_dumps_disabled: MitigationState,
+    pub debugger_blocked: MitigationState,
+    pub injection_blocked: MitigationState,
+}
+
+/// Returned by `get_hardening_status`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HardeningStatus {
+    #[serde(flatten)]
+    pub process: ProcessHardening,
+    /// Whether the vault key is kept out of swap. Inactive while locked, and in
+    /// an ephemeral session, where the key is not in this process.
+    pub key_memory_locked: MitigationState,
+}
+
+static PROCESS: OnceLock<ProcessHardening> = OnceLock::new();
+
+/// Applies the process-wide mitigations. Only the first call does anything;
+/// most of them cannot be undone anyway.
+pub fn harden_process() -> ProcessHardening {
+    *PROCESS.get_or_init(|| {
+        let hardening = ProcessHardening {
+            core_dumps_disabled: platform::disable_core_dumps(),
+            debugger_blocked: platform::block_debugger(),
+            injection_blocked: platform::block_injection(),
+        };
+        info!(?hardening, "process hardened");
+        hardening
+    })
+}
+
+/// The mitigations `harden_process` applied, all inactive before it ran
+pub fn process_hardening() -> ProcessHardening {
+    PROCESS.get().copied().unwrap_or_default()
+}
+
+/// Keeps the pages holding `bytes` out of swap until `unlock_memory`
+pub fn lock_memory(bytes: &[u8]) -> MitigationState {
+    let state = platform::lock_memory(bytes);
+    if state == MitigationState::Failed {
+        warn!("could not lock key memory");
+    }
+    state
+}
+
+/// Undoes `lock_memory` for the same bytes
+pub fn unlock_memory(bytes: &[u8]) {
+    platform::unlock_memory(bytes);
+}
+
+/// Confines the session worker to reading and writing the file descriptors it
+/// has, allocating memory and drawing random numbers. Cannot be undone, so
+/// only the worker may call it, after it has everything else it needs.
+pub fn sandbox_worker() -> MitigationState {
+    let state = platform::sandbox_worker();
+    match state {
+        MitigationState::Failed => warn!("could not sandbox the session worker"),
+        _ => info!(?state, "session worker sandboxed"),
+    }
+    state
+}
+
+/// Overwrites `bytes` with zeros in a way the compiler cannot skip because
+/// the memory is about to be freed
+pub fn wipe(bytes: &mut [u8]) {
+    for byte in bytes.iter_mut() {
+        // SAFETY: `byte` is a valid, exclusive reference
+        unsafe { std::ptr::write_volatile(byte, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod posix {
+    use std::ffi::{c_ulong, c_void};
+
+    use super::MitigationState;
+
+    /// The same on Linux and macOS
+    const RLIMIT_CORE: i32 = 4;
+
+    #[repr(C)]
+    struct Rlimit {
+        current: c_ulong,
+        max: c_ulong,
+    }
+
+    extern "C" {
+        fn setrlimit(resource: i32, limit: *const Rlimit) -> i32;
+        fn mlock(address: *const c_void, length: usize) -> i32;
+        fn munlock(address: *const c_void, length: usize) -> i32;
+    }
+
+    fn state(result: i32) -> MitigationState {
+        match result {
+            0 => MitigationState::Active,
+            _ => MitigationState::Failed,
+        }
+    }
+
+    pub fn disable_core_dumps() -> MitigationState {
+        let limit = Rlimit { current: 0, max: 0 };
+        // SAFETY: `limit` is a correctly laid out rlimit that outlives the call
+        state(unsafe { setrlimit(RLIMIT_CORE, &limit) })
+    }
+
+    pub fn lock_memory(bytes: &[u8]) -> MitigationState {
+        // SAFETY: the range is a live borrow; mlock only changes paging
+        state(unsafe { mlock(bytes.as_ptr().cast(), bytes.len()) })
+    }
+
+    pub fn unlock_memory(bytes: &[u8]) {
+        // SAFETY: as for `lock_memory`
+        unsafe { munlock(bytes.as_ptr().cast(), bytes.len()) };
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::MitigationState;
+
+    pub use super::posix::{disable_core_dumps, lock_memory, unlock_memory};
+    pub use super::seccomp::sandbox_worker;
+
+    const PR_SET_DUMPABLE: i32 = 4;
+
+    extern "C" {
+        fn prctl(option: i32, ...) -> i32;
+    }
+
+    /// A process that is not dumpable cannot be attached to, nor its memory
+    /// read through /proc, by other processes of the same user
+    pub fn block_debugger() -> MitigationState {
+        // SAFETY: PR_SET_DUMPABLE takes one integer argument
+        match unsafe { prctl(PR_SET_DUMPABLE, 0 as std::ffi::c_ulong) } {
+            0 => MitigationState::Active,
+            _ => MitigationState::Failed,
+        }
+    }
+
+    pub fn block_injection() -> MitigationState {
+        MitigationState::Unsupported
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod seccomp {
+    use super::MitigationState;
+
+    const PR_SET_NO_NEW_PRIVS: i32 = 38;
+    const PR_SET_SECCOMP: i32 = 22;
+    const SECCOMP_MODE_FILTER: std::ffi::c_ulong = 2;
+
+    const SECCOMP_RET_KILL_PROCESS: u32 = 0x8000_0000;
+    const SECCOMP_RET_ERRNO: u32 = 0x0005_0000;
+    const SECCOMP_RET_ALLOW: u32 = 0x7fff_0000;
+    const EPERM: u32 = 1;
+
+    // Classic BPF opcodes
+    const BPF_LD_W_ABS: u16 = 0x20;
+    const BPF_JEQ_K: u16 = 0x15;
+    const BPF_RET_K: u16 = 0x06;
+
+    // Offsets into `struct seccomp_data`
+    const NR_OFFSET: u32 = 0;
+    const ARCH_OFFSET: u32 = 4;
+
+    #[cfg(target_arch = "x86_64")]
+    const ARCH: Option<u32> = Some(0xc000_003e);
+    #[cfg(target_arch = "aarch64")]
+    const ARCH: Option<u32> = Some(0xc000_00b7);
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    const ARCH: Option<u32> = None;
+
+    /// What the worker needs: its pipes, the allocator, the memory lock,
+    /// random numbers for nonces, and what the runtime uses to exit or abort
+    #[cfg(target_arch = "x86_64")]
+    const ALLOWED: &[u32] = &[
+        0,   // read
+        1,   // write
+        3,   // close
+        9,   // mmap
+        10,  // mprotect
+        11,  // munmap
+        12,  // brk
+        13,  // rt_sigaction
+        14,  // rt_sigprocmask
+        15,  // rt_sigreturn
+        24,  // sched_yield
+        25,  // mremap
+        28,  // madvise
+        39,  // getpid
+        60,  // exit
+        131, // sigaltstack
+        149, // mlock
+        150, // munlock
+        186, // gettid
+        202, // futex
+        228, // clock_gettime
+        231, // exit_group
+        234, // tgkill
+        318, // getrandom
+    ];
+    #[cfg(target_arch = "aarch64")]
+    const ALLOWED: &[u32] = &[
+        57,  // close
+        63,  // read
+        64,  // write
+        93,  // exit
+        94,  // exit_group
+        98,  // futex
+        113, // clock_gettime
+        124, // sched_yield
+        131, // tgkill
+        132, // sigaltstack
+        134, // rt_sigaction
+        135, // rt_sigprocmask
+        139, // rt_sigreturn
+        172, // getpid
+        178, // gettid
+        214, // brk
+        215, // munmap
+        216, // mremap
+        222, // mmap
+        226, // mprotect
+        228, // mlock
+        229, // munlock
+        233, // madvise
+        278, // getrandom
+    ];
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    const ALLOWED: &[u32] = &[];
+
+    #[repr(C)]
+    struct SockFilter {
+        code: u16,
+        jt: u8,
+        jf: u8,
+        k: u32,
+    }
+
+    #[repr(C)]
+    struct SockFprog {
+        len: u16,
+        filter: *const SockFilter,
+    }
+
+    extern "C" {
+        fn prctl(option: i32, ...) -> i32;
+    }
+
+    fn statement(code: u16, k: u32) -> SockFilter {
+        SockFilter {
+            code,
+            jt: 0,
+            jf: 0,
+            k,
+        }
+    }
+
+    /// Skips the next instruction unless the accumulator equals `k`
+    fn jump_unless(k: u32) -> SockFilter {
+        SockFilter {
+            code: BPF_JEQ_K,
+            jt: 0,
+            jf: 1,
+            k,
+        }
+    }
+
+    /// Kills the process on a foreign architecture, where the numbers mean
+    /// other calls, allows the listed calls and fails every other one
+    fn filter(arch: u32) -> Vec<SockFilter> {
+        let mut filter = vec![
+            statement(BPF_LD_W_ABS, ARCH_OFFSET),
+            SockFilter {
+                code: BPF_JEQ_K,
+                jt: 1,
+                jf: 0,
+                k: arch,
+            },
+            statement(BPF_RET_K, SECCOMP_RET_KILL_PROCESS),
+            statement(BPF_LD_W_ABS, NR_OFFSET),
+        ];
+        for &nr in ALLOWED {
+            filter.push(jump_unless(nr));
+            filter.push(statement(BPF_RET_K, SECCOMP_RET_ALLOW));
+        }
+        filter.push(statement(BPF_RET_K, SECCOMP_RET_ERRNO | EPERM));
+        filter
+    }
+
+    pub fn sandbox_worker() -> MitigationState {
+        let Some(arch) = ARCH else {
+            return MitigationState::Unsupported;
+        };
+        let filter = filter(arch);
+        let Ok(len) = u16::try_from(filter.len()) else {
+            return MitigationState::Failed;
+        };
+        let program = SockFprog {
+            len,
+            filter: filter.as_ptr(),
+        };
+        let unused: std::ffi::c_ulong = 0;
+        // SAFETY: PR_SET_NO_NEW_PRIVS takes one integer argument and wants the
+        // rest zero; it is required to install a filter without CAP_SYS_ADMIN
+        if unsafe {
+            prctl(
+                PR_SET_NO_NEW_PRIVS,
+                1 as std::ffi::c_ulong,
+                unused,
+                unused,
+                unused,
+            )
+        } != 0
+        {
+            return MitigationState::Failed;
+        }
+        // SAFETY: `program` points to `len` valid instructions that outlive the
+        // call; the kernel copies them
+        match unsafe {
+            prctl(
+                PR_SET_SECCOMP,
+                SECCOMP_MODE_FILTER,
+                &program as *const SockFprog,
+                unused,
+                unused,
+            )
+        } {
+            0 => MitigationState::Active,
+            _ => MitigationState::Failed,
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::MitigationState;
+
+    pub use super::posix::{disable_core_dumps, lock_memory, unlock_memory};
+
+    #[cfg(not(debug_assertions))]
+    const PT_DENY_ATTACH: i32 = 31;
+
+    #[cfg(not(debug_assertions))]
+    extern "C" {
+        fn ptrace(request: i32, pid: i32, address: *mut std::ffi::c_char, data: i32) -> i32;
+    }
+
+    /// Release builds only, as it also ends a debug session in progress
+    #[cfg(not(debug_assertions))]
+    pub fn block_debugger() -> MitigationState {
+        // SAFETY: PT_DENY_ATTACH takes no address or data
+        match unsafe { ptrace(PT_DENY_ATTACH, 0, std::ptr::null_mut(), 0) } {
+            0 => MitigationState::Active,
+            _ => MitigationState::Failed,
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    pub fn block_debugger() -> MitigationState {
+        MitigationState::Unsupported
+    }
+
+    pub fn block_injection() -> MitigationState {
+        MitigationState::Unsupported
+    }
+
+    pub fn sandbox_worker() -> MitigationState {
+        MitigationState::Unsupported
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::ffi::c_void;
+
+    use super::MitigationState;
+
+    const WER_FAULT_REPORTING_FLAG_NOHEAP: u32 = 1;
+    const PROCESS_EXTENSION_POINT_DISABLE_POLICY: i32 = 6;
+    const DISABLE_EXTENSION_POINTS: u32 = 1;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn WerSetFlags(flags: u32) -> i32;
+        fn SetProcessMitigationPolicy(policy: i32, buffer: *const c_void, length: usize) -> i32;
+        fn VirtualLock(address: *const c_void, size: usize) -> i32;
+        fn VirtualUnlock(address: *const c_void, size: usize) -> i32;
+    }
+
+    /// Error reports of this process leave out its heap, where secrets live
+    pub fn disable_core_dumps() -> MitigationState {
+        // SAFETY: plain flag change
+        match unsafe { WerSetFlags(WER_FAULT_REPORTING_FLAG_NOHEAP) } {
+            hresult if hresult >= 0 => MitigationState::Active,
+            _ => MitigationState::Failed,
+        }
+    }
+
+    pub fn block_debugger() -> MitigationState {
+        MitigationState::Unsupported
+    }
+
+    /// Stops legacy DLL injection such as AppInit_DLLs and window hooks
+    pub fn block_injection() -> MitigationState {
+        let policy = DISABLE_EXTENSION_POINTS;
+        // SAFETY: the policy is a single DWORD of flags, and its size is passed
+        match unsafe {
+            SetProcessMitigationPolicy(
+                PROCESS_EXTENSION_POINT_DISABLE_POLICY,
+                (&policy as *const u32).cast(),
+                std::mem::size_of::<u32>(),
+            )
+        } {
+            0 => MitigationState::Failed,
+            _ => MitigationState::Active,
+        }
+    }
+
+    pub fn lock_memory(bytes: &[u8]) -> MitigationState {
+        // SAFETY: the range is a live borrow; VirtualLock only changes paging
+        match unsafe { VirtualLock(bytes.as_ptr().cast(), bytes.len()) } {
+            0 => MitigationState::Failed,
+            _ => MitigationState::Active,
+        }
+    }
+
+    pub fn unlock_memory(bytes: &[u8]) {
+        // SAFETY: as for `lock_memory`
+        unsafe { VirtualUnlock(bytes.as_ptr().cast(), bytes.len()) };
+    }
+
+    pub fn sandbox_worker() -> MitigationState {
+        MitigationState::Unsupported
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+mod platform {
+    use super::MitigationState;
+
+    pub fn disable_core_dumps() -> MitigationState {
+        MitigationState::Unsupported
+    }
+
+    pub fn block_debugger() -> MitigationState {
+        MitigationState::Unsupported
+    }
+
+    pub fn block_injection() -> MitigationState {
+        MitigationState::Unsupported
+    }
+
+    pub fn lock_memory(_bytes: &[u8]) -> MitigationState {
+        MitigationState::Unsupported
+    }
+
+    pub fn unlock_memory(_bytes: &[u8]) {}
+
+    pub fn sandbox_worker() -> MitigationState {
+        MitigationState::Unsupported
+    }
+}
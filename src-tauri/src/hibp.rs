@@ -1,13 +1,147 @@
+use std::time::Duration;
+
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Certificate, StatusCode};
+use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
+use tracing::{debug, warn};
+
+use crate::error::{AppError, AppResult, CommandError};
+use crate::models::{BreachApiSettings, BreachState, NetFeature};
+use crate::net::{self, classify_service_error, validate_service_url, ClientIdentity, Network};
+
+/// Endpoint used when no mirror is configured
+pub const DEFAULT_API_BASE_URL: &str = "https://api.pwnedpasswords.com";
+
+/// Progress of a batch breach check, emitted after each finished lookup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreachCheckProgress {
+    /// Credentials checked so far
+    pub checked: usize,
+    /// Credentials selected for checking
+    pub total: usize,
+    /// Credentials that turned compromised during this run
+    pub newly_compromised: usize,
+}
+
+/// Result of a batch breach check
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BreachCheckSummary {
+    /// Credentials selected for checking
+    pub total: usize,
+    /// Credentials whose state was updated
+    pub checked: usize,
+    /// Distinct passwords looked up (identical passwords share one request)
+    pub unique_passwords: usize,
+    /// Credentials found in the breach database
+    pub compromised: usize,
+    /// Credentials that were not compromised before this run
+    pub newly_compromised: usize,
+    /// Credentials whose lookup failed
+    pub failed: usize,
+    /// Credentials that could not be checked because the API was unreachable
+    pub offline: usize,
+    /// Whether the run was cancelled before finishing
+    pub cancelled: bool,
+}
+
+/// Outcome of a single breach lookup as reported to the UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreachCheckResult {
+    /// The lookup completed and the credential's state was updated
+    Checked(BreachState),
+    /// The API could not be reached; the stored state is left untouched
+    Offline,
+}
+
+impl BreachCheckResult {
+    /// Maps a lookup result to what the UI shows, treating network failures as offline
+    pub fn from_lookup(result: AppResult<BreachState>) -> AppResult<Self> {
+        match result {
+            Ok(state) => Ok(Self::Checked(state)),
+            Err(AppError::Network(_)) => Ok(Self::Offline),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Payload of the `breach-precheck-complete` event sent after a credential is saved
+#[derive(Debug, Clone, Serialize)]
+pub struct BreachPrecheck {
+    pub uuid: String,
+    /// None if the check failed; see `error`
+    pub result: Option<BreachCheckResult>,
+    pub error: Option<CommandError>,
+}
+
+/// How rate-limited (429) and server-error (5xx) responses are retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for each further retry
+    pub base_delay: Duration,
+    /// Upper bound for any single delay, including a server-sent Retry-After
+    pub max_delay: Duration,
+}
 
-use crate::error::{AppError, AppResult};
-use crate::models::BreachState;
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `attempt` (0-based), preferring the server's Retry-After
+    pub fn delay_for(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let backoff = self.base_delay.saturating_mul(2u32.saturating_pow(attempt));
+        retry_after.unwrap_or(backoff).min(self.max_delay)
+    }
+}
+
+/// Whether a response status is worth retrying
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Parses a Retry-After header given in seconds (HTTP dates fall back to backoff)
+fn parse_retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Validates a breach API base URL and returns it without a trailing slash.
+/// Plain HTTP is only accepted for loopback hosts.
+pub fn validate_api_base_url(url: &str) -> AppResult<String> {
+    validate_service_url(url, "Breach API")
+}
+
+fn read_pem(path: &str, what: &str) -> AppResult<Vec<u8>> {
+    std::fs::read(path)
+        .map_err(|e| AppError::Other(format!("Failed to read {} {}: {}", what, path, e)))
+}
 
 /// Service to check passwords against the HIBP API
 #[derive(Clone)]
 pub struct HibpService {
     api_base_url: String,
-    user_agent: String,
+    retry_policy: RetryPolicy,
+    root_certificates: Vec<Certificate>,
+    identity: Option<ClientIdentity>,
+    pinned_certificates: Vec<[u8; 32]>,
+    network: Network,
 }
 
 impl Default for HibpService {
@@ -20,13 +154,116 @@ impl HibpService {
     /// Creates a new HibpService
     pub fn new() -> Self {
         Self {
-            api_base_url: "https://api.pwnedpasswords.com".to_string(),
-            user_agent: format!("SecretPlanApp/{}", env!("CARGO_PKG_VERSION")),
+            api_base_url: DEFAULT_API_BASE_URL.to_string(),
+            retry_policy: RetryPolicy::default(),
+            root_certificates: Vec::new(),
+            identity: None,
+            pinned_certificates: Vec::new(),
+            network: Network::default(),
         }
     }
 
+    /// Points the service at a different Pwned Passwords compatible endpoint
+    pub fn with_api_base_url(mut self, api_base_url: impl Into<String>) -> Self {
+        self.api_base_url = api_base_url.into();
+        self
+    }
+
+    /// Replaces the retry policy used for rate-limited and failing requests
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Trusts an additional CA, e.g. the internal CA of a self-hosted mirror
+    pub fn with_root_certificate(mut self, certificate: Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    /// Presents a client certificate to mirrors that require mutual TLS
+    pub fn with_identity(mut self, identity: ClientIdentity) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    /// Only accepts servers whose certificate carries a public key with this
+    /// SHA-256 fingerprint, see `net::parse_pin`. Pinned servers are trusted
+    /// by their key alone; root certificates no longer apply.
+    pub fn with_pinned_certificate(mut self, fingerprint: [u8; 32]) -> Self {
+        self.pinned_certificates.push(fingerprint);
+        self
+    }
+
+    /// Sends requests through the app's proxy and offline settings
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Applies the user's breach API settings, validating the URL and loading
+    /// any certificate files. Unset fields keep the service's current values.
+    pub fn configured(mut self, settings: &BreachApiSettings) -> AppResult<Self> {
+        if let Some(url) = &settings.base_url {
+            self.api_base_url = validate_api_base_url(url)?;
+        }
+        if let Some(path) = &settings.ca_cert_path {
+            let certificate = Certificate::from_pem(&read_pem(path, "CA certificate")?)
+                .map_err(|e| AppError::Other(format!("Invalid CA certificate: {}", e)))?;
+            self = self.with_root_certificate(certificate);
+        }
+        match (&settings.client_cert_path, &settings.client_key_path) {
+            (Some(cert_path), Some(key_path)) => {
+                let identity = ClientIdentity::from_pem(
+                    read_pem(cert_path, "client certificate")?,
+                    read_pem(key_path, "client key")?,
+                )?;
+                self = self.with_identity(identity);
+            }
+            (None, None) => {}
+            _ => {
+                return Err(AppError::Other(
+                    "Client certificate and key must be configured together".to_string(),
+                ))
+            }
+        }
+        for pin in &settings.pinned_certificates {
+            self = self.with_pinned_certificate(net::parse_pin(pin)?);
+        }
+        Ok(self)
+    }
+
+    /// The base URL lookups are sent to
+    pub fn api_base_url(&self) -> &str {
+        &self.api_base_url
+    }
+
+    fn build_client(&self) -> AppResult<reqwest::Client> {
+        let mut builder = self
+            .network
+            .feature_client_builder(NetFeature::Hibp, "HIBP API")?;
+        if !self.pinned_certificates.is_empty() {
+            // Without TLS there is no certificate to check the pins against
+            if !self.api_base_url.starts_with("https://") {
+                return Err(AppError::Other(
+                    "A pinned breach API must use https".to_string(),
+                ));
+            }
+            let tls = net::pinned_tls_config(&self.pinned_certificates, self.identity.as_ref())?;
+            return net::build_client(builder.use_preconfigured_tls(tls));
+        }
+        for certificate in &self.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+        if let Some(identity) = &self.identity {
+            builder = builder.identity(identity.native()?);
+        }
+        net::build_client(builder)
+    }
+
     /// Checks if a password has been exposed in data breaches
-    /// Uses the k-anonymity model: only the first 5 chars of the hash are sent to the API
+    /// Uses the k-anonymity model: only the first 5 chars of the hash are sent to the API.
+    /// Fails with `AppError::Network` if the API cannot be reached at all.
     pub async fn check_password(&self, password_hash: &str) -> AppResult<BreachState> {
         if password_hash.len() != 40 {
             return Err(AppError::Other("Invalid SHA-1 hash length".to_string()));
@@ -39,21 +276,36 @@ impl HibpService {
         // Build the request URL
         let url = format!("{}/range/{}", self.api_base_url, prefix);
 
-        // Create HTTP client with a timeout
-        let client = reqwest::ClientBuilder::new()
-            .timeout(std::time::Duration::from_secs(10))
-            .build()
-            .map_err(|e| AppError::Other(format!("Failed to create HTTP client: {}", e)))?;
-
-        // Send the request
-        let response = client
-            .get(&url)
-            .header("User-Agent", &self.user_agent)
-            .send()
-            .await
-            .map_err(|e| AppError::Other(format!("Failed to send request to HIBP API: {}", e)))?;
+        // Create HTTP client with a timeout and any custom TLS material
+        let client = self.build_client()?;
 
-        // Check if the request was successful
+        // Send the request, backing off on 429/5xx
+        let mut attempt = 0;
+        let response = loop {
+            let response = client
+                .get(&url)
+                .send()
+                .await
+                .map_err(classify_request_error)?;
+            if !is_retryable(response.status()) || attempt >= self.retry_policy.max_retries {
+                break response;
+            }
+            let delay = self
+                .retry_policy
+                .delay_for(attempt, parse_retry_after(&response));
+            warn!(
+                status = %response.status(),
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                "HIBP lookup will be retried"
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        };
+
+        // Check if the request was successful; the hash prefix is not logged,
+        // since together with the time it narrows down which password was checked
+        debug!(status = %response.status(), attempts = attempt + 1, "HIBP lookup finished");
         if !response.status().is_success() {
             return Err(AppError::Other(format!(
                 "HIBP API returned error: {} - {}",
@@ -106,3 +358,8 @@ impl HibpService {
         Ok(BreachState::Safe)
     }
 }
+
+/// Transport-level failures (DNS, refused connection, timeout) mean we are offline
+fn classify_request_error(error: reqwest::Error) -> AppError {
+    classify_service_error(error, "HIBP API")
+}
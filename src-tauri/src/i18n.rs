@@ -0,0 +1,1515 @@
+//! Key-based localization for strings produced by the backend.
+//!
+//! Every user-facing string has a key (e.g. `error.vault_locked`) and a template
+//! per locale with `{name}` placeholders. Commands return the key and params
+//! alongside the rendered text, so the frontend can either show the text as is
+//! or translate the key itself. Missing translations fall back to English, and
+//! unknown keys render as the key.
+//!
+//! Audit log actions are stored as rendered English; `parse_audit_action` maps
+//! them back to a key and params so old and new entries can be translated alike.
+
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::AuditLogEntry;
+
+/// Languages the backend has catalogs for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "en")]
+    En,
+    #[serde(rename = "zh-TW")]
+    ZhTw,
+}
+
+impl Locale {
+    /// Parses a BCP 47 style tag, matching on language and script/region
+    pub fn parse(tag: &str) -> Option<Self> {
+        let normalized = tag.trim().replace('_', "-").to_lowercase();
+        let mut parts = normalized.split('-');
+        match (parts.next(), parts.next()) {
+            (Some("en"), _) => Some(Self::En),
+            (Some("zh"), Some("tw" | "hant" | "hk" | "mo")) => Some(Self::ZhTw),
+            _ => None,
+        }
+    }
+
+    /// The canonical tag for this locale
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::ZhTw => "zh-TW",
+        }
+    }
+
+    /// Keys and templates of this locale
+    pub fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::En => EN,
+            Self::ZhTw => ZH_TW,
+        }
+    }
+}
+
+// The locale is process-wide: it is needed before the vault is unlocked and
+// when converting errors, where no app state is at hand
+static CURRENT_LOCALE: RwLock<Locale> = RwLock::new(Locale::En);
+
+/// The locale backend strings are currently rendered in
+pub fn current_locale() -> Locale {
+    *CURRENT_LOCALE.read().unwrap()
+}
+
+/// Changes the locale backend strings are rendered in
+pub fn set_locale(locale: Locale) {
+    *CURRENT_LOCALE.write().unwrap() = locale;
+}
+
+/// A translatable message: a catalog key plus named parameters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Message {
+    pub key: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, String>,
+}
+
+impl Message {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            params: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a parameter substituted for `{name}` in the template
+    pub fn with_param(mut self, name: &str, value: impl ToString) -> Self {
+        self.params.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Renders the message in the given locale
+    pub fn render(&self, locale: Locale) -> String {
+        match template(locale, &self.key) {
+            Some(template) => fill(template, &self.params),
+            None => self.key.clone(),
+        }
+    }
+
+    /// Renders the message in the current locale
+    pub fn text(&self) -> String {
+        self.render(current_locale())
+    }
+
+    /// Renders an audit action for storage; always English so it can be parsed back
+    pub fn audit_text(&self) -> String {
+        self.render(Locale::En)
+    }
+}
+
+/// Looks up a template, falling back to English
+pub fn template(locale: Locale, key: &str) -> Option<&'static str> {
+    let find = |catalog: &'static [(&'static str, &'static str)]| {
+        catalog.iter().find(|(k, _)| *k == key).map(|(_, t)| *t)
+    };
+    find(locale.catalog()).or_else(|| find(EN))
+}
+
+fn fill(template: &str, params: &BTreeMap<String, String>) -> String {
+    let mut out = template.to_string();
+    for (name, value) in params {
+        out = out.replace(&format!("{{{}}}", name), value);
+    }
+    out
+}
+
+/// Matches `text` against a template, returning the captured params
+fn match_template(template: &str, text: &str) -> Option<BTreeMap<String, String>> {
+    let mut params = BTreeMap::new();
+    let mut rest = text;
+    let mut remaining = template;
+    while !remaining.is_empty() {
+        match remaining.find('{') {
+            // Literal up to the next placeholder (or the end) must match exactly
+            Some(0) => {
+                let close = remaining.find('}')?;
+                let name = &remaining[1..close];
+                remaining = &remaining[close + 1..];
+                let next_literal = remaining.split('{').next().unwrap_or("");
+                let end = if next_literal.is_empty() {
+                    rest.len()
+                } else {
+                    rest.find(next_literal)?
+                };
+                params.insert(name.to_string(), rest[..end].to_string());
+                rest = &rest[end..];
+            }
+            Some(start) => {
+                rest = rest.strip_prefix(&remaining[..start])?;
+                remaining = &remaining[start..];
+            }
+            None => {
+                rest = rest.strip_prefix(remaining)?;
+                remaining = "";
+            }
+        }
+    }
+    rest.is_empty().then_some(params)
+}
+
+/// Maps a stored (English) audit action back to its message
+pub fn parse_audit_action(action: &str) -> Message {
+    EN.iter()
+        .filter(|(key, _)| key.starts_with("audit."))
+        .find_map(|(key, template)| {
+            match_template(template, action).map(|params| Message {
+                key: key.to_string(),
+                params,
+            })
+        })
+        .unwrap_or_else(|| Message::new("audit.other").with_param("action", action))
+}
+
+/// An audit log entry with its action translated into the current locale
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalizedAuditEntry {
+    #[serde(flatten)]
+    pub entry: AuditLogEntry,
+    pub message: Message,
+    pub text: String,
+}
+
+impl From<AuditLogEntry> for LocalizedAuditEntry {
+    fn from(entry: AuditLogEntry) -> Self {
+        let message = parse_audit_action(&entry.action);
+        let text = message.text();
+        Self {
+            entry,
+            message,
+            text,
+        }
+    }
+}
+
+const EN: &[(&str, &str)] = &[
+    // Errors raised by the vault
+    ("error.crypto", "Cryptography error: {detail}"),
+    ("error.database", "Database error: {detail}"),
+    ("error.serialization", "Serialization error: {detail}"),
+    ("error.io", "IO error: {detail}"),
+    ("error.vault_locked", "Vault is locked"),
+    ("error.vault_not_created", "No vault has been created yet"),
+    ("error.auth_failed", "Authentication failed"),
+    (
+        "error.reauth_required",
+        "Enter the master password to view this item",
+    ),
+    (
+        "error.bulk_session_required",
+        "This reads every password; confirm with the master password first",
+    ),
+    ("error.cancelled", "The operation was cancelled"),
+    (
+        "error.recovery_failed",
+        "An interrupted operation could not be recovered: {detail}",
+    ),
+    ("error.not_found", "Item not found: {id}"),
+    ("error.conflict", "The item was changed elsewhere: {detail}"),
+    ("error.invalid_input", "Invalid input: {detail}"),
+    (
+        "error.password_too_weak",
+        "The password's strength is {score} of 4; at least {min_score} is required",
+    ),
+    (
+        "error.password_compromised",
+        "The password is on the breach list {sources}",
+    ),
+    ("validation.empty", "{field} must not be empty"),
+    ("validation.too_long", "{field} is longer than {max} bytes"),
+    ("validation.too_many", "{field} has more than {max} entries"),
+    (
+        "validation.control_characters",
+        "{field} contains control characters",
+    ),
+    (
+        "validation.invalid_encoding",
+        "{field} contains text that was not valid UTF-8",
+    ),
+    (
+        "validation.invalid_tag_character",
+        "{field} contains '{character}', which tags cannot use",
+    ),
+    (
+        "query.unclosed_quote",
+        "Search: a quote is not closed (column {column})",
+    ),
+    (
+        "query.missing_value",
+        "Search: {field}: needs a value (column {column})",
+    ),
+    (
+        "query.invalid_strength",
+        "Search: strength needs a number from 0 to 100, optionally after <, <=, > or >=, not '{value}' (column {column})",
+    ),
+    (
+        "query.invalid_breach",
+        "Search: breach must be compromised, safe or unknown, not '{value}' (column {column})",
+    ),
+    (
+        "query.invalid_date",
+        "Search: updated needs a date like 2024-01-31, optionally after <, <=, > or >=, not '{value}' (column {column})",
+    ),
+    (
+        "query.repeated",
+        "Search: {field}: is given more than once (column {column})",
+    ),
+    ("error.export", "Export error: {detail}"),
+    ("error.import", "Import error: {detail}"),
+    ("error.network", "Network error: {detail}"),
+    ("error.sync", "Sync error: {detail}"),
+    ("error.other", "Other error: {detail}"),
+    // Command failures; {reason} is the rendered underlying error
+    ("error.vault_not_initialized", "Vault not initialized"),
+    ("error.open_database", "Failed to open DB: {reason}"),
+    (
+        "error.data_location",
+        "Failed to open the data folder: {reason}",
+    ),
+    (
+        "error.initialize_vault",
+        "Failed to initialize vault: {reason}",
+    ),
+    ("error.create_vault", "Failed to create vault: {reason}"),
+    ("error.unlock_vault", "Error unlocking vault: {reason}"),
+    ("error.lock_vault", "Failed to lock vault: {reason}"),
+    ("error.panic_lock", "Failed to panic lock: {reason}"),
+    (
+        "error.master_password_reminder",
+        "Failed to check the master password: {reason}",
+    ),
+    (
+        "error.invalid_custom_fields",
+        "Invalid custom fields format: {reason}",
+    ),
+    ("error.add_credential", "Failed to add credential: {reason}"),
+    ("error.get_credential", "Failed to get credential: {reason}"),
+    ("error.decrypt_secret", "Failed to decrypt secret: {reason}"),
+    (
+        "error.set_high_security",
+        "Failed to change item protection: {reason}",
+    ),
+    ("error.validate_totp", "Failed to check TOTP code: {reason}"),
+    (
+        "error.generate_password",
+        "Failed to generate password: {reason}",
+    ),
+    (
+        "error.set_generator_constraints",
+        "Failed to save the site's password rules: {reason}",
+    ),
+    (
+        "error.delete_credential",
+        "Failed to delete credential: {reason}",
+    ),
+    ("error.bulk_edit", "Failed to edit items: {reason}"),
+    ("error.rename_tag", "Failed to rename tag: {reason}"),
+    ("error.undo", "Failed to undo: {reason}"),
+    ("error.redo", "Failed to redo: {reason}"),
+    (
+        "error.get_undo_status",
+        "Failed to get undo history: {reason}",
+    ),
+    (
+        "error.search_credentials",
+        "Failed to search credentials: {reason}",
+    ),
+    ("error.quick_search", "Quick search failed: {reason}"),
+    ("error.suggest_tags", "Could not suggest tags: {reason}"),
+    ("error.add_api_key", "Failed to add API key: {reason}"),
+    ("error.update_api_key", "Failed to update API key: {reason}"),
+    (
+        "error.check_card_number",
+        "Failed to check the card number: {reason}",
+    ),
+    ("error.add_card", "Failed to add card: {reason}"),
+    ("error.update_card", "Failed to update card: {reason}"),
+    (
+        "error.breach_check_card",
+        "Card numbers are not checked against breached passwords",
+    ),
+    ("error.add_identity", "Failed to add identity: {reason}"),
+    (
+        "error.update_identity",
+        "Failed to update identity: {reason}",
+    ),
+    (
+        "error.export_autofill_profile",
+        "Failed to export autofill profile: {reason}",
+    ),
+    (
+        "error.breach_check_identity",
+        "Identities have no password to check against breaches",
+    ),
+    ("error.add_wifi", "Failed to add Wi-Fi network: {reason}"),
+    (
+        "error.update_wifi",
+        "Failed to update Wi-Fi network: {reason}",
+    ),
+    (
+        "error.generate_wifi_qr",
+        "Failed to create QR code: {reason}",
+    ),
+    (
+        "error.export_credential_qr",
+        "Failed to create transfer code: {reason}",
+    ),
+    (
+        "error.import_credential_qr",
+        "Failed to import transfer code: {reason}",
+    ),
+    (
+        "error.set_recovery_codes",
+        "Failed to save recovery codes: {reason}",
+    ),
+    (
+        "error.mark_recovery_code_used",
+        "Failed to mark recovery code as used: {reason}",
+    ),
+    (
+        "error.import_breach_list",
+        "Failed to import breach list: {reason}",
+    ),
+    (
+        "error.list_breach_lists",
+        "Failed to load breach lists: {reason}",
+    ),
+    (
+        "error.remove_breach_list",
+        "Failed to remove breach list: {reason}",
+    ),
+    (
+        "error.check_local_breaches",
+        "Failed to check imported breach lists: {reason}",
+    ),
+    (
+        "error.begin_bulk_session",
+        "Failed to start bulk session: {reason}",
+    ),
+    (
+        "error.end_bulk_session",
+        "Failed to end bulk session: {reason}",
+    ),
+    (
+        "error.get_expiring_credentials",
+        "Failed to list expiring items: {reason}",
+    ),
+    (
+        "error.accept_deep_link",
+        "Failed to open the link: {reason}",
+    ),
+    (
+        "error.reject_deep_link",
+        "Failed to dismiss the link: {reason}",
+    ),
+    (
+        "error.find_credentials_for_url",
+        "Failed to find credentials for this page: {reason}",
+    ),
+    (
+        "error.list_pending_saves",
+        "Failed to list logins waiting to be saved: {reason}",
+    ),
+    (
+        "error.approve_pending_save",
+        "Failed to save the captured login: {reason}",
+    ),
+    (
+        "error.discard_pending_save",
+        "Failed to discard the captured login: {reason}",
+    ),
+    (
+        "error.list_credentials",
+        "Failed to list credentials: {reason}",
+    ),
+    (
+        "error.check_breach",
+        "Failed to check password breach: {reason}",
+    ),
+    (
+        "error.update_breach_state",
+        "Failed to update breach state: {reason}",
+    ),
+    ("error.get_settings", "Failed to get app settings: {reason}"),
+    ("error.get_member_key", "Failed to get member key: {reason}"),
+    (
+        "error.list_collections",
+        "Failed to list collections: {reason}",
+    ),
+    (
+        "error.create_collection",
+        "Failed to create collection: {reason}",
+    ),
+    (
+        "error.add_collection_member",
+        "Failed to add collection member: {reason}",
+    ),
+    (
+        "error.remove_collection_member",
+        "Failed to remove collection member: {reason}",
+    ),
+    (
+        "error.move_to_collection",
+        "Failed to move credential: {reason}",
+    ),
+    (
+        "error.delete_collection",
+        "Failed to delete collection: {reason}",
+    ),
+    ("error.create_share", "Failed to create share: {reason}"),
+    ("error.list_shares", "Failed to list shares: {reason}"),
+    ("error.revoke_share", "Failed to revoke share: {reason}"),
+    (
+        "error.add_emergency_contact",
+        "Failed to add emergency contact: {reason}",
+    ),
+    (
+        "error.list_emergency_contacts",
+        "Failed to list emergency contacts: {reason}",
+    ),
+    (
+        "error.request_emergency_access",
+        "Failed to request emergency access: {reason}",
+    ),
+    (
+        "error.approve_emergency_access",
+        "Failed to approve emergency access: {reason}",
+    ),
+    (
+        "error.reject_emergency_access",
+        "Failed to reject emergency access: {reason}",
+    ),
+    (
+        "error.revoke_emergency_contact",
+        "Failed to revoke emergency contact: {reason}",
+    ),
+    ("error.list_devices", "Failed to list devices: {reason}"),
+    ("error.revoke_device", "Failed to revoke device: {reason}"),
+    (
+        "error.release_emergency_key",
+        "Failed to release emergency key: {reason}",
+    ),
+    (
+        "error.enable_travel_mode",
+        "Failed to enable travel mode: {reason}",
+    ),
+    (
+        "error.disable_travel_mode",
+        "Failed to disable travel mode: {reason}",
+    ),
+    (
+        "error.get_travel_mode",
+        "Failed to get travel mode status: {reason}",
+    ),
+    (
+        "error.share_relay_not_configured",
+        "No share relay is configured; choose a file instead",
+    ),
+    ("error.invalid_share_relay", "Invalid share relay: {reason}"),
+    (
+        "error.invalid_update_manifest",
+        "Invalid update manifest URL: {reason}",
+    ),
+    (
+        "error.check_for_updates",
+        "Failed to check for updates: {reason}",
+    ),
+    (
+        "error.download_update",
+        "Failed to download update: {reason}",
+    ),
+    (
+        "error.invalid_network_settings",
+        "Invalid network settings: {reason}",
+    ),
+    (
+        "error.export_audit_log",
+        "Failed to export audit log: {reason}",
+    ),
+    ("error.export_settings_profile", "Failed to export settings profile: {reason}"),
+    ("error.import_settings_profile", "Failed to import settings profile: {reason}"),
+    (
+        "error.verify_audit_export",
+        "Failed to verify audit export: {reason}",
+    ),
+    (
+        "error.audit_public_key",
+        "Failed to read the audit signing key: {reason}",
+    ),
+    (
+        "error.get_security_trend",
+        "Failed to get security trend: {reason}",
+    ),
+    (
+        "error.get_vault_statistics",
+        "Failed to get vault statistics: {reason}",
+    ),
+    (
+        "error.get_vault_status",
+        "Failed to get vault status: {reason}",
+    ),
+    ("error.list_features", "Failed to list features: {reason}"),
+    (
+        "error.set_feature",
+        "Failed to change the feature: {reason}",
+    ),
+    ("error.list_views", "Failed to list views: {reason}"),
+    ("error.list_notifications", "Failed to list notifications: {reason}"),
+    ("error.clear_notifications", "Failed to clear notifications: {reason}"),
+    ("error.mark_notifications_read", "Failed to mark notifications as read: {reason}"),
+    ("error.get_app_status", "Failed to get app status: {reason}"),
+    ("error.save_view", "Failed to save the view: {reason}"),
+    ("error.delete_view", "Failed to delete the view: {reason}"),
+    (
+        "error.list_credentials_for_view",
+        "Failed to list the view's items: {reason}",
+    ),
+    (
+        "error.get_ui_preferences",
+        "Failed to get UI preferences: {reason}",
+    ),
+    (
+        "error.save_ui_preferences",
+        "Failed to save UI preferences: {reason}",
+    ),
+    ("error.get_wordlists", "Failed to get word lists: {reason}"),
+    ("error.set_wordlists", "Failed to save word lists: {reason}"),
+    (
+        "error.recalculate_strengths",
+        "Failed to recalculate password strengths: {reason}",
+    ),
+    (
+        "error.get_storage_profile",
+        "Failed to get storage profile: {reason}",
+    ),
+    (
+        "error.set_storage_profile",
+        "Failed to change storage: {reason}",
+    ),
+    (
+        "error.save_settings",
+        "Failed to save app settings: {reason}",
+    ),
+    (
+        "error.set_offline_mode",
+        "Failed to save offline mode: {reason}",
+    ),
+    (
+        "error.invalid_breach_api",
+        "Invalid breach API settings: {reason}",
+    ),
+    (
+        "error.master_password_incorrect",
+        "Master password is incorrect",
+    ),
+    (
+        "error.export_credentials",
+        "Failed to export credentials: {reason}",
+    ),
+    (
+        "error.export_kdbx",
+        "Failed to export KDBX database: {reason}",
+    ),
+    ("error.export_bundle", "Failed to export bundle: {reason}"),
+    ("error.preview_import", "Failed to preview import: {reason}"),
+    (
+        "error.import_credentials",
+        "Failed to import credentials: {reason}",
+    ),
+    (
+        "error.list_import_conflicts",
+        "Failed to list import conflicts: {reason}",
+    ),
+    (
+        "error.resolve_import_conflict",
+        "Failed to resolve import conflict: {reason}",
+    ),
+    (
+        "error.scan_pasted_secrets",
+        "Failed to scan the pasted text: {reason}",
+    ),
+    (
+        "error.save_pasted_secrets",
+        "Failed to save the secrets: {reason}",
+    ),
+    ("error.no_backup_folder", "No backup folder configured"),
+    ("error.backup", "Failed to back up vault: {reason}"),
+    ("error.restore_backup", "Failed to restore backup: {reason}"),
+    ("error.compact_vault", "Failed to compact vault: {reason}"),
+    ("error.run_maintenance", "Maintenance failed: {reason}"),
+    (
+        "error.create_diagnostics_bundle",
+        "Failed to create diagnostics bundle: {reason}",
+    ),
+    (
+        "error.verify_no_nonce_reuse",
+        "Failed to check for nonce reuse: {reason}",
+    ),
+    (
+        "error.get_domain_info",
+        "Could not look up the site: {reason}",
+    ),
+    (
+        "error.remediation_queue",
+        "Failed to build remediation queue: {reason}",
+    ),
+    (
+        "error.apply_remediation",
+        "Failed to apply remediation: {reason}",
+    ),
+    ("error.rotation", "Password rotation failed: {reason}"),
+    ("error.autotype", "Auto-type failed: {reason}"),
+    (
+        "error.autotype_no_window",
+        "No focused window to auto-type into",
+    ),
+    (
+        "error.autotype_expired",
+        "This auto-type request is no longer waiting",
+    ),
+    (
+        "error.autotype_window_changed",
+        "The focused window changed; auto-type was not performed",
+    ),
+    (
+        "error.set_autotype_sequence",
+        "Failed to set auto-type sequence: {reason}",
+    ),
+    (
+        "error.check_monitored_emails",
+        "Failed to check monitored email addresses: {reason}",
+    ),
+    (
+        "error.list_breach_notifications",
+        "Failed to list breach notifications: {reason}",
+    ),
+    (
+        "error.dismiss_breach_notification",
+        "Failed to dismiss breach notification: {reason}",
+    ),
+    (
+        "error.invalid_email_monitor",
+        "Invalid email monitoring settings: {reason}",
+    ),
+    (
+        "error.recheck_after_update",
+        "Password updated but breach check failed: {reason}",
+    ),
+    ("error.get_audit_log", "Failed to get audit log: {reason}"),
+    ("error.archive_audit_log", "Failed to archive the audit log: {reason}"),
+    ("error.search_audit_archives", "Failed to search the audit archives: {reason}"),
+    ("error.unsupported_locale", "Unsupported locale: {locale}"),
+    // Audit log actions, stored in English
+    ("audit.vault_unlocked", "Vault unlocked"),
+    ("audit.vault_created", "Vault created"),
+    ("audit.feature_enabled", "Feature {feature} turned on"),
+    ("audit.feature_disabled", "Feature {feature} turned off"),
+    ("audit.credentials_listed", "Listed {count} credentials"),
+    (
+        "audit.credentials_searched",
+        "Searched credentials for \"{term}\" ({count} found)",
+    ),
+    ("audit.vault_locked", "Vault locked"),
+    ("audit.panic_lock", "Panic lock triggered"),
+    ("audit.settings_updated", "Updated app settings"),
+    (
+        "audit.storage_profile_changed",
+        "Switched credential storage to {backend}",
+    ),
+    (
+        "audit.wordlists_changed",
+        "Updated word lists: {banned} banned passwords, {words} dictionary words",
+    ),
+    (
+        "audit.strengths_recalculated",
+        "Recalculated password strengths: {changed} of {total} changed",
+    ),
+    (
+        "audit.integrity_damaged",
+        "Integrity check found {damaged} of {checked} sampled items that no longer decrypt",
+    ),
+    ("audit.credential_added", "Added credential for {site}"),
+    ("audit.credential_updated", "Updated credential for {site}"),
+    ("audit.credential_deleted", "Deleted credential for {site}"),
+    (
+        "audit.operation_undone",
+        "Undid {operation} of {items} item(s)",
+    ),
+    (
+        "audit.operation_redone",
+        "Redid {operation} of {items} item(s)",
+    ),
+    ("audit.marked_safe", "Marked credential as safe"),
+    (
+        "audit.marked_compromised",
+        "Marked credential as compromised",
+    ),
+    (
+        "audit.breach_reset",
+        "Reset credential breach state to unknown",
+    ),
+    (
+        "audit.exported_plaintext",
+        "Exported {count} credentials as plaintext {format} ({scope})",
+    ),
+    (
+        "audit.exported_kdbx",
+        "Exported {count} credentials to KDBX ({scope})",
+    ),
+    (
+        "audit.exported_bundle",
+        "Exported {count} credentials to an encrypted bundle ({scope})",
+    ),
+    (
+        "audit.imported",
+        "Imported {count} credentials ({skipped} skipped)",
+    ),
+    ("audit.audit_archived", "Archived {count} audit entries"),
+    (
+        "audit.import_conflict_resolved",
+        "Resolved import conflict for {site} ({resolution})",
+    ),
+    (
+        "audit.developer_secrets_saved",
+        "Saved {count} secrets from pasted text",
+    ),
+    ("audit.backed_up", "Backed up vault to {path}"),
+    ("audit.backup_restored", "Restored a backup to {path}"),
+    (
+        "audit.vault_compacted",
+        "Compacted vault from {before} to {after} bytes",
+    ),
+    (
+        "audit.nonce_audit_run",
+        "Checked {scanned} encrypted values for nonce reuse, {reused} reused",
+    ),
+    (
+        "audit.operation_rolled_back",
+        "Rolled back interrupted operation: {operation}",
+    ),
+    (
+        "audit.operation_resumed",
+        "Finished interrupted operation: {operation}",
+    ),
+    (
+        "audit.recovery_skipped",
+        "Unlocked without recovering {count} interrupted operations",
+    ),
+    ("audit.remediated", "Remediated password for {site}"),
+    (
+        "audit.rotation_started",
+        "Started rotating {count} passwords",
+    ),
+    ("audit.password_rotated", "Rotated password for {site}"),
+    ("audit.autotyped", "Auto-typed {site} into \"{window}\""),
+    (
+        "audit.totp_attached",
+        "Added a one-time code setup to {site}",
+    ),
+    (
+        "audit.autofill_profile_exported",
+        "Exported {site} to the browser extension for autofill",
+    ),
+    ("audit.wifi_qr_generated", "Showed the QR code for {site}"),
+    (
+        "audit.credential_qr_exported",
+        "Showed a transfer code for {site}",
+    ),
+    (
+        "audit.credential_qr_imported",
+        "Imported {site} from a transfer code",
+    ),
+    (
+        "audit.bulk_session_started",
+        "Started a bulk session: {purpose}",
+    ),
+    (
+        "audit.bulk_session_ended",
+        "Ended the bulk session: {purpose}",
+    ),
+    (
+        "audit.recovery_code_used",
+        "Used a recovery code for {site}; {remaining} left",
+    ),
+    (
+        "audit.breach_list_imported",
+        "Imported {count} compromised passwords into the breach list \"{source}\"",
+    ),
+    (
+        "audit.breach_list_removed",
+        "Removed the breach list \"{source}\"",
+    ),
+    (
+        "audit.saved_from_browser",
+        "Saved the password captured in the browser for {site}",
+    ),
+    (
+        "audit.rotation_skipped",
+        "Skipped rotating {site}; change it on the website later",
+    ),
+    (
+        "audit.email_breach_found",
+        "Monitored email found in breach {breach}; {count} items affected",
+    ),
+    (
+        "audit.exported_audit_log",
+        "Exported {count} audit log entries with signature",
+    ),
+    ("audit.exported_settings_profile", "Exported a settings profile"),
+    ("audit.imported_settings_profile", "Imported a settings profile changing {count} settings"),
+    ("audit.collection_created", "Created collection {name}"),
+    (
+        "audit.collection_member_added",
+        "Added {member} to collection {name}",
+    ),
+    (
+        "audit.collection_member_removed",
+        "Removed {member} from collection {name}",
+    ),
+    ("audit.collection_deleted", "Deleted collection {name}"),
+    ("audit.moved_to_collection", "Moved {site} to collection {name}"),
+    ("audit.moved_to_personal", "Moved {site} to the personal vault"),
+    ("audit.share_created", "Shared {site} via {destination}"),
+    ("audit.share_revoked", "Revoked share of {site}"),
+    (
+        "audit.emergency_contact_added",
+        "Added emergency contact {contact} with a {days}-day wait",
+    ),
+    (
+        "audit.emergency_access_requested",
+        "{contact} requested emergency access",
+    ),
+    (
+        "audit.emergency_access_approved",
+        "Approved emergency access for {contact}",
+    ),
+    (
+        "audit.emergency_access_rejected",
+        "Rejected emergency access for {contact}",
+    ),
+    (
+        "audit.emergency_access_granted",
+        "Emergency access for {contact} was granted after the waiting period",
+    ),
+    (
+        "audit.emergency_contact_revoked",
+        "Revoked emergency contact {contact}",
+    ),
+    (
+        "audit.device_added",
+        "New device opened the vault: {device}",
+    ),
+    ("audit.device_revoked", "Revoked device {device}"),
+    (
+        "audit.emergency_key_released",
+        "Released the vault key to {contact}",
+    ),
+    (
+        "audit.travel_mode_enabled",
+        "Enabled travel mode, hiding {count} credentials",
+    ),
+    (
+        "audit.travel_mode_disabled",
+        "Disabled travel mode, restoring {count} credentials",
+    ),
+    (
+        "audit.high_security_enabled",
+        "Required the master password for {site}",
+    ),
+    (
+        "audit.high_security_disabled",
+        "Stopped requiring the master password for {site}",
+    ),
+    // Before the templates with {fields} so "all fields" parses to these keys
+    (
+        "audit.high_security_revealed_all",
+        "Viewed all fields of high-security item {site}",
+    ),
+    ("audit.secret_revealed_all", "Viewed all fields of {site}"),
+    (
+        "audit.high_security_revealed",
+        "Viewed {fields} of high-security item {site}",
+    ),
+    ("audit.secret_revealed", "Viewed {fields} of {site}"),
+    ("audit.other", "{action}"),
+    // Signed audit export verification
+    (
+        "audit_verify.unsupported_format",
+        "Unsupported audit export format: {format}",
+    ),
+    ("audit_verify.malformed", "The audit export is malformed"),
+    (
+        "audit_verify.chain_broken",
+        "The audit chain is broken at entry {entry}",
+    ),
+    (
+        "audit_verify.bad_signature",
+        "The signature does not match the audit chain",
+    ),
+    (
+        "audit_verify.no_trusted_key",
+        "Unlock the vault or enter the public key that signed this export",
+    ),
+    (
+        "audit_verify.untrusted_key",
+        "The export was not signed by the trusted key",
+    ),
+    // Report labels
+    ("risk.compromised", "Compromised"),
+    ("risk.reused", "Reused"),
+    ("risk.weak", "Weak"),
+    ("risk.old", "Old"),
+    // Desktop notifications
+    ("notification.breach_title", "{email} was in a data breach"),
+    ("notification.breach_body", "Found in {breach}; change the password of {count} items"),
+    ("notification.expiry_title", "Items expiring soon"),
+    ("notification.expiry_body", "{count} items reach their expiry date soon"),
+    ("notification.sync_conflict_title", "Vault changed by another program"),
+    ("notification.sync_conflict_body", "The vault was locked; reload it to see the changes"),
+    ("notification.backup_failed_title", "Backup failed"),
+    ("notification.backup_failed_body", "The scheduled backup failed: {reason}"),
+];
+
+const ZH_TW: &[(&str, &str)] = &[
+    ("error.crypto", "加密錯誤：{detail}"),
+    ("error.database", "資料庫錯誤：{detail}"),
+    ("error.serialization", "序列化錯誤：{detail}"),
+    ("error.io", "輸入輸出錯誤：{detail}"),
+    ("error.vault_locked", "保險庫已鎖定"),
+    ("error.vault_not_created", "尚未建立保險庫"),
+    ("error.auth_failed", "驗證失敗"),
+    ("error.reauth_required", "請輸入主密碼以檢視此項目"),
+    (
+        "error.bulk_session_required",
+        "此操作會讀取所有密碼，請先輸入主密碼確認",
+    ),
+    ("error.cancelled", "操作已取消"),
+    ("error.recovery_failed", "無法復原中斷的作業：{detail}"),
+    ("error.not_found", "找不到項目：{id}"),
+    ("error.conflict", "項目已在其他地方被修改：{detail}"),
+    ("error.invalid_input", "輸入無效：{detail}"),
+    (
+        "error.password_too_weak",
+        "此密碼強度為 {score}／4，至少需要 {min_score}",
+    ),
+    (
+        "error.password_compromised",
+        "此密碼出現在外洩清單 {sources} 中",
+    ),
+    ("validation.empty", "{field} 不可為空白"),
+    ("validation.too_long", "{field} 超過 {max} 位元組"),
+    ("validation.too_many", "{field} 超過 {max} 個項目"),
+    ("validation.control_characters", "{field} 含有控制字元"),
+    (
+        "validation.invalid_encoding",
+        "{field} 含有無效的 UTF-8 文字",
+    ),
+    (
+        "validation.invalid_tag_character",
+        "{field} 含有標籤不能使用的字元「{character}」",
+    ),
+    ("query.unclosed_quote", "搜尋：引號未關閉（第 {column} 欄）"),
+    ("query.missing_value", "搜尋：{field}: 需要一個值（第 {column} 欄）"),
+    (
+        "query.invalid_strength",
+        "搜尋：strength 需要 0 到 100 的數字，可加上 <、<=、> 或 >=，而不是「{value}」（第 {column} 欄）",
+    ),
+    (
+        "query.invalid_breach",
+        "搜尋：breach 必須是 compromised、safe 或 unknown，而不是「{value}」（第 {column} 欄）",
+    ),
+    (
+        "query.invalid_date",
+        "搜尋：updated 需要像 2024-01-31 的日期，可加上 <、<=、> 或 >=，而不是「{value}」（第 {column} 欄）",
+    ),
+    ("query.repeated", "搜尋：{field}: 指定了不只一次（第 {column} 欄）"),
+    ("error.export", "匯出錯誤：{detail}"),
+    ("error.import", "匯入錯誤：{detail}"),
+    ("error.network", "網路錯誤：{detail}"),
+    ("error.sync", "同步錯誤：{detail}"),
+    ("error.other", "其他錯誤：{detail}"),
+    ("error.vault_not_initialized", "保險庫尚未初始化"),
+    ("error.open_database", "無法開啟資料庫：{reason}"),
+    ("error.data_location", "無法開啟資料夾：{reason}"),
+    ("error.initialize_vault", "無法初始化保險庫：{reason}"),
+    ("error.create_vault", "無法建立保險庫：{reason}"),
+    ("error.unlock_vault", "解鎖保險庫時發生錯誤：{reason}"),
+    ("error.lock_vault", "無法鎖定保險庫：{reason}"),
+    ("error.panic_lock", "緊急鎖定失敗：{reason}"),
+    ("error.master_password_reminder", "無法檢查主密碼：{reason}"),
+    ("error.invalid_custom_fields", "自訂欄位格式無效：{reason}"),
+    ("error.add_credential", "無法新增憑證：{reason}"),
+    ("error.get_credential", "無法取得憑證：{reason}"),
+    ("error.decrypt_secret", "無法解密機密資料：{reason}"),
+    ("error.set_high_security", "無法變更項目保護設定：{reason}"),
+    ("error.validate_totp", "無法驗證 TOTP 驗證碼：{reason}"),
+    ("error.generate_password", "無法產生密碼：{reason}"),
+    (
+        "error.set_generator_constraints",
+        "無法儲存網站的密碼規則：{reason}",
+    ),
+    ("error.delete_credential", "無法刪除憑證：{reason}"),
+    ("error.bulk_edit", "無法編輯項目：{reason}"),
+    ("error.rename_tag", "無法重新命名標籤：{reason}"),
+    ("error.undo", "無法復原：{reason}"),
+    ("error.redo", "無法重做：{reason}"),
+    ("error.get_undo_status", "無法取得復原紀錄：{reason}"),
+    ("error.search_credentials", "無法搜尋憑證：{reason}"),
+    ("error.quick_search", "快速搜尋失敗：{reason}"),
+    ("error.suggest_tags", "無法建議標籤：{reason}"),
+    ("error.add_api_key", "無法新增 API 金鑰：{reason}"),
+    ("error.update_api_key", "無法更新 API 金鑰：{reason}"),
+    ("error.check_card_number", "無法檢查卡號：{reason}"),
+    ("error.add_card", "無法新增卡片：{reason}"),
+    ("error.update_card", "無法更新卡片：{reason}"),
+    ("error.breach_check_card", "卡號不會與外洩密碼比對"),
+    ("error.add_identity", "無法新增身分：{reason}"),
+    ("error.update_identity", "無法更新身分：{reason}"),
+    (
+        "error.export_autofill_profile",
+        "無法匯出自動填入資料：{reason}",
+    ),
+    ("error.breach_check_identity", "身分沒有可比對外洩的密碼"),
+    ("error.add_wifi", "無法新增 Wi-Fi 網路：{reason}"),
+    ("error.update_wifi", "無法更新 Wi-Fi 網路：{reason}"),
+    ("error.generate_wifi_qr", "無法產生 QR 碼：{reason}"),
+    ("error.export_credential_qr", "無法產生傳輸碼：{reason}"),
+    ("error.import_credential_qr", "無法匯入傳輸碼：{reason}"),
+    ("error.set_recovery_codes", "無法儲存復原碼：{reason}"),
+    (
+        "error.mark_recovery_code_used",
+        "無法將復原碼標示為已使用：{reason}",
+    ),
+    ("error.import_breach_list", "無法匯入外洩清單：{reason}"),
+    ("error.list_breach_lists", "無法載入外洩清單：{reason}"),
+    ("error.remove_breach_list", "無法移除外洩清單：{reason}"),
+    (
+        "error.check_local_breaches",
+        "無法比對匯入的外洩清單：{reason}",
+    ),
+    ("error.begin_bulk_session", "無法開始批次作業階段：{reason}"),
+    ("error.end_bulk_session", "無法結束批次作業階段：{reason}"),
+    (
+        "error.get_expiring_credentials",
+        "無法列出即將到期的項目：{reason}",
+    ),
+    ("error.accept_deep_link", "無法開啟連結：{reason}"),
+    ("error.reject_deep_link", "無法略過連結：{reason}"),
+    (
+        "error.find_credentials_for_url",
+        "無法找到此頁面的憑證：{reason}",
+    ),
+    (
+        "error.list_pending_saves",
+        "無法列出待儲存的登入資訊：{reason}",
+    ),
+    (
+        "error.approve_pending_save",
+        "無法儲存擷取的登入資訊：{reason}",
+    ),
+    (
+        "error.discard_pending_save",
+        "無法捨棄擷取的登入資訊：{reason}",
+    ),
+    ("error.list_credentials", "無法列出憑證：{reason}"),
+    ("error.check_breach", "無法檢查密碼外洩狀態：{reason}"),
+    ("error.update_breach_state", "無法更新外洩狀態：{reason}"),
+    ("error.get_settings", "無法取得應用程式設定：{reason}"),
+    ("error.save_settings", "無法儲存應用程式設定：{reason}"),
+    ("error.set_offline_mode", "無法儲存離線模式：{reason}"),
+    ("error.get_security_trend", "無法取得安全趨勢：{reason}"),
+    ("error.get_vault_statistics", "無法取得保險庫統計：{reason}"),
+    ("error.get_vault_status", "無法取得保險庫狀態：{reason}"),
+    ("error.list_features", "無法列出功能：{reason}"),
+    ("error.set_feature", "無法變更功能：{reason}"),
+    ("error.list_views", "無法列出檢視：{reason}"),
+    ("error.list_notifications", "無法列出通知：{reason}"),
+    ("error.clear_notifications", "無法清除通知：{reason}"),
+    ("error.mark_notifications_read", "無法將通知標示為已讀：{reason}"),
+    ("error.get_app_status", "無法取得應用程式狀態：{reason}"),
+    ("error.save_view", "無法儲存檢視：{reason}"),
+    ("error.delete_view", "無法刪除檢視：{reason}"),
+    (
+        "error.list_credentials_for_view",
+        "無法列出檢視中的項目：{reason}",
+    ),
+    ("error.get_member_key", "無法取得成員金鑰：{reason}"),
+    ("error.list_collections", "無法列出共享集合：{reason}"),
+    ("error.create_collection", "無法建立共享集合：{reason}"),
+    ("error.add_collection_member", "無法新增集合成員：{reason}"),
+    (
+        "error.remove_collection_member",
+        "無法移除集合成員：{reason}",
+    ),
+    ("error.move_to_collection", "無法移動憑證：{reason}"),
+    ("error.delete_collection", "無法刪除共享集合：{reason}"),
+    ("error.create_share", "無法建立分享：{reason}"),
+    ("error.list_shares", "無法列出分享：{reason}"),
+    ("error.revoke_share", "無法撤銷分享：{reason}"),
+    (
+        "error.add_emergency_contact",
+        "無法新增緊急聯絡人：{reason}",
+    ),
+    (
+        "error.list_emergency_contacts",
+        "無法列出緊急聯絡人：{reason}",
+    ),
+    (
+        "error.request_emergency_access",
+        "無法申請緊急存取：{reason}",
+    ),
+    (
+        "error.approve_emergency_access",
+        "無法核准緊急存取：{reason}",
+    ),
+    (
+        "error.reject_emergency_access",
+        "無法拒絕緊急存取：{reason}",
+    ),
+    (
+        "error.revoke_emergency_contact",
+        "無法撤銷緊急聯絡人：{reason}",
+    ),
+    ("error.list_devices", "無法列出裝置：{reason}"),
+    ("error.revoke_device", "無法撤銷裝置：{reason}"),
+    ("error.release_emergency_key", "無法釋出緊急金鑰：{reason}"),
+    ("error.enable_travel_mode", "無法啟用旅行模式：{reason}"),
+    ("error.disable_travel_mode", "無法停用旅行模式：{reason}"),
+    ("error.get_travel_mode", "無法取得旅行模式狀態：{reason}"),
+    (
+        "error.share_relay_not_configured",
+        "尚未設定分享中繼伺服器，請改為輸出檔案",
+    ),
+    (
+        "error.invalid_share_relay",
+        "分享中繼伺服器設定無效：{reason}",
+    ),
+    (
+        "error.invalid_update_manifest",
+        "更新清單網址無效：{reason}",
+    ),
+    ("error.check_for_updates", "無法檢查更新：{reason}"),
+    ("error.download_update", "無法下載更新：{reason}"),
+    ("error.invalid_network_settings", "網路設定無效：{reason}"),
+    ("error.export_audit_log", "無法匯出稽核紀錄：{reason}"),
+    ("error.export_settings_profile", "無法匯出設定檔：{reason}"),
+    ("error.import_settings_profile", "無法匯入設定檔：{reason}"),
+    ("error.verify_audit_export", "無法驗證稽核匯出檔：{reason}"),
+    ("error.audit_public_key", "無法讀取稽核簽署金鑰：{reason}"),
+    ("error.get_ui_preferences", "無法取得介面偏好設定：{reason}"),
+    (
+        "error.save_ui_preferences",
+        "無法儲存介面偏好設定：{reason}",
+    ),
+    ("error.get_wordlists", "無法取得字詞清單：{reason}"),
+    ("error.set_wordlists", "無法儲存字詞清單：{reason}"),
+    (
+        "error.recalculate_strengths",
+        "無法重新計算密碼強度：{reason}",
+    ),
+    (
+        "error.get_storage_profile",
+        "無法取得儲存位置設定：{reason}",
+    ),
+    ("error.set_storage_profile", "無法變更儲存位置：{reason}"),
+    (
+        "error.invalid_breach_api",
+        "外洩查詢 API 設定無效：{reason}",
+    ),
+    ("error.master_password_incorrect", "主密碼不正確"),
+    ("error.export_credentials", "無法匯出憑證：{reason}"),
+    ("error.export_kdbx", "無法匯出 KDBX 資料庫：{reason}"),
+    ("error.export_bundle", "無法匯出封包：{reason}"),
+    ("error.preview_import", "無法預覽匯入內容：{reason}"),
+    ("error.import_credentials", "無法匯入憑證：{reason}"),
+    ("error.list_import_conflicts", "無法列出匯入衝突：{reason}"),
+    (
+        "error.resolve_import_conflict",
+        "無法處理匯入衝突：{reason}",
+    ),
+    ("error.scan_pasted_secrets", "無法掃描貼上的文字：{reason}"),
+    ("error.save_pasted_secrets", "無法儲存機密：{reason}"),
+    ("error.no_backup_folder", "尚未設定備份資料夾"),
+    ("error.backup", "無法備份保險庫：{reason}"),
+    ("error.restore_backup", "無法還原備份：{reason}"),
+    ("error.compact_vault", "無法壓縮保險庫：{reason}"),
+    ("error.run_maintenance", "維護作業失敗：{reason}"),
+    (
+        "error.create_diagnostics_bundle",
+        "無法建立診斷資料包：{reason}",
+    ),
+    (
+        "error.verify_no_nonce_reuse",
+        "無法檢查隨機數重複使用：{reason}",
+    ),
+    ("error.get_domain_info", "無法查詢網站資訊：{reason}"),
+    ("error.remediation_queue", "無法建立待處理清單：{reason}"),
+    ("error.apply_remediation", "無法套用修正：{reason}"),
+    ("error.rotation", "密碼輪替失敗：{reason}"),
+    ("error.autotype", "自動輸入失敗：{reason}"),
+    ("error.autotype_no_window", "沒有可自動輸入的焦點視窗"),
+    ("error.autotype_expired", "此自動輸入請求已失效"),
+    (
+        "error.autotype_window_changed",
+        "焦點視窗已改變，未執行自動輸入",
+    ),
+    (
+        "error.set_autotype_sequence",
+        "無法設定自動輸入序列：{reason}",
+    ),
+    (
+        "error.check_monitored_emails",
+        "無法檢查監控的電子郵件：{reason}",
+    ),
+    (
+        "error.list_breach_notifications",
+        "無法列出外洩通知：{reason}",
+    ),
+    (
+        "error.dismiss_breach_notification",
+        "無法關閉外洩通知：{reason}",
+    ),
+    (
+        "error.invalid_email_monitor",
+        "電子郵件監控設定無效：{reason}",
+    ),
+    (
+        "error.recheck_after_update",
+        "密碼已更新，但外洩檢查失敗：{reason}",
+    ),
+    ("error.get_audit_log", "無法取得稽核紀錄：{reason}"),
+    ("error.archive_audit_log", "無法封存稽核紀錄：{reason}"),
+    ("error.search_audit_archives", "無法搜尋稽核封存：{reason}"),
+    ("error.unsupported_locale", "不支援的語系：{locale}"),
+    ("audit.vault_unlocked", "已解鎖保險庫"),
+    ("audit.vault_created", "已建立保險庫"),
+    ("audit.feature_enabled", "已開啟功能 {feature}"),
+    ("audit.feature_disabled", "已關閉功能 {feature}"),
+    ("audit.credentials_listed", "已列出 {count} 筆憑證"),
+    (
+        "audit.credentials_searched",
+        "已搜尋憑證「{term}」（找到 {count} 筆）",
+    ),
+    ("audit.vault_locked", "已鎖定保險庫"),
+    ("audit.panic_lock", "已觸發緊急鎖定"),
+    ("audit.settings_updated", "已更新應用程式設定"),
+    (
+        "audit.storage_profile_changed",
+        "已將憑證儲存位置切換為 {backend}",
+    ),
+    (
+        "audit.wordlists_changed",
+        "已更新字詞清單：{banned} 個禁用密碼、{words} 個字典詞彙",
+    ),
+    (
+        "audit.strengths_recalculated",
+        "已重新計算密碼強度：{total} 個中有 {changed} 個變更",
+    ),
+    (
+        "audit.integrity_damaged",
+        "完整性檢查發現抽樣的 {checked} 個項目中有 {damaged} 個無法解密",
+    ),
+    ("audit.credential_added", "已新增 {site} 的憑證"),
+    ("audit.credential_updated", "已更新 {site} 的憑證"),
+    ("audit.credential_deleted", "已刪除 {site} 的憑證"),
+    (
+        "audit.operation_undone",
+        "已復原 {items} 個項目的 {operation}",
+    ),
+    (
+        "audit.operation_redone",
+        "已重做 {items} 個項目的 {operation}",
+    ),
+    ("audit.marked_safe", "已將憑證標示為安全"),
+    ("audit.marked_compromised", "已將憑證標示為已外洩"),
+    ("audit.breach_reset", "已將憑證外洩狀態重設為未知"),
+    (
+        "audit.exported_plaintext",
+        "已將 {count} 筆憑證匯出為明文 {format}（{scope}）",
+    ),
+    (
+        "audit.exported_kdbx",
+        "已將 {count} 筆憑證匯出至 KDBX（{scope}）",
+    ),
+    (
+        "audit.exported_bundle",
+        "已將 {count} 筆憑證匯出至加密封包（{scope}）",
+    ),
+    (
+        "audit.imported",
+        "已匯入 {count} 筆憑證（略過 {skipped} 筆）",
+    ),
+    ("audit.audit_archived", "已封存 {count} 筆稽核紀錄"),
+    (
+        "audit.import_conflict_resolved",
+        "已處理 {site} 的匯入衝突（{resolution}）",
+    ),
+    (
+        "audit.developer_secrets_saved",
+        "已從貼上的文字儲存 {count} 筆機密",
+    ),
+    ("audit.backed_up", "已將保險庫備份至 {path}"),
+    ("audit.backup_restored", "已將備份還原至 {path}"),
+    (
+        "audit.vault_compacted",
+        "已將保險庫由 {before} 位元組壓縮為 {after} 位元組",
+    ),
+    (
+        "audit.nonce_audit_run",
+        "已檢查 {scanned} 個加密值的隨機數重複使用，{reused} 個重複",
+    ),
+    (
+        "audit.operation_rolled_back",
+        "已復原中斷的作業：{operation}",
+    ),
+    ("audit.operation_resumed", "已完成中斷的作業：{operation}"),
+    ("audit.recovery_skipped", "未復原 {count} 項中斷的作業即解鎖"),
+    ("audit.remediated", "已修正 {site} 的密碼"),
+    ("audit.rotation_started", "已開始輪替 {count} 組密碼"),
+    ("audit.password_rotated", "已輪替 {site} 的密碼"),
+    ("audit.autotyped", "已將 {site} 自動輸入至「{window}」"),
+    ("audit.totp_attached", "已為 {site} 新增一次性密碼設定"),
+    (
+        "audit.autofill_profile_exported",
+        "已將 {site} 匯出至瀏覽器擴充功能以自動填入",
+    ),
+    ("audit.wifi_qr_generated", "已顯示 {site} 的 QR 碼"),
+    ("audit.credential_qr_exported", "已顯示 {site} 的傳輸碼"),
+    ("audit.credential_qr_imported", "已從傳輸碼匯入 {site}"),
+    (
+        "audit.bulk_session_started",
+        "已開始批次作業階段：{purpose}",
+    ),
+    ("audit.bulk_session_ended", "已結束批次作業階段：{purpose}"),
+    (
+        "audit.recovery_code_used",
+        "已使用 {site} 的一組復原碼，剩餘 {remaining} 組",
+    ),
+    (
+        "audit.breach_list_imported",
+        "已將 {count} 組外洩密碼匯入外洩清單「{source}」",
+    ),
+    ("audit.breach_list_removed", "已移除外洩清單「{source}」"),
+    (
+        "audit.saved_from_browser",
+        "已儲存在瀏覽器中擷取的 {site} 密碼",
+    ),
+    (
+        "audit.rotation_skipped",
+        "已略過輪替 {site}，稍後需在網站上變更",
+    ),
+    (
+        "audit.email_breach_found",
+        "監控的電子郵件出現在外洩事件 {breach} 中，影響 {count} 個項目",
+    ),
+    ("audit.collection_created", "已建立共享集合 {name}"),
+    (
+        "audit.collection_member_added",
+        "已將 {member} 加入共享集合 {name}",
+    ),
+    (
+        "audit.collection_member_removed",
+        "已將 {member} 移出共享集合 {name}",
+    ),
+    ("audit.collection_deleted", "已刪除共享集合 {name}"),
+    ("audit.moved_to_collection", "已將 {site} 移至共享集合 {name}"),
+    ("audit.moved_to_personal", "已將 {site} 移回個人保險庫"),
+    ("audit.share_created", "已透過 {destination} 分享 {site}"),
+    ("audit.share_revoked", "已撤銷 {site} 的分享"),
+    (
+        "audit.emergency_contact_added",
+        "已新增緊急聯絡人 {contact}，等待期 {days} 天",
+    ),
+    (
+        "audit.emergency_access_requested",
+        "{contact} 已申請緊急存取",
+    ),
+    (
+        "audit.emergency_access_approved",
+        "已核准 {contact} 的緊急存取",
+    ),
+    (
+        "audit.emergency_access_rejected",
+        "已拒絕 {contact} 的緊急存取",
+    ),
+    (
+        "audit.emergency_access_granted",
+        "等待期已過，已授予 {contact} 緊急存取",
+    ),
+    (
+        "audit.emergency_contact_revoked",
+        "已撤銷緊急聯絡人 {contact}",
+    ),
+    ("audit.device_added", "新裝置開啟了保險庫：{device}"),
+    ("audit.device_revoked", "已撤銷裝置 {device}"),
+    (
+        "audit.emergency_key_released",
+        "已將保險庫金鑰釋出給 {contact}",
+    ),
+    (
+        "audit.travel_mode_enabled",
+        "已啟用旅行模式，隱藏 {count} 筆憑證",
+    ),
+    (
+        "audit.travel_mode_disabled",
+        "已停用旅行模式，還原 {count} 筆憑證",
+    ),
+    ("audit.high_security_enabled", "已要求以主密碼檢視 {site}"),
+    (
+        "audit.high_security_disabled",
+        "已取消以主密碼檢視 {site} 的要求",
+    ),
+    (
+        "audit.high_security_revealed_all",
+        "已檢視高安全性項目 {site} 的所有欄位",
+    ),
+    ("audit.secret_revealed_all", "已檢視 {site} 的所有欄位"),
+    (
+        "audit.high_security_revealed",
+        "已檢視高安全性項目 {site} 的{fields}",
+    ),
+    ("audit.secret_revealed", "已檢視 {site} 的{fields}"),
+    ("audit.other", "{action}"),
+    (
+        "audit.exported_audit_log",
+        "已匯出 {count} 筆附簽章的稽核紀錄",
+    ),
+    ("audit.exported_settings_profile", "已匯出設定檔"),
+    ("audit.imported_settings_profile", "已匯入設定檔，變更了 {count} 項設定"),
+    (
+        "audit_verify.unsupported_format",
+        "不支援的稽核匯出格式：{format}",
+    ),
+    ("audit_verify.malformed", "稽核匯出檔格式錯誤"),
+    ("audit_verify.chain_broken", "稽核鏈在第 {entry} 筆中斷"),
+    ("audit_verify.bad_signature", "簽章與稽核鏈不符"),
+    (
+        "audit_verify.no_trusted_key",
+        "請解鎖保險庫或輸入簽署此匯出檔的公開金鑰",
+    ),
+    ("audit_verify.untrusted_key", "此匯出檔並非由受信任的金鑰簽署"),
+    ("risk.compromised", "已外洩"),
+    ("risk.reused", "重複使用"),
+    ("risk.weak", "強度不足"),
+    ("risk.old", "過舊"),
+    ("notification.breach_title", "{email} 出現在資料外洩事件中"),
+    ("notification.breach_body", "出現於 {breach}；請變更 {count} 個項目的密碼"),
+    ("notification.expiry_title", "項目即將到期"),
+    ("notification.expiry_body", "{count} 個項目即將到期"),
+    ("notification.sync_conflict_title", "保險庫已被其他程式變更"),
+    ("notification.sync_conflict_body", "保險庫已鎖定；請重新載入以查看變更"),
+    ("notification.backup_failed_title", "備份失敗"),
+    ("notification.backup_failed_body", "排程備份失敗：{reason}"),
+];
@@ -0,0 +1,355 @@
+//! Identity items and autofill profiles.
+//!
+//! An identity is an item of kind `Identity`: a person's name, addresses, phone
+//! numbers and emails, kept encrypted in the item's secret like any password.
+//! The browser extension fills web forms from an `AutofillProfile`, a
+//! normalized copy keyed by the HTML `autocomplete` tokens
+//! (`given-name`, `postal-code`, `tel`, ...) so it can map fields without
+//! knowing how the vault stores them.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+const MAX_FIELD_BYTES: usize = 256;
+const MAX_ENTRIES: usize = 10;
+
+/// A postal address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct Address {
+    /// e.g. `Home` or `Work`
+    #[serde(default)]
+    pub label: String,
+    /// Street, building and unit, one per line
+    #[serde(default)]
+    pub lines: Vec<String>,
+    #[serde(default)]
+    pub city: String,
+    /// State, province or county
+    #[serde(default)]
+    pub region: String,
+    #[serde(default)]
+    pub postal_code: String,
+    /// A country name or ISO 3166 code
+    #[serde(default)]
+    pub country: String,
+}
+
+/// The canonical data of an identity item, stored encrypted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct Identity {
+    /// e.g. `Dr.`
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub first_name: String,
+    #[serde(default)]
+    pub middle_name: String,
+    #[serde(default)]
+    pub last_name: String,
+    #[serde(default)]
+    pub company: String,
+    /// The first address is the one forms are filled with
+    #[serde(default)]
+    pub addresses: Vec<Address>,
+    /// The first phone number is the one forms are filled with
+    #[serde(default)]
+    pub phones: Vec<String>,
+    /// The first email is the one forms are filled with
+    #[serde(default)]
+    pub emails: Vec<String>,
+}
+
+impl Identity {
+    /// Given, middle and family name as one line
+    pub fn full_name(&self) -> String {
+        [&self.first_name, &self.middle_name, &self.last_name]
+            .iter()
+            .map(|part| clean(part))
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn validate(&self) -> AppResult<()> {
+        if self.full_name().is_empty()
+            && clean(&self.company).is_empty()
+            && self.emails.iter().all(|email| email.trim().is_empty())
+        {
+            return Err(AppError::InvalidInput(
+                "An identity needs a name, a company or an email".to_string(),
+            ));
+        }
+        if self.addresses.len() > MAX_ENTRIES
+            || self.phones.len() > MAX_ENTRIES
+            || self.emails.len() > MAX_ENTRIES
+        {
+            return Err(AppError::InvalidInput(format!(
+                "An identity may have at most {} addresses, phone numbers and emails each",
+                MAX_ENTRIES
+            )));
+        }
+        let names = [
+            &self.title,
+            &self.first_name,
+            &self.middle_name,
+            &self.last_name,
+            &self.company,
+        ];
+        let addresses = self.addresses.iter().flat_map(|address| {
+            [
+                &address.label,
+                &address.city,
+                &address.region,
+                &address.postal_code,
+                &address.country,
+            ]
+            .into_iter()
+            .chain(&address.lines)
+        });
+        let too_long = names
+            .into_iter()
+            .chain(addresses)
+            .chain(&self.phones)
+            .chain(&self.emails)
+            .any(|field| field.len() > MAX_FIELD_BYTES || field.contains(['\n', '\r']));
+        if too_long {
+            return Err(AppError::InvalidInput(format!(
+                "Identity fields must be a single line of at most {} bytes",
+                MAX_FIELD_BYTES
+            )));
+        }
+        for email in self.emails.iter().map(|email| email.trim()) {
+            if !email.is_empty() && normalize_email(email).is_none() {
+                return Err(AppError::InvalidInput(format!(
+                    "\"{}\" is not an email address",
+                    email
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An identity as entered by the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentityInput {
+    /// Name of the item; defaults to the person's or company's name
+    #[serde(default)]
+    pub name: Option<String>,
+    pub identity: Identity,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl IdentityInput {
+    pub fn validate(&self) -> AppResult<()> {
+        self.identity.validate()
+    }
+
+    /// The item's name
+    pub fn site(&self) -> String {
+        let identity = &self.identity;
+        self.name
+            .clone()
+            .filter(|name| !name.trim().is_empty())
+            .or_else(|| Some(identity.full_name()).filter(|name| !name.is_empty()))
+            .or_else(|| Some(clean(&identity.company)).filter(|company| !company.is_empty()))
+            .unwrap_or_else(|| "Identity".to_string())
+    }
+
+    /// The item's username, which lists show: the primary email
+    pub fn username(&self) -> String {
+        self.identity
+            .emails
+            .iter()
+            .find_map(|email| normalize_email(email))
+            .unwrap_or_default()
+    }
+}
+
+/// An address keyed by `autocomplete` tokens
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutofillAddress {
+    pub label: String,
+    /// `street-address`, `address-line1`..`3`, `address-level2` (city),
+    /// `address-level1` (region), `postal-code`, `country` (ISO code when
+    /// known) and `country-name`
+    pub fields: BTreeMap<String, String>,
+}
+
+/// What the browser extension fills forms with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutofillProfile {
+    pub uuid: String,
+    pub name: String,
+    /// Name, organization, primary email, phone and address, keyed by
+    /// `autocomplete` token; empty fields are left out
+    pub fields: BTreeMap<String, String>,
+    /// Every email, lowercased
+    pub emails: Vec<String>,
+    /// Every phone number, as `+` and digits where possible
+    pub phones: Vec<String>,
+    pub addresses: Vec<AutofillAddress>,
+}
+
+/// Countries forms commonly ask for, by ISO 3166-1 alpha-2 code
+const COUNTRIES: &[(&str, &str)] = &[
+    ("AU", "Australia"),
+    ("CA", "Canada"),
+    ("CN", "China"),
+    ("DE", "Germany"),
+    ("ES", "Spain"),
+    ("FR", "France"),
+    ("GB", "United Kingdom"),
+    ("HK", "Hong Kong"),
+    ("IN", "India"),
+    ("IT", "Italy"),
+    ("JP", "Japan"),
+    ("KR", "South Korea"),
+    ("NL", "Netherlands"),
+    ("SG", "Singapore"),
+    ("TW", "Taiwan"),
+    ("US", "United States"),
+];
+
+/// Trimmed, with runs of whitespace collapsed
+fn clean(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Lowercased, or None when it is not shaped like an email
+fn normalize_email(email: &str) -> Option<String> {
+    let email = email.trim().to_lowercase();
+    let (local, domain) = email.split_once('@')?;
+    let valid = !local.is_empty()
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !email.contains(char::is_whitespace);
+    valid.then_some(email)
+}
+
+/// `+` and digits for numbers that have them, otherwise the cleaned text
+fn normalize_phone(phone: &str) -> String {
+    let digits: String = phone.chars().filter(char::is_ascii_digit).collect();
+    let separators_only = phone
+        .trim()
+        .chars()
+        .all(|c| c.is_ascii_digit() || " +-.()".contains(c));
+    match (separators_only, phone.trim_start().starts_with('+')) {
+        (true, true) => format!("+{}", digits),
+        (true, false) => digits,
+        (false, _) => clean(phone),
+    }
+}
+
+/// The ISO code and display name of a country given as either
+fn normalize_country(country: &str) -> (Option<&'static str>, String) {
+    let country = clean(country);
+    match COUNTRIES.iter().find(|(code, name)| {
+        code.eq_ignore_ascii_case(&country) || name.eq_ignore_ascii_case(&country)
+    }) {
+        Some((code, name)) => (Some(code), name.to_string()),
+        None => (None, country),
+    }
+}
+
+fn insert(fields: &mut BTreeMap<String, String>, token: &str, value: String) {
+    if !value.is_empty() {
+        fields.insert(token.to_string(), value);
+    }
+}
+
+fn autofill_address(address: &Address) -> AutofillAddress {
+    let lines: Vec<String> = address
+        .lines
+        .iter()
+        .map(|line| clean(line))
+        .filter(|line| !line.is_empty())
+        .collect();
+    let (code, country_name) = normalize_country(&address.country);
+
+    let mut fields = BTreeMap::new();
+    insert(&mut fields, "street-address", lines.join("\n"));
+    // Forms with a fixed number of lines get the rest in the last one
+    match lines.len() {
+        0..=3 => {
+            for (index, line) in lines.iter().enumerate() {
+                insert(
+                    &mut fields,
+                    &format!("address-line{}", index + 1),
+                    line.clone(),
+                );
+            }
+        }
+        _ => {
+            insert(&mut fields, "address-line1", lines[0].clone());
+            insert(&mut fields, "address-line2", lines[1].clone());
+            insert(&mut fields, "address-line3", lines[2..].join(", "));
+        }
+    }
+    insert(&mut fields, "address-level2", clean(&address.city));
+    insert(&mut fields, "address-level1", clean(&address.region));
+    insert(
+        &mut fields,
+        "postal-code",
+        clean(&address.postal_code).to_uppercase(),
+    );
+    insert(
+        &mut fields,
+        "country",
+        code.map(str::to_string).unwrap_or_default(),
+    );
+    insert(&mut fields, "country-name", country_name);
+    AutofillAddress {
+        label: clean(&address.label),
+        fields,
+    }
+}
+
+/// The profile the browser extension fills forms with
+pub fn autofill_profile(uuid: &str, name: &str, identity: &Identity) -> AutofillProfile {
+    let emails: Vec<String> = identity
+        .emails
+        .iter()
+        .filter_map(|email| normalize_email(email))
+        .collect();
+    let phones: Vec<String> = identity
+        .phones
+        .iter()
+        .map(|phone| normalize_phone(phone))
+        .filter(|phone| !phone.is_empty())
+        .collect();
+    let addresses: Vec<AutofillAddress> = identity.addresses.iter().map(autofill_address).collect();
+
+    let mut fields = BTreeMap::new();
+    insert(&mut fields, "honorific-prefix", clean(&identity.title));
+    insert(&mut fields, "given-name", clean(&identity.first_name));
+    insert(&mut fields, "additional-name", clean(&identity.middle_name));
+    insert(&mut fields, "family-name", clean(&identity.last_name));
+    insert(&mut fields, "name", identity.full_name());
+    insert(&mut fields, "organization", clean(&identity.company));
+    if let Some(email) = emails.first() {
+        insert(&mut fields, "email", email.clone());
+    }
+    if let Some(phone) = phones.first() {
+        insert(&mut fields, "tel", phone.clone());
+    }
+    if let Some(address) = addresses.first() {
+        fields.extend(address.fields.clone());
+    }
+
+    AutofillProfile {
+        uuid: uuid.to_string(),
+        name: name.to_string(),
+        fields,
+        emails,
+        phones,
+        addresses,
+    }
+}
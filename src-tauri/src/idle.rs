@@ -0,0 +1,196 @@
+//! Idle time for the auto-lock timeout.
+//!
+//! `SystemIdle` asks the OS how long ago the last keyboard or mouse input was:
+//! `GetLastInputInfo` on Windows, CoreGraphics on macOS, and GNOME's idle
+//! monitor or `xprintidle` on Linux. Where none of these work (e.g. other
+//! Wayland compositors, or a tool that fails) it reports nothing and the app's
+//! own activity is used.
+
+use std::time::Duration;
+
+use crate::models::IdleDetection;
+
+/// Reports how long the user has been inactive
+pub trait IdleSource: Send + Sync {
+    /// Time since the last input, or None when it cannot be determined
+    fn idle_time(&self) -> Option<Duration>;
+}
+
+/// Idle time from the operating system. On Linux it may block for up to two
+/// seconds while a helper runs, so async code calls it on a blocking thread.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemIdle;
+
+impl IdleSource for SystemIdle {
+    fn idle_time(&self) -> Option<Duration> {
+        platform::idle_time()
+    }
+}
+
+/// Idle time used for auto-lock. `app_idle` is the time since the last activity
+/// the app saw itself; `os_idle` is what an `IdleSource` reported.
+pub fn effective_idle(
+    mode: IdleDetection,
+    app_idle: Duration,
+    os_idle: Option<Duration>,
+) -> Duration {
+    match (mode, os_idle) {
+        (IdleDetection::System, Some(os_idle)) => os_idle,
+        _ => app_idle,
+    }
+}
+
+/// Whether the vault should lock after `idle` with a timeout of
+/// `timeout_minutes` (0 = never)
+pub fn should_lock(timeout_minutes: u32, idle: Duration) -> bool {
+    timeout_minutes > 0 && idle >= Duration::from_secs(u64::from(timeout_minutes) * 60)
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::time::Duration;
+
+    #[repr(C)]
+    struct LastInputInfo {
+        cb_size: u32,
+        dw_time: u32,
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetLastInputInfo(plii: *mut LastInputInfo) -> i32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetTickCount() -> u32;
+    }
+
+    pub fn idle_time() -> Option<Duration> {
+        let mut info = LastInputInfo {
+            cb_size: std::mem::size_of::<LastInputInfo>() as u32,
+            dw_time: 0,
+        };
+        // SAFETY: `info` is a correctly sized LASTINPUTINFO that outlives the call
+        if unsafe { GetLastInputInfo(&mut info) } == 0 {
+            return None;
+        }
+        // SAFETY: GetTickCount has no preconditions
+        let now = unsafe { GetTickCount() };
+        // Both are 32-bit tick counts that wrap after ~49 days
+        Some(Duration::from_millis(u64::from(
+            now.wrapping_sub(info.dw_time),
+        )))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::time::Duration;
+
+    const COMBINED_SESSION_STATE: i32 = 0;
+    const ANY_INPUT_EVENT_TYPE: u32 = !0;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventSourceSecondsSinceLastEventType(state: i32, event_type: u32) -> f64;
+    }
+
+    pub fn idle_time() -> Option<Duration> {
+        // SAFETY: plain query with constant arguments
+        let seconds = unsafe {
+            CGEventSourceSecondsSinceLastEventType(COMBINED_SESSION_STATE, ANY_INPUT_EVENT_TYPE)
+        };
+        (seconds.is_finite() && seconds >= 0.0).then(|| Duration::from_secs_f64(seconds))
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::path::Path;
+    use std::process::{Command, Stdio};
+    use std::time::{Duration, Instant};
+
+    /// A hung tool must not hold up the auto-lock check
+    const TOOL_TIMEOUT: Duration = Duration::from_secs(2);
+
+    pub fn idle_time() -> Option<Duration> {
+        mutter_idle_time().or_else(xprintidle)
+    }
+
+    /// GNOME (X11 and Wayland) via Mutter's idle monitor; prints `(uint64 1234,)`
+    fn mutter_idle_time() -> Option<Duration> {
+        let output = run_tool(
+            &["/usr/bin/gdbus", "/bin/gdbus"],
+            &[
+                "call",
+                "--session",
+                "--dest",
+                "org.gnome.Mutter.IdleMonitor",
+                "--object-path",
+                "/org/gnome/Mutter/IdleMonitor/Core",
+                "--method",
+                "org.gnome.Mutter.IdleMonitor.GetIdletime",
+            ],
+        )?;
+        super::parse_millis(output.trim_start_matches("(uint64"))
+    }
+
+    /// Any X11 session with the `xprintidle` tool installed
+    fn xprintidle() -> Option<Duration> {
+        super::parse_millis(&run_tool(&["/usr/bin/xprintidle", "/bin/xprintidle"], &[])?)
+    }
+
+    /// Stdout of the first installed tool of `paths`. Tools are run by absolute
+    /// path so nothing earlier on PATH can report a made-up idle time; a tool
+    /// that fails or hangs reports nothing, and the app's own idle time is used.
+    fn run_tool(paths: &[&str], args: &[&str]) -> Option<String> {
+        let path = paths.iter().find(|path| Path::new(path).is_file())?;
+        let mut child = Command::new(path)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let deadline = Instant::now() + TOOL_TIMEOUT;
+        loop {
+            match child.try_wait() {
+                Ok(Some(_)) => break,
+                Ok(None) if Instant::now() < deadline => {
+                    std::thread::sleep(Duration::from_millis(20))
+                }
+                _ => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return None;
+                }
+            }
+        }
+        let output = child.wait_with_output().ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+mod platform {
+    use std::time::Duration;
+
+    pub fn idle_time() -> Option<Duration> {
+        None
+    }
+}
+
+/// Parses the leading millisecond count of a tool's output
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_millis(output: &str) -> Option<Duration> {
+    let digits: String = output
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok().map(Duration::from_millis)
+}
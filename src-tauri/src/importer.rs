@@ -0,0 +1,564 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::categorize;
+use crate::error::{AppError, AppResult};
+use crate::models::{Credential, CredentialSummary, Secret};
+use crate::totp;
+use crate::validation;
+
+/// Number of rows included in an import preview
+const PREVIEW_ROWS: usize = 20;
+
+/// File formats the importer understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+    /// Any CSV with a header row (Bitwarden, Chrome, 1Password, ...)
+    Csv,
+    /// A JSON array of objects, or an object with an `items` array
+    Json,
+}
+
+/// Credential field a source column is mapped onto
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "name", rename_all = "snake_case")]
+pub enum ImportField {
+    Site,
+    Username,
+    Password,
+    Notes,
+    Totp,
+    /// Comma or semicolon separated tags (or a single folder name)
+    Tags,
+    /// Stored as a custom field with the given name
+    CustomField(String),
+    /// Bitwarden's `fields` column: `name: value` lines, one custom field each
+    CustomFields,
+    /// Column is dropped
+    Ignore,
+}
+
+/// Maps one source column onto a credential field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnMapping {
+    pub column: String,
+    pub field: ImportField,
+}
+
+/// Parsed source data: column names and string cells
+#[derive(Debug, Clone, Default)]
+pub struct ImportTable {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Dry-run result shown to the user before anything is written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPreview {
+    /// Source column names
+    pub columns: Vec<String>,
+    /// First rows of the source, for display
+    pub sample_rows: Vec<Vec<String>>,
+    /// Total number of data rows in the source
+    pub total_rows: usize,
+    /// Proposed column mapping the user can adjust
+    pub mapping: Vec<ColumnMapping>,
+    /// Problems detected with the proposed mapping
+    pub warnings: Vec<String>,
+    /// Tags suggested for untagged rows under the proposed mapping
+    #[serde(default)]
+    pub suggested_tags: Vec<ImportTagSuggestion>,
+}
+
+/// A tag `categorize` suggests for a row of the source
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportTagSuggestion {
+    /// Index of the data row, from 0
+    pub row: usize,
+    pub site: String,
+    pub tag: String,
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub imported: usize,
+    pub skipped: usize,
+    /// Rows matching an existing item, waiting in `list_import_conflicts`
+    #[serde(default)]
+    pub conflicts: usize,
+    /// One message per skipped row
+    pub errors: Vec<String>,
+    /// One message per imported row with a problem, e.g. a TOTP setup left out
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+/// A row converted into credential data, ready to be added to the vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappedItem {
+    pub site: String,
+    pub username: String,
+    pub secret: Secret,
+    pub tags: Vec<String>,
+    /// Problems with the row that did not stop it from being imported
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+/// What to do with an imported row that matches an existing item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// Drop the imported row
+    KeepExisting,
+    /// Replace the existing item's secret with the imported one; tags are combined
+    Overwrite,
+    /// Add the imported row as a separate item
+    KeepBoth,
+    /// Keep the existing item, filling in what it lacks from the imported row
+    MergeFields,
+}
+
+impl ConflictResolution {
+    /// Name used in audit entries
+    pub fn name(self) -> &'static str {
+        match self {
+            ConflictResolution::KeepExisting => "keep_existing",
+            ConflictResolution::Overwrite => "overwrite",
+            ConflictResolution::KeepBoth => "keep_both",
+            ConflictResolution::MergeFields => "merge_fields",
+        }
+    }
+}
+
+/// A pending conflict as stored: the imported row is encrypted `MappedItem` JSON
+#[derive(Debug, Clone)]
+pub struct StoredImportConflict {
+    pub id: i64,
+    pub existing_uuid: String,
+    pub item_enc: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An imported row matching an existing item by site and username, as shown to the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportConflict {
+    pub id: i64,
+    /// None if the existing item was deleted since the import
+    pub existing: Option<CredentialSummary>,
+    pub site: String,
+    pub username: String,
+    pub tags: Vec<String>,
+    /// Fields whose imported value differs from the existing one, e.g.
+    /// `password` or `custom_fields.pin`; never the values themselves
+    pub differences: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Key under which rows and items are considered the same account
+pub fn conflict_key(site: &str, username: &str) -> (String, String) {
+    (site.trim().to_lowercase(), username.trim().to_lowercase())
+}
+
+/// The existing item an imported row would duplicate, if any
+pub fn find_conflict<'a>(existing: &'a [Credential], item: &MappedItem) -> Option<&'a Credential> {
+    let key = conflict_key(&item.site, &item.username);
+    existing
+        .iter()
+        .find(|credential| conflict_key(&credential.site, &credential.username) == key)
+}
+
+/// Fields `incoming` would change in an item holding `secret` and `tags`
+pub fn differences(secret: &Secret, tags: &[String], incoming: &MappedItem) -> Vec<String> {
+    let mut fields = Vec::new();
+    if secret.password != incoming.secret.password {
+        fields.push("password".to_string());
+    }
+    if incoming.secret.notes.is_some() && secret.notes != incoming.secret.notes {
+        fields.push("notes".to_string());
+    }
+    if incoming.secret.totp.is_some() && secret.totp != incoming.secret.totp {
+        fields.push("totp".to_string());
+    }
+    let mut custom_fields: Vec<_> = incoming.secret.custom_fields.iter().collect();
+    custom_fields.sort();
+    for (name, value) in custom_fields {
+        if secret.custom_fields.get(name) != Some(value) {
+            fields.push(format!("custom_fields.{}", name));
+        }
+    }
+    if incoming.tags.iter().any(|tag| !tags.contains(tag)) {
+        fields.push("tags".to_string());
+    }
+    fields
+}
+
+/// Fills in what `secret` lacks from `incoming`; existing values win, including the password
+pub fn merge_fields(secret: &mut Secret, incoming: &Secret) {
+    if secret.notes.as_deref().unwrap_or_default().is_empty() {
+        secret.notes.clone_from(&incoming.notes);
+    }
+    if secret.totp.as_deref().unwrap_or_default().is_empty() {
+        secret.totp.clone_from(&incoming.totp);
+    }
+    for (name, value) in &incoming.custom_fields {
+        secret
+            .custom_fields
+            .entry(name.clone())
+            .or_insert_with(|| value.clone());
+    }
+}
+
+/// Adds the `incoming` tags `tags` does not have yet
+pub fn merge_tags(tags: &mut Vec<String>, incoming: &[String]) {
+    for tag in incoming {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+}
+
+/// Reads a source file into a table without touching the vault
+pub fn read_table(path: &Path, format: ImportFormat) -> AppResult<ImportTable> {
+    match format {
+        ImportFormat::Csv => read_csv(path),
+        ImportFormat::Json => read_json(path),
+    }
+}
+
+fn read_csv(path: &Path) -> AppResult<ImportTable> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::Headers)
+        .from_path(path)
+        .map_err(|e| AppError::Import(format!("Failed to open CSV: {}", e)))?;
+    let columns: Vec<String> = reader
+        .headers()
+        .map_err(|e| AppError::Import(format!("Failed to read CSV header: {}", e)))?
+        .iter()
+        .map(|h| h.trim_start_matches('\u{feff}').to_string())
+        .collect();
+
+    let mut rows = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = record
+            .map_err(|e| AppError::Import(format!("Invalid CSV row {}: {}", index + 1, e)))?;
+        let mut row: Vec<String> = record.iter().map(str::to_string).collect();
+        row.resize(columns.len(), String::new());
+        rows.push(row);
+    }
+    Ok(ImportTable { columns, rows })
+}
+
+fn read_json(path: &Path) -> AppResult<ImportTable> {
+    let document: serde_json::Value = serde_json::from_slice(&std::fs::read(path)?)?;
+    let items = match &document {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Object(map) => match map.get("items") {
+            Some(serde_json::Value::Array(items)) => items,
+            _ => {
+                return Err(AppError::Import(
+                    "JSON object has no \"items\" array".to_string(),
+                ))
+            }
+        },
+        _ => {
+            return Err(AppError::Import(
+                "JSON import must be an array of objects".to_string(),
+            ))
+        }
+    };
+
+    let mut flattened = Vec::new();
+    let mut columns = Vec::new();
+    let mut seen = BTreeSet::new();
+    for item in items {
+        let mut cells = Vec::new();
+        flatten_json("", item, &mut cells);
+        for (key, _) in &cells {
+            if seen.insert(key.clone()) {
+                columns.push(key.clone());
+            }
+        }
+        flattened.push(cells.into_iter().collect::<HashMap<_, _>>());
+    }
+
+    let rows = flattened
+        .into_iter()
+        .map(|mut cells| {
+            columns
+                .iter()
+                .map(|c| cells.remove(c).unwrap_or_default())
+                .collect()
+        })
+        .collect();
+    Ok(ImportTable { columns, rows })
+}
+
+/// Flattens nested objects into `parent.child` columns
+fn flatten_json(prefix: &str, value: &serde_json::Value, out: &mut Vec<(String, String)>) {
+    use serde_json::Value;
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let name = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten_json(&name, child, out);
+            }
+        }
+        // Bitwarden's `fields`: each becomes a `custom_fields.<name>` column
+        Value::Array(values) if !values.is_empty() && values.iter().all(is_named_field) => {
+            for field in values {
+                let name = field["name"].as_str().unwrap_or_default().trim();
+                if name.is_empty() {
+                    continue;
+                }
+                flatten_json(&format!("custom_fields.{}", name), &field["value"], out);
+            }
+        }
+        Value::Array(values) => {
+            let joined = values
+                .iter()
+                .map(|v| match v {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(";");
+            out.push((prefix.to_string(), joined));
+        }
+        Value::String(s) => out.push((prefix.to_string(), s.clone())),
+        Value::Null => out.push((prefix.to_string(), String::new())),
+        other => out.push((prefix.to_string(), other.to_string())),
+    }
+}
+
+/// Whether `value` is a `{"name": ..., "value": ...}` custom field object
+fn is_named_field(value: &serde_json::Value) -> bool {
+    value.get("name").is_some_and(serde_json::Value::is_string) && value.get("value").is_some()
+}
+
+/// Guesses the target field for a column from its name
+fn guess_field(column: &str) -> ImportField {
+    // Nested custom fields from our own JSON export keep their original name
+    if let Some(name) = column.strip_prefix("custom_fields.") {
+        return ImportField::CustomField(name.to_string());
+    }
+    // Nested JSON keys such as `login.password` read like Bitwarden's CSV columns
+    let normalized = column.trim().to_lowercase().replace([' ', '-', '.'], "_");
+    match normalized.as_str() {
+        "site" | "name" | "title" | "website" | "url" | "uri" | "login_uri" | "hostname" => {
+            ImportField::Site
+        }
+        "username" | "user" | "login" | "login_username" | "email" | "user_name" => {
+            ImportField::Username
+        }
+        "password" | "pass" | "login_password" | "pwd" => ImportField::Password,
+        "notes" | "note" | "extra" | "comments" | "comment" => ImportField::Notes,
+        "totp" | "otp" | "login_totp" | "otpauth" | "one_time_password" => ImportField::Totp,
+        "tags" | "tag" | "folder" | "group" | "grouping" | "category" => ImportField::Tags,
+        "uuid" | "id" | "created_at" | "updated_at" | "expires_at" | "favorite" | "type"
+        | "reprompt" => ImportField::Ignore,
+        "fields" | "custom_fields" => ImportField::CustomFields,
+        _ => ImportField::CustomField(column.to_string()),
+    }
+}
+
+/// Proposes a mapping; each core field is taken by the first matching column only
+pub fn propose_mapping(columns: &[String]) -> Vec<ColumnMapping> {
+    let mut taken = Vec::new();
+    columns
+        .iter()
+        .map(|column| {
+            let mut field = guess_field(column);
+            let is_single = !matches!(
+                field,
+                ImportField::CustomField(_)
+                    | ImportField::CustomFields
+                    | ImportField::Ignore
+                    | ImportField::Tags
+            );
+            if is_single {
+                if taken.contains(&field) {
+                    field = ImportField::CustomField(column.clone());
+                } else {
+                    taken.push(field.clone());
+                }
+            }
+            ColumnMapping {
+                column: column.clone(),
+                field,
+            }
+        })
+        .collect()
+}
+
+/// Reports mapping problems that would make every row fail
+pub fn mapping_warnings(mapping: &[ColumnMapping]) -> Vec<String> {
+    let has = |target: ImportField| mapping.iter().any(|m| m.field == target);
+    let mut warnings = Vec::new();
+    if !has(ImportField::Site) {
+        warnings.push("No column is mapped to the site".to_string());
+    }
+    if !has(ImportField::Password) {
+        warnings.push("No column is mapped to the password".to_string());
+    }
+    warnings
+}
+
+/// Builds the dry-run preview for a parsed table
+pub fn preview(table: &ImportTable) -> ImportPreview {
+    let mapping = propose_mapping(&table.columns);
+    ImportPreview {
+        columns: table.columns.clone(),
+        sample_rows: table.rows.iter().take(PREVIEW_ROWS).cloned().collect(),
+        total_rows: table.rows.len(),
+        warnings: mapping_warnings(&mapping),
+        suggested_tags: suggest_tags(table, &mapping),
+        mapping,
+    }
+}
+
+/// Tags for the rows `mapping` leaves untagged, from their site. Rows that
+/// would fail to import for other reasons, e.g. no password, still get one.
+fn suggest_tags(table: &ImportTable, mapping: &[ColumnMapping]) -> Vec<ImportTagSuggestion> {
+    let columns_of = |field: ImportField| -> Vec<usize> {
+        mapping
+            .iter()
+            .filter(|entry| entry.field == field)
+            .filter_map(|entry| table.columns.iter().position(|c| c == &entry.column))
+            .collect()
+    };
+    let Some(&site_column) = columns_of(ImportField::Site).first() else {
+        return Vec::new();
+    };
+    let tag_columns = columns_of(ImportField::Tags);
+    table
+        .rows
+        .iter()
+        .enumerate()
+        .filter(|(_, row)| {
+            tag_columns
+                .iter()
+                .all(|&column| row.get(column).is_none_or(|cell| cell.trim().is_empty()))
+        })
+        .filter_map(|(index, row)| {
+            let site = row.get(site_column)?.trim();
+            Some(ImportTagSuggestion {
+                row: index,
+                site: site.to_string(),
+                tag: categorize::classify(site)?.tag().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Applies a (possibly user-adjusted) mapping to every row
+pub fn apply_mapping(
+    table: &ImportTable,
+    mapping: &[ColumnMapping],
+) -> AppResult<Vec<Result<MappedItem, String>>> {
+    // Resolve column names to indices up front so typos fail loudly
+    let mut resolved = Vec::with_capacity(mapping.len());
+    for entry in mapping {
+        let index = table
+            .columns
+            .iter()
+            .position(|c| c == &entry.column)
+            .ok_or_else(|| AppError::Import(format!("Unknown column: {}", entry.column)))?;
+        resolved.push((index, &entry.field));
+    }
+
+    let items = table
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(row_index, row)| {
+            let mut item = MappedItem {
+                site: String::new(),
+                username: String::new(),
+                secret: Secret::default(),
+                tags: Vec::new(),
+                warnings: Vec::new(),
+            };
+            for (index, field) in &resolved {
+                let value = row[*index].trim();
+                if value.is_empty() {
+                    continue;
+                }
+                match field {
+                    ImportField::Site => item.site = value.to_string(),
+                    ImportField::Username => item.username = value.to_string(),
+                    ImportField::Password => item.secret.password = value.to_string(),
+                    ImportField::Notes => item.secret.notes = Some(value.to_string()),
+                    // A broken setup is left out rather than failing at code time
+                    ImportField::Totp => match totp::check_import(value) {
+                        Ok(totp) => {
+                            item.warnings.extend(
+                                totp.warning
+                                    .map(|warning| format!("Row {}: {}", row_index + 1, warning)),
+                            );
+                            item.secret.totp = Some(totp.value);
+                        }
+                        Err(AppError::InvalidInput(reason)) => item.warnings.push(format!(
+                            "Row {}: TOTP not imported: {}",
+                            row_index + 1,
+                            reason
+                        )),
+                        Err(e) => return Err(e.to_string()),
+                    },
+                    ImportField::Tags => item.tags.extend(
+                        value
+                            .split([',', ';'])
+                            .map(str::trim)
+                            .filter(|t| !t.is_empty())
+                            .map(str::to_string),
+                    ),
+                    ImportField::CustomField(name) => {
+                        item.secret
+                            .custom_fields
+                            .insert(name.clone(), value.to_string());
+                    }
+                    ImportField::CustomFields => {
+                        for line in value.lines() {
+                            let (name, field_value) = line.split_once(':').unwrap_or((line, ""));
+                            let name = name.trim();
+                            if !name.is_empty() {
+                                item.secret
+                                    .custom_fields
+                                    .insert(name.to_string(), field_value.trim().to_string());
+                            }
+                        }
+                    }
+                    ImportField::Ignore => {}
+                }
+            }
+
+            if item.site.is_empty() {
+                Err(format!("Row {}: missing site", row_index + 1))
+            } else if item.secret.password.is_empty() {
+                Err(format!("Row {}: missing password", row_index + 1))
+            } else {
+                item.tags = validation::normalize_tags(&item.tags);
+                // Rows the add command would refuse are skipped, not stored as is
+                validation::validate_credential(
+                    &item.site,
+                    &item.username,
+                    &item.secret,
+                    &item.tags,
+                )
+                .map_err(|e| format!("Row {}: {}", row_index + 1, e))?;
+                Ok(item)
+            }
+        })
+        .collect();
+    Ok(items)
+}
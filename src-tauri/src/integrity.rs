@@ -0,0 +1,64 @@
+//! A self-test of item encryption after unlock.
+//!
+//! Right after the vault is unlocked, a random sample of items is decrypted in
+//! the background. Decrypting checks both the key and the binding of each
+//! secret to its site and username, so an item that fails was damaged on disk
+//! or edited outside the app. Finding that out now, with an
+//! `integrity-warning` event, beats finding it out when the password is
+//! needed. Nothing is decrypted for display and no secret leaves the check.
+
+use rand::seq::index;
+use serde::{Deserialize, Serialize};
+
+/// Event an `IntegrityReport` with damaged items is emitted as
+pub const INTEGRITY_WARNING_EVENT: &str = "integrity-warning";
+/// Items decrypted after each unlock
+pub const INTEGRITY_SAMPLE_SIZE: usize = 25;
+
+/// Why an item failed the check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DamageKind {
+    /// The ciphertext does not decrypt with the key and the item's site and username
+    Decryption,
+    /// It decrypts, but not to a secret
+    Malformed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DamagedItem {
+    pub uuid: String,
+    pub site: String,
+    pub username: String,
+    pub kind: DamageKind,
+}
+
+/// Outcome of a self-test
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// Items in the vault
+    pub total: usize,
+    /// Items decrypted, damaged ones included
+    pub checked: usize,
+    /// Items that could not be tried for reasons unrelated to their data,
+    /// such as a shared collection this device is no longer a member of
+    pub skipped: usize,
+    pub damaged: Vec<DamagedItem>,
+}
+
+impl IntegrityReport {
+    pub fn is_intact(&self) -> bool {
+        self.damaged.is_empty()
+    }
+}
+
+/// Up to `size` of `items`, picked at random without repeats
+pub fn sample<T>(mut items: Vec<T>, size: usize) -> Vec<T> {
+    if items.len() <= size {
+        return items;
+    }
+    let mut picked = index::sample(&mut rand::thread_rng(), items.len(), size).into_vec();
+    // Taken from the back so earlier indices stay valid
+    picked.sort_unstable_by(|a, b| b.cmp(a));
+    picked.into_iter().map(|i| items.swap_remove(i)).collect()
+}
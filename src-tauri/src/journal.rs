@@ -0,0 +1,68 @@
+//! Write-ahead journal for operations that write many rows one at a time:
+//! imports, collection key rotation and collection deletion.
+//! An operation is recorded before its first step and removed after its last,
+//! so an entry still present at unlock means the app died halfway through.
+//! Recovery either undoes the finished steps or finishes the rest, whichever
+//! leaves the vault consistent without asking the user to repeat anything.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::collections::WrappedKey;
+
+/// A multi-step operation and what recovery needs to know about it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PendingOperation {
+    /// An import; rolled back by deleting the credentials it planned to add,
+    /// so that importing the file again does not create duplicates
+    Import { credential_uuids: Vec<String> },
+    /// Moving a collection's items back to the personal vault before deleting it;
+    /// resumed, since every item can still be decrypted with one key or the other
+    CollectionDeletion { collection_id: String },
+    /// Replacing a collection key after removing a member; resumed. Both keys are
+    /// wrapped for this vault so items left under the old key can still be moved over.
+    CollectionKeyRotation {
+        collection_id: String,
+        removed_member: String,
+        old_key: WrappedKey,
+        new_key: WrappedKey,
+    },
+}
+
+impl PendingOperation {
+    /// Name used in audit entries
+    pub fn name(&self) -> &'static str {
+        match self {
+            PendingOperation::Import { .. } => "import",
+            PendingOperation::CollectionDeletion { .. } => "collection_deletion",
+            PendingOperation::CollectionKeyRotation { .. } => "collection_key_rotation",
+        }
+    }
+}
+
+/// An operation that was started and has not finished yet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: String,
+    pub operation: PendingOperation,
+    pub started_at: DateTime<Utc>,
+}
+
+impl JournalEntry {
+    pub fn new(operation: PendingOperation, now: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            operation,
+            started_at: now,
+        }
+    }
+}
+
+/// What recovery did with the operations left over from a crash
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveryReport {
+    pub rolled_back: usize,
+    pub resumed: usize,
+}
@@ -0,0 +1,319 @@
+use aes::cipher::{block_padding::Pkcs7, BlockEncryptMut, KeyIvInit};
+use aes_gcm::aead::OsRng;
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::{Digest, Sha256, Sha512};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult, CryptoError};
+use crate::models::{Credential, Secret};
+
+type Aes256CbcEnc = cbc::Encryptor<aes::Aes256>;
+type HmacSha256 = Hmac<Sha256>;
+
+/// KDBX file signatures and the 4.0 format version
+const SIGNATURE_1: u32 = 0x9AA2_D903;
+const SIGNATURE_2: u32 = 0xB54B_FB67;
+const FORMAT_VERSION: u32 = 0x0004_0000;
+
+/// Well-known KeePass UUIDs
+const CIPHER_AES256: [u8; 16] = [
+    0x31, 0xC1, 0xF2, 0xE6, 0xBF, 0x71, 0x43, 0x50, 0xBE, 0x58, 0x05, 0x21, 0x6A, 0xFC, 0x5A, 0xFF,
+];
+const KDF_ARGON2ID: [u8; 16] = [
+    0x9E, 0x29, 0x8B, 0x19, 0x56, 0xDB, 0x47, 0x73, 0xB2, 0x3D, 0xFC, 0x3E, 0xC6, 0xF0, 0xA1, 0xE6,
+];
+
+/// Outer header field ids
+const HEADER_END: u8 = 0;
+const HEADER_CIPHER_ID: u8 = 2;
+const HEADER_COMPRESSION: u8 = 3;
+const HEADER_MASTER_SEED: u8 = 4;
+const HEADER_ENCRYPTION_IV: u8 = 7;
+const HEADER_KDF_PARAMETERS: u8 = 11;
+
+/// Inner header field ids
+const INNER_HEADER_END: u8 = 0;
+const INNER_RANDOM_STREAM_ID: u8 = 1;
+const INNER_RANDOM_STREAM_KEY: u8 = 2;
+const INNER_STREAM_CHACHA20: u32 = 3;
+
+/// Payload is split into HMAC-authenticated blocks of this size
+const BLOCK_SIZE: usize = 1024 * 1024;
+
+/// Seconds between 0001-01-01 (KDBX epoch) and 1970-01-01
+const KDBX_EPOCH_OFFSET: i64 = 62_135_596_800;
+
+/// Argon2id parameters written into the exported database
+#[derive(Debug, Clone, Copy)]
+pub struct KdfParams {
+    pub memory_kb: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        // Same cost as the vault's own default Argon2 settings
+        Self {
+            memory_kb: 65536,
+            iterations: 3,
+            parallelism: 4,
+        }
+    }
+}
+
+/// Serializes credentials into an AES-256 / Argon2id protected KeePass 4.0 database
+pub fn write_database(
+    entries: &[(Credential, Secret)],
+    password: &str,
+    kdf: KdfParams,
+) -> AppResult<Vec<u8>> {
+    let mut master_seed = [0u8; 32];
+    let mut encryption_iv = [0u8; 16];
+    let mut kdf_salt = [0u8; 32];
+    let mut inner_stream_key = [0u8; 64];
+    OsRng.fill_bytes(&mut master_seed);
+    OsRng.fill_bytes(&mut encryption_iv);
+    OsRng.fill_bytes(&mut kdf_salt);
+    OsRng.fill_bytes(&mut inner_stream_key);
+
+    // Outer header
+    let mut header = Vec::new();
+    header.extend_from_slice(&SIGNATURE_1.to_le_bytes());
+    header.extend_from_slice(&SIGNATURE_2.to_le_bytes());
+    header.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    write_header_field(&mut header, HEADER_CIPHER_ID, &CIPHER_AES256);
+    write_header_field(&mut header, HEADER_COMPRESSION, &0u32.to_le_bytes());
+    write_header_field(&mut header, HEADER_MASTER_SEED, &master_seed);
+    write_header_field(&mut header, HEADER_ENCRYPTION_IV, &encryption_iv);
+    write_header_field(
+        &mut header,
+        HEADER_KDF_PARAMETERS,
+        &kdf_parameters(&kdf_salt, kdf),
+    );
+    write_header_field(&mut header, HEADER_END, b"\r\n\r\n");
+
+    // Key schedule
+    let transformed_key = transform_key(password, &kdf_salt, kdf)?;
+    let encryption_key = Sha256::new()
+        .chain_update(master_seed)
+        .chain_update(transformed_key)
+        .finalize();
+    let hmac_base_key = Sha512::new()
+        .chain_update(master_seed)
+        .chain_update(transformed_key)
+        .chain_update([1u8])
+        .finalize();
+
+    let mut output = header.clone();
+    output.extend_from_slice(&Sha256::digest(&header));
+    output.extend_from_slice(&hmac_sha256(
+        &block_hmac_key(&hmac_base_key, u64::MAX),
+        &[&header],
+    )?);
+
+    // Inner header + XML, encrypted as one CBC stream
+    let mut payload = Vec::new();
+    write_header_field(
+        &mut payload,
+        INNER_RANDOM_STREAM_ID,
+        &INNER_STREAM_CHACHA20.to_le_bytes(),
+    );
+    write_header_field(&mut payload, INNER_RANDOM_STREAM_KEY, &inner_stream_key);
+    write_header_field(&mut payload, INNER_HEADER_END, &[]);
+    payload.extend_from_slice(build_xml(entries).as_bytes());
+
+    let ciphertext = Aes256CbcEnc::new_from_slices(&encryption_key, &encryption_iv)
+        .map_err(|e| CryptoError::Encryption(format!("Invalid KDBX cipher parameters: {}", e)))?
+        .encrypt_padded_vec_mut::<Pkcs7>(&payload);
+
+    // HMAC block stream, terminated by an empty block
+    let blocks = ciphertext
+        .chunks(BLOCK_SIZE)
+        .chain(std::iter::once(&[][..]));
+    for (index, chunk) in (0u64..).zip(blocks) {
+        let size = (chunk.len() as i32).to_le_bytes();
+        let mac = hmac_sha256(
+            &block_hmac_key(&hmac_base_key, index),
+            &[&index.to_le_bytes(), &size, chunk],
+        )?;
+        output.extend_from_slice(&mac);
+        output.extend_from_slice(&size);
+        output.extend_from_slice(chunk);
+    }
+
+    Ok(output)
+}
+
+/// Appends a `(id: u8, size: u32, data)` header field
+fn write_header_field(buf: &mut Vec<u8>, id: u8, data: &[u8]) {
+    buf.push(id);
+    buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    buf.extend_from_slice(data);
+}
+
+/// Encodes the Argon2id parameters as a KeePass VariantDictionary
+fn kdf_parameters(salt: &[u8], kdf: KdfParams) -> Vec<u8> {
+    const TYPE_UINT32: u8 = 0x04;
+    const TYPE_UINT64: u8 = 0x05;
+    const TYPE_BYTES: u8 = 0x42;
+
+    fn entry(buf: &mut Vec<u8>, value_type: u8, key: &str, value: &[u8]) {
+        buf.push(value_type);
+        buf.extend_from_slice(&(key.len() as u32).to_le_bytes());
+        buf.extend_from_slice(key.as_bytes());
+        buf.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        buf.extend_from_slice(value);
+    }
+
+    let mut buf = 0x0100u16.to_le_bytes().to_vec();
+    entry(&mut buf, TYPE_BYTES, "$UUID", &KDF_ARGON2ID);
+    entry(&mut buf, TYPE_BYTES, "S", salt);
+    entry(&mut buf, TYPE_UINT32, "P", &kdf.parallelism.to_le_bytes());
+    entry(
+        &mut buf,
+        TYPE_UINT64,
+        "M",
+        &(kdf.memory_kb as u64 * 1024).to_le_bytes(),
+    );
+    entry(
+        &mut buf,
+        TYPE_UINT64,
+        "I",
+        &(kdf.iterations as u64).to_le_bytes(),
+    );
+    entry(&mut buf, TYPE_UINT32, "V", &0x13u32.to_le_bytes());
+    buf.push(0);
+    buf
+}
+
+/// Derives the transformed key from a password-only composite key
+fn transform_key(password: &str, salt: &[u8], kdf: KdfParams) -> AppResult<[u8; 32]> {
+    let composite_key = Sha256::digest(Sha256::digest(password.as_bytes()));
+    let params = Params::new(kdf.memory_kb, kdf.iterations, kdf.parallelism, Some(32))
+        .map_err(|e| CryptoError::KeyDerivation(format!("Invalid KDBX KDF parameters: {}", e)))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(&composite_key, salt, &mut key)
+        .map_err(|e| CryptoError::KeyDerivation(format!("KDBX key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+fn block_hmac_key(hmac_base_key: &[u8], index: u64) -> Vec<u8> {
+    Sha512::new()
+        .chain_update(index.to_le_bytes())
+        .chain_update(hmac_base_key)
+        .finalize()
+        .to_vec()
+}
+
+fn hmac_sha256(key: &[u8], parts: &[&[u8]]) -> AppResult<Vec<u8>> {
+    let mut mac = HmacSha256::new_from_slice(key)
+        .map_err(|e| CryptoError::Encryption(format!("Invalid HMAC key: {}", e)))?;
+    for part in parts {
+        mac.update(part);
+    }
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+/// Builds the KeePass XML document with one group holding every entry
+fn build_xml(entries: &[(Credential, Secret)]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\" standalone=\"yes\"?>\n<KeePassFile>\n",
+    );
+    xml.push_str("<Meta><Generator>Secret Plan</Generator>");
+    xml.push_str("<DatabaseName>Secret Plan export</DatabaseName></Meta>\n");
+    xml.push_str("<Root><Group>");
+    xml.push_str(&format!(
+        "<UUID>{}</UUID><Name>Secret Plan</Name>\n",
+        BASE64.encode(Uuid::new_v4().as_bytes())
+    ));
+
+    for (credential, secret) in entries {
+        let entry_uuid = Uuid::parse_str(&credential.uuid).unwrap_or_else(|_| Uuid::new_v4());
+        xml.push_str("<Entry>");
+        xml.push_str(&format!(
+            "<UUID>{}</UUID>",
+            BASE64.encode(entry_uuid.as_bytes())
+        ));
+        xml.push_str(&format!(
+            "<Tags>{}</Tags>",
+            escape_xml(&credential.tags.join(";"))
+        ));
+        xml.push_str(&format!(
+            "<Times><CreationTime>{}</CreationTime><LastModificationTime>{}</LastModificationTime>",
+            kdbx_time(credential.created_at),
+            kdbx_time(credential.updated_at)
+        ));
+        match credential.expires_at {
+            Some(expires_at) => xml.push_str(&format!(
+                "<ExpiryTime>{}</ExpiryTime><Expires>True</Expires></Times>",
+                kdbx_time(expires_at)
+            )),
+            None => xml.push_str("<Expires>False</Expires></Times>"),
+        }
+
+        push_string(&mut xml, "Title", &credential.site);
+        push_string(&mut xml, "UserName", &credential.username);
+        push_string(&mut xml, "Password", &secret.password);
+        push_string(&mut xml, "URL", &credential.site);
+        push_string(&mut xml, "Notes", secret.notes.as_deref().unwrap_or(""));
+        if let Some(totp) = &secret.totp {
+            // KeePassXC reads TOTP settings from the "otp" attribute
+            push_string(&mut xml, "otp", totp);
+        }
+        let mut custom_fields: Vec<_> = secret.custom_fields.iter().collect();
+        custom_fields.sort();
+        for (key, value) in custom_fields {
+            push_string(&mut xml, key, value);
+        }
+        xml.push_str("</Entry>\n");
+    }
+
+    xml.push_str("</Group></Root>\n</KeePassFile>\n");
+    xml
+}
+
+fn push_string(xml: &mut String, key: &str, value: &str) {
+    xml.push_str(&format!(
+        "<String><Key>{}</Key><Value>{}</Value></String>",
+        escape_xml(key),
+        escape_xml(value)
+    ));
+}
+
+/// KDBX 4 stores times as base64 little-endian seconds since 0001-01-01
+fn kdbx_time(time: DateTime<Utc>) -> String {
+    BASE64.encode((time.timestamp() + KDBX_EPOCH_OFFSET).to_le_bytes())
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tab/newline are not valid XML 1.0
+            c if c.is_control() && c != '\t' && c != '\n' && c != '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Rejects empty KDBX passwords before any work is done
+pub fn validate_password(password: &str) -> AppResult<()> {
+    if password.is_empty() {
+        return Err(AppError::Export(
+            "A password is required for KDBX exports".to_string(),
+        ));
+    }
+    Ok(())
+}
@@ -1,29 +1,188 @@
 // Export modules
+pub mod api_keys;
+pub mod audit_archive;
+pub mod audit_export;
+pub mod audit_level;
+pub mod autotype;
+pub mod backup;
+pub mod blocking;
+pub mod breach_list;
+pub mod breach_queue;
+pub mod bridge;
+pub mod bulk_session;
+pub mod bundle;
+pub mod cards;
+pub mod categorize;
+pub mod collections;
+pub mod compact;
 pub mod crypto;
+pub mod deeplink;
+pub mod devices;
+pub mod diagnostics;
+pub mod domain_info;
+pub mod email_monitor;
+pub mod emergency;
 pub mod error;
+pub mod export;
+pub mod features;
+pub mod generator;
+pub mod hardening;
 pub mod hibp;
+pub mod i18n;
+pub mod identity;
+pub mod idle;
+pub mod importer;
+pub mod integrity;
+pub mod journal;
+pub mod kdbx;
+pub mod logging;
+pub mod maintenance;
+pub mod master_password;
 pub mod models;
+pub mod net;
+pub mod nonce_audit;
+pub mod notifications;
+pub mod password_policy;
+pub mod performance;
+pub mod portable;
+pub mod qr_transfer;
+pub mod query;
+pub mod recovery_codes;
+pub mod remediation;
+pub mod remote;
+pub mod rotation;
+pub mod search_index;
+pub mod secret_scan;
+pub mod session;
+pub mod settings_profile;
+pub mod setup;
+pub mod shares;
+pub mod site_match;
+pub mod sql_filter;
 pub mod sqlite_repo;
+pub mod stats;
+pub mod status;
 pub mod strength;
+pub mod subkeys;
 #[cfg(test)]
 pub mod tests;
+pub mod text_match;
+pub mod totp;
 pub mod traits;
+pub mod travel;
+pub mod undo;
+pub mod unlock;
+pub mod updates;
+pub mod validation;
 pub mod vault;
+pub mod views;
+pub mod watch;
+pub mod wifi;
 
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use tauri::{AppHandle, Manager, State};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_notification::NotificationExt;
+use tracing::warn;
 
-use hibp::HibpService;
-use models::{AppSettings, BreachState, Credential, Secret};
-use vault::{CredentialFilter, VaultManager};
+use api_keys::{ApiKeyInput, ApiKeyPreset};
+use audit_archive::{AuditArchiveRange, AuditArchiveReport, DEFAULT_AUDIT_ARCHIVE_AGE_DAYS};
+use audit_export::AuditVerification;
+use autotype::{AutotypeRequest, AutotypeTarget, SystemKeyboard};
+use backup::{BackupJob, BackupOutcome};
+use breach_list::{BreachListImport, BreachListSource, LocalBreachMatch};
+use breach_queue::BreachCheckQueue;
+use bridge::{BridgeMessage, BridgeResponse, PendingSave};
+use bulk_session::BulkSession;
+use cards::{CardInput, CardNumberCheck};
+use collections::{Collection, WrappedKey};
+use compact::{CompactProgress, CompactReport};
+use deeplink::{DeepLinkRequest, PendingDeepLink};
+use devices::{Device, DeviceIdentity};
+use diagnostics::DiagnosticsReport;
+use domain_info::DomainInfo;
+use email_monitor::{AccountBreachClient, BreachNotification};
+use emergency::EmergencyContact;
+use error::{AppError, AppResult, CommandError, ResultExt};
+use export::{ExportFormat, ExportReport, ExportScope};
+use features::FeatureState;
+use generator::{GeneratorConstraints, GeneratorOptions};
+use hardening::HardeningStatus;
+use hibp::{
+    BreachCheckProgress, BreachCheckResult, BreachCheckSummary, BreachPrecheck, HibpService,
+};
+use i18n::{Locale, LocalizedAuditEntry, Message};
+use identity::{AutofillProfile, IdentityInput};
+use idle::{IdleSource, SystemIdle};
+use importer::{
+    ColumnMapping, ConflictResolution, ImportConflict, ImportFormat, ImportPreview, ImportReport,
+};
+use integrity::{INTEGRITY_SAMPLE_SIZE, INTEGRITY_WARNING_EVENT};
+use logging::{LogEntry, LogLevel};
+use maintenance::{MaintenanceReport, MAINTENANCE_IDLE};
+use master_password::{MasterPasswordReminder, MASTER_PASSWORD_REMINDER_EVENT};
+use models::{
+    AppSettings, BreachState, Credential, CredentialKind, CredentialSummary, IdleDetection, Secret,
+    SecretField, StorageProfile, UiPreferences,
+};
+use net::Network;
+use nonce_audit::NonceAuditReport;
+use notifications::{Notification, NotificationCategory, NotificationSink};
+use password_policy::PolicyCheck;
+use portable::DataLocation;
+use qr_transfer::CredentialQr;
+use remediation::{RemediationItem, RemediationOutcome, DEFAULT_MAX_PASSWORD_AGE_DAYS};
+use remote::RemoteRepository;
+use rotation::{RotationItem, RotationStatus};
+use search_index::{QuickSearchHit, TagSuggestion};
+use secret_scan::DetectedSecret;
+use settings_profile::SettingsProfileImport;
+use setup::{VaultSetup, VaultSetupOptions, VaultStatus};
+use shares::{ShareDestination, ShareLink, ShareRecord, ShareRelay};
+use site_match::UrlMatch;
+use sqlite_repo::SqliteRepository;
+use stats::{SecuritySnapshot, TrendRange, VaultStatistics};
+use status::{AppStatus, StatusCache};
+use strength::MasterPasswordReport;
+use strength::SimpleStrengthCalculator;
+use strength::Wordlists;
+use strength::{StrengthRecalcProgress, STRENGTH_RECALC_BATCH};
+use text_match::MatchMode;
+use totp::TotpValidation;
+use traits::SettingsRepository;
+use travel::{TravelActivation, TravelModeStatus};
+use undo::{BulkEdit, OperationSummary, UndoStatus};
+use unlock::{UnlockProgress, UNLOCK_PROGRESS_EVENT};
+use updates::{DownloadedUpdate, UpdateCheck, UpdateChecker};
+use vault::{CredentialFilter, SmartFilters, VaultManager};
+use views::{SavedView, ViewConfig};
+use wifi::{WifiInput, WifiQr};
 
 // App state that will be shared across commands
 pub struct AppState {
     vault_manager: Option<VaultManager>,
     hibp_service: HibpService,
     app_handle: Option<AppHandle>,
+    // Set to stop a running batch breach check
+    breach_check_cancel: Arc<AtomicBool>,
+    // Set to abandon the key derivation of a running unlock
+    unlock_cancel: Arc<AtomicBool>,
+    // Shares lookups of the same password and orders breach state updates
+    breach_queue: Arc<BreachCheckQueue>,
+    // Set while the strength recalculation job runs, so only one runs at a time
+    strength_recalc_running: Arc<AtomicBool>,
+    // Last activity the app saw itself, for auto-lock without OS idle time
+    last_activity: Instant,
+    // Links the app was opened with, until the user accepts or rejects them
+    deep_links: Vec<PendingDeepLink>,
+    // Day the scheduler last reminded of expiring items
+    last_expiry_reminder: Option<chrono::NaiveDate>,
+    // Last `get_app_status` result, handed out again to frequent callers
+    status_cache: StatusCache,
 }
 
 impl Default for AppState {
@@ -38,6 +197,14 @@ impl AppState {
             vault_manager: None,
             hibp_service: HibpService::new(),
             app_handle: None,
+            breach_check_cancel: Arc::new(AtomicBool::new(false)),
+            unlock_cancel: Arc::new(AtomicBool::new(false)),
+            breach_queue: Arc::new(BreachCheckQueue::default()),
+            strength_recalc_running: Arc::new(AtomicBool::new(false)),
+            last_activity: Instant::now(),
+            deep_links: Vec::new(),
+            last_expiry_reminder: None,
+            status_cache: StatusCache::default(),
         }
     }
 
@@ -46,11 +213,23 @@ impl AppState {
     }
 }
 
+// Where the vault lives: next to the executable in portable mode, otherwise the
+// platform's app data directory
+fn data_location(app_handle: &AppHandle) -> Result<DataLocation, CommandError> {
+    let exe = std::env::current_exe().context("error.data_location")?;
+    let installed_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Other(e.to_string()))
+        .context("error.data_location")?;
+    DataLocation::detect(exe.parent(), installed_dir).context("error.data_location")
+}
+
 // Helper function to get the vault database path
-fn get_vault_path(app_handle: &AppHandle) -> PathBuf {
-    let app_dir = app_handle.path().app_data_dir().unwrap();
-    std::fs::create_dir_all(&app_dir).unwrap();
-    app_dir.join("vault.db")
+fn get_vault_path(app_handle: &AppHandle) -> Result<PathBuf, CommandError> {
+    let location = data_location(app_handle)?;
+    std::fs::create_dir_all(&location.dir).context("error.data_location")?;
+    Ok(location.vault_path())
 }
 
 // ========== Tauri Commands ==========
@@ -59,400 +238,3940 @@ fn get_vault_path(app_handle: &AppHandle) -> PathBuf {
 async fn initialize_vault(
     app_handle: AppHandle,
     state: State<'_, Mutex<AppState>>,
-) -> Result<bool, String> {
-    let vault_path = get_vault_path(&app_handle);
-    let vault_exists = vault_path.exists();
+) -> Result<bool, CommandError> {
     let mut state_guard = state.lock().unwrap();
     if state_guard.vault_manager.is_none() {
-        let settings = AppSettings::default();
-        use crate::sqlite_repo::SqliteRepository;
-        use crate::strength::SimpleStrengthCalculator;
-        use std::sync::Arc;
-        let repo = Arc::new(
-            SqliteRepository::new(&vault_path).map_err(|e| format!("Failed to open DB: {}", e))?,
-        );
-        let strength = Arc::new(SimpleStrengthCalculator);
-        let vault_manager =
-            VaultManager::new(repo.clone(), repo.clone(), repo.clone(), strength, settings)
-                .map_err(|e| format!("Failed to initialize vault: {}", e))?;
+        let vault_manager = open_vault_manager(&get_vault_path(&app_handle)?)?;
+        // Apply the saved UI language before anything is rendered
+        if let Some(locale) = vault_manager
+            .get_ui_preferences()
+            .ok()
+            .and_then(|preferences| preferences.locale)
+            .and_then(|tag| Locale::parse(&tag))
+        {
+            i18n::set_locale(locale);
+        }
         state_guard.vault_manager = Some(vault_manager);
         state_guard.set_app_handle(app_handle);
     }
-    Ok(vault_exists)
+    // An empty database left by an abandoned setup does not count as a vault
+    let status = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?
+        .vault_status()
+        .context("error.initialize_vault")?;
+    Ok(status != VaultStatus::NotInitialized)
+}
+
+#[tauri::command]
+async fn get_vault_status(state: State<'_, Mutex<AppState>>) -> Result<VaultStatus, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+    vault_manager
+        .vault_status()
+        .context("error.get_vault_status")
+}
+
+// Everything the window chrome shows, see `status`
+#[tauri::command]
+async fn get_app_status(state: State<'_, Mutex<AppState>>) -> Result<AppStatus, CommandError> {
+    let mut state_guard = state.lock().unwrap();
+    let AppState {
+        vault_manager,
+        status_cache,
+        ..
+    } = &mut *state_guard;
+    let vault_manager = vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    status_cache
+        .get_or_gather(vault_manager.is_unlocked(), Instant::now(), || {
+            vault_manager.app_status()
+        })
+        .context("error.get_app_status")
+}
+
+// Where the vault and its files are kept, and whether that is portable mode
+#[tauri::command]
+async fn get_data_location(app_handle: AppHandle) -> Result<DataLocation, CommandError> {
+    data_location(&app_handle)
+}
+
+// Which memory protections are in effect: no core dumps, no debugger, no
+// injection, and the vault key kept out of swap
+#[tauri::command]
+async fn get_hardening_status(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<HardeningStatus, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+    Ok(vault_manager.hardening_status())
+}
+
+// Whether the vault key is held by an ephemeral session process, see `session`
+#[tauri::command]
+async fn in_ephemeral_session(state: State<'_, Mutex<AppState>>) -> Result<bool, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+    Ok(vault_manager.in_ephemeral_session())
+}
+
+// Builds a VaultManager backed by the SQLite database at `vault_path`
+fn open_vault_manager(vault_path: &Path) -> Result<VaultManager, CommandError> {
+    let settings = AppSettings::default();
+    let repo = Arc::new(SqliteRepository::new(vault_path).context("error.open_database")?);
+    let strength = Arc::new(SimpleStrengthCalculator);
+    let profile: StorageProfile = match repo.get_storage_profile().context("error.open_database")? {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(AppError::from)
+            .context("error.open_database")?,
+        None => StorageProfile::default(),
+    };
+    let device = DeviceIdentity::load_or_create(&vault_path.with_file_name(devices::IDENTITY_FILE))
+        .context("error.initialize_vault")?;
+    let data_dir = vault_path.parent().unwrap_or(Path::new("")).to_path_buf();
+    if let StorageProfile::Remote { url, token } = profile {
+        return open_remote_vault_manager(repo, &url, &token, strength, settings, device)
+            .map(|vault_manager| vault_manager.with_data_dir(data_dir));
+    }
+    VaultManager::new(repo.clone(), repo.clone(), repo.clone(), strength, settings)
+        .map(|vault_manager| {
+            vault_manager
+                .with_data_dir(data_dir)
+                .with_maintenance_repo(repo.clone())
+                .with_device_repo(repo.clone(), device)
+                .with_stats_repo(repo.clone())
+                .with_breach_list_repo(repo.clone())
+                .with_import_conflict_repo(repo.clone())
+                .with_audit_archive_repo(repo.clone())
+                .with_collection_repo(repo.clone())
+                .with_share_repo(repo.clone())
+                .with_emergency_repo(repo.clone())
+                .with_travel_repo(repo.clone())
+                .with_journal_repo(repo.clone())
+                .with_external_change_source(repo.clone())
+                .with_audit_filter(repo.audit_filter())
+                .with_transaction_repo(repo)
+        })
+        .context("error.initialize_vault")
+}
+
+// Builds a VaultManager whose credentials live on a vault server. Features that
+// work on the local item table directly (backups, compaction, travel mode,
+// transactions) are left out.
+fn open_remote_vault_manager(
+    repo: Arc<SqliteRepository>,
+    url: &str,
+    token: &str,
+    strength: Arc<SimpleStrengthCalculator>,
+    settings: AppSettings,
+    device: DeviceIdentity,
+) -> Result<VaultManager, CommandError> {
+    let remote = Arc::new(
+        RemoteRepository::new(url, token, repo.clone())
+            .context("error.open_database")?
+            .with_audit_filter(repo.audit_filter()),
+    );
+    VaultManager::new(
+        remote.clone(),
+        repo.clone(),
+        repo.clone(),
+        strength,
+        settings,
+    )
+    .map(|vault_manager| {
+        vault_manager
+            .with_async_credential_repo(remote.clone())
+            .with_encrypted_storage(remote)
+            .with_stats_repo(repo.clone())
+            .with_breach_list_repo(repo.clone())
+            .with_import_conflict_repo(repo.clone())
+            .with_audit_archive_repo(repo.clone())
+            .with_collection_repo(repo.clone())
+            .with_share_repo(repo.clone())
+            .with_emergency_repo(repo.clone())
+            .with_device_repo(repo.clone(), device)
+            .with_external_change_source(repo.clone())
+            .with_audit_filter(repo.audit_filter())
+            .with_journal_repo(repo)
+    })
+    .context("error.initialize_vault")
+}
+
+// How often the background scheduler checks whether a backup or snapshot is due
+const SCHEDULER_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+// How often the auto-lock task compares idle time with the timeout
+const AUTO_LOCK_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+// How often the vault file is checked for changes made by other programs
+const VAULT_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+// How often the maintenance task checks whether the user is idle
+const MAINTENANCE_CHECK_INTERVAL: Duration = Duration::from_secs(300);
+
+// How long item writes have to pause before `vault-stats-changed` is sent
+const STATS_EVENT_DEBOUNCE: Duration = Duration::from_millis(750);
+
+// Time since the last input as the OS reports it. On Linux this runs a helper
+// that may take up to two seconds, so it runs on a blocking thread rather than
+// a runtime thread.
+async fn system_idle_time() -> Option<Duration> {
+    tokio::task::spawn_blocking(|| SystemIdle.idle_time())
+        .await
+        .ok()
+        .flatten()
+}
+
+// Background task locking the vault after the configured period of inactivity
+fn spawn_auto_lock(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(AUTO_LOCK_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let state = app_handle.state::<Mutex<AppState>>();
+            let (mode, timeout, app_idle) = {
+                let state_guard = state.lock().unwrap();
+                match state_guard.vault_manager.as_ref() {
+                    Some(vault_manager) if vault_manager.is_unlocked() => {
+                        match vault_manager.get_settings() {
+                            Ok(settings) => (
+                                settings.idle_detection,
+                                settings.auto_lock_timeout,
+                                state_guard.last_activity.elapsed(),
+                            ),
+                            Err(_) => continue,
+                        }
+                    }
+                    _ => continue,
+                }
+            };
+            if timeout == 0 {
+                continue;
+            }
+
+            // Query the OS without holding the state lock
+            let os_idle = match mode {
+                IdleDetection::System => system_idle_time().await,
+                IdleDetection::App => None,
+            };
+            let idle = idle::effective_idle(mode, app_idle, os_idle);
+            if !idle::should_lock(timeout, idle) {
+                continue;
+            }
+
+            let mut state_guard = state.lock().unwrap();
+            if let Some(vault_manager) = state_guard.vault_manager.as_mut() {
+                if vault_manager.is_unlocked() && vault_manager.lock().is_ok() {
+                    let _ = app_handle.emit("vault-auto-locked", idle.as_secs());
+                }
+            }
+        }
+    });
+}
+
+// Background task running the maintenance that is due, see `maintenance`,
+// while the vault is unlocked and the user is idle
+fn spawn_maintenance(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(MAINTENANCE_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let state = app_handle.state::<Mutex<AppState>>();
+            let (mode, app_idle) = {
+                let state_guard = state.lock().unwrap();
+                match state_guard.vault_manager.as_ref() {
+                    Some(vault_manager) if vault_manager.is_unlocked() => {
+                        match vault_manager.get_settings() {
+                            Ok(settings) => {
+                                (settings.idle_detection, state_guard.last_activity.elapsed())
+                            }
+                            Err(_) => continue,
+                        }
+                    }
+                    _ => continue,
+                }
+            };
+            let os_idle = match mode {
+                IdleDetection::System => system_idle_time().await,
+                IdleDetection::App => None,
+            };
+            if idle::effective_idle(mode, app_idle, os_idle) < MAINTENANCE_IDLE {
+                continue;
+            }
+
+            let report = {
+                let state_guard = state.lock().unwrap();
+                match state_guard.vault_manager.as_ref() {
+                    Some(vault_manager) if vault_manager.is_unlocked() => {
+                        vault_manager.run_maintenance(false, chrono::Utc::now())
+                    }
+                    _ => continue,
+                }
+            };
+            if let Ok(report) = report {
+                if !report.ran.is_empty() {
+                    let _ = app_handle.emit("maintenance-completed", report);
+                }
+            }
+        }
+    });
+}
+
+// Background task that locks the vault when another program (e.g. a sync client)
+// changes the vault file, and asks the frontend to reload it
+fn spawn_vault_watcher(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(VAULT_WATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let state = app_handle.state::<Mutex<AppState>>();
+            let change = match state.lock().unwrap().vault_manager.as_mut() {
+                Some(vault_manager) => vault_manager.check_external_changes(),
+                None => continue,
+            };
+            if let Ok(Some(change)) = change {
+                let _ = app_handle.emit("vault-externally-modified", change);
+                notify(
+                    &app_handle,
+                    NotificationCategory::SyncConflict,
+                    Message::new("notification.sync_conflict_title"),
+                    Message::new("notification.sync_conflict_body"),
+                );
+            }
+        }
+    });
+}
+
+// Background task sending `vault-stats-changed` with fresh statistics once item
+// writes have paused for one `STATS_EVENT_DEBOUNCE` tick, so a bulk import sends
+// one event rather than one per item. Nothing is sent if the numbers came out the same.
+fn spawn_stats_notifier(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(STATS_EVENT_DEBOUNCE);
+        // Write count at the previous tick, and the one the last statistics were taken at
+        let mut seen = None;
+        let mut computed = None;
+        let mut sent: Option<VaultStatistics> = None;
+        loop {
+            interval.tick().await;
+            let state = app_handle.state::<Mutex<AppState>>();
+            let state_guard = state.lock().unwrap();
+            let vault_manager = match state_guard.vault_manager.as_ref() {
+                Some(vault_manager) if vault_manager.is_unlocked() => vault_manager,
+                _ => {
+                    (seen, computed, sent) = (None, None, None);
+                    continue;
+                }
+            };
+            let changes = Some(vault_manager.item_changes());
+            if changes == computed {
+                continue;
+            }
+            if changes != seen {
+                seen = changes;
+                continue;
+            }
+            computed = changes;
+            let Ok(statistics) = vault_manager.get_vault_statistics() else {
+                continue;
+            };
+            if sent.as_ref() != Some(&statistics) {
+                let _ = app_handle.emit("vault-stats-changed", &statistics);
+                sent = Some(statistics);
+            }
+        }
+    });
+}
+
+// Recalculates every stored strength in batches, taking the state lock once per
+// batch so other commands are not held up. Reports `strength-recalc-progress` after
+// each batch and `strength-recalc-complete` (or `strength-recalc-failed`) at the end.
+// Returns false if a recalculation is already running.
+fn spawn_strength_recalculation<R: Runtime>(app_handle: AppHandle<R>, state: &AppState) -> bool {
+    let running = state.strength_recalc_running.clone();
+    if running.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<Mutex<AppState>>();
+        match recalculate_all_strengths_in_batches(&app_handle, &state).await {
+            Ok(progress) => {
+                let _ = app_handle.emit("strength-recalc-complete", progress);
+            }
+            Err(e) => {
+                let _ = app_handle.emit("strength-recalc-failed", e.to_string());
+            }
+        }
+        running.store(false, Ordering::SeqCst);
+    });
+    true
+}
+
+async fn recalculate_all_strengths_in_batches<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    state: &Mutex<AppState>,
+) -> AppResult<StrengthRecalcProgress> {
+    // Locking the vault mid-run stops the job at the next batch
+    let with_vault = |body: &mut dyn FnMut(&VaultManager) -> AppResult<()>| -> AppResult<()> {
+        let state_guard = state.lock().unwrap();
+        match state_guard.vault_manager.as_ref() {
+            Some(vault_manager) if vault_manager.is_unlocked() => body(vault_manager),
+            _ => Err(AppError::VaultLocked),
+        }
+    };
+
+    let mut uuids = Vec::new();
+    with_vault(&mut |vault_manager| {
+        uuids = vault_manager
+            .list_credentials(None)?
+            .into_iter()
+            .map(|credential| credential.uuid)
+            .collect();
+        Ok(())
+    })?;
+
+    let mut progress = StrengthRecalcProgress {
+        total: uuids.len(),
+        ..Default::default()
+    };
+    for batch in uuids.chunks(STRENGTH_RECALC_BATCH) {
+        with_vault(&mut |vault_manager| vault_manager.recalculate_strengths(batch, &mut progress))?;
+        let _ = app_handle.emit("strength-recalc-progress", &progress);
+        tokio::task::yield_now().await;
+    }
+    with_vault(&mut |vault_manager| vault_manager.finish_strength_recalculation(&progress))?;
+    Ok(progress)
+}
+
+// Decrypts a sample of items after unlock and emits `integrity-warning` with the
+// report if any fail. Skipped if the vault was locked again in the meantime.
+fn spawn_integrity_check<R: Runtime>(app_handle: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let report = {
+            let state_guard = state.lock().unwrap();
+            match state_guard.vault_manager.as_ref() {
+                Some(vault_manager) if vault_manager.is_unlocked() => {
+                    vault_manager.check_integrity(INTEGRITY_SAMPLE_SIZE)
+                }
+                _ => return,
+            }
+        };
+        // A check that could not run, e.g. because the database is busy, is not a warning
+        if let Ok(report) = report {
+            if !report.is_intact() {
+                let _ = app_handle.emit(INTEGRITY_WARNING_EVENT, report);
+            }
+        }
+    });
+}
+
+// Background task running automatic backups and security snapshots while the vault is unlocked
+fn spawn_scheduler(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(SCHEDULER_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let state = app_handle.state::<Mutex<AppState>>();
+            let (job, snapshot) = {
+                let state_guard = state.lock().unwrap();
+                match state_guard.vault_manager.as_ref() {
+                    Some(vault_manager) if vault_manager.is_unlocked() => (
+                        vault_manager.scheduled_backup_job(),
+                        vault_manager.run_scheduled_snapshot(),
+                    ),
+                    _ => continue,
+                }
+            };
+            if let Ok(Some(snapshot)) = snapshot {
+                let _ = app_handle.emit("security-snapshot-recorded", snapshot);
+            }
+            // The copy is written without the state lock, so commands keep running
+            let result = match job {
+                Ok(Some(job)) => run_backup_job(&state, job).await.map(Some),
+                Ok(None) => Ok(None),
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(Some(outcome)) => {
+                    let _ = app_handle.emit("backup-completed", outcome);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    notify(
+                        &app_handle,
+                        NotificationCategory::BackupFailed,
+                        Message::new("notification.backup_failed_title"),
+                        Message::new("notification.backup_failed_body").with_param("reason", &e),
+                    );
+                    let _ = app_handle.emit("backup-failed", e.to_string());
+                }
+            }
+            if let Some(expiring) = due_expiry_reminder(&state) {
+                notify(
+                    &app_handle,
+                    NotificationCategory::Expiry,
+                    Message::new("notification.expiry_title"),
+                    Message::new("notification.expiry_body").with_param("count", expiring.len()),
+                );
+                let _ = app_handle.emit("credentials-expiring", expiring);
+            }
+            if let Err(e) = check_monitored_emails_inner(&app_handle, &state, false).await {
+                let _ = app_handle.emit("email-monitor-failed", e.to_string());
+            }
+        }
+    });
+}
+
+// Writes the backup on a blocking thread, then audits it under the state lock
+async fn run_backup_job(state: &Mutex<AppState>, job: BackupJob) -> AppResult<BackupOutcome> {
+    let outcome = tauri::async_runtime::spawn_blocking(move || job.run())
+        .await
+        .map_err(|e| AppError::Other(e.to_string()))??;
+    if let Some(vault_manager) = state.lock().unwrap().vault_manager.as_ref() {
+        vault_manager.record_backup(&outcome)?;
+    }
+    Ok(outcome)
+}
+
+// Items nearing their expiry date, at most once a day while the vault is unlocked
+fn due_expiry_reminder(state: &Mutex<AppState>) -> Option<Vec<CredentialSummary>> {
+    let mut state_guard = state.lock().unwrap();
+    let now = chrono::Utc::now();
+    if state_guard.last_expiry_reminder == Some(now.date_naive()) {
+        return None;
+    }
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .filter(|vault_manager| vault_manager.is_unlocked())?;
+    let days = vault_manager.get_settings().ok()?.expiry_reminder_days;
+    if days == 0 {
+        return None;
+    }
+    let expiring = vault_manager.expiring_credentials(days, now).ok()?;
+    state_guard.last_expiry_reminder = Some(now.date_naive());
+    (!expiring.is_empty()).then_some(expiring)
+}
+
+// Shows notifications in the system notification center
+struct SystemNotifier<'a, R: Runtime>(&'a AppHandle<R>);
+
+impl<R: Runtime> NotificationSink for SystemNotifier<'_, R> {
+    fn show(&self, title: &str, body: &str) -> AppResult<()> {
+        self.0
+            .notification()
+            .builder()
+            .title(title)
+            .body(body)
+            .show()
+            .map_err(|e| AppError::Other(e.to_string()))
+    }
+}
+
+// Dispatches a backend alert, see `notifications`, and sends it to the
+// frontend as `notification-added`. Must be called without the state lock.
+fn notify<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    category: NotificationCategory,
+    title: Message,
+    body: Message,
+) {
+    let state = app_handle.state::<Mutex<AppState>>();
+    let dispatched = match state.lock().unwrap().vault_manager.as_ref() {
+        Some(vault_manager) => vault_manager.dispatch_notification(
+            Notification::new(category, title, body),
+            &SystemNotifier(app_handle),
+        ),
+        None => return,
+    };
+    if let Ok(notification) = dispatched {
+        let _ = app_handle.emit("notification-added", notification);
+    }
+}
+
+#[tauri::command]
+async fn list_notifications(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<Notification>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .list_notifications()
+        .context("error.list_notifications")
+}
+
+#[tauri::command]
+async fn clear_notifications(state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .clear_notifications()
+        .context("error.clear_notifications")
+}
+
+#[tauri::command]
+async fn mark_notifications_read(state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .mark_notifications_read()
+        .context("error.mark_notifications_read")
+}
+
+#[tauri::command]
+async fn create_vault(
+    master_password: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let mut state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_mut()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .initialize(&master_password)
+        .context("error.create_vault")?;
+
+    Ok(())
+}
+
+// Creates the vault from the setup wizard; see `VaultManager::create_vault`
+#[tauri::command]
+async fn create_vault_v2(
+    master_password: String,
+    confirm: String,
+    options: VaultSetupOptions,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<VaultSetup, CommandError> {
+    let mut state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_mut()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    let setup = vault_manager
+        .create_vault(&master_password, &confirm, options)
+        .context("error.create_vault")?;
+    if let Ok(settings) = vault_manager.get_settings() {
+        logging::set_level(settings.log_level);
+    }
+    state_guard.last_activity = Instant::now();
+    Ok(setup)
+}
+
+#[tauri::command]
+async fn unlock_vault<R: Runtime>(
+    master_password: String,
+    skip_recovery: Option<bool>,
+    app_handle: AppHandle<R>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<bool, CommandError> {
+    let (stored_hash, cancel) = {
+        let state_guard = state.lock().unwrap();
+        let vault_manager = state_guard
+            .vault_manager
+            .as_ref()
+            .ok_or_else(CommandError::vault_not_initialized)?;
+        let stored_hash = vault_manager.begin_unlock().context("error.unlock_vault")?;
+        let cancel = state_guard.unlock_cancel.clone();
+        cancel.store(false, Ordering::SeqCst);
+        (stored_hash, cancel)
+    };
+
+    // The key is derived without holding the app state, emitting `unlock-progress`
+    let _ = app_handle.emit(UNLOCK_PROGRESS_EVENT, UnlockProgress::DerivationStarted);
+    let key = unlock::derive_key(master_password, stored_hash.clone(), cancel.clone()).await;
+    let _ = app_handle.emit(
+        UNLOCK_PROGRESS_EVENT,
+        match key {
+            Err(AppError::Cancelled) => UnlockProgress::Cancelled,
+            _ => UnlockProgress::DerivationFinished,
+        },
+    );
+
+    let mut state_guard = state.lock().unwrap();
+    // A panic lock that came in after the derivation finished still wins
+    let key = key.and_then(|key| match cancel.load(Ordering::SeqCst) {
+        true => Err(AppError::Cancelled),
+        false => Ok(key),
+    });
+    let vault_manager = state_guard
+        .vault_manager
+        .as_mut()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    // The user confirmed opening the vault after recovery failed on an earlier try
+    if skip_recovery.unwrap_or(false) {
+        vault_manager.skip_next_recovery();
+    }
+    match key.and_then(|key| vault_manager.complete_unlock(&stored_hash, key)) {
+        Ok(_) => {
+            if let Ok(settings) = vault_manager.get_settings() {
+                logging::set_level(settings.log_level);
+            }
+            if let Ok(Some(reminder)) = vault_manager.master_password_reminder() {
+                let _ = app_handle.emit(MASTER_PASSWORD_REMINDER_EVENT, reminder);
+            }
+            // Scores stored by an older version are brought up to date in the background
+            if vault_manager.strength_recalculation_due().unwrap_or(false) {
+                if let Some(app_handle) = state_guard.app_handle.clone() {
+                    spawn_strength_recalculation(app_handle, &state_guard);
+                }
+            }
+            if let Some(app_handle) = state_guard.app_handle.clone() {
+                spawn_integrity_check(app_handle);
+            }
+            state_guard.last_activity = Instant::now();
+            Ok(true)
+        }
+        Err(AppError::Cancelled) => Err(CommandError::context(
+            "error.unlock_vault",
+            AppError::Cancelled,
+        )),
+        Err(e) => {
+            warn!(error = %e, "unlock failed");
+            match e {
+                AppError::AuthFailed => Ok(false),
+                e => Err(CommandError::context("error.unlock_vault", e)),
+            }
+        }
+    }
+}
+
+// Reopens the vault file after `vault-externally-modified`; the vault ends up locked
+#[tauri::command]
+async fn reload_vault(
+    app_handle: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let mut state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_mut()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    // Nothing is logged if an external change already closed the vault
+    vault_manager.lock().context("error.lock_vault")?;
+    let reopened = open_vault_manager(&get_vault_path(&app_handle)?)?;
+    vault_manager.hand_over_notifications(&reopened);
+    state_guard.vault_manager = Some(reopened);
+
+    Ok(())
+}
+
+// The reminder `unlock_vault` emits as `master-password-reminder`, if any
+#[tauri::command]
+async fn get_master_password_reminder(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Option<MasterPasswordReminder>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .master_password_reminder()
+        .context("error.master_password_reminder")
+}
+
+#[tauri::command]
+async fn lock_vault(state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
+    let mut state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_mut()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager.lock().context("error.lock_vault")?;
+
+    Ok(())
+}
+
+// Bound to a global hotkey: wipes the keys even mid-unlock and logs it. The
+// `vault-panic-locked` event tells every window to clear the clipboard if it
+// still holds what the app copied and to close anything showing secrets.
+#[tauri::command]
+async fn panic_lock(
+    app_handle: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let mut state_guard = state.lock().unwrap();
+    state_guard.unlock_cancel.store(true, Ordering::SeqCst);
+    state_guard.deep_links.clear();
+    let _ = app_handle.emit("vault-panic-locked", ());
+    let vault_manager = state_guard
+        .vault_manager
+        .as_mut()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager.panic_lock().context("error.panic_lock")?;
+
+    Ok(())
+}
+
+// Resets the app-side idle timer; the frontend reports input in its window
+#[tauri::command]
+async fn record_activity(state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
+    state.lock().unwrap().last_activity = Instant::now();
+    Ok(())
+}
+
+#[tauri::command]
+async fn is_vault_locked(state: State<'_, Mutex<AppState>>) -> Result<bool, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    Ok(!vault_manager.is_unlocked())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn add_credential<R: Runtime>(
+    site: String,
+    username: String,
+    password: String,
+    notes: Option<String>,
+    totp: Option<String>,
+    custom_fields: Option<serde_json::Value>,
+    tags: Option<Vec<String>>, // Accept tags as Vec<String>
+    // Saves a password the password policy refused, after the user confirmed it
+    save_anyway: Option<bool>,
+    app_handle: AppHandle<R>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    // Parse custom fields if provided
+    let custom_fields_map = match custom_fields {
+        Some(value) => serde_json::from_value(value).context("error.invalid_custom_fields")?,
+        None => std::collections::HashMap::new(),
+    };
+
+    // Create secret
+    let secret = Secret {
+        password,
+        notes,
+        totp,
+        custom_fields: custom_fields_map,
+        password_history: Vec::new(),
+        autotype_sequence: None,
+        identity: None,
+        recovery_codes: Vec::new(),
+    };
+
+    // Validate and encrypt under the state lock
+    let (credential, credentials) = {
+        let state_guard = state.lock().unwrap();
+        let vault_manager = state_guard
+            .vault_manager
+            .as_ref()
+            .ok_or_else(CommandError::vault_not_initialized)?;
+        let credential = vault_manager
+            .prepare_credential(
+                &site,
+                &username,
+                &secret,
+                tags,
+                PolicyCheck::from_save_anyway(save_anyway),
+            )
+            .context("error.add_credential")?;
+        let credentials = vault_manager
+            .async_credentials()
+            .context("error.add_credential")?;
+        (credential, credentials)
+    };
+
+    // Store without holding the lock; a remote vault waits on the network here
+    credentials
+        .add_credential(credential.clone(), credential.strength)
+        .await
+        .context("error.add_credential")?;
+    if let Some(vault_manager) = state.lock().unwrap().vault_manager.as_ref() {
+        precheck_on_save(app_handle, vault_manager, &credential.uuid);
+    }
+
+    Ok(credential)
+}
+
+// Like `add_credential`, but the password is generated in the backend and never returned
+#[tauri::command]
+async fn add_generated_credential<R: Runtime>(
+    site: String,
+    username: String,
+    generator_options: GeneratorOptions,
+    tags: Option<Vec<String>>,
+    app_handle: AppHandle<R>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    let credential = vault_manager
+        .add_generated_credential(&site, &username, &generator_options, tags)
+        .context("error.add_credential")?;
+    precheck_on_save(app_handle, vault_manager, &credential.uuid);
+
+    Ok(credential)
+}
+
+#[tauri::command]
+async fn get_api_key_presets() -> Result<Vec<ApiKeyPreset>, CommandError> {
+    Ok(api_keys::PRESETS.to_vec())
+}
+
+#[tauri::command]
+async fn add_api_key(
+    key: ApiKeyInput,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager.add_api_key(key).context("error.add_api_key")
+}
+
+#[tauri::command]
+async fn update_api_key(
+    uuid: String,
+    key: ApiKeyInput,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .update_api_key(&uuid, key)
+        .context("error.update_api_key")
+}
+
+// Checks a card number as it is typed, so the frontend never validates one itself
+#[tauri::command]
+async fn check_card_number(number: String) -> Result<CardNumberCheck, CommandError> {
+    cards::check_number(&number).context("error.check_card_number")
+}
+
+#[tauri::command]
+async fn add_card(
+    card: CardInput,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager.add_card(card).context("error.add_card")
+}
+
+#[tauri::command]
+async fn update_card(
+    uuid: String,
+    card: CardInput,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .update_card(&uuid, card)
+        .context("error.update_card")
+}
+
+#[tauri::command]
+async fn add_identity(
+    identity: IdentityInput,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .add_identity(identity)
+        .context("error.add_identity")
+}
+
+#[tauri::command]
+async fn update_identity(
+    uuid: String,
+    identity: IdentityInput,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .update_identity(&uuid, identity)
+        .context("error.update_identity")
+}
+
+// The normalized profile the browser extension maps onto form fields
+#[tauri::command]
+async fn export_autofill_profile(
+    uuid: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<AutofillProfile, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .export_autofill_profile(&uuid)
+        .context("error.export_autofill_profile")
+}
+
+#[tauri::command]
+async fn add_wifi(
+    wifi: WifiInput,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager.add_wifi(wifi).context("error.add_wifi")
+}
+
+#[tauri::command]
+async fn update_wifi(
+    uuid: String,
+    wifi: WifiInput,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .update_wifi(&uuid, wifi)
+        .context("error.update_wifi")
+}
+
+// The network's QR code as SVG and PNG, rendered here so the passphrase stays in the backend
+#[tauri::command]
+async fn generate_wifi_qr(
+    uuid: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<WifiQr, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .generate_wifi_qr(&uuid)
+        .context("error.generate_wifi_qr")
+}
+
+// One item encrypted under a PIN, as a QR code for another device to scan
+#[tauri::command]
+async fn export_credential_qr(
+    uuid: String,
+    pin: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<CredentialQr, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .export_credential_qr(&uuid, &pin)
+        .context("error.export_credential_qr")
+}
+
+// Adds the item carried by a scanned or pasted transfer code
+#[tauri::command]
+async fn import_credential_qr(
+    payload: String,
+    pin: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .import_credential_qr(&payload, &pin)
+        .context("error.import_credential_qr")
+}
+
+// Imports a file of compromised passwords (or their SHA-1 hashes) as the list `source`
+#[tauri::command]
+async fn import_breach_list(
+    path: String,
+    source: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<BreachListImport, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .import_breach_list(Path::new(&path), &source, chrono::Utc::now())
+        .context("error.import_breach_list")
+}
+
+#[tauri::command]
+async fn list_breach_lists(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<BreachListSource>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .breach_lists()
+        .context("error.list_breach_lists")
+}
+
+#[tauri::command]
+async fn remove_breach_list(
+    source: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<usize, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .remove_breach_list(&source)
+        .context("error.remove_breach_list")
+}
+
+// Opens a bulk session, needed by reports that read every secret
+#[tauri::command]
+async fn begin_bulk_session(
+    purpose: String,
+    master_password: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<BulkSession, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .begin_bulk_session(&purpose, &master_password)
+        .map_err(|e| match e {
+            error::AppError::AuthFailed => Message::new("error.master_password_incorrect").into(),
+            e => CommandError::context("error.begin_bulk_session", e),
+        })
+}
+
+#[tauri::command]
+async fn end_bulk_session(state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .end_bulk_session()
+        .context("error.end_bulk_session")
+}
+
+// The open bulk session, or None once it has expired
+#[tauri::command]
+async fn get_bulk_session(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Option<BulkSession>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    Ok(vault_manager.bulk_session())
+}
+
+// Flags items whose password is on an imported list; complements `check_all_breaches`
+#[tauri::command]
+async fn check_local_breaches(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<LocalBreachMatch>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .check_local_breaches()
+        .context("error.check_local_breaches")
+}
+
+// Replaces an item's recovery codes with those pasted as `codes`
+#[tauri::command]
+async fn set_recovery_codes(
+    uuid: String,
+    codes: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .set_recovery_codes(&uuid, &codes)
+        .context("error.set_recovery_codes")
+}
+
+#[tauri::command]
+async fn mark_recovery_code_used(
+    uuid: String,
+    index: usize,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .mark_recovery_code_used(&uuid, index, chrono::Utc::now())
+        .context("error.mark_recovery_code_used")
+}
+
+// Items expiring within `within_days` (default: the reminder setting) or already expired
+#[tauri::command]
+async fn get_expiring_credentials(
+    within_days: Option<u32>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<CredentialSummary>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    let within_days = match within_days {
+        Some(days) => days,
+        None => {
+            vault_manager
+                .get_settings()
+                .context("error.get_expiring_credentials")?
+                .expiry_reminder_days
+        }
+    };
+    vault_manager
+        .expiring_credentials(within_days, chrono::Utc::now())
+        .context("error.get_expiring_credentials")
+}
+
+// Starts a breach check for a just-saved credential if `check_breach_on_save` is on.
+// The result arrives as a `breach-precheck-complete` event; saving never waits for it.
+fn precheck_on_save<R: Runtime>(
+    app_handle: AppHandle<R>,
+    vault_manager: &VaultManager,
+    uuid: &str,
+) {
+    let enabled = vault_manager
+        .get_settings()
+        .map(|settings| settings.check_breach_on_save)
+        .unwrap_or(false);
+    if !enabled {
+        return;
+    }
+    let uuid = uuid.to_string();
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let (result, error) = match check_breach(&state, uuid.clone()).await {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        let _ = app_handle.emit(
+            "breach-precheck-complete",
+            BreachPrecheck {
+                uuid,
+                result,
+                error,
+            },
+        );
+    });
+}
+
+#[tauri::command]
+async fn get_credential(
+    uuid: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let credentials = state
+        .lock()
+        .unwrap()
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?
+        .async_credentials()
+        .context("error.get_credential")?;
+
+    credentials
+        .get_credential(uuid)
+        .await
+        .context("error.get_credential")
+}
+
+#[tauri::command]
+async fn get_credential_secret(
+    uuid: String,
+    master_password: Option<String>,
+    fields: Option<Vec<SecretField>>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Secret, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    // Get the credential
+    let credential = vault_manager
+        .get_credential(&uuid)
+        .context("error.get_credential")?;
+
+    // Decrypt only the requested fields; high-security items need the master password
+    vault_manager
+        .reveal_secret(&credential, fields.as_deref(), master_password.as_deref())
+        .map_err(|e| match e {
+            error::AppError::AuthFailed => Message::new("error.master_password_incorrect").into(),
+            e => CommandError::context("error.decrypt_secret", e),
+        })
+}
+
+#[tauri::command]
+async fn set_high_security(
+    uuid: String,
+    enabled: bool,
+    master_password: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .set_high_security(&uuid, enabled, &master_password)
+        .map_err(|e| match e {
+            error::AppError::AuthFailed => Message::new("error.master_password_incorrect").into(),
+            e => CommandError::context("error.set_high_security", e),
+        })
+}
+
+#[tauri::command]
+async fn validate_totp_input(
+    uuid: String,
+    code: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<TotpValidation, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .validate_totp_input(&uuid, &code)
+        .context("error.validate_totp")
+}
+
+#[tauri::command]
+async fn delete_credential(
+    uuid: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let credentials = state
+        .lock()
+        .unwrap()
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?
+        .async_credentials()
+        .context("error.delete_credential")?;
+
+    // Read and delete without holding the lock, then record the undo step
+    let before = credentials
+        .get_credential(uuid.clone())
+        .await
+        .context("error.delete_credential")?;
+    credentials
+        .delete_credential(uuid)
+        .await
+        .context("error.delete_credential")?;
+    if let Some(vault_manager) = state.lock().unwrap().vault_manager.as_ref() {
+        vault_manager.record_deleted(before);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn bulk_edit(
+    uuids: Vec<String>,
+    edit: BulkEdit,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<CredentialSummary>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    let edited = vault_manager
+        .bulk_edit(&uuids, &edit)
+        .context("error.bulk_edit")?;
+
+    Ok(edited.into_iter().map(CredentialSummary::from).collect())
+}
+
+#[tauri::command]
+async fn rename_tag(
+    old: String,
+    new: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<CredentialSummary>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    let edited = vault_manager
+        .rename_tag(&old, &new)
+        .context("error.rename_tag")?;
+
+    Ok(edited.into_iter().map(CredentialSummary::from).collect())
+}
+
+#[tauri::command]
+async fn undo_last_operation(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Option<OperationSummary>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager.undo_last_operation().context("error.undo")
+}
+
+#[tauri::command]
+async fn redo(state: State<'_, Mutex<AppState>>) -> Result<Option<OperationSummary>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager.redo().context("error.redo")
+}
+
+#[tauri::command]
+async fn get_undo_status(state: State<'_, Mutex<AppState>>) -> Result<UndoStatus, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager.undo_status().context("error.get_undo_status")
+}
+
+// Applies the breach API settings stored in the vault to the base service
+fn breach_service(
+    hibp_service: &HibpService,
+    vault_manager: &VaultManager,
+) -> Result<HibpService, CommandError> {
+    let settings = vault_manager.get_settings().context("error.get_settings")?;
+    let network = Network::new(&settings.network).context("error.invalid_network_settings")?;
+    hibp_service
+        .clone()
+        .with_network(network)
+        .configured(&settings.breach_api)
+        .context("error.invalid_breach_api")
+}
+
+// Builds a CredentialFilter from command arguments, parsing the search term's
+// query syntax (see `query`)
+fn build_filter(
+    search_term: Option<String>,
+    tag: Option<String>,
+    min_strength: Option<u8>,
+    breach_state: Option<i32>,
+    password_older_than_days: Option<u32>,
+    match_mode: MatchMode,
+    smart: SmartFilters,
+) -> AppResult<CredentialFilter> {
+    // Convert breach_state from i32 to BreachState enum
+    let breach_state_enum = match breach_state {
+        Some(0) => Some(BreachState::Unknown),
+        Some(1) => Some(BreachState::Safe),
+        Some(2) => Some(BreachState::Compromised),
+        Some(_) => None,
+        None => None,
+    };
+
+    let filter = query::apply(CredentialFilter {
+        search_term,
+        tag,
+        min_strength,
+        breach_state: breach_state_enum,
+        password_older_than_days,
+        match_mode,
+        smart,
+        ..CredentialFilter::default()
+    })?;
+    // A bad regular expression fails here rather than in the repository
+    filter.term_matcher()?;
+    Ok(filter)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn search_credentials(
+    search_term: Option<String>,
+    tag: Option<String>,
+    min_strength: Option<u8>,
+    breach_state: Option<i32>,
+    password_older_than_days: Option<u32>,
+    match_mode: Option<MatchMode>,
+    smart: Option<SmartFilters>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<CredentialSummary>, CommandError> {
+    // Create filter
+    let filter = build_filter(
+        search_term,
+        tag,
+        min_strength,
+        breach_state,
+        password_older_than_days,
+        match_mode.unwrap_or_default(),
+        smart.unwrap_or_default(),
+    )
+    .context("error.search_credentials")?;
+    list_summaries(filter, &state, "error.search_credentials").await
+}
+
+/// Items passing `filter`, with failures reported under `error_key`
+async fn list_summaries(
+    filter: CredentialFilter,
+    state: &State<'_, Mutex<AppState>>,
+    error_key: &str,
+) -> Result<Vec<CredentialSummary>, CommandError> {
+    let credentials = {
+        let state_guard = state.lock().unwrap();
+        let vault_manager = state_guard
+            .vault_manager
+            .as_ref()
+            .ok_or_else(CommandError::vault_not_initialized)?;
+        // Large vaults are listed from the in-memory index
+        if let Some(summaries) = vault_manager
+            .indexed_summaries(&filter)
+            .context(error_key)?
+        {
+            return Ok(summaries);
+        }
+        vault_manager.async_credentials().context(error_key)?
+    };
+
+    // Query off the runtime threads and without holding the state lock
+    let search_term = filter.search_term.clone();
+    let credentials = credentials
+        .list_credentials(Some(filter))
+        .await
+        .context(error_key)?;
+    if let Some(vault_manager) = state.lock().unwrap().vault_manager.as_ref() {
+        vault_manager
+            .record_listing(search_term.as_deref(), credentials.len())
+            .context(error_key)?;
+    }
+
+    Ok(credentials
+        .into_iter()
+        .map(CredentialSummary::from)
+        .collect())
+}
+
+#[tauri::command]
+async fn list_views(state: State<'_, Mutex<AppState>>) -> Result<Vec<SavedView>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager.list_views().context("error.list_views")
+}
+
+#[tauri::command]
+async fn save_view(
+    name: String,
+    config: ViewConfig,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<SavedView, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .save_view(&name, config)
+        .context("error.save_view")
+}
+
+#[tauri::command]
+async fn delete_view(name: String, state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .delete_view(&name)
+        .context("error.delete_view")
+}
+
+/// The items of the saved view `name`, filtered and sorted as it says
+#[tauri::command]
+async fn list_credentials_for_view(
+    name: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<CredentialSummary>, CommandError> {
+    let config = {
+        let state_guard = state.lock().unwrap();
+        let vault_manager = state_guard
+            .vault_manager
+            .as_ref()
+            .ok_or_else(CommandError::vault_not_initialized)?;
+        vault_manager
+            .view_config(&name)
+            .context("error.list_credentials_for_view")?
+    };
+    let filter =
+        query::apply(config.filter.to_filter()).context("error.list_credentials_for_view")?;
+    let mut summaries = list_summaries(filter, &state, "error.list_credentials_for_view").await?;
+    views::sort_summaries(&mut summaries, config.sort);
+    Ok(summaries)
+}
+
+/// Handles a message forwarded by the browser extension's native messaging
+/// host. Captured logins become pending saves and are announced to the UI with
+/// a `pending-save-added` event; nothing is written to the vault without approval.
+pub fn handle_bridge_message<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    message: BridgeMessage,
+) -> AppResult<BridgeResponse> {
+    let state = app_handle.state::<Mutex<AppState>>();
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or(AppError::VaultLocked)?;
+
+    match message {
+        BridgeMessage::LoginCaptured {
+            url,
+            username,
+            password,
+        } => match vault_manager.capture_login(&url, &username, &password, chrono::Utc::now())? {
+            Some(save) => {
+                let id = save.id.clone();
+                let _ = app_handle.emit("pending-save-added", save);
+                Ok(BridgeResponse::PendingSaveCreated { id })
+            }
+            None => Ok(BridgeResponse::AlreadySaved),
+        },
+    }
+}
+
+/// Queues the links among `args` for the user to confirm and tells the UI
+pub fn receive_deep_links<R: Runtime>(app_handle: &AppHandle<R>, args: Vec<String>) {
+    let state = app_handle.state::<Mutex<AppState>>();
+    let added = deeplink::enqueue(
+        &mut state.lock().unwrap().deep_links,
+        args,
+        chrono::Utc::now(),
+    );
+    if added > 0 {
+        let _ = app_handle.emit("deep-link-received", added);
+    }
+}
+
+#[tauri::command]
+async fn list_pending_deep_links(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<DeepLinkRequest>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    // A link to an item that no longer exists is dropped by accept or reject
+    Ok(state_guard
+        .deep_links
+        .iter()
+        .filter_map(|pending| vault_manager.deep_link_request(pending).ok())
+        .collect())
+}
+
+#[tauri::command]
+async fn accept_deep_link(
+    id: String,
+    target_uuid: Option<String>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let mut state_guard = state.lock().unwrap();
+    let index = state_guard
+        .deep_links
+        .iter()
+        .position(|pending| pending.id == id)
+        .ok_or_else(|| AppError::NotFound(id.clone()))
+        .context("error.accept_deep_link")?;
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    let credential = vault_manager
+        .accept_deep_link(&state_guard.deep_links[index].link, target_uuid.as_deref())
+        .context("error.accept_deep_link")?;
+    state_guard.deep_links.remove(index);
+    Ok(credential)
+}
+
+#[tauri::command]
+async fn reject_deep_link(
+    id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let mut state_guard = state.lock().unwrap();
+    let count = state_guard.deep_links.len();
+    state_guard.deep_links.retain(|pending| pending.id != id);
+    if state_guard.deep_links.len() == count {
+        return Err(AppError::NotFound(id)).context("error.reject_deep_link");
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn list_pending_saves(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<PendingSave>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .list_pending_saves()
+        .context("error.list_pending_saves")
+}
+
+#[tauri::command]
+async fn approve_pending_save<R: Runtime>(
+    id: String,
+    app_handle: AppHandle<R>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    let credential = vault_manager
+        .approve_pending_save(&id)
+        .context("error.approve_pending_save")?;
+    precheck_on_save(app_handle, vault_manager, &credential.uuid);
+
+    Ok(credential)
+}
+
+#[tauri::command]
+async fn discard_pending_save(
+    id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .discard_pending_save(&id)
+        .context("error.discard_pending_save")
+}
+
+// Results returned by `quick_search` when the caller gives no limit
+const QUICK_SEARCH_LIMIT: usize = 20;
+
+// Results for the quick search overlay, which queries on every keystroke
+#[tauri::command]
+async fn quick_search(
+    term: String,
+    limit: Option<usize>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<QuickSearchHit>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .quick_search(&term, limit.unwrap_or(QUICK_SEARCH_LIMIT))
+        .context("error.quick_search")
+}
+
+const TAG_SUGGESTION_LIMIT: usize = 10;
+
+// Tags in use that start with what the user typed so far
+#[tauri::command]
+async fn suggest_tags(
+    prefix: String,
+    limit: Option<usize>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<TagSuggestion>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .suggest_tags(&prefix, limit.unwrap_or(TAG_SUGGESTION_LIMIT))
+        .context("error.suggest_tags")
+}
+
+// What the browser bridge may fill in on `url`; a set `warning` means the page
+// imitates a saved site and nothing should be filled
+#[tauri::command]
+async fn find_credentials_for_url(
+    url: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<UrlMatch, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .find_credentials_for_url(&url)
+        .context("error.find_credentials_for_url")
+}
+
+#[tauri::command]
+async fn check_password_breach(
+    uuid: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<BreachCheckResult, CommandError> {
+    check_breach(&state, uuid).await
+}
+
+// Looks up one credential's password and stores the verdict
+async fn check_breach(
+    state: &Mutex<AppState>,
+    uuid: String,
+) -> Result<BreachCheckResult, CommandError> {
+    // Extract only what is needed before await
+    let (password, hibp_service, queue);
+    {
+        let state_guard = state.lock().unwrap();
+        let vault_manager = state_guard
+            .vault_manager
+            .as_ref()
+            .ok_or_else(CommandError::vault_not_initialized)?;
+        let credential = vault_manager
+            .get_credential(&uuid)
+            .context("error.get_credential")?;
+        match credential.kind {
+            CredentialKind::CreditCard => {
+                return Err(Message::new("error.breach_check_card").into())
+            }
+            CredentialKind::Identity => {
+                return Err(Message::new("error.breach_check_identity").into())
+            }
+            CredentialKind::Login | CredentialKind::ApiKey | CredentialKind::Wifi => {}
+        }
+        let secret = vault_manager
+            .decrypt_secret_for_scan(&credential)
+            .context("error.decrypt_secret")?;
+        password = secret.password;
+        hibp_service = breach_service(&state_guard.hibp_service, vault_manager)?;
+        queue = state_guard.breach_queue.clone();
+    }
+    // Compute SHA-1 hash of the password
+    let password_hash = hibp_service.compute_sha1_hash(password.as_bytes());
+    // Check if the password is in the HIBP database, sharing a lookup already under way
+    let result = BreachCheckResult::from_lookup(queue.lookup(&hibp_service, &password_hash).await)
+        .context("error.check_breach")?;
+    // Leave the stored state alone when the API could not be reached
+    let BreachCheckResult::Checked(breach_state) = result else {
+        return Ok(result);
+    };
+    // The vault may have been locked while the lookup was under way
+    let credentials = state
+        .lock()
+        .unwrap()
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?
+        .async_credentials()
+        .context("error.update_breach_state")?;
+    queue
+        .record(credentials.update_breach_state(uuid, breach_state))
+        .await
+        .context("error.update_breach_state")?;
+    Ok(result)
+}
+
+// The update client with the vault's manifest URL and network settings
+fn update_checker(vault_manager: &VaultManager) -> Result<UpdateChecker, CommandError> {
+    let settings = vault_manager.get_settings().context("error.get_settings")?;
+    let network = Network::new(&settings.network).context("error.invalid_network_settings")?;
+    Ok(UpdateChecker::new(settings.update_manifest_url.as_deref())
+        .context("error.invalid_update_manifest")?
+        .with_network(network))
+}
+
+// Asks the update server whether a newer version is available for this platform
+#[tauri::command]
+async fn check_for_updates(state: State<'_, Mutex<AppState>>) -> Result<UpdateCheck, CommandError> {
+    let checker = {
+        let state_guard = state.lock().unwrap();
+        let vault_manager = state_guard
+            .vault_manager
+            .as_ref()
+            .ok_or_else(CommandError::vault_not_initialized)?;
+        update_checker(vault_manager)?
+    };
+    checker.check().await.context("error.check_for_updates")
+}
+
+// Downloads and verifies the latest version into the app's cache directory,
+// emitting `update-download-progress` events along the way
+#[tauri::command]
+async fn download_update<R: Runtime>(
+    app_handle: AppHandle<R>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<DownloadedUpdate, CommandError> {
+    let checker = {
+        let state_guard = state.lock().unwrap();
+        let vault_manager = state_guard
+            .vault_manager
+            .as_ref()
+            .ok_or_else(CommandError::vault_not_initialized)?;
+        update_checker(vault_manager)?
+    };
+    let dir = app_handle
+        .path()
+        .app_cache_dir()
+        .map_err(|e| {
+            CommandError::context("error.download_update", AppError::Other(e.to_string()))
+        })?
+        .join("updates");
+    checker
+        .download(&dir, |progress| {
+            let _ = app_handle.emit("update-download-progress", progress);
+        })
+        .await
+        .context("error.download_update")
+}
+
+// Maximum number of concurrent HIBP requests during a batch check
+const BREACH_CHECK_CONCURRENCY: usize = 4;
+
+#[tauri::command]
+async fn check_all_breaches<R: Runtime>(
+    app_handle: AppHandle<R>,
+    search_term: Option<String>,
+    tag: Option<String>,
+    min_strength: Option<u8>,
+    breach_state: Option<i32>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<BreachCheckSummary, CommandError> {
+    // Group credentials by password hash so each password is looked up once
+    let mut by_hash: HashMap<String, Vec<(String, BreachState)>> = HashMap::new();
+    let (hibp_service, queue, cancel, total);
+    {
+        let state_guard = state.lock().unwrap();
+        let vault_manager = state_guard
+            .vault_manager
+            .as_ref()
+            .ok_or_else(CommandError::vault_not_initialized)?;
+        vault_manager
+            .require_bulk_session()
+            .context("error.check_breach")?;
+        let filter = build_filter(
+            search_term,
+            tag,
+            min_strength,
+            breach_state,
+            None,
+            MatchMode::default(),
+            SmartFilters::default(),
+        )
+        .context("error.check_breach")?;
+        let mut credentials = vault_manager
+            .list_credentials(Some(filter))
+            .context("error.list_credentials")?;
+        // Card numbers are not passwords; looking them up would only leak hash
+        // prefixes. Identities have no password at all.
+        credentials.retain(|credential| {
+            matches!(
+                credential.kind,
+                CredentialKind::Login | CredentialKind::ApiKey | CredentialKind::Wifi
+            )
+        });
+        total = credentials.len();
+        hibp_service = breach_service(&state_guard.hibp_service, vault_manager)?;
+        for credential in credentials {
+            let secret = vault_manager
+                .decrypt_secret_for_scan(&credential)
+                .context("error.decrypt_secret")?;
+            let hash = hibp_service.compute_sha1_hash(secret.password.as_bytes());
+            by_hash
+                .entry(hash)
+                .or_default()
+                .push((credential.uuid, credential.breach_state));
+        }
+        queue = state_guard.breach_queue.clone();
+        cancel = state_guard.breach_check_cancel.clone();
+        cancel.store(false, Ordering::SeqCst);
+    }
+
+    let mut summary = BreachCheckSummary {
+        total,
+        unique_passwords: by_hash.len(),
+        ..Default::default()
+    };
+    let hashes: Vec<String> = by_hash.keys().cloned().collect();
+    let mut lookups = queue.lookup_many(
+        &hibp_service,
+        hashes,
+        BREACH_CHECK_CONCURRENCY,
+        cancel.clone(),
+    );
+    while let Some(joined) = lookups.join_next().await {
+        let Ok(Some((hash, result))) = joined else {
+            continue;
+        };
+        let credentials = &by_hash[&hash];
+        match result {
+            Ok(new_state) => {
+                // The vault may have been locked while the lookup was under way
+                let repo = state
+                    .lock()
+                    .unwrap()
+                    .vault_manager
+                    .as_ref()
+                    .and_then(|vault_manager| vault_manager.async_credentials().ok());
+                let Some(repo) = repo else {
+                    summary.failed += credentials.len();
+                    continue;
+                };
+                for (uuid, old_state) in credentials {
+                    let update = repo.update_breach_state(uuid.clone(), new_state);
+                    if queue.record(update).await.is_err() {
+                        summary.failed += 1;
+                        continue;
+                    }
+                    summary.checked += 1;
+                    if new_state == BreachState::Compromised {
+                        summary.compromised += 1;
+                        if *old_state != BreachState::Compromised {
+                            summary.newly_compromised += 1;
+                        }
+                    }
+                }
+            }
+            Err(AppError::Network(_)) => summary.offline += credentials.len(),
+            Err(_) => summary.failed += credentials.len(),
+        }
+        let _ = app_handle.emit(
+            "breach-check-progress",
+            BreachCheckProgress {
+                checked: summary.checked + summary.failed + summary.offline,
+                total,
+                newly_compromised: summary.newly_compromised,
+            },
+        );
+    }
+    summary.cancelled = cancel.load(Ordering::SeqCst);
+
+    Ok(summary)
+}
+
+#[tauri::command]
+async fn cancel_unlock(state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
+    state
+        .lock()
+        .unwrap()
+        .unlock_cancel
+        .store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+#[tauri::command]
+async fn cancel_breach_check(state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
+    let state_guard = state.lock().unwrap();
+    state_guard
+        .breach_check_cancel
+        .store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_app_settings(state: State<'_, Mutex<AppState>>) -> Result<AppSettings, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    let settings = vault_manager.get_settings().context("error.get_settings")?;
+
+    Ok(settings)
+}
+
+#[tauri::command]
+async fn get_ui_preferences(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<UiPreferences, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .get_ui_preferences()
+        .context("error.get_ui_preferences")
+}
+
+#[tauri::command]
+async fn get_storage_profile(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<StorageProfile, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .get_storage_profile()
+        .context("error.get_storage_profile")
+}
+
+// Saves the profile and reopens the vault on the new backend; the vault ends up locked
+#[tauri::command]
+async fn set_storage_profile(
+    profile: StorageProfile,
+    app_handle: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let mut state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_mut()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .set_storage_profile(&profile)
+        .context("error.set_storage_profile")?;
+    vault_manager.lock().context("error.lock_vault")?;
+    let reopened = open_vault_manager(&get_vault_path(&app_handle)?)?;
+    vault_manager.hand_over_notifications(&reopened);
+    state_guard.vault_manager = Some(reopened);
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn save_ui_preferences(
+    preferences: UiPreferences,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .save_ui_preferences(&preferences)
+        .context("error.save_ui_preferences")?;
+
+    if let Some(locale) = preferences.locale.as_deref().and_then(Locale::parse) {
+        i18n::set_locale(locale);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn save_app_settings(
+    settings: AppSettings,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    // Reject an unusable breach endpoint or proxy before it is persisted
+    Network::new(&settings.network).context("error.invalid_network_settings")?;
+    HibpService::new()
+        .configured(&settings.breach_api)
+        .context("error.invalid_breach_api")?;
+    if let Some(url) = &settings.share_relay_url {
+        ShareRelay::new(url).context("error.invalid_share_relay")?;
+    }
+    if let Some(url) = &settings.update_manifest_url {
+        UpdateChecker::new(Some(url)).context("error.invalid_update_manifest")?;
+    }
+    if let Some(api_key) = &settings.email_monitor.api_key {
+        AccountBreachClient::new(settings.email_monitor.base_url.as_deref(), api_key)
+            .context("error.invalid_email_monitor")?;
+    }
+
+    vault_manager
+        .save_settings(&settings)
+        .context("error.save_settings")?;
+    logging::set_level(settings.log_level);
+    net::set_offline(settings.network.offline);
+
+    Ok(())
+}
+
+// The network kill switch: stops every network feature at once, including
+// requests of work already under way, and remembers the choice in the vault
+// settings when the vault is unlocked
+#[tauri::command]
+async fn set_offline_mode(
+    offline: bool,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    net::set_offline(offline);
+    let state_guard = state.lock().unwrap();
+    let Some(vault_manager) = state_guard
+        .vault_manager
+        .as_ref()
+        .filter(|vault_manager| vault_manager.is_unlocked())
+    else {
+        return Ok(());
+    };
+
+    vault_manager
+        .set_offline_mode(offline)
+        .context("error.set_offline_mode")?;
+    Ok(())
+}
+
+// Recent diagnostics log entries, newest first, already scrubbed of secrets
+#[tauri::command]
+async fn get_recent_logs(
+    limit: Option<usize>,
+    min_level: Option<LogLevel>,
+) -> Result<Vec<LogEntry>, CommandError> {
+    Ok(logging::recent_logs(
+        limit.unwrap_or(logging::RECENT_LOG_CAPACITY),
+        min_level,
+    ))
+}
+
+#[tauri::command]
+async fn export_plaintext(
+    path: String,
+    format: ExportFormat,
+    scope: Option<ExportScope>,
+    master_password: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ExportReport, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .export_plaintext(
+            &master_password,
+            Path::new(&path),
+            format,
+            &scope.unwrap_or_default(),
+        )
+        .map_err(|e| match e {
+            error::AppError::AuthFailed => Message::new("error.master_password_incorrect").into(),
+            e => CommandError::context("error.export_credentials", e),
+        })
+}
+
+#[tauri::command]
+async fn export_kdbx(
+    path: String,
+    password: String,
+    scope: Option<ExportScope>,
+    master_password: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ExportReport, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .export_kdbx(
+            &master_password,
+            Path::new(&path),
+            &password,
+            &scope.unwrap_or_default(),
+        )
+        .map_err(|e| match e {
+            error::AppError::AuthFailed => Message::new("error.master_password_incorrect").into(),
+            e => CommandError::context("error.export_kdbx", e),
+        })
+}
+
+// Exports to an encrypted bundle of this app, protected by `password`
+#[tauri::command]
+async fn export_bundle(
+    path: String,
+    password: String,
+    scope: Option<ExportScope>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ExportReport, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .export_bundle(Path::new(&path), &password, &scope.unwrap_or_default())
+        .context("error.export_bundle")
+}
+
+#[tauri::command]
+async fn preview_import(
+    path: String,
+    format: ImportFormat,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ImportPreview, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .preview_import(Path::new(&path), format)
+        .context("error.preview_import")
+}
+
+// `apply_suggested_tags` tags untagged rows as the preview suggested
+#[tauri::command]
+async fn import_with_mapping(
+    path: String,
+    format: ImportFormat,
+    mapping: Vec<ColumnMapping>,
+    apply_suggested_tags: Option<bool>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ImportReport, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .import_with_mapping(
+            Path::new(&path),
+            format,
+            &mapping,
+            apply_suggested_tags.unwrap_or(false),
+        )
+        .context("error.import_credentials")
+}
+
+#[tauri::command]
+async fn list_import_conflicts(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<ImportConflict>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .list_import_conflicts()
+        .context("error.list_import_conflicts")
+}
+
+#[tauri::command]
+async fn resolve_import_conflict<R: Runtime>(
+    id: i64,
+    resolution: ConflictResolution,
+    app_handle: AppHandle<R>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Option<Credential>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    let written = vault_manager
+        .resolve_import_conflict(id, resolution)
+        .context("error.resolve_import_conflict")?;
+    // Overwriting (or keeping both) saves the imported password
+    if let Some(credential) = &written {
+        precheck_on_save(app_handle, vault_manager, &credential.uuid);
+    }
+
+    Ok(written)
+}
+
+#[tauri::command]
+async fn compact_vault(
+    wipe: bool,
+    app_handle: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<CompactReport, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .compact_vault(wipe, &|progress: &CompactProgress| {
+            let _ = app_handle.emit("compact-progress", progress);
+        })
+        .context("error.compact_vault")
+}
+
+// Runs every maintenance task now, whether due or not
+#[tauri::command]
+async fn run_maintenance_now(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<MaintenanceReport, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .run_maintenance(true, chrono::Utc::now())
+        .context("error.run_maintenance")
+}
+
+/// Secrets found in pasted `.env`, JSON or YAML text, with their values hidden
+#[tauri::command]
+async fn scan_pasted_secrets(text: String) -> Result<Vec<DetectedSecret>, CommandError> {
+    secret_scan::parse_secrets_from_text(&text).context("error.scan_pasted_secrets")
+}
+
+/// Saves the proposals `ids` of `scan_pasted_secrets` on the same text
+#[tauri::command]
+async fn save_pasted_secrets(
+    text: String,
+    ids: Vec<usize>,
+    tags: Option<Vec<String>>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<CredentialSummary>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    let selected = secret_scan::parse_secrets_from_text(&text)
+        .and_then(|secrets| secret_scan::select(secrets, &ids))
+        .context("error.save_pasted_secrets")?;
+    let saved = vault_manager
+        .save_detected_secrets(&selected, tags.unwrap_or_default())
+        .context("error.save_pasted_secrets")?;
+
+    Ok(saved.into_iter().map(CredentialSummary::from).collect())
+}
+
+// Writes the database in the backup at `path` to the new file `target`
+#[tauri::command]
+async fn restore_backup(
+    path: String,
+    master_password: String,
+    target: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<String, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    let restored = vault_manager
+        .restore_backup(Path::new(&path), &master_password, Path::new(&target))
+        .context("error.restore_backup")?;
+    Ok(restored.display().to_string())
+}
+
+#[tauri::command]
+async fn backup_vault_now(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<BackupOutcome, CommandError> {
+    let job = {
+        let state_guard = state.lock().unwrap();
+        let vault_manager = state_guard
+            .vault_manager
+            .as_ref()
+            .ok_or_else(CommandError::vault_not_initialized)?;
+
+        let settings = vault_manager.get_settings().context("error.get_settings")?;
+        let backup_dir = settings
+            .backup_dir
+            .ok_or_else(|| CommandError::from(Message::new("error.no_backup_folder")))?;
+        vault_manager
+            .backup_job(Path::new(&backup_dir), settings.backup_retention as usize)
+            .context("error.backup")?
+    };
+
+    run_backup_job(&state, job).await.context("error.backup")
+}
+
+#[tauri::command]
+async fn create_diagnostics_bundle(
+    path: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<DiagnosticsReport, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .create_diagnostics_bundle(Path::new(&path))
+        .context("error.create_diagnostics_bundle")
+}
+
+#[tauri::command]
+async fn verify_no_nonce_reuse(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<NonceAuditReport, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .verify_no_nonce_reuse()
+        .context("error.verify_no_nonce_reuse")
+}
+
+// Password rules and the change-password page known for a site or URL
+#[tauri::command]
+async fn get_domain_info(domain: String) -> Result<DomainInfo, CommandError> {
+    domain_info::lookup(&domain).context("error.get_domain_info")
+}
+
+#[tauri::command]
+async fn get_remediation_queue(
+    max_password_age_days: Option<u32>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<RemediationItem>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .get_remediation_queue(max_password_age_days.unwrap_or(DEFAULT_MAX_PASSWORD_AGE_DAYS))
+        .context("error.remediation_queue")
+}
+
+#[tauri::command]
+async fn apply_remediation(
+    uuid: String,
+    new_password: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<RemediationOutcome, CommandError> {
+    let (credential, hibp_service, queue);
+    {
+        let state_guard = state.lock().unwrap();
+        let vault_manager = state_guard
+            .vault_manager
+            .as_ref()
+            .ok_or_else(CommandError::vault_not_initialized)?;
+        credential = vault_manager
+            .apply_remediation(&uuid, &new_password)
+            .context("error.apply_remediation")?;
+        hibp_service = breach_service(&state_guard.hibp_service, vault_manager)?;
+        queue = state_guard.breach_queue.clone();
+    }
+
+    // Re-check the new password so the queue reflects it right away
+    let password_hash = hibp_service.compute_sha1_hash(new_password.as_bytes());
+    let breach_check =
+        BreachCheckResult::from_lookup(queue.lookup(&hibp_service, &password_hash).await)
+            .context("error.recheck_after_update")?;
+    let credential = match breach_check {
+        BreachCheckResult::Checked(breach_state) => {
+            // The vault may have been locked while the lookup was under way
+            let credentials = state
+                .lock()
+                .unwrap()
+                .vault_manager
+                .as_ref()
+                .ok_or_else(CommandError::vault_not_initialized)?
+                .async_credentials()
+                .context("error.update_breach_state")?;
+            queue
+                .record(credentials.update_breach_state(uuid.clone(), breach_state))
+                .await
+                .context("error.update_breach_state")?;
+            credentials
+                .get_credential(uuid)
+                .await
+                .context("error.get_credential")?
+        }
+        BreachCheckResult::Offline => credential,
+    };
+
+    Ok(RemediationOutcome {
+        credential,
+        breach_check,
+    })
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn start_rotation(
+    search_term: Option<String>,
+    tag: Option<String>,
+    min_strength: Option<u8>,
+    breach_state: Option<i32>,
+    password_older_than_days: Option<u32>,
+    options: Option<GeneratorOptions>,
+    master_password: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<RotationStatus, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    let filter = build_filter(
+        search_term,
+        tag,
+        min_strength,
+        breach_state,
+        password_older_than_days,
+        MatchMode::default(),
+        SmartFilters::default(),
+    )
+    .context("error.rotation")?;
+    vault_manager
+        .start_rotation(Some(filter), options.unwrap_or_default(), &master_password)
+        .map_err(|e| match e {
+            error::AppError::AuthFailed => Message::new("error.master_password_incorrect").into(),
+            e => CommandError::context("error.rotation", e),
+        })
+}
+
+#[tauri::command]
+async fn next_rotation_item(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Option<RotationItem>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager.next_rotation_item().context("error.rotation")
+}
+
+#[tauri::command]
+async fn confirm_rotated<R: Runtime>(
+    uuid: String,
+    app_handle: AppHandle<R>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<RotationStatus, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    let status = vault_manager
+        .confirm_rotated(&uuid)
+        .context("error.rotation")?;
+    precheck_on_save(app_handle, vault_manager, &uuid);
+
+    Ok(status)
+}
+
+#[tauri::command]
+async fn skip_rotation_item(
+    uuid: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<RotationStatus, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .skip_rotation_item(&uuid)
+        .context("error.rotation")
+}
+
+#[tauri::command]
+async fn get_rotation_status(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Option<RotationStatus>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager.rotation_status().context("error.rotation")
+}
+
+// Looks up every monitored address and records new breaches. Unless `force`
+// is set, nothing is sent before the configured interval has passed.
+async fn check_monitored_emails_inner<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    state: &Mutex<AppState>,
+    force: bool,
+) -> AppResult<Vec<BreachNotification>> {
+    let (emails, client) = {
+        let state_guard = state.lock().unwrap();
+        let vault_manager = match state_guard.vault_manager.as_ref() {
+            Some(vault_manager) if vault_manager.is_unlocked() => vault_manager,
+            _ => return Err(AppError::VaultLocked),
+        };
+        if !force && !vault_manager.email_monitor_due(chrono::Utc::now())? {
+            return Ok(Vec::new());
+        }
+        let settings = vault_manager.get_settings()?;
+        let api_key = settings.email_monitor.api_key.as_deref().ok_or_else(|| {
+            AppError::InvalidInput("No breached account API key is configured".to_string())
+        })?;
+        let client = AccountBreachClient::new(settings.email_monitor.base_url.as_deref(), api_key)?
+            .with_network(Network::new(&settings.network)?);
+        (settings.email_monitor.emails, client)
+    };
+
+    // Query without holding the state lock
+    let mut results = Vec::with_capacity(emails.len());
+    for email in emails {
+        let breaches = client.breaches_for(&email).await?;
+        results.push((email, breaches));
+    }
+
+    let notifications = {
+        let state_guard = state.lock().unwrap();
+        let vault_manager = state_guard
+            .vault_manager
+            .as_ref()
+            .ok_or(AppError::VaultLocked)?;
+        vault_manager.record_email_breaches(results, chrono::Utc::now())?
+    };
+    for found in &notifications {
+        notify(
+            app_handle,
+            NotificationCategory::Breach,
+            Message::new("notification.breach_title").with_param("email", &found.email),
+            Message::new("notification.breach_body")
+                .with_param("breach", &found.breach.title)
+                .with_param("count", found.credential_uuids.len()),
+        );
+    }
+    if !notifications.is_empty() {
+        let _ = app_handle.emit("email-breaches-found", &notifications);
+    }
+    Ok(notifications)
+}
+
+#[tauri::command]
+async fn check_monitored_emails<R: Runtime>(
+    app_handle: AppHandle<R>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<BreachNotification>, CommandError> {
+    check_monitored_emails_inner(&app_handle, &state, true)
+        .await
+        .context("error.check_monitored_emails")
+}
+
+#[tauri::command]
+async fn list_breach_notifications(
+    include_dismissed: Option<bool>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<BreachNotification>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .list_breach_notifications(include_dismissed.unwrap_or(false))
+        .context("error.list_breach_notifications")
+}
+
+#[tauri::command]
+async fn dismiss_breach_notification(
+    id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<BreachNotification, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .dismiss_breach_notification(&id)
+        .context("error.dismiss_breach_notification")
+}
+
+// The title of the focused window. Asking may start a helper process, so it
+// runs on a blocking thread rather than a runtime thread.
+async fn focused_window_title() -> Option<String> {
+    tokio::task::spawn_blocking(|| SystemKeyboard.focused_window_title())
+        .await
+        .ok()
+        .flatten()
+}
+
+// Called by the auto-type shortcut: matches the focused window and asks the UI
+// to confirm with an `autotype-requested` event. Nothing is typed yet.
+#[tauri::command]
+async fn prepare_autotype<R: Runtime>(
+    app_handle: AppHandle<R>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<AutotypeRequest, CommandError> {
+    let window_title = focused_window_title()
+        .await
+        .ok_or_else(|| CommandError::from(Message::new("error.autotype_no_window")))?;
+
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+    let request = vault_manager
+        .prepare_autotype(&window_title)
+        .context("error.autotype")?;
+    let _ = app_handle.emit("autotype-requested", &request);
+
+    Ok(request)
+}
+
+// The user confirmed auto-type with `uuid`: steps aside so the target window
+// gets the focus back, checks it is still the window the request was made for,
+// then types
+#[tauri::command]
+async fn confirm_autotype<R: Runtime>(
+    request_id: String,
+    uuid: String,
+    app_handle: AppHandle<R>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let window_title = {
+        let state_guard = state.lock().unwrap();
+        let vault_manager = state_guard
+            .vault_manager
+            .as_ref()
+            .ok_or_else(CommandError::vault_not_initialized)?;
+        vault_manager
+            .pending_autotype()
+            .filter(|request| request.id == request_id)
+            .ok_or_else(|| CommandError::from(Message::new("error.autotype_expired")))?
+            .window_title
+    };
+
+    for window in app_handle.webview_windows().values() {
+        let _ = window.minimize();
+    }
+    tokio::time::sleep(autotype::FOCUS_RETURN_DELAY).await;
+    if focused_window_title().await.as_deref() != Some(window_title.as_str()) {
+        return Err(Message::new("error.autotype_window_changed").into());
+    }
+
+    let keys = {
+        let state_guard = state.lock().unwrap();
+        let vault_manager = state_guard
+            .vault_manager
+            .as_ref()
+            .ok_or_else(CommandError::vault_not_initialized)?;
+        vault_manager
+            .autotype_keystrokes(&request_id, &uuid, chrono::Utc::now())
+            .map_err(|e| match e {
+                // Shown as is, so the user knows to reveal the item instead
+                error::AppError::ReauthRequired => Message::new("error.reauth_required").into(),
+                e => CommandError::context("error.autotype", e),
+            })?
+    };
+
+    // Typing sleeps between keys, so keep it off the runtime threads
+    tokio::task::spawn_blocking(move || SystemKeyboard.send(&keys))
+        .await
+        .map_err(|e| AppError::Other(e.to_string()))
+        .and_then(|result| result)
+        .context("error.autotype")
+}
+
+#[tauri::command]
+async fn cancel_autotype(state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager.cancel_autotype();
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_autotype_sequence(
+    uuid: String,
+    sequence: Option<String>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .set_autotype_sequence(&uuid, sequence)
+        .context("error.set_autotype_sequence")
+}
+
+#[tauri::command]
+async fn get_vault_statistics(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<VaultStatistics, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .get_vault_statistics()
+        .context("error.get_vault_statistics")
+}
+
+#[tauri::command]
+async fn list_features(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<FeatureState>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager.list_features().context("error.list_features")
+}
+
+#[tauri::command]
+async fn set_feature(
+    name: String,
+    enabled: bool,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<FeatureState, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .set_feature(&name, enabled)
+        .context("error.set_feature")
+}
+
+#[tauri::command]
+async fn get_security_trend(
+    range: TrendRange,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<SecuritySnapshot>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .get_security_trend(range)
+        .context("error.get_security_trend")
+}
+
+#[tauri::command]
+async fn analyze_master_password(
+    candidate: String,
+    username: Option<String>,
+    email: Option<String>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<MasterPasswordReport, CommandError> {
+    let user_inputs: Vec<&str> = [username.as_deref(), email.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect();
+    // The word lists are encrypted, so they only apply while the vault is unlocked
+    let wordlists = {
+        let state_guard = state.lock().unwrap();
+        match state_guard.vault_manager.as_ref() {
+            Some(vault_manager) if vault_manager.is_unlocked() => vault_manager
+                .get_wordlists()
+                .context("error.get_wordlists")?,
+            _ => Wordlists::default(),
+        }
+    };
+    Ok(strength::analyze_master_password(
+        &candidate,
+        &user_inputs,
+        &wordlists,
+    ))
+}
+
+#[tauri::command]
+async fn get_wordlists(state: State<'_, Mutex<AppState>>) -> Result<Wordlists, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager.get_wordlists().context("error.get_wordlists")
+}
+
+#[tauri::command]
+async fn set_wordlists(
+    wordlists: Wordlists,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Wordlists, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .set_wordlists(&wordlists)
+        .context("error.set_wordlists")
+}
+
+// Starts recalculating every stored strength, e.g. after changing the word lists.
+// Progress arrives as events (see `spawn_strength_recalculation`); returns false if
+// a recalculation is already running.
+#[tauri::command]
+async fn recalculate_all_strengths<R: Runtime>(
+    app_handle: AppHandle<R>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<bool, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+    if !vault_manager.is_unlocked() {
+        return Err(CommandError::context(
+            "error.recalculate_strengths",
+            AppError::VaultLocked,
+        ));
+    }
+
+    Ok(spawn_strength_recalculation(app_handle, &state_guard))
+}
+
+#[tauri::command]
+async fn generate_password(
+    length: usize,
+    use_uppercase: bool,
+    use_lowercase: bool,
+    use_numbers: bool,
+    use_symbols: bool,
+    exclude_similar: bool,
+) -> Result<String, CommandError> {
+    generator::generate(&GeneratorOptions {
+        length,
+        use_uppercase,
+        use_lowercase,
+        use_numbers,
+        use_symbols,
+        exclude_similar,
+    })
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn generate_password_for(
+    uuid: String,
+    options: Option<GeneratorOptions>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<String, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .generate_password_for(&uuid, &options.unwrap_or_default())
+        .context("error.generate_password")
+}
+
+#[tauri::command]
+async fn set_generator_constraints(
+    uuid: String,
+    constraints: Option<GeneratorConstraints>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Credential, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .set_generator_constraints(&uuid, constraints)
+        .context("error.set_generator_constraints")
+}
+
+#[tauri::command]
+async fn set_locale(locale: String) -> Result<Locale, CommandError> {
+    let parsed = Locale::parse(&locale).ok_or_else(|| {
+        CommandError::from(Message::new("error.unsupported_locale").with_param("locale", &locale))
+    })?;
+    i18n::set_locale(parsed);
+    Ok(parsed)
+}
+
+#[tauri::command]
+async fn get_locale() -> Result<Locale, CommandError> {
+    Ok(i18n::current_locale())
+}
+
+#[tauri::command]
+async fn get_audit_log(
+    limit: Option<i64>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<LocalizedAuditEntry>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    let entries = vault_manager
+        .get_audit_log(limit)
+        .context("error.get_audit_log")?;
+    Ok(entries.into_iter().map(LocalizedAuditEntry::from).collect())
+}
+
+// Moves audit entries older than `older_than_days` (90 by default) out of the
+// audit log into encrypted archive segments
+#[tauri::command]
+async fn archive_audit_log(
+    older_than_days: Option<u32>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<AuditArchiveReport, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .archive_audit_log(
+            older_than_days.unwrap_or(DEFAULT_AUDIT_ARCHIVE_AGE_DAYS),
+            chrono::Utc::now(),
+        )
+        .context("error.archive_audit_log")
+}
+
+#[tauri::command]
+async fn search_audit_archives(
+    range: AuditArchiveRange,
+    limit: Option<usize>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<LocalizedAuditEntry>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    let entries = vault_manager
+        .search_audit_archives(&range, limit)
+        .context("error.search_audit_archives")?;
+    Ok(entries.into_iter().map(LocalizedAuditEntry::from).collect())
+}
+
+#[tauri::command]
+async fn export_settings_profile(
+    path: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .export_settings_profile(Path::new(&path))
+        .context("error.export_settings_profile")
+}
+
+// Call without `include_kdf` first; if the result says the KDF differs, ask
+// the user and import again with it
+#[tauri::command]
+async fn import_settings_profile(
+    path: String,
+    include_kdf: bool,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<SettingsProfileImport, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .import_settings_profile(Path::new(&path), include_kdf)
+        .context("error.import_settings_profile")
 }
 
 #[tauri::command]
-async fn create_vault(
-    master_password: String,
+async fn export_signed_audit_log(
+    path: String,
     state: State<'_, Mutex<AppState>>,
-) -> Result<(), String> {
-    let mut state_guard = state.lock().unwrap();
+) -> Result<usize, CommandError> {
+    let state_guard = state.lock().unwrap();
     let vault_manager = state_guard
         .vault_manager
-        .as_mut()
-        .ok_or_else(|| "Vault not initialized".to_string())?;
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
-    // Unlock (which will create a new vault if it doesn't exist)
     vault_manager
-        .unlock(&master_password)
-        .map_err(|e| format!("Failed to create vault: {}", e))?;
-
-    Ok(())
+        .export_signed_audit_log(Path::new(&path))
+        .context("error.export_audit_log")
 }
 
 #[tauri::command]
-async fn unlock_vault(
-    master_password: String,
+async fn verify_signed_audit_export(
+    path: String,
+    public_key: Option<String>,
     state: State<'_, Mutex<AppState>>,
-) -> Result<bool, String> {
-    let mut state_guard = state.lock().unwrap();
+) -> Result<AuditVerification, CommandError> {
+    let state_guard = state.lock().unwrap();
     let vault_manager = state_guard
         .vault_manager
-        .as_mut()
-        .ok_or_else(|| "Vault not initialized".to_string())?;
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
-    // Attempt to unlock
-    match vault_manager.unlock(&master_password) {
-        Ok(_) => Ok(true),
-        Err(error::AppError::AuthFailed) => Ok(false),
-        Err(e) => Err(format!("Error unlocking vault: {}", e)),
-    }
+    vault_manager
+        .verify_signed_audit_export(Path::new(&path), public_key.as_deref())
+        .context("error.verify_audit_export")
 }
 
 #[tauri::command]
-async fn lock_vault(state: State<'_, Mutex<AppState>>) -> Result<(), String> {
-    let mut state_guard = state.lock().unwrap();
+async fn get_audit_public_key(state: State<'_, Mutex<AppState>>) -> Result<String, CommandError> {
+    let state_guard = state.lock().unwrap();
     let vault_manager = state_guard
         .vault_manager
-        .as_mut()
-        .ok_or_else(|| "Vault not initialized".to_string())?;
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
     vault_manager
-        .lock()
-        .map_err(|e| format!("Failed to lock vault: {}", e))?;
-
-    Ok(())
+        .audit_public_key()
+        .context("error.audit_public_key")
 }
 
 #[tauri::command]
-async fn is_vault_locked(state: State<'_, Mutex<AppState>>) -> Result<bool, String> {
+async fn get_member_public_key(state: State<'_, Mutex<AppState>>) -> Result<String, CommandError> {
     let state_guard = state.lock().unwrap();
     let vault_manager = state_guard
         .vault_manager
         .as_ref()
-        .ok_or_else(|| "Vault not initialized".to_string())?;
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
-    Ok(!vault_manager.is_unlocked())
+    vault_manager
+        .member_public_key()
+        .context("error.get_member_key")
 }
 
 #[tauri::command]
-async fn add_credential(
-    site: String,
-    username: String,
-    password: String,
-    notes: Option<String>,
-    totp: Option<String>,
-    custom_fields: Option<serde_json::Value>,
-    tags: Option<Vec<String>>, // Accept tags as Vec<String>
+async fn list_collections(
     state: State<'_, Mutex<AppState>>,
-) -> Result<Credential, String> {
+) -> Result<Vec<Collection>, CommandError> {
     let state_guard = state.lock().unwrap();
     let vault_manager = state_guard
         .vault_manager
         .as_ref()
-        .ok_or_else(|| "Vault not initialized".to_string())?;
-
-    // Parse custom fields if provided
-    let custom_fields_map = match custom_fields {
-        Some(value) => serde_json::from_value(value)
-            .map_err(|e| format!("Invalid custom fields format: {}", e))?,
-        None => std::collections::HashMap::new(),
-    };
-
-    // Create secret
-    let secret = Secret {
-        password,
-        notes,
-        totp,
-        custom_fields: custom_fields_map,
-    };
-
-    // Add credential to vault
-    let credential = vault_manager
-        .add_credential(&site, &username, secret, tags) // Pass tags to add_credential
-        .map_err(|e| format!("Failed to add credential: {}", e))?;
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
-    Ok(credential)
+    vault_manager
+        .list_collections()
+        .context("error.list_collections")
 }
 
 #[tauri::command]
-async fn get_credential(
-    uuid: String,
+async fn create_collection(
+    name: String,
     state: State<'_, Mutex<AppState>>,
-) -> Result<Credential, String> {
+) -> Result<Collection, CommandError> {
     let state_guard = state.lock().unwrap();
     let vault_manager = state_guard
         .vault_manager
         .as_ref()
-        .ok_or_else(|| "Vault not initialized".to_string())?;
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
-    let credential = vault_manager
-        .get_credential(&uuid)
-        .map_err(|e| format!("Failed to get credential: {}", e))?;
-
-    Ok(credential)
+    vault_manager
+        .create_collection(&name)
+        .context("error.create_collection")
 }
 
 #[tauri::command]
-async fn get_credential_secret(
-    uuid: String,
+async fn add_collection_member(
+    collection_id: String,
+    public_key: String,
+    label: String,
     state: State<'_, Mutex<AppState>>,
-) -> Result<Secret, String> {
+) -> Result<Collection, CommandError> {
     let state_guard = state.lock().unwrap();
     let vault_manager = state_guard
         .vault_manager
         .as_ref()
-        .ok_or_else(|| "Vault not initialized".to_string())?;
-
-    // Get the credential
-    let credential = vault_manager
-        .get_credential(&uuid)
-        .map_err(|e| format!("Failed to get credential: {}", e))?;
-
-    // Decrypt the secret
-    let secret = vault_manager
-        .decrypt_secret(&credential)
-        .map_err(|e| format!("Failed to decrypt secret: {}", e))?;
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
-    Ok(secret)
+    vault_manager
+        .add_collection_member(&collection_id, &public_key, &label)
+        .context("error.add_collection_member")
 }
 
 #[tauri::command]
-async fn delete_credential(uuid: String, state: State<'_, Mutex<AppState>>) -> Result<(), String> {
+async fn remove_collection_member(
+    collection_id: String,
+    public_key: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Collection, CommandError> {
     let state_guard = state.lock().unwrap();
     let vault_manager = state_guard
         .vault_manager
         .as_ref()
-        .ok_or_else(|| "Vault not initialized".to_string())?;
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
     vault_manager
-        .delete_credential(&uuid)
-        .map_err(|e| format!("Failed to delete credential: {}", e))?;
-
-    Ok(())
+        .remove_collection_member(&collection_id, &public_key)
+        .context("error.remove_collection_member")
 }
 
 #[tauri::command]
-async fn search_credentials(
-    search_term: Option<String>,
-    tag: Option<String>,
-    min_strength: Option<u8>,
-    breach_state: Option<i32>,
+async fn move_to_collection(
+    uuid: String,
+    collection_id: Option<String>,
     state: State<'_, Mutex<AppState>>,
-) -> Result<Vec<Credential>, String> {
+) -> Result<Credential, CommandError> {
     let state_guard = state.lock().unwrap();
     let vault_manager = state_guard
         .vault_manager
         .as_ref()
-        .ok_or_else(|| "Vault not initialized".to_string())?;
-
-    // Convert breach_state from i32 to BreachState enum
-    let breach_state_enum = match breach_state {
-        Some(0) => Some(BreachState::Unknown),
-        Some(1) => Some(BreachState::Safe),
-        Some(2) => Some(BreachState::Compromised),
-        Some(_) => None,
-        None => None,
-    };
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
-    // Create filter
-    let filter = CredentialFilter {
-        search_term,
-        tag,
-        min_strength,
-        breach_state: breach_state_enum,
-    };
+    vault_manager
+        .move_to_collection(&uuid, collection_id.as_deref())
+        .context("error.move_to_collection")
+}
 
-    // Get credentials
-    let credentials = vault_manager
-        .list_credentials(Some(filter))
-        .map_err(|e| format!("Failed to search credentials: {}", e))?;
+#[tauri::command]
+async fn delete_collection(
+    collection_id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
-    Ok(credentials)
+    vault_manager
+        .delete_collection(&collection_id)
+        .context("error.delete_collection")
 }
 
+// One-time shares are single-view unless the caller asks for more
+const DEFAULT_SHARE_MAX_VIEWS: u32 = 1;
+
 #[tauri::command]
-async fn check_password_breach(
+async fn create_share(
     uuid: String,
+    expires_in: u64,
+    max_views: Option<u32>,
+    output_path: Option<String>,
     state: State<'_, Mutex<AppState>>,
-) -> Result<BreachState, String> {
-    // Extract only what is needed before await
-    let (password, hibp_service);
-    {
+) -> Result<ShareLink, CommandError> {
+    let max_views = max_views.unwrap_or(DEFAULT_SHARE_MAX_VIEWS);
+
+    // Seal the secret while holding the lock; file shares finish right here
+    let (prepared, relay) = {
         let state_guard = state.lock().unwrap();
         let vault_manager = state_guard
             .vault_manager
             .as_ref()
-            .ok_or_else(|| "Vault not initialized".to_string())?;
-        let credential = vault_manager
-            .get_credential(&uuid)
-            .map_err(|e| format!("Failed to get credential: {}", e))?;
-        let secret = vault_manager
-            .decrypt_secret(&credential)
-            .map_err(|e| format!("Failed to decrypt secret: {}", e))?;
-        password = secret.password;
-        hibp_service = state_guard.hibp_service.clone();
-    }
-    // Compute SHA-1 hash of the password
-    let password_hash = hibp_service.compute_sha1_hash(password.as_bytes());
-    // Check if the password is in the HIBP database
-    let breach_state = hibp_service
-        .check_password(&password_hash)
+            .ok_or_else(CommandError::vault_not_initialized)?;
+
+        if let Some(path) = output_path {
+            return vault_manager
+                .create_file_share(&uuid, expires_in, max_views, Path::new(&path))
+                .context("error.create_share");
+        }
+        let settings = vault_manager.get_settings().context("error.create_share")?;
+        let relay_url = settings
+            .share_relay_url
+            .ok_or_else(|| CommandError::from(Message::new("error.share_relay_not_configured")))?;
+        let network = Network::new(&settings.network).context("error.invalid_network_settings")?;
+        let relay = ShareRelay::new(&relay_url)
+            .context("error.create_share")?
+            .with_network(network);
+        let prepared = vault_manager
+            .prepare_share(&uuid, expires_in, max_views)
+            .context("error.create_share")?;
+        (prepared, relay)
+    };
+
+    // Upload without holding the state lock
+    let revoke_token = shares::generate_revoke_token();
+    relay
+        .upload(&prepared.envelope, &revoke_token)
         .await
-        .map_err(|e| format!("Failed to check password breach: {}", e))?;
-    // Reacquire lock to update breach state
-    {
-        let mut state_guard = state.lock().unwrap();
+        .context("error.create_share")?;
+
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+    let link = relay.share_url(&prepared.envelope.id);
+    vault_manager
+        .record_share(
+            prepared,
+            ShareDestination::Relay,
+            relay.base_url(),
+            &revoke_token,
+            Some(link),
+        )
+        .context("error.create_share")
+}
+
+#[tauri::command]
+async fn list_shares(state: State<'_, Mutex<AppState>>) -> Result<Vec<ShareRecord>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager.list_shares().context("error.list_shares")
+}
+
+#[tauri::command]
+async fn revoke_share(
+    id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ShareRecord, CommandError> {
+    let (share, network) = {
+        let state_guard = state.lock().unwrap();
         let vault_manager = state_guard
             .vault_manager
-            .as_mut()
-            .ok_or_else(|| "Vault not initialized".to_string())?;
-        vault_manager
-            .update_breach_state(&uuid, breach_state)
-            .map_err(|e| format!("Failed to update breach state: {}", e))?;
+            .as_ref()
+            .ok_or_else(CommandError::vault_not_initialized)?;
+        let settings = vault_manager.get_settings().context("error.revoke_share")?;
+        (
+            vault_manager.get_share(&id).context("error.revoke_share")?,
+            Network::new(&settings.network).context("error.invalid_network_settings")?,
+        )
+    };
+
+    // Delete the relay copy first so a failure leaves the share listed as active
+    if share.destination == ShareDestination::Relay && share.revoked_at.is_none() {
+        ShareRelay::new(&share.location)
+            .context("error.revoke_share")?
+            .with_network(network)
+            .revoke(&share.id, &share.revoke_token)
+            .await
+            .context("error.revoke_share")?;
     }
-    Ok(breach_state)
+
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+    vault_manager
+        .revoke_share(&id)
+        .context("error.revoke_share")
 }
 
 #[tauri::command]
-async fn get_app_settings(state: State<'_, Mutex<AppState>>) -> Result<AppSettings, String> {
+async fn list_devices(state: State<'_, Mutex<AppState>>) -> Result<Vec<Device>, CommandError> {
     let state_guard = state.lock().unwrap();
     let vault_manager = state_guard
         .vault_manager
         .as_ref()
-        .ok_or_else(|| "Vault not initialized".to_string())?;
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
-    let settings = vault_manager
-        .get_settings()
-        .map_err(|e| format!("Failed to get app settings: {}", e))?;
+    vault_manager.list_devices().context("error.list_devices")
+}
 
-    Ok(settings)
+#[tauri::command]
+async fn revoke_device(
+    id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Device, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .revoke_device(&id)
+        .context("error.revoke_device")
 }
 
 #[tauri::command]
-async fn save_app_settings(
-    settings: AppSettings,
+async fn add_emergency_contact(
+    label: String,
+    public_key: String,
+    wait_days: u32,
     state: State<'_, Mutex<AppState>>,
-) -> Result<(), String> {
+) -> Result<EmergencyContact, CommandError> {
     let state_guard = state.lock().unwrap();
     let vault_manager = state_guard
         .vault_manager
         .as_ref()
-        .ok_or_else(|| "Vault not initialized".to_string())?;
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
     vault_manager
-        .save_settings(&settings)
-        .map_err(|e| format!("Failed to save app settings: {}", e))?;
+        .add_emergency_contact(&label, &public_key, wait_days)
+        .context("error.add_emergency_contact")
+}
 
-    Ok(())
+#[tauri::command]
+async fn list_emergency_contacts(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<EmergencyContact>, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .list_emergency_contacts()
+        .context("error.list_emergency_contacts")
 }
 
 #[tauri::command]
-async fn generate_password(
-    length: usize,
-    use_uppercase: bool,
-    use_lowercase: bool,
-    use_numbers: bool,
-    use_symbols: bool,
-    exclude_similar: bool,
-) -> Result<String, String> {
-    if length < 1 {
-        return Err("Password length must be at least 1".to_string());
-    }
+async fn request_emergency_access(
+    id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<EmergencyContact, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
-    if !(use_uppercase || use_lowercase || use_numbers || use_symbols) {
-        return Err("At least one character type must be selected".to_string());
-    }
+    vault_manager
+        .request_emergency_access(&id)
+        .context("error.request_emergency_access")
+}
+
+#[tauri::command]
+async fn approve_emergency_access(
+    id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<EmergencyContact, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
-    // Define character sets
-    let upper = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
-    let lower = "abcdefghijklmnopqrstuvwxyz";
-    let numbers = "0123456789";
-    let symbols = "!@#$%^&*()-_=+[]{}|;:,.<>?";
+    vault_manager
+        .approve_emergency_access(&id)
+        .context("error.approve_emergency_access")
+}
 
-    // Similar looking characters
-    let similar = "Il1O0";
+#[tauri::command]
+async fn reject_emergency_access(
+    id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<EmergencyContact, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
-    // Build the charset
-    let mut charset = String::new();
+    vault_manager
+        .reject_emergency_access(&id)
+        .context("error.reject_emergency_access")
+}
 
-    if use_uppercase {
-        charset.push_str(upper);
-    }
+#[tauri::command]
+async fn revoke_emergency_contact(
+    id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<EmergencyContact, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
-    if use_lowercase {
-        charset.push_str(lower);
-    }
+    vault_manager
+        .revoke_emergency_contact(&id)
+        .context("error.revoke_emergency_contact")
+}
 
-    if use_numbers {
-        charset.push_str(numbers);
-    }
+#[tauri::command]
+async fn release_emergency_key(
+    id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<WrappedKey, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
-    if use_symbols {
-        charset.push_str(symbols);
-    }
+    vault_manager
+        .release_emergency_key(&id)
+        .context("error.release_emergency_key")
+}
 
-    // Remove similar characters if requested
-    if exclude_similar {
-        for c in similar.chars() {
-            charset = charset.replace(c, "");
-        }
-    }
+#[tauri::command]
+async fn enable_travel_mode(
+    allowed_tags: Vec<String>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<TravelActivation, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
-    // Generate the password
-    let mut rng = rand::thread_rng();
-    let password: String = (0..length)
-        .map(|_| {
-            let idx = rand::Rng::gen_range(&mut rng, 0..charset.len());
-            charset.chars().nth(idx).unwrap()
-        })
-        .collect();
+    vault_manager
+        .enable_travel_mode(&allowed_tags)
+        .context("error.enable_travel_mode")
+}
+
+#[tauri::command]
+async fn disable_travel_mode(
+    recovery_blob: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<usize, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
 
-    Ok(password)
+    vault_manager
+        .disable_travel_mode(&recovery_blob)
+        .context("error.disable_travel_mode")
 }
 
 #[tauri::command]
-async fn greet(name: &str) -> Result<String, String> {
-    Ok(format!("Hello, {}! You've been greeted from Rust!", name))
+async fn get_travel_mode_status(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<TravelModeStatus, CommandError> {
+    let state_guard = state.lock().unwrap();
+    let vault_manager = state_guard
+        .vault_manager
+        .as_ref()
+        .ok_or_else(CommandError::vault_not_initialized)?;
+
+    vault_manager
+        .travel_mode_status()
+        .context("error.get_travel_mode")
 }
 
 // Entrypoint
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // The configured level is only known once the vault is unlocked
+    logging::init(LogLevel::default());
+
     // Create app state
     let app_state = Mutex::new(AppState::new());
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(app_state)
         .invoke_handler(tauri::generate_handler![
-            greet,
+            get_app_status,
             initialize_vault,
+            get_vault_status,
+            in_ephemeral_session,
+            get_hardening_status,
+            get_data_location,
             create_vault,
+            create_vault_v2,
             unlock_vault,
             lock_vault,
+            panic_lock,
+            list_notifications,
+            clear_notifications,
+            mark_notifications_read,
+            get_master_password_reminder,
+            reload_vault,
             is_vault_locked,
+            record_activity,
             add_credential,
+            add_generated_credential,
+            get_api_key_presets,
+            add_api_key,
+            update_api_key,
+            check_card_number,
+            add_card,
+            update_card,
+            add_identity,
+            update_identity,
+            export_autofill_profile,
+            add_wifi,
+            update_wifi,
+            generate_wifi_qr,
+            export_credential_qr,
+            import_credential_qr,
+            set_recovery_codes,
+            mark_recovery_code_used,
+            import_breach_list,
+            list_breach_lists,
+            remove_breach_list,
+            begin_bulk_session,
+            end_bulk_session,
+            get_bulk_session,
+            check_local_breaches,
+            get_expiring_credentials,
             get_credential,
             get_credential_secret,
+            set_high_security,
+            validate_totp_input,
             delete_credential,
+            bulk_edit,
+            rename_tag,
+            undo_last_operation,
+            redo,
+            get_undo_status,
             search_credentials,
+            list_views,
+            save_view,
+            delete_view,
+            list_credentials_for_view,
+            quick_search,
+            suggest_tags,
+            find_credentials_for_url,
+            list_pending_saves,
+            approve_pending_save,
+            discard_pending_save,
+            list_pending_deep_links,
+            accept_deep_link,
+            reject_deep_link,
             check_password_breach,
+            check_all_breaches,
+            cancel_breach_check,
+            cancel_unlock,
             get_app_settings,
             save_app_settings,
+            set_offline_mode,
+            check_for_updates,
+            download_update,
+            get_recent_logs,
+            get_ui_preferences,
+            save_ui_preferences,
+            get_storage_profile,
+            set_storage_profile,
+            export_plaintext,
+            export_kdbx,
+            export_bundle,
+            preview_import,
+            import_with_mapping,
+            list_import_conflicts,
+            resolve_import_conflict,
+            scan_pasted_secrets,
+            save_pasted_secrets,
+            backup_vault_now,
+            restore_backup,
+            compact_vault,
+            run_maintenance_now,
+            create_diagnostics_bundle,
+            verify_no_nonce_reuse,
             generate_password,
+            generate_password_for,
+            set_generator_constraints,
+            analyze_master_password,
+            get_wordlists,
+            recalculate_all_strengths,
+            set_wordlists,
+            set_locale,
+            get_locale,
+            get_audit_log,
+            archive_audit_log,
+            search_audit_archives,
+            export_settings_profile,
+            import_settings_profile,
+            export_signed_audit_log,
+            verify_signed_audit_export,
+            get_audit_public_key,
+            get_member_public_key,
+            list_collections,
+            create_collection,
+            add_collection_member,
+            remove_collection_member,
+            move_to_collection,
+            delete_collection,
+            create_share,
+            list_shares,
+            revoke_share,
+            list_devices,
+            revoke_device,
+            add_emergency_contact,
+            list_emergency_contacts,
+            request_emergency_access,
+            approve_emergency_access,
+            reject_emergency_access,
+            revoke_emergency_contact,
+            release_emergency_key,
+            enable_travel_mode,
+            disable_travel_mode,
+            get_travel_mode_status,
+            get_domain_info,
+            get_remediation_queue,
+            apply_remediation,
+            start_rotation,
+            next_rotation_item,
+            confirm_rotated,
+            skip_rotation_item,
+            get_rotation_status,
+            check_monitored_emails,
+            list_breach_notifications,
+            dismiss_breach_notification,
+            prepare_autotype,
+            confirm_autotype,
+            cancel_autotype,
+            set_autotype_sequence,
+            get_security_trend,
+            get_vault_statistics,
+            list_features,
+            set_feature,
         ])
         .setup(move |app| {
+            spawn_scheduler(app.handle().clone());
+            spawn_auto_lock(app.handle().clone());
+            spawn_maintenance(app.handle().clone());
+            spawn_vault_watcher(app.handle().clone());
+            spawn_stats_notifier(app.handle().clone());
+            // Windows and Linux pass the link as an argument
+            receive_deep_links(app.handle(), std::env::args().skip(1).collect());
+
             #[cfg(debug_assertions)]
             {
                 let window = app.get_webview_window("main").unwrap();
@@ -461,6 +4180,16 @@ pub fn run() {
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app_handle, _event| {
+            // macOS delivers links as open events instead of arguments
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            if let tauri::RunEvent::Opened { urls } = _event {
+                receive_deep_links(
+                    _app_handle,
+                    urls.into_iter().map(|url| url.to_string()).collect(),
+                );
+            }
+        });
 }
@@ -0,0 +1,248 @@
+//! Structured logging for diagnostics.
+//!
+//! Modules log through the `tracing` macros. The subscriber installed by `init`
+//! keeps recent events in memory for `get_recent_logs`, scrubbing them on the
+//! way in: fields whose names suggest a secret are replaced outright, and runs
+//! of text that look like key material (long base64 or hex) are masked in every
+//! message and value. Code must still never hand a secret to a log macro; the
+//! scrubber is what makes a mistake harmless, not a license to make one.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::field::{Field, Visit};
+use tracing::subscriber::Interest;
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+/// Most events kept for `get_recent_logs`; older ones are dropped
+pub const RECENT_LOG_CAPACITY: usize = 1000;
+
+/// Replaces anything the scrubber takes out
+pub const REDACTED: &str = "[redacted]";
+
+/// Field names containing any of these are never logged
+const SECRET_FIELD_NAMES: &[&str] = &[
+    "password",
+    "passphrase",
+    "secret",
+    "key",
+    "token",
+    "totp",
+    "nonce",
+    "seed",
+    "hash",
+    "plaintext",
+    "recovery",
+];
+
+/// Runs of base64 or hex at least this long, with letters and digits, are masked
+const MIN_KEY_MATERIAL_RUN: usize = 24;
+
+/// How much to log, from least to most
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error = 1,
+    Warn = 2,
+    #[default]
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+impl From<&Level> for LogLevel {
+    fn from(level: &Level) -> Self {
+        match *level {
+            Level::ERROR => LogLevel::Error,
+            Level::WARN => LogLevel::Warn,
+            Level::INFO => LogLevel::Info,
+            Level::DEBUG => LogLevel::Debug,
+            Level::TRACE => LogLevel::Trace,
+        }
+    }
+}
+
+impl LogLevel {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => LogLevel::Error,
+            2 => LogLevel::Warn,
+            4 => LogLevel::Debug,
+            5 => LogLevel::Trace,
+            _ => LogLevel::Info,
+        }
+    }
+}
+
+/// A logged event, already scrubbed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub timestamp: DateTime<Utc>,
+    pub level: LogLevel,
+    /// Module that logged the event, e.g. "secret_plan_lib::vault"
+    pub target: String,
+    pub message: String,
+    pub fields: BTreeMap<String, String>,
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:?} {}: {}",
+            self.timestamp.to_rfc3339(),
+            self.level,
+            self.target,
+            self.message
+        )?;
+        for (name, value) in &self.fields {
+            write!(f, " {}={}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+static RECENT: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+
+/// Installs the scrubbing subscriber for the whole process. Later calls only change the level.
+pub fn init(level: LogLevel) {
+    set_level(level);
+    let _ = tracing::subscriber::set_global_default(
+        tracing_subscriber::registry().with(RecentLogsLayer),
+    );
+}
+
+/// Changes what gets logged from now on
+pub fn set_level(level: LogLevel) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn level() -> LogLevel {
+    LogLevel::from_u8(LEVEL.load(Ordering::Relaxed))
+}
+
+/// Up to `limit` recent events at `min_level` or more severe, newest first
+pub fn recent_logs(limit: usize, min_level: Option<LogLevel>) -> Vec<LogEntry> {
+    let max = min_level.unwrap_or(LogLevel::Trace);
+    RECENT
+        .lock()
+        .unwrap()
+        .iter()
+        .rev()
+        .filter(|entry| entry.level <= max)
+        .take(limit)
+        .cloned()
+        .collect()
+}
+
+/// What may be logged for field `name` with `value`
+pub fn scrub_field(name: &str, value: &str) -> String {
+    let name = name.to_lowercase();
+    if SECRET_FIELD_NAMES
+        .iter()
+        .any(|secret| name.contains(secret))
+    {
+        REDACTED.to_string()
+    } else {
+        scrub_text(value)
+    }
+}
+
+/// `text` with anything that looks like key material masked
+pub fn scrub_text(text: &str) -> String {
+    let is_key_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=' | '_');
+    let mut scrubbed = String::with_capacity(text.len());
+    let mut run = String::new();
+    let flush = |run: &mut String, scrubbed: &mut String| {
+        let looks_like_key = run.len() >= MIN_KEY_MATERIAL_RUN
+            && run.chars().any(|c| c.is_ascii_digit())
+            && run.chars().any(|c| c.is_ascii_alphabetic());
+        scrubbed.push_str(if looks_like_key { REDACTED } else { run });
+        run.clear();
+    };
+    for c in text.chars() {
+        if is_key_char(c) {
+            run.push(c);
+        } else {
+            flush(&mut run, &mut scrubbed);
+            scrubbed.push(c);
+        }
+    }
+    flush(&mut run, &mut scrubbed);
+    scrubbed
+}
+
+/// The events this crate logs at the configured level; dependencies only from `Warn` up
+fn wanted(metadata: &Metadata<'_>) -> bool {
+    let own = metadata.target().starts_with(env!("CARGO_CRATE_NAME"));
+    let max = if own {
+        level()
+    } else {
+        level().min(LogLevel::Warn)
+    };
+    LogLevel::from(metadata.level()) <= max
+}
+
+/// Scrubs each event and appends it to `RECENT`
+struct RecentLogsLayer;
+
+impl<S: Subscriber> Layer<S> for RecentLogsLayer {
+    // The level can change at runtime, so the answer must not be cached per callsite
+    fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        Interest::sometimes()
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+        wanted(metadata)
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let entry = LogEntry {
+            timestamp: Utc::now(),
+            level: LogLevel::from(event.metadata().level()),
+            target: event.metadata().target().to_string(),
+            message: scrub_text(&visitor.message),
+            fields: visitor.fields,
+        };
+        let mut recent = RECENT.lock().unwrap();
+        if recent.len() == RECENT_LOG_CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(entry);
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: BTreeMap<String, String>,
+}
+
+impl FieldVisitor {
+    fn record(&mut self, field: &Field, value: String) {
+        if field.name() == "message" {
+            self.message = value;
+        } else {
+            self.fields
+                .insert(field.name().to_string(), scrub_field(field.name(), &value));
+        }
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, format!("{:?}", value));
+    }
+}
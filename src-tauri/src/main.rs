@@ -2,5 +2,10 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // An ephemeral session keeps the vault key in a second copy of this executable
+    if std::env::args().nth(1).as_deref() == Some(secret_plan_lib::session::WORKER_ARG) {
+        secret_plan_lib::session::run_worker();
+        return;
+    }
     secret_plan_lib::run()
 }
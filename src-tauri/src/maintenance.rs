@@ -0,0 +1,85 @@
+//! Database housekeeping run while the user is away.
+//!
+//! The maintenance task of the app checks every few minutes whether the vault
+//! is unlocked and the user has been idle for `MAINTENANCE_IDLE`. If so, the
+//! tasks that are due run: a write-ahead log checkpoint, `PRAGMA optimize`,
+//! archiving old audit entries (see `audit_archive`) and removing the records
+//! of expired shares. When each task last ran is kept in an encrypted metadata
+//! entry, so the intervals hold across restarts. `run_maintenance_now` runs
+//! every task at once, idle or not.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Metadata entry holding `MaintenanceState`
+pub const MAINTENANCE_META_KEY: &str = "maintenance";
+/// Time without input after which maintenance may run
+pub const MAINTENANCE_IDLE: Duration = Duration::from_secs(120);
+
+/// A housekeeping job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceTask {
+    WalCheckpoint,
+    Optimize,
+    AuditArchive,
+    ShareCleanup,
+}
+
+impl MaintenanceTask {
+    pub const ALL: [MaintenanceTask; 4] = [
+        MaintenanceTask::WalCheckpoint,
+        MaintenanceTask::Optimize,
+        MaintenanceTask::AuditArchive,
+        MaintenanceTask::ShareCleanup,
+    ];
+
+    /// Time between two runs
+    pub fn interval(self) -> chrono::Duration {
+        match self {
+            MaintenanceTask::WalCheckpoint => chrono::Duration::hours(1),
+            MaintenanceTask::Optimize
+            | MaintenanceTask::AuditArchive
+            | MaintenanceTask::ShareCleanup => chrono::Duration::days(1),
+        }
+    }
+}
+
+/// When each task last ran
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaintenanceState {
+    pub last_run: BTreeMap<MaintenanceTask, DateTime<Utc>>,
+}
+
+impl MaintenanceState {
+    /// Tasks that never ran or whose interval has passed
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<MaintenanceTask> {
+        MaintenanceTask::ALL
+            .into_iter()
+            .filter(|task| {
+                self.last_run
+                    .get(task)
+                    .is_none_or(|at| now - *at >= task.interval())
+            })
+            .collect()
+    }
+}
+
+/// Result of a maintenance run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    /// Tasks that completed
+    pub ran: Vec<MaintenanceTask>,
+    /// Pages moved out of the write-ahead log, if there was one
+    pub checkpointed_pages: Option<u64>,
+    pub audit_entries_archived: usize,
+    pub shares_removed: usize,
+    /// One message per task that failed; it is retried on the next run
+    pub errors: Vec<String>,
+    /// When each task last completed, this run included
+    pub last_run: BTreeMap<MaintenanceTask, DateTime<Utc>>,
+}
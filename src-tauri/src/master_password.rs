@@ -0,0 +1,70 @@
+//! Reminders to change a weak or old master password.
+//!
+//! When the master password is set, its zxcvbn score and the time are stored
+//! encrypted in the vault metadata; the password itself never is. After each
+//! unlock the record is compared with `AppSettings::master_password_min_score`
+//! and `master_password_max_age_days`, and the user is reminded with a
+//! `master-password-reminder` event if either is not met.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::AppSettings;
+
+/// Metadata entry holding the `MasterPasswordRecord`
+pub const MASTER_PASSWORD_META_KEY: &str = "master_password";
+/// Event a `MasterPasswordReminder` is emitted as after unlocking
+pub const MASTER_PASSWORD_REMINDER_EVENT: &str = "master-password-reminder";
+
+/// What is known about the current master password
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MasterPasswordRecord {
+    /// zxcvbn score from 0 to 4 when the password was set
+    pub score: u8,
+    pub set_at: DateTime<Utc>,
+}
+
+/// Why changing the master password is suggested
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReminderReason {
+    /// The score is below `AppSettings::master_password_min_score`
+    Weak,
+    /// The password is older than `AppSettings::master_password_max_age_days`
+    Old,
+}
+
+/// "Consider changing your master password"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MasterPasswordReminder {
+    pub reasons: Vec<ReminderReason>,
+    pub score: u8,
+    pub min_score: u8,
+    pub set_at: DateTime<Utc>,
+    pub age_days: i64,
+}
+
+/// The reminder to show for `record` under `settings`, if any
+pub fn reminder(
+    record: &MasterPasswordRecord,
+    settings: &AppSettings,
+    now: DateTime<Utc>,
+) -> Option<MasterPasswordReminder> {
+    let age_days = (now - record.set_at).num_days();
+    let mut reasons = Vec::new();
+    if record.score < settings.master_password_min_score {
+        reasons.push(ReminderReason::Weak);
+    }
+    if settings.master_password_max_age_days > 0
+        && age_days >= i64::from(settings.master_password_max_age_days)
+    {
+        reasons.push(ReminderReason::Old);
+    }
+    (!reasons.is_empty()).then_some(MasterPasswordReminder {
+        reasons,
+        score: record.score,
+        min_score: settings.master_password_min_score,
+        set_at: record.set_at,
+        age_days,
+    })
+}
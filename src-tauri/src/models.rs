@@ -3,10 +3,23 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::api_keys::ApiKeyDetails;
+use crate::audit_level::AuditLevel;
+use crate::cards::CardDetails;
+use crate::error::{AppError, AppResult};
+use crate::generator::GeneratorConstraints;
+use crate::identity::Identity;
+use crate::logging::LogLevel;
+use crate::notifications::NotificationSettings;
+use crate::performance::PerformanceMode;
+use crate::recovery_codes::RecoveryCode;
+use crate::wifi::WifiDetails;
+
 /// Represents the breach status of a credential
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum BreachState {
     /// Not checked against breach database
+    #[default]
     Unknown = 0,
     /// Checked and not found in breach database
     Safe = 1,
@@ -14,10 +27,30 @@ pub enum BreachState {
     Compromised = 2,
 }
 
-impl Default for BreachState {
-    fn default() -> Self {
-        Self::Unknown
-    }
+/// How a credential's strength score was determined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum StrengthSource {
+    /// Estimated by the strength calculator from the password itself
+    #[default]
+    Analyzed = 0,
+    /// Computed exactly from the generator's character set and length
+    Generated = 1,
+}
+
+/// What an item holds, which decides how it is shown and used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum CredentialKind {
+    /// A website or app login
+    #[default]
+    Login = 0,
+    /// A developer secret (API key, token, database URL); the secret is in `password`
+    ApiKey = 1,
+    /// A payment card; the number is in `password`
+    CreditCard = 2,
+    /// A person's name, addresses, phone numbers and emails, in `Secret::identity`
+    Identity = 3,
+    /// A wireless network; the passphrase is in `password`
+    Wifi = 4,
 }
 
 /// Secret data that will be encrypted
@@ -31,6 +64,89 @@ pub struct Secret {
     pub totp: Option<String>,
     /// Additional custom fields (key-value pairs)
     pub custom_fields: HashMap<String, String>,
+    /// Previous passwords, newest first (maintained by the vault, not by callers)
+    #[serde(default)]
+    pub password_history: Vec<PasswordHistoryEntry>,
+    /// Keys auto-type sends, e.g. `{USERNAME}{TAB}{PASSWORD}{ENTER}`; None uses
+    /// `autotype::DEFAULT_SEQUENCE`
+    #[serde(default)]
+    pub autotype_sequence: Option<String>,
+    /// Details of an identity item
+    #[serde(default)]
+    pub identity: Option<Identity>,
+    /// Two-factor recovery codes, in the order the site listed them
+    #[serde(default)]
+    pub recovery_codes: Vec<RecoveryCode>,
+}
+
+/// Part of a secret that a caller can ask for on its own
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretField {
+    Password,
+    Notes,
+    Totp,
+    /// A custom field, by name
+    CustomField(String),
+    PasswordHistory,
+    Identity,
+    RecoveryCodes,
+}
+
+impl SecretField {
+    /// Name used in audit entries
+    pub fn label(&self) -> String {
+        match self {
+            SecretField::Password => "password".to_string(),
+            SecretField::Notes => "notes".to_string(),
+            SecretField::Totp => "totp".to_string(),
+            SecretField::CustomField(name) => format!("custom field \"{}\"", name),
+            SecretField::PasswordHistory => "password history".to_string(),
+            SecretField::Identity => "identity".to_string(),
+            SecretField::RecoveryCodes => "recovery codes".to_string(),
+        }
+    }
+}
+
+impl Secret {
+    /// Keeps only `fields`; everything else is left at its default (empty) value
+    pub fn redacted(self, fields: &[SecretField]) -> Secret {
+        let wants = |field: &SecretField| fields.contains(field);
+        Secret {
+            password: if wants(&SecretField::Password) {
+                self.password
+            } else {
+                String::new()
+            },
+            notes: self.notes.filter(|_| wants(&SecretField::Notes)),
+            totp: self.totp.filter(|_| wants(&SecretField::Totp)),
+            custom_fields: self
+                .custom_fields
+                .into_iter()
+                .filter(|(name, _)| wants(&SecretField::CustomField(name.clone())))
+                .collect(),
+            password_history: if wants(&SecretField::PasswordHistory) {
+                self.password_history
+            } else {
+                Vec::new()
+            },
+            autotype_sequence: None,
+            identity: self.identity.filter(|_| wants(&SecretField::Identity)),
+            recovery_codes: if wants(&SecretField::RecoveryCodes) {
+                self.recovery_codes
+            } else {
+                Vec::new()
+            },
+        }
+    }
+}
+
+/// A password a credential used before
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PasswordHistoryEntry {
+    pub password: String,
+    /// When this password was replaced
+    pub replaced_at: DateTime<Utc>,
 }
 
 /// Represents a credential (login information)
@@ -54,8 +170,49 @@ pub struct Credential {
     pub expires_at: Option<DateTime<Utc>>,
     /// Password strength score (0-100)
     pub strength: u8,
+    /// Whether `strength` is an estimate or exact
+    #[serde(default)]
+    pub strength_source: StrengthSource,
+    /// Entropy of a generated password in bits (None for analyzed passwords)
+    #[serde(default)]
+    pub entropy_bits: Option<f64>,
     /// Breach status
     pub breach_state: BreachState,
+    /// When the breach status was last determined (None = never checked)
+    pub breach_checked_at: Option<DateTime<Utc>>,
+    /// When the password itself last changed (unlike `updated_at`, which any edit bumps)
+    pub password_changed_at: DateTime<Utc>,
+    /// Shared collection the item belongs to (None = personal vault).
+    /// Collection items are encrypted with the collection key instead of the vault key.
+    #[serde(default)]
+    pub collection_id: Option<String>,
+    /// Decrypting this item needs the master password again, even while unlocked
+    #[serde(default)]
+    pub high_security: bool,
+    /// Bumped by the store on every write; an update carrying an older value is rejected
+    #[serde(default)]
+    pub revision: u64,
+    /// Password rules of the site, honored by `generate_password_for`
+    #[serde(default)]
+    pub generator_constraints: Option<GeneratorConstraints>,
+    #[serde(default)]
+    pub kind: CredentialKind,
+    /// Provider, key id and scopes of an `ApiKey` item
+    #[serde(default)]
+    pub api_key: Option<ApiKeyDetails>,
+    /// Brand, masked number and expiry of a `CreditCard` item
+    #[serde(default)]
+    pub card: Option<CardDetails>,
+    /// SSID and security type of a `Wifi` item
+    #[serde(default)]
+    pub wifi: Option<WifiDetails>,
+    /// Whether the secret holds a TOTP seed; kept outside it so lists can filter on it
+    #[serde(default)]
+    pub has_totp: bool,
+    /// When the secret was last revealed or typed (None = never). Not an edit, so
+    /// `updated_at` and `revision` stay as they are.
+    #[serde(default)]
+    pub last_used_at: Option<DateTime<Utc>>,
 }
 
 impl Credential {
@@ -71,13 +228,93 @@ impl Credential {
             updated_at: now,
             expires_at: None,
             strength: 0,
+            strength_source: StrengthSource::Analyzed,
+            entropy_bits: None,
             breach_state: BreachState::Unknown,
+            breach_checked_at: None,
+            password_changed_at: now,
+            collection_id: None,
+            high_security: false,
+            revision: 0,
+            generator_constraints: None,
+            kind: CredentialKind::Login,
+            api_key: None,
+            card: None,
+            wifi: None,
+            has_totp: false,
+            last_used_at: None,
+        }
+    }
+
+    /// zxcvbn-style score (0-4) derived from `strength`
+    pub fn strength_bucket(&self) -> u8 {
+        crate::strength::strength_bucket(self.strength)
+    }
+}
+
+/// A credential as listed to the frontend: everything but the encrypted secret,
+/// which the frontend never reads and which would bloat every list response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialSummary {
+    pub uuid: String,
+    pub site: String,
+    pub username: String,
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Password strength score (0-100)
+    pub strength: u8,
+    pub strength_source: StrengthSource,
+    pub entropy_bits: Option<f64>,
+    pub breach_state: BreachState,
+    pub breach_checked_at: Option<DateTime<Utc>>,
+    pub password_changed_at: DateTime<Utc>,
+    pub collection_id: Option<String>,
+    pub high_security: bool,
+    pub revision: u64,
+    pub generator_constraints: Option<GeneratorConstraints>,
+    pub kind: CredentialKind,
+    pub api_key: Option<ApiKeyDetails>,
+    pub card: Option<CardDetails>,
+    pub wifi: Option<WifiDetails>,
+    pub has_totp: bool,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<Credential> for CredentialSummary {
+    fn from(credential: Credential) -> Self {
+        Self {
+            uuid: credential.uuid,
+            site: credential.site,
+            username: credential.username,
+            tags: credential.tags,
+            created_at: credential.created_at,
+            updated_at: credential.updated_at,
+            expires_at: credential.expires_at,
+            strength: credential.strength,
+            strength_source: credential.strength_source,
+            entropy_bits: credential.entropy_bits,
+            breach_state: credential.breach_state,
+            breach_checked_at: credential.breach_checked_at,
+            password_changed_at: credential.password_changed_at,
+            collection_id: credential.collection_id,
+            high_security: credential.high_security,
+            revision: credential.revision,
+            generator_constraints: credential.generator_constraints,
+            kind: credential.kind,
+            api_key: credential.api_key,
+            card: credential.card,
+            wifi: credential.wifi,
+            has_totp: credential.has_totp,
+            last_used_at: credential.last_used_at,
         }
     }
 }
 
 /// Application settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)] // Settings saved by older versions lack newer fields
 pub struct AppSettings {
     /// Argon2 memory cost
     pub argon2_memory_kb: u32,
@@ -89,10 +326,248 @@ pub struct AppSettings {
     pub use_biometrics: bool,
     /// Auto-lock timeout in minutes (0 = never)
     pub auto_lock_timeout: u32,
+    /// What counts as activity for the auto-lock timeout
+    pub idle_detection: IdleDetection,
     /// Whether to sync with cloud storage
     pub enable_sync: bool,
     /// Sync provider details
     pub sync_config: Option<HashMap<String, String>>,
+    /// Directory for automatic encrypted backups (None = disabled)
+    pub backup_dir: Option<String>,
+    /// Hours between automatic backups
+    pub backup_interval_hours: u32,
+    /// Number of automatic backups to keep
+    pub backup_retention: u32,
+    /// Breach API endpoint (self-hosted Pwned Passwords mirrors)
+    pub breach_api: BreachApiSettings,
+    /// Seconds before a copied secret is cleared from the clipboard
+    pub clipboard_clear_seconds: u32,
+    /// Seconds a revealed password stays visible
+    pub reveal_timeout_seconds: u32,
+    /// Whether to lock the vault when the system suspends
+    pub lock_on_suspend: bool,
+    /// Minutes after which sensitive actions ask for the master password again (0 = always)
+    pub require_reauth_minutes: u32,
+    /// Hours between background breach checks (0 = disabled)
+    pub breach_check_interval_hours: u32,
+    /// Whether to check a password against the breach API as soon as it is saved
+    pub check_breach_on_save: bool,
+    /// Relay that one-time share links are uploaded to (None = file shares only)
+    pub share_relay_url: Option<String>,
+    /// Signed release manifest checked for updates (None = the project's releases)
+    pub update_manifest_url: Option<String>,
+    /// Proxy and offline mode for all network features
+    pub network: NetworkSettings,
+    /// How much the diagnostics log records
+    pub log_level: LogLevel,
+    /// How much the audit log records
+    pub audit_level: AuditLevel,
+    /// Email addresses watched for new data breaches
+    pub email_monitor: EmailMonitorSettings,
+    /// Days before an item's expiry date that it is reminded of (0 = no reminders)
+    pub expiry_reminder_days: u32,
+    /// Lowest strength score (0-4) a saved password may have (0 = any)
+    pub min_password_score: u8,
+    /// Refuse passwords found on an imported breach list
+    pub block_compromised_passwords: bool,
+    /// Master passwords scoring below this (0-4) are reminded of after unlocking
+    pub master_password_min_score: u8,
+    /// Days after which changing the master password is suggested (0 = never)
+    pub master_password_max_age_days: u32,
+    /// When large-vault optimizations apply
+    pub performance_mode: PerformanceMode,
+    /// Keep the vault key in a separate process that is ended on lock, see
+    /// `session`. Turning it off takes effect at the next unlock.
+    pub ephemeral_session: bool,
+    /// Which kinds of desktop notification are shown
+    pub notifications: NotificationSettings,
+}
+
+/// Source of idle time for the auto-lock timeout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdleDetection {
+    /// Keyboard and mouse input anywhere on the system, falling back to app
+    /// activity where the platform cannot report it
+    #[default]
+    System,
+    /// Activity inside the app only
+    App,
+}
+
+/// Color scheme of the UI
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Light,
+    Dark,
+    #[default]
+    System,
+}
+
+/// Non-sensitive preferences stored unencrypted so the lock screen can use them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiPreferences {
+    pub theme: Theme,
+    /// UI language tag (None = follow the system)
+    pub locale: Option<String>,
+    /// Copy of `AppSettings::auto_lock_timeout` for display while locked
+    pub auto_lock_timeout: u32,
+}
+
+impl Default for UiPreferences {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            locale: None,
+            auto_lock_timeout: AppSettings::default().auto_lock_timeout,
+        }
+    }
+}
+
+/// Where a vault keeps its credentials. Stored unencrypted, since the backend
+/// has to be chosen before the vault can be unlocked, and only shown to an
+/// unlocked vault, with the token redacted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum StorageProfile {
+    /// The local database
+    #[default]
+    Local,
+    /// A self-hosted vault server that only ever receives ciphertext
+    Remote {
+        url: String,
+        /// Bearer token. The server only holds ciphertext, but the token lets
+        /// whoever has it read, overwrite and delete every item there.
+        token: String,
+    },
+}
+
+impl StorageProfile {
+    pub fn backend(&self) -> &'static str {
+        match self {
+            StorageProfile::Local => "local",
+            StorageProfile::Remote { .. } => "remote",
+        }
+    }
+}
+
+/// Addresses to look up in the breached-account API, and how to reach it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailMonitorSettings {
+    pub emails: Vec<String>,
+    /// Key for the breached-account API; monitoring is off without one
+    pub api_key: Option<String>,
+    /// Base URL of the API (None = haveibeenpwned.com)
+    pub base_url: Option<String>,
+    /// Hours between automatic checks (0 = only when asked)
+    pub interval_hours: u32,
+}
+
+impl Default for EmailMonitorSettings {
+    fn default() -> Self {
+        Self {
+            emails: Vec::new(),
+            api_key: None,
+            base_url: None,
+            interval_hours: 24,
+        }
+    }
+}
+
+/// Connection settings for a Pwned Passwords compatible breach API
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BreachApiSettings {
+    /// Base URL of the API (None = the public api.pwnedpasswords.com)
+    pub base_url: Option<String>,
+    /// PEM file with an extra CA certificate to trust
+    pub ca_cert_path: Option<String>,
+    /// PEM client certificate for mutual TLS
+    pub client_cert_path: Option<String>,
+    /// PKCS#8 PEM private key for `client_cert_path`
+    pub client_key_path: Option<String>,
+    /// Hex SHA-256 fingerprints of the server public keys (SPKI) to accept, see
+    /// `net::parse_pin` (empty = any trusted certificate)
+    pub pinned_certificates: Vec<String>,
+}
+
+/// How outbound requests reach the internet
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum ProxySettings {
+    /// The operating system's proxy settings and the HTTP(S)_PROXY variables
+    #[default]
+    System,
+    /// Connect directly, ignoring any system proxy
+    Direct,
+    /// Always use this HTTP(S) proxy
+    Manual { url: String },
+}
+
+/// Settings for all outbound network traffic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkSettings {
+    /// Turns off every feature that talks to the network
+    pub offline: bool,
+    pub proxy: ProxySettings,
+    /// Breach lookups of passwords and of monitored email addresses
+    pub allow_hibp: bool,
+    /// Downloading website icons
+    pub allow_icons: bool,
+    /// Remote vault storage
+    pub allow_sync: bool,
+    /// Asking whether a new version is available
+    pub allow_update_checks: bool,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            offline: false,
+            proxy: ProxySettings::default(),
+            allow_hibp: true,
+            allow_icons: true,
+            allow_sync: true,
+            allow_update_checks: true,
+        }
+    }
+}
+
+impl NetworkSettings {
+    /// Whether the permission for `feature` is on; `offline` is not considered
+    pub fn allows(&self, feature: NetFeature) -> bool {
+        match feature {
+            NetFeature::Hibp => self.allow_hibp,
+            NetFeature::Icons => self.allow_icons,
+            NetFeature::Sync => self.allow_sync,
+            NetFeature::UpdateChecks => self.allow_update_checks,
+        }
+    }
+}
+
+/// Features with their own network permission in `NetworkSettings`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetFeature {
+    Hibp,
+    Icons,
+    Sync,
+    UpdateChecks,
+}
+
+impl NetFeature {
+    pub fn name(self) -> &'static str {
+        match self {
+            NetFeature::Hibp => "breach checks",
+            NetFeature::Icons => "website icons",
+            NetFeature::Sync => "sync",
+            NetFeature::UpdateChecks => "update checks",
+        }
+    }
 }
 
 impl Default for AppSettings {
@@ -103,9 +578,112 @@ impl Default for AppSettings {
             argon2_parallelism: 4,
             use_biometrics: true,
             auto_lock_timeout: 5,
+            idle_detection: IdleDetection::default(),
             enable_sync: false,
             sync_config: None,
+            backup_dir: None,
+            backup_interval_hours: 24,
+            backup_retention: 7,
+            breach_api: BreachApiSettings::default(),
+            clipboard_clear_seconds: 30,
+            reveal_timeout_seconds: 15,
+            lock_on_suspend: true,
+            require_reauth_minutes: 15,
+            breach_check_interval_hours: 168, // Weekly
+            check_breach_on_save: false,
+            share_relay_url: None,
+            update_manifest_url: None,
+            network: NetworkSettings::default(),
+            log_level: LogLevel::default(),
+            audit_level: AuditLevel::default(),
+            email_monitor: EmailMonitorSettings::default(),
+            expiry_reminder_days: crate::api_keys::DEFAULT_EXPIRY_REMINDER_DAYS,
+            min_password_score: 0,
+            block_compromised_passwords: false,
+            master_password_min_score: crate::strength::MASTER_PASSWORD_MIN_SCORE,
+            master_password_max_age_days: 365,
+            performance_mode: PerformanceMode::default(),
+            ephemeral_session: false,
+            notifications: NotificationSettings::default(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// Checks that every value is within the range the app supports
+    pub fn validate(&self) -> AppResult<()> {
+        fn check(ok: bool, message: &str) -> AppResult<()> {
+            if ok {
+                Ok(())
+            } else {
+                Err(AppError::InvalidInput(message.to_string()))
+            }
+        }
+
+        check(
+            self.argon2_iterations >= 1,
+            "Argon2 iterations must be at least 1",
+        )?;
+        check(
+            (1..=16).contains(&self.argon2_parallelism),
+            "Argon2 parallelism must be between 1 and 16",
+        )?;
+        check(
+            self.argon2_memory_kb >= 8 * self.argon2_parallelism,
+            "Argon2 memory must be at least 8 KiB per lane",
+        )?;
+        check(
+            self.auto_lock_timeout <= 24 * 60,
+            "Auto-lock timeout must be at most 24 hours",
+        )?;
+        check(
+            self.backup_interval_hours >= 1,
+            "Backup interval must be at least 1 hour",
+        )?;
+        check(
+            self.backup_retention >= 1,
+            "At least one backup must be kept",
+        )?;
+        check(
+            (5..=600).contains(&self.clipboard_clear_seconds),
+            "Clipboard clear delay must be between 5 and 600 seconds",
+        )?;
+        check(
+            (5..=300).contains(&self.reveal_timeout_seconds),
+            "Reveal timeout must be between 5 and 300 seconds",
+        )?;
+        check(
+            self.require_reauth_minutes <= 24 * 60,
+            "Re-authentication interval must be at most 24 hours",
+        )?;
+        check(
+            self.breach_check_interval_hours <= 24 * 30,
+            "Breach check interval must be at most 30 days",
+        )?;
+        check(
+            self.email_monitor.interval_hours <= 24 * 30,
+            "Email monitoring interval must be at most 30 days",
+        )?;
+        check(
+            self.expiry_reminder_days <= 365,
+            "Expiry reminders must start at most a year ahead",
+        )?;
+        check(
+            self.min_password_score <= 4,
+            "Minimum password score must be between 0 and 4",
+        )?;
+        check(
+            self.master_password_min_score <= 4,
+            "Minimum master password score must be between 0 and 4",
+        )?;
+        check(
+            self.master_password_max_age_days <= 10 * 365,
+            "Master password reminders must start within ten years",
+        )?;
+        for email in &self.email_monitor.emails {
+            crate::email_monitor::validate_email(email)?;
         }
+        Ok(())
     }
 }
 
@@ -0,0 +1,314 @@
+//! Outbound HTTP. Every feature that talks to the network builds its client
+//! through `Network`, which applies the proxy configuration, the feature's own
+//! permission and the global offline switch, so a new feature cannot bypass them.
+//!
+//! Besides the `offline` setting of each `Network`, `set_offline` switches the
+//! whole process offline at once, including work already under way: every
+//! request builds its client first, and building fails while offline.
+
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::{Client, ClientBuilder, Identity, Proxy, Url};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, WebPkiSupportedAlgorithms};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::server::ParsedCertificate;
+use rustls::{CertificateError, ClientConfig, DigitallySignedStruct, SignatureScheme};
+use sha2::{Digest, Sha256};
+use tracing::debug;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{NetFeature, NetworkSettings, ProxySettings};
+
+/// Sent with every request
+pub const USER_AGENT: &str = concat!("SecretPlanApp/", env!("CARGO_PKG_VERSION"));
+
+/// Upper bound for any single request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The process-wide offline switch, see `set_offline`
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Switches every network feature off or back on, taking effect for the next
+/// request of any `Network`
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::SeqCst);
+}
+
+/// Proxy, permission and offline configuration shared by all network features
+#[derive(Debug, Clone, Default)]
+pub struct Network {
+    settings: NetworkSettings,
+}
+
+impl Network {
+    /// Validates the user's network settings
+    pub fn new(settings: &NetworkSettings) -> AppResult<Self> {
+        if let ProxySettings::Manual { url } = &settings.proxy {
+            proxy_for(url)?;
+        }
+        Ok(Self {
+            settings: settings.clone(),
+        })
+    }
+
+    /// Whether network features are switched off, by the settings or `set_offline`
+    pub fn is_offline(&self) -> bool {
+        self.settings.offline || OFFLINE.load(Ordering::SeqCst)
+    }
+
+    /// Whether `feature` may use the network
+    pub fn allows(&self, feature: NetFeature) -> bool {
+        !self.is_offline() && self.settings.allows(feature)
+    }
+
+    /// `client_builder` for a service that belongs to `feature`. Fails with
+    /// `AppError::Network` when the user has not allowed the feature network access.
+    pub fn feature_client_builder(
+        &self,
+        feature: NetFeature,
+        service: &str,
+    ) -> AppResult<ClientBuilder> {
+        if !self.settings.allows(feature) {
+            debug!(
+                service,
+                feature = feature.name(),
+                "request refused by network permissions"
+            );
+            return Err(AppError::Network(format!(
+                "{} is unavailable: network access for {} is turned off",
+                service,
+                feature.name()
+            )));
+        }
+        self.client_builder(service)
+    }
+
+    /// A client builder for `service` with the timeout, user agent and proxy applied.
+    /// Fails with `AppError::Network` in offline mode.
+    pub fn client_builder(&self, service: &str) -> AppResult<ClientBuilder> {
+        if self.is_offline() {
+            debug!(service, "request refused in offline mode");
+            return Err(AppError::Network(format!(
+                "{} is unavailable in offline mode",
+                service
+            )));
+        }
+        let builder = ClientBuilder::new()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent(USER_AGENT);
+        // Only the kind of proxy: a manual proxy URL may carry credentials
+        let proxy = match &self.settings.proxy {
+            ProxySettings::System => "system",
+            ProxySettings::Direct => "direct",
+            ProxySettings::Manual { .. } => "manual",
+        };
+        debug!(service, proxy, "building HTTP client");
+        Ok(match &self.settings.proxy {
+            // reqwest reads HTTP(S)_PROXY and the macOS/Windows system settings
+            ProxySettings::System => builder,
+            ProxySettings::Direct => builder.no_proxy(),
+            ProxySettings::Manual { url } => builder.proxy(proxy_for(url)?),
+        })
+    }
+}
+
+fn proxy_for(url: &str) -> AppResult<Proxy> {
+    let parsed =
+        Url::parse(url.trim()).map_err(|e| AppError::Other(format!("Invalid proxy URL: {}", e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(AppError::Other(format!(
+            "Proxy URL must use http or https (got {})",
+            parsed.scheme()
+        )));
+    }
+    Proxy::all(parsed).map_err(|e| AppError::Other(format!("Invalid proxy URL: {}", e)))
+}
+
+/// Finishes a builder from `Network::client_builder`
+pub fn build_client(builder: ClientBuilder) -> AppResult<Client> {
+    builder
+        .build()
+        .map_err(|e| AppError::Other(format!("Failed to create HTTP client: {}", e)))
+}
+
+/// Parses a certificate pin: the hex SHA-256 of the server certificate's
+/// DER SubjectPublicKeyInfo (colons optional), as printed by
+/// `openssl x509 -noout -pubkey | openssl pkey -pubin -outform der | openssl dgst -sha256`.
+/// Pinning the public key rather than the certificate survives renewals that keep the key.
+pub fn parse_pin(pin: &str) -> AppResult<[u8; 32]> {
+    let hex: String = pin.chars().filter(|c| *c != ':').collect();
+    let invalid = || AppError::Other(format!("Invalid certificate pin: {}", pin));
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+    }
+    Ok(bytes)
+}
+
+/// A client certificate and its PKCS#8 key, PEM encoded, for mutual TLS
+#[derive(Clone)]
+pub struct ClientIdentity {
+    cert_pem: Vec<u8>,
+    key_pem: Vec<u8>,
+}
+
+impl ClientIdentity {
+    /// Checks that the certificate and key can be used
+    pub fn from_pem(cert_pem: Vec<u8>, key_pem: Vec<u8>) -> AppResult<Self> {
+        let identity = Self { cert_pem, key_pem };
+        identity.native()?;
+        Ok(identity)
+    }
+
+    /// The identity for the platform TLS stack
+    pub fn native(&self) -> AppResult<Identity> {
+        Identity::from_pkcs8_pem(&self.cert_pem, &self.key_pem)
+            .map_err(|e| AppError::Other(format!("Invalid client certificate: {}", e)))
+    }
+}
+
+/// Accepts a server only if its certificate carries one of the pinned public
+/// keys and names the host. The pin takes the place of the CA chain, so it is
+/// checked during the handshake, before a single request byte is sent.
+#[derive(Debug)]
+pub struct PinnedServerVerifier {
+    pins: Vec<[u8; 32]>,
+    algorithms: WebPkiSupportedAlgorithms,
+}
+
+impl PinnedServerVerifier {
+    pub fn new(pins: &[[u8; 32]]) -> Self {
+        Self {
+            pins: pins.to_vec(),
+            algorithms: rustls::crypto::ring::default_provider().signature_verification_algorithms,
+        }
+    }
+}
+
+impl ServerCertVerifier for PinnedServerVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let certificate = ParsedCertificate::try_from(end_entity)?;
+        let spki: [u8; 32] = Sha256::digest(certificate.subject_public_key_info()).into();
+        if !self.pins.contains(&spki) {
+            return Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ));
+        }
+        rustls::client::verify_server_name(&certificate, server_name)?;
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
+
+/// TLS settings that only trust servers holding a key in `pins`, see
+/// `PinnedServerVerifier`. Pass it to `ClientBuilder::use_preconfigured_tls`.
+pub fn pinned_tls_config(
+    pins: &[[u8; 32]],
+    identity: Option<&ClientIdentity>,
+) -> AppResult<ClientConfig> {
+    let tls_error = |e: rustls::Error| AppError::Other(format!("Invalid TLS settings: {}", e));
+    let builder =
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(tls_error)?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(PinnedServerVerifier::new(pins)));
+    let Some(identity) = identity else {
+        return Ok(builder.with_no_client_auth());
+    };
+    let invalid = || AppError::Other("Invalid client certificate".to_string());
+    let certificates = rustls_pemfile::certs(&mut identity.cert_pem.as_slice())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| invalid())?;
+    let key = rustls_pemfile::private_key(&mut identity.key_pem.as_slice())
+        .ok()
+        .flatten()
+        .ok_or_else(invalid)?;
+    builder
+        .with_client_auth_cert(certificates, key)
+        .map_err(tls_error)
+}
+
+/// Validates the base URL of an HTTP service the app talks to and returns it
+/// without a trailing slash; `service` names it in error messages.
+/// Plain HTTP is only accepted for loopback hosts.
+pub fn validate_service_url(url: &str, service: &str) -> AppResult<String> {
+    let parsed = Url::parse(url.trim())
+        .map_err(|e| AppError::Other(format!("Invalid {} URL: {}", service, e)))?;
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| AppError::Other(format!("{} URL must include a host", service)))?;
+    let is_loopback = host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_matches(|c| c == '[' || c == ']')
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback());
+    match parsed.scheme() {
+        "https" => {}
+        "http" if is_loopback => {}
+        scheme => {
+            return Err(AppError::Other(format!(
+                "{} URL must use https (got {})",
+                service, scheme
+            )))
+        }
+    }
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        return Err(AppError::Other(format!(
+            "{} URL must not contain a query or fragment",
+            service
+        )));
+    }
+    if !parsed.username().is_empty() || parsed.password().is_some() {
+        return Err(AppError::Other(format!(
+            "{} URL must not contain credentials",
+            service
+        )));
+    }
+    Ok(parsed.as_str().trim_end_matches('/').to_string())
+}
+
+/// Transport-level failures (DNS, refused connection, timeout) mean the
+/// service is unreachable; `service` names it in messages
+pub fn classify_service_error(error: reqwest::Error, service: &str) -> AppError {
+    if error.is_connect() || error.is_timeout() || error.is_request() {
+        AppError::Network(format!("{} unreachable: {}", service, error))
+    } else {
+        AppError::Other(format!("Failed to send request to {}: {}", service, error))
+    }
+}
@@ -0,0 +1,79 @@
+//! Detection of reused AES-GCM nonces.
+//!
+//! Every encryption draws a fresh random 96-bit nonce, so two containers under
+//! the same key should never share one. If they do, the random number generator
+//! has failed, and GCM loses both confidentiality and integrity for the affected
+//! pair. The audit only reads the nonces, which are stored in the clear, and
+//! groups them by the key they were used with: the vault key, a collection key
+//! or the travel key.
+
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde::{Deserialize, Serialize};
+
+/// Key epoch of containers encrypted with the vault key
+pub const VAULT_EPOCH: &str = "vault";
+/// Key epoch of the travel stash
+pub const TRAVEL_EPOCH: &str = "travel";
+
+/// Key epoch of items in a shared collection
+pub fn collection_epoch(collection_id: &str) -> String {
+    format!("collection:{}", collection_id)
+}
+
+/// One encrypted value found in the vault
+#[derive(Debug, Clone)]
+pub struct NonceUse {
+    /// The key the value was encrypted with, e.g. `VAULT_EPOCH`
+    pub key_epoch: String,
+    pub nonce: Vec<u8>,
+    /// Where the value is stored, e.g. `item <uuid>`
+    pub location: String,
+}
+
+/// A nonce used more than once with one key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NonceReuse {
+    pub key_epoch: String,
+    /// Base64
+    pub nonce: String,
+    pub locations: Vec<String>,
+}
+
+/// Result of `verify_no_nonce_reuse`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NonceAuditReport {
+    pub containers_scanned: usize,
+    /// Empty for a healthy vault
+    pub reused: Vec<NonceReuse>,
+    /// Stored values whose nonce could not be read
+    pub unreadable: Vec<String>,
+}
+
+impl NonceAuditReport {
+    pub fn is_ok(&self) -> bool {
+        self.reused.is_empty()
+    }
+}
+
+/// Groups `uses` by key epoch and nonce, returning every pair seen more than once
+pub fn find_reuse(uses: &[NonceUse]) -> Vec<NonceReuse> {
+    let mut seen: HashMap<(&str, &[u8]), Vec<&str>> = HashMap::new();
+    for nonce_use in uses {
+        seen.entry((nonce_use.key_epoch.as_str(), nonce_use.nonce.as_slice()))
+            .or_default()
+            .push(nonce_use.location.as_str());
+    }
+    let mut reused: Vec<NonceReuse> = seen
+        .into_iter()
+        .filter(|(_, locations)| locations.len() > 1)
+        .map(|((key_epoch, nonce), locations)| NonceReuse {
+            key_epoch: key_epoch.to_string(),
+            nonce: BASE64.encode(nonce),
+            locations: locations.into_iter().map(str::to_string).collect(),
+        })
+        .collect();
+    reused.sort_by(|a, b| (&a.key_epoch, &a.nonce).cmp(&(&b.key_epoch, &b.nonce)));
+    reused
+}
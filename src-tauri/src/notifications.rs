@@ -0,0 +1,109 @@
+//! Desktop notifications for what the backend finds by itself.
+//!
+//! Background jobs report a breached email address, items about to expire, a
+//! vault changed by a sync client or a failed backup through `dispatch`. Each
+//! goes into a history kept in an encrypted metadata entry, whether or not it
+//! is shown, since titles name sites and addresses. It is then handed to a
+//! `NotificationSink`, the system notification center in the app, if its
+//! category is enabled in `NotificationSettings`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::AppResult;
+use crate::i18n::Message;
+
+/// Metadata entry holding the notification history
+pub const NOTIFICATIONS_META_KEY: &str = "notifications";
+/// Notifications kept in the history, newest first
+pub const NOTIFICATION_HISTORY_LIMIT: usize = 200;
+
+/// What a notification is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+    /// A monitored email address turned up in a breach
+    Breach,
+    /// Items reach their expiry date soon
+    Expiry,
+    /// Another program, such as a sync client, changed the open vault
+    SyncConflict,
+    /// A scheduled backup failed
+    BackupFailed,
+}
+
+/// Which categories are shown; the history records all of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub breach: bool,
+    pub expiry: bool,
+    pub sync_conflict: bool,
+    pub backup_failed: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            breach: true,
+            expiry: true,
+            sync_conflict: true,
+            backup_failed: true,
+        }
+    }
+}
+
+impl NotificationSettings {
+    pub fn enabled(&self, category: NotificationCategory) -> bool {
+        match category {
+            NotificationCategory::Breach => self.breach,
+            NotificationCategory::Expiry => self.expiry,
+            NotificationCategory::SyncConflict => self.sync_conflict,
+            NotificationCategory::BackupFailed => self.backup_failed,
+        }
+    }
+}
+
+/// An entry of the notification history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: String,
+    pub category: NotificationCategory,
+    pub title: Message,
+    pub body: Message,
+    pub created_at: DateTime<Utc>,
+    /// Whether it was handed to the sink, i.e. its category was enabled and
+    /// showing it did not fail
+    pub shown: bool,
+    /// Set by `mark_notifications_read`
+    #[serde(default)]
+    pub read: bool,
+}
+
+impl Notification {
+    pub fn new(category: NotificationCategory, title: Message, body: Message) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            category,
+            title,
+            body,
+            created_at: Utc::now(),
+            shown: false,
+            read: false,
+        }
+    }
+}
+
+/// Where notifications are shown
+pub trait NotificationSink {
+    /// Shows already rendered text
+    fn show(&self, title: &str, body: &str) -> AppResult<()>;
+}
+
+/// Adds `notification` to the front of `history`, dropping the oldest beyond
+/// `NOTIFICATION_HISTORY_LIMIT`
+pub fn record(history: &mut Vec<Notification>, notification: Notification) {
+    history.insert(0, notification);
+    history.truncate(NOTIFICATION_HISTORY_LIMIT);
+}
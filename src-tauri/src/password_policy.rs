@@ -0,0 +1,78 @@
+//! Rules a password has to meet before it is saved.
+//!
+//! With `AppSettings::min_password_score` or `block_compromised_passwords` set,
+//! `VaultManager::add_credential` and `update_credential` refuse weak or
+//! known-compromised passwords with `AppError::PasswordPolicy`. The error says
+//! what was wrong, so the UI can explain it and offer to save anyway, which it
+//! does by calling again with `PolicyCheck::SaveAnyway`.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::i18n::Message;
+use crate::models::AppSettings;
+
+/// Whether a save goes through the password policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PolicyCheck {
+    Enforce,
+    /// The user saw the violation and chose to save anyway
+    SaveAnyway,
+}
+
+impl PolicyCheck {
+    /// From a command's `save_anyway` flag
+    pub fn from_save_anyway(save_anyway: Option<bool>) -> Self {
+        if save_anyway.unwrap_or(false) {
+            PolicyCheck::SaveAnyway
+        } else {
+            PolicyCheck::Enforce
+        }
+    }
+}
+
+/// Why a password was refused
+#[derive(Debug, Clone, PartialEq, Eq, Error, Serialize)]
+#[error("{}", self.message().audit_text())]
+pub struct PolicyViolation {
+    /// The password's score (0-4) when it is below `min_score`
+    pub score: Option<u8>,
+    pub min_score: u8,
+    /// Imported breach lists the password is on
+    pub breach_sources: Vec<String>,
+}
+
+impl PolicyViolation {
+    /// The translatable form; a compromised password is the more urgent problem
+    pub fn message(&self) -> Message {
+        match (self.breach_sources.is_empty(), self.score) {
+            (false, _) => Message::new("error.password_compromised")
+                .with_param("sources", self.breach_sources.join(", ")),
+            (true, score) => Message::new("error.password_too_weak")
+                .with_param("score", score.unwrap_or_default())
+                .with_param("min_score", self.min_score),
+        }
+    }
+}
+
+/// Checks a password's score (0-4) and the breach lists it is on against the settings
+pub fn check(
+    settings: &AppSettings,
+    score: u8,
+    breach_sources: Vec<String>,
+) -> Result<(), PolicyViolation> {
+    let too_weak = score < settings.min_password_score;
+    let compromised = settings.block_compromised_passwords && !breach_sources.is_empty();
+    if !too_weak && !compromised {
+        return Ok(());
+    }
+    Err(PolicyViolation {
+        score: too_weak.then_some(score),
+        min_score: settings.min_password_score,
+        breach_sources: if compromised {
+            breach_sources
+        } else {
+            Vec::new()
+        },
+    })
+}
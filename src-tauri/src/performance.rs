@@ -0,0 +1,39 @@
+//! Performance mode for large vaults.
+//!
+//! Past `LARGE_VAULT_ITEMS` items (or always, if the user asks for it) the vault
+//! keeps the quick search index built, answers item lists from the summaries in
+//! that index instead of reading every row, and gives SQLite a larger page
+//! cache. `AppSettings::performance_mode` chooses when the mode applies; whether
+//! it is active is reported by `get_vault_statistics`.
+
+use serde::{Deserialize, Serialize};
+
+/// Soft limit on the number of items: `PerformanceMode::Auto` turns the mode on
+/// from here
+pub const LARGE_VAULT_ITEMS: usize = 5_000;
+/// SQLite page cache while the mode is active
+pub const PERFORMANCE_CACHE_KIB: u32 = 64 * 1024;
+/// SQLite's own default page cache
+pub const DEFAULT_CACHE_KIB: u32 = 2_000;
+
+/// When performance mode applies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PerformanceMode {
+    /// Once the vault holds `LARGE_VAULT_ITEMS` items
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+impl PerformanceMode {
+    /// Whether the mode is active for a vault of `items` items
+    pub fn is_active(self, items: usize) -> bool {
+        match self {
+            PerformanceMode::Auto => items >= LARGE_VAULT_ITEMS,
+            PerformanceMode::On => true,
+            PerformanceMode::Off => false,
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Portable mode: keeping the vault next to the executable.
+//!
+//! Normally the vault lives in the platform's app data directory. If a file
+//! named `PORTABLE_MARKER` sits next to the executable, everything lives in a
+//! folder beside it instead, so the app can run from a USB stick without
+//! leaving anything behind. The marker may name that folder on its first
+//! line, absolute or relative to the executable; it defaults to `data`.
+//!
+//! In both modes a relative backup folder or export path is taken from the
+//! data directory rather than from wherever the app happened to be started.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppResult;
+
+/// File next to the executable that turns on portable mode
+pub const PORTABLE_MARKER: &str = "portable.txt";
+/// Data folder of a portable install whose marker names none
+pub const DEFAULT_PORTABLE_DIR: &str = "data";
+/// The vault database, inside the data directory
+pub const VAULT_FILE: &str = "vault.db";
+
+/// Where the vault and the files that go with it are kept
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataLocation {
+    pub dir: PathBuf,
+    pub portable: bool,
+}
+
+impl DataLocation {
+    /// Portable if `exe_dir` holds a marker, otherwise `installed_dir`
+    pub fn detect(exe_dir: Option<&Path>, installed_dir: PathBuf) -> AppResult<Self> {
+        Ok(match exe_dir.map(portable_dir).transpose()?.flatten() {
+            Some(dir) => Self {
+                dir,
+                portable: true,
+            },
+            None => Self {
+                dir: installed_dir,
+                portable: false,
+            },
+        })
+    }
+
+    pub fn vault_path(&self) -> PathBuf {
+        self.dir.join(VAULT_FILE)
+    }
+}
+
+/// The data folder a marker in `exe_dir` names, or None without a marker
+pub fn portable_dir(exe_dir: &Path) -> AppResult<Option<PathBuf>> {
+    let contents = match fs::read_to_string(exe_dir.join(PORTABLE_MARKER)) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let named = contents
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty());
+    Ok(Some(exe_dir.join(named.unwrap_or(DEFAULT_PORTABLE_DIR))))
+}
+
+/// `path` taken from `data_dir` if it is relative
+pub fn resolve(data_dir: Option<&Path>, path: &Path) -> PathBuf {
+    match data_dir {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path.to_path_buf(),
+    }
+}
@@ -0,0 +1,188 @@
+//! Moving a single item to another device with a QR code.
+//!
+//! The item is encrypted under a key derived from a PIN the user chooses and
+//! tells the other device out of band. Anyone who photographs the QR code can
+//! try PINs offline for as long as they like; the expiry only stops imports.
+//! So the PIN must be at least `MIN_PIN_LEN` characters and, by zxcvbn's
+//! estimate, take `10^MIN_PIN_GUESSES_LOG10` guesses, which the slow key
+//! derivation makes out of reach. The expiry is bound to the ciphertext, so it
+//! cannot be extended without the PIN.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zxcvbn::zxcvbn;
+
+use crate::api_keys::ApiKeyDetails;
+use crate::cards::CardDetails;
+use crate::crypto::CryptoService;
+use crate::error::{AppError, AppResult, CryptoError};
+use crate::models::{Credential, CredentialKind, Secret};
+use crate::wifi::{self, WifiDetails};
+
+/// Prefix of every transfer payload, so a scanner can tell it apart
+pub const PAYLOAD_PREFIX: &str = "SPQR1:";
+/// How long a transfer code can be imported
+pub const TRANSFER_LIFETIME_MINUTES: i64 = 10;
+pub const MIN_PIN_LEN: usize = 10;
+const MAX_PIN_LEN: usize = 64;
+/// Guesses, as a power of ten, a PIN must take by zxcvbn's estimate; its
+/// highest score starts here
+pub const MIN_PIN_GUESSES_LOG10: f64 = 10.0;
+/// Argon2id cost for the PIN: 64 MiB, 3 passes
+const PIN_KDF_MEMORY_KB: u32 = 64 * 1024;
+const PIN_KDF_ITERATIONS: u32 = 3;
+const SALT_BYTES: usize = 16;
+
+/// What a transfer code carries: the item without its password history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferredItem {
+    pub site: String,
+    pub username: String,
+    pub tags: Vec<String>,
+    pub kind: CredentialKind,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub api_key: Option<ApiKeyDetails>,
+    pub card: Option<CardDetails>,
+    pub wifi: Option<WifiDetails>,
+    pub secret: Secret,
+}
+
+impl TransferredItem {
+    pub fn new(credential: &Credential, mut secret: Secret) -> Self {
+        secret.password_history.clear();
+        Self {
+            site: credential.site.clone(),
+            username: credential.username.clone(),
+            tags: credential.tags.clone(),
+            kind: credential.kind,
+            expires_at: credential.expires_at,
+            api_key: credential.api_key.clone(),
+            card: credential.card.clone(),
+            wifi: credential.wifi.clone(),
+            secret,
+        }
+    }
+}
+
+/// A transfer code to show on screen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialQr {
+    pub site: String,
+    /// The text encoded in the QR code, for pasting when no camera is available
+    pub payload: String,
+    pub svg: String,
+    /// Base64 PNG
+    pub png: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    salt: String,
+    /// Unix seconds
+    expires_at: i64,
+    /// `CryptoService::encrypt_with_key` container holding a `TransferredItem`
+    item: String,
+}
+
+/// Checks a PIN for a new transfer code; `context` is what the code is about,
+/// such as the site and username, which do not count towards its strength
+fn check_pin(pin: &str, context: &[&str]) -> AppResult<()> {
+    let length = pin.chars().count();
+    if !(MIN_PIN_LEN..=MAX_PIN_LEN).contains(&length) {
+        return Err(AppError::InvalidInput(format!(
+            "The PIN must have {MIN_PIN_LEN} to {MAX_PIN_LEN} characters"
+        )));
+    }
+    if zxcvbn(pin, context).guesses_log10() < MIN_PIN_GUESSES_LOG10 {
+        return Err(AppError::InvalidInput(
+            "The PIN is too easy to guess; use a few unrelated words, or mix letters, digits and symbols"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn pin_key(pin: &str, salt: &[u8]) -> AppResult<[u8; 32]> {
+    let params = Params::new(PIN_KDF_MEMORY_KB, PIN_KDF_ITERATIONS, 1, Some(32))
+        .map_err(|e| CryptoError::KeyDerivation(format!("Invalid PIN KDF parameters: {}", e)))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(pin.as_bytes(), salt, &mut key)
+        .map_err(|e| CryptoError::KeyDerivation(format!("PIN key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+/// Associated data binding the item to the salt and expiry it travels with
+fn associated_data(salt: &str, expires_at: i64) -> Vec<u8> {
+    format!("{PAYLOAD_PREFIX}{salt}:{expires_at}").into_bytes()
+}
+
+/// Encrypts `item` under `pin` and renders the transfer code
+pub fn seal(item: &TransferredItem, pin: &str, now: DateTime<Utc>) -> AppResult<CredentialQr> {
+    check_pin(pin, &[&item.site, &item.username])?;
+    let mut salt = [0u8; SALT_BYTES];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let salt_text = URL_SAFE_NO_PAD.encode(salt);
+    let expires_at = now + Duration::minutes(TRANSFER_LIFETIME_MINUTES);
+    let plaintext = serde_json::to_vec(item).map_err(AppError::Serialization)?;
+    let envelope = Envelope {
+        item: CryptoService::encrypt_with_key(
+            &pin_key(pin, &salt)?,
+            &plaintext,
+            &associated_data(&salt_text, expires_at.timestamp()),
+        )?,
+        salt: salt_text,
+        expires_at: expires_at.timestamp(),
+    };
+    let payload = format!(
+        "{PAYLOAD_PREFIX}{}",
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(&envelope).map_err(AppError::Serialization)?)
+    );
+    let (svg, png) = wifi::render_qr(&payload).map_err(|_| {
+        AppError::InvalidInput(format!("{} holds too much to fit in a QR code", item.site))
+    })?;
+    Ok(CredentialQr {
+        site: item.site.clone(),
+        payload,
+        svg,
+        png,
+        expires_at,
+    })
+}
+
+/// Decrypts a scanned transfer code. A wrong PIN fails with `AppError::AuthFailed`.
+pub fn open(payload: &str, pin: &str, now: DateTime<Utc>) -> AppResult<TransferredItem> {
+    let damaged = || AppError::InvalidInput("This is not a valid transfer code".to_string());
+    let encoded = payload
+        .trim()
+        .strip_prefix(PAYLOAD_PREFIX)
+        .ok_or_else(damaged)?;
+    let envelope: Envelope = URL_SAFE_NO_PAD
+        .decode(encoded)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .ok_or_else(damaged)?;
+    let expires_at = Utc
+        .timestamp_opt(envelope.expires_at, 0)
+        .single()
+        .ok_or_else(damaged)?;
+    if now > expires_at {
+        return Err(AppError::InvalidInput(
+            "The transfer code has expired; create a new one".to_string(),
+        ));
+    }
+    let salt = URL_SAFE_NO_PAD
+        .decode(&envelope.salt)
+        .map_err(|_| damaged())?;
+    let plaintext = CryptoService::decrypt_with_key(
+        &pin_key(pin, &salt)?,
+        &envelope.item,
+        &associated_data(&envelope.salt, envelope.expires_at),
+    )
+    .map_err(|_| AppError::AuthFailed)?;
+    serde_json::from_slice(&plaintext).map_err(AppError::Serialization)
+}
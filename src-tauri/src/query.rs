@@ -0,0 +1,370 @@
+//! The search box's query syntax.
+//!
+//! A search term is free text mixed with `field:value` filters, for example
+//! `github tag:work strength:<50 breach:compromised updated:>2024-01-01`.
+//! Values with spaces go in double quotes: `site:"my bank"`. Only the fields
+//! below are filters; any other `word:` stays part of the text, so pasting a
+//! URL still searches for it. The free text keeps its old meaning: it must
+//! appear in the site, username or a tag, matched as `text_match` says.
+//!
+//! | Field      | Value                                              |
+//! |------------|----------------------------------------------------|
+//! | `tag`      | a tag, matched whole                               |
+//! | `site`     | text the site must contain                         |
+//! | `strength` | 0-100, optionally after `<`, `<=`, `>`, `>=`, `=`  |
+//! | `breach`   | `compromised`, `safe` or `unknown`                 |
+//! | `updated`  | `YYYY-MM-DD` (UTC), with the same comparisons      |
+
+use std::fmt;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use thiserror::Error;
+
+use crate::i18n::Message;
+use crate::models::BreachState;
+use crate::text_match::MatchMode;
+use crate::vault::CredentialFilter;
+
+const FIELDS: [&str; 5] = ["tag", "site", "strength", "breach", "updated"];
+
+/// What is wrong with a search term
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryProblem {
+    UnclosedQuote,
+    MissingValue {
+        field: String,
+    },
+    InvalidStrength {
+        value: String,
+    },
+    InvalidBreachState {
+        value: String,
+    },
+    InvalidDate {
+        value: String,
+    },
+    /// The same filter given twice with different values
+    Repeated {
+        field: String,
+    },
+}
+
+impl fmt::Display for QueryProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryProblem::UnclosedQuote => write!(f, "a quote is not closed"),
+            QueryProblem::MissingValue { field } => write!(f, "{}: needs a value", field),
+            QueryProblem::InvalidStrength { value } => write!(
+                f,
+                "strength needs a number from 0 to 100, optionally after <, <=, > or >=, not '{}'",
+                value
+            ),
+            QueryProblem::InvalidBreachState { value } => write!(
+                f,
+                "breach must be compromised, safe or unknown, not '{}'",
+                value
+            ),
+            QueryProblem::InvalidDate { value } => write!(
+                f,
+                "updated needs a date like 2024-01-31, optionally after <, <=, > or >=, not '{}'",
+                value
+            ),
+            QueryProblem::Repeated { field } => write!(f, "{}: is given more than once", field),
+        }
+    }
+}
+
+/// A search term that could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{problem} (column {column})")]
+pub struct QueryError {
+    /// 1-based character position of the offending part of the term
+    pub column: usize,
+    pub problem: QueryProblem,
+}
+
+impl QueryError {
+    fn new(start: usize, problem: QueryProblem) -> Self {
+        Self {
+            column: start + 1,
+            problem,
+        }
+    }
+
+    /// The translatable form, with the position as `{column}`
+    pub fn message(&self) -> Message {
+        let message = match &self.problem {
+            QueryProblem::UnclosedQuote => Message::new("query.unclosed_quote"),
+            QueryProblem::MissingValue { field } => {
+                Message::new("query.missing_value").with_param("field", field)
+            }
+            QueryProblem::InvalidStrength { value } => {
+                Message::new("query.invalid_strength").with_param("value", value)
+            }
+            QueryProblem::InvalidBreachState { value } => {
+                Message::new("query.invalid_breach").with_param("value", value)
+            }
+            QueryProblem::InvalidDate { value } => {
+                Message::new("query.invalid_date").with_param("value", value)
+            }
+            QueryProblem::Repeated { field } => {
+                Message::new("query.repeated").with_param("field", field)
+            }
+        };
+        message.with_param("column", self.column)
+    }
+}
+
+/// A parsed search term
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    /// The free text, words separated by single spaces
+    pub text: Option<String>,
+    pub tag: Option<String>,
+    pub site: Option<String>,
+    pub min_strength: Option<u8>,
+    pub max_strength: Option<u8>,
+    pub breach_state: Option<BreachState>,
+    /// Inclusive
+    pub updated_after: Option<DateTime<Utc>>,
+    /// Exclusive
+    pub updated_before: Option<DateTime<Utc>>,
+}
+
+/// A word of the term, with quotes removed
+struct Token {
+    /// Character offset in the term
+    start: usize,
+    text: String,
+    /// Byte offset in `text` of a `:` that came before any quote
+    colon: Option<usize>,
+}
+
+fn tokenize(term: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = term.chars().enumerate().peekable();
+    while let Some(&(start, _)) = chars.peek() {
+        let mut token = Token {
+            start,
+            text: String::new(),
+            colon: None,
+        };
+        let mut quote_start = None;
+        let mut quoted = false;
+        while let Some(&(i, c)) = chars.peek() {
+            if c.is_whitespace() && quote_start.is_none() {
+                break;
+            }
+            chars.next();
+            match c {
+                '"' if quote_start.is_some() => quote_start = None,
+                '"' => {
+                    quote_start = Some(i);
+                    quoted = true;
+                }
+                ':' if !quoted && token.colon.is_none() => {
+                    token.colon = Some(token.text.len());
+                    token.text.push(c);
+                }
+                _ => token.text.push(c),
+            }
+        }
+        if let Some(i) = quote_start {
+            return Err(QueryError::new(i, QueryProblem::UnclosedQuote));
+        }
+        if !token.text.is_empty() {
+            tokens.push(token);
+        }
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+    Ok(tokens)
+}
+
+/// Splits a leading `<`, `<=`, `>`, `>=` or `=` off `value`
+fn comparison(value: &str) -> (&str, &str) {
+    for op in ["<=", ">=", "<", ">", "="] {
+        if let Some(rest) = value.strip_prefix(op) {
+            return (op, rest);
+        }
+    }
+    ("=", value)
+}
+
+/// Sets `slot` unless it already holds a different value
+fn set<T: PartialEq>(
+    slot: &mut Option<T>,
+    value: T,
+    field: &str,
+    start: usize,
+) -> Result<(), QueryError> {
+    match slot {
+        Some(existing) if *existing != value => Err(QueryError::new(
+            start,
+            QueryProblem::Repeated {
+                field: field.to_string(),
+            },
+        )),
+        _ => {
+            *slot = Some(value);
+            Ok(())
+        }
+    }
+}
+
+impl SearchQuery {
+    pub fn parse(term: &str) -> Result<Self, QueryError> {
+        let mut query = SearchQuery::default();
+        let mut words = Vec::new();
+        for token in tokenize(term)? {
+            let field = token
+                .colon
+                .map(|colon| token.text[..colon].to_ascii_lowercase())
+                .filter(|name| FIELDS.contains(&name.as_str()));
+            match field {
+                Some(field) => {
+                    let value = &token.text[field.len() + 1..];
+                    query.add_filter(&field, value, token.start)?;
+                }
+                None => words.push(token.text),
+            }
+        }
+        if !words.is_empty() {
+            query.text = Some(words.join(" "));
+        }
+        Ok(query)
+    }
+
+    fn add_filter(&mut self, field: &str, value: &str, start: usize) -> Result<(), QueryError> {
+        if value.is_empty() {
+            return Err(QueryError::new(
+                start,
+                QueryProblem::MissingValue {
+                    field: field.to_string(),
+                },
+            ));
+        }
+        match field {
+            "tag" => set(&mut self.tag, value.to_string(), field, start),
+            "site" => set(&mut self.site, value.to_string(), field, start),
+            "breach" => {
+                let state = match value.to_ascii_lowercase().as_str() {
+                    "compromised" => BreachState::Compromised,
+                    "safe" => BreachState::Safe,
+                    "unknown" => BreachState::Unknown,
+                    _ => {
+                        return Err(QueryError::new(
+                            start,
+                            QueryProblem::InvalidBreachState {
+                                value: value.to_string(),
+                            },
+                        ))
+                    }
+                };
+                set(&mut self.breach_state, state, field, start)
+            }
+            "strength" => {
+                let invalid = || {
+                    QueryError::new(
+                        start,
+                        QueryProblem::InvalidStrength {
+                            value: value.to_string(),
+                        },
+                    )
+                };
+                let (op, number) = comparison(value);
+                let n: u8 = number
+                    .parse()
+                    .ok()
+                    .filter(|n| *n <= 100)
+                    .ok_or_else(invalid)?;
+                let (min, max) = match op {
+                    "<" => (None, Some(n.checked_sub(1).ok_or_else(invalid)?)),
+                    "<=" => (None, Some(n)),
+                    ">" => (
+                        Some(Some(n + 1).filter(|n| *n <= 100).ok_or_else(invalid)?),
+                        None,
+                    ),
+                    ">=" => (Some(n), None),
+                    _ => (Some(n), Some(n)),
+                };
+                if let Some(min) = min {
+                    set(&mut self.min_strength, min, field, start)?;
+                }
+                if let Some(max) = max {
+                    set(&mut self.max_strength, max, field, start)?;
+                }
+                Ok(())
+            }
+            _ => {
+                let (op, date) = comparison(value);
+                let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .ok()
+                    .and_then(|day| day.and_hms_opt(0, 0, 0))
+                    .map(|midnight| midnight.and_utc())
+                    .ok_or_else(|| {
+                        QueryError::new(
+                            start,
+                            QueryProblem::InvalidDate {
+                                value: value.to_string(),
+                            },
+                        )
+                    })?;
+                let next_day = day + Duration::days(1);
+                let (after, before) = match op {
+                    "<" => (None, Some(day)),
+                    "<=" => (None, Some(next_day)),
+                    ">" => (Some(next_day), None),
+                    ">=" => (Some(day), None),
+                    _ => (Some(day), Some(next_day)),
+                };
+                if let Some(after) = after {
+                    set(&mut self.updated_after, after, field, start)?;
+                }
+                if let Some(before) = before {
+                    set(&mut self.updated_before, before, field, start)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// `filter` with its search term parsed into the filters it names. Filters
+/// set both by the term and by `filter` itself must agree. A regular
+/// expression is left whole, since its syntax overlaps with the query's.
+pub fn apply(filter: CredentialFilter) -> Result<CredentialFilter, QueryError> {
+    let Some(term) = &filter.search_term else {
+        return Ok(filter);
+    };
+    if filter.match_mode == MatchMode::Regex {
+        return Ok(filter);
+    }
+    let query = SearchQuery::parse(term)?;
+    // Conflicts with the filter's own fields are reported at the start of the term
+    let mut merged = CredentialFilter {
+        search_term: query.text,
+        ..filter
+    };
+    if let Some(tag) = query.tag {
+        set(&mut merged.tag, tag, "tag", 0)?;
+    }
+    if let Some(site) = query.site {
+        set(&mut merged.site, site, "site", 0)?;
+    }
+    if let Some(min) = query.min_strength {
+        set(&mut merged.min_strength, min, "strength", 0)?;
+    }
+    if let Some(max) = query.max_strength {
+        set(&mut merged.max_strength, max, "strength", 0)?;
+    }
+    if let Some(state) = query.breach_state {
+        set(&mut merged.breach_state, state, "breach", 0)?;
+    }
+    if let Some(after) = query.updated_after {
+        set(&mut merged.updated_after, after, "updated", 0)?;
+    }
+    if let Some(before) = query.updated_before {
+        set(&mut merged.updated_before, before, "updated", 0)?;
+    }
+    Ok(merged)
+}
@@ -0,0 +1,107 @@
+//! Two-factor recovery codes.
+//!
+//! Sites hand out a list of single-use codes when two-factor authentication is
+//! turned on. They are kept in the item's secret as a list, each code with the
+//! time it was used, instead of as text pasted into the notes.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+/// Sites give out 5 to 16; anything beyond this is not a list of codes
+pub const MAX_RECOVERY_CODES: usize = 50;
+const MAX_CODE_BYTES: usize = 64;
+
+/// One recovery code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveryCode {
+    pub code: String,
+    /// When it was used to sign in; None while it is still good
+    #[serde(default)]
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+impl RecoveryCode {
+    pub fn is_used(&self) -> bool {
+        self.used_at.is_some()
+    }
+}
+
+/// Strips list bullets and numbering such as `-`, `1.`, `2)` or `#3` from a line
+fn strip_numbering(line: &str) -> &str {
+    let line = line.trim().trim_start_matches(['-', '*', '•']).trim_start();
+    match line.split_once(char::is_whitespace) {
+        Some((marker, rest)) if is_list_number(marker) => rest.trim(),
+        _ => line,
+    }
+}
+
+fn is_list_number(marker: &str) -> bool {
+    let number = marker
+        .strip_prefix('#')
+        .or_else(|| marker.strip_suffix(['.', ')']))
+        .unwrap_or("");
+    !number.is_empty() && number.chars().all(|c| c.is_ascii_digit())
+}
+
+/// The codes in text as sites show them: one or more per line, separated by
+/// whitespace or commas, possibly numbered. Duplicates are dropped.
+pub fn parse_codes(text: &str) -> AppResult<Vec<String>> {
+    let mut codes: Vec<String> = Vec::new();
+    for line in text.lines() {
+        for code in strip_numbering(line).split(|c: char| c == ',' || c.is_whitespace()) {
+            if code.is_empty() || codes.iter().any(|existing| existing == code) {
+                continue;
+            }
+            if code.len() > MAX_CODE_BYTES {
+                return Err(AppError::InvalidInput(format!(
+                    "Recovery codes must be at most {} bytes",
+                    MAX_CODE_BYTES
+                )));
+            }
+            codes.push(code.to_string());
+        }
+    }
+    if codes.len() > MAX_RECOVERY_CODES {
+        return Err(AppError::InvalidInput(format!(
+            "At most {} recovery codes can be stored",
+            MAX_RECOVERY_CODES
+        )));
+    }
+    Ok(codes)
+}
+
+/// `codes` as stored; codes that were already stored keep when they were used
+pub fn replace(existing: &[RecoveryCode], codes: Vec<String>) -> Vec<RecoveryCode> {
+    codes
+        .into_iter()
+        .map(|code| RecoveryCode {
+            used_at: existing
+                .iter()
+                .find(|existing| existing.code == code)
+                .and_then(|existing| existing.used_at),
+            code,
+        })
+        .collect()
+}
+
+/// Marks the code at `index` used; returns how many are left
+pub fn mark_used(codes: &mut [RecoveryCode], index: usize, now: DateTime<Utc>) -> AppResult<usize> {
+    let code = codes.get_mut(index).ok_or_else(|| {
+        AppError::InvalidInput(format!("There is no recovery code {}", index + 1))
+    })?;
+    if code.is_used() {
+        return Err(AppError::InvalidInput(format!(
+            "Recovery code {} was already used",
+            index + 1
+        )));
+    }
+    code.used_at = Some(now);
+    Ok(remaining(codes))
+}
+
+/// How many codes have not been used
+pub fn remaining(codes: &[RecoveryCode]) -> usize {
+    codes.iter().filter(|code| !code.is_used()).count()
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain_info;
+use crate::error::AppResult;
+use crate::generator::{self, GeneratorOptions};
+use crate::hibp::BreachCheckResult;
+use crate::models::{BreachState, Credential, CredentialSummary, Secret};
+
+/// Strength scores below this are considered weak (0-100 scale, zxcvbn score 3)
+pub const WEAK_STRENGTH_THRESHOLD: u8 = 60;
+
+/// Passwords older than this are due for rotation unless the caller says otherwise
+pub const DEFAULT_MAX_PASSWORD_AGE_DAYS: u32 = 365;
+
+/// Why a credential needs attention, most severe first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskReason {
+    /// Found in the breach database
+    Compromised,
+    /// Same password as at least one other credential
+    Reused,
+    /// Strength below `WEAK_STRENGTH_THRESHOLD`
+    Weak,
+    /// Password not changed within the maximum age
+    Old,
+}
+
+/// A credential in the remediation queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemediationItem {
+    pub credential: CredentialSummary,
+    /// All reasons that apply, most severe first
+    pub reasons: Vec<RiskReason>,
+    /// Number of other credentials sharing this password
+    pub reused_with: usize,
+    /// A freshly generated replacement the user can accept. It follows the
+    /// item's password rules, or else those known for its site.
+    pub suggested_password: String,
+    /// Where to change the password, if the site is a domain
+    #[serde(default)]
+    pub change_password_url: Option<String>,
+}
+
+/// Result of applying a remediation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemediationOutcome {
+    pub credential: Credential,
+    /// Breach status of the new password
+    pub breach_check: BreachCheckResult,
+}
+
+/// Orders credentials by risk and attaches a suggested replacement to each.
+/// Credentials with no risk are left out.
+pub fn build_queue(
+    entries: Vec<(Credential, Secret)>,
+    max_age_days: u32,
+    now: DateTime<Utc>,
+) -> AppResult<Vec<RemediationItem>> {
+    let mut uses: HashMap<String, usize> = HashMap::new();
+    for (_, secret) in &entries {
+        *uses.entry(secret.password.clone()).or_default() += 1;
+    }
+    let cutoff = now - chrono::Duration::days(i64::from(max_age_days));
+
+    let mut queue = Vec::new();
+    for (credential, secret) in entries {
+        let reused_with = uses[&secret.password] - 1;
+        let mut reasons = Vec::new();
+        if credential.breach_state == BreachState::Compromised {
+            reasons.push(RiskReason::Compromised);
+        }
+        if reused_with > 0 {
+            reasons.push(RiskReason::Reused);
+        }
+        if credential.strength < WEAK_STRENGTH_THRESHOLD {
+            reasons.push(RiskReason::Weak);
+        }
+        if credential.password_changed_at <= cutoff {
+            reasons.push(RiskReason::Old);
+        }
+        if reasons.is_empty() {
+            continue;
+        }
+        let domain_info = domain_info::lookup(&credential.site).ok();
+        let constraints = credential
+            .generator_constraints
+            .clone()
+            .or_else(|| domain_info.as_ref()?.constraints.clone())
+            .unwrap_or_default();
+        queue.push(RemediationItem {
+            suggested_password: generator::generate_constrained(
+                &GeneratorOptions::default(),
+                &constraints,
+            )?,
+            change_password_url: domain_info.map(|info| info.change_password_url),
+            credential: credential.into(),
+            reasons,
+            reused_with,
+        });
+    }
+
+    // Most severe reason first, then more reasons, then the oldest password
+    queue.sort_by(|a, b| {
+        a.reasons[0]
+            .cmp(&b.reasons[0])
+            .then(b.reasons.len().cmp(&a.reasons.len()))
+            .then(
+                a.credential
+                    .password_changed_at
+                    .cmp(&b.credential.password_changed_at),
+            )
+    });
+    Ok(queue)
+}
@@ -0,0 +1,461 @@
+//! Credential storage on a self-hosted vault server.
+//!
+//! Every credential, metadata included, is encrypted with a key derived from
+//! the vault key before it leaves the device, through a `SubkeyCipher` so the
+//! key stays with the vault key, in an ephemeral session in its worker; the server only sees item ids
+//! and ciphertext, so it cannot filter or sort and `list_credentials` does
+//! both locally. Settings, keys and the audit log stay in the local database.
+//!
+//! Server API (JSON, `Authorization: Bearer <token>`):
+//!
+//! ```text
+//! GET    /v1/items        -> [RemoteItem]
+//! POST   /v1/items        <- RemoteItem        409 if the id exists
+//! GET    /v1/items/{id}   -> RemoteItem        404 if missing
+//! PUT    /v1/items/{id}   <- RemoteItem        404 if missing
+//! DELETE /v1/items/{id}                        404 if missing
+//! ```
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::audit_level::AuditFilter;
+use crate::crypto::SubkeyCipher;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::models::{BreachState, Credential, CredentialSummary, NetFeature, NetworkSettings};
+use crate::net::{self, classify_service_error, validate_service_url, Network};
+use crate::strength::StrengthUpdate;
+use crate::traits::{
+    AsyncCredentialRepository, AuditLogger, CredentialRepository, EncryptedStorage, RepoFuture,
+};
+use crate::vault::CredentialFilter;
+
+/// Context for deriving the item encryption key from the vault key
+pub const STORAGE_KEY_CONTEXT: &[u8] = b"secret-plan remote storage v1";
+
+const SERVICE: &str = "Vault server";
+
+/// A credential as stored on the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteItem {
+    pub id: String,
+    /// The whole `Credential` as JSON, encrypted with the storage key and bound to `id`
+    pub ciphertext: String,
+}
+
+/// What the repository needs while the vault is unlocked
+#[derive(Clone)]
+struct Session {
+    cipher: SubkeyCipher,
+    network: Network,
+}
+
+/// `CredentialRepository` backed by a vault server
+pub struct RemoteRepository {
+    base_url: String,
+    token: String,
+    audit_logger: Arc<dyn AuditLogger>,
+    audit_filter: AuditFilter,
+    session: Mutex<Option<Session>>,
+    // Time of the last successful request
+    last_synced_at: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl RemoteRepository {
+    /// `audit_logger` receives the same entries `SqliteRepository` writes for credential changes
+    pub fn new(base_url: &str, token: &str, audit_logger: Arc<dyn AuditLogger>) -> AppResult<Self> {
+        if token.trim().is_empty() {
+            return Err(AppError::InvalidInput(
+                "Vault server access token must not be empty".to_string(),
+            ));
+        }
+        Ok(Self {
+            base_url: validate_service_url(base_url, SERVICE)?,
+            token: token.trim().to_string(),
+            audit_logger,
+            audit_filter: AuditFilter::default(),
+            session: Mutex::new(None),
+            last_synced_at: Mutex::new(None),
+        })
+    }
+
+    /// Shares the audit level of the vault, see `VaultManager::with_audit_filter`
+    pub fn with_audit_filter(mut self, audit_filter: AuditFilter) -> Self {
+        self.audit_filter = audit_filter;
+        self
+    }
+
+    fn session(&self) -> AppResult<Session> {
+        self.session
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or(AppError::VaultLocked)
+    }
+
+    fn client(&self, session: &Session) -> AppResult<Client> {
+        net::build_client(
+            session
+                .network
+                .feature_client_builder(NetFeature::Sync, SERVICE)?,
+        )
+    }
+
+    fn items_url(&self) -> String {
+        format!("{}/v1/items", self.base_url)
+    }
+
+    fn item_url(&self, id: &str) -> String {
+        format!("{}/v1/items/{}", self.base_url, id)
+    }
+
+    /// Sends a request; 404 becomes `AppError::NotFound(id)`
+    async fn send(&self, request: RequestBuilder, id: &str) -> AppResult<Response> {
+        let response = request.bearer_auth(&self.token).send().await.map_err(|e| {
+            let error = classify_service_error(e, SERVICE);
+            warn!(item = id, error = %error, "sync request failed");
+            error
+        })?;
+        debug!(item = id, status = %response.status(), "sync request finished");
+        match response.status() {
+            status if status.is_success() => {
+                *self.last_synced_at.lock().unwrap() = Some(Utc::now());
+                Ok(response)
+            }
+            StatusCode::NOT_FOUND => Err(AppError::NotFound(id.to_string())),
+            StatusCode::CONFLICT => Err(AppError::InvalidInput(format!(
+                "{} already has an item {}",
+                SERVICE, id
+            ))),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Err(AppError::Other(format!(
+                "{} rejected the access token",
+                SERVICE
+            ))),
+            status => Err(AppError::Other(format!(
+                "{} returned error: {}",
+                SERVICE, status
+            ))),
+        }
+    }
+
+    fn seal(&self, session: &Session, credential: &Credential) -> AppResult<RemoteItem> {
+        Ok(RemoteItem {
+            id: credential.uuid.clone(),
+            ciphertext: session
+                .cipher
+                .encrypt(&serde_json::to_vec(credential)?, credential.uuid.as_bytes())?,
+        })
+    }
+
+    fn open(&self, session: &Session, item: &RemoteItem) -> AppResult<Credential> {
+        let plaintext = session
+            .cipher
+            .decrypt(&item.ciphertext, item.id.as_bytes())?;
+        let credential: Credential = serde_json::from_slice(&plaintext)?;
+        if credential.uuid != item.id {
+            return Err(AppError::Other(format!(
+                "{} returned item {} under id {}",
+                SERVICE, credential.uuid, item.id
+            )));
+        }
+        Ok(credential)
+    }
+
+    async fn fetch(&self, session: &Session, uuid: &str) -> AppResult<Credential> {
+        let item: RemoteItem = self
+            .send(self.client(session)?.get(self.item_url(uuid)), uuid)
+            .await?
+            .json()
+            .await
+            .map_err(|e| classify_service_error(e, SERVICE))?;
+        self.open(session, &item)
+    }
+
+    async fn store(&self, session: &Session, credential: &Credential) -> AppResult<()> {
+        let item = self.seal(session, credential)?;
+        self.send(
+            self.client(session)?
+                .put(self.item_url(&credential.uuid))
+                .json(&item),
+            &credential.uuid,
+        )
+        .await?;
+        Ok(())
+    }
+
+    fn log(&self, key: &str, site: &str, uuid: &str) -> AppResult<()> {
+        if !self.audit_filter.records(key) {
+            return Ok(());
+        }
+        self.audit_logger.add_log(
+            &Message::new(key).with_param("site", site).audit_text(),
+            Some(uuid),
+        )?;
+        Ok(())
+    }
+
+    async fn add(&self, credential: Credential, strength: u8) -> AppResult<()> {
+        let session = self.session()?;
+        let mut credential = credential;
+        credential.strength = strength;
+        let item = self.seal(&session, &credential)?;
+        self.send(
+            self.client(&session)?.post(self.items_url()).json(&item),
+            &credential.uuid,
+        )
+        .await?;
+        self.log("audit.credential_added", &credential.site, &credential.uuid)
+    }
+
+    async fn update(&self, credential: Credential) -> AppResult<()> {
+        let session = self.session()?;
+        // The server cannot compare revisions inside ciphertext, so check here.
+        // This narrows the window for lost updates but does not close it.
+        let current = self.fetch(&session, &credential.uuid).await?;
+        if current.revision != credential.revision {
+            return Err(AppError::Conflict(format!(
+                "{} was changed since revision {}",
+                credential.uuid, credential.revision
+            )));
+        }
+        let mut credential = credential;
+        credential.updated_at = Utc::now();
+        credential.revision += 1;
+        self.store(&session, &credential).await?;
+        self.log(
+            "audit.credential_updated",
+            &credential.site,
+            &credential.uuid,
+        )
+    }
+
+    async fn delete(&self, uuid: String) -> AppResult<String> {
+        let session = self.session()?;
+        let site = self.fetch(&session, &uuid).await?.site;
+        self.send(self.client(&session)?.delete(self.item_url(&uuid)), &uuid)
+            .await?;
+        self.log("audit.credential_deleted", &site, &uuid)?;
+        Ok(site)
+    }
+
+    async fn get(&self, uuid: String) -> AppResult<Credential> {
+        let session = self.session()?;
+        self.fetch(&session, &uuid).await
+    }
+
+    async fn list(&self, filter: Option<CredentialFilter>) -> AppResult<Vec<Credential>> {
+        let session = self.session()?;
+        let items: Vec<RemoteItem> = self
+            .send(self.client(&session)?.get(self.items_url()), "")
+            .await?
+            .json()
+            .await
+            .map_err(|e| classify_service_error(e, SERVICE))?;
+        let now = Utc::now();
+        let term = filter
+            .as_ref()
+            .map(CredentialFilter::term_matcher)
+            .transpose()?
+            .flatten();
+        let mut credentials = items
+            .iter()
+            .map(|item| self.open(&session, item))
+            .filter(|credential| match (credential, &filter) {
+                (Ok(credential), Some(filter)) => filter.matches_with(
+                    term.as_ref(),
+                    &CredentialSummary::from(credential.clone()),
+                    now,
+                ),
+                _ => true,
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+        // Same order as the local database
+        credentials.sort_by(|a, b| (&a.site, &a.username).cmp(&(&b.site, &b.username)));
+        Ok(credentials)
+    }
+
+    async fn set_breach_state(&self, uuid: String, state: BreachState) -> AppResult<()> {
+        let session = self.session()?;
+        let mut credential = self.fetch(&session, &uuid).await?;
+        credential.breach_state = state;
+        // Resetting to Unknown also clears the check time
+        credential.breach_checked_at = match state {
+            BreachState::Unknown => None,
+            _ => Some(Utc::now()),
+        };
+        credential.revision += 1;
+        self.store(&session, &credential).await?;
+        let action = match state {
+            BreachState::Safe => "audit.marked_safe",
+            BreachState::Compromised => "audit.marked_compromised",
+            BreachState::Unknown => "audit.breach_reset",
+        };
+        if self.audit_filter.records(action) {
+            self.audit_logger
+                .add_log(&Message::new(action).audit_text(), Some(&uuid))?;
+        }
+        Ok(())
+    }
+
+    /// Rewrites an item with a field that does not count as an edit
+    async fn set_untracked(
+        &self,
+        uuid: String,
+        change: impl FnOnce(&mut Credential),
+    ) -> AppResult<()> {
+        let session = self.session()?;
+        let mut credential = self.fetch(&session, &uuid).await?;
+        change(&mut credential);
+        self.store(&session, &credential).await
+    }
+
+    async fn set_strengths(&self, updates: Vec<StrengthUpdate>) -> AppResult<usize> {
+        let session = self.session()?;
+        let mut written = 0;
+        for update in updates {
+            let mut credential = match self.fetch(&session, &update.uuid).await {
+                Ok(credential) => credential,
+                Err(AppError::NotFound(_)) => continue,
+                Err(e) => return Err(e),
+            };
+            if credential.revision != update.revision {
+                continue;
+            }
+            credential.strength = update.strength;
+            credential.revision += 1;
+            self.store(&session, &credential).await?;
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    async fn exists(&self, uuid: String) -> AppResult<bool> {
+        match self.get(uuid).await {
+            Ok(_) => Ok(true),
+            Err(AppError::NotFound(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Waits for `future` from synchronous code. It runs on its own thread and
+/// runtime, so this works inside any runtime flavor without stalling one of
+/// its workers; hot commands avoid it by using the async impl instead.
+fn block_on<F>(future: F) -> AppResult<F::Output>
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                Ok(tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()?
+                    .block_on(future))
+            })
+            .join()
+            .map_err(|_| AppError::Other("vault server request panicked".to_string()))?
+    })
+}
+
+impl EncryptedStorage for RemoteRepository {
+    fn open_storage(&self, cipher: SubkeyCipher, network: &NetworkSettings) -> AppResult<()> {
+        let network = Network::new(network)?;
+        *self.session.lock().unwrap() = Some(Session { cipher, network });
+        Ok(())
+    }
+
+    fn close_storage(&self) {
+        *self.session.lock().unwrap() = None;
+    }
+
+    fn last_synced_at(&self) -> Option<DateTime<Utc>> {
+        *self.last_synced_at.lock().unwrap()
+    }
+}
+
+impl AsyncCredentialRepository for RemoteRepository {
+    fn add_credential(&self, credential: Credential, strength: u8) -> RepoFuture<'_, ()> {
+        Box::pin(self.add(credential, strength))
+    }
+
+    fn update_credential(&self, credential: Credential) -> RepoFuture<'_, ()> {
+        Box::pin(self.update(credential))
+    }
+
+    fn delete_credential(&self, uuid: String) -> RepoFuture<'_, String> {
+        Box::pin(self.delete(uuid))
+    }
+
+    fn get_credential(&self, uuid: String) -> RepoFuture<'_, Credential> {
+        Box::pin(self.get(uuid))
+    }
+
+    fn list_credentials(
+        &self,
+        filter: Option<CredentialFilter>,
+    ) -> RepoFuture<'_, Vec<Credential>> {
+        Box::pin(self.list(filter))
+    }
+
+    fn update_breach_state(&self, uuid: String, state: BreachState) -> RepoFuture<'_, ()> {
+        Box::pin(self.set_breach_state(uuid, state))
+    }
+
+    fn credential_exists(&self, uuid: String) -> RepoFuture<'_, bool> {
+        Box::pin(self.exists(uuid))
+    }
+}
+
+impl CredentialRepository for RemoteRepository {
+    fn add_credential(&self, credential: &Credential, strength: u8) -> AppResult<()> {
+        block_on(self.add(credential.clone(), strength))?
+    }
+
+    fn update_credential(&self, credential: &Credential) -> AppResult<()> {
+        block_on(self.update(credential.clone()))?
+    }
+
+    fn delete_credential(&self, uuid: &str) -> AppResult<String> {
+        block_on(self.delete(uuid.to_string()))?
+    }
+
+    fn get_credential(&self, uuid: &str) -> AppResult<Credential> {
+        block_on(self.get(uuid.to_string()))?
+    }
+
+    fn list_credentials(&self, filter: Option<CredentialFilter>) -> AppResult<Vec<Credential>> {
+        block_on(self.list(filter))?
+    }
+
+    fn update_breach_state(&self, uuid: &str, state: BreachState) -> AppResult<()> {
+        block_on(self.set_breach_state(uuid.to_string(), state))?
+    }
+
+    fn mark_used(&self, uuid: &str, at: DateTime<Utc>) -> AppResult<()> {
+        block_on(self.set_untracked(uuid.to_string(), |credential| {
+            credential.last_used_at = Some(at)
+        }))?
+    }
+
+    fn set_has_totp(&self, uuid: &str, has_totp: bool) -> AppResult<()> {
+        block_on(self.set_untracked(uuid.to_string(), |credential| {
+            credential.has_totp = has_totp
+        }))?
+    }
+
+    fn update_strengths(&self, updates: &[StrengthUpdate]) -> AppResult<usize> {
+        block_on(self.set_strengths(updates.to_vec()))?
+    }
+
+    fn credential_exists(&self, uuid: &str) -> AppResult<bool> {
+        block_on(self.exists(uuid.to_string()))?
+    }
+}
@@ -0,0 +1,149 @@
+//! Bulk password rotation.
+//!
+//! A rotation session walks a list of items one at a time. For each, the user is
+//! shown the current password and a newly generated one, changes the password on
+//! the website, then either confirms (the new password is saved) or skips (the
+//! account is recorded as still needing a manual change). Sessions live in memory
+//! only, hold generated passwords in plaintext, and end when the vault locks.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::generator::GeneratorOptions;
+use crate::models::Credential;
+
+/// An account the session has dealt with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationEntry {
+    pub uuid: String,
+    pub site: String,
+    pub username: String,
+}
+
+impl From<&Credential> for RotationEntry {
+    fn from(credential: &Credential) -> Self {
+        Self {
+            uuid: credential.uuid.clone(),
+            site: credential.site.clone(),
+            username: credential.username.clone(),
+        }
+    }
+}
+
+/// The item currently being rotated, as shown to the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotationItem {
+    pub uuid: String,
+    pub site: String,
+    pub username: String,
+    pub current_password: String,
+    pub new_password: String,
+    /// 1-based position in the session
+    pub position: usize,
+    pub total: usize,
+}
+
+/// Progress of a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotationStatus {
+    pub session_id: String,
+    pub total: usize,
+    /// Items not yet shown, plus the current one if any
+    pub remaining: usize,
+    pub rotated: Vec<RotationEntry>,
+    /// Skipped accounts, whose password still has to be changed on the website
+    pub needs_manual_rotation: Vec<RotationEntry>,
+    pub finished: bool,
+}
+
+/// State of one rotation run
+pub struct RotationSession {
+    id: String,
+    options: GeneratorOptions,
+    queue: VecDeque<String>,
+    total: usize,
+    /// Shown to the user and waiting for `confirm_rotated` or `skip_rotation_item`
+    current: Option<RotationItem>,
+    rotated: Vec<RotationEntry>,
+    needs_manual_rotation: Vec<RotationEntry>,
+}
+
+impl RotationSession {
+    pub fn new(uuids: Vec<String>, options: GeneratorOptions) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            options,
+            total: uuids.len(),
+            queue: uuids.into(),
+            current: None,
+            rotated: Vec::new(),
+            needs_manual_rotation: Vec::new(),
+        }
+    }
+
+    /// Generator options the new passwords start from
+    pub fn options(&self) -> &GeneratorOptions {
+        &self.options
+    }
+
+    /// The item waiting for an answer, if any
+    pub fn current(&self) -> Option<&RotationItem> {
+        self.current.as_ref()
+    }
+
+    /// Takes the next item's uuid off the queue
+    pub fn next_uuid(&mut self) -> Option<String> {
+        self.queue.pop_front()
+    }
+
+    /// 1-based position of the item last taken by `next_uuid`
+    pub fn position(&self) -> usize {
+        self.total - self.queue.len()
+    }
+
+    /// Counts an item that disappeared before its turn as done
+    pub fn drop_missing(&mut self) {
+        self.total -= 1;
+    }
+
+    pub fn set_current(&mut self, item: RotationItem) {
+        self.current = Some(item);
+    }
+
+    /// Ends the current item, which must be `uuid`, returning it
+    pub fn take_current(&mut self, uuid: &str) -> AppResult<RotationItem> {
+        match &self.current {
+            Some(item) if item.uuid == uuid => Ok(self.current.take().unwrap()),
+            Some(item) => Err(AppError::Conflict(format!(
+                "{} is being rotated, not {}",
+                item.uuid, uuid
+            ))),
+            None => Err(AppError::InvalidInput(
+                "No item is waiting to be rotated".to_string(),
+            )),
+        }
+    }
+
+    pub fn record_rotated(&mut self, entry: RotationEntry) {
+        self.rotated.push(entry);
+    }
+
+    pub fn record_skipped(&mut self, entry: RotationEntry) {
+        self.needs_manual_rotation.push(entry);
+    }
+
+    pub fn status(&self) -> RotationStatus {
+        let remaining = self.queue.len() + usize::from(self.current.is_some());
+        RotationStatus {
+            session_id: self.id.clone(),
+            total: self.total,
+            remaining,
+            rotated: self.rotated.clone(),
+            needs_manual_rotation: self.needs_manual_rotation.clone(),
+            finished: remaining == 0,
+        }
+    }
+}
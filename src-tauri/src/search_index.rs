@@ -0,0 +1,275 @@
+//! In-memory index for quick search.
+//!
+//! The quick search overlay queries on every keystroke, so it cannot afford a
+//! database scan per query. The index keeps the words of every item's site,
+//! username and tags in one sorted list; a query word is found by binary search
+//! and matches every indexed word it is a prefix of. The index is built on the
+//! first search and dropped whenever a credential is written through the
+//! `IndexedRepository` wrapper, to be rebuilt on the next search. The wrapper
+//! also counts those writes, which tells the app when vault statistics may
+//! have changed.
+//!
+//! Alongside the words the index counts how many items carry each tag, in its
+//! normalized form, for suggesting tags while the user types one.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppResult;
+use crate::models::{BreachState, Credential, CredentialSummary};
+use crate::strength::StrengthUpdate;
+use crate::traits::CredentialRepository;
+use crate::validation;
+use crate::vault::CredentialFilter;
+
+/// Words that appear in too many sites to be worth matching
+const IGNORED_WORDS: &[&str] = &["http", "https", "www"];
+
+/// Which field an indexed word came from; the value is its weight in scores
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Tag = 1,
+    Username = 2,
+    Site = 3,
+}
+
+/// An item found by `SearchIndex::search`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickSearchHit {
+    pub credential: CredentialSummary,
+    /// Higher is better. Each query word adds up to 40: 10 to 30 for the field it
+    /// matched (tag, username, site) plus up to 10 for how much of the word it covers.
+    pub score: u32,
+}
+
+/// A tag found by `SearchIndex::suggest_tags`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagSuggestion {
+    pub tag: String,
+    /// Items carrying it
+    pub count: u32,
+}
+
+/// Prefix index over the searchable fields of every item
+pub struct SearchIndex {
+    /// (word, item, field), sorted by word
+    words: Vec<(String, u32, Field)>,
+    items: Vec<CredentialSummary>,
+    /// Normalized tags with the number of items carrying them, sorted by tag
+    tags: Vec<TagSuggestion>,
+}
+
+/// Splits `text` into lowercase words at anything that is not a letter or digit
+fn words_of(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+impl SearchIndex {
+    pub fn build(credentials: Vec<Credential>) -> Self {
+        let mut words = Vec::new();
+        let mut items = Vec::with_capacity(credentials.len());
+        let mut tag_counts: HashMap<String, u32> = HashMap::new();
+        for (item, credential) in credentials.into_iter().enumerate() {
+            let item = item as u32;
+            let mut add = |text: &str, field: Field| {
+                for word in words_of(text) {
+                    if !IGNORED_WORDS.contains(&word.as_str()) {
+                        words.push((word, item, field));
+                    }
+                }
+            };
+            add(&credential.site, Field::Site);
+            add(&credential.username, Field::Username);
+            for tag in &credential.tags {
+                add(tag, Field::Tag);
+            }
+            // Items stored before tags were normalized may spell one tag twice
+            for tag in validation::normalize_tags(&credential.tags) {
+                *tag_counts.entry(tag).or_default() += 1;
+            }
+            items.push(CredentialSummary::from(credential));
+        }
+        words.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        let mut tags: Vec<TagSuggestion> = tag_counts
+            .into_iter()
+            .map(|(tag, count)| TagSuggestion { tag, count })
+            .collect();
+        tags.sort_unstable_by(|a, b| a.tag.cmp(&b.tag));
+        Self { words, items, tags }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Every indexed item, ordered by site and username like the repository lists them
+    pub fn items(&self) -> &[CredentialSummary] {
+        &self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Tags starting with `prefix` once normalized, the most used first. An
+    /// empty prefix suggests from every tag.
+    pub fn suggest_tags(&self, prefix: &str, limit: usize) -> Vec<TagSuggestion> {
+        let prefix = validation::normalize_tag(prefix);
+        let start = self
+            .tags
+            .partition_point(|suggestion| suggestion.tag.as_str() < prefix.as_str());
+        let mut suggestions: Vec<TagSuggestion> = self.tags[start..]
+            .iter()
+            .take_while(|suggestion| suggestion.tag.starts_with(&prefix))
+            .cloned()
+            .collect();
+        suggestions.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+        suggestions.truncate(limit);
+        suggestions
+    }
+
+    /// Items in which every word of `term` starts some word of the site,
+    /// username or tags, best first. An empty term finds nothing.
+    pub fn search(&self, term: &str, limit: usize) -> Vec<QuickSearchHit> {
+        let mut scores: Option<HashMap<u32, u32>> = None;
+        for query in words_of(term) {
+            let mut word_scores: HashMap<u32, u32> = HashMap::new();
+            let start = self
+                .words
+                .partition_point(|(word, _, _)| word.as_str() < query.as_str());
+            for (word, item, field) in self.words[start..]
+                .iter()
+                .take_while(|(word, _, _)| word.starts_with(&query))
+            {
+                // Coverage in tenths, so "pay" scores lower than "paypal" on "paypal"
+                let coverage = (query.chars().count() * 10 / word.chars().count()) as u32;
+                let score = *field as u32 * 10 + coverage;
+                let best = word_scores.entry(*item).or_default();
+                *best = (*best).max(score);
+            }
+            scores = Some(match scores {
+                // Every query word has to match
+                Some(previous) => previous
+                    .into_iter()
+                    .filter_map(|(item, score)| {
+                        word_scores
+                            .get(&item)
+                            .map(|word_score| (item, score + word_score))
+                    })
+                    .collect(),
+                None => word_scores,
+            });
+        }
+
+        let mut hits: Vec<(u32, u32)> = scores.unwrap_or_default().into_iter().collect();
+        hits.sort_unstable_by(|a, b| {
+            b.1.cmp(&a.1).then_with(|| {
+                let (a, b) = (&self.items[a.0 as usize], &self.items[b.0 as usize]);
+                (&a.site, &a.username).cmp(&(&b.site, &b.username))
+            })
+        });
+        hits.into_iter()
+            .take(limit)
+            .map(|(item, score)| QuickSearchHit {
+                credential: self.items[item as usize].clone(),
+                score,
+            })
+            .collect()
+    }
+}
+
+/// The current index, shared by the vault and the repository wrapper; None
+/// until the next search rebuilds it
+pub type SharedSearchIndex = Arc<Mutex<Option<Arc<SearchIndex>>>>;
+
+/// Number of item writes so far
+pub type ChangeCounter = Arc<AtomicU64>;
+
+/// A `CredentialRepository` that drops the search index on every write
+pub struct IndexedRepository {
+    inner: Arc<dyn CredentialRepository>,
+    index: SharedSearchIndex,
+    changes: ChangeCounter,
+}
+
+impl IndexedRepository {
+    pub fn new(
+        inner: Arc<dyn CredentialRepository>,
+        index: SharedSearchIndex,
+        changes: ChangeCounter,
+    ) -> Self {
+        Self {
+            inner,
+            index,
+            changes,
+        }
+    }
+
+    // After the write, so a search running meanwhile cannot keep the old rows
+    fn invalidate(&self) {
+        *self.index.lock().unwrap() = None;
+        self.changes.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+impl CredentialRepository for IndexedRepository {
+    fn add_credential(&self, credential: &Credential, strength: u8) -> AppResult<()> {
+        let result = self.inner.add_credential(credential, strength);
+        self.invalidate();
+        result
+    }
+
+    fn update_credential(&self, credential: &Credential) -> AppResult<()> {
+        let result = self.inner.update_credential(credential);
+        self.invalidate();
+        result
+    }
+
+    fn delete_credential(&self, uuid: &str) -> AppResult<String> {
+        let result = self.inner.delete_credential(uuid);
+        self.invalidate();
+        result
+    }
+
+    fn get_credential(&self, uuid: &str) -> AppResult<Credential> {
+        self.inner.get_credential(uuid)
+    }
+
+    fn list_credentials(&self, filter: Option<CredentialFilter>) -> AppResult<Vec<Credential>> {
+        self.inner.list_credentials(filter)
+    }
+
+    fn update_breach_state(&self, uuid: &str, state: BreachState) -> AppResult<()> {
+        let result = self.inner.update_breach_state(uuid, state);
+        self.invalidate();
+        result
+    }
+
+    fn update_strengths(&self, updates: &[StrengthUpdate]) -> AppResult<usize> {
+        let result = self.inner.update_strengths(updates);
+        self.invalidate();
+        result
+    }
+
+    fn credential_exists(&self, uuid: &str) -> AppResult<bool> {
+        self.inner.credential_exists(uuid)
+    }
+
+    fn mark_used(&self, uuid: &str, at: DateTime<Utc>) -> AppResult<()> {
+        let result = self.inner.mark_used(uuid, at);
+        self.invalidate();
+        result
+    }
+
+    fn set_has_totp(&self, uuid: &str, has_totp: bool) -> AppResult<()> {
+        let result = self.inner.set_has_totp(uuid, has_totp);
+        self.invalidate();
+        result
+    }
+}
@@ -0,0 +1,424 @@
+//! Finds developer secrets in pasted text.
+//!
+//! Pasting a `.env`, JSON or YAML file proposes one `ApiKey` item per secret in
+//! it. Three things are detected: values in a known provider format (GitHub,
+//! AWS, Stripe, ...), database URLs that carry a password, and `KEY=VALUE` or
+//! `key: value` pairs whose name says they hold a secret. Placeholders such as
+//! `changeme` or `${OTHER_VAR}` are skipped.
+//!
+//! Scanning is deterministic, so the frontend picks proposals by `id` and the
+//! text is scanned again when they are saved; values never leave the backend.
+
+use std::collections::{HashMap, HashSet};
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::models::Secret;
+
+/// Largest text `parse_secrets_from_text` accepts
+pub const MAX_SCAN_BYTES: usize = 1024 * 1024;
+
+/// Value prefixes of well-known API keys: (prefix, minimum length, provider, site).
+/// Longer prefixes come before shorter ones they start with.
+const PROVIDER_PREFIXES: &[(&str, usize, &str, &str)] = &[
+    ("github_pat_", 40, "GitHub", "github.com"),
+    ("ghp_", 40, "GitHub", "github.com"),
+    ("gho_", 40, "GitHub", "github.com"),
+    ("ghu_", 40, "GitHub", "github.com"),
+    ("ghs_", 40, "GitHub", "github.com"),
+    ("ghr_", 40, "GitHub", "github.com"),
+    ("glpat-", 26, "GitLab", "gitlab.com"),
+    ("sk_live_", 24, "Stripe", "stripe.com"),
+    ("sk_test_", 24, "Stripe", "stripe.com"),
+    ("rk_live_", 24, "Stripe", "stripe.com"),
+    ("rk_test_", 24, "Stripe", "stripe.com"),
+    ("sk-ant-", 40, "Anthropic", "anthropic.com"),
+    ("sk-", 40, "OpenAI", "openai.com"),
+    ("xoxb-", 24, "Slack", "slack.com"),
+    ("xoxp-", 24, "Slack", "slack.com"),
+    ("xoxa-", 24, "Slack", "slack.com"),
+    ("AKIA", 20, "AWS", "aws.amazon.com"),
+    ("ASIA", 20, "AWS", "aws.amazon.com"),
+    ("AIza", 39, "Google Cloud", "console.cloud.google.com"),
+    ("SG.", 60, "SendGrid", "sendgrid.com"),
+    ("npm_", 40, "npm", "npmjs.com"),
+    ("hf_", 34, "Hugging Face", "huggingface.co"),
+];
+
+/// URL schemes of databases and brokers whose URLs carry credentials
+const DATABASE_SCHEMES: &[&str] = &[
+    "postgres",
+    "postgresql",
+    "mysql",
+    "mariadb",
+    "mongodb",
+    "mongodb+srv",
+    "redis",
+    "rediss",
+    "amqp",
+    "amqps",
+    "mssql",
+    "sqlserver",
+];
+
+/// Name parts that mark a variable as secret
+const SECRET_NAME_WORDS: &[&str] = &[
+    "key",
+    "apikey",
+    "secret",
+    "token",
+    "password",
+    "passwd",
+    "pwd",
+    "pass",
+    "auth",
+    "credential",
+    "credentials",
+    "dsn",
+    "private",
+];
+
+/// Name parts that mark a variable as not secret despite the words above,
+/// e.g. `STRIPE_PUBLISHABLE_KEY` or `SSH_KEY_FILE`
+const PUBLIC_NAME_WORDS: &[&str] = &["public", "publishable", "id", "file", "path"];
+
+/// Values that stand in for a real secret in example files
+const PLACEHOLDERS: &[&str] = &[
+    "changeme",
+    "change_me",
+    "password",
+    "secret",
+    "example",
+    "todo",
+    "null",
+    "none",
+    "true",
+    "false",
+];
+
+/// Secrets shorter than this are assumed to be placeholders or flags
+const MIN_SECRET_LEN: usize = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectedKind {
+    /// A value in a known provider's key format
+    ApiKey,
+    /// A database or broker URL with a password
+    DatabaseUrl,
+    /// A value whose name says it is secret
+    Variable,
+}
+
+/// A secret found in pasted text and the item proposed for it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectedSecret {
+    /// Position in the scan, used to choose which proposals to save
+    pub id: usize,
+    pub kind: DetectedKind,
+    /// The variable or key the value was assigned to
+    pub name: Option<String>,
+    pub provider: Option<String>,
+    /// Proposed site and username of the item
+    pub site: String,
+    pub username: String,
+    /// The value with most of it hidden, e.g. `ghp_…Xy9z`
+    pub preview: String,
+    /// 1-based line the value is on
+    pub line: usize,
+    #[serde(skip)]
+    value: String,
+}
+
+impl DetectedSecret {
+    /// The secret of the proposed item: the value as the password (the password
+    /// alone for database URLs, which keep the whole URL as a custom field) and
+    /// the variable name as a custom field
+    pub fn to_secret(&self) -> Secret {
+        let mut secret = Secret {
+            password: self.value.clone(),
+            ..Secret::default()
+        };
+        if self.kind == DetectedKind::DatabaseUrl {
+            if let Some(password) = Url::parse(&self.value)
+                .ok()
+                .and_then(|url| url.password().map(percent_decode))
+            {
+                secret.password = password;
+            }
+            secret
+                .custom_fields
+                .insert("url".to_string(), self.value.clone());
+        }
+        if let Some(name) = &self.name {
+            secret
+                .custom_fields
+                .insert("variable".to_string(), name.clone());
+        }
+        secret
+    }
+}
+
+/// Finds secrets in pasted `.env`, JSON or YAML text, in the order they appear.
+/// A value found more than once is proposed once.
+pub fn parse_secrets_from_text(text: &str) -> AppResult<Vec<DetectedSecret>> {
+    if text.len() > MAX_SCAN_BYTES {
+        return Err(AppError::InvalidInput(format!(
+            "Pasted text is larger than {} bytes",
+            MAX_SCAN_BYTES
+        )));
+    }
+
+    let mut found = Vec::new();
+    match serde_json::from_str::<serde_json::Value>(text) {
+        // JSON may be minified, so it is walked instead of read line by line
+        Ok(value @ serde_json::Value::Object(_)) => {
+            let mut pairs = Vec::new();
+            json_pairs(&value, "", &mut pairs);
+            for (name, value) in pairs {
+                let line = text
+                    .find(value.as_str())
+                    .map_or(1, |at| text[..at].matches('\n').count() + 1);
+                found.extend(classify(Some(&name), &value, line));
+            }
+        }
+        _ => {
+            for (index, line) in text.lines().enumerate() {
+                match split_pair(line) {
+                    // `Authorization: Bearer <key>` names the key but is not a secret name
+                    Some((name, value)) => {
+                        found.extend(classify(Some(name), &value, index + 1).or_else(|| {
+                            value
+                                .split_whitespace()
+                                .find_map(|word| classify(Some(name), word, index + 1))
+                        }))
+                    }
+                    // A bare key or URL on a line of its own
+                    None => {
+                        for word in line.split(|c: char| c.is_whitespace() || "\"'`,;".contains(c))
+                        {
+                            found.extend(classify(None, word, index + 1));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut seen = HashSet::new();
+    found.retain(|secret| seen.insert(secret.value.clone()));
+    for (id, secret) in found.iter_mut().enumerate() {
+        secret.id = id;
+    }
+    Ok(found)
+}
+
+/// String leaves of a JSON document, named by their dotted path
+fn json_pairs(value: &serde_json::Value, path: &str, out: &mut Vec<(String, String)>) {
+    let child = |key: &str| match path {
+        "" => key.to_string(),
+        _ => format!("{}.{}", path, key),
+    };
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                json_pairs(value, &child(key), out);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (index, value) in items.iter().enumerate() {
+                json_pairs(value, &child(&index.to_string()), out);
+            }
+        }
+        serde_json::Value::String(text) => out.push((path.to_string(), text.clone())),
+        _ => {}
+    }
+}
+
+/// Splits `NAME=value`, `export NAME=value`, `name: value` or `"name": "value",`
+/// into name and unquoted value
+fn split_pair(line: &str) -> Option<(&str, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+        return None;
+    }
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    let line = line.strip_prefix("- ").unwrap_or(line).trim_start();
+
+    let (name, rest, quoted_name) = match line.strip_prefix('"') {
+        Some(rest) => {
+            let (name, rest) = rest.split_once('"')?;
+            (name, rest.trim_start(), true)
+        }
+        None => {
+            let end = line
+                .find(|c: char| !(c.is_ascii_alphanumeric() || "_.-".contains(c)))
+                .unwrap_or(line.len());
+            (&line[..end], line[end..].trim_start(), false)
+        }
+    };
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let value = match (rest.strip_prefix('='), rest.strip_prefix(':')) {
+        (Some(value), _) => value,
+        // YAML needs a space after the colon, which tells `key: value` from a URL
+        (None, Some(value)) if quoted_name || value.is_empty() || value.starts_with(' ') => value,
+        _ => return None,
+    };
+    Some((name, unquote(value.trim())))
+}
+
+/// Strips quotes, a trailing comma (JSON) or a trailing comment (unquoted values)
+fn unquote(value: &str) -> String {
+    let value = value.strip_suffix(',').unwrap_or(value).trim_end();
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|rest| rest.rfind(quote).map(|end| &rest[..end]))
+        {
+            return inner.to_string();
+        }
+    }
+    match value.find(" #") {
+        Some(comment) => value[..comment].trim_end().to_string(),
+        None => value.to_string(),
+    }
+}
+
+fn classify(name: Option<&str>, value: &str, line: usize) -> Option<DetectedSecret> {
+    let value = value.trim();
+    let detected = |kind, provider: Option<&str>, site: String, username: String| DetectedSecret {
+        id: 0,
+        kind,
+        name: name.map(str::to_string),
+        provider: provider.map(str::to_string),
+        site,
+        username,
+        preview: preview(kind, value),
+        line,
+        value: value.to_string(),
+    };
+
+    if let Some((site, username)) = database_url(value) {
+        return Some(detected(DetectedKind::DatabaseUrl, None, site, username));
+    }
+    if let Some((provider, site)) = provider_of(value) {
+        let username = name.unwrap_or_default().to_string();
+        return Some(detected(
+            DetectedKind::ApiKey,
+            Some(provider),
+            site.to_string(),
+            username,
+        ));
+    }
+    let name = name.filter(|name| is_secret_name(name))?;
+    if is_placeholder(value) {
+        return None;
+    }
+    Some(detected(
+        DetectedKind::Variable,
+        None,
+        name.to_string(),
+        String::new(),
+    ))
+}
+
+/// Site (the URL without credentials) and user of a database URL with a password
+fn database_url(value: &str) -> Option<(String, String)> {
+    let url = Url::parse(value).ok()?;
+    if !DATABASE_SCHEMES.contains(&url.scheme()) || url.password().is_none_or(str::is_empty) {
+        return None;
+    }
+    let username = percent_decode(url.username());
+    let mut site = url;
+    site.set_password(None).ok()?;
+    site.set_username("").ok()?;
+    site.set_query(None);
+    Some((site.to_string(), username))
+}
+
+fn provider_of(value: &str) -> Option<(&'static str, &'static str)> {
+    if !value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+    {
+        return None;
+    }
+    PROVIDER_PREFIXES
+        .iter()
+        .find(|(prefix, min_len, _, _)| value.starts_with(prefix) && value.len() >= *min_len)
+        .map(|(_, _, provider, site)| (*provider, *site))
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    if words.iter().any(|word| PUBLIC_NAME_WORDS.contains(word)) {
+        return false;
+    }
+    // camelCase names (`apiKey`, `clientSecret`) end in a secret word
+    words.iter().any(|word| SECRET_NAME_WORDS.contains(word))
+        || ["key", "secret", "token", "password"]
+            .iter()
+            .any(|suffix| lower.ends_with(suffix))
+}
+
+fn is_placeholder(value: &str) -> bool {
+    let lower = value.to_lowercase();
+    value.chars().count() < MIN_SECRET_LEN
+        || PLACEHOLDERS.contains(&lower.as_str())
+        || lower.starts_with("your")
+        || value.starts_with("${")
+        || value.starts_with("$(")
+        || (value.starts_with('<') && value.ends_with('>'))
+        || value
+            .chars()
+            .all(|c| c == value.chars().next().unwrap_or(c))
+}
+
+/// The value with all but a few characters hidden; database URLs show
+/// everything except the password
+fn preview(kind: DetectedKind, value: &str) -> String {
+    if kind == DetectedKind::DatabaseUrl {
+        if let Ok(mut url) = Url::parse(value) {
+            if url.set_password(Some("****")).is_ok() {
+                return url.to_string();
+            }
+        }
+    }
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() < 16 {
+        return "•".repeat(chars.len().min(8));
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+fn percent_decode(text: &str) -> String {
+    percent_encoding::percent_decode_str(text)
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+/// Proposals chosen by id; unknown ids are an error, since they mean the text
+/// changed since it was scanned
+pub fn select(secrets: Vec<DetectedSecret>, ids: &[usize]) -> AppResult<Vec<DetectedSecret>> {
+    let mut by_id: HashMap<usize, DetectedSecret> = secrets
+        .into_iter()
+        .map(|secret| (secret.id, secret))
+        .collect();
+    ids.iter()
+        .map(|id| {
+            by_id
+                .remove(id)
+                .ok_or_else(|| AppError::NotFound(format!("detected secret {}", id)))
+        })
+        .collect()
+}
@@ -0,0 +1,685 @@
+//! Ephemeral sessions for shared or untrusted machines.
+//!
+//! With `AppSettings::ephemeral_session` on, the vault key leaves the app
+//! process right after unlock. It is handed to a child process, this same
+//! executable started with `WORKER_ARG`, and every operation needing it is
+//! sent there as a request. Locking kills the child, and the child exits by
+//! itself if the app dies, since its input is closed. A memory dump of the app
+//! then no longer holds the key.
+//!
+//! Keys derived from the vault key stay in the child too: the subkeys for
+//! encrypted storage and breach lists, the audit signing key, the member key
+//! and the collection keys it unwraps. The child runs every operation needing
+//! one, see `subkeys`, and only results come back: ciphertext, MACs,
+//! signatures, public keys and collection keys wrapped for other members.
+//! Secrets the user opens still come back to the app to be shown or typed; a
+//! secret in use is exposed either way. Handing out the raw key, as adding an
+//! emergency contact needs, is refused.
+//!
+//! Once it has the key, the child gives up everything it does not need to
+//! answer requests, see `hardening::sandbox_worker`: on Linux a seccomp filter
+//! leaves it reading and writing its pipes, allocating memory and drawing
+//! random numbers, so a compromised child can neither open files, connect
+//! anywhere nor start programs.
+//!
+//! Requests and replies are JSON, one per line, on the child's stdin and
+//! stdout. The first line the child reads is the key, in base64. A child that
+//! does not answer within `REPLY_TIMEOUT` is killed, so a hung worker cannot
+//! hold up the app, which waits for replies while its state is locked.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use aes_gcm::{Aes256Gcm, Key};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use x25519_dalek::PublicKey;
+use zeroize::Zeroizing;
+
+use crate::collections::WrappedKey;
+use crate::crypto;
+use crate::error::{AppError, AppResult, CryptoError};
+use crate::hardening;
+use crate::subkeys;
+
+/// First argument that makes the executable run as a session worker
+pub const WORKER_ARG: &str = "--session-worker";
+
+/// How long the worker may take to answer one request
+pub const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Most messages sent to the worker in one `Mac` request, so a large breach
+/// list cannot run into `REPLY_TIMEOUT`
+const MAC_BATCH: usize = 4096;
+
+/// Sent to the worker; byte strings are base64, containers are
+/// `EncryptedContainer` JSON
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum SessionRequest {
+    Seal {
+        plaintext: String,
+        aad: String,
+    },
+    Open {
+        ciphertext: String,
+        aad: String,
+        nonce: String,
+    },
+    SealWithSubkey {
+        context: String,
+        plaintext: String,
+        aad: String,
+    },
+    OpenWithSubkey {
+        context: String,
+        container: String,
+        aad: String,
+    },
+    Mac {
+        context: String,
+        messages: Vec<String>,
+    },
+    Sign {
+        message: String,
+    },
+    SigningPublicKey,
+    MemberPublicKey,
+    NewCollectionKey {
+        collection_id: String,
+    },
+    RewrapCollectionKey {
+        wrapped: WrappedKey,
+        collection_id: String,
+        recipient: String,
+    },
+    SealCollection {
+        wrapped: WrappedKey,
+        collection_id: String,
+        plaintext: String,
+        aad: String,
+    },
+    OpenCollection {
+        wrapped: WrappedKey,
+        collection_id: String,
+        container: String,
+        aad: String,
+    },
+    RecryptCollection {
+        from: WrappedKey,
+        to: WrappedKey,
+        collection_id: String,
+        container: String,
+        aad: String,
+    },
+}
+
+/// Sent back by the worker; byte strings are base64
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum SessionResponse {
+    Sealed {
+        nonce: String,
+        ciphertext: String,
+    },
+    Opened {
+        plaintext: String,
+    },
+    Container {
+        container: String,
+    },
+    /// None when the container was under the target key already
+    Recrypted {
+        container: Option<String>,
+    },
+    Macs {
+        macs: Vec<String>,
+    },
+    Signature {
+        signature: String,
+    },
+    PublicKey {
+        key: String,
+    },
+    Wrapped {
+        wrapped: WrappedKey,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+fn decode(field: &str, value: &str) -> Result<Vec<u8>, String> {
+    BASE64
+        .decode(value)
+        .map_err(|e| format!("Invalid {} encoding: {}", field, e))
+}
+
+fn public_key(field: &str, value: &str) -> Result<[u8; 32], String> {
+    decode(field, value)?
+        .try_into()
+        .map_err(|_| format!("{} must be 32 bytes", field))
+}
+
+fn handle(key: &Key<Aes256Gcm>, request: SessionRequest) -> Result<SessionResponse, String> {
+    let failed = |e: AppError| e.to_string();
+    match request {
+        SessionRequest::Seal { plaintext, aad } => {
+            let (nonce, ciphertext) = crypto::seal(
+                key,
+                &decode("plaintext", &plaintext)?,
+                &decode("aad", &aad)?,
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(SessionResponse::Sealed {
+                nonce: BASE64.encode(nonce),
+                ciphertext: BASE64.encode(ciphertext),
+            })
+        }
+        SessionRequest::Open {
+            ciphertext,
+            aad,
+            nonce,
+        } => {
+            let plaintext = crypto::open(
+                key,
+                &decode("ciphertext", &ciphertext)?,
+                &decode("aad", &aad)?,
+                &decode("nonce", &nonce)?,
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(SessionResponse::Opened {
+                plaintext: BASE64.encode(plaintext),
+            })
+        }
+        SessionRequest::SealWithSubkey {
+            context,
+            plaintext,
+            aad,
+        } => Ok(SessionResponse::Container {
+            container: subkeys::seal(
+                key,
+                &decode("context", &context)?,
+                &decode("plaintext", &plaintext)?,
+                &decode("aad", &aad)?,
+            )
+            .map_err(failed)?,
+        }),
+        SessionRequest::OpenWithSubkey {
+            context,
+            container,
+            aad,
+        } => {
+            let plaintext = subkeys::open(
+                key,
+                &decode("context", &context)?,
+                &container,
+                &decode("aad", &aad)?,
+            )
+            .map_err(failed)?;
+            Ok(SessionResponse::Opened {
+                plaintext: BASE64.encode(plaintext),
+            })
+        }
+        SessionRequest::Mac { context, messages } => {
+            let messages = messages
+                .iter()
+                .map(|message| decode("message", message))
+                .collect::<Result<Vec<_>, _>>()?;
+            let macs =
+                subkeys::mac(key, &decode("context", &context)?, &messages).map_err(failed)?;
+            Ok(SessionResponse::Macs {
+                macs: macs.iter().map(|mac| BASE64.encode(mac)).collect(),
+            })
+        }
+        SessionRequest::Sign { message } => {
+            let signature = subkeys::sign(key, &decode("message", &message)?).map_err(failed)?;
+            Ok(SessionResponse::Signature {
+                signature: BASE64.encode(signature.to_bytes()),
+            })
+        }
+        SessionRequest::SigningPublicKey => Ok(SessionResponse::PublicKey {
+            key: BASE64.encode(subkeys::signing_public_key(key).map_err(failed)?.as_bytes()),
+        }),
+        SessionRequest::MemberPublicKey => Ok(SessionResponse::PublicKey {
+            key: BASE64.encode(subkeys::member_public_key(key).map_err(failed)?.as_bytes()),
+        }),
+        SessionRequest::NewCollectionKey { collection_id } => Ok(SessionResponse::Wrapped {
+            wrapped: subkeys::new_collection_key(key, &collection_id).map_err(failed)?,
+        }),
+        SessionRequest::RewrapCollectionKey {
+            wrapped,
+            collection_id,
+            recipient,
+        } => Ok(SessionResponse::Wrapped {
+            wrapped: subkeys::rewrap_collection_key(
+                key,
+                &wrapped,
+                &collection_id,
+                &PublicKey::from(public_key("recipient", &recipient)?),
+            )
+            .map_err(failed)?,
+        }),
+        SessionRequest::SealCollection {
+            wrapped,
+            collection_id,
+            plaintext,
+            aad,
+        } => Ok(SessionResponse::Container {
+            container: subkeys::seal_collection(
+                key,
+                &wrapped,
+                &collection_id,
+                &decode("plaintext", &plaintext)?,
+                &decode("aad", &aad)?,
+            )
+            .map_err(failed)?,
+        }),
+        SessionRequest::OpenCollection {
+            wrapped,
+            collection_id,
+            container,
+            aad,
+        } => {
+            let plaintext = subkeys::open_collection(
+                key,
+                &wrapped,
+                &collection_id,
+                &container,
+                &decode("aad", &aad)?,
+            )
+            .map_err(failed)?;
+            Ok(SessionResponse::Opened {
+                plaintext: BASE64.encode(plaintext),
+            })
+        }
+        SessionRequest::RecryptCollection {
+            from,
+            to,
+            collection_id,
+            container,
+            aad,
+        } => Ok(SessionResponse::Recrypted {
+            container: subkeys::recrypt_collection(
+                key,
+                &from,
+                &to,
+                &collection_id,
+                &container,
+                &decode("aad", &aad)?,
+            )
+            .map_err(failed)?,
+        }),
+    }
+}
+
+/// Answers requests from `reader` with `key` until it is closed
+pub fn serve(key: &[u8; 32], reader: impl BufRead, mut writer: impl Write) -> io::Result<()> {
+    let key = Key::<Aes256Gcm>::from_slice(key);
+    for line in reader.lines() {
+        let response = serde_json::from_str::<SessionRequest>(&line?)
+            .map_err(|e| format!("Invalid request: {}", e))
+            .and_then(|request| handle(key, request))
+            .unwrap_or_else(|message| SessionResponse::Failed { message });
+        serde_json::to_writer(&mut writer, &response)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Entry point of the worker process: reads the key, then serves until the
+/// app closes its input or kills it
+pub fn run_worker() {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut line = Zeroizing::new(String::new());
+    if input.read_line(&mut line).is_err() {
+        return;
+    }
+    let Ok(decoded) = BASE64.decode(line.trim()).map(Zeroizing::new) else {
+        return;
+    };
+    let key: [u8; 32] = match decoded.as_slice().try_into() {
+        Ok(key) => key,
+        Err(_) => return,
+    };
+    // The worker holds the key, so it gets the same protection as the app,
+    // and is then confined to answering requests
+    hardening::harden_process();
+    hardening::lock_memory(&key);
+    let output = io::stdout().lock();
+    if hardening::sandbox_worker() == hardening::MitigationState::Failed {
+        // Better no session than one that is not confined; the app sees the
+        // worker exit and keeps the vault locked
+        return;
+    }
+    let _ = serve(&key, input, output);
+}
+
+/// A running worker holding the vault key. Dropping it kills the worker.
+pub struct EphemeralSession {
+    child: Child,
+    stdin: ChildStdin,
+    // Lines the worker wrote, read on a thread of their own so waiting can time out
+    replies: Receiver<io::Result<String>>,
+    reply_timeout: Duration,
+}
+
+impl EphemeralSession {
+    /// Starts a worker from this executable and hands it `key`
+    pub fn start(key: &[u8; 32]) -> AppResult<Self> {
+        let mut command = Command::new(std::env::current_exe()?);
+        command.arg(WORKER_ARG);
+        Self::start_command(command, key, REPLY_TIMEOUT)
+    }
+
+    /// Starts `command` as the worker, giving up on replies after `reply_timeout`
+    pub(crate) fn start_command(
+        mut command: Command,
+        key: &[u8; 32],
+        reply_timeout: Duration,
+    ) -> AppResult<Self> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            let _ = child.kill();
+            return Err(AppError::Other(
+                "Could not talk to the session process".to_string(),
+            ));
+        };
+        let (sender, replies) = mpsc::channel();
+        thread::spawn(move || {
+            // Ends when the worker exits and its output closes
+            for line in BufReader::new(stdout).lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut session = Self {
+            child,
+            stdin,
+            replies,
+            reply_timeout,
+        };
+        let encoded = Zeroizing::new(BASE64.encode(key));
+        writeln!(session.stdin, "{}", *encoded)?;
+        session.stdin.flush()?;
+        debug!(pid = session.child.id(), "ephemeral session started");
+        Ok(session)
+    }
+
+    fn call(&mut self, request: &SessionRequest) -> AppResult<SessionResponse> {
+        let ended = || {
+            AppError::Other(
+                "The ephemeral session ended; lock the vault and unlock it again".to_string(),
+            )
+        };
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+        if self
+            .stdin
+            .write_all(line.as_bytes())
+            .and_then(|()| self.stdin.flush())
+            .is_err()
+        {
+            warn!("ephemeral session process is gone");
+            return Err(ended());
+        }
+        match self.replies.recv_timeout(self.reply_timeout) {
+            Ok(reply) => Ok(serde_json::from_str(&reply?)?),
+            Err(RecvTimeoutError::Timeout) => {
+                // Later requests then fail right away instead of waiting again
+                warn!("ephemeral session process stopped answering; ending it");
+                let _ = self.child.kill();
+                Err(ended())
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                warn!("ephemeral session process is gone");
+                Err(ended())
+            }
+        }
+    }
+
+    pub fn seal(&mut self, plaintext: &[u8], aad: &[u8]) -> AppResult<([u8; 12], Vec<u8>)> {
+        let request = SessionRequest::Seal {
+            plaintext: BASE64.encode(plaintext),
+            aad: BASE64.encode(aad),
+        };
+        match self.call(&request)? {
+            SessionResponse::Sealed { nonce, ciphertext } => {
+                let nonce = reply_bytes(&nonce)?
+                    .try_into()
+                    .map_err(|_| unexpected_reply())?;
+                Ok((nonce, reply_bytes(&ciphertext)?))
+            }
+            SessionResponse::Failed { message } => Err(CryptoError::Encryption(message).into()),
+            _ => Err(unexpected_reply()),
+        }
+    }
+
+    pub fn open(&mut self, ciphertext: &[u8], aad: &[u8], nonce: &[u8]) -> AppResult<Vec<u8>> {
+        let request = SessionRequest::Open {
+            ciphertext: BASE64.encode(ciphertext),
+            aad: BASE64.encode(aad),
+            nonce: BASE64.encode(nonce),
+        };
+        match self.call(&request)? {
+            SessionResponse::Opened { plaintext } => reply_bytes(&plaintext),
+            SessionResponse::Failed { message } => Err(CryptoError::Decryption(message).into()),
+            _ => Err(unexpected_reply()),
+        }
+    }
+
+    /// Sends a request answered with a container
+    fn call_container(&mut self, request: &SessionRequest) -> AppResult<String> {
+        match self.call(request)? {
+            SessionResponse::Container { container } => Ok(container),
+            SessionResponse::Failed { message } => Err(CryptoError::Encryption(message).into()),
+            _ => Err(unexpected_reply()),
+        }
+    }
+
+    /// Sends a request answered with plaintext
+    fn call_opened(&mut self, request: &SessionRequest) -> AppResult<Vec<u8>> {
+        match self.call(request)? {
+            SessionResponse::Opened { plaintext } => reply_bytes(&plaintext),
+            SessionResponse::Failed { message } => Err(CryptoError::Decryption(message).into()),
+            _ => Err(unexpected_reply()),
+        }
+    }
+
+    /// Sends a request answered with a public key
+    fn call_public_key(&mut self, request: &SessionRequest) -> AppResult<[u8; 32]> {
+        match self.call(request)? {
+            SessionResponse::PublicKey { key } => reply_bytes(&key)?
+                .try_into()
+                .map_err(|_| unexpected_reply()),
+            SessionResponse::Failed { message } => Err(CryptoError::KeyDerivation(message).into()),
+            _ => Err(unexpected_reply()),
+        }
+    }
+
+    /// Sends a request answered with a wrapped collection key
+    fn call_wrapped(&mut self, request: &SessionRequest) -> AppResult<WrappedKey> {
+        match self.call(request)? {
+            SessionResponse::Wrapped { wrapped } => Ok(wrapped),
+            SessionResponse::Failed { message } => Err(CryptoError::KeyDerivation(message).into()),
+            _ => Err(unexpected_reply()),
+        }
+    }
+
+    pub fn seal_with_subkey(
+        &mut self,
+        context: &[u8],
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> AppResult<String> {
+        self.call_container(&SessionRequest::SealWithSubkey {
+            context: BASE64.encode(context),
+            plaintext: BASE64.encode(plaintext),
+            aad: BASE64.encode(aad),
+        })
+    }
+
+    pub fn open_with_subkey(
+        &mut self,
+        context: &[u8],
+        container: &str,
+        aad: &[u8],
+    ) -> AppResult<Vec<u8>> {
+        self.call_opened(&SessionRequest::OpenWithSubkey {
+            context: BASE64.encode(context),
+            container: container.to_string(),
+            aad: BASE64.encode(aad),
+        })
+    }
+
+    pub fn mac(&mut self, context: &[u8], messages: &[Vec<u8>]) -> AppResult<Vec<[u8; 32]>> {
+        let mut macs = Vec::with_capacity(messages.len());
+        for batch in messages.chunks(MAC_BATCH) {
+            let request = SessionRequest::Mac {
+                context: BASE64.encode(context),
+                messages: batch.iter().map(|message| BASE64.encode(message)).collect(),
+            };
+            match self.call(&request)? {
+                SessionResponse::Macs { macs: batch_macs } if batch_macs.len() == batch.len() => {
+                    for mac in batch_macs {
+                        macs.push(
+                            reply_bytes(&mac)?
+                                .try_into()
+                                .map_err(|_| unexpected_reply())?,
+                        );
+                    }
+                }
+                SessionResponse::Failed { message } => {
+                    return Err(CryptoError::KeyDerivation(message).into())
+                }
+                _ => return Err(unexpected_reply()),
+            }
+        }
+        Ok(macs)
+    }
+
+    pub fn sign(&mut self, message: &[u8]) -> AppResult<Signature> {
+        let request = SessionRequest::Sign {
+            message: BASE64.encode(message),
+        };
+        match self.call(&request)? {
+            SessionResponse::Signature { signature } => {
+                let bytes: [u8; 64] = reply_bytes(&signature)?
+                    .try_into()
+                    .map_err(|_| unexpected_reply())?;
+                Ok(Signature::from_bytes(&bytes))
+            }
+            SessionResponse::Failed { message } => Err(CryptoError::Encryption(message).into()),
+            _ => Err(unexpected_reply()),
+        }
+    }
+
+    pub fn signing_public_key(&mut self) -> AppResult<VerifyingKey> {
+        let key = self.call_public_key(&SessionRequest::SigningPublicKey)?;
+        VerifyingKey::from_bytes(&key).map_err(|_| unexpected_reply())
+    }
+
+    pub fn member_public_key(&mut self) -> AppResult<PublicKey> {
+        self.call_public_key(&SessionRequest::MemberPublicKey)
+            .map(PublicKey::from)
+    }
+
+    pub fn new_collection_key(&mut self, collection_id: &str) -> AppResult<WrappedKey> {
+        self.call_wrapped(&SessionRequest::NewCollectionKey {
+            collection_id: collection_id.to_string(),
+        })
+    }
+
+    pub fn rewrap_collection_key(
+        &mut self,
+        wrapped: &WrappedKey,
+        collection_id: &str,
+        recipient: &PublicKey,
+    ) -> AppResult<WrappedKey> {
+        self.call_wrapped(&SessionRequest::RewrapCollectionKey {
+            wrapped: wrapped.clone(),
+            collection_id: collection_id.to_string(),
+            recipient: BASE64.encode(recipient.as_bytes()),
+        })
+    }
+
+    pub fn seal_collection(
+        &mut self,
+        wrapped: &WrappedKey,
+        collection_id: &str,
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> AppResult<String> {
+        self.call_container(&SessionRequest::SealCollection {
+            wrapped: wrapped.clone(),
+            collection_id: collection_id.to_string(),
+            plaintext: BASE64.encode(plaintext),
+            aad: BASE64.encode(aad),
+        })
+    }
+
+    pub fn open_collection(
+        &mut self,
+        wrapped: &WrappedKey,
+        collection_id: &str,
+        container: &str,
+        aad: &[u8],
+    ) -> AppResult<Vec<u8>> {
+        self.call_opened(&SessionRequest::OpenCollection {
+            wrapped: wrapped.clone(),
+            collection_id: collection_id.to_string(),
+            container: container.to_string(),
+            aad: BASE64.encode(aad),
+        })
+    }
+
+    pub fn recrypt_collection(
+        &mut self,
+        from: &WrappedKey,
+        to: &WrappedKey,
+        collection_id: &str,
+        container: &str,
+        aad: &[u8],
+    ) -> AppResult<Option<String>> {
+        let request = SessionRequest::RecryptCollection {
+            from: from.clone(),
+            to: to.clone(),
+            collection_id: collection_id.to_string(),
+            container: container.to_string(),
+            aad: BASE64.encode(aad),
+        };
+        match self.call(&request)? {
+            SessionResponse::Recrypted { container } => Ok(container),
+            SessionResponse::Failed { message } => Err(CryptoError::Decryption(message).into()),
+            _ => Err(unexpected_reply()),
+        }
+    }
+}
+
+impl Drop for EphemeralSession {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        debug!("ephemeral session ended");
+    }
+}
+
+fn unexpected_reply() -> AppError {
+    AppError::Other("Unexpected reply from the session process".to_string())
+}
+
+fn reply_bytes(value: &str) -> AppResult<Vec<u8>> {
+    BASE64.decode(value).map_err(|_| unexpected_reply())
+}
@@ -0,0 +1,150 @@
+//! Settings profiles for replicating a configuration on other machines.
+//!
+//! A profile is a plain JSON file with the app settings minus anything
+//! secret: sync credentials and the breach monitoring API key stay behind,
+//! and importing keeps the values this machine already has for them. The
+//! Argon2 parameters travel in a section of their own and are only applied
+//! when the import asks for them, since they decide how long every unlock
+//! takes on the receiving machine.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::{AppError, AppResult};
+use crate::models::AppSettings;
+
+/// Format tag written into every profile
+pub const SETTINGS_PROFILE_FORMAT: &str = "secret-plan-settings/1";
+
+/// Settings that go in `SettingsProfile::kdf` instead
+const KDF_FIELDS: &[&str] = &[
+    "argon2_memory_kb",
+    "argon2_iterations",
+    "argon2_parallelism",
+];
+
+/// Argon2 parameters of the exporting machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfProfile {
+    pub memory_kb: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl KdfProfile {
+    fn of(settings: &AppSettings) -> Self {
+        Self {
+            memory_kb: settings.argon2_memory_kb,
+            iterations: settings.argon2_iterations,
+            parallelism: settings.argon2_parallelism,
+        }
+    }
+}
+
+/// File contents of a settings profile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettingsProfile {
+    pub format: String,
+    pub exported_at: DateTime<Utc>,
+    /// `AppSettings` fields by name, with secrets cleared and without the KDF.
+    /// Fields a newer version added are ignored on import, missing ones are
+    /// left as they are.
+    pub settings: Map<String, Value>,
+    pub kdf: KdfProfile,
+}
+
+/// Result of importing a profile
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettingsProfileImport {
+    /// Settings whose value changed, by field name
+    pub changed: Vec<String>,
+    /// The profile's KDF parameters differ from the current ones
+    pub kdf_differs: bool,
+    /// They were applied, as asked
+    pub kdf_applied: bool,
+}
+
+/// Copies the secret settings of `from` into `settings`
+fn keep_secrets(settings: &mut AppSettings, from: &AppSettings) {
+    settings.sync_config = from.sync_config.clone();
+    settings.email_monitor.api_key = from.email_monitor.api_key.clone();
+}
+
+fn to_map(settings: &AppSettings) -> AppResult<Map<String, Value>> {
+    match serde_json::to_value(settings)? {
+        Value::Object(map) => Ok(map),
+        _ => Err(AppError::Other("Settings are not an object".to_string())),
+    }
+}
+
+/// The profile of `settings`
+pub fn build(settings: &AppSettings, now: DateTime<Utc>) -> AppResult<SettingsProfile> {
+    let mut shared = settings.clone();
+    keep_secrets(&mut shared, &AppSettings::default());
+    let mut map = to_map(&shared)?;
+    for field in KDF_FIELDS {
+        map.remove(*field);
+    }
+    Ok(SettingsProfile {
+        format: SETTINGS_PROFILE_FORMAT.to_string(),
+        exported_at: now,
+        settings: map,
+        kdf: KdfProfile::of(settings),
+    })
+}
+
+/// Reads a profile, refusing other files
+pub fn parse(bytes: &[u8]) -> AppResult<SettingsProfile> {
+    let profile: SettingsProfile = serde_json::from_slice(bytes)
+        .map_err(|e| AppError::InvalidInput(format!("Not a settings profile: {}", e)))?;
+    if profile.format != SETTINGS_PROFILE_FORMAT {
+        return Err(AppError::InvalidInput(format!(
+            "Unsupported settings profile format: {}",
+            profile.format
+        )));
+    }
+    Ok(profile)
+}
+
+/// `current` with the profile laid over it. Secrets are kept from `current`
+/// whatever the profile holds, and the KDF only changes with `include_kdf`.
+pub fn apply(
+    profile: &SettingsProfile,
+    current: &AppSettings,
+    include_kdf: bool,
+) -> AppResult<(AppSettings, SettingsProfileImport)> {
+    let before = to_map(current)?;
+    let mut merged = before.clone();
+    for (field, value) in &profile.settings {
+        if merged.contains_key(field) && !KDF_FIELDS.contains(&field.as_str()) {
+            merged.insert(field.clone(), value.clone());
+        }
+    }
+
+    let mut settings: AppSettings = serde_json::from_value(Value::Object(merged))
+        .map_err(|e| AppError::InvalidInput(format!("Invalid settings in profile: {}", e)))?;
+    keep_secrets(&mut settings, current);
+    let kdf_differs = profile.kdf != KdfProfile::of(current);
+    if include_kdf {
+        settings.argon2_memory_kb = profile.kdf.memory_kb;
+        settings.argon2_iterations = profile.kdf.iterations;
+        settings.argon2_parallelism = profile.kdf.parallelism;
+    }
+    settings.validate()?;
+
+    let after = to_map(&settings)?;
+    let changed = after
+        .iter()
+        .filter(|(field, value)| before.get(*field) != Some(*value))
+        .map(|(field, _)| field.clone())
+        .collect();
+    Ok((
+        settings,
+        SettingsProfileImport {
+            changed,
+            kdf_differs,
+            kdf_applied: include_kdf && kdf_differs,
+        },
+    ))
+}
@@ -0,0 +1,47 @@
+//! Creating a vault in one step.
+//!
+//! `VaultManager::create_vault` checks the master password against the policy,
+//! calibrates Argon2 for this machine and stores the password hash together with
+//! the first settings, so a vault is never left with a hash but no settings.
+//!
+//! Whether a vault exists at all is decided by the stored master password hash,
+//! not by the database file: `VaultManager::vault_status` reports it, and
+//! `unlock` fails with `AppError::VaultNotCreated` instead of creating a vault.
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::KdfCalibration;
+use crate::models::AppSettings;
+
+/// Where the vault is in its life, as the start screen needs to know
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VaultStatus {
+    /// No master password has been set; the vault has to be created
+    NotInitialized,
+    /// The vault exists and has not been unlocked since it was opened
+    Created,
+    Unlocked,
+    /// Unlocked earlier in this session and locked since
+    Locked,
+}
+
+/// Choices made in the vault creation wizard
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VaultSetupOptions {
+    /// Unlock time to calibrate Argon2 for; None keeps the configured parameters
+    #[serde(default)]
+    pub kdf_target_ms: Option<u32>,
+    /// Initial settings; defaults when None
+    #[serde(default)]
+    pub settings: Option<AppSettings>,
+    /// Username, email and the like, which the master password should not contain
+    #[serde(default)]
+    pub user_inputs: Vec<String>,
+}
+
+/// What `create_vault` set up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultSetup {
+    /// None when the configured parameters were kept
+    pub kdf: Option<KdfCalibration>,
+}
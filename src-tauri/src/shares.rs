@@ -0,0 +1,261 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::crypto::CryptoService;
+use crate::error::{AppError, AppResult};
+use crate::net::{self, classify_service_error, validate_service_url, Network};
+
+/// Format tag written into every share envelope
+pub const SHARE_FORMAT: &str = "secret-plan-share/1";
+
+/// Shortest and longest allowed share lifetime
+pub const MIN_SHARE_LIFETIME_SECS: u64 = 60;
+pub const MAX_SHARE_LIFETIME_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Upper bound for `max_views`
+pub const MAX_SHARE_VIEWS: u32 = 100;
+
+/// What the recipient sees after decrypting a share
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SharedSecret {
+    pub site: String,
+    pub username: String,
+    pub password: String,
+    pub notes: Option<String>,
+}
+
+/// An encrypted share; safe to hand to a relay or write to disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareEnvelope {
+    pub format: String,
+    pub id: String,
+    pub expires_at: DateTime<Utc>,
+    pub max_views: u32,
+    /// `SharedSecret` encrypted with the share key, bound to `id`
+    pub ciphertext: String,
+}
+
+/// Where a share was published
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareDestination {
+    /// Uploaded to the configured share relay
+    Relay,
+    /// Written to a self-contained file; expiry and view limits are up to whoever opens it
+    File,
+}
+
+impl ShareDestination {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ShareDestination::Relay => "relay",
+            ShareDestination::File => "file",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "relay" => Some(ShareDestination::Relay),
+            "file" => Some(ShareDestination::File),
+            _ => None,
+        }
+    }
+}
+
+/// Local bookkeeping for a share. Never contains the share key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareRecord {
+    pub id: String,
+    pub item_uuid: String,
+    pub site: String,
+    pub destination: ShareDestination,
+    /// Relay base URL or file path
+    pub location: String,
+    /// Secret the relay requires to delete the share early
+    #[serde(skip_serializing, default)]
+    pub revoke_token: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub max_views: u32,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Returned once when a share is created; the key is not stored anywhere
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareLink {
+    pub share: ShareRecord,
+    /// Base64url share key, to be sent to the recipient separately for file shares
+    pub key: String,
+    /// Relay link with the key in the fragment, which never reaches the relay
+    pub link: Option<String>,
+}
+
+/// A sealed share that has not been published yet
+#[derive(Debug, Clone)]
+pub struct PreparedShare {
+    pub envelope: ShareEnvelope,
+    pub key: [u8; 32],
+    pub item_uuid: String,
+    pub site: String,
+}
+
+/// Checks the requested lifetime and view limit
+pub fn validate_limits(expires_in_secs: u64, max_views: u32) -> AppResult<()> {
+    if !(MIN_SHARE_LIFETIME_SECS..=MAX_SHARE_LIFETIME_SECS).contains(&expires_in_secs) {
+        return Err(AppError::InvalidInput(
+            "Share lifetime must be between 1 minute and 30 days".to_string(),
+        ));
+    }
+    if !(1..=MAX_SHARE_VIEWS).contains(&max_views) {
+        return Err(AppError::InvalidInput(format!(
+            "Share view limit must be between 1 and {}",
+            MAX_SHARE_VIEWS
+        )));
+    }
+    Ok(())
+}
+
+/// Encrypts `secret` with a fresh random key. Returns the envelope and the key.
+pub fn seal(
+    secret: &SharedSecret,
+    expires_in_secs: u64,
+    max_views: u32,
+    now: DateTime<Utc>,
+) -> AppResult<(ShareEnvelope, [u8; 32])> {
+    validate_limits(expires_in_secs, max_views)?;
+    let id = Uuid::new_v4().to_string();
+    let key = CryptoService::generate_key();
+    let ciphertext =
+        CryptoService::encrypt_with_key(&key, &serde_json::to_vec(secret)?, id.as_bytes())?;
+    Ok((
+        ShareEnvelope {
+            format: SHARE_FORMAT.to_string(),
+            id,
+            expires_at: now + chrono::Duration::seconds(expires_in_secs as i64),
+            max_views,
+            ciphertext,
+        },
+        key,
+    ))
+}
+
+/// Decrypts an envelope with its Base64url key, rejecting expired shares
+pub fn open(envelope: &ShareEnvelope, key: &str, now: DateTime<Utc>) -> AppResult<SharedSecret> {
+    if envelope.format != SHARE_FORMAT {
+        return Err(AppError::InvalidInput(format!(
+            "Unsupported share format: {}",
+            envelope.format
+        )));
+    }
+    if envelope.expires_at <= now {
+        return Err(AppError::InvalidInput("The share has expired".to_string()));
+    }
+    let key = URL_SAFE_NO_PAD
+        .decode(key.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| AppError::InvalidInput("Invalid share key".to_string()))?;
+    let plaintext =
+        CryptoService::decrypt_with_key(&key, &envelope.ciphertext, envelope.id.as_bytes())?;
+    serde_json::from_slice(&plaintext).map_err(AppError::Serialization)
+}
+
+/// Encodes a share key for links and manual transfer
+pub fn encode_key(key: &[u8; 32]) -> String {
+    URL_SAFE_NO_PAD.encode(key)
+}
+
+/// Random token the relay requires before deleting a share early
+pub fn generate_revoke_token() -> String {
+    URL_SAFE_NO_PAD.encode(CryptoService::generate_key())
+}
+
+/// Upload body: the envelope plus the token needed to revoke it
+#[derive(Serialize)]
+struct RelayUpload<'a> {
+    #[serde(flatten)]
+    envelope: &'a ShareEnvelope,
+    revoke_token: &'a str,
+}
+
+/// Client for a share relay: `POST {base}/shares` stores an envelope and
+/// `DELETE {base}/shares/{id}` (bearer revoke token) removes it.
+/// Recipients open `{base}/s/{id}#{key}`.
+#[derive(Debug, Clone)]
+pub struct ShareRelay {
+    base_url: String,
+    network: Network,
+}
+
+impl ShareRelay {
+    /// Validates the relay URL (https, or http on loopback)
+    pub fn new(base_url: &str) -> AppResult<Self> {
+        Ok(Self {
+            base_url: validate_service_url(base_url, "Share relay")?,
+            network: Network::default(),
+        })
+    }
+
+    /// Sends requests through the app's proxy and offline settings
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// The validated base URL
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Link the recipient opens, without the key fragment
+    pub fn share_url(&self, id: &str) -> String {
+        format!("{}/s/{}", self.base_url, id)
+    }
+
+    fn client(&self) -> AppResult<reqwest::Client> {
+        net::build_client(self.network.client_builder("Share relay")?)
+    }
+
+    /// Uploads an envelope; fails with `AppError::Network` when the relay is unreachable
+    pub async fn upload(&self, envelope: &ShareEnvelope, revoke_token: &str) -> AppResult<()> {
+        let response = self
+            .client()?
+            .post(format!("{}/shares", self.base_url))
+            .json(&RelayUpload {
+                envelope,
+                revoke_token,
+            })
+            .send()
+            .await
+            .map_err(|e| classify_service_error(e, "Share relay"))?;
+        if !response.status().is_success() {
+            return Err(AppError::Other(format!(
+                "Share relay returned error: {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Deletes a share from the relay. Shares the relay no longer has count as revoked.
+    pub async fn revoke(&self, id: &str, revoke_token: &str) -> AppResult<()> {
+        let response = self
+            .client()?
+            .delete(format!("{}/shares/{}", self.base_url, id))
+            .bearer_auth(revoke_token)
+            .send()
+            .await
+            .map_err(|e| classify_service_error(e, "Share relay"))?;
+        let status = response.status();
+        if !status.is_success() && status != StatusCode::NOT_FOUND && status != StatusCode::GONE {
+            return Err(AppError::Other(format!(
+                "Share relay returned error: {}",
+                status
+            )));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,239 @@
+//! Matching credentials to the URL of a page.
+//!
+//! An item matches a URL when both belong to the same registrable domain, so
+//! `accounts.example.com` finds the item saved for `example.com`. A URL that
+//! matches nothing but resembles a stored domain (a homograph such as a
+//! Cyrillic "а" in place of "a", a small misspelling, or a stored domain
+//! buried in a longer hostname) is reported as a look-alike instead, so the
+//! browser bridge can warn rather than autofill on a phishing site.
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Credential, CredentialSummary};
+
+/// Second-level labels under which country TLDs register names, as in
+/// `example.co.uk`. Without a public suffix list this covers the common cases.
+const SECOND_LEVEL_LABELS: &[&str] = &[
+    "ac", "co", "com", "edu", "go", "gov", "ne", "net", "or", "org",
+];
+
+/// Domain names shorter than this (without the TLD) are never reported for
+/// misspellings: short names are too often one edit apart by coincidence
+const MIN_SIMILAR_NAME_LENGTH: usize = 5;
+
+/// Characters that render like ASCII letters in common fonts, mapped to the
+/// letter they imitate
+const CONFUSABLES: &[(char, char)] = &[
+    // Cyrillic
+    ('а', 'a'),
+    ('в', 'b'),
+    ('с', 'c'),
+    ('ԁ', 'd'),
+    ('е', 'e'),
+    ('ё', 'e'),
+    ('һ', 'h'),
+    ('і', 'i'),
+    ('ї', 'i'),
+    ('ј', 'j'),
+    ('к', 'k'),
+    ('м', 'm'),
+    ('н', 'h'),
+    ('о', 'o'),
+    ('р', 'p'),
+    ('ԛ', 'q'),
+    ('ѕ', 's'),
+    ('т', 't'),
+    ('у', 'y'),
+    ('х', 'x'),
+    ('ԝ', 'w'),
+    // Greek
+    ('α', 'a'),
+    ('β', 'b'),
+    ('ε', 'e'),
+    ('ι', 'i'),
+    ('κ', 'k'),
+    ('ν', 'v'),
+    ('ο', 'o'),
+    ('ρ', 'p'),
+    ('τ', 't'),
+    ('υ', 'u'),
+    ('χ', 'x'),
+    // Latin look-alikes and digits
+    ('ı', 'i'),
+    ('ɡ', 'g'),
+    ('ӏ', 'l'),
+    ('0', 'o'),
+    ('1', 'l'),
+];
+
+/// Why a URL was taken for a look-alike
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LookalikeReason {
+    /// Spelled with characters that look like those of the stored domain,
+    /// usually an internationalized (punycode) name
+    Homograph,
+    /// One or two edits away from the stored domain
+    SimilarSpelling,
+    /// The stored domain appears as part of a different domain, as in
+    /// `example.com.login.net`
+    EmbeddedDomain,
+}
+
+/// A URL that resembles, but is not, a domain credentials are saved for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LookalikeWarning {
+    /// The visited domain, in Unicode so homographs can be shown as such
+    pub domain: String,
+    /// The stored domain it resembles
+    pub resembles: String,
+    pub reason: LookalikeReason,
+    /// Items saved for `resembles`; these must not be filled in
+    pub credential_uuids: Vec<String>,
+}
+
+/// Result of looking up the credentials for a URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlMatch {
+    /// Registrable domain of the URL, None if it has none (e.g. an IP address)
+    pub domain: Option<String>,
+    pub matches: Vec<CredentialSummary>,
+    /// Only set when nothing matched
+    pub warning: Option<LookalikeWarning>,
+}
+
+/// The host of a URL or of a credential's site field, lowercased and in
+/// punycode. Sites saved without a scheme (`example.com`) are accepted; names
+/// that are not hostnames (`My bank`) give None.
+pub fn host_of(site: &str) -> Option<String> {
+    let site = site.trim();
+    let url = if site.contains("://") {
+        Url::parse(site).ok()?
+    } else {
+        Url::parse(&format!("https://{}", site)).ok()?
+    };
+    url.domain()
+        .filter(|host| host.contains('.'))
+        .map(|host| host.trim_end_matches('.').to_string())
+}
+
+/// The part of `host` a registrant controls, e.g. `example.co.uk` for
+/// `www.example.co.uk`
+pub fn registrable_domain(host: &str) -> String {
+    let labels: Vec<&str> = host.split('.').collect();
+    let keep = match labels.as_slice() {
+        [.., second, tld]
+            if labels.len() >= 3 && tld.len() == 2 && SECOND_LEVEL_LABELS.contains(second) =>
+        {
+            3
+        }
+        _ => 2,
+    };
+    labels[labels.len().saturating_sub(keep)..].join(".")
+}
+
+/// `domain` with look-alike characters replaced by what they imitate, so that
+/// two domains a reader cannot tell apart compare equal
+fn skeleton(domain: &str) -> String {
+    let (unicode, _) = idna::domain_to_unicode(domain);
+    unicode
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            CONFUSABLES
+                .iter()
+                .find(|(confusable, _)| *confusable == c)
+                .map_or(c, |(_, ascii)| *ascii)
+        })
+        .collect::<String>()
+        .replace("rn", "m")
+        .replace("vv", "w")
+}
+
+/// Why `visited` could be mistaken for `stored`, both registrable domains of
+/// different hosts; None if it could not
+fn resemblance(
+    visited_host: &str,
+    visited: &str,
+    stored: &str,
+) -> Option<(LookalikeReason, usize)> {
+    let visited_skeleton = skeleton(visited);
+    let stored_skeleton = skeleton(stored);
+    if visited_skeleton == stored_skeleton {
+        return Some((LookalikeReason::Homograph, 0));
+    }
+
+    let name_length = stored_skeleton
+        .split('.')
+        .next()
+        .unwrap_or("")
+        .chars()
+        .count();
+    if name_length >= MIN_SIMILAR_NAME_LENGTH {
+        let distance = strsim::levenshtein(&visited_skeleton, &stored_skeleton);
+        let allowed = if name_length >= 8 { 2 } else { 1 };
+        if distance <= allowed {
+            return Some((LookalikeReason::SimilarSpelling, distance));
+        }
+    }
+
+    let embedded = format!("{}.", stored);
+    let visited_labels = visited_host.strip_suffix(visited).unwrap_or("");
+    if visited_labels.starts_with(&embedded) || visited_labels.contains(&format!(".{}", embedded)) {
+        return Some((LookalikeReason::EmbeddedDomain, 0));
+    }
+    None
+}
+
+/// The credentials to offer for `url`, or a warning if the URL imitates a
+/// domain credentials are saved for
+pub fn match_url(url: &str, credentials: Vec<Credential>) -> UrlMatch {
+    let Some(host) = host_of(url) else {
+        return UrlMatch {
+            domain: None,
+            matches: Vec::new(),
+            warning: None,
+        };
+    };
+    let domain = registrable_domain(&host);
+
+    let mut matches = Vec::new();
+    let mut others = Vec::new();
+    for credential in credentials {
+        match host_of(&credential.site).map(|site| registrable_domain(&site)) {
+            Some(stored) if stored == domain => matches.push(CredentialSummary::from(credential)),
+            Some(stored) => others.push((stored, credential.uuid)),
+            None => {}
+        }
+    }
+
+    let warning = if matches.is_empty() {
+        // The closest resemblance wins, e.g. a homograph over a misspelling
+        others
+            .iter()
+            .filter_map(|(stored, _)| {
+                resemblance(&host, &domain, stored)
+                    .map(|(reason, distance)| (stored, reason, distance))
+            })
+            .min_by_key(|(_, _, distance)| *distance)
+            .map(|(stored, reason, _)| LookalikeWarning {
+                domain: idna::domain_to_unicode(&domain).0,
+                resembles: stored.clone(),
+                reason,
+                credential_uuids: others
+                    .iter()
+                    .filter(|(other, _)| other == stored)
+                    .map(|(_, uuid)| uuid.clone())
+                    .collect(),
+            })
+    } else {
+        None
+    };
+
+    UrlMatch {
+        domain: Some(domain),
+        matches,
+        warning,
+    }
+}
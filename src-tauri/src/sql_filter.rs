@@ -0,0 +1,217 @@
+//! Building the WHERE clause of a list query from typed conditions.
+//!
+//! `SqlFilter` takes column names only as `&'static str`, so nothing a user
+//! typed can end up in the SQL text. Every value is bound as a numbered
+//! parameter (`?1`, `?2`, ...), never mixed with bare `?`, and text matched
+//! with LIKE has its `%`, `_` and `\` escaped, so a search for `50%` finds
+//! that text instead of treating `%` as a wildcard. Like LIKE itself, `contains`
+//! ignores ASCII case only. JSON arrays are read with SQLite's `json_each`
+//! rather than by matching their text, so an element is never confused with
+//! part of another one or with JSON escaping.
+
+use rusqlite::types::ToSqlOutput;
+use rusqlite::ToSql;
+
+/// Character that escapes LIKE wildcards in patterns built here
+const LIKE_ESCAPE: char = '\\';
+
+/// A bound parameter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlValue {
+    Integer(i64),
+    Text(String),
+}
+
+impl ToSql for SqlValue {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        match self {
+            SqlValue::Integer(value) => value.to_sql(),
+            SqlValue::Text(value) => value.to_sql(),
+        }
+    }
+}
+
+impl From<i64> for SqlValue {
+    fn from(value: i64) -> Self {
+        SqlValue::Integer(value)
+    }
+}
+
+impl From<i32> for SqlValue {
+    fn from(value: i32) -> Self {
+        SqlValue::Integer(i64::from(value))
+    }
+}
+
+impl From<u8> for SqlValue {
+    fn from(value: u8) -> Self {
+        SqlValue::Integer(i64::from(value))
+    }
+}
+
+impl From<bool> for SqlValue {
+    fn from(value: bool) -> Self {
+        SqlValue::Integer(i64::from(value))
+    }
+}
+
+impl From<&str> for SqlValue {
+    fn from(value: &str) -> Self {
+        SqlValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for SqlValue {
+    fn from(value: String) -> Self {
+        SqlValue::Text(value)
+    }
+}
+
+/// How a column is compared with a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn operator(self) -> &'static str {
+        match self {
+            Comparison::Eq => "=",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+        }
+    }
+}
+
+/// Where text is looked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextField {
+    /// A text column
+    Column(&'static str),
+    /// Each element of a column holding a JSON array of strings
+    JsonArray(&'static str),
+}
+
+/// Conditions that must all hold, with their parameters
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SqlFilter {
+    conditions: Vec<String>,
+    params: Vec<SqlValue>,
+}
+
+impl SqlFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds `value` and returns its placeholder
+    fn bind(&mut self, value: SqlValue) -> String {
+        self.params.push(value);
+        format!("?{}", self.params.len())
+    }
+
+    /// `column <comparison> value`
+    pub fn compare(
+        &mut self,
+        column: &'static str,
+        comparison: Comparison,
+        value: impl Into<SqlValue>,
+    ) -> &mut Self {
+        let placeholder = self.bind(value.into());
+        self.conditions.push(format!(
+            "{} {} {}",
+            column,
+            comparison.operator(),
+            placeholder
+        ));
+        self
+    }
+
+    /// `column IS NULL`
+    pub fn is_null(&mut self, column: &'static str) -> &mut Self {
+        self.conditions.push(format!("{} IS NULL", column));
+        self
+    }
+
+    /// Any of `fields` contains `text`
+    pub fn contains(&mut self, fields: &[TextField], text: &str) -> &mut Self {
+        let pattern = format!("%{}%", escape_like(text));
+        self.like_any(fields, pattern)
+    }
+
+    /// The JSON array in `column` has an element equal to `value`. Compared
+    /// as is, so a tag must be given in its stored, normalized form.
+    pub fn json_array_contains(&mut self, column: &'static str, value: &str) -> &mut Self {
+        let placeholder = self.bind(SqlValue::Text(value.to_string()));
+        self.conditions.push(format!(
+            "EXISTS (SELECT 1 FROM json_each({}) WHERE json_each.value = {})",
+            column, placeholder
+        ));
+        self
+    }
+
+    /// Binds `pattern` once and matches it against every field
+    fn like_any(&mut self, fields: &[TextField], pattern: String) -> &mut Self {
+        if fields.is_empty() {
+            // Nowhere to look, so nothing matches
+            self.conditions.push("0".to_string());
+            return self;
+        }
+        let placeholder = self.bind(SqlValue::Text(pattern));
+        let like = |expression: &str| {
+            format!(
+                "{} LIKE {} ESCAPE '{}'",
+                expression, placeholder, LIKE_ESCAPE
+            )
+        };
+        let alternatives: Vec<String> = fields
+            .iter()
+            .map(|field| match field {
+                TextField::Column(column) => like(column),
+                TextField::JsonArray(column) => format!(
+                    "EXISTS (SELECT 1 FROM json_each({}) WHERE {})",
+                    column,
+                    like("json_each.value")
+                ),
+            })
+            .collect();
+        self.conditions
+            .push(format!("({})", alternatives.join(" OR ")));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    /// ` WHERE ...` to append to a query, or nothing without conditions
+    pub fn where_clause(&self) -> String {
+        if self.conditions.is_empty() {
+            return String::new();
+        }
+        format!(" WHERE {}", self.conditions.join(" AND "))
+    }
+
+    /// Parameters in placeholder order
+    pub fn params(&self) -> &[SqlValue] {
+        &self.params
+    }
+}
+
+/// `text` with LIKE wildcards and the escape character escaped
+pub fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | LIKE_ESCAPE) {
+            escaped.push(LIKE_ESCAPE);
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -1,28 +1,267 @@
+use crate::audit_archive::StoredAuditArchive;
+use crate::audit_level::AuditFilter;
+use crate::breach_list::BreachListSource;
+use crate::collections::{Collection, CollectionMember};
+use crate::compact::{self, CompactProgress, CompactReport, CompactStage, ProgressSink};
+use crate::devices::Device;
+use crate::emergency::{EmergencyContact, EmergencyState};
 use crate::error::{AppError, AppResult};
-use crate::models::{AuditLogEntry, BreachState, Credential};
-use crate::traits::{AuditLogger, CredentialRepository, SettingsRepository};
+use crate::i18n::Message;
+use crate::importer::StoredImportConflict;
+use crate::journal::JournalEntry;
+use crate::models::{AuditLogEntry, BreachState, Credential, CredentialKind, StrengthSource};
+use crate::shares::{ShareDestination, ShareRecord};
+use crate::sql_filter::{Comparison, SqlFilter, TextField};
+use crate::stats::SecuritySnapshot;
+use crate::strength::StrengthUpdate;
+use crate::text_match::MatchMode;
+use crate::traits::{
+    AuditArchiveRepository, AuditLogger, BreachListRepository, CollectionRepository,
+    CredentialRepository, DeviceRepository, EmergencyRepository, ExternalChangeSource,
+    ImportConflictRepository, JournalRepository, MaintenanceRepository, RepositoryTransaction,
+    SettingsRepository, ShareRepository, StatsRepository, TransactionBody, TransactionalRepository,
+    TravelRepository,
+};
+use crate::travel::StashedCredential;
+use crate::validation::{normalize_tag, normalize_tags};
 use crate::vault::CredentialFilter; // Keep filter definition accessible
-use chrono::{TimeZone, Utc};
-use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use crate::watch::{ExternalChange, FileIdentity, WatchBaseline};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{
+    params, params_from_iter, Connection, DatabaseName, OptionalExtension, Transaction,
+};
 use serde_json;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
+/// `meta` keys with a dedicated accessor, which `get_meta` and `put_meta` refuse
+const RESERVED_META_KEYS: &[&str] = &[
+    "email_monitor",
+    "master_password_hash",
+    "pending_saves",
+    "settings",
+    "storage_profile",
+    "strength_scale",
+    "strength_version",
+    "tags_normalized",
+    "travel_mode_since",
+    "ui_preferences",
+    "wordlists",
+];
+const MAX_META_KEY_LEN: usize = 64;
+
+/// Columns selected for a `Credential`, in the order `row_to_credential` expects.
+const CREDENTIAL_COLUMNS: &str = "uuid, site, username, secret_enc, tags, created_at, updated_at, expires_at, strength, breach_state, breach_checked_at, password_changed_at, collection_id, high_security, revision, strength_source, entropy_bits, generator_constraints, kind, api_key, card, wifi, has_totp, last_used_at";
+
+/// Maps a row selected with `CREDENTIAL_COLUMNS` to a `Credential`.
+fn row_to_credential(row: &rusqlite::Row) -> rusqlite::Result<Credential> {
+    let created_ts: i64 = row.get(5)?;
+    let updated_ts: i64 = row.get(6)?;
+    let expires_ts: Option<i64> = row.get(7)?;
+    let breach_state_int: i32 = row.get(9)?;
+    let breach_checked_ts: Option<i64> = row.get(10)?;
+    // NULL only for rows a migration could not backfill; fall back to the last edit
+    let password_changed_ts: Option<i64> = row.get(11)?;
+    let tags_json: String = row.get(4)?;
+    let constraints_json: Option<String> = row.get(17)?;
+    let api_key_json: Option<String> = row.get(19)?;
+    let card_json: Option<String> = row.get(20)?;
+    let wifi_json: Option<String> = row.get(21)?;
+
+    // Deserialize tags from JSON string
+    let tags = serde_json::from_str(&tags_json).map_err(|_e| {
+        rusqlite::Error::InvalidColumnType(4, "tags".to_string(), rusqlite::types::Type::Text)
+    })?;
+    let generator_constraints = constraints_json
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|_e| {
+            rusqlite::Error::InvalidColumnType(
+                17,
+                "generator_constraints".to_string(),
+                rusqlite::types::Type::Text,
+            )
+        })?;
+    let api_key = api_key_json
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|_e| {
+            rusqlite::Error::InvalidColumnType(
+                19,
+                "api_key".to_string(),
+                rusqlite::types::Type::Text,
+            )
+        })?;
+    let card = card_json
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|_e| {
+            rusqlite::Error::InvalidColumnType(20, "card".to_string(), rusqlite::types::Type::Text)
+        })?;
+    let wifi = wifi_json
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|_e| {
+            rusqlite::Error::InvalidColumnType(21, "wifi".to_string(), rusqlite::types::Type::Text)
+        })?;
+
+    Ok(Credential {
+        uuid: row.get(0)?,
+        site: row.get(1)?,
+        username: row.get(2)?,
+        secret_enc: row.get(3)?,
+        tags,
+        created_at: Utc.timestamp_opt(created_ts, 0).single().ok_or(
+            rusqlite::Error::InvalidColumnType(
+                5,
+                "created_at".to_string(),
+                rusqlite::types::Type::Integer,
+            ),
+        )?,
+        updated_at: Utc.timestamp_opt(updated_ts, 0).single().ok_or(
+            rusqlite::Error::InvalidColumnType(
+                6,
+                "updated_at".to_string(),
+                rusqlite::types::Type::Integer,
+            ),
+        )?,
+        expires_at: expires_ts.and_then(|ts| Utc.timestamp_opt(ts, 0).single()),
+        strength: row.get(8)?,
+        breach_state: match breach_state_int {
+            1 => BreachState::Safe,
+            2 => BreachState::Compromised,
+            _ => BreachState::Unknown,
+        },
+        breach_checked_at: breach_checked_ts.and_then(|ts| Utc.timestamp_opt(ts, 0).single()),
+        password_changed_at: Utc
+            .timestamp_opt(password_changed_ts.unwrap_or(updated_ts), 0)
+            .single()
+            .ok_or(rusqlite::Error::InvalidColumnType(
+                11,
+                "password_changed_at".to_string(),
+                rusqlite::types::Type::Integer,
+            ))?,
+        collection_id: row.get(12)?,
+        high_security: row.get(13)?,
+        revision: row.get::<_, i64>(14)? as u64,
+        strength_source: match row.get::<_, i32>(15)? {
+            1 => StrengthSource::Generated,
+            _ => StrengthSource::Analyzed,
+        },
+        entropy_bits: row.get(16)?,
+        generator_constraints,
+        kind: match row.get::<_, i32>(18)? {
+            1 => CredentialKind::ApiKey,
+            2 => CredentialKind::CreditCard,
+            3 => CredentialKind::Identity,
+            4 => CredentialKind::Wifi,
+            _ => CredentialKind::Login,
+        },
+        api_key,
+        card,
+        wifi,
+        has_totp: row.get(22)?,
+        last_used_at: row
+            .get::<_, Option<i64>>(23)?
+            .and_then(|ts| Utc.timestamp_opt(ts, 0).single()),
+    })
+}
+
+/// `generator_constraints` as stored in its column
+fn constraints_json(credential: &Credential) -> AppResult<Option<String>> {
+    Ok(credential
+        .generator_constraints
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()?)
+}
+
+/// `api_key` as stored in its column
+fn api_key_json(credential: &Credential) -> AppResult<Option<String>> {
+    Ok(credential
+        .api_key
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()?)
+}
+
+/// `card` as stored in its column
+fn card_json(credential: &Credential) -> AppResult<Option<String>> {
+    Ok(credential
+        .card
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()?)
+}
+
+/// `wifi` as stored in its column
+fn wifi_json(credential: &Credential) -> AppResult<Option<String>> {
+    Ok(credential
+        .wifi
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()?)
+}
+
 /// Concrete implementation for database operations using SQLite.
 pub struct SqliteRepository {
     conn: Mutex<Connection>,
+    // State of the database file as of the last external change check
+    watch_baseline: Mutex<Option<WatchBaseline>>,
+    // Audit level for the entries written with item changes
+    audit_filter: AuditFilter,
 }
 
+/// Recorded in `PRAGMA user_version` once `migrate_schema` has run; bump it
+/// whenever a migration step is added
+pub const SCHEMA_VERSION: u32 = 9;
+
 impl SqliteRepository {
     /// Creates a new repository and initializes the schema if needed.
     pub fn new(db_path: &Path) -> AppResult<Self> {
-        let conn = Connection::open(db_path)?;
+        let conn = Self::open_connection(db_path)?;
         Self::init_schema(&conn)?;
+        let watch_baseline = Self::watch_state(&conn)?;
         Ok(Self {
             conn: Mutex::new(conn),
+            watch_baseline: Mutex::new(watch_baseline),
+            audit_filter: AuditFilter::default(),
         })
     }
 
+    /// Handle to the audit level this repository applies; hand it to
+    /// `VaultManager::with_audit_filter` so the vault's setting takes effect here
+    pub fn audit_filter(&self) -> AuditFilter {
+        self.audit_filter.clone()
+    }
+
+    /// File identity and data version of the database, or None for in-memory databases
+    fn watch_state(conn: &Connection) -> AppResult<Option<WatchBaseline>> {
+        let Some(path) = conn.path().filter(|path| !path.is_empty()) else {
+            return Ok(None);
+        };
+        Ok(Some(WatchBaseline {
+            identity: FileIdentity::of(Path::new(path))?,
+            data_version: conn.pragma_query_value(None, "data_version", |row| row.get(0))?,
+        }))
+    }
+
+    /// Opens the database with the connection settings every handle needs.
+    fn open_connection(db_path: &Path) -> AppResult<Connection> {
+        let conn = Connection::open(db_path)?;
+        // Zero deleted content instead of leaving it in free pages
+        conn.pragma_update(None, "secure_delete", true)?;
+        Ok(conn)
+    }
+
+    /// Database size in bytes and number of free pages
+    fn page_stats(conn: &Connection) -> AppResult<(u64, u64)> {
+        let page_size: u64 = conn.pragma_query_value(None, "page_size", |row| row.get(0))?;
+        let page_count: u64 = conn.pragma_query_value(None, "page_count", |row| row.get(0))?;
+        let free_pages: u64 = conn.pragma_query_value(None, "freelist_count", |row| row.get(0))?;
+        Ok((page_size * page_count, free_pages))
+    }
+
     /// Initializes the database schema.
     fn init_schema(conn: &Connection) -> AppResult<()> {
         conn.execute_batch(
@@ -42,7 +281,21 @@ impl SqliteRepository {
                 updated_at INTEGER NOT NULL,
                 expires_at INTEGER,
                 strength INTEGER NOT NULL DEFAULT 0,
-                breach_state INTEGER NOT NULL DEFAULT 0
+                breach_state INTEGER NOT NULL DEFAULT 0,
+                breach_checked_at INTEGER,
+                password_changed_at INTEGER,
+                collection_id TEXT,
+                high_security INTEGER NOT NULL DEFAULT 0,
+                revision INTEGER NOT NULL DEFAULT 0,
+                strength_source INTEGER NOT NULL DEFAULT 0,
+                entropy_bits REAL,
+                generator_constraints TEXT, -- JSON, NULL when the site has no known rules
+                kind INTEGER NOT NULL DEFAULT 0,
+                api_key TEXT, -- JSON, set for ApiKey items
+                card TEXT, -- JSON, set for CreditCard items
+                wifi TEXT, -- JSON, set for Wifi items
+                has_totp INTEGER NOT NULL DEFAULT 0,
+                last_used_at INTEGER
             );
             CREATE TABLE IF NOT EXISTS audit_log (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -50,15 +303,239 @@ impl SqliteRepository {
                 action TEXT NOT NULL,
                 item_uuid TEXT
             );
+            CREATE TABLE IF NOT EXISTS stats_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                taken_at INTEGER NOT NULL,
+                total INTEGER NOT NULL,
+                weak INTEGER NOT NULL,
+                compromised INTEGER NOT NULL,
+                reused INTEGER NOT NULL,
+                strength_histogram TEXT NOT NULL -- JSON array indexed by score
+            );
+            CREATE TABLE IF NOT EXISTS collections (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS collection_members (
+                collection_id TEXT NOT NULL,
+                public_key TEXT NOT NULL,
+                label TEXT NOT NULL,
+                added_at INTEGER NOT NULL,
+                wrapped_key TEXT NOT NULL, -- JSON WrappedKey
+                PRIMARY KEY (collection_id, public_key)
+            );
+            CREATE TABLE IF NOT EXISTS shares (
+                id TEXT PRIMARY KEY,
+                item_uuid TEXT NOT NULL,
+                site TEXT NOT NULL,
+                destination TEXT NOT NULL, -- 'relay' or 'file'
+                location TEXT NOT NULL,
+                revoke_token TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                expires_at INTEGER NOT NULL,
+                max_views INTEGER NOT NULL,
+                revoked_at INTEGER
+            );
+            CREATE TABLE IF NOT EXISTS emergency_contacts (
+                id TEXT PRIMARY KEY,
+                label TEXT NOT NULL,
+                public_key TEXT NOT NULL,
+                wait_days INTEGER NOT NULL,
+                state TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                requested_at INTEGER,
+                state_changed_at INTEGER NOT NULL,
+                wrapped_key TEXT -- JSON WrappedKey, NULL once revoked
+            );
+            CREATE TABLE IF NOT EXISTS devices (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                platform TEXT NOT NULL,
+                public_key TEXT NOT NULL,
+                first_seen INTEGER NOT NULL,
+                last_seen INTEGER NOT NULL,
+                revoked_at INTEGER
+            );
+            CREATE TABLE IF NOT EXISTS travel_stash (
+                uuid TEXT PRIMARY KEY,
+                ciphertext TEXT NOT NULL -- Credential JSON under the travel key
+            );
+            CREATE TABLE IF NOT EXISTS operations_journal (
+                id TEXT PRIMARY KEY,
+                operation TEXT NOT NULL, -- JSON PendingOperation
+                started_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS breach_list (
+                fingerprint TEXT NOT NULL, -- HMAC of the entry's SHA-1
+                source TEXT NOT NULL,
+                imported_at INTEGER NOT NULL,
+                PRIMARY KEY (fingerprint, source)
+            );
+            CREATE TABLE IF NOT EXISTS import_conflicts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                existing_uuid TEXT NOT NULL,
+                item_enc TEXT NOT NULL, -- encrypted MappedItem JSON
+                created_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS audit_archives (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                first_at INTEGER NOT NULL,
+                last_at INTEGER NOT NULL,
+                entries INTEGER NOT NULL,
+                data_enc TEXT NOT NULL -- encrypted, deflated AuditLogEntry JSON
+            );
             CREATE INDEX IF NOT EXISTS idx_vault_site ON vault_items(site);
             CREATE INDEX IF NOT EXISTS idx_vault_username ON vault_items(username);
             CREATE INDEX IF NOT EXISTS idx_vault_tags ON vault_items(tags);
             CREATE INDEX IF NOT EXISTS idx_audit_timestamp ON audit_log(timestamp);
+            CREATE INDEX IF NOT EXISTS idx_stats_taken_at ON stats_history(taken_at);
+            COMMIT;",
+        )?;
+        Self::migrate_schema(conn)?;
+        Ok(())
+    }
+
+    /// Brings databases created by older versions up to the current schema.
+    fn migrate_schema(conn: &Connection) -> AppResult<()> {
+        Self::ensure_column(conn, "vault_items", "breach_checked_at", "INTEGER")?;
+        Self::ensure_column(conn, "vault_items", "collection_id", "TEXT")?;
+        Self::ensure_column(
+            conn,
+            "vault_items",
+            "high_security",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        Self::ensure_column(
+            conn,
+            "vault_items",
+            "revision",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        Self::ensure_column(
+            conn,
+            "vault_items",
+            "strength_source",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        Self::ensure_column(conn, "vault_items", "entropy_bits", "REAL")?;
+        Self::ensure_column(conn, "vault_items", "generator_constraints", "TEXT")?;
+        Self::ensure_column(conn, "vault_items", "kind", "INTEGER NOT NULL DEFAULT 0")?;
+        Self::ensure_column(conn, "vault_items", "api_key", "TEXT")?;
+        Self::ensure_column(conn, "vault_items", "card", "TEXT")?;
+        Self::ensure_column(conn, "vault_items", "wifi", "TEXT")?;
+        // Filled in from the secrets on the next unlock, see `VaultManager::backfill_totp_flags`
+        Self::ensure_column(
+            conn,
+            "vault_items",
+            "has_totp",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        Self::ensure_column(conn, "vault_items", "last_used_at", "INTEGER")?;
+        if Self::ensure_column(conn, "vault_items", "password_changed_at", "INTEGER")? {
+            // The best available estimate for existing rows is their last edit
+            conn.execute(
+                "UPDATE vault_items SET password_changed_at = updated_at WHERE password_changed_at IS NULL",
+                [],
+            )?;
+        }
+        Self::migrate_strength_scale(conn)?;
+        Self::migrate_tag_normalization(conn)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(())
+    }
+
+    /// Strengths used to be the bare zxcvbn score (0-4). The passwords are encrypted,
+    /// so they cannot be rescored here; each old score becomes the middle of its
+    /// bucket on the 0-100 scale. Runs once, recorded in the meta table.
+    fn migrate_strength_scale(conn: &Connection) -> AppResult<()> {
+        let migrated = conn
+            .query_row(
+                "SELECT 1 FROM meta WHERE key = 'strength_scale'",
+                [],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if migrated {
+            return Ok(());
+        }
+        conn.execute_batch(
+            "BEGIN;
+            UPDATE vault_items SET strength = MIN(strength, 4) * 20 + 10;
+            INSERT INTO meta (key, value) VALUES ('strength_scale', '100');
             COMMIT;",
         )?;
         Ok(())
     }
 
+    /// Tags are stored normalized (see `validation::normalize_tag`), which the
+    /// exact tag filter relies on. Items written before that may still have tags
+    /// in other spellings; they are rewritten once, recorded in the meta table.
+    /// Not an edit, so the revision stays.
+    fn migrate_tag_normalization(conn: &Connection) -> AppResult<()> {
+        let migrated = conn
+            .query_row(
+                "SELECT 1 FROM meta WHERE key = 'tags_normalized'",
+                [],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if migrated {
+            return Ok(());
+        }
+        let tx = conn.unchecked_transaction()?;
+        let rows = {
+            let mut stmt = tx.prepare("SELECT uuid, tags FROM vault_items WHERE tags != '[]'")?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows
+        };
+        for (uuid, json) in rows {
+            let tags: Vec<String> = serde_json::from_str(&json)?;
+            let normalized = normalize_tags(&tags);
+            if normalized != tags {
+                tx.execute(
+                    "UPDATE vault_items SET tags = ? WHERE uuid = ?",
+                    params![serde_json::to_string(&normalized)?, uuid],
+                )?;
+            }
+        }
+        tx.execute(
+            "INSERT INTO meta (key, value) VALUES ('tags_normalized', '1')",
+            [],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Adds a column to an existing table if it is missing.
+    /// Returns whether the column was added.
+    fn ensure_column(
+        conn: &Connection,
+        table: &str,
+        column: &str,
+        definition: &str,
+    ) -> AppResult<bool> {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<_>, _>>()?
+            .iter()
+            .any(|name| name == column);
+        if !exists {
+            conn.execute_batch(&format!(
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table, column, definition
+            ))?;
+        }
+        Ok(!exists)
+    }
+
     /// Helper to add an audit log entry within a transaction.
     fn add_audit_log_tx(
         &self,
@@ -73,58 +550,224 @@ impl SqliteRepository {
         )?;
         Ok(tx.last_insert_rowid())
     }
+
+    /// Records an item change within its transaction, if the audit level asks for it
+    fn log_item_change_tx(
+        &self,
+        tx: &Transaction,
+        message: &Message,
+        item_uuid: &str,
+    ) -> AppResult<()> {
+        if self.audit_filter.records(&message.key) {
+            self.add_audit_log_tx(tx, &message.audit_text(), Some(item_uuid))?;
+        }
+        Ok(())
+    }
 }
 
 impl CredentialRepository for SqliteRepository {
     fn add_credential(&self, credential: &Credential, strength: u8) -> AppResult<()> {
+        self.with_transaction(&mut |txn: &dyn RepositoryTransaction| {
+            txn.add_credential(credential, strength)
+        })
+    }
+
+    fn update_credential(&self, credential: &Credential) -> AppResult<()> {
+        self.with_transaction(&mut |txn: &dyn RepositoryTransaction| {
+            txn.update_credential(credential)
+        })
+    }
+
+    fn delete_credential(&self, uuid: &str) -> AppResult<String> {
+        let mut site = String::new();
+        self.with_transaction(&mut |txn: &dyn RepositoryTransaction| {
+            site = txn.delete_credential(uuid)?;
+            Ok(())
+        })?;
+        Ok(site) // Return site name for audit log message construction elsewhere
+    }
+
+    fn get_credential(&self, uuid: &str) -> AppResult<Credential> {
+        let conn = self.conn.lock().unwrap();
+        Self::get_credential_tx(&conn, uuid)
+    }
+
+    fn list_credentials(&self, filter: Option<CredentialFilter>) -> AppResult<Vec<Credential>> {
+        let conn = self.conn.lock().unwrap();
+        let mut sql = SqlFilter::new();
+        // Fuzzy and regex terms have no SQL form; they are matched below
+        let mut term_matcher = None;
+
+        if let Some(f) = filter {
+            if f.match_mode != MatchMode::Substring {
+                term_matcher = f.term_matcher()?;
+            } else if let Some(term) = &f.search_term {
+                sql.contains(
+                    &[
+                        TextField::Column("site"),
+                        TextField::Column("username"),
+                        TextField::JsonArray("tags"),
+                    ],
+                    term,
+                );
+            }
+            if let Some(tag) = &f.tag {
+                sql.json_array_contains("tags", &normalize_tag(tag));
+            }
+            if let Some(strength) = f.min_strength {
+                sql.compare("strength", Comparison::Ge, strength);
+            }
+            if let Some(strength) = f.max_strength {
+                sql.compare("strength", Comparison::Le, strength);
+            }
+            if let Some(site) = &f.site {
+                sql.contains(&[TextField::Column("site")], site);
+            }
+            if let Some(after) = f.updated_after {
+                sql.compare("updated_at", Comparison::Ge, after.timestamp());
+            }
+            if let Some(before) = f.updated_before {
+                sql.compare("updated_at", Comparison::Lt, before.timestamp());
+            }
+            let now = Utc::now();
+            let days_ago = |days: u32| (now - chrono::Duration::days(i64::from(days))).timestamp();
+            if let Some(days) = f.smart.modified_within_days {
+                sql.compare("updated_at", Comparison::Ge, days_ago(days));
+            }
+            if let Some(days) = f.smart.created_within_days {
+                sql.compare("created_at", Comparison::Ge, days_ago(days));
+            }
+            if f.smart.never_used {
+                sql.is_null("last_used_at");
+            }
+            if f.smart.no_totp {
+                sql.compare("has_totp", Comparison::Eq, false);
+            }
+            if f.smart.no_tags {
+                sql.compare("tags", Comparison::Eq, "[]");
+            }
+            if let Some(state) = f.breach_state {
+                sql.compare("breach_state", Comparison::Eq, state as i32);
+            }
+            if let Some(days) = f.password_older_than_days {
+                sql.compare("password_changed_at", Comparison::Le, days_ago(days));
+            }
+        }
+
+        let query = format!(
+            "SELECT {} FROM vault_items{} ORDER BY site, username",
+            CREDENTIAL_COLUMNS,
+            sql.where_clause()
+        );
+        let mut stmt = conn.prepare(&query)?;
+        let rows = stmt.query_map(params_from_iter(sql.params()), row_to_credential)?;
+
+        let mut credentials = Vec::new();
+        for row_result in rows {
+            credentials.push(row_result?);
+        }
+        if let Some(term) = term_matcher {
+            credentials.retain(|credential| {
+                term.matches_fields(&credential.site, &credential.username, &credential.tags)
+            });
+        }
+
+        Ok(credentials)
+    }
+
+    fn update_breach_state(&self, uuid: &str, state: BreachState) -> AppResult<()> {
+        self.with_transaction(&mut |txn: &dyn RepositoryTransaction| {
+            txn.update_breach_state(uuid, state)
+        })
+    }
+
+    fn update_strengths(&self, updates: &[StrengthUpdate]) -> AppResult<usize> {
         let mut conn = self.conn.lock().unwrap();
         let tx = conn.transaction()?;
+        let mut written = 0;
+        {
+            let mut stmt = tx.prepare(
+                "UPDATE vault_items SET strength = ?, revision = revision + 1 WHERE uuid = ? AND revision = ?",
+            )?;
+            for update in updates {
+                written += stmt.execute(params![update.strength, update.uuid, update.revision])?;
+            }
+        }
+        tx.commit()?;
+        Ok(written)
+    }
 
-        // Serialize tags to JSON string
-        let tags_json =
-            serde_json::to_string(&credential.tags).map_err(|e| AppError::Serialization(e))?;
+    fn credential_exists(&self, uuid: &str) -> AppResult<bool> {
+        let conn = self.conn.lock().unwrap();
+        Self::credential_exists_tx(&conn, uuid)
+    }
 
-        tx.execute(
-            "INSERT INTO vault_items (
-                uuid, site, username, secret_enc, tags, created_at, updated_at, expires_at, strength, breach_state
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-            params![
-                credential.uuid,
-                credential.site,
-                credential.username,
-                credential.secret_enc,
-                tags_json,
-                credential.created_at.timestamp(),
-                credential.updated_at.timestamp(),
-                credential.expires_at.map(|dt| dt.timestamp()),
-                strength,
-                credential.breach_state as i32,
-            ],
+    fn mark_used(&self, uuid: &str, at: DateTime<Utc>) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        let rows_affected = conn.execute(
+            "UPDATE vault_items SET last_used_at = ? WHERE uuid = ?",
+            params![at.timestamp(), uuid],
         )?;
+        if rows_affected == 0 {
+            return Err(AppError::NotFound(uuid.to_string()));
+        }
+        Ok(())
+    }
 
-        self.add_audit_log_tx(
-            &tx,
-            &format!("Added credential for {}", credential.site),
-            Some(&credential.uuid),
+    fn set_has_totp(&self, uuid: &str, has_totp: bool) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        let rows_affected = conn.execute(
+            "UPDATE vault_items SET has_totp = ? WHERE uuid = ?",
+            params![has_totp, uuid],
         )?;
-
-        tx.commit()?;
+        if rows_affected == 0 {
+            return Err(AppError::NotFound(uuid.to_string()));
+        }
         Ok(())
     }
+}
 
-    fn update_credential(&self, credential: &Credential) -> AppResult<()> {
+impl TransactionalRepository for SqliteRepository {
+    fn with_transaction(&self, body: &mut dyn TransactionBody) -> AppResult<()> {
         let mut conn = self.conn.lock().unwrap();
         let tx = conn.transaction()?;
+        // Dropping `tx` without committing rolls everything back
+        body.run(&SqliteTransaction {
+            repo: self,
+            tx: &tx,
+        })?;
+        tx.commit()?;
+        Ok(())
+    }
+}
 
-        if !self.credential_exists_tx(&tx, &credential.uuid)? {
-            return Err(AppError::NotFound(credential.uuid.clone()));
-        }
+/// Repository calls running inside an open transaction.
+/// Holds the connection lock, so the body must not call back into `SqliteRepository`.
+struct SqliteTransaction<'a> {
+    repo: &'a SqliteRepository,
+    tx: &'a Transaction<'a>,
+}
+
+impl RepositoryTransaction for SqliteTransaction<'_> {
+    fn add_credential(&self, credential: &Credential, strength: u8) -> AppResult<()> {
+        self.repo
+            .insert_credential_tx(self.tx, credential, strength)?;
+
+        self.repo.log_item_change_tx(
+            self.tx,
+            &Message::new("audit.credential_added").with_param("site", &credential.site),
+            &credential.uuid,
+        )?;
+        Ok(())
+    }
 
+    fn update_credential(&self, credential: &Credential) -> AppResult<()> {
+        let tx = self.tx;
         let updated_at = Utc::now();
-        tx.execute(
+        let rows_affected = tx.execute(
             "UPDATE vault_items SET 
-                site = ?, username = ?, secret_enc = ?, tags = ?, updated_at = ?, expires_at = ?, strength = ?, breach_state = ? 
-             WHERE uuid = ?",
+                site = ?, username = ?, secret_enc = ?, tags = ?, updated_at = ?, expires_at = ?, strength = ?, breach_state = ?, breach_checked_at = ?, password_changed_at = ?, collection_id = ?, high_security = ?, strength_source = ?, entropy_bits = ?, generator_constraints = ?, kind = ?, api_key = ?, card = ?, wifi = ?, has_totp = ?, revision = revision + 1
+             WHERE uuid = ? AND revision = ?",
             params![
                 credential.site,
                 credential.username,
@@ -134,24 +777,44 @@ impl CredentialRepository for SqliteRepository {
                 credential.expires_at.map(|dt| dt.timestamp()),
                 credential.strength, // Assuming strength is recalculated and passed in Credential
                 credential.breach_state as i32,
+                credential.breach_checked_at.map(|dt| dt.timestamp()),
+                credential.password_changed_at.timestamp(),
+                credential.collection_id,
+                credential.high_security,
+                credential.strength_source as i32,
+                credential.entropy_bits,
+                constraints_json(credential)?,
+                credential.kind as i32,
+                api_key_json(credential)?,
+                card_json(credential)?,
+                wifi_json(credential)?,
+                credential.has_totp,
                 credential.uuid,
+                credential.revision as i64,
             ],
         )?;
 
-        self.add_audit_log_tx(
-            &tx,
-            &format!("Updated credential for {}", credential.site),
-            Some(&credential.uuid),
-        )?;
+        if rows_affected == 0 {
+            // Either the item is gone or someone else wrote it since it was read
+            if !SqliteRepository::credential_exists_tx(tx, &credential.uuid)? {
+                return Err(AppError::NotFound(credential.uuid.clone()));
+            }
+            return Err(AppError::Conflict(format!(
+                "{} was changed since revision {}",
+                credential.uuid, credential.revision
+            )));
+        }
 
-        tx.commit()?;
+        self.repo.log_item_change_tx(
+            tx,
+            &Message::new("audit.credential_updated").with_param("site", &credential.site),
+            &credential.uuid,
+        )?;
         Ok(())
     }
 
     fn delete_credential(&self, uuid: &str) -> AppResult<String> {
-        let mut conn = self.conn.lock().unwrap();
-        let tx = conn.transaction()?;
-
+        let tx = self.tx;
         let site: String = tx
             .query_row(
                 "SELECT site FROM vault_items WHERE uuid = ?",
@@ -165,186 +828,121 @@ impl CredentialRepository for SqliteRepository {
 
         tx.execute("DELETE FROM vault_items WHERE uuid = ?", params![uuid])?;
 
-        self.add_audit_log_tx(&tx, &format!("Deleted credential for {}", site), Some(uuid))?;
-
-        tx.commit()?;
-        Ok(site) // Return site name for audit log message construction elsewhere
+        self.repo.log_item_change_tx(
+            tx,
+            &Message::new("audit.credential_deleted").with_param("site", &site),
+            uuid,
+        )?;
+        Ok(site)
     }
 
     fn get_credential(&self, uuid: &str) -> AppResult<Credential> {
-        let conn = self.conn.lock().unwrap();
-        conn.query_row(
-            "SELECT uuid, site, username, secret_enc, tags, created_at, updated_at, expires_at, strength, breach_state FROM vault_items WHERE uuid = ?",
-            params![uuid],
-            |row| {
-                let created_ts: i64 = row.get(5)?;
-                let updated_ts: i64 = row.get(6)?;
-                let expires_ts: Option<i64> = row.get(7)?;
-                let breach_state_int: i32 = row.get(9)?;
-                let tags_json: String = row.get(4)?;
-
-                // Deserialize tags from JSON string
-                let tags = serde_json::from_str(&tags_json)
-                    .map_err(|_e| rusqlite::Error::InvalidColumnType(4, "tags".to_string(), rusqlite::types::Type::Text))?;
-
-                Ok(Credential {
-                    uuid: row.get(0)?,
-                    site: row.get(1)?,
-                    username: row.get(2)?,
-                    secret_enc: row.get(3)?,
-                    tags,
-                    created_at: Utc.timestamp_opt(created_ts, 0).single().ok_or(rusqlite::Error::InvalidColumnType(5, "created_at".to_string(), rusqlite::types::Type::Integer))?,
-                    updated_at: Utc.timestamp_opt(updated_ts, 0).single().ok_or(rusqlite::Error::InvalidColumnType(6, "updated_at".to_string(), rusqlite::types::Type::Integer))?,
-                    expires_at: expires_ts.and_then(|ts| Utc.timestamp_opt(ts, 0).single()),
-                    strength: row.get(8)?,
-                    breach_state: match breach_state_int {
-                        1 => BreachState::Safe,
-                        2 => BreachState::Compromised,
-                        _ => BreachState::Unknown,
-                    },
-                })
-            },
-        ).map_err(|e| match e {
-            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(uuid.to_string()),
-            _ => AppError::Database(e),
-        })
+        SqliteRepository::get_credential_tx(self.tx, uuid)
     }
 
-    fn list_credentials(&self, filter: Option<CredentialFilter>) -> AppResult<Vec<Credential>> {
-        let conn = self.conn.lock().unwrap();
-        let mut query = String::from(
-            "SELECT uuid, site, username, secret_enc, tags, created_at, updated_at, expires_at, strength, breach_state FROM vault_items",
-        );
-        let mut conditions = Vec::new();
-        let mut params_dyn: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+    fn credential_exists(&self, uuid: &str) -> AppResult<bool> {
+        SqliteRepository::credential_exists_tx(self.tx, uuid)
+    }
 
-        if let Some(f) = filter {
-            if let Some(term) = f.search_term {
-                conditions.push("(site LIKE ?1 OR username LIKE ?1 OR tags LIKE ?1)".to_string());
-                params_dyn.push(Box::new(format!("%{}%", term)));
-            }
-            if let Some(tag) = f.tag {
-                // Use JSON_ARRAY_LENGTH to ensure it's an array first, then check if it contains tag
-                // For SQLite 3.38.0+ you could use JSON_CONTAINS, but we use LIKE for compatibility
-                conditions.push("(JSON_ARRAY_LENGTH(tags) > 0 AND tags LIKE ?)".to_string());
-                params_dyn.push(Box::new(format!("%\"{}\"%", tag)));
-            }
-            if let Some(strength) = f.min_strength {
-                conditions.push("strength >= ?".to_string());
-                params_dyn.push(Box::new(strength));
-            }
-            if let Some(state) = f.breach_state {
-                conditions.push("breach_state = ?".to_string());
-                params_dyn.push(Box::new(state as i32));
-            }
-        }
+    fn update_breach_state(&self, uuid: &str, state: BreachState) -> AppResult<()> {
+        let tx = self.tx;
+        // Resetting to Unknown also clears the check time
+        let checked_at = match state {
+            BreachState::Unknown => None,
+            _ => Some(Utc::now().timestamp()),
+        };
+        let rows_affected = tx.execute(
+            "UPDATE vault_items SET breach_state = ?, breach_checked_at = ?, revision = revision + 1 WHERE uuid = ?",
+            params![state as i32, checked_at, uuid],
+        )?;
 
-        if !conditions.is_empty() {
-            query.push_str(" WHERE ");
-            query.push_str(&conditions.join(" AND "));
+        if rows_affected == 0 {
+            return Err(AppError::NotFound(uuid.to_string()));
         }
-        query.push_str(" ORDER BY site, username");
 
-        let mut stmt = conn.prepare(&query)?;
-        let params_ref: Vec<&dyn rusqlite::ToSql> = params_dyn.iter().map(|b| b.as_ref()).collect();
+        let action = match state {
+            BreachState::Safe => "audit.marked_safe",
+            BreachState::Compromised => "audit.marked_compromised",
+            BreachState::Unknown => "audit.breach_reset",
+        };
+        self.repo
+            .log_item_change_tx(tx, &Message::new(action), uuid)?;
+        Ok(())
+    }
 
-        let rows = stmt.query_map(params_ref.as_slice(), |row| {
-            let created_ts: i64 = row.get(5)?;
-            let updated_ts: i64 = row.get(6)?;
-            let expires_ts: Option<i64> = row.get(7)?;
-            let breach_state_int: i32 = row.get(9)?;
-            let tags_json: String = row.get(4)?;
+    fn add_log(&self, action: &str, item_uuid: Option<&str>) -> AppResult<i64> {
+        self.repo.add_audit_log_tx(self.tx, action, item_uuid)
+    }
+}
 
-            // Deserialize tags from JSON string
-            let tags = serde_json::from_str(&tags_json).map_err(|_e| {
-                rusqlite::Error::InvalidColumnType(
-                    4,
-                    "tags".to_string(),
-                    rusqlite::types::Type::Text,
-                )
-            })?;
+// Separate helper for transaction context
+impl SqliteRepository {
+    fn insert_credential_tx(
+        &self,
+        tx: &Transaction,
+        credential: &Credential,
+        strength: u8,
+    ) -> AppResult<()> {
+        // Serialize tags to JSON string
+        let tags_json = serde_json::to_string(&credential.tags).map_err(AppError::Serialization)?;
 
-            Ok(Credential {
-                uuid: row.get(0)?,
-                site: row.get(1)?,
-                username: row.get(2)?,
-                secret_enc: row.get(3)?,
-                tags,
-                created_at: Utc.timestamp_opt(created_ts, 0).single().ok_or(
-                    rusqlite::Error::InvalidColumnType(
-                        5,
-                        "created_at".to_string(),
-                        rusqlite::types::Type::Integer,
-                    ),
-                )?,
-                updated_at: Utc.timestamp_opt(updated_ts, 0).single().ok_or(
-                    rusqlite::Error::InvalidColumnType(
-                        6,
-                        "updated_at".to_string(),
-                        rusqlite::types::Type::Integer,
-                    ),
-                )?,
-                expires_at: expires_ts.and_then(|ts| Utc.timestamp_opt(ts, 0).single()),
-                strength: row.get(8)?,
-                breach_state: match breach_state_int {
-                    1 => BreachState::Safe,
-                    2 => BreachState::Compromised,
-                    _ => BreachState::Unknown,
-                },
-            })
-        })?;
-
-        let mut credentials = Vec::new();
-        for row_result in rows {
-            credentials.push(row_result?);
-        }
-
-        Ok(credentials)
-    }
-
-    fn update_breach_state(&self, uuid: &str, state: BreachState) -> AppResult<()> {
-        let mut conn = self.conn.lock().unwrap();
-        let tx = conn.transaction()?;
-
-        let rows_affected = tx.execute(
-            "UPDATE vault_items SET breach_state = ? WHERE uuid = ?",
-            params![state as i32, uuid],
+        tx.execute(
+            "INSERT INTO vault_items (
+                uuid, site, username, secret_enc, tags, created_at, updated_at, expires_at, strength, breach_state, breach_checked_at, password_changed_at, collection_id, high_security, revision, strength_source, entropy_bits, generator_constraints, kind, api_key, card, wifi, has_totp, last_used_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                credential.uuid,
+                credential.site,
+                credential.username,
+                credential.secret_enc,
+                tags_json,
+                credential.created_at.timestamp(),
+                credential.updated_at.timestamp(),
+                credential.expires_at.map(|dt| dt.timestamp()),
+                strength,
+                credential.breach_state as i32,
+                credential.breach_checked_at.map(|dt| dt.timestamp()),
+                credential.password_changed_at.timestamp(),
+                credential.collection_id,
+                credential.high_security,
+                credential.revision as i64,
+                credential.strength_source as i32,
+                credential.entropy_bits,
+                constraints_json(credential)?,
+                credential.kind as i32,
+                api_key_json(credential)?,
+                card_json(credential)?,
+                wifi_json(credential)?,
+                credential.has_totp,
+                credential.last_used_at.map(|dt| dt.timestamp()),
+            ],
         )?;
-
-        if rows_affected == 0 {
-            return Err(AppError::NotFound(uuid.to_string()));
-        }
-
-        let action = match state {
-            BreachState::Safe => "Marked credential as safe",
-            BreachState::Compromised => "Marked credential as compromised",
-            BreachState::Unknown => "Reset credential breach state to unknown",
-        };
-        self.add_audit_log_tx(&tx, action, Some(uuid))?;
-
-        tx.commit()?;
         Ok(())
     }
 
-    fn credential_exists(&self, uuid: &str) -> AppResult<bool> {
-        let mut conn = self.conn.lock().unwrap();
-        let tx = conn.transaction()?;
-        let exists = self.credential_exists_tx(&tx, uuid)?;
-        tx.commit()?;
-        Ok(exists)
-    }
-}
-
-// Separate helper for transaction context
-impl SqliteRepository {
-    fn credential_exists_tx(&self, tx: &Transaction, uuid: &str) -> AppResult<bool> {
-        let count: i64 = tx.query_row(
+    fn credential_exists_tx(conn: &Connection, uuid: &str) -> AppResult<bool> {
+        let count: i64 = conn.query_row(
             "SELECT COUNT(*) FROM vault_items WHERE uuid = ?",
             params![uuid],
             |row| row.get(0),
         )?;
         Ok(count > 0)
     }
+
+    fn get_credential_tx(conn: &Connection, uuid: &str) -> AppResult<Credential> {
+        conn.query_row(
+            &format!(
+                "SELECT {} FROM vault_items WHERE uuid = ?",
+                CREDENTIAL_COLUMNS
+            ),
+            params![uuid],
+            row_to_credential,
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(uuid.to_string()),
+            _ => AppError::Database(e),
+        })
+    }
 }
 
 impl SettingsRepository for SqliteRepository {
@@ -391,6 +989,192 @@ impl SettingsRepository for SqliteRepository {
         )?;
         Ok(())
     }
+
+    fn create_master_password(
+        &self,
+        hash: &str,
+        nonce: &[u8],
+        encrypted_settings: &[u8],
+    ) -> AppResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO meta (key, value) VALUES ('master_password_hash', ?)",
+            [hash],
+        )?;
+        if inserted == 0 {
+            return Err(AppError::Conflict(
+                "The vault already has a master password".to_string(),
+            ));
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, nonce, value) VALUES ('settings', ?, ?)",
+            params![nonce, encrypted_settings],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_ui_preferences(&self) -> AppResult<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT value FROM meta WHERE key = 'ui_preferences'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(AppError::Database)
+    }
+
+    fn save_ui_preferences(&self, preferences_json: &str) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('ui_preferences', ?)",
+            [preferences_json],
+        )?;
+        Ok(())
+    }
+
+    fn get_storage_profile(&self) -> AppResult<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT value FROM meta WHERE key = 'storage_profile'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(AppError::Database)
+    }
+
+    fn save_storage_profile(&self, profile_json: &str) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('storage_profile', ?)",
+            [profile_json],
+        )?;
+        Ok(())
+    }
+
+    fn get_encrypted_wordlists(&self) -> AppResult<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT value FROM meta WHERE key = 'wordlists'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(AppError::Database)
+    }
+
+    fn save_encrypted_wordlists(&self, container: &str) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('wordlists', ?)",
+            [container],
+        )?;
+        Ok(())
+    }
+
+    fn get_encrypted_email_monitor(&self) -> AppResult<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT value FROM meta WHERE key = 'email_monitor'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(AppError::Database)
+    }
+
+    fn save_encrypted_email_monitor(&self, container: &str) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('email_monitor', ?)",
+            [container],
+        )?;
+        Ok(())
+    }
+
+    fn get_encrypted_pending_saves(&self) -> AppResult<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT value FROM meta WHERE key = 'pending_saves'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(AppError::Database)
+    }
+
+    fn save_encrypted_pending_saves(&self, container: &str) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('pending_saves', ?)",
+            [container],
+        )?;
+        Ok(())
+    }
+
+    fn get_strength_version(&self) -> AppResult<Option<u32>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT value FROM meta WHERE key = 'strength_version'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(AppError::Database)
+    }
+
+    fn save_strength_version(&self, version: u32) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('strength_version', ?)",
+            [version],
+        )?;
+        Ok(())
+    }
+
+    fn get_meta(&self, key: &str) -> AppResult<Option<Vec<u8>>> {
+        check_meta_key(key)?;
+        let conn = self.conn.lock().unwrap();
+        conn.query_row("SELECT value FROM meta WHERE key = ?", [key], |row| {
+            row.get(0)
+        })
+        .optional()
+        .map_err(AppError::Database)
+    }
+
+    fn put_meta(&self, key: &str, value: &[u8]) -> AppResult<()> {
+        check_meta_key(key)?;
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES (?, ?)",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+}
+
+/// Keys for `get_meta` and `put_meta`: up to 64 of a-z, 0-9, '.', '_' and '-',
+/// and none of the keys the repository uses itself
+fn check_meta_key(key: &str) -> AppResult<()> {
+    let well_formed = !key.is_empty()
+        && key.len() <= MAX_META_KEY_LEN
+        && key
+            .bytes()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || b"._-".contains(&c));
+    if !well_formed {
+        return Err(AppError::InvalidInput(format!(
+            "Invalid metadata key: {key:?}"
+        )));
+    }
+    if RESERVED_META_KEYS.contains(&key) {
+        return Err(AppError::InvalidInput(format!(
+            "The metadata key {key} is reserved"
+        )));
+    }
+    Ok(())
 }
 
 impl AuditLogger for SqliteRepository {
@@ -408,29 +1192,1057 @@ impl AuditLogger for SqliteRepository {
         let conn = self.conn.lock().unwrap();
         let limit = limit.unwrap_or(100);
         let mut stmt = conn.prepare(
-            "SELECT id, timestamp, action, item_uuid FROM audit_log ORDER BY timestamp DESC LIMIT ?",
+            "SELECT id, timestamp, action, item_uuid FROM audit_log ORDER BY timestamp DESC, id DESC LIMIT ?",
         )?;
 
-        let rows = stmt.query_map([limit], |row| {
-            let timestamp_val: i64 = row.get(1)?;
-            Ok(AuditLogEntry {
-                id: row.get(0)?,
-                timestamp: Utc.timestamp_opt(timestamp_val, 0).single().ok_or(
+        let rows = stmt.query_map([limit], row_to_audit_entry)?;
+
+        let mut entries = Vec::new();
+        for row_result in rows {
+            entries.push(row_result?);
+        }
+        Ok(entries)
+    }
+}
+
+fn row_to_audit_entry(row: &rusqlite::Row) -> rusqlite::Result<AuditLogEntry> {
+    let timestamp_val: i64 = row.get(1)?;
+    Ok(AuditLogEntry {
+        id: row.get(0)?,
+        timestamp: Utc.timestamp_opt(timestamp_val, 0).single().ok_or(
+            rusqlite::Error::InvalidColumnType(
+                1,
+                "timestamp".to_string(),
+                rusqlite::types::Type::Integer,
+            ),
+        )?,
+        action: row.get(2)?,
+        item_uuid: row.get(3)?,
+    })
+}
+
+impl AuditArchiveRepository for SqliteRepository {
+    fn audit_entries_before(
+        &self,
+        before: DateTime<Utc>,
+        limit: usize,
+    ) -> AppResult<Vec<AuditLogEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, timestamp, action, item_uuid FROM audit_log WHERE timestamp < ? ORDER BY timestamp, id LIMIT ?",
+        )?;
+        let rows = stmt.query_map(
+            params![before.timestamp(), limit as i64],
+            row_to_audit_entry,
+        )?;
+
+        let mut entries = Vec::new();
+        for row_result in rows {
+            entries.push(row_result?);
+        }
+        Ok(entries)
+    }
+
+    fn add_audit_archive(&self, archive: &StoredAuditArchive, entry_ids: &[i64]) -> AppResult<i64> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO audit_archives (first_at, last_at, entries, data_enc) VALUES (?, ?, ?, ?)",
+            params![
+                archive.first_at.timestamp(),
+                archive.last_at.timestamp(),
+                archive.entries as i64,
+                archive.data_enc
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+        {
+            let mut delete = tx.prepare("DELETE FROM audit_log WHERE id = ?")?;
+            for entry_id in entry_ids {
+                delete.execute([entry_id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(id)
+    }
+
+    fn list_audit_archives(&self) -> AppResult<Vec<StoredAuditArchive>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, first_at, last_at, entries, data_enc FROM audit_archives ORDER BY first_at, id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let timestamp = |index: usize, column: &str| -> rusqlite::Result<DateTime<Utc>> {
+                Utc.timestamp_opt(row.get(index)?, 0).single().ok_or(
                     rusqlite::Error::InvalidColumnType(
-                        1,
-                        "timestamp".to_string(),
+                        index,
+                        column.to_string(),
                         rusqlite::types::Type::Integer,
                     ),
-                )?,
-                action: row.get(2)?,
-                item_uuid: row.get(3)?,
+                )
+            };
+            Ok(StoredAuditArchive {
+                id: row.get(0)?,
+                first_at: timestamp(1, "first_at")?,
+                last_at: timestamp(2, "last_at")?,
+                entries: row.get::<_, i64>(3)? as usize,
+                data_enc: row.get(4)?,
             })
         })?;
 
-        let mut entries = Vec::new();
+        let mut archives = Vec::new();
         for row_result in rows {
-            entries.push(row_result?);
+            archives.push(row_result?);
         }
-        Ok(entries)
+        Ok(archives)
+    }
+}
+
+impl MaintenanceRepository for SqliteRepository {
+    fn integrity_check(&self) -> AppResult<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+        let results = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(results)
+    }
+
+    fn schema_version(&self) -> AppResult<u32> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+    }
+
+    fn set_cache_size(&self, kib: u32) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        // A negative cache size is in KiB rather than pages
+        conn.pragma_update(None, "cache_size", -i64::from(kib))?;
+        Ok(())
+    }
+
+    fn checkpoint(&self) -> AppResult<Option<u64>> {
+        let conn = self.conn.lock().unwrap();
+        // (busy, pages in the log, pages moved); the log size is -1 without one
+        let (log_pages, moved): (i64, i64) =
+            conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| {
+                Ok((row.get(1)?, row.get(2)?))
+            })?;
+        Ok((log_pages >= 0).then_some(moved.max(0) as u64))
+    }
+
+    fn optimize(&self) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute_batch("PRAGMA optimize;")?;
+        Ok(())
+    }
+
+    fn snapshot(&self) -> AppResult<Vec<u8>> {
+        let conn = self.conn.lock().unwrap();
+        // Serialized in memory, so the unencrypted copy never touches the disk
+        let data = conn.serialize(DatabaseName::Main)?;
+        Ok(data.to_vec())
+    }
+
+    fn compact(&self, wipe: bool, progress: &dyn ProgressSink) -> AppResult<CompactReport> {
+        let mut conn = self.conn.lock().unwrap();
+        let (size_before, freed_pages) = Self::page_stats(&conn)?;
+        progress.report(&CompactProgress::step(CompactStage::Vacuuming, false));
+
+        if !wipe {
+            conn.execute_batch("VACUUM")?;
+            progress.report(&CompactProgress::step(CompactStage::Vacuuming, true));
+            let (size_after, _) = Self::page_stats(&conn)?;
+            return Ok(CompactReport {
+                size_before,
+                size_after,
+                freed_pages,
+                wiped: false,
+            });
+        }
+
+        // An in-place VACUUM leaves the old pages in the truncated tail and the
+        // rollback journal. Build the compact copy next to the vault instead,
+        // rename it over the original, then zero the original's blocks through
+        // a handle kept open across the rename. Until the rename the original
+        // is untouched, and the rename itself is atomic.
+        let path = conn
+            .path()
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .ok_or_else(|| {
+                AppError::InvalidInput("In-memory vaults cannot be wiped".to_string())
+            })?;
+        let compacted = path.with_extension("compact");
+        match std::fs::remove_file(&compacted) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        let target = compacted
+            .to_str()
+            .ok_or_else(|| AppError::Other("Vault path is not valid UTF-8".to_string()))?;
+        conn.execute("VACUUM INTO ?", params![target])?;
+        let synced = std::fs::File::open(&compacted).and_then(|file| file.sync_all());
+        if let Err(e) = synced {
+            let _ = std::fs::remove_file(&compacted);
+            return Err(e.into());
+        }
+        progress.report(&CompactProgress::step(CompactStage::Vacuuming, true));
+
+        let mut original = std::fs::OpenOptions::new().write(true).open(&path)?;
+        let old = std::mem::replace(&mut *conn, Connection::open_in_memory()?);
+        if let Err((old, e)) = old.close() {
+            *conn = old;
+            let _ = std::fs::remove_file(&compacted);
+            return Err(e.into());
+        }
+        progress.report(&CompactProgress::step(CompactStage::Restoring, false));
+        if let Err(e) = std::fs::rename(&compacted, &path) {
+            let _ = std::fs::remove_file(&compacted);
+            *conn = Self::open_connection(&path)?;
+            return Err(e.into());
+        }
+        *conn = Self::open_connection(&path)?;
+        // The rewrite was ours; only later changes count as external
+        *self.watch_baseline.lock().unwrap() = Self::watch_state(&conn)?;
+        // Make the rename itself durable; directories cannot be opened on Windows
+        #[cfg(unix)]
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::File::open(dir)?.sync_all()?;
+        }
+        progress.report(&CompactProgress::step(CompactStage::Restoring, true));
+
+        compact::overwrite_file(&mut original, progress)?;
+        drop(original);
+
+        let (size_after, _) = Self::page_stats(&conn)?;
+        Ok(CompactReport {
+            size_before,
+            size_after,
+            freed_pages,
+            wiped: true,
+        })
+    }
+}
+
+impl StatsRepository for SqliteRepository {
+    fn add_snapshot(&self, snapshot: &SecuritySnapshot) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO stats_history (taken_at, total, weak, compromised, reused, strength_histogram) VALUES (?, ?, ?, ?, ?, ?)",
+            params![
+                snapshot.taken_at.timestamp(),
+                snapshot.total,
+                snapshot.weak,
+                snapshot.compromised,
+                snapshot.reused,
+                serde_json::to_string(&snapshot.strength_histogram)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn list_snapshots(
+        &self,
+        since: Option<chrono::DateTime<Utc>>,
+    ) -> AppResult<Vec<SecuritySnapshot>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT taken_at, total, weak, compromised, reused, strength_histogram FROM stats_history WHERE taken_at >= ? ORDER BY taken_at ASC, id ASC",
+        )?;
+        let since = since.map_or(i64::MIN, |time| time.timestamp());
+
+        let rows = stmt.query_map([since], |row| {
+            let taken_ts: i64 = row.get(0)?;
+            let histogram_json: String = row.get(5)?;
+            Ok(SecuritySnapshot {
+                taken_at: Utc.timestamp_opt(taken_ts, 0).single().ok_or(
+                    rusqlite::Error::InvalidColumnType(
+                        0,
+                        "taken_at".to_string(),
+                        rusqlite::types::Type::Integer,
+                    ),
+                )?,
+                total: row.get(1)?,
+                weak: row.get(2)?,
+                compromised: row.get(3)?,
+                reused: row.get(4)?,
+                strength_histogram: serde_json::from_str(&histogram_json).map_err(|_e| {
+                    rusqlite::Error::InvalidColumnType(
+                        5,
+                        "strength_histogram".to_string(),
+                        rusqlite::types::Type::Text,
+                    )
+                })?,
+            })
+        })?;
+
+        let mut snapshots = Vec::new();
+        for row_result in rows {
+            snapshots.push(row_result?);
+        }
+        Ok(snapshots)
+    }
+
+    fn latest_snapshot_at(&self) -> AppResult<Option<chrono::DateTime<Utc>>> {
+        let conn = self.conn.lock().unwrap();
+        let latest: Option<i64> =
+            conn.query_row("SELECT MAX(taken_at) FROM stats_history", [], |row| {
+                row.get(0)
+            })?;
+        Ok(latest.and_then(|ts| Utc.timestamp_opt(ts, 0).single()))
+    }
+}
+
+impl SqliteRepository {
+    fn collection_members(&self, conn: &Connection, id: &str) -> AppResult<Vec<CollectionMember>> {
+        let mut stmt = conn.prepare(
+            "SELECT public_key, label, added_at, wrapped_key FROM collection_members WHERE collection_id = ? ORDER BY added_at ASC, public_key ASC",
+        )?;
+        let rows = stmt.query_map([id], |row| {
+            let added_ts: i64 = row.get(2)?;
+            let wrapped_json: String = row.get(3)?;
+            Ok(CollectionMember {
+                public_key: row.get(0)?,
+                label: row.get(1)?,
+                added_at: Utc.timestamp_opt(added_ts, 0).single().ok_or(
+                    rusqlite::Error::InvalidColumnType(
+                        2,
+                        "added_at".to_string(),
+                        rusqlite::types::Type::Integer,
+                    ),
+                )?,
+                wrapped_key: serde_json::from_str(&wrapped_json).map_err(|_e| {
+                    rusqlite::Error::InvalidColumnType(
+                        3,
+                        "wrapped_key".to_string(),
+                        rusqlite::types::Type::Text,
+                    )
+                })?,
+            })
+        })?;
+
+        let mut members = Vec::new();
+        for row_result in rows {
+            members.push(row_result?);
+        }
+        Ok(members)
+    }
+
+    fn save_member_tx(
+        tx: &Transaction,
+        collection_id: &str,
+        member: &CollectionMember,
+    ) -> AppResult<()> {
+        tx.execute(
+            "INSERT OR REPLACE INTO collection_members (collection_id, public_key, label, added_at, wrapped_key) VALUES (?, ?, ?, ?, ?)",
+            params![
+                collection_id,
+                member.public_key,
+                member.label,
+                member.added_at.timestamp(),
+                serde_json::to_string(&member.wrapped_key)?,
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+impl CollectionRepository for SqliteRepository {
+    fn create_collection(&self, collection: &Collection) -> AppResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO collections (id, name, created_at) VALUES (?, ?, ?)",
+            params![
+                collection.id,
+                collection.name,
+                collection.created_at.timestamp()
+            ],
+        )?;
+        for member in &collection.members {
+            Self::save_member_tx(&tx, &collection.id, member)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_collection(&self, id: &str) -> AppResult<Collection> {
+        let conn = self.conn.lock().unwrap();
+        let (name, created_ts): (String, i64) = conn
+            .query_row(
+                "SELECT name, created_at FROM collections WHERE id = ?",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?
+            .ok_or_else(|| AppError::NotFound(id.to_string()))?;
+        Ok(Collection {
+            id: id.to_string(),
+            name,
+            created_at: Utc
+                .timestamp_opt(created_ts, 0)
+                .single()
+                .ok_or_else(|| AppError::Other("Invalid collection timestamp".to_string()))?,
+            members: self.collection_members(&conn, id)?,
+        })
+    }
+
+    fn list_collections(&self) -> AppResult<Vec<Collection>> {
+        let ids: Vec<String> = {
+            let conn = self.conn.lock().unwrap();
+            let mut stmt = conn.prepare("SELECT id FROM collections ORDER BY name ASC, id ASC")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        ids.iter().map(|id| self.get_collection(id)).collect()
+    }
+
+    fn save_member(&self, collection_id: &str, member: &CollectionMember) -> AppResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        Self::save_member_tx(&tx, collection_id, member)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn remove_member(&self, collection_id: &str, public_key: &str) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        let removed = conn.execute(
+            "DELETE FROM collection_members WHERE collection_id = ? AND public_key = ?",
+            params![collection_id, public_key],
+        )?;
+        if removed == 0 {
+            return Err(AppError::NotFound(public_key.to_string()));
+        }
+        Ok(())
+    }
+
+    fn delete_collection(&self, id: &str) -> AppResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM collection_members WHERE collection_id = ?",
+            [id],
+        )?;
+        if tx.execute("DELETE FROM collections WHERE id = ?", [id])? == 0 {
+            return Err(AppError::NotFound(id.to_string()));
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// Columns selected for a `ShareRecord`, in the order `row_to_share` expects.
+const SHARE_COLUMNS: &str = "id, item_uuid, site, destination, location, revoke_token, created_at, expires_at, max_views, revoked_at";
+
+/// Maps a row selected with `SHARE_COLUMNS` to a `ShareRecord`.
+fn row_to_share(row: &rusqlite::Row) -> rusqlite::Result<ShareRecord> {
+    let destination: String = row.get(3)?;
+    let timestamp = |index: usize, name: &str| -> rusqlite::Result<chrono::DateTime<Utc>> {
+        Utc.timestamp_opt(row.get(index)?, 0)
+            .single()
+            .ok_or(rusqlite::Error::InvalidColumnType(
+                index,
+                name.to_string(),
+                rusqlite::types::Type::Integer,
+            ))
+    };
+    let revoked_ts: Option<i64> = row.get(9)?;
+    Ok(ShareRecord {
+        id: row.get(0)?,
+        item_uuid: row.get(1)?,
+        site: row.get(2)?,
+        destination: ShareDestination::parse(&destination).ok_or(
+            rusqlite::Error::InvalidColumnType(
+                3,
+                "destination".to_string(),
+                rusqlite::types::Type::Text,
+            ),
+        )?,
+        location: row.get(4)?,
+        revoke_token: row.get(5)?,
+        created_at: timestamp(6, "created_at")?,
+        expires_at: timestamp(7, "expires_at")?,
+        max_views: row.get(8)?,
+        revoked_at: revoked_ts.and_then(|ts| Utc.timestamp_opt(ts, 0).single()),
+    })
+}
+
+impl ShareRepository for SqliteRepository {
+    fn save_share(&self, share: &ShareRecord) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            &format!(
+                "INSERT OR REPLACE INTO shares ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                SHARE_COLUMNS
+            ),
+            params![
+                share.id,
+                share.item_uuid,
+                share.site,
+                share.destination.as_str(),
+                share.location,
+                share.revoke_token,
+                share.created_at.timestamp(),
+                share.expires_at.timestamp(),
+                share.max_views,
+                share.revoked_at.map(|dt| dt.timestamp()),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn get_share(&self, id: &str) -> AppResult<ShareRecord> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            &format!("SELECT {} FROM shares WHERE id = ?", SHARE_COLUMNS),
+            [id],
+            row_to_share,
+        )
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(id.to_string()))
+    }
+
+    fn list_shares(&self) -> AppResult<Vec<ShareRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM shares ORDER BY created_at DESC, id ASC",
+            SHARE_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], row_to_share)?;
+
+        let mut shares = Vec::new();
+        for row_result in rows {
+            shares.push(row_result?);
+        }
+        Ok(shares)
+    }
+
+    fn mark_share_revoked(&self, id: &str, revoked_at: chrono::DateTime<Utc>) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE shares SET revoked_at = ? WHERE id = ?",
+            params![revoked_at.timestamp(), id],
+        )?;
+        if updated == 0 {
+            return Err(AppError::NotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    fn remove_expired_shares(&self, before: chrono::DateTime<Utc>) -> AppResult<usize> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute(
+            "DELETE FROM shares WHERE expires_at < ?",
+            [before.timestamp()],
+        )?)
+    }
+}
+
+/// Columns selected for an `EmergencyContact`, in the order `row_to_contact` expects.
+const CONTACT_COLUMNS: &str = "id, label, public_key, wait_days, state, created_at, requested_at, state_changed_at, wrapped_key";
+
+/// Maps a row selected with `CONTACT_COLUMNS` to an `EmergencyContact`.
+fn row_to_contact(row: &rusqlite::Row) -> rusqlite::Result<EmergencyContact> {
+    let state: String = row.get(4)?;
+    let timestamp = |index: usize, name: &str| -> rusqlite::Result<chrono::DateTime<Utc>> {
+        Utc.timestamp_opt(row.get(index)?, 0)
+            .single()
+            .ok_or(rusqlite::Error::InvalidColumnType(
+                index,
+                name.to_string(),
+                rusqlite::types::Type::Integer,
+            ))
+    };
+    let requested_ts: Option<i64> = row.get(6)?;
+    let wrapped_json: Option<String> = row.get(8)?;
+    Ok(EmergencyContact {
+        id: row.get(0)?,
+        label: row.get(1)?,
+        public_key: row.get(2)?,
+        wait_days: row.get(3)?,
+        state: EmergencyState::parse(&state).ok_or(rusqlite::Error::InvalidColumnType(
+            4,
+            "state".to_string(),
+            rusqlite::types::Type::Text,
+        ))?,
+        created_at: timestamp(5, "created_at")?,
+        requested_at: requested_ts.and_then(|ts| Utc.timestamp_opt(ts, 0).single()),
+        state_changed_at: timestamp(7, "state_changed_at")?,
+        wrapped_key: wrapped_json
+            .map(|json| serde_json::from_str(&json))
+            .transpose()
+            .map_err(|_e| {
+                rusqlite::Error::InvalidColumnType(
+                    8,
+                    "wrapped_key".to_string(),
+                    rusqlite::types::Type::Text,
+                )
+            })?,
+    })
+}
+
+impl EmergencyRepository for SqliteRepository {
+    fn save_contact(&self, contact: &EmergencyContact) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        let wrapped_json = contact
+            .wrapped_key
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        conn.execute(
+            &format!(
+                "INSERT OR REPLACE INTO emergency_contacts ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                CONTACT_COLUMNS
+            ),
+            params![
+                contact.id,
+                contact.label,
+                contact.public_key,
+                contact.wait_days,
+                contact.state.as_str(),
+                contact.created_at.timestamp(),
+                contact.requested_at.map(|dt| dt.timestamp()),
+                contact.state_changed_at.timestamp(),
+                wrapped_json,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn get_contact(&self, id: &str) -> AppResult<EmergencyContact> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            &format!(
+                "SELECT {} FROM emergency_contacts WHERE id = ?",
+                CONTACT_COLUMNS
+            ),
+            [id],
+            row_to_contact,
+        )
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(id.to_string()))
+    }
+
+    fn list_contacts(&self) -> AppResult<Vec<EmergencyContact>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM emergency_contacts ORDER BY created_at ASC, id ASC",
+            CONTACT_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], row_to_contact)?;
+
+        let mut contacts = Vec::new();
+        for row_result in rows {
+            contacts.push(row_result?);
+        }
+        Ok(contacts)
+    }
+}
+
+/// Columns selected for a `Device`, in the order `row_to_device` expects.
+const DEVICE_COLUMNS: &str = "id, name, platform, public_key, first_seen, last_seen, revoked_at";
+
+/// Maps a row selected with `DEVICE_COLUMNS` to a `Device`.
+fn row_to_device(row: &rusqlite::Row) -> rusqlite::Result<Device> {
+    let timestamp = |index: usize, name: &str| -> rusqlite::Result<chrono::DateTime<Utc>> {
+        Utc.timestamp_opt(row.get(index)?, 0)
+            .single()
+            .ok_or(rusqlite::Error::InvalidColumnType(
+                index,
+                name.to_string(),
+                rusqlite::types::Type::Integer,
+            ))
+    };
+    let revoked_ts: Option<i64> = row.get(6)?;
+    Ok(Device {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        platform: row.get(2)?,
+        public_key: row.get(3)?,
+        first_seen: timestamp(4, "first_seen")?,
+        last_seen: timestamp(5, "last_seen")?,
+        revoked_at: revoked_ts.and_then(|ts| Utc.timestamp_opt(ts, 0).single()),
+    })
+}
+
+impl DeviceRepository for SqliteRepository {
+    fn record_device(&self, device: &Device) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO devices (id, name, platform, public_key, first_seen, last_seen, revoked_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET
+                name = excluded.name, platform = excluded.platform, last_seen = excluded.last_seen",
+            params![
+                device.id,
+                device.name,
+                device.platform,
+                device.public_key,
+                device.first_seen.timestamp(),
+                device.last_seen.timestamp(),
+                device.revoked_at.map(|dt| dt.timestamp()),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn get_device(&self, id: &str) -> AppResult<Device> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            &format!("SELECT {} FROM devices WHERE id = ?", DEVICE_COLUMNS),
+            [id],
+            row_to_device,
+        )
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(id.to_string()))
+    }
+
+    fn list_devices(&self) -> AppResult<Vec<Device>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM devices ORDER BY last_seen DESC, id ASC",
+            DEVICE_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], row_to_device)?;
+
+        let mut devices = Vec::new();
+        for row_result in rows {
+            devices.push(row_result?);
+        }
+        Ok(devices)
+    }
+
+    fn revoke_device(&self, id: &str, revoked_at: chrono::DateTime<Utc>) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        let rows_affected = conn.execute(
+            "UPDATE devices SET revoked_at = COALESCE(revoked_at, ?) WHERE id = ?",
+            params![revoked_at.timestamp(), id],
+        )?;
+        if rows_affected == 0 {
+            return Err(AppError::NotFound(id.to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl TravelRepository for SqliteRepository {
+    fn item_audit_entries(&self, uuids: &[String]) -> AppResult<Vec<AuditLogEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, timestamp, action, item_uuid FROM audit_log WHERE item_uuid = ? ORDER BY id",
+        )?;
+        let mut entries = Vec::new();
+        for uuid in uuids {
+            for row_result in stmt.query_map([uuid], row_to_audit_entry)? {
+                entries.push(row_result?);
+            }
+        }
+        entries.sort_by_key(|entry| entry.id);
+        Ok(entries)
+    }
+
+    fn enter_travel_mode(
+        &self,
+        stashed: &[StashedCredential],
+        archives: &[StoredAuditArchive],
+        enabled_at: chrono::DateTime<Utc>,
+    ) -> AppResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for item in stashed {
+            tx.execute(
+                "INSERT INTO travel_stash (uuid, ciphertext) VALUES (?, ?)",
+                params![item.uuid, item.ciphertext],
+            )?;
+            tx.execute("DELETE FROM vault_items WHERE uuid = ?", [&item.uuid])?;
+            tx.execute("DELETE FROM audit_log WHERE item_uuid = ?", [&item.uuid])?;
+        }
+        for archive in archives {
+            if archive.entries == 0 {
+                tx.execute("DELETE FROM audit_archives WHERE id = ?", [archive.id])?;
+            } else {
+                tx.execute(
+                    "UPDATE audit_archives SET first_at = ?, last_at = ?, entries = ?, data_enc = ? WHERE id = ?",
+                    params![
+                        archive.first_at.timestamp(),
+                        archive.last_at.timestamp(),
+                        archive.entries as i64,
+                        archive.data_enc,
+                        archive.id
+                    ],
+                )?;
+            }
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('travel_mode_since', ?)",
+            [enabled_at.timestamp()],
+        )?;
+        tx.commit()?;
+        // secure_delete zeroes the deleted rows; VACUUM also rebuilds the file so
+        // no page keeps their old layout
+        conn.execute_batch("VACUUM")?;
+        Ok(())
+    }
+
+    fn travel_mode_since(&self) -> AppResult<Option<chrono::DateTime<Utc>>> {
+        let conn = self.conn.lock().unwrap();
+        let since: Option<i64> = conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = 'travel_mode_since'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(since.and_then(|ts| Utc.timestamp_opt(ts, 0).single()))
+    }
+
+    fn list_stashed(&self) -> AppResult<Vec<StashedCredential>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT uuid, ciphertext FROM travel_stash ORDER BY uuid")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(StashedCredential {
+                uuid: row.get(0)?,
+                ciphertext: row.get(1)?,
+            })
+        })?;
+
+        let mut stashed = Vec::new();
+        for row_result in rows {
+            stashed.push(row_result?);
+        }
+        Ok(stashed)
+    }
+
+    fn leave_travel_mode(&self, restored: &[Credential], audit: &[AuditLogEntry]) -> AppResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for credential in restored {
+            self.insert_credential_tx(&tx, credential, credential.strength)?;
+        }
+        // Ids are never reused, so the entries return to their place in the log
+        for entry in audit {
+            tx.execute(
+                "INSERT OR IGNORE INTO audit_log (id, timestamp, action, item_uuid) VALUES (?, ?, ?, ?)",
+                params![
+                    entry.id,
+                    entry.timestamp.timestamp(),
+                    entry.action,
+                    entry.item_uuid
+                ],
+            )?;
+        }
+        tx.execute("DELETE FROM travel_stash", [])?;
+        tx.execute("DELETE FROM meta WHERE key = 'travel_mode_since'", [])?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+impl JournalRepository for SqliteRepository {
+    fn begin_operation(&self, entry: &JournalEntry) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO operations_journal (id, operation, started_at) VALUES (?, ?, ?)",
+            params![
+                entry.id,
+                serde_json::to_string(&entry.operation)?,
+                entry.started_at.timestamp()
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn complete_operation(&self, id: &str) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM operations_journal WHERE id = ?", [id])?;
+        Ok(())
+    }
+
+    fn list_pending_operations(&self) -> AppResult<Vec<JournalEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, operation, started_at FROM operations_journal ORDER BY started_at ASC, rowid ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let operation_json: String = row.get(1)?;
+            let started_ts: i64 = row.get(2)?;
+            Ok(JournalEntry {
+                id: row.get(0)?,
+                operation: serde_json::from_str(&operation_json).map_err(|_e| {
+                    rusqlite::Error::InvalidColumnType(
+                        1,
+                        "operation".to_string(),
+                        rusqlite::types::Type::Text,
+                    )
+                })?,
+                started_at: Utc.timestamp_opt(started_ts, 0).single().ok_or(
+                    rusqlite::Error::InvalidColumnType(
+                        2,
+                        "started_at".to_string(),
+                        rusqlite::types::Type::Integer,
+                    ),
+                )?,
+            })
+        })?;
+
+        let mut entries = Vec::new();
+        for row_result in rows {
+            entries.push(row_result?);
+        }
+        Ok(entries)
+    }
+}
+
+impl BreachListRepository for SqliteRepository {
+    fn add_breach_entries(
+        &self,
+        source: &str,
+        fingerprints: &[String],
+        imported_at: chrono::DateTime<Utc>,
+    ) -> AppResult<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let mut added = 0;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR IGNORE INTO breach_list (fingerprint, source, imported_at) VALUES (?, ?, ?)",
+            )?;
+            for fingerprint in fingerprints {
+                added += stmt.execute(params![fingerprint, source, imported_at.timestamp()])?;
+            }
+        }
+        tx.commit()?;
+        Ok(added)
+    }
+
+    fn find_breach_entries(&self, fingerprints: &[String]) -> AppResult<Vec<(String, String)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT source FROM breach_list WHERE fingerprint = ? ORDER BY source")?;
+        let mut found = Vec::new();
+        for fingerprint in fingerprints {
+            let sources = stmt.query_map([fingerprint], |row| row.get::<_, String>(0))?;
+            for source in sources {
+                found.push((fingerprint.clone(), source?));
+            }
+        }
+        Ok(found)
+    }
+
+    fn list_breach_sources(&self) -> AppResult<Vec<BreachListSource>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT source, COUNT(*), MAX(imported_at) FROM breach_list GROUP BY source ORDER BY source",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let imported_ts: i64 = row.get(2)?;
+            Ok(BreachListSource {
+                source: row.get(0)?,
+                entries: row.get::<_, i64>(1)? as u64,
+                imported_at: Utc.timestamp_opt(imported_ts, 0).single().ok_or(
+                    rusqlite::Error::InvalidColumnType(
+                        2,
+                        "imported_at".to_string(),
+                        rusqlite::types::Type::Integer,
+                    ),
+                )?,
+            })
+        })?;
+
+        let mut sources = Vec::new();
+        for row_result in rows {
+            sources.push(row_result?);
+        }
+        Ok(sources)
+    }
+
+    fn remove_breach_source(&self, source: &str) -> AppResult<usize> {
+        let conn = self.conn.lock().unwrap();
+        let removed = conn.execute("DELETE FROM breach_list WHERE source = ?", [source])?;
+        if removed == 0 {
+            return Err(AppError::NotFound(source.to_string()));
+        }
+        Ok(removed)
+    }
+}
+
+fn row_to_import_conflict(row: &rusqlite::Row) -> rusqlite::Result<StoredImportConflict> {
+    let created_ts: i64 = row.get(3)?;
+    Ok(StoredImportConflict {
+        id: row.get(0)?,
+        existing_uuid: row.get(1)?,
+        item_enc: row.get(2)?,
+        created_at: Utc.timestamp_opt(created_ts, 0).single().ok_or(
+            rusqlite::Error::InvalidColumnType(
+                3,
+                "created_at".to_string(),
+                rusqlite::types::Type::Integer,
+            ),
+        )?,
+    })
+}
+
+impl ImportConflictRepository for SqliteRepository {
+    fn add_import_conflict(
+        &self,
+        existing_uuid: &str,
+        item_enc: &str,
+        created_at: chrono::DateTime<Utc>,
+    ) -> AppResult<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO import_conflicts (existing_uuid, item_enc, created_at) VALUES (?, ?, ?)",
+            params![existing_uuid, item_enc, created_at.timestamp()],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    fn list_import_conflicts(&self) -> AppResult<Vec<StoredImportConflict>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, existing_uuid, item_enc, created_at FROM import_conflicts ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], row_to_import_conflict)?;
+
+        let mut conflicts = Vec::new();
+        for row_result in rows {
+            conflicts.push(row_result?);
+        }
+        Ok(conflicts)
+    }
+
+    fn get_import_conflict(&self, id: i64) -> AppResult<StoredImportConflict> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT id, existing_uuid, item_enc, created_at FROM import_conflicts WHERE id = ?",
+            [id],
+            row_to_import_conflict,
+        )
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("import conflict {}", id)))
+    }
+
+    fn remove_import_conflict(&self, id: i64) -> AppResult<()> {
+        let conn = self.conn.lock().unwrap();
+        if conn.execute("DELETE FROM import_conflicts WHERE id = ?", [id])? == 0 {
+            return Err(AppError::NotFound(format!("import conflict {}", id)));
+        }
+        Ok(())
+    }
+}
+
+impl ExternalChangeSource for SqliteRepository {
+    fn poll_external_change(&self) -> AppResult<Option<ExternalChange>> {
+        let conn = self.conn.lock().unwrap();
+        let Some(current) = Self::watch_state(&conn)? else {
+            return Ok(None);
+        };
+        let mut baseline = self.watch_baseline.lock().unwrap();
+        let change = baseline
+            .as_ref()
+            .and_then(|baseline| baseline.compare(&current));
+        *baseline = Some(current);
+        Ok(change)
     }
 }
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{BreachState, Credential, CredentialKind, Secret};
+use crate::remediation::WEAK_STRENGTH_THRESHOLD;
+
+/// Minimum time between two automatic snapshots
+pub const SNAPSHOT_INTERVAL_HOURS: i64 = 24;
+
+/// Aggregate vault health at one point in time. Holds counts only, never secrets.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SecuritySnapshot {
+    pub taken_at: DateTime<Utc>,
+    pub total: u32,
+    pub weak: u32,
+    pub compromised: u32,
+    /// Credentials sharing their password with at least one other credential
+    pub reused: u32,
+    /// Number of credentials per strength bucket, index = zxcvbn-style score (0-4)
+    pub strength_histogram: [u32; 5],
+}
+
+/// Item counts and password health of the vault, see `get_vault_statistics`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VaultStatistics {
+    /// Items of every kind
+    pub total: u32,
+    pub logins: u32,
+    pub api_keys: u32,
+    pub cards: u32,
+    pub identities: u32,
+    pub wifi: u32,
+    /// The rest count only items with a password (logins, API keys and Wi-Fi)
+    pub weak: u32,
+    pub compromised: u32,
+    /// Comparing passwords decrypts them all, so this is None outside a bulk session
+    pub reused: Option<u32>,
+    pub strength_histogram: [u32; 5],
+    /// Whether large-vault optimizations are in use, see `performance`
+    pub performance_mode_active: bool,
+}
+
+/// Time window for `get_security_trend`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendRange {
+    Month,
+    Quarter,
+    Year,
+    All,
+}
+
+impl TrendRange {
+    /// Earliest snapshot time included in the range
+    pub fn since(self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let days = match self {
+            TrendRange::Month => 30,
+            TrendRange::Quarter => 91,
+            TrendRange::Year => 365,
+            TrendRange::All => return None,
+        };
+        Some(now - Duration::days(days))
+    }
+}
+
+/// Whether items of `kind` hold a password. Card numbers are not passwords and
+/// identities have none, so they cannot be weak, reused or rotated.
+pub fn has_password(kind: CredentialKind) -> bool {
+    matches!(
+        kind,
+        CredentialKind::Login | CredentialKind::ApiKey | CredentialKind::Wifi
+    )
+}
+
+/// Computes the aggregate stats of the given credentials
+pub fn take_snapshot(entries: &[(Credential, Secret)], now: DateTime<Utc>) -> SecuritySnapshot {
+    let mut snapshot = health(entries.iter().map(|(credential, _)| credential), now);
+    snapshot.reused = count_reused(entries);
+    snapshot
+}
+
+/// Credentials sharing their password with at least one other of `entries`
+pub fn count_reused(entries: &[(Credential, Secret)]) -> u32 {
+    let mut uses: HashMap<&str, u32> = HashMap::new();
+    for (_, secret) in entries {
+        *uses.entry(secret.password.as_str()).or_default() += 1;
+    }
+    entries
+        .iter()
+        .filter(|(_, secret)| uses[secret.password.as_str()] > 1)
+        .count() as u32
+}
+
+/// Everything in a snapshot that the stored metadata tells, i.e. all but `reused`
+fn health<'a>(
+    credentials: impl Iterator<Item = &'a Credential>,
+    now: DateTime<Utc>,
+) -> SecuritySnapshot {
+    let mut snapshot = SecuritySnapshot {
+        taken_at: now,
+        total: 0,
+        weak: 0,
+        compromised: 0,
+        reused: 0,
+        strength_histogram: [0; 5],
+    };
+    for credential in credentials {
+        snapshot.total += 1;
+        snapshot.strength_histogram[usize::from(credential.strength_bucket())] += 1;
+        if credential.strength < WEAK_STRENGTH_THRESHOLD {
+            snapshot.weak += 1;
+        }
+        if credential.breach_state == BreachState::Compromised {
+            snapshot.compromised += 1;
+        }
+    }
+    snapshot
+}
+
+/// Counts `items` by kind and sums up the health of those with a password.
+/// `reused` comes from `count_reused` when a bulk session allows it.
+pub fn vault_statistics(items: &[Credential], reused: Option<u32>) -> VaultStatistics {
+    let health = health(
+        items.iter().filter(|item| has_password(item.kind)),
+        Utc::now(),
+    );
+    let mut stats = VaultStatistics {
+        total: items.len() as u32,
+        weak: health.weak,
+        compromised: health.compromised,
+        reused,
+        strength_histogram: health.strength_histogram,
+        ..Default::default()
+    };
+    for item in items {
+        *match item.kind {
+            CredentialKind::Login => &mut stats.logins,
+            CredentialKind::ApiKey => &mut stats.api_keys,
+            CredentialKind::CreditCard => &mut stats.cards,
+            CredentialKind::Identity => &mut stats.identities,
+            CredentialKind::Wifi => &mut stats.wifi,
+        } += 1;
+    }
+    stats
+}
+
+/// Whether a new snapshot should be recorded, given the time of the latest one
+pub fn is_due(latest: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    match latest {
+        Some(time) => now - time >= Duration::hours(SNAPSHOT_INTERVAL_HOURS),
+        None => true,
+    }
+}
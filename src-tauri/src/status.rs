@@ -0,0 +1,60 @@
+//! The app status the window chrome shows, in one call.
+//!
+//! The title bar, lock button and badges need a handful of facts that would
+//! otherwise take a round-trip each. `get_app_status` gathers them, and since
+//! the chrome polls, a status younger than `STATUS_MIN_INTERVAL` is handed out
+//! again instead of being gathered anew, unless the vault was locked or
+//! unlocked in between.
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppResult;
+use crate::features::Feature;
+use crate::setup::VaultStatus;
+
+/// Shortest time between two gatherings of the status
+pub const STATUS_MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Returned by `get_app_status`. What is stored encrypted, the features and
+/// the backup folder, is left empty while the vault is locked.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppStatus {
+    pub version: String,
+    pub vault_status: VaultStatus,
+    /// Features enabled for this vault
+    pub features: Vec<Feature>,
+    /// Unread notifications
+    pub pending_notifications: usize,
+    /// Newest backup in the backup folder
+    pub last_backup_at: Option<DateTime<Utc>>,
+    /// Last exchange with the vault server, for a remote vault
+    pub last_sync_at: Option<DateTime<Utc>>,
+}
+
+/// The status last gathered, see the module docs
+#[derive(Debug, Default)]
+pub struct StatusCache(Option<(Instant, AppStatus)>);
+
+impl StatusCache {
+    /// The cached status if it is fresh and agrees on whether the vault is
+    /// unlocked, otherwise the one `gather` returns
+    pub fn get_or_gather(
+        &mut self,
+        unlocked: bool,
+        now: Instant,
+        gather: impl FnOnce() -> AppResult<AppStatus>,
+    ) -> AppResult<AppStatus> {
+        if let Some((at, status)) = &self.0 {
+            let fresh = now.saturating_duration_since(*at) < STATUS_MIN_INTERVAL;
+            if fresh && (status.vault_status == VaultStatus::Unlocked) == unlocked {
+                return Ok(status.clone());
+            }
+        }
+        let status = gather()?;
+        self.0 = Some((now, status.clone()));
+        Ok(status)
+    }
+}
@@ -1,15 +1,227 @@
+use crate::error::{AppError, AppResult};
 use crate::traits::PasswordStrengthCalculator;
+use serde::{Deserialize, Serialize};
 use zxcvbn::zxcvbn;
 
 /// Simple password strength calculator.
 pub struct SimpleStrengthCalculator;
 
 impl PasswordStrengthCalculator for SimpleStrengthCalculator {
-    fn calculate_strength(&self, password: &str) -> u8 {
-        // Use zxcvbn to calculate password strength
-        let estimate = zxcvbn(password, &[]);
+    fn calculate_strength(&self, password: &str, wordlists: &Wordlists) -> u8 {
+        // A banned password is as weak as it gets, whatever zxcvbn thinks of it
+        if wordlists.is_banned(password) {
+            return 0;
+        }
 
-        // zxcvbn returns a score from 0 to 4, score less than 3 should be considered too weak
-        estimate.score() as u8
+        // Use zxcvbn to estimate how many guesses the password takes
+        let estimate = zxcvbn(password, &wordlists.dictionary_words());
+        strength_from_guesses_log10(estimate.guesses_log10())
+    }
+}
+
+/// Guesses (as powers of ten) at which the 0-100 scale reaches each knot.
+/// The zxcvbn score boundaries (10^3, 10^6, 10^8 and 10^10 guesses) land on
+/// 20, 40, 60 and 80; 10^20 guesses, out of reach of any offline attack, is 100.
+const STRENGTH_KNOTS: [(f64, f64); 6] = [
+    (0.0, 0.0),
+    (3.0, 20.0),
+    (6.0, 40.0),
+    (8.0, 60.0),
+    (10.0, 80.0),
+    (20.0, 100.0),
+];
+
+/// Strength (0-100) of a password that takes 10^`guesses_log10` guesses to crack,
+/// interpolated linearly between `STRENGTH_KNOTS`
+pub fn strength_from_guesses_log10(guesses_log10: f64) -> u8 {
+    let (last_guesses, last_strength) = STRENGTH_KNOTS[STRENGTH_KNOTS.len() - 1];
+    if guesses_log10 >= last_guesses {
+        return last_strength as u8;
+    }
+    STRENGTH_KNOTS
+        .windows(2)
+        .find(|knots| guesses_log10 < knots[1].0)
+        .map(|knots| {
+            let ((g0, s0), (g1, s1)) = (knots[0], knots[1]);
+            let strength = s0 + (guesses_log10.max(g0) - g0) / (g1 - g0) * (s1 - s0);
+            // Round down so a password never lands in a higher bucket than zxcvbn gives it
+            strength.floor() as u8
+        })
+        .unwrap_or(0)
+}
+
+/// Strength (0-100) for a password with `bits` of entropy
+pub fn strength_from_entropy(bits: f64) -> u8 {
+    // log10 of the expected number of guesses, taking half the keyspace
+    strength_from_guesses_log10((bits - 1.0) * std::f64::consts::LOG10_2)
+}
+
+/// zxcvbn-style score (0-4) for a strength on the 0-100 scale
+pub fn strength_bucket(strength: u8) -> u8 {
+    (strength / 20).min(4)
+}
+
+/// Version of the scoring above. Bump it whenever stored strengths would change,
+/// so they get recalculated after the upgrade.
+pub const STRENGTH_ALGORITHM_VERSION: u32 = 1;
+
+/// Credentials rescored per batch by the recalculation job
+pub const STRENGTH_RECALC_BATCH: usize = 50;
+
+/// New strength for a credential, written only if it is still at `revision`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrengthUpdate {
+    pub uuid: String,
+    pub revision: u64,
+    pub strength: u8,
+}
+
+/// Progress of the strength recalculation job, emitted after each batch
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StrengthRecalcProgress {
+    /// Credentials looked at so far
+    pub processed: usize,
+    /// Credentials the job started with
+    pub total: usize,
+    /// Credentials whose stored strength changed
+    pub changed: usize,
+    /// Credentials that could not be decrypted
+    pub failed: usize,
+}
+
+/// Most entries allowed per word list; every strength estimate scans them
+pub const MAX_WORDLIST_ENTRIES: usize = 100_000;
+
+/// Word lists loaded by the user, stored encrypted in the vault
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Wordlists {
+    /// Passwords that score 0 and fail the master password policy, compared case-insensitively
+    pub banned_passwords: Vec<String>,
+    /// Extra words (company names, local words) that zxcvbn treats as easy to guess
+    pub dictionary: Vec<String>,
+}
+
+impl Wordlists {
+    /// Trimmed, lowercased, sorted and deduplicated copies of both lists
+    pub fn normalized(&self) -> AppResult<Self> {
+        let normalize = |list: &[String], name: &str| -> AppResult<Vec<String>> {
+            let mut entries: Vec<String> = list
+                .iter()
+                .map(|entry| entry.trim().to_lowercase())
+                .filter(|entry| !entry.is_empty())
+                .collect();
+            entries.sort();
+            entries.dedup();
+            if entries.len() > MAX_WORDLIST_ENTRIES {
+                return Err(AppError::InvalidInput(format!(
+                    "The {} list has {} entries; at most {} are allowed",
+                    name,
+                    entries.len(),
+                    MAX_WORDLIST_ENTRIES
+                )));
+            }
+            Ok(entries)
+        };
+        Ok(Self {
+            banned_passwords: normalize(&self.banned_passwords, "banned password")?,
+            dictionary: normalize(&self.dictionary, "dictionary")?,
+        })
+    }
+
+    /// Whether `password` is on the banned list
+    pub fn is_banned(&self, password: &str) -> bool {
+        let password = password.trim().to_lowercase();
+        self.banned_passwords
+            .iter()
+            .any(|banned| banned.trim().to_lowercase() == password)
+    }
+
+    fn dictionary_words(&self) -> Vec<&str> {
+        self.dictionary.iter().map(String::as_str).collect()
+    }
+}
+
+/// Words specific to this app that make a master password easy to guess
+const APP_DICTIONARY: &[&str] = &["secret", "plan", "secretplan", "secret-plan", "vault"];
+
+/// Requirements a master password must meet
+pub const MASTER_PASSWORD_MIN_LENGTH: usize = 12;
+/// Minimum zxcvbn score (0-4) for a master password
+pub const MASTER_PASSWORD_MIN_SCORE: u8 = 3;
+
+/// Strength report for a proposed master password
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MasterPasswordReport {
+    /// zxcvbn score from 0 (terrible) to 4 (great)
+    pub score: u8,
+    /// Estimated guesses needed, as a power of ten
+    pub guesses_log10: f64,
+    /// Human-readable time to crack offline against a slow hash (like Argon2)
+    pub crack_time_offline: String,
+    /// Human-readable time to crack through a throttled online service
+    pub crack_time_online: String,
+    /// Main problem, if any
+    pub warning: Option<String>,
+    /// How to improve the password
+    pub suggestions: Vec<String>,
+    /// Whether the password satisfies the policy
+    pub meets_policy: bool,
+    /// Unmet policy requirements, empty when `meets_policy` is true
+    pub policy_failures: Vec<String>,
+}
+
+/// Analyzes a master password candidate without storing anything.
+/// `user_inputs` (username, email, ...) are penalized like dictionary words,
+/// and so are the words in `wordlists`; banned passwords fail the policy.
+pub fn analyze_master_password(
+    candidate: &str,
+    user_inputs: &[&str],
+    wordlists: &Wordlists,
+) -> MasterPasswordReport {
+    // Split emails so "alice@example.com" also penalizes "alice" and "example"
+    let mut dictionary: Vec<&str> = APP_DICTIONARY.to_vec();
+    dictionary.extend(wordlists.dictionary_words());
+    for input in user_inputs {
+        dictionary.push(input);
+        dictionary.extend(
+            input
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|part| part.len() >= 3),
+        );
+    }
+
+    let estimate = zxcvbn(candidate, &dictionary);
+    let score = u8::from(estimate.score());
+    let crack_times = estimate.crack_times();
+    let feedback = estimate.feedback();
+
+    let mut policy_failures = Vec::new();
+    if candidate.chars().count() < MASTER_PASSWORD_MIN_LENGTH {
+        policy_failures.push(format!(
+            "Use at least {} characters",
+            MASTER_PASSWORD_MIN_LENGTH
+        ));
+    }
+    if score < MASTER_PASSWORD_MIN_SCORE {
+        policy_failures.push("Choose a password that is harder to guess".to_string());
+    }
+    if wordlists.is_banned(candidate) {
+        policy_failures.push("This password is on the banned list".to_string());
+    }
+
+    MasterPasswordReport {
+        score,
+        guesses_log10: estimate.guesses_log10(),
+        crack_time_offline: crack_times
+            .offline_slow_hashing_1e4_per_second()
+            .to_string(),
+        crack_time_online: crack_times.online_throttling_100_per_hour().to_string(),
+        warning: feedback.and_then(|f| f.warning()).map(|w| w.to_string()),
+        suggestions: feedback
+            .map(|f| f.suggestions().iter().map(ToString::to_string).collect())
+            .unwrap_or_default(),
+        meets_policy: policy_failures.is_empty(),
+        policy_failures,
     }
 }
@@ -0,0 +1,143 @@
+//! Operations with keys derived from the vault key.
+//!
+//! Each operation takes the vault key and derives what it needs itself: a
+//! subkey for a context, the vault's signing or member key, or a collection
+//! key unwrapped with the member key. Only the result is returned, never the
+//! key. `CryptoService` runs these in the app, or in an ephemeral session has
+//! the worker run them, see `session`, so derived keys exist only where the
+//! vault key does.
+
+use aes_gcm::{Aes256Gcm, Key};
+use ed25519_dalek::{Signature, Signer, VerifyingKey};
+use x25519_dalek::PublicKey;
+use zeroize::Zeroizing;
+
+use crate::audit_export;
+use crate::collections::{self, WrappedKey};
+use crate::crypto::{self, CryptoService};
+use crate::error::AppResult;
+
+fn subkey(key: &Key<Aes256Gcm>, context: &[u8]) -> AppResult<Zeroizing<[u8; 32]>> {
+    crypto::subkey(key, context).map(Zeroizing::new)
+}
+
+/// Encrypts with the subkey for `context` into an `EncryptedContainer`
+pub fn seal(
+    key: &Key<Aes256Gcm>,
+    context: &[u8],
+    plaintext: &[u8],
+    aad: &[u8],
+) -> AppResult<String> {
+    let subkey = subkey(key, context)?;
+    CryptoService::encrypt_with_key(&subkey, plaintext, aad)
+}
+
+/// Decrypts a container made by `seal` with the same context
+pub fn open(
+    key: &Key<Aes256Gcm>,
+    context: &[u8],
+    container: &str,
+    aad: &[u8],
+) -> AppResult<Vec<u8>> {
+    let subkey = subkey(key, context)?;
+    CryptoService::decrypt_with_key(&subkey, container, aad)
+}
+
+/// HMAC-SHA256 of each message under the subkey for `context`
+pub fn mac(key: &Key<Aes256Gcm>, context: &[u8], messages: &[Vec<u8>]) -> AppResult<Vec<[u8; 32]>> {
+    let subkey = subkey(key, context)?;
+    let subkey = Key::<Aes256Gcm>::from_slice(subkey.as_slice());
+    messages
+        .iter()
+        .map(|message| crypto::subkey(subkey, message))
+        .collect()
+}
+
+/// Signs `message` with the vault's audit signing key
+pub fn sign(key: &Key<Aes256Gcm>, message: &[u8]) -> AppResult<Signature> {
+    let seed = subkey(key, audit_export::SIGNING_KEY_CONTEXT)?;
+    Ok(audit_export::signing_key(&seed).sign(message))
+}
+
+/// Public half of the vault's audit signing key
+pub fn signing_public_key(key: &Key<Aes256Gcm>) -> AppResult<VerifyingKey> {
+    let seed = subkey(key, audit_export::SIGNING_KEY_CONTEXT)?;
+    Ok(audit_export::signing_key(&seed).verifying_key())
+}
+
+/// Public half of the vault's X25519 member key
+pub fn member_public_key(key: &Key<Aes256Gcm>) -> AppResult<PublicKey> {
+    let seed = subkey(key, collections::MEMBER_KEY_CONTEXT)?;
+    Ok(PublicKey::from(&collections::member_secret(*seed)))
+}
+
+/// A collection key wrapped for this vault's member key
+fn collection_key(
+    key: &Key<Aes256Gcm>,
+    wrapped: &WrappedKey,
+    collection_id: &str,
+) -> AppResult<Zeroizing<[u8; 32]>> {
+    let seed = subkey(key, collections::MEMBER_KEY_CONTEXT)?;
+    let secret = collections::member_secret(*seed);
+    collections::unwrap_key(wrapped, &secret, collection_id).map(Zeroizing::new)
+}
+
+/// A fresh collection key, wrapped for this vault's member key
+pub fn new_collection_key(key: &Key<Aes256Gcm>, collection_id: &str) -> AppResult<WrappedKey> {
+    let collection_key = Zeroizing::new(collections::generate_collection_key());
+    collections::wrap_key(&collection_key, &member_public_key(key)?, collection_id)
+}
+
+/// The collection key in `wrapped`, wrapped again for `recipient`
+pub fn rewrap_collection_key(
+    key: &Key<Aes256Gcm>,
+    wrapped: &WrappedKey,
+    collection_id: &str,
+    recipient: &PublicKey,
+) -> AppResult<WrappedKey> {
+    let collection_key = collection_key(key, wrapped, collection_id)?;
+    collections::wrap_key(&collection_key, recipient, collection_id)
+}
+
+/// Encrypts with the collection key in `wrapped`
+pub fn seal_collection(
+    key: &Key<Aes256Gcm>,
+    wrapped: &WrappedKey,
+    collection_id: &str,
+    plaintext: &[u8],
+    aad: &[u8],
+) -> AppResult<String> {
+    let collection_key = collection_key(key, wrapped, collection_id)?;
+    CryptoService::encrypt_with_key(&collection_key, plaintext, aad)
+}
+
+/// Decrypts with the collection key in `wrapped`
+pub fn open_collection(
+    key: &Key<Aes256Gcm>,
+    wrapped: &WrappedKey,
+    collection_id: &str,
+    container: &str,
+    aad: &[u8],
+) -> AppResult<Vec<u8>> {
+    let collection_key = collection_key(key, wrapped, collection_id)?;
+    CryptoService::decrypt_with_key(&collection_key, container, aad)
+}
+
+/// Re-encrypts a container from the collection key in `from` to the one in
+/// `to`, None if it is under `to` already. Fails if neither key opens it.
+pub fn recrypt_collection(
+    key: &Key<Aes256Gcm>,
+    from: &WrappedKey,
+    to: &WrappedKey,
+    collection_id: &str,
+    container: &str,
+    aad: &[u8],
+) -> AppResult<Option<String>> {
+    let to_key = collection_key(key, to, collection_id)?;
+    if CryptoService::decrypt_with_key(&to_key, container, aad).is_ok() {
+        return Ok(None);
+    }
+    let from_key = collection_key(key, from, collection_id)?;
+    let plaintext = Zeroizing::new(CryptoService::decrypt_with_key(&from_key, container, aad)?);
+    CryptoService::encrypt_with_key(&to_key, &plaintext, aad).map(Some)
+}
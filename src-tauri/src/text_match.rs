@@ -0,0 +1,129 @@
+//! How the free text of a search is matched against items.
+//!
+//! By default the text must appear in the site, username or a tag, ignoring
+//! ASCII case, exactly like the SQL `LIKE` the repository runs. Two other modes
+//! are matched in memory after the query:
+//!
+//! - fuzzy: also accepts near misses, so `gogle` finds `google.com`. A field
+//!   matches if it shares enough trigrams with the text, or if one of its words
+//!   is within a few edits of it.
+//! - regex: the text is a regular expression, searched case-insensitively.
+//!   The `regex` crate matches in time linear in the input and never
+//!   backtracks, so a pattern cannot run away; what is bounded instead is the
+//!   length of the pattern and the size it compiles to.
+
+use std::collections::HashSet;
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+/// Longest regular expression accepted, in bytes
+pub const MAX_REGEX_BYTES: usize = 256;
+/// Limit on the compiled form of a regular expression
+const REGEX_SIZE_LIMIT: usize = 256 * 1024;
+/// Limit on the cache the regex engine builds while matching
+const REGEX_DFA_SIZE_LIMIT: usize = 1024 * 1024;
+/// Share of trigrams two strings need in common to match fuzzily
+pub const MIN_TRIGRAM_SIMILARITY: f64 = 0.5;
+
+/// How the free text of a search is matched
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    #[default]
+    Substring,
+    Fuzzy,
+    Regex,
+}
+
+/// The free text of a search, ready to match
+#[derive(Debug, Clone)]
+pub enum TermMatcher {
+    /// Lowercased
+    Substring(String),
+    /// Lowercased
+    Fuzzy(String),
+    Regex(Regex),
+}
+
+impl TermMatcher {
+    /// Fails for a regular expression that is invalid or too large
+    pub fn new(term: &str, mode: MatchMode) -> AppResult<Self> {
+        Ok(match mode {
+            MatchMode::Substring => TermMatcher::Substring(term.to_ascii_lowercase()),
+            MatchMode::Fuzzy => TermMatcher::Fuzzy(term.trim().to_lowercase()),
+            MatchMode::Regex => {
+                if term.len() > MAX_REGEX_BYTES {
+                    return Err(AppError::InvalidInput(format!(
+                        "Regular expressions are limited to {} bytes",
+                        MAX_REGEX_BYTES
+                    )));
+                }
+                let regex = RegexBuilder::new(term)
+                    .case_insensitive(true)
+                    .size_limit(REGEX_SIZE_LIMIT)
+                    .dfa_size_limit(REGEX_DFA_SIZE_LIMIT)
+                    .build()
+                    .map_err(|e| {
+                        AppError::InvalidInput(format!("Invalid regular expression: {}", e))
+                    })?;
+                TermMatcher::Regex(regex)
+            }
+        })
+    }
+
+    pub fn is_match(&self, haystack: &str) -> bool {
+        match self {
+            TermMatcher::Substring(needle) => haystack.to_ascii_lowercase().contains(needle),
+            TermMatcher::Fuzzy(needle) => fuzzy_match(needle, &haystack.to_lowercase()),
+            TermMatcher::Regex(regex) => regex.is_match(haystack),
+        }
+    }
+
+    /// Whether any of the fields the free text is searched in match
+    pub fn matches_fields(&self, site: &str, username: &str, tags: &[String]) -> bool {
+        self.is_match(site) || self.is_match(username) || tags.iter().any(|tag| self.is_match(tag))
+    }
+}
+
+/// Edits allowed between the text and a word of a field
+fn max_edits(needle: &str) -> usize {
+    match needle.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    let padded: Vec<char> = format!("  {} ", text).chars().collect();
+    padded.windows(3).map(|w| [w[0], w[1], w[2]]).collect()
+}
+
+/// Jaccard similarity of the trigrams of `a` and `b`, from 0 to 1
+pub fn trigram_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (trigrams(a), trigrams(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// Both arguments lowercased
+fn fuzzy_match(needle: &str, haystack: &str) -> bool {
+    if needle.is_empty() || haystack.contains(needle) {
+        return true;
+    }
+    if trigram_similarity(needle, haystack) >= MIN_TRIGRAM_SIMILARITY {
+        return true;
+    }
+    let edits = max_edits(needle);
+    edits > 0
+        && haystack
+            .split(|c: char| !c.is_alphanumeric())
+            .chain([haystack])
+            .any(|word| strsim::levenshtein(needle, word) <= edits)
+}
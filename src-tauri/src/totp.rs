@@ -0,0 +1,308 @@
+//! Time-based one-time passwords (RFC 6238).
+//!
+//! Items store their TOTP setup as entered: either a bare base32 secret or an
+//! `otpauth://totp/...` URI as exported by authenticator apps. Codes are only
+//! computed to check what the user typed, so a newly imported secret can be
+//! confirmed to work before it is removed from the old authenticator.
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+
+use crate::error::{AppError, AppResult};
+
+/// Time steps on either side of the current one that still count as valid,
+/// to allow for clock drift between this device and the user's authenticator
+pub const TOTP_DRIFT_STEPS: i64 = 1;
+
+/// Secrets shorter than this (80 bits) are refused on import
+pub const MIN_SECRET_BYTES: usize = 10;
+/// RFC 4226 requires at least 128 bits; shorter secrets are imported with a warning
+pub const RECOMMENDED_SECRET_BYTES: usize = 16;
+
+const DEFAULT_DIGITS: u32 = 6;
+const DEFAULT_PERIOD: u64 = 30;
+const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// HMAC algorithm the codes are derived with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TotpAlgorithm {
+    #[default]
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+/// Parameters of a TOTP setup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TotpConfig {
+    pub secret: Vec<u8>,
+    pub digits: u32,
+    /// Seconds per time step
+    pub period: u64,
+    pub algorithm: TotpAlgorithm,
+}
+
+impl TotpAlgorithm {
+    /// Name used in otpauth URIs
+    pub fn name(self) -> &'static str {
+        match self {
+            TotpAlgorithm::Sha1 => "SHA1",
+            TotpAlgorithm::Sha256 => "SHA256",
+            TotpAlgorithm::Sha512 => "SHA512",
+        }
+    }
+}
+
+/// Outcome of checking a code the user entered
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TotpValidation {
+    pub valid: bool,
+    /// Steps between the matching code and the current one: -1 means the
+    /// authenticator is behind, 1 that it is ahead. None if nothing matched.
+    pub drift_steps: Option<i64>,
+}
+
+impl TotpConfig {
+    /// Parses a bare base32 secret or an `otpauth://totp/` URI
+    pub fn parse(input: &str) -> AppResult<Self> {
+        let input = input.trim();
+        let Some(rest) = strip_prefix_ignore_case(input, "otpauth://") else {
+            return Ok(Self {
+                secret: decode_base32(input)?,
+                digits: DEFAULT_DIGITS,
+                period: DEFAULT_PERIOD,
+                algorithm: TotpAlgorithm::Sha1,
+            });
+        };
+        if strip_prefix_ignore_case(rest, "totp/").is_none() {
+            return Err(AppError::InvalidInput(
+                "Only time-based (totp) otpauth URIs are supported".to_string(),
+            ));
+        }
+
+        let mut secret = None;
+        let mut digits = DEFAULT_DIGITS;
+        let mut period = DEFAULT_PERIOD;
+        let mut algorithm = TotpAlgorithm::Sha1;
+        let query = rest.split_once('?').map(|(_, query)| query).unwrap_or("");
+        for pair in query.split('&') {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            match name.to_ascii_lowercase().as_str() {
+                "secret" => secret = Some(decode_base32(value)?),
+                "digits" => digits = parse_param(name, value)?,
+                "period" => period = parse_param(name, value)?,
+                "algorithm" => {
+                    algorithm = match value.to_ascii_uppercase().as_str() {
+                        "SHA1" => TotpAlgorithm::Sha1,
+                        "SHA256" => TotpAlgorithm::Sha256,
+                        "SHA512" => TotpAlgorithm::Sha512,
+                        _ => {
+                            return Err(AppError::InvalidInput(format!(
+                                "Unsupported TOTP algorithm: {}",
+                                value
+                            )))
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let secret = secret
+            .ok_or_else(|| AppError::InvalidInput("The otpauth URI has no secret".to_string()))?;
+        if !(6..=8).contains(&digits) || period == 0 {
+            return Err(AppError::InvalidInput(
+                "TOTP codes must have 6 to 8 digits and a non-zero period".to_string(),
+            ));
+        }
+        Ok(Self {
+            secret,
+            digits,
+            period,
+            algorithm,
+        })
+    }
+
+    /// The setup in canonical form: uppercase base32 without padding for a bare
+    /// secret, or an otpauth URI with the label and issuer kept and every
+    /// parameter spelled out
+    pub fn normalize(input: &str) -> AppResult<String> {
+        let config = Self::parse(input)?;
+        let input = input.trim();
+        let Some(rest) = strip_prefix_ignore_case(input, "otpauth://totp/") else {
+            return Ok(encode_base32(&config.secret));
+        };
+        let (label, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let mut uri = format!(
+            "otpauth://totp/{}?secret={}",
+            label,
+            encode_base32(&config.secret)
+        );
+        if let Some((_, issuer)) = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(name, _)| name.eq_ignore_ascii_case("issuer"))
+        {
+            uri.push_str(&format!("&issuer={}", issuer));
+        }
+        uri.push_str(&format!(
+            "&algorithm={}&digits={}&period={}",
+            config.algorithm.name(),
+            config.digits,
+            config.period
+        ));
+        Ok(uri)
+    }
+
+    /// The code for the time step containing `unix_time`
+    pub fn code_at(&self, unix_time: i64) -> String {
+        self.code_for_step(unix_time.div_euclid(self.period as i64))
+    }
+
+    /// Checks `code` against the current step and `TOTP_DRIFT_STEPS` either side
+    pub fn validate(&self, code: &str, unix_time: i64) -> TotpValidation {
+        let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+        let current = unix_time.div_euclid(self.period as i64);
+        // Nearest steps first, so an exact match is reported as no drift
+        let drift_steps = std::iter::once(0)
+            .chain((1..=TOTP_DRIFT_STEPS).flat_map(|step| [-step, step]))
+            .find(|offset| codes_match(&self.code_for_step(current + offset), &code));
+        TotpValidation {
+            valid: drift_steps.is_some(),
+            drift_steps,
+        }
+    }
+
+    fn code_for_step(&self, step: i64) -> String {
+        let counter = (step as u64).to_be_bytes();
+        let digest = match self.algorithm {
+            TotpAlgorithm::Sha1 => hmac_digest::<Hmac<Sha1>>(&self.secret, &counter),
+            TotpAlgorithm::Sha256 => hmac_digest::<Hmac<Sha256>>(&self.secret, &counter),
+            TotpAlgorithm::Sha512 => hmac_digest::<Hmac<Sha512>>(&self.secret, &counter),
+        };
+        // Dynamic truncation, RFC 4226 section 5.3
+        let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([
+            digest[offset] & 0x7f,
+            digest[offset + 1],
+            digest[offset + 2],
+            digest[offset + 3],
+        ]);
+        format!(
+            "{:0width$}",
+            binary % 10u32.pow(self.digits),
+            width = self.digits as usize
+        )
+    }
+}
+
+fn hmac_digest<M: Mac + hmac::digest::KeyInit>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = <M as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Compares without stopping at the first differing digit
+fn codes_match(expected: &str, entered: &str) -> bool {
+    expected.len() == entered.len()
+        && expected
+            .bytes()
+            .zip(entered.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = text.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &text[prefix.len()..])
+}
+
+fn parse_param<T: std::str::FromStr>(name: &str, value: &str) -> AppResult<T> {
+    value
+        .parse()
+        .map_err(|_| AppError::InvalidInput(format!("Invalid TOTP {}: {}", name, value)))
+}
+
+/// A TOTP setup checked for import
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedTotp {
+    /// The setup in the form `TotpConfig::normalize` gives
+    pub value: String,
+    /// Set when the secret is shorter than `RECOMMENDED_SECRET_BYTES`
+    pub warning: Option<String>,
+}
+
+/// Checks an imported TOTP setup. Malformed setups and secrets shorter than
+/// `MIN_SECRET_BYTES` are refused, since they would only fail once a code is needed.
+pub fn check_import(input: &str) -> AppResult<ImportedTotp> {
+    let config = TotpConfig::parse(input)?;
+    let bits = config.secret.len() * 8;
+    if config.secret.len() < MIN_SECRET_BYTES {
+        return Err(AppError::InvalidInput(format!(
+            "The TOTP secret has only {} bits; at least {} are needed",
+            bits,
+            MIN_SECRET_BYTES * 8
+        )));
+    }
+    let warning = (config.secret.len() < RECOMMENDED_SECRET_BYTES).then(|| {
+        format!(
+            "The TOTP secret has only {} bits; {} are recommended",
+            bits,
+            RECOMMENDED_SECRET_BYTES * 8
+        )
+    });
+    Ok(ImportedTotp {
+        value: TotpConfig::normalize(input)?,
+        warning,
+    })
+}
+
+fn encode_base32(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        encoded.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    encoded
+}
+
+/// Decodes RFC 4648 base32, ignoring case, spaces, dashes and padding
+fn decode_base32(input: &str) -> AppResult<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(input.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in input.chars().filter(|c| !matches!(c, ' ' | '-' | '=')) {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a as char == c.to_ascii_uppercase())
+            .ok_or_else(|| {
+                AppError::InvalidInput("The TOTP secret is not valid base32".to_string())
+            })?;
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    if bytes.is_empty() {
+        return Err(AppError::InvalidInput(
+            "The TOTP secret is empty".to_string(),
+        ));
+    }
+    Ok(bytes)
+}
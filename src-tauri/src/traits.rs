@@ -1,21 +1,123 @@
+//! Dependency-injection seams for `VaultManager`.
+//!
+//! `VaultManager` never talks to SQLite (or any other backend) directly; it only
+//! holds `Arc<dyn Trait>` handles to the traits below. A new storage backend is
+//! added by implementing these traits and passing the instances to
+//! `VaultManager::new`, the same way `SqliteRepository` is wired up in `lib.rs`.
+//!
+//! In tests, `mockall` generates a `Mock*` type for every trait (e.g.
+//! `MockCredentialRepository`), so vault logic can be exercised without a database.
+//!
+//! The `Async*` traits are the same operations for callers on the async runtime.
+//! Synchronous backends get them for free through `blocking::BlockingRepository`;
+//! a server-backed repository implements them directly.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use chrono::{DateTime, Utc};
+
+use crate::audit_archive::StoredAuditArchive;
+use crate::breach_list::BreachListSource;
+use crate::collections::{Collection, CollectionMember};
+use crate::compact::{CompactReport, ProgressSink};
+use crate::crypto::SubkeyCipher;
+use crate::devices::Device;
+use crate::emergency::EmergencyContact;
 use crate::error::AppResult;
-use crate::models::{AuditLogEntry, BreachState, Credential};
+use crate::importer::StoredImportConflict;
+use crate::journal::JournalEntry;
+use crate::models::{AuditLogEntry, BreachState, Credential, NetworkSettings};
+use crate::shares::ShareRecord;
+use crate::stats::SecuritySnapshot;
+use crate::strength::{StrengthUpdate, Wordlists};
+use crate::travel::StashedCredential;
 use crate::vault::CredentialFilter;
+use crate::watch::ExternalChange;
+
+#[cfg(test)]
+use mockall::automock;
 
 // Trait for managing credentials
 // Needs Send + Sync bounds if used with Arc<Mutex<dyn ...>> across threads
+#[cfg_attr(test, automock)]
 pub trait CredentialRepository: Send + Sync {
     fn add_credential(&self, credential: &Credential, strength: u8) -> AppResult<()>;
+    // Fails with AppError::Conflict if `credential.revision` is no longer the stored one
     fn update_credential(&self, credential: &Credential) -> AppResult<()>;
     // Returns site name for audit log upon successful deletion
     fn delete_credential(&self, uuid: &str) -> AppResult<String>;
     fn get_credential(&self, uuid: &str) -> AppResult<Credential>;
     fn list_credentials(&self, filter: Option<CredentialFilter>) -> AppResult<Vec<Credential>>;
     fn update_breach_state(&self, uuid: &str, state: BreachState) -> AppResult<()>;
+    // Writes recalculated strengths, skipping items changed since their revision was read.
+    // Returns how many were written.
+    fn update_strengths(&self, updates: &[StrengthUpdate]) -> AppResult<usize>;
     fn credential_exists(&self, uuid: &str) -> AppResult<bool>;
+    // Records that the item's secret was used; not an edit, so the revision stays
+    fn mark_used(&self, uuid: &str, at: DateTime<Utc>) -> AppResult<()>;
+    // Sets `has_totp` without counting as an edit, for items stored before it was tracked
+    fn set_has_totp(&self, uuid: &str, has_totp: bool) -> AppResult<()>;
+}
+
+// Repository calls that can be grouped with `TransactionalRepository::with_transaction`.
+// Same semantics as the `CredentialRepository` and `AuditLogger` methods of the same name.
+pub trait RepositoryTransaction {
+    fn add_credential(&self, credential: &Credential, strength: u8) -> AppResult<()>;
+    fn update_credential(&self, credential: &Credential) -> AppResult<()>;
+    fn delete_credential(&self, uuid: &str) -> AppResult<String>;
+    fn get_credential(&self, uuid: &str) -> AppResult<Credential>;
+    fn credential_exists(&self, uuid: &str) -> AppResult<bool>;
+    fn update_breach_state(&self, uuid: &str, state: BreachState) -> AppResult<()>;
+    fn add_log(&self, action: &str, item_uuid: Option<&str>) -> AppResult<i64>;
+}
+
+// Work run inside a transaction; implemented for closures
+pub trait TransactionBody {
+    fn run(&mut self, txn: &dyn RepositoryTransaction) -> AppResult<()>;
+}
+
+impl<F> TransactionBody for F
+where
+    F: FnMut(&dyn RepositoryTransaction) -> AppResult<()>,
+{
+    fn run(&mut self, txn: &dyn RepositoryTransaction) -> AppResult<()> {
+        self(txn)
+    }
+}
+
+// Trait for composing several repository writes atomically
+#[cfg_attr(test, automock)]
+pub trait TransactionalRepository: Send + Sync {
+    // Commits if `body` returns Ok and rolls every write back otherwise
+    fn with_transaction(&self, body: &mut dyn TransactionBody) -> AppResult<()>;
+}
+
+// Future returned by the async repository traits
+pub type RepoFuture<'a, T> = Pin<Box<dyn Future<Output = AppResult<T>> + Send + 'a>>;
+
+// Async counterpart of `CredentialRepository`
+#[cfg_attr(test, automock)]
+pub trait AsyncCredentialRepository: Send + Sync {
+    fn add_credential(&self, credential: Credential, strength: u8) -> RepoFuture<'_, ()>;
+    fn update_credential(&self, credential: Credential) -> RepoFuture<'_, ()>;
+    fn delete_credential(&self, uuid: String) -> RepoFuture<'_, String>;
+    fn get_credential(&self, uuid: String) -> RepoFuture<'_, Credential>;
+    fn list_credentials(&self, filter: Option<CredentialFilter>)
+        -> RepoFuture<'_, Vec<Credential>>;
+    fn update_breach_state(&self, uuid: String, state: BreachState) -> RepoFuture<'_, ()>;
+    fn credential_exists(&self, uuid: String) -> RepoFuture<'_, bool>;
+}
+
+// Async counterpart of `AuditLogger`
+#[cfg_attr(test, automock)]
+pub trait AsyncAuditLogger: Send + Sync {
+    fn add_log(&self, action: String, item_uuid: Option<String>) -> RepoFuture<'_, i64>;
+    fn get_logs(&self, limit: Option<i64>) -> RepoFuture<'_, Vec<AuditLogEntry>>;
 }
 
 // Trait for managing application settings
+#[cfg_attr(test, automock)]
 pub trait SettingsRepository: Send + Sync {
     // Returns Option<(nonce, encrypted_settings)> or None if not found
     fn get_encrypted_settings(&self) -> AppResult<Option<(Vec<u8>, Vec<u8>)>>;
@@ -23,15 +125,229 @@ pub trait SettingsRepository: Send + Sync {
     fn save_encrypted_settings(&self, nonce: &[u8], encrypted_settings: &[u8]) -> AppResult<()>;
     fn get_master_password_hash(&self) -> AppResult<Option<String>>;
     fn save_master_password_hash(&self, hash: &str) -> AppResult<()>;
+    // Stores the hash of a new vault together with its first settings, all or nothing.
+    // Fails with AppError::Conflict if the vault already has a master password.
+    fn create_master_password(
+        &self,
+        hash: &str,
+        nonce: &[u8],
+        encrypted_settings: &[u8],
+    ) -> AppResult<()>;
+    // Unencrypted UI preferences as JSON, readable while the vault is locked
+    fn get_ui_preferences(&self) -> AppResult<Option<String>>;
+    fn save_ui_preferences(&self, preferences_json: &str) -> AppResult<()>;
+    // Unencrypted storage profile as JSON, needed to open the vault before unlock
+    fn get_storage_profile(&self) -> AppResult<Option<String>>;
+    fn save_storage_profile(&self, profile_json: &str) -> AppResult<()>;
+    // Custom word lists as an encrypted container (see `CryptoService::encrypt`)
+    fn get_encrypted_wordlists(&self) -> AppResult<Option<String>>;
+    fn save_encrypted_wordlists(&self, container: &str) -> AppResult<()>;
+    // Email breach monitor state as an encrypted container; None until the first check
+    fn get_encrypted_email_monitor(&self) -> AppResult<Option<String>>;
+    fn save_encrypted_email_monitor(&self, container: &str) -> AppResult<()>;
+    // Logins captured in the browser and not yet approved, as an encrypted container
+    fn get_encrypted_pending_saves(&self) -> AppResult<Option<String>>;
+    fn save_encrypted_pending_saves(&self, container: &str) -> AppResult<()>;
+    // `STRENGTH_ALGORITHM_VERSION` the stored strengths were last calculated with
+    fn get_strength_version(&self) -> AppResult<Option<u32>>;
+    fn save_strength_version(&self, version: u32) -> AppResult<()>;
+    // Free-form entries for subsystems that need a little storage of their own.
+    // Callers encrypt `value`; keys used internally are refused with AppError::InvalidInput.
+    fn get_meta(&self, key: &str) -> AppResult<Option<Vec<u8>>>;
+    fn put_meta(&self, key: &str, value: &[u8]) -> AppResult<()>;
 }
 
 // Trait for logging audit events
+// The explicit lifetime on `item_uuid` is required for `automock`
+#[cfg_attr(test, automock)]
 pub trait AuditLogger: Send + Sync {
-    fn add_log(&self, action: &str, item_uuid: Option<&str>) -> AppResult<i64>;
+    #[allow(clippy::needless_lifetimes)]
+    fn add_log<'a>(&self, action: &str, item_uuid: Option<&'a str>) -> AppResult<i64>;
     fn get_logs(&self, limit: Option<i64>) -> AppResult<Vec<AuditLogEntry>>;
 }
 
+// Old audit entries rolled into encrypted segments, see `audit_archive`
+#[cfg_attr(test, automock)]
+pub trait AuditArchiveRepository: Send + Sync {
+    // Up to `limit` audit entries older than `before`, oldest first
+    fn audit_entries_before(
+        &self,
+        before: DateTime<Utc>,
+        limit: usize,
+    ) -> AppResult<Vec<AuditLogEntry>>;
+    // Stores a segment and removes the audit entries `entry_ids` it holds, all
+    // or nothing. Returns the segment's id.
+    fn add_audit_archive(&self, archive: &StoredAuditArchive, entry_ids: &[i64]) -> AppResult<i64>;
+    // Every segment, oldest first
+    fn list_audit_archives(&self) -> AppResult<Vec<StoredAuditArchive>>;
+}
+
+// Trait for whole-database maintenance operations
+#[cfg_attr(test, automock)]
+pub trait MaintenanceRepository: Send + Sync {
+    // A consistent copy of the database file, held in memory only
+    fn snapshot(&self) -> AppResult<Vec<u8>>;
+    // Rebuilds the database without free pages; with `wipe`, the old file is
+    // overwritten before the compacted copy replaces it
+    fn compact(&self, wipe: bool, progress: &dyn ProgressSink) -> AppResult<CompactReport>;
+    // SQLite's integrity check: a single "ok", or one line per problem found
+    fn integrity_check(&self) -> AppResult<Vec<String>>;
+    // Schema version recorded in the database file
+    fn schema_version(&self) -> AppResult<u32>;
+    // Sets the size of the page cache in KiB
+    fn set_cache_size(&self, kib: u32) -> AppResult<()>;
+    // Moves the write-ahead log into the database and truncates it. Returns the
+    // pages moved, None if the database does not use a write-ahead log.
+    fn checkpoint(&self) -> AppResult<Option<u64>>;
+    // Lets SQLite refresh the statistics its query planner uses
+    fn optimize(&self) -> AppResult<()>;
+}
+
+// Trait for storing periodic security snapshots
+#[cfg_attr(test, automock)]
+pub trait StatsRepository: Send + Sync {
+    fn add_snapshot(&self, snapshot: &SecuritySnapshot) -> AppResult<()>;
+    // Snapshots taken at or after `since` (all if None), oldest first
+    fn list_snapshots(&self, since: Option<DateTime<Utc>>) -> AppResult<Vec<SecuritySnapshot>>;
+    fn latest_snapshot_at(&self) -> AppResult<Option<DateTime<Utc>>>;
+}
+
+// Trait for shared collections and their per-member wrapped keys
+#[cfg_attr(test, automock)]
+pub trait CollectionRepository: Send + Sync {
+    // Stores a new collection together with its members
+    fn create_collection(&self, collection: &Collection) -> AppResult<()>;
+    fn get_collection(&self, id: &str) -> AppResult<Collection>;
+    fn list_collections(&self) -> AppResult<Vec<Collection>>;
+    // Inserts a member or replaces their wrapped key
+    fn save_member(&self, collection_id: &str, member: &CollectionMember) -> AppResult<()>;
+    fn remove_member(&self, collection_id: &str, public_key: &str) -> AppResult<()>;
+    // Deletes the collection and its members; items must be moved out first
+    fn delete_collection(&self, id: &str) -> AppResult<()>;
+}
+
+// Trait for the local records of one-time shares
+#[cfg_attr(test, automock)]
+pub trait ShareRepository: Send + Sync {
+    fn save_share(&self, share: &ShareRecord) -> AppResult<()>;
+    fn get_share(&self, id: &str) -> AppResult<ShareRecord>;
+    // Newest first
+    fn list_shares(&self) -> AppResult<Vec<ShareRecord>>;
+    fn mark_share_revoked(&self, id: &str, revoked_at: DateTime<Utc>) -> AppResult<()>;
+    // Removes the records of shares that expired before `before`; returns how many
+    fn remove_expired_shares(&self, before: DateTime<Utc>) -> AppResult<usize>;
+}
+
+// Trait for emergency access contacts and their wrapped vault keys
+#[cfg_attr(test, automock)]
+pub trait EmergencyRepository: Send + Sync {
+    // Inserts a contact or replaces its stored state
+    fn save_contact(&self, contact: &EmergencyContact) -> AppResult<()>;
+    fn get_contact(&self, id: &str) -> AppResult<EmergencyContact>;
+    fn list_contacts(&self) -> AppResult<Vec<EmergencyContact>>;
+}
+
+// Trait for the devices that have opened the vault
+#[cfg_attr(test, automock)]
+pub trait DeviceRepository: Send + Sync {
+    // Inserts a device, or updates the name, platform and last_seen of a known one
+    fn record_device(&self, device: &Device) -> AppResult<()>;
+    fn get_device(&self, id: &str) -> AppResult<Device>;
+    fn list_devices(&self) -> AppResult<Vec<Device>>;
+    fn revoke_device(&self, id: &str, revoked_at: DateTime<Utc>) -> AppResult<()>;
+}
+
+// Trait for hiding credentials while travel mode is on
+#[cfg_attr(test, automock)]
+pub trait TravelRepository: Send + Sync {
+    // Audit entries about any of the credentials `uuids`, oldest first
+    fn item_audit_entries(&self, uuids: &[String]) -> AppResult<Vec<AuditLogEntry>>;
+    // Stores the stash, deletes the stashed credentials and their audit entries
+    // and replaces the audit archive segments `archives` (deleting those without
+    // entries) in one transaction, then scrubs them from the database file
+    fn enter_travel_mode(
+        &self,
+        stashed: &[StashedCredential],
+        archives: &[StoredAuditArchive],
+        enabled_at: DateTime<Utc>,
+    ) -> AppResult<()>;
+    fn travel_mode_since(&self) -> AppResult<Option<DateTime<Utc>>>;
+    fn list_stashed(&self) -> AppResult<Vec<StashedCredential>>;
+    // Re-inserts the credentials and their audit entries and clears the stash
+    // in one transaction
+    fn leave_travel_mode(&self, restored: &[Credential], audit: &[AuditLogEntry]) -> AppResult<()>;
+}
+
+// Trait for the write-ahead journal of multi-step operations
+#[cfg_attr(test, automock)]
+pub trait JournalRepository: Send + Sync {
+    // Records an operation before its first step runs
+    fn begin_operation(&self, entry: &JournalEntry) -> AppResult<()>;
+    // Removes an operation once all its steps are done or undone
+    fn complete_operation(&self, id: &str) -> AppResult<()>;
+    // Oldest first
+    fn list_pending_operations(&self) -> AppResult<Vec<JournalEntry>>;
+}
+
+// Trait for imported lists of compromised passwords, stored as fingerprints
+// (see `breach_list::fingerprint`)
+#[cfg_attr(test, automock)]
+pub trait BreachListRepository: Send + Sync {
+    // Adds fingerprints to a source, creating it; returns how many it did not have yet
+    fn add_breach_entries(
+        &self,
+        source: &str,
+        fingerprints: &[String],
+        imported_at: DateTime<Utc>,
+    ) -> AppResult<usize>;
+    // The (fingerprint, source) pairs for those of `fingerprints` that are listed
+    fn find_breach_entries(&self, fingerprints: &[String]) -> AppResult<Vec<(String, String)>>;
+    fn list_breach_sources(&self) -> AppResult<Vec<BreachListSource>>;
+    // Returns how many entries were removed
+    fn remove_breach_source(&self, source: &str) -> AppResult<usize>;
+}
+
+// Imported rows waiting for the user to resolve their conflict with an existing item
+#[cfg_attr(test, automock)]
+pub trait ImportConflictRepository: Send + Sync {
+    // Returns the new conflict's id
+    fn add_import_conflict(
+        &self,
+        existing_uuid: &str,
+        item_enc: &str,
+        created_at: DateTime<Utc>,
+    ) -> AppResult<i64>;
+    // Oldest first
+    fn list_import_conflicts(&self) -> AppResult<Vec<StoredImportConflict>>;
+    fn get_import_conflict(&self, id: i64) -> AppResult<StoredImportConflict>;
+    fn remove_import_conflict(&self, id: i64) -> AppResult<()>;
+}
+
+// Trait for backends that encrypt whole records with a key derived from the vault key
+#[cfg_attr(test, automock)]
+pub trait EncryptedStorage: Send + Sync {
+    // Called after unlock and whenever the network settings change; the cipher
+    // holds or reaches the storage key
+    fn open_storage(&self, cipher: SubkeyCipher, network: &NetworkSettings) -> AppResult<()>;
+    // Called on lock; the backend must forget the cipher
+    fn close_storage(&self);
+    // When data last went to or came from the server, for backends that have one
+    fn last_synced_at(&self) -> Option<DateTime<Utc>> {
+        None
+    }
+}
+
+// Trait for storage that other programs can change underneath the app
+#[cfg_attr(test, automock)]
+pub trait ExternalChangeSource: Send + Sync {
+    // Compares the storage with the previous call (or with how it was opened)
+    // and reports each change once
+    fn poll_external_change(&self) -> AppResult<Option<ExternalChange>>;
+}
+
 // Trait for calculating password strength
+#[cfg_attr(test, automock)]
 pub trait PasswordStrengthCalculator: Send + Sync {
-    fn calculate_strength(&self, password: &str) -> u8;
+    // `wordlists` holds the user's banned passwords and extra dictionary words
+    fn calculate_strength(&self, password: &str, wordlists: &Wordlists) -> u8;
 }
@@ -0,0 +1,153 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::CryptoService;
+use crate::error::{AppError, AppResult};
+use crate::models::{AuditLogEntry, Credential};
+use crate::validation::{normalize_tag, normalize_tags};
+
+/// Format tag written into every travel recovery blob
+pub const TRAVEL_FORMAT: &str = "secret-plan-travel/1";
+
+/// A credential hidden while travel mode is on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StashedCredential {
+    pub uuid: String,
+    /// `HiddenItem` as JSON, encrypted with the travel key and bound to `uuid`
+    pub ciphertext: String,
+}
+
+/// What travel mode takes off the device for one credential
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HiddenItem {
+    #[serde(flatten)]
+    credential: Credential,
+    /// Audit entries about the item, which name its site
+    audit: Vec<AuditLogEntry>,
+}
+
+/// The only copy of the travel key; keep it off the device while travelling
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TravelRecovery {
+    pub format: String,
+    pub created_at: DateTime<Utc>,
+    pub hidden_count: usize,
+    /// Base64url travel key
+    pub key: String,
+}
+
+/// Returned by `enable_travel_mode`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TravelActivation {
+    pub hidden_count: usize,
+    /// `TravelRecovery` as JSON, needed to disable travel mode
+    pub recovery_blob: String,
+}
+
+/// Whether travel mode is on and how many items it hides
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TravelModeStatus {
+    pub active: bool,
+    pub enabled_at: Option<DateTime<Utc>>,
+    pub hidden_count: usize,
+}
+
+/// Whether a credential stays visible in travel mode. Tags are compared in
+/// their stored form, so allowing "Work" keeps items tagged "work".
+pub fn is_allowed(credential: &Credential, allowed_tags: &[String]) -> bool {
+    let allowed: Vec<String> = allowed_tags.iter().map(|tag| normalize_tag(tag)).collect();
+    credential
+        .tags
+        .iter()
+        .any(|tag| allowed.contains(&normalize_tag(tag)))
+}
+
+/// Encrypts credentials, with the audit entries about each, under the travel key
+pub fn stash(
+    credentials: &[Credential],
+    audit: &[AuditLogEntry],
+    key: &[u8; 32],
+) -> AppResult<Vec<StashedCredential>> {
+    credentials
+        .iter()
+        .map(|credential| {
+            let item = HiddenItem {
+                credential: credential.clone(),
+                audit: audit
+                    .iter()
+                    .filter(|entry| entry.item_uuid.as_deref() == Some(credential.uuid.as_str()))
+                    .cloned()
+                    .collect(),
+            };
+            Ok(StashedCredential {
+                uuid: credential.uuid.clone(),
+                ciphertext: CryptoService::encrypt_with_key(
+                    key,
+                    &serde_json::to_vec(&item)?,
+                    credential.uuid.as_bytes(),
+                )?,
+            })
+        })
+        .collect()
+}
+
+/// Decrypts stashed credentials and their audit entries; fails if `key` is
+/// not the travel key. Tags are normalized: the stash is encrypted, so the
+/// migration that normalized stored tags could not reach items hidden before it.
+pub fn restore(
+    stashed: &[StashedCredential],
+    key: &[u8; 32],
+) -> AppResult<(Vec<Credential>, Vec<AuditLogEntry>)> {
+    let mut credentials = Vec::with_capacity(stashed.len());
+    let mut audit = Vec::new();
+    for item in stashed {
+        let plaintext = CryptoService::decrypt_with_key(
+            key,
+            &item.ciphertext,
+            item.uuid.as_bytes(),
+        )
+        .map_err(|_| {
+            AppError::InvalidInput("The recovery blob does not belong to this vault".to_string())
+        })?;
+        let hidden: HiddenItem =
+            serde_json::from_slice(&plaintext).map_err(AppError::Serialization)?;
+        let mut credential = hidden.credential;
+        credential.tags = normalize_tags(&credential.tags);
+        credentials.push(credential);
+        audit.extend(hidden.audit);
+    }
+    audit.sort_by_key(|entry| entry.id);
+    Ok((credentials, audit))
+}
+
+/// Serializes the recovery blob for a travel key
+pub fn encode_recovery(
+    key: &[u8; 32],
+    hidden_count: usize,
+    now: DateTime<Utc>,
+) -> AppResult<String> {
+    Ok(serde_json::to_string(&TravelRecovery {
+        format: TRAVEL_FORMAT.to_string(),
+        created_at: now,
+        hidden_count,
+        key: URL_SAFE_NO_PAD.encode(key),
+    })?)
+}
+
+/// Extracts the travel key from a recovery blob
+pub fn decode_recovery(blob: &str) -> AppResult<[u8; 32]> {
+    let recovery: TravelRecovery = serde_json::from_str(blob.trim())
+        .map_err(|_| AppError::InvalidInput("Malformed travel recovery blob".to_string()))?;
+    if recovery.format != TRAVEL_FORMAT {
+        return Err(AppError::InvalidInput(format!(
+            "Unsupported travel recovery format: {}",
+            recovery.format
+        )));
+    }
+    URL_SAFE_NO_PAD
+        .decode(&recovery.key)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| AppError::InvalidInput("Invalid travel key".to_string()))
+}
@@ -0,0 +1,187 @@
+//! Undo and redo of destructive edits: deletes, bulk edits and tag renames.
+//!
+//! Each operation is kept as the stored rows of the items it touched, before and
+//! after. Undoing writes the "before" rows back, redoing the "after" rows, but
+//! only while the items are still exactly as the operation (or the last undo)
+//! left them, so an edit made in between is never overwritten. Rows hold secrets
+//! only in encrypted form; the history lives in memory and ends when the vault locks.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::Credential;
+
+/// Operations kept for undo; older ones are forgotten
+pub const MAX_UNDO_OPERATIONS: usize = 20;
+
+/// Tag changes `bulk_edit` makes to every selected item
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BulkEdit {
+    #[serde(default)]
+    pub add_tags: Vec<String>,
+    #[serde(default)]
+    pub remove_tags: Vec<String>,
+}
+
+impl BulkEdit {
+    /// `tags` with the edit applied
+    pub fn apply(&self, tags: &[String]) -> Vec<String> {
+        let mut edited: Vec<String> = tags
+            .iter()
+            .filter(|tag| !self.remove_tags.contains(tag))
+            .cloned()
+            .collect();
+        for tag in &self.add_tags {
+            if !edited.contains(tag) {
+                edited.push(tag.clone());
+            }
+        }
+        edited
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    Delete,
+    BulkEdit,
+    TagRename,
+}
+
+impl OperationKind {
+    /// Name used in audit entries
+    pub fn name(self) -> &'static str {
+        match self {
+            OperationKind::Delete => "delete",
+            OperationKind::BulkEdit => "bulk_edit",
+            OperationKind::TagRename => "tag_rename",
+        }
+    }
+}
+
+/// An operation as shown on undo and redo buttons
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationSummary {
+    pub kind: OperationKind,
+    /// Items the operation changed
+    pub items: usize,
+}
+
+/// What can be undone and redone next
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UndoStatus {
+    pub undo: Option<OperationSummary>,
+    pub redo: Option<OperationSummary>,
+}
+
+/// An item's stored row, None if the item did not exist
+#[derive(Debug, Clone)]
+pub struct ItemState {
+    pub uuid: String,
+    pub credential: Option<Credential>,
+}
+
+impl ItemState {
+    pub fn stored(credential: Credential) -> Self {
+        Self {
+            uuid: credential.uuid.clone(),
+            credential: Some(credential),
+        }
+    }
+
+    pub fn absent(uuid: &str) -> Self {
+        Self {
+            uuid: uuid.to_string(),
+            credential: None,
+        }
+    }
+
+    /// The stored revision, which changes on every write
+    pub fn revision(&self) -> Option<u64> {
+        self.credential
+            .as_ref()
+            .map(|credential| credential.revision)
+    }
+}
+
+/// A finished operation
+#[derive(Debug, Clone)]
+pub struct Operation {
+    pub kind: OperationKind,
+    pub before: Vec<ItemState>,
+    pub after: Vec<ItemState>,
+}
+
+impl Operation {
+    pub fn summary(&self) -> OperationSummary {
+        OperationSummary {
+            kind: self.kind,
+            items: self.after.len(),
+        }
+    }
+}
+
+/// Undo and redo stacks of the unlocked session
+#[derive(Debug, Default)]
+pub struct UndoHistory {
+    undo: VecDeque<Operation>,
+    redo: Vec<Operation>,
+}
+
+impl UndoHistory {
+    /// Records a new operation, which makes undone ones impossible to redo
+    pub fn record(&mut self, operation: Operation) {
+        self.redo.clear();
+        self.push_undo(operation);
+    }
+
+    pub fn take_undo(&mut self) -> Option<Operation> {
+        self.undo.pop_back()
+    }
+
+    pub fn take_redo(&mut self) -> Option<Operation> {
+        self.redo.pop()
+    }
+
+    /// After a redo; an operation that fails to apply is dropped, as the items it
+    /// would write have changed since
+    pub fn push_undo(&mut self, operation: Operation) {
+        self.undo.push_back(operation);
+        if self.undo.len() > MAX_UNDO_OPERATIONS {
+            self.undo.pop_front();
+        }
+    }
+
+    /// After an undo
+    pub fn push_redo(&mut self, operation: Operation) {
+        self.redo.push(operation);
+    }
+
+    /// Points the remaining operations at rows that were written back:
+    /// `restored[i]` holds the content of `replaced[i]` under a new revision, so an
+    /// operation expecting `replaced[i]` still finds the item unchanged
+    pub fn rebase(&mut self, replaced: &[ItemState], restored: &[ItemState]) {
+        let operations = self.undo.iter_mut().chain(self.redo.iter_mut());
+        for operation in operations {
+            let states = operation
+                .before
+                .iter_mut()
+                .chain(operation.after.iter_mut());
+            for state in states.filter(|state| state.credential.is_some()) {
+                if let Some(index) = replaced.iter().position(|replaced| {
+                    replaced.uuid == state.uuid && replaced.revision() == state.revision()
+                }) {
+                    *state = restored[index].clone();
+                }
+            }
+        }
+    }
+
+    pub fn status(&self) -> UndoStatus {
+        UndoStatus {
+            undo: self.undo.back().map(Operation::summary),
+            redo: self.redo.last().map(Operation::summary),
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Unlocking without blocking the app.
+//!
+//! Argon2 with a vault's parameters can take seconds, or far longer if a vault
+//! was created with huge ones. The derivation runs on a thread of its own, so
+//! the command that started it neither holds the app state nor ties up the
+//! async runtime meanwhile, and the user can cancel it. A cancelled derivation
+//! cannot be interrupted inside Argon2: its thread finishes in the background
+//! and the key it derives is dropped.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::crypto::{CryptoService, DerivedKey};
+use crate::error::{AppError, AppResult};
+
+/// Event the progress of an unlock is emitted as
+pub const UNLOCK_PROGRESS_EVENT: &str = "unlock-progress";
+
+/// How often a running derivation checks whether it was cancelled
+const CANCEL_POLL: Duration = Duration::from_millis(50);
+
+/// Payload of `unlock-progress` events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnlockProgress {
+    DerivationStarted,
+    DerivationFinished,
+    Cancelled,
+}
+
+/// Derives the master key from `stored_hash` on a dedicated thread. Fails with
+/// `AppError::Cancelled` as soon as `cancel` is set.
+pub async fn derive_key(
+    master_password: String,
+    stored_hash: String,
+    cancel: Arc<AtomicBool>,
+) -> AppResult<DerivedKey> {
+    let (sender, mut receiver) = oneshot::channel();
+    let thread_cancel = cancel.clone();
+    std::thread::Builder::new()
+        .name("key-derivation".to_string())
+        .spawn(move || {
+            let result =
+                CryptoService::derive_unlock_key(&master_password, &stored_hash, &thread_cancel);
+            // The receiver is gone if the unlock was cancelled
+            let _ = sender.send(result);
+        })?;
+
+    loop {
+        tokio::select! {
+            result = &mut receiver => {
+                return result.unwrap_or_else(|_| {
+                    Err(AppError::Other("Key derivation stopped unexpectedly".to_string()))
+                });
+            }
+            _ = tokio::time::sleep(CANCEL_POLL) => {
+                if cancel.load(Ordering::SeqCst) {
+                    return Err(AppError::Cancelled);
+                }
+            }
+        }
+    }
+}
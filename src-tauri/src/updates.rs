@@ -0,0 +1,369 @@
+//! Checking for and downloading new versions.
+//!
+//! A release publishes a signed manifest: the manifest JSON as a string, and an
+//! Ed25519 signature over exactly those bytes. The manifest names the latest
+//! version and, per platform, the artifact URL, its size and an Ed25519
+//! signature over the artifact. Both signatures must verify against the key
+//! this build was made with before anything is trusted; a downloaded artifact
+//! is written to disk only after its signature checks out. Installing it is
+//! left to the platform installer.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::NetFeature;
+use crate::net::{self, classify_service_error, validate_service_url, Network};
+
+/// Where releases are announced unless `AppSettings::update_manifest_url` says otherwise
+pub const DEFAULT_MANIFEST_URL: &str =
+    "https://github.com/JacobLinCool/secret-plan/releases/latest/download/manifest.json";
+
+/// Base64 Ed25519 key that release manifests and artifacts are signed with, set
+/// by the release build. Builds without it cannot check for updates.
+const RELEASE_PUBLIC_KEY: Option<&str> = option_env!("SECRET_PLAN_UPDATE_PUBLIC_KEY");
+
+/// Largest artifact accepted, so a bad manifest cannot fill the disk
+const MAX_ARTIFACT_BYTES: u64 = 512 * 1024 * 1024;
+
+const SERVICE: &str = "Update server";
+
+/// The running version
+pub fn current_version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+/// Key of this build's platform in `ReleaseManifest::platforms`, e.g. `linux-x86_64`
+pub fn platform() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// What the update server serves
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedManifest {
+    /// `ReleaseManifest` as JSON; the signature covers these exact bytes
+    pub manifest: String,
+    /// Base64 Ed25519 signature over `manifest`
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseManifest {
+    pub version: String,
+    pub published_at: DateTime<Utc>,
+    #[serde(default)]
+    pub notes: String,
+    /// Artifacts by platform, see `platform`
+    pub platforms: std::collections::BTreeMap<String, ReleaseArtifact>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseArtifact {
+    pub url: String,
+    pub size: u64,
+    /// Base64 Ed25519 signature over the artifact's bytes
+    pub signature: String,
+}
+
+/// A newer version than the running one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AvailableUpdate {
+    pub version: String,
+    pub published_at: DateTime<Utc>,
+    pub notes: String,
+    /// Download size in bytes
+    pub size: u64,
+}
+
+/// Result of `check_for_updates`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdateCheck {
+    pub current_version: String,
+    /// None when the running version is the latest for this platform
+    pub update: Option<AvailableUpdate>,
+}
+
+/// Progress of `download_update`, emitted as `update-download-progress` events
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct UpdateProgress {
+    pub downloaded: u64,
+    pub total: u64,
+}
+
+/// A downloaded and verified artifact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadedUpdate {
+    pub version: String,
+    pub path: PathBuf,
+}
+
+/// Parses `1.2.3` (optionally `v1.2.3`); pre-release and build suffixes are ignored
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version
+        .trim()
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let version = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(version)
+}
+
+/// Whether `candidate` is a later version than `current`
+pub fn is_newer(candidate: &str, current: &str) -> AppResult<bool> {
+    let parse = |version: &str| {
+        parse_version(version)
+            .ok_or_else(|| AppError::Other(format!("Invalid version number: {}", version)))
+    };
+    Ok(parse(candidate)? > parse(current)?)
+}
+
+fn decode_signature(signature: &str, what: &str) -> AppResult<Signature> {
+    let bytes = BASE64
+        .decode(signature.trim())
+        .map_err(|_| AppError::Other(format!("The {} signature is not valid base64", what)))?;
+    Signature::from_slice(&bytes)
+        .map_err(|_| AppError::Other(format!("The {} signature is malformed", what)))
+}
+
+/// Fails unless `signature` is `key`'s signature over `data`
+fn verify(key: &VerifyingKey, data: &[u8], signature: &str, what: &str) -> AppResult<()> {
+    key.verify(data, &decode_signature(signature, what)?)
+        .map_err(|_| AppError::Other(format!("The {} signature does not verify", what)))
+}
+
+/// Client for the update server
+#[derive(Debug, Clone)]
+pub struct UpdateChecker {
+    manifest_url: String,
+    public_key: Option<VerifyingKey>,
+    network: Network,
+}
+
+impl UpdateChecker {
+    /// Validates the manifest URL (https, or http on loopback); None uses
+    /// `DEFAULT_MANIFEST_URL`
+    pub fn new(manifest_url: Option<&str>) -> AppResult<Self> {
+        let public_key = match RELEASE_PUBLIC_KEY {
+            Some(key) => Some(parse_public_key(key)?),
+            None => None,
+        };
+        Ok(Self {
+            manifest_url: validate_service_url(
+                manifest_url.unwrap_or(DEFAULT_MANIFEST_URL),
+                "Update manifest",
+            )?,
+            public_key,
+            network: Network::default(),
+        })
+    }
+
+    /// Verifies releases against `key` instead of the build's release key
+    pub fn with_public_key(mut self, key: VerifyingKey) -> Self {
+        self.public_key = Some(key);
+        self
+    }
+
+    /// Sends requests through the app's proxy, offline and permission settings
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = network;
+        self
+    }
+
+    fn public_key(&self) -> AppResult<&VerifyingKey> {
+        self.public_key.as_ref().ok_or_else(|| {
+            AppError::Other("This build has no key to verify updates with".to_string())
+        })
+    }
+
+    fn client(&self) -> AppResult<reqwest::Client> {
+        net::build_client(
+            self.network
+                .feature_client_builder(NetFeature::UpdateChecks, SERVICE)?,
+        )
+    }
+
+    /// Fetches the manifest and returns it once its signature verifies
+    pub async fn fetch_manifest(&self) -> AppResult<ReleaseManifest> {
+        let key = self.public_key()?;
+        let response = self
+            .client()?
+            .get(&self.manifest_url)
+            .send()
+            .await
+            .map_err(|e| classify_service_error(e, SERVICE))?;
+        if !response.status().is_success() {
+            return Err(AppError::Other(format!(
+                "{} returned error: {}",
+                SERVICE,
+                response.status()
+            )));
+        }
+        let signed: SignedManifest = response
+            .json()
+            .await
+            .map_err(|e| AppError::Other(format!("Invalid update manifest: {}", e)))?;
+        verify(
+            key,
+            signed.manifest.as_bytes(),
+            &signed.signature,
+            "manifest",
+        )?;
+        serde_json::from_str(&signed.manifest)
+            .map_err(|e| AppError::Other(format!("Invalid update manifest: {}", e)))
+    }
+
+    /// Whether the manifest offers a newer version for this platform
+    pub async fn check(&self) -> AppResult<UpdateCheck> {
+        let manifest = self.fetch_manifest().await?;
+        let update = match manifest.platforms.get(&platform()) {
+            Some(artifact) if is_newer(&manifest.version, current_version())? => {
+                Some(AvailableUpdate {
+                    version: manifest.version.clone(),
+                    published_at: manifest.published_at,
+                    notes: manifest.notes.clone(),
+                    size: artifact.size,
+                })
+            }
+            _ => None,
+        };
+        debug!(latest = %manifest.version, available = update.is_some(), "checked for updates");
+        Ok(UpdateCheck {
+            current_version: current_version().to_string(),
+            update,
+        })
+    }
+
+    /// Downloads this platform's artifact of the latest version into `dir`,
+    /// reporting progress after each chunk, and verifies it before writing it
+    pub async fn download<F>(&self, dir: &Path, mut on_progress: F) -> AppResult<DownloadedUpdate>
+    where
+        F: FnMut(UpdateProgress),
+    {
+        let manifest = self.fetch_manifest().await?;
+        if !is_newer(&manifest.version, current_version())? {
+            return Err(AppError::InvalidInput(
+                "No newer version is available".to_string(),
+            ));
+        }
+        let platform = platform();
+        let artifact = manifest.platforms.get(&platform).ok_or_else(|| {
+            AppError::NotFound(format!("update {} for {}", manifest.version, platform))
+        })?;
+        if artifact.size > MAX_ARTIFACT_BYTES {
+            return Err(AppError::Other(format!(
+                "The update is too large ({} bytes)",
+                artifact.size
+            )));
+        }
+        let url = validate_service_url(&artifact.url, "Update download")?;
+        let file_name = Url::parse(&url)
+            .ok()
+            .and_then(|url| url.path_segments()?.next_back().map(str::to_string))
+            .filter(|name| {
+                !name.is_empty()
+                    && name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+            })
+            .ok_or_else(|| AppError::Other(format!("Unusable update file name in {}", url)))?;
+
+        let mut response = self
+            .client()?
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| classify_service_error(e, SERVICE))?;
+        if !response.status().is_success() {
+            return Err(AppError::Other(format!(
+                "{} returned error: {}",
+                SERVICE,
+                response.status()
+            )));
+        }
+        let mut bytes = Vec::with_capacity(artifact.size as usize);
+        on_progress(UpdateProgress {
+            downloaded: 0,
+            total: artifact.size,
+        });
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| classify_service_error(e, SERVICE))?
+        {
+            bytes.extend_from_slice(&chunk);
+            if bytes.len() as u64 > artifact.size {
+                return Err(AppError::Other(
+                    "The update is larger than its manifest says".to_string(),
+                ));
+            }
+            on_progress(UpdateProgress {
+                downloaded: bytes.len() as u64,
+                total: artifact.size,
+            });
+        }
+        if bytes.len() as u64 != artifact.size {
+            return Err(AppError::Other(
+                "The update download is incomplete".to_string(),
+            ));
+        }
+        verify(self.public_key()?, &bytes, &artifact.signature, "update")?;
+
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(file_name);
+        write_new_private_file(&path, &bytes)?;
+        info!(version = %manifest.version, "update downloaded and verified");
+        Ok(DownloadedUpdate {
+            version: manifest.version,
+            path,
+        })
+    }
+}
+
+/// Writes `bytes` into a file created fresh next to `path`, readable only by the
+/// current user where supported, and renames it into place. Whatever was at
+/// `path` before, a link planted there included, is replaced, never written
+/// through.
+fn write_new_private_file(path: &Path, bytes: &[u8]) -> AppResult<()> {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("update");
+    let partial = path.with_file_name(format!(".{}.{}.partial", name, Uuid::new_v4()));
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&partial)?;
+    let written = file
+        .write_all(bytes)
+        .and_then(|()| file.sync_all())
+        .and_then(|()| std::fs::rename(&partial, path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    Ok(written?)
+}
+
+/// Parses a base64 Ed25519 public key
+pub fn parse_public_key(key: &str) -> AppResult<VerifyingKey> {
+    let bytes: [u8; 32] = BASE64
+        .decode(key.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| AppError::Other("Invalid update signing key".to_string()))?;
+    VerifyingKey::from_bytes(&bytes)
+        .map_err(|_| AppError::Other("Invalid update signing key".to_string()))
+}
@@ -0,0 +1,295 @@
+//! Limits on what the add, update and import commands accept.
+//!
+//! `VaultManager` checks these before anything is encrypted or written, so
+//! oversized or malformed input fails with an error naming the offending field
+//! instead of reaching SQLite or the cipher. Lengths are in bytes, which is what
+//! ends up being stored.
+
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::api_keys::ApiKeyDetails;
+use crate::i18n::Message;
+use crate::models::Secret;
+
+pub const MAX_SITE_BYTES: usize = 512;
+pub const MAX_USERNAME_BYTES: usize = 512;
+pub const MAX_PASSWORD_BYTES: usize = 4096;
+pub const MAX_TOTP_BYTES: usize = 1024;
+pub const MAX_AUTOTYPE_SEQUENCE_BYTES: usize = 1024;
+pub const MAX_NOTES_BYTES: usize = 64 * 1024;
+pub const MAX_TAGS: usize = 32;
+pub const MAX_TAG_BYTES: usize = 64;
+pub const MAX_CUSTOM_FIELDS: usize = 64;
+pub const MAX_CUSTOM_FIELD_NAME_BYTES: usize = 128;
+pub const MAX_CUSTOM_FIELD_VALUE_BYTES: usize = 8 * 1024;
+pub const MAX_PROVIDER_BYTES: usize = 128;
+pub const MAX_KEY_ID_BYTES: usize = 512;
+pub const MAX_SCOPES: usize = 64;
+pub const MAX_SCOPE_BYTES: usize = 128;
+
+/// Punctuation allowed in tags besides letters, digits and spaces
+const TAG_PUNCTUATION: &[char] = &['-', '_', '.', '/', ':', '+', '#', '&', '@', '\''];
+
+/// What is wrong with a field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    Empty,
+    TooLong {
+        max_bytes: usize,
+    },
+    TooMany {
+        max: usize,
+    },
+    /// Control characters, other than line breaks and tabs in multi-line fields
+    ControlCharacters,
+    /// U+FFFD, left behind when text was decoded from invalid UTF-8
+    ReplacementCharacter,
+    InvalidTagCharacter(char),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Empty => write!(f, "must not be empty"),
+            Violation::TooLong { max_bytes } => write!(f, "is longer than {} bytes", max_bytes),
+            Violation::TooMany { max } => write!(f, "has more than {} entries", max),
+            Violation::ControlCharacters => write!(f, "contains control characters"),
+            Violation::ReplacementCharacter => write!(f, "contains text that was not valid UTF-8"),
+            Violation::InvalidTagCharacter(c) => {
+                write!(f, "contains '{}', which tags cannot use", c)
+            }
+        }
+    }
+}
+
+/// A field that failed validation, e.g. `tags[2]` or `custom_fields.pin`
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{field} {violation}")]
+pub struct ValidationError {
+    pub field: String,
+    pub violation: Violation,
+}
+
+impl ValidationError {
+    fn new(field: impl Into<String>, violation: Violation) -> Self {
+        Self {
+            field: field.into(),
+            violation,
+        }
+    }
+
+    /// The translatable form, with the field name as `{field}`
+    pub fn message(&self) -> Message {
+        let message = match &self.violation {
+            Violation::Empty => Message::new("validation.empty"),
+            Violation::TooLong { max_bytes } => {
+                Message::new("validation.too_long").with_param("max", max_bytes)
+            }
+            Violation::TooMany { max } => {
+                Message::new("validation.too_many").with_param("max", max)
+            }
+            Violation::ControlCharacters => Message::new("validation.control_characters"),
+            Violation::ReplacementCharacter => Message::new("validation.invalid_encoding"),
+            Violation::InvalidTagCharacter(c) => {
+                Message::new("validation.invalid_tag_character").with_param("character", c)
+            }
+        };
+        message.with_param("field", &self.field)
+    }
+}
+
+/// Checks everything an add would store
+pub fn validate_credential(
+    site: &str,
+    username: &str,
+    secret: &Secret,
+    tags: &[String],
+) -> Result<(), ValidationError> {
+    validate_fields(site, username, secret)?;
+    validate_tags(tags)
+}
+
+/// Checks everything an update would store. Tags the item already has are
+/// not checked again, so items saved before these limits stay editable.
+pub fn validate_update(
+    site: &str,
+    username: &str,
+    secret: &Secret,
+    tags: &[String],
+    existing_tags: &[String],
+) -> Result<(), ValidationError> {
+    validate_fields(site, username, secret)?;
+    validate_tag_changes(tags, existing_tags)
+}
+
+/// Checks everything but the tags
+fn validate_fields(site: &str, username: &str, secret: &Secret) -> Result<(), ValidationError> {
+    if site.trim().is_empty() {
+        return Err(ValidationError::new("site", Violation::Empty));
+    }
+    check_line("site", site, MAX_SITE_BYTES)?;
+    check_line("username", username, MAX_USERNAME_BYTES)?;
+    check_line("password", &secret.password, MAX_PASSWORD_BYTES)?;
+    if let Some(totp) = &secret.totp {
+        check_line("totp", totp, MAX_TOTP_BYTES)?;
+    }
+    if let Some(notes) = &secret.notes {
+        check_text("notes", notes, MAX_NOTES_BYTES)?;
+    }
+    if let Some(sequence) = &secret.autotype_sequence {
+        check_line("autotype_sequence", sequence, MAX_AUTOTYPE_SEQUENCE_BYTES)?;
+    }
+
+    if secret.custom_fields.len() > MAX_CUSTOM_FIELDS {
+        return Err(ValidationError::new(
+            "custom_fields",
+            Violation::TooMany {
+                max: MAX_CUSTOM_FIELDS,
+            },
+        ));
+    }
+    for (name, value) in &secret.custom_fields {
+        let field = format!("custom_fields.{}", name);
+        if name.trim().is_empty() {
+            return Err(ValidationError::new(field, Violation::Empty));
+        }
+        check_line(&field, name, MAX_CUSTOM_FIELD_NAME_BYTES)?;
+        check_text(&field, value, MAX_CUSTOM_FIELD_VALUE_BYTES)?;
+    }
+    Ok(())
+}
+
+/// Checks the tags an item would store
+pub fn validate_tags(tags: &[String]) -> Result<(), ValidationError> {
+    if tags.len() > MAX_TAGS {
+        return Err(ValidationError::new(
+            "tags",
+            Violation::TooMany { max: MAX_TAGS },
+        ));
+    }
+    for (index, tag) in tags.iter().enumerate() {
+        check_tag(index, tag)?;
+    }
+    Ok(())
+}
+
+/// `validate_tags` for an item that already has `existing` tags: only added
+/// tags are checked, and the count only when it grows past the limit
+pub fn validate_tag_changes(tags: &[String], existing: &[String]) -> Result<(), ValidationError> {
+    if tags.len() > MAX_TAGS && tags.len() > existing.len() {
+        return Err(ValidationError::new(
+            "tags",
+            Violation::TooMany { max: MAX_TAGS },
+        ));
+    }
+    let existing = normalize_tags(existing);
+    for (index, tag) in tags.iter().enumerate() {
+        if !existing.contains(&normalize_tag(tag)) {
+            check_tag(index, tag)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_tag(index: usize, tag: &str) -> Result<(), ValidationError> {
+    let field = format!("tags[{}]", index);
+    if tag.trim().is_empty() {
+        return Err(ValidationError::new(field, Violation::Empty));
+    }
+    check_length(&field, tag, MAX_TAG_BYTES)?;
+    if let Some(c) = tag
+        .chars()
+        .find(|c| !(c.is_alphanumeric() || *c == ' ' || TAG_PUNCTUATION.contains(c)))
+    {
+        return Err(ValidationError::new(
+            field,
+            Violation::InvalidTagCharacter(c),
+        ));
+    }
+    Ok(())
+}
+
+/// The form a tag is stored in: trimmed, inner whitespace collapsed to single
+/// spaces and lowercased, so "Work" and " work " are the same tag
+pub fn normalize_tag(tag: &str) -> String {
+    tag.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// `tags` normalized, without empty tags and duplicates, in their first order
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags.iter().map(|tag| normalize_tag(tag)) {
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+/// Checks the unencrypted details of an API key item
+pub fn validate_api_key(details: &ApiKeyDetails) -> Result<(), ValidationError> {
+    if details.provider.trim().is_empty() {
+        return Err(ValidationError::new("provider", Violation::Empty));
+    }
+    check_line("provider", &details.provider, MAX_PROVIDER_BYTES)?;
+    if let Some(key_id) = &details.key_id {
+        check_line("key_id", key_id, MAX_KEY_ID_BYTES)?;
+    }
+    if details.scopes.len() > MAX_SCOPES {
+        return Err(ValidationError::new(
+            "scopes",
+            Violation::TooMany { max: MAX_SCOPES },
+        ));
+    }
+    for (index, scope) in details.scopes.iter().enumerate() {
+        let field = format!("scopes[{}]", index);
+        if scope.trim().is_empty() {
+            return Err(ValidationError::new(field, Violation::Empty));
+        }
+        check_line(&field, scope, MAX_SCOPE_BYTES)?;
+    }
+    Ok(())
+}
+
+/// A single-line value: no control characters at all
+fn check_line(field: &str, value: &str, max_bytes: usize) -> Result<(), ValidationError> {
+    check_length(field, value, max_bytes)?;
+    check_characters(field, value, |c| c.is_control())
+}
+
+/// Free text: line breaks and tabs are fine
+fn check_text(field: &str, value: &str, max_bytes: usize) -> Result<(), ValidationError> {
+    check_length(field, value, max_bytes)?;
+    check_characters(field, value, |c| {
+        c.is_control() && !matches!(c, '\n' | '\r' | '\t')
+    })
+}
+
+fn check_length(field: &str, value: &str, max_bytes: usize) -> Result<(), ValidationError> {
+    if value.len() > max_bytes {
+        return Err(ValidationError::new(
+            field,
+            Violation::TooLong { max_bytes },
+        ));
+    }
+    Ok(())
+}
+
+fn check_characters(
+    field: &str,
+    value: &str,
+    forbidden: impl Fn(char) -> bool,
+) -> Result<(), ValidationError> {
+    if value.chars().any(forbidden) {
+        return Err(ValidationError::new(field, Violation::ControlCharacters));
+    }
+    if value.contains('\u{FFFD}') {
+        return Err(ValidationError::new(field, Violation::ReplacementCharacter));
+    }
+    Ok(())
+}
@@ -1,13 +1,144 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use serde_json;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
-use crate::crypto::CryptoService;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::api_keys::{self, ApiKeyDetails, ApiKeyInput};
+use crate::audit_archive::{
+    self, AuditArchiveRange, AuditArchiveReport, StoredAuditArchive, AUDIT_ARCHIVE_SEGMENT_ENTRIES,
+    DEFAULT_AUDIT_ARCHIVE_AGE_DAYS,
+};
+use crate::audit_export::{self, AuditVerification, SignedAuditExport};
+use crate::audit_level::AuditFilter;
+use crate::autotype::{self, AutotypeCandidate, AutotypeRequest, Keystroke};
+use crate::backup::{self, BackupJob, BackupOutcome};
+use crate::blocking::BlockingRepository;
+use crate::breach_list::{self, BreachListImport, BreachListSource, LocalBreachMatch};
+use crate::bridge::{PendingSave, PendingSaveRecord, MAX_PENDING_SAVES};
+use crate::bulk_session::BulkSession;
+use crate::bundle::{self, BundleKdf, BundleWriter};
+use crate::cards::CardInput;
+use crate::categorize;
+use crate::collections::WrappedKey;
+use crate::collections::{self, Collection, CollectionMember};
+use crate::compact::{CompactReport, ProgressSink};
+use crate::crypto::{self, CryptoService, DerivedKey};
+use crate::deeplink::{self, DeepLink, DeepLinkKind, DeepLinkRequest, PendingDeepLink};
+use crate::devices::{Device, DeviceIdentity};
+use crate::diagnostics::{self, DiagnosticsBundle, DiagnosticsReport};
+use crate::email_monitor::{AccountBreach, BreachNotification, EmailMonitorState};
+use crate::emergency::EmergencyContact;
 use crate::error::{AppError, AppResult};
-use crate::models::{AppSettings, AuditLogEntry, BreachState, Credential, Secret};
+use crate::export::{self, ExportFormat, ExportReport, ExportScope};
+use crate::features::{Feature, FeatureFlags, FeatureState, FEATURES_META_KEY};
+use crate::generator::{self, GeneratorConstraints, GeneratorOptions};
+use crate::hardening::{self, HardeningStatus};
+use crate::i18n::{Locale, Message};
+use crate::identity::{self, AutofillProfile, IdentityInput};
+use crate::importer::{
+    self, ColumnMapping, ConflictResolution, ImportConflict, ImportFormat, ImportPreview,
+    ImportReport, MappedItem, StoredImportConflict,
+};
+use crate::integrity::{self, DamageKind, DamagedItem, IntegrityReport};
+use crate::journal::{JournalEntry, PendingOperation, RecoveryReport};
+use crate::kdbx;
+use crate::logging;
+use crate::maintenance::{
+    MaintenanceReport, MaintenanceState, MaintenanceTask, MAINTENANCE_META_KEY,
+};
+use crate::master_password::{
+    self, MasterPasswordRecord, MasterPasswordReminder, MASTER_PASSWORD_META_KEY,
+};
+use crate::models::{
+    AppSettings, AuditLogEntry, BreachState, Credential, CredentialKind, CredentialSummary,
+    PasswordHistoryEntry, Secret, SecretField, StorageProfile, StrengthSource, UiPreferences,
+};
+use crate::nonce_audit::{self, NonceAuditReport, NonceUse};
+use crate::notifications::{
+    self, Notification, NotificationSettings, NotificationSink, NOTIFICATIONS_META_KEY,
+};
+use crate::password_policy::{self, PolicyCheck};
+use crate::performance::{self, PerformanceMode};
+use crate::portable;
+use crate::qr_transfer::{self, CredentialQr, TransferredItem};
+use crate::query;
+use crate::recovery_codes;
+use crate::remediation::{self, RemediationItem};
+use crate::remote::{self, RemoteRepository};
+use crate::rotation::{RotationEntry, RotationItem, RotationSession, RotationStatus};
+use crate::search_index::{
+    ChangeCounter, IndexedRepository, QuickSearchHit, SearchIndex, SharedSearchIndex, TagSuggestion,
+};
+use crate::secret_scan::DetectedSecret;
+use crate::settings_profile::{self, SettingsProfileImport};
+use crate::setup::{VaultSetup, VaultSetupOptions, VaultStatus};
+use crate::shares::{self, PreparedShare, ShareDestination, ShareLink, ShareRecord, SharedSecret};
+use crate::site_match::{self, UrlMatch};
+use crate::stats::{self, SecuritySnapshot, TrendRange, VaultStatistics};
+use crate::status::AppStatus;
+use crate::strength::{self, StrengthRecalcProgress, StrengthUpdate, Wordlists};
+use crate::text_match::{MatchMode, TermMatcher};
+use crate::totp::{TotpConfig, TotpValidation};
 use crate::traits::{
-    AuditLogger, CredentialRepository, PasswordStrengthCalculator, SettingsRepository,
+    AsyncCredentialRepository, AuditArchiveRepository, AuditLogger, BreachListRepository,
+    CollectionRepository, CredentialRepository, DeviceRepository, EmergencyRepository,
+    EncryptedStorage, ExternalChangeSource, ImportConflictRepository, JournalRepository,
+    MaintenanceRepository, PasswordStrengthCalculator, RepositoryTransaction, SettingsRepository,
+    ShareRepository, StatsRepository, TransactionalRepository, TravelRepository,
 };
+use crate::travel::{self, TravelActivation, TravelModeStatus};
+use crate::undo::{
+    BulkEdit, ItemState, Operation, OperationKind, OperationSummary, UndoHistory, UndoStatus,
+};
+use crate::validation;
+use crate::views::{self, SavedView, ViewConfig, VIEWS_META_KEY};
+use crate::watch::ExternalChange;
+use crate::wifi::{self, WifiInput, WifiQr};
+
+/// Associated data binding the encrypted word lists to their purpose
+const WORDLISTS_AAD: &[u8] = b"wordlists";
+const EMAIL_MONITOR_AAD: &[u8] = b"email_monitor";
+const PENDING_SAVES_AAD: &[u8] = b"pending_saves";
+const IMPORT_CONFLICT_AAD: &[u8] = b"import_conflict";
+const AUDIT_ARCHIVE_AAD: &[u8] = b"audit_archive";
+
+/// Number of previous passwords kept per credential
+const PASSWORD_HISTORY_LIMIT: usize = 10;
+
+/// Member label the creating vault gives itself in a new collection
+const OWNER_LABEL: &str = "Owner";
+/// Metadata entry set once `backfill_totp_flags` has run
+const TOTP_FLAGS_META_KEY: &str = "totp_flags";
+/// Every metadata entry written through `put_meta`
+const ENCRYPTED_META_KEYS: &[&str] = &[
+    FEATURES_META_KEY,
+    MAINTENANCE_META_KEY,
+    MASTER_PASSWORD_META_KEY,
+    NOTIFICATIONS_META_KEY,
+    TOTP_FLAGS_META_KEY,
+    VIEWS_META_KEY,
+];
+
+/// Built-in filters for tidying up a vault, e.g. items never used since import.
+/// All that are set must hold.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmartFilters {
+    /// Only items edited in the last this many days
+    pub modified_within_days: Option<u32>,
+    /// Only items added in the last this many days
+    pub created_within_days: Option<u32>,
+    /// Only items whose secret was never revealed or typed
+    pub never_used: bool,
+    /// Only items without a TOTP seed
+    pub no_totp: bool,
+    pub no_tags: bool,
+}
 
 /// Filter options for listing credentials
 // Keep this struct here as it relates to the VaultManager's public API
@@ -17,17 +148,198 @@ pub struct CredentialFilter {
     pub tag: Option<String>,
     pub min_strength: Option<u8>,
     pub breach_state: Option<BreachState>,
+    /// Only credentials whose password is at least this many days old
+    pub password_older_than_days: Option<u32>,
+    /// Text the site must contain
+    pub site: Option<String>,
+    pub max_strength: Option<u8>,
+    /// Only credentials changed at or after this time
+    pub updated_after: Option<DateTime<Utc>>,
+    /// Only credentials changed before this time
+    pub updated_before: Option<DateTime<Utc>>,
+    /// How `search_term` is matched
+    pub match_mode: MatchMode,
+    pub smart: SmartFilters,
+}
+
+impl CredentialFilter {
+    /// Whether a credential passes the filter, for backends that cannot query.
+    /// Mirrors the SQL in `SqliteRepository::list_credentials`.
+    pub fn matches(&self, credential: &Credential, now: DateTime<Utc>) -> bool {
+        self.matches_summary(&CredentialSummary::from(credential.clone()), now)
+    }
+
+    /// `matches` for an item's summary, which has every field the filter looks at.
+    /// A search term that does not compile matches nothing.
+    pub fn matches_summary(&self, credential: &CredentialSummary, now: DateTime<Utc>) -> bool {
+        self.term_matcher()
+            .is_ok_and(|term| self.matches_with(term.as_ref(), credential, now))
+    }
+
+    /// The search term compiled for `match_mode`
+    pub fn term_matcher(&self) -> AppResult<Option<TermMatcher>> {
+        self.search_term
+            .as_deref()
+            .map(|term| TermMatcher::new(term, self.match_mode))
+            .transpose()
+    }
+
+    /// `matches_summary` with the term compiled once by `term_matcher`, for lists
+    pub fn matches_with(
+        &self,
+        term: Option<&TermMatcher>,
+        credential: &CredentialSummary,
+        now: DateTime<Utc>,
+    ) -> bool {
+        // SQLite's LIKE ignores ASCII case only
+        let contains = |haystack: &str, needle: &str| {
+            haystack
+                .to_ascii_lowercase()
+                .contains(&needle.to_ascii_lowercase())
+        };
+        if term.is_some_and(|term| {
+            !term.matches_fields(&credential.site, &credential.username, &credential.tags)
+        }) {
+            return false;
+        }
+        if let Some(tag) = &self.tag {
+            let tag = validation::normalize_tag(tag);
+            if !credential
+                .tags
+                .iter()
+                .any(|t| validation::normalize_tag(t) == tag)
+            {
+                return false;
+            }
+        }
+        if self
+            .min_strength
+            .is_some_and(|min| credential.strength < min)
+        {
+            return false;
+        }
+        if self
+            .max_strength
+            .is_some_and(|max| credential.strength > max)
+        {
+            return false;
+        }
+        if self
+            .breach_state
+            .is_some_and(|state| credential.breach_state != state)
+        {
+            return false;
+        }
+        if let Some(site) = &self.site {
+            if !contains(&credential.site, site) {
+                return false;
+            }
+        }
+        let within = |days: Option<u32>, at: DateTime<Utc>| {
+            days.is_none_or(|days| at >= now - chrono::Duration::days(i64::from(days)))
+        };
+        let smart = &self.smart;
+        if !within(smart.modified_within_days, credential.updated_at)
+            || !within(smart.created_within_days, credential.created_at)
+            || (smart.never_used && credential.last_used_at.is_some())
+            || (smart.no_totp && credential.has_totp)
+            || (smart.no_tags && !credential.tags.is_empty())
+        {
+            return false;
+        }
+        if self
+            .updated_after
+            .is_some_and(|after| credential.updated_at < after)
+            || self
+                .updated_before
+                .is_some_and(|before| credential.updated_at >= before)
+        {
+            return false;
+        }
+        if let Some(days) = self.password_older_than_days {
+            if credential.password_changed_at > now - chrono::Duration::days(i64::from(days)) {
+                return false;
+            }
+        }
+        true
+    }
 }
 
 /// Singleton manager for vault operations, orchestrating dependencies.
 pub struct VaultManager {
     // Dependencies injected via traits
     credential_repo: Arc<dyn CredentialRepository>,
+    async_credential_repo: Arc<dyn AsyncCredentialRepository>,
     settings_repo: Arc<dyn SettingsRepository>,
     audit_logger: Arc<dyn AuditLogger>,
+    // `AppSettings::audit_level`, shared with the repositories that write audit entries
+    audit_filter: AuditFilter,
     strength_calculator: Arc<dyn PasswordStrengthCalculator>,
+    maintenance_repo: Option<Arc<dyn MaintenanceRepository>>,
+    stats_repo: Option<Arc<dyn StatsRepository>>,
+    breach_list_repo: Option<Arc<dyn BreachListRepository>>,
+    import_conflict_repo: Option<Arc<dyn ImportConflictRepository>>,
+    audit_archive_repo: Option<Arc<dyn AuditArchiveRepository>>,
+    collection_repo: Option<Arc<dyn CollectionRepository>>,
+    share_repo: Option<Arc<dyn ShareRepository>>,
+    emergency_repo: Option<Arc<dyn EmergencyRepository>>,
+    // Where devices are recorded, and the one this manager runs on
+    devices: Option<(Arc<dyn DeviceRepository>, DeviceIdentity)>,
+    travel_repo: Option<Arc<dyn TravelRepository>>,
+    journal_repo: Option<Arc<dyn JournalRepository>>,
+    // Set by `skip_next_recovery` for the next unlock only
+    skip_recovery: bool,
+    transaction_repo: Option<Arc<dyn TransactionalRepository>>,
+    // Folder relative backup and export paths are taken from, see `portable`
+    data_dir: Option<PathBuf>,
+    encrypted_storage: Option<Arc<dyn EncryptedStorage>>,
+    external_change_source: Option<Arc<dyn ExternalChangeSource>>,
+    // Set once another program changed the storage; the manager must be reopened
+    external_change: Option<ExternalChange>,
     crypto: Arc<Mutex<CryptoService>>,
     is_unlocked: bool,
+    // Bulk rotation in progress; holds generated passwords, so it is dropped on lock
+    rotation: Mutex<Option<RotationSession>>,
+    // Auto-type waiting for the user's confirmation
+    autotype: Mutex<Option<AutotypeRequest>>,
+    // Elevation for operations that decrypt every secret; dropped on lock
+    bulk_session: Mutex<Option<BulkSession>>,
+    // Deletes and bulk edits of this session that can be undone; dropped on lock
+    undo: Mutex<UndoHistory>,
+    // Word lists as decrypted on first use this session; dropped on lock
+    wordlists: Mutex<Option<Arc<Wordlists>>>,
+    // Quick search index, dropped by `IndexedRepository` whenever items change
+    search_index: SharedSearchIndex,
+    // Item writes so far, see `item_changes`
+    item_changes: ChangeCounter,
+    // Whether `AppSettings::performance_mode` applies, see `refresh_performance_mode`
+    performance_active: AtomicBool,
+    // `AppSettings::notifications` as last seen, for notifications while locked
+    notification_settings: Mutex<NotificationSettings>,
+    // Notifications dispatched while locked, added to the history at the next unlock
+    pending_notifications: Mutex<Vec<Notification>>,
+}
+
+/// The secret of a card item: the number as the password and the security
+/// code as the `cvv` custom field
+fn card_secret(
+    number: String,
+    cvv: Option<String>,
+    notes: Option<String>,
+    mut secret: Secret,
+) -> Secret {
+    secret.password = number;
+    secret.notes = notes;
+    match cvv.filter(|cvv| !cvv.is_empty()) {
+        Some(cvv) => secret.custom_fields.insert("cvv".to_string(), cvv),
+        None => secret.custom_fields.remove("cvv"),
+    };
+    secret
+}
+
+/// Associated data binding a metadata entry to its key
+fn meta_aad(key: &str) -> Vec<u8> {
+    format!("meta:{key}").into_bytes()
 }
 
 impl VaultManager {
@@ -43,86 +355,679 @@ impl VaultManager {
         let crypto = Arc::new(Mutex::new(
             CryptoService::new(settings).with_settings_repo(settings_repo.clone()),
         ));
+        let search_index = SharedSearchIndex::default();
+        let item_changes = ChangeCounter::default();
+        let credential_repo: Arc<dyn CredentialRepository> = Arc::new(IndexedRepository::new(
+            credential_repo,
+            search_index.clone(),
+            item_changes.clone(),
+        ));
         Ok(Self {
+            async_credential_repo: Arc::new(BlockingRepository::new(credential_repo.clone())),
             credential_repo,
             settings_repo,
             audit_logger,
+            audit_filter: AuditFilter::default(),
             strength_calculator,
+            maintenance_repo: None,
+            stats_repo: None,
+            breach_list_repo: None,
+            import_conflict_repo: None,
+            audit_archive_repo: None,
+            collection_repo: None,
+            share_repo: None,
+            emergency_repo: None,
+            devices: None,
+            travel_repo: None,
+            journal_repo: None,
+            skip_recovery: false,
+            transaction_repo: None,
+            data_dir: None,
+            encrypted_storage: None,
+            external_change_source: None,
+            external_change: None,
             crypto,
             is_unlocked: false,
+            rotation: Mutex::new(None),
+            autotype: Mutex::new(None),
+            bulk_session: Mutex::new(None),
+            undo: Mutex::new(UndoHistory::default()),
+            wordlists: Mutex::new(None),
+            search_index,
+            item_changes,
+            performance_active: AtomicBool::new(false),
+            notification_settings: Mutex::new(NotificationSettings::default()),
+            pending_notifications: Mutex::new(Vec::new()),
         })
     }
 
+    /// Enables whole-database operations such as backups
+    pub fn with_maintenance_repo(mut self, repo: Arc<dyn MaintenanceRepository>) -> Self {
+        self.maintenance_repo = Some(repo);
+        self
+    }
+
+    /// Takes relative backup and export paths from `dir`, the vault's data directory
+    pub fn with_data_dir(mut self, dir: PathBuf) -> Self {
+        self.data_dir = Some(dir);
+        self
+    }
+
+    /// Applies the vault's audit level through `audit_filter`, which the
+    /// repositories writing audit entries must share
+    pub fn with_audit_filter(mut self, audit_filter: AuditFilter) -> Self {
+        self.audit_filter = audit_filter;
+        self
+    }
+
+    /// Enables security snapshots (`record_security_snapshot`, `get_security_trend`)
+    pub fn with_stats_repo(mut self, repo: Arc<dyn StatsRepository>) -> Self {
+        self.stats_repo = Some(repo);
+        self
+    }
+
+    /// Enables imported lists of compromised passwords
+    pub fn with_breach_list_repo(mut self, repo: Arc<dyn BreachListRepository>) -> Self {
+        self.breach_list_repo = Some(repo);
+        self
+    }
+
+    /// Enables staged imports: rows matching an existing item wait for the user
+    /// instead of being added as duplicates
+    pub fn with_import_conflict_repo(mut self, repo: Arc<dyn ImportConflictRepository>) -> Self {
+        self.import_conflict_repo = Some(repo);
+        self
+    }
+
+    /// Enables moving old audit entries into encrypted archive segments
+    pub fn with_audit_archive_repo(mut self, repo: Arc<dyn AuditArchiveRepository>) -> Self {
+        self.audit_archive_repo = Some(repo);
+        self
+    }
+
+    /// Enables shared collections
+    pub fn with_collection_repo(mut self, repo: Arc<dyn CollectionRepository>) -> Self {
+        self.collection_repo = Some(repo);
+        self
+    }
+
+    /// Enables one-time share links
+    pub fn with_share_repo(mut self, repo: Arc<dyn ShareRepository>) -> Self {
+        self.share_repo = Some(repo);
+        self
+    }
+
+    /// Enables emergency access contacts
+    pub fn with_emergency_repo(mut self, repo: Arc<dyn EmergencyRepository>) -> Self {
+        self.emergency_repo = Some(repo);
+        self
+    }
+
+    /// Records `device` on every unlock and refuses to unlock once it is revoked
+    pub fn with_device_repo(
+        mut self,
+        repo: Arc<dyn DeviceRepository>,
+        device: DeviceIdentity,
+    ) -> Self {
+        self.devices = Some((repo, device));
+        self
+    }
+
+    /// Enables travel mode
+    pub fn with_travel_repo(mut self, repo: Arc<dyn TravelRepository>) -> Self {
+        self.travel_repo = Some(repo);
+        self
+    }
+
+    /// Makes imports and collection deletions crash-safe: an interrupted
+    /// operation is rolled back or finished on the next unlock
+    pub fn with_journal_repo(mut self, repo: Arc<dyn JournalRepository>) -> Self {
+        self.journal_repo = Some(repo);
+        self
+    }
+
+    /// Replaces the default async view of the credential repository (the sync
+    /// repository on the blocking pool) with a natively async backend
+    pub fn with_async_credential_repo(mut self, repo: Arc<dyn AsyncCredentialRepository>) -> Self {
+        self.async_credential_repo = repo;
+        self
+    }
+
+    /// Credential storage for async callers; the handle outlives any lock held on
+    /// the manager, so queries can be awaited without blocking other commands
+    pub fn async_credentials(&self) -> AppResult<Arc<dyn AsyncCredentialRepository>> {
+        self.ensure_unlocked()?;
+        Ok(self.async_credential_repo.clone())
+    }
+
+    /// Enables `with_transaction` and the operations built on it
+    pub fn with_transaction_repo(mut self, repo: Arc<dyn TransactionalRepository>) -> Self {
+        self.transaction_repo = Some(repo);
+        self
+    }
+
+    /// Gives an end-to-end encrypted backend (e.g. `RemoteRepository`) its key on unlock
+    pub fn with_encrypted_storage(mut self, storage: Arc<dyn EncryptedStorage>) -> Self {
+        self.encrypted_storage = Some(storage);
+        self
+    }
+
+    /// Enables `check_external_changes`
+    pub fn with_external_change_source(mut self, source: Arc<dyn ExternalChangeSource>) -> Self {
+        self.external_change_source = Some(source);
+        self
+    }
+
+    /// Looks for changes another program made to the storage, e.g. a sync client
+    /// replacing the vault file. On a change the vault is locked without writing
+    /// anything and stays locked until it is reopened, since the open handles may
+    /// now serve stale data. Returns the change the first time it is seen.
+    pub fn check_external_changes(&mut self) -> AppResult<Option<ExternalChange>> {
+        let Some(source) = &self.external_change_source else {
+            return Ok(None);
+        };
+        let change = source.poll_external_change()?;
+        if let Some(change) = change {
+            warn!(?change, "vault storage changed externally; locking");
+            self.external_change = Some(change);
+            self.close();
+        }
+        Ok(change)
+    }
+
+    /// Runs `body` against repositories whose writes all commit or all roll back
+    pub fn with_transaction<T>(
+        &self,
+        body: impl FnOnce(&dyn RepositoryTransaction) -> AppResult<T>,
+    ) -> AppResult<T> {
+        let repo = self.transaction_repo.as_ref().ok_or_else(|| {
+            AppError::Other("Transactions are not supported by this vault".to_string())
+        })?;
+        let mut body = Some(body);
+        let mut output = None;
+        let result = repo.with_transaction(&mut |txn: &dyn RepositoryTransaction| {
+            if let Some(body) = body.take() {
+                output = Some(body(txn)?);
+            }
+            Ok(())
+        });
+        *self.search_index.lock().unwrap() = None;
+        self.item_changes.fetch_add(1, Ordering::SeqCst);
+        result?;
+        output.ok_or_else(|| AppError::Other("Transaction body did not run".to_string()))
+    }
+
     /// Unlocks the vault with the master password
     pub fn unlock(&mut self, master_password: &str) -> AppResult<()> {
+        if self.external_change.is_some() {
+            return Err(AppError::Conflict(
+                "The vault file was changed by another program; reload it".to_string(),
+            ));
+        }
+
         // Unlock the crypto service
         let mut crypto = self.crypto.lock().unwrap();
-        crypto.unlock(master_password)?;
-        self.is_unlocked = true;
+        if let Err(e) = crypto.unlock(master_password) {
+            warn!(error = %e, "unlock failed");
+            return Err(e);
+        }
         drop(crypto); // Release lock before logging
+        self.mark_unlocked()
+    }
+
+    /// Loads the stored hash for an unlock whose key is derived outside the vault,
+    /// so the slow derivation does not hold up anything else; see `unlock`
+    pub fn begin_unlock(&self) -> AppResult<String> {
+        if self.external_change.is_some() {
+            return Err(AppError::Conflict(
+                "The vault file was changed by another program; reload it".to_string(),
+            ));
+        }
+        self.crypto.lock().unwrap().load_hash_for_unlock()
+    }
+
+    /// Unlocks with a key derived from the hash `begin_unlock` returned
+    pub fn complete_unlock(&mut self, stored_hash: &str, key: DerivedKey) -> AppResult<()> {
+        self.crypto
+            .lock()
+            .unwrap()
+            .unlock_with_key(stored_hash, key)?;
+        self.mark_unlocked()
+    }
+
+    fn mark_unlocked(&mut self) -> AppResult<()> {
+        self.is_unlocked = true;
+        self.load_audit_level();
 
         // Log the unlock action via the AuditLogger trait
-        self.audit_logger.add_log("Vault unlocked", None)?;
+        self.audit(Message::new("audit.vault_unlocked"), None)?;
+        self.finish_unlock()
+    }
+
+    /// Creates the vault with the configured key derivation and default settings,
+    /// without the policy check and calibration of `create_vault`. Fails if the
+    /// vault already exists.
+    pub fn initialize(&mut self, master_password: &str) -> AppResult<()> {
+        let mut crypto = self.crypto.lock().unwrap();
+        let hash = crypto.initialize(master_password)?;
+        if let Err(e) = self.settings_repo.save_master_password_hash(&hash) {
+            crypto.abandon_initialization();
+            return Err(e);
+        }
+        self.is_unlocked = true;
+        drop(crypto);
+        self.load_audit_level();
+
+        let report = strength::analyze_master_password(master_password, &[], &Wordlists::default());
+        self.record_master_password(report.score)?;
+        self.audit(Message::new("audit.vault_created"), None)?;
+        self.finish_unlock()
+    }
+
+    /// Creates the vault: checks `master_password` against the policy, calibrates
+    /// the key derivation if asked, then stores the password hash and the first
+    /// settings together and unlocks. Fails if the vault already exists.
+    pub fn create_vault(
+        &mut self,
+        master_password: &str,
+        confirm: &str,
+        options: VaultSetupOptions,
+    ) -> AppResult<VaultSetup> {
+        if master_password != confirm {
+            return Err(AppError::InvalidInput(
+                "The passwords do not match".to_string(),
+            ));
+        }
+        let user_inputs: Vec<&str> = options.user_inputs.iter().map(String::as_str).collect();
+        let report =
+            strength::analyze_master_password(master_password, &user_inputs, &Wordlists::default());
+        if !report.meets_policy {
+            return Err(AppError::InvalidInput(report.policy_failures.join("; ")));
+        }
+
+        let mut settings = options.settings.unwrap_or_default();
+        settings.validate()?;
+        let kdf = match options.kdf_target_ms {
+            Some(target_ms) => {
+                let calibration = crypto::calibrate_kdf(&settings, target_ms)?;
+                settings.argon2_iterations = calibration.iterations;
+                Some(calibration)
+            }
+            None => None,
+        };
+
+        let settings_json = serde_json::to_vec(&settings).map_err(AppError::Serialization)?;
+        let mut crypto = self.crypto.lock().unwrap();
+        crypto.update_kdf_settings(settings.clone());
+        let hash = crypto.initialize(master_password)?;
+        let stored = crypto
+            .encrypt_return_nonce(&settings_json, b"app_settings")
+            .and_then(|(nonce, encrypted_settings)| {
+                self.settings_repo
+                    .create_master_password(&hash, &nonce, &encrypted_settings)
+            });
+        if let Err(e) = stored {
+            crypto.abandon_initialization();
+            return Err(e);
+        }
+        self.is_unlocked = true;
+        drop(crypto);
+        self.load_audit_level();
+
+        self.record_master_password(report.score)?;
+        self.audit(Message::new("audit.vault_created"), None)?;
+        self.finish_unlock()?;
+        Ok(VaultSetup { kdf })
+    }
+
+    /// Stores the score of a newly set master password, never the password itself
+    fn record_master_password(&self, score: u8) -> AppResult<()> {
+        let record = MasterPasswordRecord {
+            score,
+            set_at: Utc::now(),
+        };
+        self.put_meta(
+            MASTER_PASSWORD_META_KEY,
+            &serde_json::to_vec(&record).map_err(AppError::Serialization)?,
+        )
+    }
+
+    /// A suggestion to change the master password if it is weaker or older than
+    /// the settings allow. Vaults created before scores were recorded get none.
+    pub fn master_password_reminder(&self) -> AppResult<Option<MasterPasswordReminder>> {
+        let Some(json) = self.get_meta(MASTER_PASSWORD_META_KEY)? else {
+            return Ok(None);
+        };
+        let record: MasterPasswordRecord =
+            serde_json::from_slice(&json).map_err(AppError::Serialization)?;
+        Ok(master_password::reminder(
+            &record,
+            &self.get_settings()?,
+            Utc::now(),
+        ))
+    }
+
+    /// Records `message` if the audit level asks for it
+    fn audit(&self, message: Message, item_uuid: Option<&str>) -> AppResult<()> {
+        if self.audit_filter.records(&message.key) {
+            self.audit_logger
+                .add_log(&message.audit_text(), item_uuid)?;
+        }
+        Ok(())
+    }
+
+    /// `audit` within a transaction
+    fn audit_txn(
+        &self,
+        txn: &dyn RepositoryTransaction,
+        message: Message,
+        item_uuid: Option<&str>,
+    ) -> AppResult<()> {
+        if self.audit_filter.records(&message.key) {
+            txn.add_log(&message.audit_text(), item_uuid)?;
+        }
+        Ok(())
+    }
+
+    /// Takes the audit level from the settings of the vault just unlocked
+    fn load_audit_level(&self) {
+        let level = self
+            .get_settings()
+            .map(|settings| settings.audit_level)
+            .unwrap_or_default();
+        self.audit_filter.set_level(level);
+    }
+
+    /// What follows a successful unlock or vault creation
+    fn finish_unlock(&mut self) -> AppResult<()> {
+        hardening::harden_process();
+        // Storage must be reachable and a half-finished operation must not be visible;
+        // stay locked until both are sorted out
+        let ready = self
+            .register_device()
+            .and_then(|()| self.apply_ephemeral_session())
+            .and_then(|()| self.open_encrypted_storage())
+            .and_then(|()| self.recover_on_unlock());
+        if let Err(e) = ready {
+            warn!(error = %e, "vault unlocked but not ready; locking again");
+            self.close();
+            return Err(e);
+        }
+        // Only makes things faster, so a failure does not keep the vault locked
+        if let Err(e) = self.refresh_performance_mode() {
+            warn!(error = %e, "could not apply performance mode");
+        }
+        if let Err(e) = self.backfill_totp_flags() {
+            warn!(error = %e, "could not record which items have TOTP");
+        }
+        if let Err(e) = self.load_notifications() {
+            warn!(error = %e, "could not record notifications from while locked");
+        }
+
+        info!("vault unlocked");
+        Ok(())
+    }
+
+    /// `path` taken from the data directory if it is relative
+    fn resolve_path(&self, path: &Path) -> PathBuf {
+        portable::resolve(self.data_dir.as_deref(), path)
+    }
+
+    /// Moves the key into a session process if the settings ask for one. A
+    /// session that cannot start keeps the vault locked rather than leave the
+    /// key where the user asked it not to be.
+    fn apply_ephemeral_session(&self) -> AppResult<()> {
+        if !self.get_settings()?.ephemeral_session {
+            return Ok(());
+        }
+        self.crypto.lock().unwrap().start_ephemeral_session()?;
+        info!("vault key moved to an ephemeral session");
+        Ok(())
+    }
+
+    /// Whether the vault key is held by an ephemeral session process
+    pub fn in_ephemeral_session(&self) -> bool {
+        self.crypto.lock().unwrap().in_ephemeral_session()
+    }
+
+    /// Which memory protections are in effect, see `hardening`
+    pub fn hardening_status(&self) -> HardeningStatus {
+        HardeningStatus {
+            process: hardening::process_hardening(),
+            key_memory_locked: self.crypto.lock().unwrap().key_memory_state(),
+        }
+    }
+
+    /// What `get_app_status` reports
+    pub fn app_status(&self) -> AppResult<AppStatus> {
+        let mut status = AppStatus {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            vault_status: self.vault_status()?,
+            features: Vec::new(),
+            pending_notifications: self.unread_notification_count()?,
+            last_backup_at: None,
+            last_sync_at: self
+                .encrypted_storage
+                .as_ref()
+                .and_then(|storage| storage.last_synced_at()),
+        };
+        if self.is_unlocked {
+            status.features = self
+                .list_features()?
+                .into_iter()
+                .filter(|state| state.enabled)
+                .map(|state| state.feature)
+                .collect();
+            if let Some(dir) = self.get_settings()?.backup_dir {
+                status.last_backup_at = backup::latest_backup(&self.resolve_path(Path::new(&dir)))?;
+            }
+        }
+        Ok(status)
+    }
 
+    /// Records this device as having opened the vault, unless it was revoked
+    fn register_device(&self) -> AppResult<()> {
+        let Some((repo, identity)) = &self.devices else {
+            return Ok(());
+        };
+        let now = Utc::now();
+        let known = match repo.get_device(&identity.id) {
+            Ok(device) => Some(device),
+            Err(AppError::NotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+        if known
+            .as_ref()
+            .is_some_and(|device| device.revoked_at.is_some())
+        {
+            warn!(device = %identity.id, "revoked device tried to unlock the vault");
+            return Err(AppError::Other(
+                "This device was revoked and can no longer open the vault".to_string(),
+            ));
+        }
+        repo.record_device(&Device::new(identity, now))?;
+        if known.is_none() {
+            self.audit(
+                Message::new("audit.device_added").with_param("device", &identity.name),
+                None,
+            )?;
+        }
         Ok(())
     }
 
+    /// Hands the storage key and current network settings to an encrypted backend
+    fn open_encrypted_storage(&self) -> AppResult<()> {
+        let Some(storage) = &self.encrypted_storage else {
+            return Ok(());
+        };
+        let cipher = self
+            .crypto
+            .lock()
+            .unwrap()
+            .subkey_cipher(remote::STORAGE_KEY_CONTEXT)?;
+        storage.open_storage(cipher, &self.get_settings()?.network)
+    }
+
     /// Locks the vault
     pub fn lock(&mut self) -> AppResult<()> {
         if self.is_unlocked {
-            // Lock the crypto service
-            let mut crypto = self.crypto.lock().unwrap();
-            crypto.lock();
-            self.is_unlocked = false;
-            drop(crypto); // Release lock before logging
+            self.close();
+            info!("vault locked");
 
             // Log the lock action via the AuditLogger trait
-            self.audit_logger.add_log("Vault locked", None)?;
+            self.audit(Message::new("audit.vault_locked"), None)?;
         }
         Ok(())
     }
 
+    /// Locks at once, e.g. from a global hotkey. Unlike `lock` it always
+    /// wipes and logs, even if the vault looks locked already, so the audit log
+    /// shows the panic was triggered.
+    pub fn panic_lock(&mut self) -> AppResult<()> {
+        self.close();
+        warn!("panic lock");
+        self.audit(Message::new("audit.panic_lock"), None)
+    }
+
+    /// Forgets the keys without logging
+    fn close(&mut self) {
+        // Lock the crypto service
+        self.crypto.lock().unwrap().lock();
+        self.is_unlocked = false;
+        *self.rotation.get_mut().unwrap() = None;
+        *self.autotype.get_mut().unwrap() = None;
+        *self.bulk_session.get_mut().unwrap() = None;
+        *self.undo.get_mut().unwrap() = UndoHistory::default();
+        *self.wordlists.get_mut().unwrap() = None;
+        *self.search_index.lock().unwrap() = None;
+        self.performance_active.store(false, Ordering::SeqCst);
+        if let Some(storage) = &self.encrypted_storage {
+            storage.close_storage();
+        }
+    }
+
+    /// Whether the vault exists and is unlocked, going by the stored master password hash
+    pub fn vault_status(&self) -> AppResult<VaultStatus> {
+        if self.is_unlocked {
+            return Ok(VaultStatus::Unlocked);
+        }
+        if self.settings_repo.get_master_password_hash()?.is_none() {
+            return Ok(VaultStatus::NotInitialized);
+        }
+        Ok(match self.crypto.lock().unwrap().has_loaded_hash() {
+            true => VaultStatus::Locked,
+            false => VaultStatus::Created,
+        })
+    }
+
     /// Checks if the vault is unlocked
     pub fn is_unlocked(&self) -> bool {
         self.is_unlocked
     }
 
-    /// Adds a new credential to the vault
+    /// Adds a new credential to the vault, subject to the password policy
     pub fn add_credential(
         &self,
         site: &str,
         username: &str,
         secret: Secret,
         tags: Option<Vec<String>>,
+    ) -> AppResult<Credential> {
+        self.add_credential_checked(site, username, secret, tags, PolicyCheck::Enforce)
+    }
+
+    /// `add_credential`, optionally saving a password the policy refuses
+    pub fn add_credential_checked(
+        &self,
+        site: &str,
+        username: &str,
+        secret: Secret,
+        tags: Option<Vec<String>>,
+        check: PolicyCheck,
+    ) -> AppResult<Credential> {
+        let credential = self.prepare_credential(site, username, &secret, tags, check)?;
+
+        // Persist using the CredentialRepository trait
+        self.credential_repo
+            .add_credential(&credential, credential.strength)?;
+        debug!(uuid = %credential.uuid, "credential added");
+
+        Ok(credential)
+    }
+
+    /// Validates and encrypts a new credential without storing it, so async
+    /// callers can write it through `async_credentials` after releasing their lock
+    pub fn prepare_credential(
+        &self,
+        site: &str,
+        username: &str,
+        secret: &Secret,
+        tags: Option<Vec<String>>,
+        check: PolicyCheck,
+    ) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        validation::validate_credential(site, username, secret, tags.as_deref().unwrap_or(&[]))?;
+        if check == PolicyCheck::Enforce {
+            self.check_password_policy(&secret.password)?;
+        }
+        self.new_credential(site, username, secret, tags)
+    }
+
+    /// Generates a password and stores it as a new credential in one step, so the
+    /// plaintext never has to pass through the caller. Read it back with
+    /// `get_credential_secret` when it is needed.
+    pub fn add_generated_credential(
+        &self,
+        site: &str,
+        username: &str,
+        options: &GeneratorOptions,
+        tags: Option<Vec<String>>,
     ) -> AppResult<Credential> {
         self.ensure_unlocked()?;
+        let secret = Secret {
+            password: generator::generate(options)?,
+            ..Secret::default()
+        };
+        validation::validate_credential(site, username, &secret, tags.as_deref().unwrap_or(&[]))?;
+        let mut credential = self.new_credential(site, username, &secret, tags)?;
+
+        // zxcvbn looks for patterns and undervalues random strings; the exact
+        // entropy is known here, so score from that instead
+        let bits = generator::entropy_bits(options)?;
+        credential.strength = strength::strength_from_entropy(bits);
+        credential.strength_source = StrengthSource::Generated;
+        credential.entropy_bits = Some(bits);
+
+        self.credential_repo
+            .add_credential(&credential, credential.strength)?;
+        Ok(credential)
+    }
 
+    /// Encrypts a secret into a new credential without storing it
+    fn new_credential(
+        &self,
+        site: &str,
+        username: &str,
+        secret: &Secret,
+        tags: Option<Vec<String>>,
+    ) -> AppResult<Credential> {
         // Encrypt the secret
-        let secret_json = serde_json::to_string(&secret).map_err(AppError::Serialization)?;
-        let crypto = self.crypto.lock().unwrap();
-        let secret_enc = crypto.encrypt(
-            secret_json.as_bytes(),
-            format!("{}:{}", site, username).as_bytes(), // AAD
-        )?;
-        drop(crypto);
+        let secret_enc = self.encrypt_secret(secret, site, username, None)?;
 
         // Create a new credential struct
         let mut credential = Credential::new(site.to_string(), username.to_string(), secret_enc);
+        credential.has_totp = secret.totp.is_some();
 
         // Set tags if provided
         if let Some(tag_vec) = tags {
-            credential.tags = tag_vec;
+            credential.tags = validation::normalize_tags(&tag_vec);
         }
 
         // Calculate password strength using the injected calculator
         let strength = self
             .strength_calculator
-            .calculate_strength(&secret.password);
+            .calculate_strength(&secret.password, &*self.wordlists()?);
         credential.strength = strength;
-
-        // Persist using the CredentialRepository trait
-        self.credential_repo.add_credential(&credential, strength)?;
-
         Ok(credential)
     }
 
@@ -136,140 +1041,4054 @@ impl VaultManager {
         tags: Vec<String>,
         expires_at: Option<chrono::DateTime<Utc>>,
     ) -> AppResult<()> {
-        self.ensure_unlocked()?;
-
-        // Encrypt the updated secret
-        let secret_json = serde_json::to_string(&secret).map_err(AppError::Serialization)?;
-        let crypto = self.crypto.lock().unwrap();
-        let secret_enc = crypto.encrypt(
-            secret_json.as_bytes(),
-            format!("{}:{}", site, username).as_bytes(), // AAD
-        )?;
-        drop(crypto);
-
-        // Calculate new strength
-        let strength = self
-            .strength_calculator
-            .calculate_strength(&secret.password);
-
-        // Fetch existing to preserve created_at and potentially breach_state
-        // Alternatively, the update method in the repo could handle partial updates
-        let mut existing_credential = self.credential_repo.get_credential(uuid)?;
+        self.update_credential_checked(
+            uuid,
+            site,
+            username,
+            secret,
+            tags,
+            expires_at,
+            PolicyCheck::Enforce,
+        )
+    }
 
-        // Update fields
-        existing_credential.site = site.to_string();
-        existing_credential.username = username.to_string();
-        existing_credential.secret_enc = secret_enc;
-        existing_credential.tags = tags;
-        existing_credential.updated_at = Utc::now();
-        existing_credential.expires_at = expires_at;
-        existing_credential.strength = strength;
-        // Keep existing_credential.breach_state
-        // Keep existing_credential.created_at
+    /// `update_credential`, optionally saving a password the policy refuses. The
+    /// policy only applies when the password changes.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_credential_checked(
+        &self,
+        uuid: &str,
+        site: &str,
+        username: &str,
+        secret: Secret,
+        tags: Vec<String>,
+        expires_at: Option<chrono::DateTime<Utc>>,
+        check: PolicyCheck,
+    ) -> AppResult<()> {
+        self.ensure_unlocked()?;
+        if check == PolicyCheck::Enforce {
+            self.check_changed_password_policy(uuid, &secret.password)?;
+        }
+        let updated = self.updated_credential(uuid, site, username, secret, tags, expires_at)?;
 
         // Persist changes using the CredentialRepository trait
-        self.credential_repo
-            .update_credential(&existing_credential)?;
+        self.credential_repo.update_credential(&updated)?;
+        debug!(uuid, revision = updated.revision, "credential updated");
 
         // Audit log handled by repository's update_credential
 
         Ok(())
     }
 
-    /// Deletes a credential by UUID
-    pub fn delete_credential(&self, uuid: &str) -> AppResult<()> {
+    /// Updates a credential only if it is still at `revision`, the value the
+    /// caller read before editing. Returns `AppError::Conflict` otherwise, so an
+    /// edit made from a stale copy cannot overwrite a newer change.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_credential_at_revision(
+        &self,
+        uuid: &str,
+        revision: u64,
+        site: &str,
+        username: &str,
+        secret: Secret,
+        tags: Vec<String>,
+        expires_at: Option<chrono::DateTime<Utc>>,
+    ) -> AppResult<()> {
         self.ensure_unlocked()?;
+        self.check_changed_password_policy(uuid, &secret.password)?;
+        let mut updated =
+            self.updated_credential(uuid, site, username, secret, tags, expires_at)?;
+        updated.revision = revision;
+        self.credential_repo.update_credential(&updated)
+    }
+
+    /// Refuses `password` if it is weaker than the settings allow or on an
+    /// imported breach list (when they are to be blocked). Items without a
+    /// password, such as TOTP-only ones, have nothing to check.
+    fn check_password_policy(&self, password: &str) -> AppResult<()> {
+        let settings = self.get_settings()?;
+        if password.is_empty()
+            || (settings.min_password_score == 0 && !settings.block_compromised_passwords)
+        {
+            return Ok(());
+        }
+        let strength = self
+            .strength_calculator
+            .calculate_strength(password, &*self.wordlists()?);
+        let breach_sources = if settings.block_compromised_passwords {
+            self.local_breach_sources(password)?
+        } else {
+            Vec::new()
+        };
+        password_policy::check(
+            &settings,
+            strength::strength_bucket(strength),
+            breach_sources,
+        )?;
+        Ok(())
+    }
+
+    /// `check_password_policy` for an edit, if it changes the password
+    fn check_changed_password_policy(&self, uuid: &str, password: &str) -> AppResult<()> {
+        let existing = self.credential_repo.get_credential(uuid)?;
+        let unchanged = self
+            .open_secret(&existing)
+            .is_ok_and(|old| old.password == password);
+        if unchanged {
+            return Ok(());
+        }
+        self.check_password_policy(password)
+    }
+
+    /// Validates an edit and applies it to a stored credential without saving it
+    fn updated_credential(
+        &self,
+        uuid: &str,
+        site: &str,
+        username: &str,
+        mut secret: Secret,
+        tags: Vec<String>,
+        expires_at: Option<chrono::DateTime<Utc>>,
+    ) -> AppResult<Credential> {
+        // Fetch existing to preserve created_at and potentially breach_state
+        // Alternatively, the update method in the repo could handle partial updates
+        let mut existing_credential = self.credential_repo.get_credential(uuid)?;
+        validation::validate_update(site, username, &secret, &tags, &existing_credential.tags)?;
+
+        // A breach verdict only applies to the password it was computed for.
+        // If the old secret can't be decrypted, assume the password changed.
+        let old_secret = self.open_secret(&existing_credential).ok();
+        let password_changed = old_secret
+            .as_ref()
+            .map(|old| old.password != secret.password)
+            .unwrap_or(true);
+
+        // Carry the history over and record the replaced password
+        if let Some(old) = old_secret {
+            secret.password_history = old.password_history;
+            if password_changed {
+                secret.password_history.insert(
+                    0,
+                    PasswordHistoryEntry {
+                        password: old.password,
+                        replaced_at: Utc::now(),
+                    },
+                );
+                secret.password_history.truncate(PASSWORD_HISTORY_LIMIT);
+            }
+        }
+
+        // Encrypt the updated secret
+        let secret_enc = self.encrypt_secret(
+            &secret,
+            site,
+            username,
+            existing_credential.collection_id.as_deref(),
+        )?;
+
+        // Update fields
+        existing_credential.site = site.to_string();
+        existing_credential.username = username.to_string();
+        existing_credential.secret_enc = secret_enc;
+        existing_credential.has_totp = secret.totp.is_some();
+        existing_credential.tags = validation::normalize_tags(&tags);
+        existing_credential.updated_at = Utc::now();
+        existing_credential.expires_at = expires_at;
+        // A generated password keeps its exact score until it is replaced
+        if password_changed || existing_credential.strength_source == StrengthSource::Analyzed {
+            existing_credential.strength = self
+                .strength_calculator
+                .calculate_strength(&secret.password, &*self.wordlists()?);
+            existing_credential.strength_source = StrengthSource::Analyzed;
+            existing_credential.entropy_bits = None;
+        }
+        if let Some(api_key) = existing_credential.api_key.as_mut() {
+            api_key.secret_preview = api_keys::secret_preview(&secret.password);
+        }
+        if password_changed {
+            existing_credential.breach_state = BreachState::Unknown;
+            existing_credential.breach_checked_at = None;
+            existing_credential.password_changed_at = existing_credential.updated_at;
+        }
+        // Keep existing_credential.created_at
+        Ok(existing_credential)
+    }
+
+    /// Adds an API key item; its expiry is the item's `expires_at`
+    pub fn add_api_key(&self, input: ApiKeyInput) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        let (site, details) = (input.site(), input.details());
+        let secret = Secret {
+            password: input.secret,
+            notes: input.notes,
+            ..Secret::default()
+        };
+        validation::validate_credential(&site, &input.username, &secret, &input.tags)?;
+        validation::validate_api_key(&details)?;
+        if secret.password.is_empty() {
+            return Err(AppError::InvalidInput(
+                "The API key secret must not be empty".to_string(),
+            ));
+        }
+
+        let mut credential =
+            self.new_credential(&site, &input.username, &secret, Some(input.tags))?;
+        credential.kind = CredentialKind::ApiKey;
+        credential.expires_at = input.expires_at;
+        credential.api_key = Some(details);
+        self.credential_repo
+            .add_credential(&credential, credential.strength)?;
+        debug!(uuid = %credential.uuid, "API key added");
+        Ok(credential)
+    }
+
+    /// Replaces everything about an API key item; a changed secret goes to the
+    /// password history like any password
+    pub fn update_api_key(&self, uuid: &str, input: ApiKeyInput) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        let (site, details) = (input.site(), input.details());
+        let existing = self.credential_repo.get_credential(uuid)?;
+        let mut secret = self.open_secret(&existing)?;
+        secret.password = input.secret;
+        secret.notes = input.notes;
+        validation::validate_api_key(&details)?;
+        if secret.password.is_empty() {
+            return Err(AppError::InvalidInput(
+                "The API key secret must not be empty".to_string(),
+            ));
+        }
+
+        let mut updated = self.updated_credential(
+            uuid,
+            &site,
+            &input.username,
+            secret,
+            input.tags,
+            input.expires_at,
+        )?;
+        updated.kind = CredentialKind::ApiKey;
+        updated.api_key = Some(details);
+        self.credential_repo.update_credential(&updated)?;
+        self.credential_repo.get_credential(uuid)
+    }
+
+    /// Adds a payment card. The number is checked (Luhn and length) and stored
+    /// as the password; the expiry becomes the item's `expires_at`.
+    pub fn add_card(&self, input: CardInput) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        let (number, details) = input.validate()?;
+        let site = input.site(&details);
+        let secret = card_secret(number, input.cvv, input.notes, Secret::default());
+        validation::validate_credential(&site, &input.cardholder, &secret, &input.tags)?;
+
+        let mut credential =
+            self.new_credential(&site, &input.cardholder, &secret, Some(input.tags))?;
+        credential.kind = CredentialKind::CreditCard;
+        credential.expires_at = Some(details.expiry.expires_at());
+        credential.card = Some(details);
+        self.credential_repo
+            .add_credential(&credential, credential.strength)?;
+        debug!(uuid = %credential.uuid, "card added");
+        Ok(credential)
+    }
+
+    /// Replaces everything about a card item
+    pub fn update_card(&self, uuid: &str, input: CardInput) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        let (number, details) = input.validate()?;
+        let site = input.site(&details);
+        let existing = self.credential_repo.get_credential(uuid)?;
+        let secret = card_secret(number, input.cvv, input.notes, self.open_secret(&existing)?);
+
+        let mut updated = self.updated_credential(
+            uuid,
+            &site,
+            &input.cardholder,
+            secret,
+            input.tags,
+            Some(details.expiry.expires_at()),
+        )?;
+        updated.kind = CredentialKind::CreditCard;
+        updated.card = Some(details);
+        self.credential_repo.update_credential(&updated)?;
+        self.credential_repo.get_credential(uuid)
+    }
+
+    /// Adds an identity item
+    pub fn add_identity(&self, input: IdentityInput) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        input.validate()?;
+        let (site, username) = (input.site(), input.username());
+        let secret = Secret {
+            notes: input.notes,
+            identity: Some(input.identity),
+            ..Secret::default()
+        };
+        validation::validate_credential(&site, &username, &secret, &input.tags)?;
+
+        let mut credential = self.new_credential(&site, &username, &secret, Some(input.tags))?;
+        credential.kind = CredentialKind::Identity;
+        self.credential_repo
+            .add_credential(&credential, credential.strength)?;
+        debug!(uuid = %credential.uuid, "identity added");
+        Ok(credential)
+    }
+
+    /// Replaces everything about an identity item
+    pub fn update_identity(&self, uuid: &str, input: IdentityInput) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        input.validate()?;
+        let (site, username) = (input.site(), input.username());
+        let existing = self.credential_repo.get_credential(uuid)?;
+        let mut secret = self.open_secret(&existing)?;
+        secret.notes = input.notes;
+        secret.identity = Some(input.identity);
+
+        let mut updated =
+            self.updated_credential(uuid, &site, &username, secret, input.tags, None)?;
+        updated.kind = CredentialKind::Identity;
+        self.credential_repo.update_credential(&updated)?;
+        self.credential_repo.get_credential(uuid)
+    }
+
+    /// Decrypts an identity item into the profile the browser extension fills
+    /// forms with, and records the export
+    pub fn export_autofill_profile(&self, uuid: &str) -> AppResult<AutofillProfile> {
+        self.ensure_unlocked()?;
+        let credential = self.credential_repo.get_credential(uuid)?;
+        let identity = match credential.kind {
+            CredentialKind::Identity => self.decrypt_secret(&credential)?.identity,
+            _ => None,
+        }
+        .ok_or_else(|| AppError::InvalidInput(format!("{} is not an identity", credential.site)))?;
+        let profile = identity::autofill_profile(uuid, &credential.site, &identity);
+
+        self.audit(
+            Message::new("audit.autofill_profile_exported").with_param("site", &credential.site),
+            Some(uuid),
+        )?;
+        Ok(profile)
+    }
+
+    /// Adds a Wi-Fi network item
+    pub fn add_wifi(&self, input: WifiInput) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        let details = input.validate()?;
+        let secret = Secret {
+            password: input.password,
+            notes: input.notes,
+            ..Secret::default()
+        };
+        validation::validate_credential(&details.ssid, "", &secret, &input.tags)?;
+
+        let mut credential = self.new_credential(&details.ssid, "", &secret, Some(input.tags))?;
+        credential.kind = CredentialKind::Wifi;
+        credential.wifi = Some(details);
+        self.credential_repo
+            .add_credential(&credential, credential.strength)?;
+        debug!(uuid = %credential.uuid, "wifi network added");
+        Ok(credential)
+    }
+
+    /// Replaces everything about a Wi-Fi network item
+    pub fn update_wifi(&self, uuid: &str, input: WifiInput) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        let details = input.validate()?;
+        let existing = self.credential_repo.get_credential(uuid)?;
+        let mut secret = self.open_secret(&existing)?;
+        secret.password = input.password;
+        secret.notes = input.notes;
+        validation::validate_credential(&details.ssid, "", &secret, &input.tags)?;
+
+        let mut updated =
+            self.updated_credential(uuid, &details.ssid, "", secret, input.tags, None)?;
+        updated.kind = CredentialKind::Wifi;
+        updated.wifi = Some(details);
+        self.credential_repo.update_credential(&updated)?;
+        self.credential_repo.get_credential(uuid)
+    }
+
+    /// Renders a Wi-Fi network's QR code for guests to scan, and records that
+    /// its passphrase was shown
+    pub fn generate_wifi_qr(&self, uuid: &str) -> AppResult<WifiQr> {
+        self.ensure_unlocked()?;
+        let credential = self.credential_repo.get_credential(uuid)?;
+        let details = credential
+            .wifi
+            .as_ref()
+            .filter(|_| credential.kind == CredentialKind::Wifi)
+            .ok_or_else(|| {
+                AppError::InvalidInput(format!("{} is not a Wi-Fi network", credential.site))
+            })?;
+        let secret = self.decrypt_secret(&credential)?;
+        let qr = wifi::qr_code(details, &secret.password)?;
+
+        self.audit(
+            Message::new("audit.wifi_qr_generated").with_param("site", &credential.site),
+            Some(uuid),
+        )?;
+        Ok(qr)
+    }
+
+    /// Encrypts one item under `pin` as a short-lived QR code for another device
+    /// to import with `import_credential_qr`. Password history is left out.
+    pub fn export_credential_qr(&self, uuid: &str, pin: &str) -> AppResult<CredentialQr> {
+        self.ensure_unlocked()?;
+        let credential = self.credential_repo.get_credential(uuid)?;
+        let secret = self.decrypt_secret(&credential)?;
+        let qr = qr_transfer::seal(&TransferredItem::new(&credential, secret), pin, Utc::now())?;
+
+        self.audit(
+            Message::new("audit.credential_qr_exported").with_param("site", &credential.site),
+            Some(uuid),
+        )?;
+        Ok(qr)
+    }
+
+    /// Adds the item carried by a transfer code from `export_credential_qr`
+    pub fn import_credential_qr(&self, payload: &str, pin: &str) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        let item = qr_transfer::open(payload, pin, Utc::now())?;
+        validation::validate_credential(&item.site, &item.username, &item.secret, &item.tags)?;
+
+        let mut credential =
+            self.new_credential(&item.site, &item.username, &item.secret, Some(item.tags))?;
+        credential.kind = item.kind;
+        credential.expires_at = item.expires_at;
+        credential.api_key = item.api_key;
+        credential.card = item.card;
+        credential.wifi = item.wifi;
+        self.credential_repo
+            .add_credential(&credential, credential.strength)?;
+        self.audit(
+            Message::new("audit.credential_qr_imported").with_param("site", &credential.site),
+            Some(&credential.uuid),
+        )?;
+        Ok(credential)
+    }
+
+    /// Replaces an item's recovery codes with those in `text` (one or more per
+    /// line, as sites show them); codes kept from before stay marked used
+    pub fn set_recovery_codes(&self, uuid: &str, text: &str) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        let codes = recovery_codes::parse_codes(text)?;
+        let credential = self.credential_repo.get_credential(uuid)?;
+        let mut secret = self.open_secret(&credential)?;
+        secret.recovery_codes = recovery_codes::replace(&secret.recovery_codes, codes);
+        self.update_credential(
+            uuid,
+            &credential.site,
+            &credential.username,
+            secret,
+            credential.tags,
+            credential.expires_at,
+        )?;
+        self.credential_repo.get_credential(uuid)
+    }
+
+    /// Marks the recovery code at `index` used, so it is not tried again
+    pub fn mark_recovery_code_used(
+        &self,
+        uuid: &str,
+        index: usize,
+        now: DateTime<Utc>,
+    ) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        let credential = self.credential_repo.get_credential(uuid)?;
+        let mut secret = self.open_secret(&credential)?;
+        let remaining = recovery_codes::mark_used(&mut secret.recovery_codes, index, now)?;
+        let updated = self.updated_credential(
+            uuid,
+            &credential.site,
+            &credential.username,
+            secret,
+            credential.tags.clone(),
+            credential.expires_at,
+        )?;
+        self.with_transaction(|txn| {
+            txn.update_credential(&updated)?;
+            self.audit_txn(
+                txn,
+                Message::new("audit.recovery_code_used")
+                    .with_param("site", &credential.site)
+                    .with_param("remaining", remaining),
+                Some(uuid),
+            )?;
+            txn.get_credential(uuid)
+        })
+    }
+
+    /// Items that expire within `within_days` or already have, soonest first
+    pub fn expiring_credentials(
+        &self,
+        within_days: u32,
+        now: DateTime<Utc>,
+    ) -> AppResult<Vec<CredentialSummary>> {
+        self.ensure_unlocked()?;
+        Ok(api_keys::expiring(
+            self.credential_repo.list_credentials(None)?,
+            within_days,
+            now,
+        ))
+    }
+
+    /// Deletes a credential by UUID; undoable
+    pub fn delete_credential(&self, uuid: &str) -> AppResult<()> {
+        self.ensure_unlocked()?;
+        let before = self.credential_repo.get_credential(uuid)?;
 
         // Delete using the CredentialRepository trait
         // The repository handles the audit log internally
         self.credential_repo.delete_credential(uuid)?;
+        self.record_deleted(before);
+        Ok(())
+    }
+
+    /// Makes a deletion done through `async_credentials` undoable; `before` is
+    /// the item as it was read before deleting it
+    pub fn record_deleted(&self, before: Credential) {
+        debug!(uuid = %before.uuid, "credential deleted");
+        let uuid = before.uuid.clone();
+        self.undo.lock().unwrap().record(Operation {
+            kind: OperationKind::Delete,
+            before: vec![ItemState::stored(before)],
+            after: vec![ItemState::absent(&uuid)],
+        });
+    }
+
+    /// Adds and removes tags on several items at once; undoable.
+    /// Returns the items that changed.
+    pub fn bulk_edit(&self, uuids: &[String], edit: &BulkEdit) -> AppResult<Vec<Credential>> {
+        self.ensure_unlocked()?;
+        let edit = BulkEdit {
+            add_tags: validation::normalize_tags(&edit.add_tags),
+            remove_tags: validation::normalize_tags(&edit.remove_tags),
+        };
+        let mut edited = Vec::new();
+        for uuid in uuids {
+            let credential = self.credential_repo.get_credential(uuid)?;
+            let tags = edit.apply(&validation::normalize_tags(&credential.tags));
+            if tags != credential.tags {
+                validation::validate_tag_changes(&tags, &credential.tags)?;
+                edited.push(Credential { tags, ..credential });
+            }
+        }
+        self.write_undoable(OperationKind::BulkEdit, edited)
+    }
+
+    /// Renames a tag on every item that has it, in any spelling; undoable.
+    /// Returns the items that changed.
+    pub fn rename_tag(&self, old: &str, new: &str) -> AppResult<Vec<Credential>> {
+        self.ensure_unlocked()?;
+        let new = validation::normalize_tag(new);
+        validation::validate_tags(std::slice::from_ref(&new))?;
+        let old = validation::normalize_tag(old);
+        let mut edited = Vec::new();
+        for credential in self.credential_repo.list_credentials(None)? {
+            if !credential
+                .tags
+                .iter()
+                .any(|tag| validation::normalize_tag(tag) == old)
+            {
+                continue;
+            }
+            // In place, so the tag keeps its position
+            let renamed: Vec<String> = credential
+                .tags
+                .iter()
+                .map(|tag| {
+                    if validation::normalize_tag(tag) == old {
+                        new.clone()
+                    } else {
+                        tag.clone()
+                    }
+                })
+                .collect();
+            let tags = validation::normalize_tags(&renamed);
+            if tags == credential.tags {
+                continue;
+            }
+            edited.push(Credential { tags, ..credential });
+        }
+        self.write_undoable(OperationKind::TagRename, edited)
+    }
+
+    /// Writes edited copies of stored items and records the operation for undo,
+    /// including the items already written when a write fails
+    fn write_undoable(
+        &self,
+        kind: OperationKind,
+        edited: Vec<Credential>,
+    ) -> AppResult<Vec<Credential>> {
+        let mut operation = Operation {
+            kind,
+            before: Vec::new(),
+            after: Vec::new(),
+        };
+        let mut result = Ok(());
+        for credential in &edited {
+            let before = match self.credential_repo.get_credential(&credential.uuid) {
+                Ok(before) => before,
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            };
+            if let Err(e) = self.credential_repo.update_credential(credential) {
+                result = Err(e);
+                break;
+            }
+            operation.before.push(ItemState::stored(before));
+            operation.after.push(ItemState::stored(
+                self.credential_repo.get_credential(&credential.uuid)?,
+            ));
+        }
+        let written = operation
+            .after
+            .iter()
+            .filter_map(|state| state.credential.clone())
+            .collect();
+        if !operation.after.is_empty() {
+            self.undo.lock().unwrap().record(operation);
+        }
+        result.map(|()| written)
+    }
+
+    /// Reverts the latest delete, bulk edit or tag rename of this session.
+    /// Returns None if there is nothing to undo.
+    pub fn undo_last_operation(&self) -> AppResult<Option<OperationSummary>> {
+        self.ensure_unlocked()?;
+        let Some(mut operation) = self.undo.lock().unwrap().take_undo() else {
+            return Ok(None);
+        };
+        let restored = self.restore_items(&operation.after, &operation.before)?;
+        let mut history = self.undo.lock().unwrap();
+        history.rebase(&operation.before, &restored);
+        operation.before = restored;
+        let summary = operation.summary();
+        history.push_redo(operation);
+        drop(history);
+        self.audit(
+            Message::new("audit.operation_undone")
+                .with_param("operation", summary.kind.name())
+                .with_param("items", summary.items),
+            None,
+        )?;
+        Ok(Some(summary))
+    }
+
+    /// Applies the latest undone operation again. Returns None if there is nothing to redo.
+    pub fn redo(&self) -> AppResult<Option<OperationSummary>> {
+        self.ensure_unlocked()?;
+        let Some(mut operation) = self.undo.lock().unwrap().take_redo() else {
+            return Ok(None);
+        };
+        let restored = self.restore_items(&operation.before, &operation.after)?;
+        let mut history = self.undo.lock().unwrap();
+        history.rebase(&operation.after, &restored);
+        operation.after = restored;
+        let summary = operation.summary();
+        history.push_undo(operation);
+        drop(history);
+        self.audit(
+            Message::new("audit.operation_redone")
+                .with_param("operation", summary.kind.name())
+                .with_param("items", summary.items),
+            None,
+        )?;
+        Ok(Some(summary))
+    }
+
+    /// What `undo_last_operation` and `redo` would do next
+    pub fn undo_status(&self) -> AppResult<UndoStatus> {
+        self.ensure_unlocked()?;
+        Ok(self.undo.lock().unwrap().status())
+    }
+
+    /// Writes `target` over items that must still be as in `expected`; returns
+    /// the items as stored afterwards
+    fn restore_items(
+        &self,
+        expected: &[ItemState],
+        target: &[ItemState],
+    ) -> AppResult<Vec<ItemState>> {
+        for state in expected {
+            if self.stored_state(&state.uuid)?.revision() != state.revision() {
+                return Err(AppError::Conflict(format!(
+                    "{} was changed after the operation",
+                    state.uuid
+                )));
+            }
+        }
+        let mut restored = Vec::with_capacity(target.len());
+        for state in target {
+            let current = self.stored_state(&state.uuid)?;
+            match (&state.credential, current.credential) {
+                (Some(credential), Some(stored)) => {
+                    self.credential_repo.update_credential(&Credential {
+                        revision: stored.revision,
+                        ..credential.clone()
+                    })?;
+                }
+                (Some(credential), None) => {
+                    self.credential_repo
+                        .add_credential(credential, credential.strength)?;
+                }
+                (None, Some(_)) => {
+                    self.credential_repo.delete_credential(&state.uuid)?;
+                }
+                (None, None) => {}
+            }
+            restored.push(self.stored_state(&state.uuid)?);
+        }
+        Ok(restored)
+    }
+
+    fn stored_state(&self, uuid: &str) -> AppResult<ItemState> {
+        if self.credential_repo.credential_exists(uuid)? {
+            Ok(ItemState::stored(
+                self.credential_repo.get_credential(uuid)?,
+            ))
+        } else {
+            Ok(ItemState::absent(uuid))
+        }
+    }
+
+    /// Credentials saved for the site of `url`, or a look-alike warning when the
+    /// URL only resembles a saved site
+    pub fn find_credentials_for_url(&self, url: &str) -> AppResult<UrlMatch> {
+        self.ensure_unlocked()?;
+        let credentials = self.credential_repo.list_credentials(None)?;
+        Ok(site_match::match_url(url, credentials))
+    }
+
+    fn pending_save_records(&self) -> AppResult<Vec<PendingSaveRecord>> {
+        self.ensure_unlocked()?;
+        match self.settings_repo.get_encrypted_pending_saves()? {
+            Some(container) => {
+                let json = self
+                    .crypto
+                    .lock()
+                    .unwrap()
+                    .decrypt(&container, PENDING_SAVES_AAD)?;
+                serde_json::from_slice(&json).map_err(AppError::Serialization)
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn save_pending_save_records(&self, records: &[PendingSaveRecord]) -> AppResult<()> {
+        let container = self
+            .crypto
+            .lock()
+            .unwrap()
+            .encrypt(&serde_json::to_vec(records)?, PENDING_SAVES_AAD)?;
+        self.settings_repo.save_encrypted_pending_saves(&container)
+    }
+
+    /// Keeps a login captured in the browser until the user approves it. Returns
+    /// None if the vault already holds this password for the site. A newer capture
+    /// of the same site and username replaces the older one.
+    pub fn capture_login(
+        &self,
+        url: &str,
+        username: &str,
+        password: &str,
+        now: DateTime<Utc>,
+    ) -> AppResult<Option<PendingSave>> {
+        self.ensure_unlocked()?;
+        if !self.is_feature_enabled(Feature::BrowserBridge)? {
+            return Err(AppError::InvalidInput(
+                "Saving logins from the browser is turned off".to_string(),
+            ));
+        }
+        if password.is_empty() {
+            return Err(AppError::InvalidInput(
+                "Captured password must not be empty".to_string(),
+            ));
+        }
+        let site = site_match::host_of(url)
+            .ok_or_else(|| AppError::InvalidInput(format!("Not a web page address: {}", url)))?;
+        let domain = site_match::registrable_domain(&site);
+
+        let credentials = self.credential_repo.list_credentials(None)?;
+        let existing = credentials.iter().find(|credential| {
+            credential.username.eq_ignore_ascii_case(username)
+                && site_match::host_of(&credential.site)
+                    .is_some_and(|host| site_match::registrable_domain(&host) == domain)
+        });
+        if let Some(credential) = existing {
+            if self.open_secret(credential)?.password == password {
+                return Ok(None);
+            }
+        }
+        let existing_uuid = existing.map(|credential| credential.uuid.clone());
+        let warning = match existing_uuid {
+            Some(_) => None,
+            None => site_match::match_url(url, credentials).warning,
+        };
+
+        let save = PendingSave {
+            id: Uuid::new_v4().to_string(),
+            url: url.to_string(),
+            site,
+            username: username.to_string(),
+            existing_uuid,
+            warning,
+            captured_at: now,
+        };
+        let mut records = self.pending_save_records()?;
+        records.retain(|record| {
+            record.save.site != save.site || !record.save.username.eq_ignore_ascii_case(username)
+        });
+        records.push(PendingSaveRecord {
+            save: save.clone(),
+            password: password.to_string(),
+        });
+        let excess = records.len().saturating_sub(MAX_PENDING_SAVES);
+        records.drain(..excess);
+        self.save_pending_save_records(&records)?;
+        Ok(Some(save))
+    }
+
+    /// Captured logins waiting for approval, newest first
+    pub fn list_pending_saves(&self) -> AppResult<Vec<PendingSave>> {
+        Ok(self
+            .pending_save_records()?
+            .into_iter()
+            .rev()
+            .map(|record| record.save)
+            .collect())
+    }
+
+    /// Writes a captured login to the vault: the matching item's password is
+    /// replaced, or a new item is added if there is none (any more)
+    pub fn approve_pending_save(&self, id: &str) -> AppResult<Credential> {
+        let mut records = self.pending_save_records()?;
+        let index = records
+            .iter()
+            .position(|record| record.save.id == id)
+            .ok_or_else(|| AppError::NotFound(id.to_string()))?;
+        let record = &records[index];
+
+        let existing = match &record.save.existing_uuid {
+            Some(uuid) => match self.credential_repo.get_credential(uuid) {
+                Ok(credential) => Some(credential),
+                Err(AppError::NotFound(_)) => None,
+                Err(e) => return Err(e),
+            },
+            None => None,
+        };
+        let credential = match existing {
+            Some(credential) => self.replace_password(
+                &credential.uuid,
+                &record.password,
+                "audit.saved_from_browser",
+            )?,
+            // The site already uses this password; refusing to store it would
+            // not make it any stronger
+            None => self.add_credential_checked(
+                &record.save.site,
+                &record.save.username,
+                Secret {
+                    password: record.password.clone(),
+                    ..Secret::default()
+                },
+                None,
+                PolicyCheck::SaveAnyway,
+            )?,
+        };
+
+        records.remove(index);
+        self.save_pending_save_records(&records)?;
+        Ok(credential)
+    }
+
+    /// Drops a captured login without saving it
+    pub fn discard_pending_save(&self, id: &str) -> AppResult<()> {
+        let mut records = self.pending_save_records()?;
+        let count = records.len();
+        records.retain(|record| record.save.id != id);
+        if records.len() == count {
+            return Err(AppError::NotFound(id.to_string()));
+        }
+        self.save_pending_save_records(&records)
+    }
+
+    /// Sets `has_totp` on items stored before it was tracked. Their secrets have to be
+    /// read for that, so it happens once, on the first unlock after the upgrade.
+    fn backfill_totp_flags(&self) -> AppResult<()> {
+        if self.get_meta(TOTP_FLAGS_META_KEY)?.is_some() {
+            return Ok(());
+        }
+        let mut flagged = 0;
+        for credential in self.credential_repo.list_credentials(None)? {
+            // An item that cannot be read now is no worse off than before
+            let has_totp = match self.open_secret(&credential) {
+                Ok(secret) => secret.totp.is_some(),
+                Err(e) => {
+                    warn!(uuid = %credential.uuid, error = %e, "could not check item for TOTP");
+                    continue;
+                }
+            };
+            if has_totp != credential.has_totp {
+                self.credential_repo
+                    .set_has_totp(&credential.uuid, has_totp)?;
+                flagged += 1;
+            }
+        }
+        self.put_meta(TOTP_FLAGS_META_KEY, b"1")?;
+        info!(flagged, "recorded which items have TOTP");
         Ok(())
     }
 
-    /// Gets a credential by UUID (metadata only, no decrypted secret)
-    pub fn get_credential(&self, uuid: &str) -> AppResult<Credential> {
+    /// The quick search index, built if it was dropped since the last search
+    fn search_index(&self) -> AppResult<Arc<SearchIndex>> {
+        let mut cached = self.search_index.lock().unwrap();
+        if let Some(index) = cached.as_ref() {
+            return Ok(index.clone());
+        }
+        let index = Arc::new(SearchIndex::build(
+            self.credential_repo.list_credentials(None)?,
+        ));
+        debug!(items = index.len(), "quick search index built");
+        *cached = Some(index.clone());
+        Ok(index)
+    }
+
+    /// Items whose site, username or tags start with the words of `term`, best
+    /// first. Served from an in-memory index that is rebuilt after changes.
+    pub fn quick_search(&self, term: &str, limit: usize) -> AppResult<Vec<QuickSearchHit>> {
+        self.ensure_unlocked()?;
+        let hits = self.search_index()?.search(term, limit);
+        self.record_listing(Some(term), hits.len())?;
+        Ok(hits)
+    }
+
+    /// Tags already in use that start with `prefix`, the most used first
+    pub fn suggest_tags(&self, prefix: &str, limit: usize) -> AppResult<Vec<TagSuggestion>> {
+        self.ensure_unlocked()?;
+        Ok(self.search_index()?.suggest_tags(prefix, limit))
+    }
+
+    /// What the user is asked to confirm for a received link. A TOTP setup comes
+    /// with the items it probably belongs to; the item an `open_credential` link
+    /// points to has to exist.
+    pub fn deep_link_request(&self, pending: &PendingDeepLink) -> AppResult<DeepLinkRequest> {
+        self.ensure_unlocked()?;
+        let request = match &pending.link {
+            DeepLink::Totp {
+                issuer, account, ..
+            } => {
+                let credentials = self.credential_repo.list_credentials(None)?;
+                DeepLinkRequest {
+                    id: pending.id.clone(),
+                    kind: DeepLinkKind::Totp,
+                    issuer: issuer.clone(),
+                    account: account.clone(),
+                    credential_uuid: None,
+                    candidates: deeplink::totp_candidates(
+                        issuer.as_deref(),
+                        account.as_deref(),
+                        &credentials,
+                    )
+                    .into_iter()
+                    .map(|credential| CredentialSummary::from(credential.clone()))
+                    .collect(),
+                    received_at: pending.received_at,
+                }
+            }
+            DeepLink::OpenCredential { uuid } => {
+                let credential = self.credential_repo.get_credential(uuid)?;
+                DeepLinkRequest {
+                    id: pending.id.clone(),
+                    kind: DeepLinkKind::OpenCredential,
+                    issuer: None,
+                    account: None,
+                    credential_uuid: Some(credential.uuid.clone()),
+                    candidates: vec![CredentialSummary::from(credential)],
+                    received_at: pending.received_at,
+                }
+            }
+        };
+        Ok(request)
+    }
+
+    /// Acts on a link the user confirmed. A TOTP setup is added to `target_uuid`,
+    /// or to a new item named after its issuer when there is no target. Returns
+    /// the item to show.
+    pub fn accept_deep_link(
+        &self,
+        link: &DeepLink,
+        target_uuid: Option<&str>,
+    ) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        match (link, target_uuid) {
+            (DeepLink::Totp { uri, .. }, Some(uuid)) => self.attach_totp(uuid, uri),
+            (
+                DeepLink::Totp {
+                    uri,
+                    issuer,
+                    account,
+                },
+                None,
+            ) => {
+                let site = issuer.as_deref().or(account.as_deref()).unwrap_or("TOTP");
+                self.add_credential(
+                    site,
+                    account.as_deref().unwrap_or_default(),
+                    Secret {
+                        totp: Some(uri.clone()),
+                        ..Secret::default()
+                    },
+                    None,
+                )
+            }
+            (DeepLink::OpenCredential { uuid }, _) => self.credential_repo.get_credential(uuid),
+        }
+    }
+
+    /// Sets the TOTP setup of an item, replacing any it had
+    pub fn attach_totp(&self, uuid: &str, uri: &str) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        TotpConfig::parse(uri)?;
+        let credential = self.credential_repo.get_credential(uuid)?;
+        let mut secret = self.open_secret(&credential)?;
+        secret.totp = Some(uri.to_string());
+        validation::validate_credential(
+            &credential.site,
+            &credential.username,
+            &secret,
+            &credential.tags,
+        )?;
+        let updated = self.updated_credential(
+            uuid,
+            &credential.site,
+            &credential.username,
+            secret,
+            credential.tags.clone(),
+            credential.expires_at,
+        )?;
+        self.with_transaction(|txn| {
+            txn.update_credential(&updated)?;
+            self.audit_txn(
+                txn,
+                Message::new("audit.totp_attached").with_param("site", &credential.site),
+                Some(uuid),
+            )?;
+            txn.get_credential(uuid)
+        })
+    }
+
+    /// Gets a credential by UUID (metadata only, no decrypted secret)
+    pub fn get_credential(&self, uuid: &str) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        self.credential_repo.get_credential(uuid)
+    }
+
+    /// Lists credentials matching the filter criteria
+    pub fn list_credentials(&self, filter: Option<CredentialFilter>) -> AppResult<Vec<Credential>> {
+        self.ensure_unlocked()?;
+        let search_term = filter
+            .as_ref()
+            .and_then(|filter| filter.search_term.clone());
+        let credentials = self.credential_repo.list_credentials(filter)?;
+        self.record_listing(search_term.as_deref(), credentials.len())?;
+        Ok(credentials)
+    }
+
+    /// Audits a search (with `search_term`) or listing that returned `count` items;
+    /// recorded only at the verbose audit level. For callers that query
+    /// `async_credentials` directly.
+    pub fn record_listing(&self, search_term: Option<&str>, count: usize) -> AppResult<()> {
+        let message = match search_term.filter(|term| !term.is_empty()) {
+            Some(term) => Message::new("audit.credentials_searched").with_param("term", term),
+            None => Message::new("audit.credentials_listed"),
+        };
+        self.audit(message.with_param("count", count), None)
+    }
+
+    /// Decrypts the secret data from a credential.
+    /// Fails with `AppError::ReauthRequired` for `high_security` items.
+    pub fn decrypt_secret(&self, credential: &Credential) -> AppResult<Secret> {
+        self.ensure_unlocked()?;
+        if credential.high_security {
+            return Err(AppError::ReauthRequired);
+        }
+        self.open_secret(credential)
+    }
+
+    /// Decrypts any item, including `high_security` ones, after checking the master password
+    pub fn decrypt_secret_with_password(
+        &self,
+        credential: &Credential,
+        master_password: &str,
+    ) -> AppResult<Secret> {
+        self.reauthenticate(master_password)?;
+        let secret = self.open_secret(credential)?;
+        if credential.high_security {
+            self.audit(
+                Message::new("audit.high_security_revealed_all")
+                    .with_param("site", &credential.site),
+                Some(&credential.uuid),
+            )?;
+        }
+        Ok(secret)
+    }
+
+    /// Decrypts a secret to show to the user, keeping only `fields` (all of them when
+    /// None), and records what was revealed. High-security items need `master_password`.
+    pub fn reveal_secret(
+        &self,
+        credential: &Credential,
+        fields: Option<&[SecretField]>,
+        master_password: Option<&str>,
+    ) -> AppResult<Secret> {
+        if fields.is_some_and(|fields| fields.is_empty()) {
+            return Err(AppError::InvalidInput(
+                "No secret fields were requested".to_string(),
+            ));
+        }
+        let secret = match master_password {
+            Some(master_password) => {
+                self.reauthenticate(master_password)?;
+                self.open_secret(credential)?
+            }
+            None => self.decrypt_secret(credential)?,
+        };
+        let (secret, message) = match fields {
+            Some(fields) => {
+                let labels: Vec<String> = fields.iter().map(SecretField::label).collect();
+                let key = if credential.high_security {
+                    "audit.high_security_revealed"
+                } else {
+                    "audit.secret_revealed"
+                };
+                (
+                    secret.redacted(fields),
+                    Message::new(key).with_param("fields", labels.join(", ")),
+                )
+            }
+            None => {
+                let key = if credential.high_security {
+                    "audit.high_security_revealed_all"
+                } else {
+                    "audit.secret_revealed_all"
+                };
+                (secret, Message::new(key))
+            }
+        };
+
+        self.credential_repo
+            .mark_used(&credential.uuid, Utc::now())?;
+        self.audit(
+            message.with_param("site", &credential.site),
+            Some(&credential.uuid),
+        )?;
+        Ok(secret)
+    }
+
+    /// Decrypts a password for automated checks such as breach lookups, which never
+    /// show it to the user. Bypasses `high_security`.
+    pub(crate) fn decrypt_secret_for_scan(&self, credential: &Credential) -> AppResult<Secret> {
+        self.ensure_unlocked()?;
+        self.open_secret(credential)
+    }
+
+    /// Checks a code from the user's authenticator against the item's TOTP secret,
+    /// allowing `TOTP_DRIFT_STEPS` of clock drift. Nothing secret is returned, so
+    /// this works for high-security items without the master password.
+    pub fn validate_totp_input(&self, uuid: &str, code: &str) -> AppResult<TotpValidation> {
+        self.ensure_unlocked()?;
+        let credential = self.credential_repo.get_credential(uuid)?;
+        let secret = self.open_secret(&credential)?;
+        let totp = secret
+            .totp
+            .as_deref()
+            .ok_or_else(|| AppError::InvalidInput("This item has no TOTP secret".to_string()))?;
+        let validation = TotpConfig::parse(totp)?.validate(code, Utc::now().timestamp());
+        debug!(
+            valid = validation.valid,
+            drift_steps = validation.drift_steps,
+            "TOTP code checked"
+        );
+        Ok(validation)
+    }
+
+    /// Sets the keys auto-type sends for an item; None restores the default
+    pub fn set_autotype_sequence(
+        &self,
+        uuid: &str,
+        sequence: Option<String>,
+    ) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        if let Some(sequence) = &sequence {
+            autotype::parse_sequence(sequence)?;
+        }
+        let credential = self.credential_repo.get_credential(uuid)?;
+        let mut secret = self.open_secret(&credential)?;
+        secret.autotype_sequence = sequence;
+        self.update_credential(
+            uuid,
+            &credential.site,
+            &credential.username,
+            secret,
+            credential.tags,
+            credential.expires_at,
+        )?;
+        self.credential_repo.get_credential(uuid)
+    }
+
+    /// Starts auto-type for the window titled `window_title`, replacing any
+    /// request still waiting. Nothing is typed until `autotype_keystrokes`.
+    pub fn prepare_autotype(&self, window_title: &str) -> AppResult<AutotypeRequest> {
+        self.ensure_unlocked()?;
+        let credentials = self.credential_repo.list_credentials(None)?;
+        let request = AutotypeRequest {
+            id: Uuid::new_v4().to_string(),
+            window_title: window_title.to_string(),
+            candidates: autotype::matching_credentials(window_title, &credentials)
+                .into_iter()
+                .map(|credential| AutotypeCandidate {
+                    uuid: credential.uuid.clone(),
+                    site: credential.site.clone(),
+                    username: credential.username.clone(),
+                })
+                .collect(),
+        };
+        *self.autotype.lock().unwrap() = Some(request.clone());
+        Ok(request)
+    }
+
+    /// The auto-type request waiting for confirmation, if any
+    pub fn pending_autotype(&self) -> Option<AutotypeRequest> {
+        self.autotype.lock().unwrap().clone()
+    }
+
+    /// Confirms request `request_id` with the item the user chose, returning the
+    /// keys to type. A request can be confirmed once; the typing is audited.
+    /// Fails with `AppError::ReauthRequired` for `high_security` items.
+    pub fn autotype_keystrokes(
+        &self,
+        request_id: &str,
+        uuid: &str,
+        now: DateTime<Utc>,
+    ) -> AppResult<Vec<Keystroke>> {
+        self.ensure_unlocked()?;
+        let request = {
+            let mut pending = self.autotype.lock().unwrap();
+            match pending.as_ref() {
+                Some(request) if request.id == request_id => pending.take().unwrap(),
+                _ => return Err(AppError::NotFound(request_id.to_string())),
+            }
+        };
+        let credential = self.credential_repo.get_credential(uuid)?;
+        // High-security items are not typed without the master password
+        let secret = self.decrypt_secret(&credential)?;
+        let keys = autotype::keystrokes(&credential, &secret, now.timestamp())?;
+        self.credential_repo.mark_used(uuid, now)?;
+
+        self.audit(
+            Message::new("audit.autotyped")
+                .with_param("site", &credential.site)
+                .with_param("window", &request.window_title),
+            Some(uuid),
+        )?;
+        Ok(keys)
+    }
+
+    /// Drops the auto-type request waiting for confirmation
+    pub fn cancel_autotype(&self) {
+        *self.autotype.lock().unwrap() = None;
+    }
+
+    /// Marks an item as requiring the master password to decrypt, or clears the mark
+    pub fn set_high_security(
+        &self,
+        uuid: &str,
+        enabled: bool,
+        master_password: &str,
+    ) -> AppResult<Credential> {
+        self.reauthenticate(master_password)?;
+        let mut credential = self.credential_repo.get_credential(uuid)?;
+        if credential.high_security == enabled {
+            return Ok(credential);
+        }
+        credential.high_security = enabled;
+        self.credential_repo.update_credential(&credential)?;
+
+        let key = if enabled {
+            "audit.high_security_enabled"
+        } else {
+            "audit.high_security_disabled"
+        };
+        self.audit(
+            Message::new(key).with_param("site", &credential.site),
+            Some(uuid),
+        )?;
+        self.credential_repo.get_credential(uuid)
+    }
+
+    /// Stores the site's password rules on an item, or clears them with None
+    pub fn set_generator_constraints(
+        &self,
+        uuid: &str,
+        constraints: Option<GeneratorConstraints>,
+    ) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        if let Some(constraints) = &constraints {
+            constraints.check()?;
+        }
+        let mut credential = self.credential_repo.get_credential(uuid)?;
+        credential.generator_constraints = constraints;
+        self.credential_repo.update_credential(&credential)?;
+        self.credential_repo.get_credential(uuid)
+    }
+
+    /// Generates a new password for an item from `options`, narrowed by the
+    /// item's `generator_constraints` so the site will accept it
+    pub fn generate_password_for(
+        &self,
+        uuid: &str,
+        options: &GeneratorOptions,
+    ) -> AppResult<String> {
+        self.ensure_unlocked()?;
+        let credential = self.credential_repo.get_credential(uuid)?;
+        generator::generate_constrained(
+            options,
+            &credential.generator_constraints.unwrap_or_default(),
+        )
+    }
+
+    /// Decrypts a secret without the `high_security` check, for internal use
+    fn open_secret(&self, credential: &Credential) -> AppResult<Secret> {
+        let aad = format!("{}:{}", credential.site, credential.username);
+        let plaintext = match credential.collection_id.as_deref() {
+            Some(collection_id) => {
+                let wrapped = self.own_wrapped_key(collection_id)?;
+                let crypto = self.crypto.lock().unwrap();
+                crypto.decrypt_for_collection(
+                    &wrapped,
+                    collection_id,
+                    &credential.secret_enc,
+                    aad.as_bytes(),
+                )?
+            }
+            None => {
+                let crypto = self.crypto.lock().unwrap();
+                crypto.decrypt(&credential.secret_enc, aad.as_bytes())?
+            }
+        };
+
+        let secret: Secret = serde_json::from_slice(&plaintext).map_err(AppError::Serialization)?;
+        Ok(secret)
+    }
+
+    /// Encrypts a secret with the vault key, or with the collection key for shared items
+    fn encrypt_secret(
+        &self,
+        secret: &Secret,
+        site: &str,
+        username: &str,
+        collection_id: Option<&str>,
+    ) -> AppResult<String> {
+        let secret_json = serde_json::to_string(secret).map_err(AppError::Serialization)?;
+        let aad = format!("{}:{}", site, username);
+        match collection_id {
+            Some(collection_id) => {
+                let wrapped = self.own_wrapped_key(collection_id)?;
+                let crypto = self.crypto.lock().unwrap();
+                crypto.encrypt_for_collection(
+                    &wrapped,
+                    collection_id,
+                    secret_json.as_bytes(),
+                    aad.as_bytes(),
+                )
+            }
+            None => {
+                let crypto = self.crypto.lock().unwrap();
+                crypto.encrypt(secret_json.as_bytes(), aad.as_bytes())
+            }
+        }
+    }
+
+    /// Updates the breach state for a credential
+    pub fn update_breach_state(&self, uuid: &str, state: BreachState) -> AppResult<()> {
+        self.ensure_unlocked()?;
+        // Update using the CredentialRepository trait
+        // The repository handles the audit log internally
+        self.credential_repo.update_breach_state(uuid, state)
+    }
+
+    /// Imports a personal list of compromised passwords under the name `source`;
+    /// importing into an existing source adds to it
+    pub fn import_breach_list(
+        &self,
+        path: &Path,
+        source: &str,
+        now: DateTime<Utc>,
+    ) -> AppResult<BreachListImport> {
+        self.ensure_unlocked()?;
+        let repo = self.breach_list_repo()?;
+        let source = breach_list::validate_source(source)?;
+        let hashes = breach_list::read_entries(path)?;
+        let fingerprints = self.breach_list_fingerprints(&hashes)?;
+        let added = repo.add_breach_entries(&source, &fingerprints, now)?;
+
+        self.audit(
+            Message::new("audit.breach_list_imported")
+                .with_param("source", &source)
+                .with_param("count", added),
+            None,
+        )?;
+        Ok(BreachListImport {
+            source,
+            read: hashes.len(),
+            added,
+        })
+    }
+
+    /// Names of the imported lists that contain `password`; empty when this vault
+    /// has no breach lists
+    pub fn local_breach_sources(&self, password: &str) -> AppResult<Vec<String>> {
+        self.ensure_unlocked()?;
+        let Some(repo) = self.breach_list_repo.as_ref() else {
+            return Ok(Vec::new());
+        };
+        let fingerprints = self.breach_list_fingerprints(&[breach_list::sha1_hex(password)])?;
+        Ok(repo
+            .find_breach_entries(&fingerprints)?
+            .into_iter()
+            .map(|(_, source)| source)
+            .collect())
+    }
+
+    /// Marks every item whose password is on an imported list as compromised
+    /// Reads every password, so it needs a bulk session.
+    pub fn check_local_breaches(&self) -> AppResult<Vec<LocalBreachMatch>> {
+        self.require_bulk_session()?;
+        let repo = self.breach_list_repo()?;
+        let (credentials, hashes): (Vec<Credential>, Vec<String>) = self
+            .password_entries()?
+            .into_iter()
+            .filter(|(_, secret)| !secret.password.is_empty())
+            .map(|(credential, secret)| (credential, breach_list::sha1_hex(&secret.password)))
+            .unzip();
+        let fingerprints = self.breach_list_fingerprints(&hashes)?;
+        let entries: Vec<(Credential, String)> = credentials
+            .into_iter()
+            .zip(fingerprints.iter().cloned())
+            .collect();
+        let found = repo.find_breach_entries(&fingerprints)?;
+
+        let mut matches = Vec::new();
+        for (credential, fingerprint) in entries {
+            let sources: Vec<String> = found
+                .iter()
+                .filter(|(found, _)| *found == fingerprint)
+                .map(|(_, source)| source.clone())
+                .collect();
+            if sources.is_empty() {
+                continue;
+            }
+            if credential.breach_state != BreachState::Compromised {
+                self.credential_repo
+                    .update_breach_state(&credential.uuid, BreachState::Compromised)?;
+            }
+            matches.push(LocalBreachMatch {
+                uuid: credential.uuid,
+                site: credential.site,
+                username: credential.username,
+                sources,
+            });
+        }
+        Ok(matches)
+    }
+
+    /// Imported lists, by name
+    pub fn breach_lists(&self) -> AppResult<Vec<BreachListSource>> {
+        self.ensure_unlocked()?;
+        self.breach_list_repo()?.list_breach_sources()
+    }
+
+    /// Deletes an imported list; items it flagged stay flagged
+    pub fn remove_breach_list(&self, source: &str) -> AppResult<usize> {
+        self.ensure_unlocked()?;
+        let removed = self.breach_list_repo()?.remove_breach_source(source)?;
+        self.audit(
+            Message::new("audit.breach_list_removed").with_param("source", source),
+            None,
+        )?;
+        Ok(removed)
+    }
+
+    fn breach_list_repo(&self) -> AppResult<&Arc<dyn BreachListRepository>> {
+        self.breach_list_repo.as_ref().ok_or_else(|| {
+            AppError::Other("Breach lists are not supported by this vault".to_string())
+        })
+    }
+
+    /// Fingerprints of SHA-1 hex digests, keyed where the vault key is
+    fn breach_list_fingerprints(&self, sha1_hexes: &[String]) -> AppResult<Vec<String>> {
+        let messages: Vec<Vec<u8>> = sha1_hexes
+            .iter()
+            .map(|hash| hash.as_bytes().to_vec())
+            .collect();
+        let macs = self
+            .crypto
+            .lock()
+            .unwrap()
+            .mac_with_subkey(breach_list::BREACH_LIST_KEY_CONTEXT, &messages)?;
+        Ok(macs.iter().map(breach_list::fingerprint).collect())
+    }
+
+    /// Whether stored strengths predate the current strength algorithm
+    pub fn strength_recalculation_due(&self) -> AppResult<bool> {
+        let version = self.settings_repo.get_strength_version()?.unwrap_or(0);
+        Ok(version < strength::STRENGTH_ALGORITHM_VERSION)
+    }
+
+    /// Recomputes the strength of the credentials in `uuids` with the current
+    /// calculator and adds the outcome to `progress`. Generated passwords are
+    /// rescored from their entropy without decrypting them.
+    pub fn recalculate_strengths(
+        &self,
+        uuids: &[String],
+        progress: &mut StrengthRecalcProgress,
+    ) -> AppResult<()> {
+        self.ensure_unlocked()?;
+        let wordlists = self.wordlists()?;
+        let mut updates = Vec::new();
+        for uuid in uuids {
+            progress.processed += 1;
+            let credential = match self.credential_repo.get_credential(uuid) {
+                Ok(credential) => credential,
+                // Deleted since the job started
+                Err(AppError::NotFound(_)) => continue,
+                Err(e) => return Err(e),
+            };
+            let strength = match (credential.strength_source, credential.entropy_bits) {
+                (StrengthSource::Generated, Some(bits)) => strength::strength_from_entropy(bits),
+                _ => match self.open_secret(&credential) {
+                    Ok(secret) => self
+                        .strength_calculator
+                        .calculate_strength(&secret.password, &wordlists),
+                    Err(_) => {
+                        progress.failed += 1;
+                        continue;
+                    }
+                },
+            };
+            if strength != credential.strength {
+                updates.push(StrengthUpdate {
+                    uuid: credential.uuid,
+                    revision: credential.revision,
+                    strength,
+                });
+            }
+        }
+        if !updates.is_empty() {
+            progress.changed += self.credential_repo.update_strengths(&updates)?;
+        }
+        Ok(())
+    }
+
+    /// Logs a finished recalculation. Unless some items failed, stored strengths
+    /// are then marked as current, so the job does not run again until the next upgrade.
+    pub fn finish_strength_recalculation(
+        &self,
+        progress: &StrengthRecalcProgress,
+    ) -> AppResult<()> {
+        self.ensure_unlocked()?;
+        info!(
+            total = progress.total,
+            changed = progress.changed,
+            failed = progress.failed,
+            "strength recalculation finished"
+        );
+        if progress.failed == 0 {
+            self.settings_repo
+                .save_strength_version(strength::STRENGTH_ALGORITHM_VERSION)?;
+        }
+        self.audit(
+            Message::new("audit.strengths_recalculated")
+                .with_param("changed", progress.changed)
+                .with_param("total", progress.total),
+            None,
+        )?;
+        Ok(())
+    }
+
+    /// Decrypts up to `sample_size` items picked at random and reports those
+    /// that fail. Damage is logged and audited; nothing is repaired.
+    pub fn check_integrity(&self, sample_size: usize) -> AppResult<IntegrityReport> {
+        self.ensure_unlocked()?;
+        let credentials = self.credential_repo.list_credentials(None)?;
+        let mut report = IntegrityReport {
+            total: credentials.len(),
+            ..IntegrityReport::default()
+        };
+        for credential in integrity::sample(credentials, sample_size) {
+            let kind = match self.open_secret(&credential) {
+                Ok(_) => None,
+                Err(AppError::Crypto(_)) => Some(DamageKind::Decryption),
+                Err(AppError::Serialization(_)) => Some(DamageKind::Malformed),
+                Err(e) => {
+                    debug!(uuid = %credential.uuid, error = %e, "item skipped by integrity check");
+                    report.skipped += 1;
+                    continue;
+                }
+            };
+            report.checked += 1;
+            if let Some(kind) = kind {
+                warn!(uuid = %credential.uuid, ?kind, "item failed integrity check");
+                report.damaged.push(DamagedItem {
+                    uuid: credential.uuid,
+                    site: credential.site,
+                    username: credential.username,
+                    kind,
+                });
+            }
+        }
+        info!(
+            total = report.total,
+            checked = report.checked,
+            damaged = report.damaged.len(),
+            "integrity check finished"
+        );
+        if !report.is_intact() {
+            self.audit(
+                Message::new("audit.integrity_damaged")
+                    .with_param("damaged", report.damaged.len())
+                    .with_param("checked", report.checked),
+                None,
+            )?;
+        }
+        Ok(report)
+    }
+
+    /// Gets the app settings, decrypting them first.
+    pub fn get_settings(&self) -> AppResult<AppSettings> {
+        self.ensure_unlocked()?;
+
+        // Fetch encrypted settings and nonce from the SettingsRepository
+        let encrypted_data = self.settings_repo.get_encrypted_settings()?;
+
+        if let Some((nonce, encrypted_settings)) = encrypted_data {
+            // Decrypt using CryptoService
+            let crypto = self.crypto.lock().unwrap();
+            let settings_json =
+                crypto.decrypt_with_nonce(&encrypted_settings, b"app_settings", &nonce)?;
+            drop(crypto);
+
+            // Deserialize
+            serde_json::from_slice(&settings_json).map_err(AppError::Serialization)
+        } else {
+            // If no settings saved yet, return default
+            Ok(AppSettings::default())
+        }
+    }
+
+    /// A subsystem's entry in the vault metadata, decrypted; None if never stored
+    pub fn get_meta(&self, key: &str) -> AppResult<Option<Vec<u8>>> {
+        self.ensure_unlocked()?;
+        let Some(stored) = self.settings_repo.get_meta(key)? else {
+            return Ok(None);
+        };
+        let container = String::from_utf8(stored)
+            .map_err(|_| AppError::Other(format!("Metadata entry {key} is damaged")))?;
+        self.crypto
+            .lock()
+            .unwrap()
+            .decrypt(&container, &meta_aad(key))
+            .map(Some)
+    }
+
+    /// Stores `value` encrypted under `key`, replacing what was there. The key is
+    /// bound to the ciphertext, so an entry copied to another key does not decrypt.
+    pub fn put_meta(&self, key: &str, value: &[u8]) -> AppResult<()> {
+        self.ensure_unlocked()?;
+        let container = self.crypto.lock().unwrap().encrypt(value, &meta_aad(key))?;
+        self.settings_repo.put_meta(key, container.as_bytes())
+    }
+
+    /// Every feature flag of this vault
+    pub fn list_features(&self) -> AppResult<Vec<FeatureState>> {
+        let flags = self.feature_flags()?;
+        Ok(Feature::ALL
+            .into_iter()
+            .map(|feature| flags.state(feature))
+            .collect())
+    }
+
+    /// Turns the feature called `name` on or off for this vault
+    pub fn set_feature(&self, name: &str, enabled: bool) -> AppResult<FeatureState> {
+        let feature = Feature::from_name(name)?;
+        let mut flags = self.feature_flags()?;
+        flags.set(feature, enabled)?;
+        self.put_meta(
+            FEATURES_META_KEY,
+            &serde_json::to_vec(&flags).map_err(AppError::Serialization)?,
+        )?;
+        let key = match enabled {
+            true => "audit.feature_enabled",
+            false => "audit.feature_disabled",
+        };
+        self.audit(
+            Message::new(key).with_param("feature", feature.name()),
+            None,
+        )?;
+        Ok(flags.state(feature))
+    }
+
+    pub fn is_feature_enabled(&self, feature: Feature) -> AppResult<bool> {
+        Ok(self.feature_flags()?.is_enabled(feature))
+    }
+
+    fn feature_flags(&self) -> AppResult<FeatureFlags> {
+        match self.get_meta(FEATURES_META_KEY)? {
+            Some(json) => serde_json::from_slice(&json).map_err(AppError::Serialization),
+            None => Ok(FeatureFlags::default()),
+        }
+    }
+
+    /// Saved views, ordered by name
+    pub fn list_views(&self) -> AppResult<Vec<SavedView>> {
+        match self.get_meta(VIEWS_META_KEY)? {
+            Some(json) => serde_json::from_slice(&json).map_err(AppError::Serialization),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Saves a view, replacing any view of the same name
+    pub fn save_view(&self, name: &str, config: ViewConfig) -> AppResult<SavedView> {
+        // Refuse terms the view could never be listed with
+        query::apply(config.filter.to_filter())?.term_matcher()?;
+        let view = SavedView {
+            name: views::normalize_name(name)?,
+            config,
+        };
+        let mut saved = self.list_views()?;
+        views::upsert(&mut saved, view.clone());
+        self.put_views(&saved)?;
+        Ok(view)
+    }
+
+    pub fn delete_view(&self, name: &str) -> AppResult<()> {
+        let mut saved = self.list_views()?;
+        let count = saved.len();
+        saved.retain(|view| view.name != name);
+        if saved.len() == count {
+            return Err(AppError::NotFound(format!("view {name}")));
+        }
+        self.put_views(&saved)
+    }
+
+    /// How the view `name` lists items
+    pub fn view_config(&self, name: &str) -> AppResult<ViewConfig> {
+        self.list_views()?
+            .into_iter()
+            .find(|view| view.name == name)
+            .map(|view| view.config)
+            .ok_or_else(|| AppError::NotFound(format!("view {name}")))
+    }
+
+    fn put_views(&self, saved: &[SavedView]) -> AppResult<()> {
+        self.put_meta(
+            VIEWS_META_KEY,
+            &serde_json::to_vec(saved).map_err(AppError::Serialization)?,
+        )
+    }
+
+    /// The notification history, newest first
+    pub fn list_notifications(&self) -> AppResult<Vec<Notification>> {
+        match self.get_meta(NOTIFICATIONS_META_KEY)? {
+            Some(json) => serde_json::from_slice(&json).map_err(AppError::Serialization),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn clear_notifications(&self) -> AppResult<()> {
+        self.put_notifications(&[])
+    }
+
+    pub fn mark_notifications_read(&self) -> AppResult<()> {
+        let mut history = self.list_notifications()?;
+        for notification in &mut history {
+            notification.read = true;
+        }
+        self.put_notifications(&history)
+    }
+
+    /// Notifications not read yet. While locked only those dispatched since
+    /// locking are known.
+    pub fn unread_notification_count(&self) -> AppResult<usize> {
+        let pending = self.pending_notifications.lock().unwrap().len();
+        if !self.is_unlocked {
+            return Ok(pending);
+        }
+        let unread = self
+            .list_notifications()?
+            .iter()
+            .filter(|notification| !notification.read)
+            .count();
+        Ok(pending + unread)
+    }
+
+    /// Shows `notification` through `sink` if its category is enabled and adds
+    /// it to the history. While locked the settings last seen decide, and it
+    /// joins the history at the next unlock. A sink that fails is only logged.
+    pub fn dispatch_notification(
+        &self,
+        mut notification: Notification,
+        sink: &dyn NotificationSink,
+    ) -> AppResult<Notification> {
+        let settings = *self.notification_settings.lock().unwrap();
+        if settings.enabled(notification.category) {
+            match sink.show(&notification.title.text(), &notification.body.text()) {
+                Ok(()) => notification.shown = true,
+                Err(e) => warn!(error = %e, "could not show notification"),
+            }
+        }
+        if self.is_unlocked {
+            let mut history = self.list_notifications()?;
+            notifications::record(&mut history, notification.clone());
+            self.put_notifications(&history)?;
+        } else {
+            self.pending_notifications
+                .lock()
+                .unwrap()
+                .push(notification.clone());
+        }
+        Ok(notification)
+    }
+
+    /// Gives `other`, the manager reopening this vault, what this one knows
+    /// about notifications while locked
+    pub fn hand_over_notifications(&self, other: &VaultManager) {
+        *other.notification_settings.lock().unwrap() = *self.notification_settings.lock().unwrap();
+        other
+            .pending_notifications
+            .lock()
+            .unwrap()
+            .append(&mut self.pending_notifications.lock().unwrap());
+    }
+
+    /// Refreshes the notification settings and records the notifications
+    /// dispatched while locked
+    fn load_notifications(&self) -> AppResult<()> {
+        *self.notification_settings.lock().unwrap() = self.get_settings()?.notifications;
+        let pending = std::mem::take(&mut *self.pending_notifications.lock().unwrap());
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let mut history = self.list_notifications()?;
+        for notification in pending {
+            notifications::record(&mut history, notification);
+        }
+        self.put_notifications(&history)
+    }
+
+    fn put_notifications(&self, history: &[Notification]) -> AppResult<()> {
+        self.put_meta(
+            NOTIFICATIONS_META_KEY,
+            &serde_json::to_vec(history).map_err(AppError::Serialization)?,
+        )
+    }
+
+    /// The user's banned passwords and extra dictionary words
+    pub fn get_wordlists(&self) -> AppResult<Wordlists> {
+        Ok(Wordlists::clone(&*self.wordlists()?))
+    }
+
+    // The word lists for strength checks, decrypted once per session
+    fn wordlists(&self) -> AppResult<Arc<Wordlists>> {
+        self.ensure_unlocked()?;
+        let mut cached = self.wordlists.lock().unwrap();
+        if let Some(wordlists) = cached.as_ref() {
+            return Ok(wordlists.clone());
+        }
+        let wordlists = match self.settings_repo.get_encrypted_wordlists()? {
+            Some(container) => {
+                let json = self
+                    .crypto
+                    .lock()
+                    .unwrap()
+                    .decrypt(&container, WORDLISTS_AAD)?;
+                serde_json::from_slice(&json).map_err(AppError::Serialization)?
+            }
+            None => Wordlists::default(),
+        };
+        Ok(cached.insert(Arc::new(wordlists)).clone())
+    }
+
+    /// Replaces the word lists and returns them as stored (normalized).
+    /// Strength scores are computed on save, so they only apply to passwords saved from now on.
+    pub fn set_wordlists(&self, wordlists: &Wordlists) -> AppResult<Wordlists> {
+        self.ensure_unlocked()?;
+        let wordlists = wordlists.normalized()?;
+        let container = self
+            .crypto
+            .lock()
+            .unwrap()
+            .encrypt(&serde_json::to_vec(&wordlists)?, WORDLISTS_AAD)?;
+        self.settings_repo.save_encrypted_wordlists(&container)?;
+        *self.wordlists.lock().unwrap() = Some(Arc::new(wordlists.clone()));
+
+        self.audit(
+            Message::new("audit.wordlists_changed")
+                .with_param("banned", wordlists.banned_passwords.len())
+                .with_param("words", wordlists.dictionary.len()),
+            None,
+        )?;
+        Ok(wordlists)
+    }
+
+    /// What the email breach monitor has seen and reported so far
+    pub fn email_monitor_state(&self) -> AppResult<EmailMonitorState> {
+        self.ensure_unlocked()?;
+        match self.settings_repo.get_encrypted_email_monitor()? {
+            Some(container) => {
+                let json = self
+                    .crypto
+                    .lock()
+                    .unwrap()
+                    .decrypt(&container, EMAIL_MONITOR_AAD)?;
+                serde_json::from_slice(&json).map_err(AppError::Serialization)
+            }
+            None => Ok(EmailMonitorState::default()),
+        }
+    }
+
+    fn save_email_monitor_state(&self, state: &EmailMonitorState) -> AppResult<()> {
+        let container = self
+            .crypto
+            .lock()
+            .unwrap()
+            .encrypt(&serde_json::to_vec(state)?, EMAIL_MONITOR_AAD)?;
+        self.settings_repo.save_encrypted_email_monitor(&container)
+    }
+
+    /// Whether monitored addresses are configured and a scheduled check is due
+    pub fn email_monitor_due(&self, now: DateTime<Utc>) -> AppResult<bool> {
+        let settings = self.get_settings()?.email_monitor;
+        if settings.emails.is_empty() || settings.api_key.is_none() {
+            return Ok(false);
+        }
+        Ok(self
+            .email_monitor_state()?
+            .is_due(settings.interval_hours, now))
+    }
+
+    /// Stores the result of a check of every monitored address, returning
+    /// notifications for the breaches not reported before
+    pub fn record_email_breaches(
+        &self,
+        results: Vec<(String, Vec<AccountBreach>)>,
+        now: DateTime<Utc>,
+    ) -> AppResult<Vec<BreachNotification>> {
+        self.ensure_unlocked()?;
+        let mut state = self.email_monitor_state()?;
+        let credentials = self.credential_repo.list_credentials(None)?;
+        let mut new = Vec::new();
+        for (email, breaches) in results {
+            new.extend(state.record(&email, breaches, &credentials, now));
+        }
+        state.last_checked_at = Some(now);
+        self.save_email_monitor_state(&state)?;
+
+        for notification in &new {
+            self.audit(
+                Message::new("audit.email_breach_found")
+                    .with_param("breach", &notification.breach.title)
+                    .with_param("count", notification.credential_uuids.len()),
+                None,
+            )?;
+        }
+        Ok(new)
+    }
+
+    /// Breach notifications, newest first; dismissed ones only if asked for
+    pub fn list_breach_notifications(
+        &self,
+        include_dismissed: bool,
+    ) -> AppResult<Vec<BreachNotification>> {
+        let mut notifications: Vec<_> = self
+            .email_monitor_state()?
+            .notifications
+            .into_iter()
+            .filter(|notification| include_dismissed || notification.dismissed_at.is_none())
+            .collect();
+        notifications.reverse();
+        Ok(notifications)
+    }
+
+    /// Marks a breach notification as dealt with
+    pub fn dismiss_breach_notification(&self, id: &str) -> AppResult<BreachNotification> {
+        let mut state = self.email_monitor_state()?;
+        let notification = state
+            .notifications
+            .iter_mut()
+            .find(|notification| notification.id == id)
+            .ok_or_else(|| AppError::NotFound(id.to_string()))?;
+        notification.dismissed_at.get_or_insert_with(Utc::now);
+        let notification = notification.clone();
+        self.save_email_monitor_state(&state)?;
+        Ok(notification)
+    }
+
+    /// Gets the UI preferences; available while the vault is locked.
+    pub fn get_ui_preferences(&self) -> AppResult<UiPreferences> {
+        match self.settings_repo.get_ui_preferences()? {
+            Some(json) => serde_json::from_str(&json).map_err(AppError::Serialization),
+            None => Ok(UiPreferences::default()),
+        }
+    }
+
+    /// Saves the UI preferences unencrypted; allowed while the vault is locked.
+    /// Only non-sensitive values belong here.
+    pub fn save_ui_preferences(&self, preferences: &UiPreferences) -> AppResult<()> {
+        if let Some(tag) = &preferences.locale {
+            Locale::parse(tag)
+                .ok_or_else(|| AppError::InvalidInput(format!("Unsupported locale: {}", tag)))?;
+        }
+        if preferences.auto_lock_timeout > 24 * 60 {
+            return Err(AppError::InvalidInput(
+                "Auto-lock timeout must be at most 24 hours".to_string(),
+            ));
+        }
+        self.settings_repo
+            .save_ui_preferences(&serde_json::to_string(preferences)?)
+    }
+
+    /// Saves app settings, encrypting them first.
+    pub fn save_settings(&self, settings: &AppSettings) -> AppResult<()> {
+        self.ensure_unlocked()?;
+        settings.validate()?;
+
+        let settings_json = serde_json::to_vec(settings).map_err(AppError::Serialization)?;
+
+        // Encrypt the settings using CryptoService
+        let crypto = self.crypto.lock().unwrap();
+        // Use a method that returns nonce and ciphertext separately
+        let (nonce, encrypted_settings) =
+            crypto.encrypt_return_nonce(&settings_json, b"app_settings")?;
+        drop(crypto); // Explicitly drop the crypto lock
+
+        // Save nonce and encrypted data using SettingsRepository
+        self.settings_repo
+            .save_encrypted_settings(&nonce, &encrypted_settings)?;
+
+        // Update crypto service's in-memory settings (KDF params)
+        let mut crypto = self.crypto.lock().unwrap();
+        crypto.update_kdf_settings(settings.clone());
+        drop(crypto);
+        self.audit_filter.set_level(settings.audit_level);
+        *self.notification_settings.lock().unwrap() = settings.notifications;
+        self.apply_ephemeral_session()?;
+
+        // Apply new network settings to remote storage
+        self.open_encrypted_storage()?;
+
+        // Keep the lock screen's copy of the auto-lock timeout in sync
+        let mut preferences = self.get_ui_preferences()?;
+        if preferences.auto_lock_timeout != settings.auto_lock_timeout {
+            preferences.auto_lock_timeout = settings.auto_lock_timeout;
+            self.save_ui_preferences(&preferences)?;
+        }
+
+        self.refresh_performance_mode()?;
+
+        // Log the action using AuditLogger
+        self.audit(Message::new("audit.settings_updated"), None)?;
+
+        Ok(())
+    }
+
+    /// Writes the settings without secrets to `path`, see `settings_profile`
+    pub fn export_settings_profile(&self, path: &Path) -> AppResult<()> {
+        let profile = settings_profile::build(&self.get_settings()?, Utc::now())?;
+        export::write_export_file(
+            &self.resolve_path(path),
+            &serde_json::to_vec_pretty(&profile)?,
+        )?;
+        self.audit(Message::new("audit.exported_settings_profile"), None)
+    }
+
+    /// Applies the profile at `path` over the current settings. Its KDF
+    /// parameters are only taken with `include_kdf`; the result tells whether
+    /// they differ, so the user can be asked first.
+    pub fn import_settings_profile(
+        &self,
+        path: &Path,
+        include_kdf: bool,
+    ) -> AppResult<SettingsProfileImport> {
+        let profile = settings_profile::parse(&std::fs::read(self.resolve_path(path))?)?;
+        let (settings, outcome) =
+            settings_profile::apply(&profile, &self.get_settings()?, include_kdf)?;
+        if !outcome.changed.is_empty() {
+            self.save_settings(&settings)?;
+        }
+        self.audit(
+            Message::new("audit.imported_settings_profile")
+                .with_param("count", outcome.changed.len()),
+            None,
+        )?;
+        Ok(outcome)
+    }
+
+    /// Turns the `offline` network setting on or off, keeping the rest
+    pub fn set_offline_mode(&self, offline: bool) -> AppResult<AppSettings> {
+        let mut settings = self.get_settings()?;
+        settings.network.offline = offline;
+        self.save_settings(&settings)?;
+        Ok(settings)
+    }
+
+    /// Where this vault keeps its credentials, with the server token replaced by
+    /// `logging::REDACTED`
+    pub fn get_storage_profile(&self) -> AppResult<StorageProfile> {
+        self.ensure_unlocked()?;
+        Ok(match self.stored_storage_profile()? {
+            StorageProfile::Remote { url, .. } => StorageProfile::Remote {
+                url,
+                token: logging::REDACTED.to_string(),
+            },
+            profile => profile,
+        })
+    }
+
+    fn stored_storage_profile(&self) -> AppResult<StorageProfile> {
+        match self.settings_repo.get_storage_profile()? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(StorageProfile::default()),
+        }
+    }
+
+    /// Chooses the storage backend used the next time the vault is opened.
+    /// Items are not moved, so the current backend must be empty. A redacted
+    /// token, as `get_storage_profile` returns it, keeps the stored one for the
+    /// same server.
+    pub fn set_storage_profile(&self, profile: &StorageProfile) -> AppResult<()> {
+        self.ensure_unlocked()?;
+        let mut profile = profile.clone();
+        if let StorageProfile::Remote { url, token } = &mut profile {
+            if token == logging::REDACTED {
+                match self.stored_storage_profile()? {
+                    StorageProfile::Remote {
+                        url: stored_url,
+                        token: stored_token,
+                    } if stored_url == *url => *token = stored_token,
+                    _ => {
+                        return Err(AppError::InvalidInput(
+                            "Enter the access token for the new vault server".to_string(),
+                        ))
+                    }
+                }
+            }
+        }
+        let profile = &profile;
+        if let StorageProfile::Remote { url, token } = profile {
+            // Validates the URL and token
+            RemoteRepository::new(url, token, self.audit_logger.clone())?
+                .with_audit_filter(self.audit_filter.clone());
+        }
+        if !self.credential_repo.list_credentials(None)?.is_empty() {
+            return Err(AppError::InvalidInput(
+                "Export and delete the existing items before switching storage".to_string(),
+            ));
+        }
+        self.settings_repo
+            .save_storage_profile(&serde_json::to_string(profile)?)?;
+
+        self.audit(
+            Message::new("audit.storage_profile_changed").with_param("backend", profile.backend()),
+            None,
+        )?;
+        Ok(())
+    }
+
+    /// Re-authenticates the user with the master password while unlocked
+    pub fn reauthenticate(&self, master_password: &str) -> AppResult<()> {
+        self.ensure_unlocked()?;
+        let crypto = self.crypto.lock().unwrap();
+        crypto.verify_master_password(master_password)
+    }
+
+    /// Opens a bulk session for `purpose`, allowing operations that decrypt every
+    /// secret for the next `BULK_SESSION_MINUTES`. Replaces any open session.
+    pub fn begin_bulk_session(
+        &self,
+        purpose: &str,
+        master_password: &str,
+    ) -> AppResult<BulkSession> {
+        self.reauthenticate(master_password)?;
+        let session = BulkSession::start(purpose, Utc::now())?;
+        *self.bulk_session.lock().unwrap() = Some(session.clone());
+
+        self.audit(
+            Message::new("audit.bulk_session_started").with_param("purpose", &session.purpose),
+            None,
+        )?;
+        Ok(session)
+    }
+
+    /// Ends the bulk session before it expires
+    pub fn end_bulk_session(&self) -> AppResult<()> {
+        if let Some(session) = self.bulk_session.lock().unwrap().take() {
+            self.audit(
+                Message::new("audit.bulk_session_ended").with_param("purpose", &session.purpose),
+                None,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The open bulk session, if it has not expired
+    pub fn bulk_session(&self) -> Option<BulkSession> {
+        let mut session = self.bulk_session.lock().unwrap();
+        if session
+            .as_ref()
+            .is_some_and(|session| !session.is_active(Utc::now()))
+        {
+            *session = None;
+        }
+        session.clone()
+    }
+
+    /// Fails with `AppError::BulkSessionRequired` unless a bulk session is open
+    pub fn require_bulk_session(&self) -> AppResult<()> {
+        self.ensure_unlocked()?;
+        match self.bulk_session() {
+            Some(_) => Ok(()),
+            None => Err(AppError::BulkSessionRequired),
+        }
+    }
+
+    /// Writes an unencrypted export of the credentials in `scope` to `path`.
+    /// Requires the master password again and leaves an audit entry.
+    pub fn export_plaintext(
+        &self,
+        master_password: &str,
+        path: &Path,
+        format: ExportFormat,
+        scope: &ExportScope,
+    ) -> AppResult<ExportReport> {
+        self.reauthenticate(master_password)?;
+        let path = &self.resolve_path(path);
+
+        let (credentials, excluded) = scope.apply(self.credential_repo.list_credentials(None)?);
+        let mut entries = Vec::new();
+        for credential in credentials {
+            let secret = self.open_secret(&credential)?;
+            entries.push((credential, secret));
+        }
+
+        let contents = export::render(format, &entries)?;
+        export::write_export_file(path, &contents)?;
+
+        self.audit(
+            Message::new("audit.exported_plaintext")
+                .with_param("count", entries.len())
+                .with_param("format", format.label())
+                .with_param("scope", scope.describe()),
+            None,
+        )?;
+
+        Ok(ExportReport {
+            path: path.display().to_string(),
+            format,
+            exported: entries.len(),
+            excluded,
+            warnings: export::plaintext_warnings(format, &entries),
+        })
+    }
+
+    /// Writes the credentials in `scope` to a KeePass 4.x database protected by
+    /// `password`. Asks for the master password again, like the plaintext export.
+    pub fn export_kdbx(
+        &self,
+        master_password: &str,
+        path: &Path,
+        password: &str,
+        scope: &ExportScope,
+    ) -> AppResult<ExportReport> {
+        self.reauthenticate(master_password)?;
+        kdbx::validate_password(password)?;
+        let path = &self.resolve_path(path);
+
+        let (credentials, excluded) = scope.apply(self.credential_repo.list_credentials(None)?);
+        let mut entries = Vec::new();
+        for credential in credentials {
+            let secret = self.open_secret(&credential)?;
+            entries.push((credential, secret));
+        }
+
+        let contents = kdbx::write_database(&entries, password, kdbx::KdfParams::default())?;
+        export::write_export_file(path, &contents)?;
+
+        self.audit(
+            Message::new("audit.exported_kdbx")
+                .with_param("count", entries.len())
+                .with_param("scope", scope.describe()),
+            None,
+        )?;
+
+        Ok(ExportReport {
+            path: path.display().to_string(),
+            format: ExportFormat::Kdbx,
+            exported: entries.len(),
+            excluded,
+            warnings: Vec::new(),
+        })
+    }
+
+    /// Writes the credentials in `scope` to an encrypted bundle at `path`, one item
+    /// at a time. A partly written bundle is removed if the export fails.
+    /// Fails with `AppError::ReauthRequired` if `scope` holds `high_security` items.
+    pub fn export_bundle(
+        &self,
+        path: &Path,
+        password: &str,
+        scope: &ExportScope,
+    ) -> AppResult<ExportReport> {
+        self.ensure_unlocked()?;
+        bundle::validate_password(password)?;
+        let path = &self.resolve_path(path);
+
+        let (credentials, excluded) = scope.apply(self.credential_repo.list_credentials(None)?);
+        let write = || -> AppResult<usize> {
+            let mut writer = BundleWriter::create(path, password, BundleKdf::default())?;
+            for credential in &credentials {
+                writer.add_item(credential, &self.decrypt_secret(credential)?)?;
+            }
+            writer.finish()
+        };
+        let exported = write().inspect_err(|_| {
+            let _ = std::fs::remove_file(path);
+        })?;
+
+        self.audit(
+            Message::new("audit.exported_bundle")
+                .with_param("count", exported)
+                .with_param("scope", scope.describe()),
+            None,
+        )?;
+
+        Ok(ExportReport {
+            path: path.display().to_string(),
+            format: ExportFormat::Bundle,
+            exported,
+            excluded,
+            warnings: Vec::new(),
+        })
+    }
+
+    /// Parses an import file and proposes a column mapping without writing anything
+    pub fn preview_import(&self, path: &Path, format: ImportFormat) -> AppResult<ImportPreview> {
+        self.ensure_unlocked()?;
+        let table = importer::read_table(path, format)?;
+        Ok(importer::preview(&table))
+    }
+
+    /// Imports a file using a user-adjusted column mapping. With
+    /// `apply_suggested_tags`, rows without tags get the tag `categorize`
+    /// suggests for their site, as in the preview.
+    pub fn import_with_mapping(
+        &self,
+        path: &Path,
+        format: ImportFormat,
+        mapping: &[ColumnMapping],
+        apply_suggested_tags: bool,
+    ) -> AppResult<ImportReport> {
+        self.ensure_unlocked()?;
+        let table = importer::read_table(path, format)?;
+
+        // Rows matching an existing item are staged when the vault supports it
+        let existing = match self.import_conflict_repo {
+            Some(_) => self.credential_repo.list_credentials(None)?,
+            None => Vec::new(),
+        };
+
+        // Encrypt everything first so the journal knows every row the import adds
+        let mut report = ImportReport::default();
+        let mut credentials = Vec::new();
+        let mut conflicts = Vec::new();
+        for item in importer::apply_mapping(&table, mapping)? {
+            match item {
+                Ok(mut item) => {
+                    if apply_suggested_tags {
+                        if let Some(tag) = categorize::suggest_tag(&item.site, &item.tags) {
+                            item.tags.push(tag.to_string());
+                        }
+                    }
+                    report.warnings.extend(item.warnings.iter().cloned());
+                    if let Some(existing) = importer::find_conflict(&existing, &item) {
+                        conflicts.push((existing.uuid.clone(), item));
+                        continue;
+                    }
+                    credentials.push(self.new_credential(
+                        &item.site,
+                        &item.username,
+                        &item.secret,
+                        Some(item.tags),
+                    )?);
+                }
+                Err(message) => {
+                    report.skipped += 1;
+                    report.errors.push(message);
+                }
+            }
+        }
+
+        let entry = self.begin_operation(PendingOperation::Import {
+            credential_uuids: credentials.iter().map(|c| c.uuid.clone()).collect(),
+        })?;
+        for credential in &credentials {
+            self.credential_repo
+                .add_credential(credential, credential.strength)?;
+            report.imported += 1;
+        }
+        self.complete_operation(entry)?;
+
+        if !conflicts.is_empty() {
+            let repo = self.import_conflict_repo()?;
+            let now = Utc::now();
+            for (existing_uuid, item) in &conflicts {
+                let item_enc = self
+                    .crypto
+                    .lock()
+                    .unwrap()
+                    .encrypt(&serde_json::to_vec(item)?, IMPORT_CONFLICT_AAD)?;
+                repo.add_import_conflict(existing_uuid, &item_enc, now)?;
+                report.conflicts += 1;
+            }
+        }
+
+        self.audit(
+            Message::new("audit.imported")
+                .with_param("count", report.imported)
+                .with_param("skipped", report.skipped),
+            None,
+        )?;
+        Ok(report)
+    }
+
+    /// Imported rows waiting for a `resolve_import_conflict` decision, oldest first
+    pub fn list_import_conflicts(&self) -> AppResult<Vec<ImportConflict>> {
+        self.ensure_unlocked()?;
+        let mut conflicts = Vec::new();
+        for stored in self.import_conflict_repo()?.list_import_conflicts()? {
+            let item = self.open_import_conflict(&stored)?;
+            let existing = if self
+                .credential_repo
+                .credential_exists(&stored.existing_uuid)?
+            {
+                Some(self.credential_repo.get_credential(&stored.existing_uuid)?)
+            } else {
+                None
+            };
+            // Only field names are shown, so high-security items are compared too
+            let differences = match &existing {
+                Some(existing) => {
+                    importer::differences(&self.open_secret(existing)?, &existing.tags, &item)
+                }
+                None => Vec::new(),
+            };
+            conflicts.push(ImportConflict {
+                id: stored.id,
+                existing: existing.map(CredentialSummary::from),
+                site: item.site,
+                username: item.username,
+                tags: item.tags,
+                differences,
+                created_at: stored.created_at,
+            });
+        }
+        Ok(conflicts)
+    }
+
+    /// Settles a staged import row. Returns the item written, None for `KeepExisting`.
+    pub fn resolve_import_conflict(
+        &self,
+        id: i64,
+        resolution: ConflictResolution,
+    ) -> AppResult<Option<Credential>> {
+        self.ensure_unlocked()?;
+        let repo = self.import_conflict_repo()?;
+        let stored = repo.get_import_conflict(id)?;
+        let item = self.open_import_conflict(&stored)?;
+
+        let written = match resolution {
+            ConflictResolution::KeepExisting => None,
+            ConflictResolution::KeepBoth => {
+                let credential =
+                    self.new_credential(&item.site, &item.username, &item.secret, Some(item.tags))?;
+                self.credential_repo
+                    .add_credential(&credential, credential.strength)?;
+                Some(credential)
+            }
+            ConflictResolution::Overwrite | ConflictResolution::MergeFields => {
+                let existing = self.credential_repo.get_credential(&stored.existing_uuid)?;
+                let mut tags = existing.tags.clone();
+                importer::merge_tags(&mut tags, &item.tags);
+                let secret = if resolution == ConflictResolution::Overwrite {
+                    item.secret
+                } else {
+                    let mut secret = self.decrypt_secret(&existing)?;
+                    importer::merge_fields(&mut secret, &item.secret);
+                    secret
+                };
+                // Imported rows are not held to the password policy, as with `import_with_mapping`
+                self.update_credential_checked(
+                    &existing.uuid,
+                    &existing.site,
+                    &existing.username,
+                    secret,
+                    tags,
+                    existing.expires_at,
+                    PolicyCheck::SaveAnyway,
+                )?;
+                Some(self.credential_repo.get_credential(&existing.uuid)?)
+            }
+        };
+        repo.remove_import_conflict(id)?;
+
+        self.audit(
+            Message::new("audit.import_conflict_resolved")
+                .with_param("site", &item.site)
+                .with_param("resolution", resolution.name()),
+            written.as_ref().map(|credential| credential.uuid.as_str()),
+        )?;
+        Ok(written)
+    }
+
+    fn open_import_conflict(&self, stored: &StoredImportConflict) -> AppResult<MappedItem> {
+        let json = self
+            .crypto
+            .lock()
+            .unwrap()
+            .decrypt(&stored.item_enc, IMPORT_CONFLICT_AAD)?;
+        serde_json::from_slice(&json).map_err(AppError::Serialization)
+    }
+
+    fn import_conflict_repo(&self) -> AppResult<&Arc<dyn ImportConflictRepository>> {
+        self.import_conflict_repo.as_ref().ok_or_else(|| {
+            AppError::Other("Staged imports are not supported by this vault".to_string())
+        })
+    }
+
+    /// Saves secrets found in pasted text as API key items, all or none
+    pub fn save_detected_secrets(
+        &self,
+        secrets: &[DetectedSecret],
+        tags: Vec<String>,
+    ) -> AppResult<Vec<Credential>> {
+        self.ensure_unlocked()?;
+        let mut credentials = Vec::with_capacity(secrets.len());
+        for detected in secrets {
+            let secret = detected.to_secret();
+            validation::validate_credential(&detected.site, &detected.username, &secret, &tags)?;
+            let mut credential = self.new_credential(
+                &detected.site,
+                &detected.username,
+                &secret,
+                Some(tags.clone()),
+            )?;
+            credential.kind = CredentialKind::ApiKey;
+            credential.api_key = Some(ApiKeyDetails {
+                provider: detected
+                    .provider
+                    .as_deref()
+                    .map_or_else(|| "other".to_string(), api_keys::provider_id),
+                secret_preview: api_keys::secret_preview(&secret.password),
+                ..ApiKeyDetails::default()
+            });
+            credentials.push(credential);
+        }
+
+        // Journaled like an import, so a crash cannot leave half of them behind
+        let entry = self.begin_operation(PendingOperation::Import {
+            credential_uuids: credentials.iter().map(|c| c.uuid.clone()).collect(),
+        })?;
+        for credential in &credentials {
+            self.credential_repo
+                .add_credential(credential, credential.strength)?;
+        }
+        self.complete_operation(entry)?;
+
+        self.audit(
+            Message::new("audit.developer_secrets_saved").with_param("count", credentials.len()),
+            None,
+        )?;
+        Ok(credentials)
+    }
+
+    /// Writes an encrypted backup of the vault into `dir` and applies retention.
+    /// Holds the caller for as long as the copy takes; background tasks use
+    /// `backup_job` and `record_backup` instead.
+    pub fn backup_now(&self, dir: &Path, retention: usize) -> AppResult<BackupOutcome> {
+        let outcome = self.backup_job(dir, retention)?.run()?;
+        self.record_backup(&outcome)?;
+        Ok(outcome)
+    }
+
+    /// A backup into `dir` to be run without holding the vault
+    pub fn backup_job(&self, dir: &Path, retention: usize) -> AppResult<BackupJob> {
+        self.ensure_unlocked()?;
+        let repo = self.maintenance_repo.as_ref().ok_or_else(|| {
+            AppError::Other("Backups are not supported by this vault".to_string())
+        })?;
+        Ok(BackupJob {
+            dir: self.resolve_path(dir),
+            retention,
+            repo: repo.clone(),
+            crypto: self.crypto.clone(),
+        })
+    }
+
+    /// Decrypts the backup at `backup` with the master password it was made
+    /// under into a new database file at `target`, which is opened like any
+    /// other vault. The open vault is left as it is, so `target` must not exist
+    /// yet. Returns the path written.
+    pub fn restore_backup(
+        &self,
+        backup: &Path,
+        master_password: &str,
+        target: &Path,
+    ) -> AppResult<PathBuf> {
+        let target = self.resolve_path(target);
+        if target.exists() {
+            return Err(AppError::InvalidInput(format!(
+                "{} already exists",
+                target.display()
+            )));
+        }
+        backup::restore(&self.resolve_path(backup), master_password, &target)?;
+        self.audit(
+            Message::new("audit.backup_restored").with_param("path", target.display().to_string()),
+            None,
+        )?;
+        Ok(target)
+    }
+
+    /// Audits a backup written by a `BackupJob`
+    pub fn record_backup(&self, outcome: &BackupOutcome) -> AppResult<()> {
+        self.audit(
+            Message::new("audit.backed_up").with_param("path", &outcome.path),
+            None,
+        )
+    }
+
+    /// Rebuilds the database so deleted rows no longer linger in free pages.
+    /// With `wipe`, the old file is also overwritten before being replaced.
+    pub fn compact_vault(
+        &self,
+        wipe: bool,
+        progress: &dyn ProgressSink,
+    ) -> AppResult<CompactReport> {
+        self.ensure_unlocked()?;
+        let repo = self.maintenance_repo.as_ref().ok_or_else(|| {
+            AppError::Other("Compaction is not supported by this vault".to_string())
+        })?;
+
+        let report = repo.compact(wipe, progress)?;
+        info!(
+            before = report.size_before,
+            after = report.size_after,
+            wiped = report.wiped,
+            "vault compacted"
+        );
+
+        self.audit(
+            Message::new("audit.vault_compacted")
+                .with_param("before", report.size_before)
+                .with_param("after", report.size_after),
+            None,
+        )?;
+        Ok(report)
+    }
+
+    /// Looks for a nonce used twice with the same key among every encrypted value
+    /// in the vault, which would mean the random number generator has failed
+    pub fn verify_no_nonce_reuse(&self) -> AppResult<NonceAuditReport> {
+        self.ensure_unlocked()?;
+        let mut uses = Vec::new();
+        let mut report = NonceAuditReport::default();
+        let mut scan = |key_epoch: String, container: &str, location: String| {
+            match CryptoService::container_nonce(container) {
+                Ok(nonce) => uses.push(NonceUse {
+                    key_epoch,
+                    nonce,
+                    location,
+                }),
+                Err(_) => report.unreadable.push(location),
+            }
+        };
+
+        for credential in self.credential_repo.list_credentials(None)? {
+            let key_epoch = match credential.collection_id.as_deref() {
+                Some(collection_id) => nonce_audit::collection_epoch(collection_id),
+                None => nonce_audit::VAULT_EPOCH.to_string(),
+            };
+            scan(
+                key_epoch,
+                &credential.secret_enc,
+                format!("item {}", credential.uuid),
+            );
+        }
+        if let Some(repo) = &self.import_conflict_repo {
+            for conflict in repo.list_import_conflicts()? {
+                scan(
+                    nonce_audit::VAULT_EPOCH.to_string(),
+                    &conflict.item_enc,
+                    format!("import conflict {}", conflict.id),
+                );
+            }
+        }
+        if let Some(repo) = &self.travel_repo {
+            for stashed in repo.list_stashed()? {
+                scan(
+                    nonce_audit::TRAVEL_EPOCH.to_string(),
+                    &stashed.ciphertext,
+                    format!("travel stash {}", stashed.uuid),
+                );
+            }
+        }
+        let stored_containers = [
+            ("wordlists", self.settings_repo.get_encrypted_wordlists()?),
+            (
+                "email monitor",
+                self.settings_repo.get_encrypted_email_monitor()?,
+            ),
+            (
+                "pending saves",
+                self.settings_repo.get_encrypted_pending_saves()?,
+            ),
+        ];
+        for (location, container) in stored_containers {
+            if let Some(container) = container {
+                scan(
+                    nonce_audit::VAULT_EPOCH.to_string(),
+                    &container,
+                    location.to_string(),
+                );
+            }
+        }
+        for key in ENCRYPTED_META_KEYS {
+            if let Some(stored) = self.settings_repo.get_meta(key)? {
+                scan(
+                    nonce_audit::VAULT_EPOCH.to_string(),
+                    &String::from_utf8_lossy(&stored),
+                    format!("metadata {key}"),
+                );
+            }
+        }
+        if let Some(repo) = &self.audit_archive_repo {
+            for archive in repo.list_audit_archives()? {
+                scan(
+                    nonce_audit::VAULT_EPOCH.to_string(),
+                    &archive.data_enc,
+                    format!("audit archive {}", archive.id),
+                );
+            }
+        }
+        // Settings keep their nonce beside the ciphertext rather than in a container
+        if let Some((nonce, _)) = self.settings_repo.get_encrypted_settings()? {
+            uses.push(NonceUse {
+                key_epoch: nonce_audit::VAULT_EPOCH.to_string(),
+                nonce,
+                location: "settings".to_string(),
+            });
+        }
+
+        report.containers_scanned = uses.len() + report.unreadable.len();
+        report.reused = nonce_audit::find_reuse(&uses);
+        if !report.is_ok() {
+            warn!(reused = report.reused.len(), "nonce reuse detected");
+        }
+        self.audit(
+            Message::new("audit.nonce_audit_run")
+                .with_param("scanned", report.containers_scanned)
+                .with_param("reused", report.reused.len()),
+            None,
+        )?;
+        Ok(report)
+    }
+
+    /// Writes a diagnostics bundle for bug reports to `path`. Works while locked,
+    /// but the settings can only be included once the vault is unlocked.
+    pub fn create_diagnostics_bundle(&self, path: &Path) -> AppResult<DiagnosticsReport> {
+        let (schema_version, integrity) = match &self.maintenance_repo {
+            Some(repo) => (Some(repo.schema_version()?), Some(repo.integrity_check()?)),
+            None => (None, None),
+        };
+        let (settings, nonce_audit) = if self.is_unlocked {
+            (
+                Some(diagnostics::sanitize_settings(&self.get_settings()?)),
+                Some(self.verify_no_nonce_reuse()?),
+            )
+        } else {
+            (None, None)
+        };
+        let mut logs = logging::recent_logs(diagnostics::DIAGNOSTICS_LOG_LIMIT, None);
+        logs.reverse();
+
+        let bundle = DiagnosticsBundle {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            created_at: Utc::now(),
+            schema_version,
+            integrity,
+            nonce_audit,
+            settings,
+            logs,
+        };
+        let files = diagnostics::write_bundle(path, &bundle)?;
+        let integrity_ok = bundle
+            .integrity
+            .as_ref()
+            .map(|results| results.len() == 1 && results[0] == "ok");
+        if integrity_ok == Some(false) {
+            warn!(problems = ?bundle.integrity, "database integrity check failed");
+        }
+        info!("diagnostics bundle written");
+
+        Ok(DiagnosticsReport {
+            path: path.display().to_string(),
+            files,
+            integrity_ok,
+            nonces_ok: bundle.nonce_audit.as_ref().map(NonceAuditReport::is_ok),
+        })
+    }
+
+    /// The automatic backup, if one is configured and due, for `BackupJob::run`
+    pub fn scheduled_backup_job(&self) -> AppResult<Option<BackupJob>> {
+        let settings = self.get_settings()?;
+        let Some(dir) = settings.backup_dir.as_deref().map(Path::new) else {
+            return Ok(None);
+        };
+        let dir = &self.resolve_path(dir);
+        if !backup::is_due(dir, settings.backup_interval_hours, Utc::now())? {
+            return Ok(None);
+        }
+        self.backup_job(dir, settings.backup_retention as usize)
+            .map(Some)
+    }
+
+    /// Lists risky credentials, most urgent first, each with a suggested replacement.
+    /// Finding reused passwords reads every password, so it needs a bulk session.
+    pub fn get_remediation_queue(&self, max_age_days: u32) -> AppResult<Vec<RemediationItem>> {
+        self.require_bulk_session()?;
+        remediation::build_queue(self.password_entries()?, max_age_days, Utc::now())
+    }
+
+    /// Records the current aggregate vault stats in the stats history. Counting
+    /// reused passwords reads every password, so it needs a bulk session.
+    pub fn record_security_snapshot(&self) -> AppResult<SecuritySnapshot> {
+        self.require_bulk_session()?;
+        let repo = self.stats_repo()?;
+        let snapshot = stats::take_snapshot(&self.password_entries()?, Utc::now());
+        repo.add_snapshot(&snapshot)?;
+        Ok(snapshot)
+    }
+
+    /// Records a snapshot if the vault supports it and the latest one is old
+    /// enough. Without a bulk session it stays due until one is opened.
+    pub fn run_scheduled_snapshot(&self) -> AppResult<Option<SecuritySnapshot>> {
+        let Some(repo) = self.stats_repo.as_ref() else {
+            return Ok(None);
+        };
+        if self.bulk_session().is_none() {
+            return Ok(None);
+        }
+        if !stats::is_due(repo.latest_snapshot_at()?, Utc::now()) {
+            return Ok(None);
+        }
+        self.record_security_snapshot().map(Some)
+    }
+
+    /// Runs the maintenance tasks that are due, or all of them with `force`.
+    /// A failed task is reported and left due; the others still run.
+    pub fn run_maintenance(&self, force: bool, now: DateTime<Utc>) -> AppResult<MaintenanceReport> {
+        self.ensure_unlocked()?;
+        let mut state: MaintenanceState = match self.get_meta(MAINTENANCE_META_KEY)? {
+            Some(json) => serde_json::from_slice(&json).map_err(AppError::Serialization)?,
+            None => MaintenanceState::default(),
+        };
+        let tasks = if force {
+            MaintenanceTask::ALL.to_vec()
+        } else {
+            state.due(now)
+        };
+        let mut report = MaintenanceReport::default();
+        for task in tasks {
+            // Tasks this vault has no storage for have nothing to do
+            let result = match task {
+                MaintenanceTask::WalCheckpoint => match &self.maintenance_repo {
+                    Some(repo) => repo
+                        .checkpoint()
+                        .map(|pages| report.checkpointed_pages = pages),
+                    None => Ok(()),
+                },
+                MaintenanceTask::Optimize => match &self.maintenance_repo {
+                    Some(repo) => repo.optimize(),
+                    None => Ok(()),
+                },
+                MaintenanceTask::AuditArchive => match &self.audit_archive_repo {
+                    Some(_) => self
+                        .archive_audit_log(DEFAULT_AUDIT_ARCHIVE_AGE_DAYS, now)
+                        .map(|archived| report.audit_entries_archived = archived.archived),
+                    None => Ok(()),
+                },
+                MaintenanceTask::ShareCleanup => match &self.share_repo {
+                    Some(repo) => repo
+                        .remove_expired_shares(now)
+                        .map(|removed| report.shares_removed = removed),
+                    None => Ok(()),
+                },
+            };
+            match result {
+                Ok(()) => {
+                    state.last_run.insert(task, now);
+                    report.ran.push(task);
+                }
+                Err(e) => {
+                    warn!(?task, error = %e, "maintenance task failed");
+                    report.errors.push(format!("{:?}: {}", task, e));
+                }
+            }
+        }
+        if !report.ran.is_empty() {
+            debug!(tasks = report.ran.len(), "maintenance ran");
+            self.put_meta(
+                MAINTENANCE_META_KEY,
+                &serde_json::to_vec(&state).map_err(AppError::Serialization)?,
+            )?;
+        }
+        report.last_run = state.last_run;
+        Ok(report)
+    }
+
+    /// Lists recorded snapshots within `range`, oldest first
+    pub fn get_security_trend(&self, range: TrendRange) -> AppResult<Vec<SecuritySnapshot>> {
+        self.ensure_unlocked()?;
+        self.stats_repo()?.list_snapshots(range.since(Utc::now()))
+    }
+
+    /// Item counts and password health right now
+    pub fn get_vault_statistics(&self) -> AppResult<VaultStatistics> {
+        self.ensure_unlocked()?;
+        let items = self.credential_repo.list_credentials(None)?;
+        // Only metadata is read unless a bulk session allows comparing passwords
+        let reused = match self.bulk_session() {
+            Some(_) => Some(stats::count_reused(&self.password_entries()?)),
+            None => None,
+        };
+        let mut statistics = stats::vault_statistics(&items, reused);
+        statistics.performance_mode_active =
+            self.apply_performance_mode(self.get_settings()?.performance_mode, items.len())?;
+        Ok(statistics)
+    }
+
+    /// Turns performance mode on or off for the current size of the vault
+    pub fn refresh_performance_mode(&self) -> AppResult<bool> {
+        self.ensure_unlocked()?;
+        let mode = self.get_settings()?.performance_mode;
+        let items = match mode {
+            PerformanceMode::Off => 0,
+            // Building the index counts the items and warms it for the mode
+            _ => self.search_index()?.len(),
+        };
+        self.apply_performance_mode(mode, items)
+    }
+
+    fn apply_performance_mode(&self, mode: PerformanceMode, items: usize) -> AppResult<bool> {
+        let active = mode.is_active(items);
+        if self.performance_active.swap(active, Ordering::SeqCst) != active {
+            if let Some(repo) = &self.maintenance_repo {
+                repo.set_cache_size(match active {
+                    true => performance::PERFORMANCE_CACHE_KIB,
+                    false => performance::DEFAULT_CACHE_KIB,
+                })?;
+            }
+            info!(active, items, "performance mode changed");
+        }
+        Ok(active)
+    }
+
+    /// Summaries of the items passing `filter`, taken from the search index when
+    /// performance mode is active; None otherwise, to query the repository instead
+    pub fn indexed_summaries(
+        &self,
+        filter: &CredentialFilter,
+    ) -> AppResult<Option<Vec<CredentialSummary>>> {
+        self.ensure_unlocked()?;
+        if !self.performance_active.load(Ordering::SeqCst) {
+            return Ok(None);
+        }
+        let now = Utc::now();
+        let term = filter.term_matcher()?;
+        let summaries: Vec<CredentialSummary> = self
+            .search_index()?
+            .items()
+            .iter()
+            .filter(|summary| filter.matches_with(term.as_ref(), summary, now))
+            .cloned()
+            .collect();
+        self.record_listing(filter.search_term.as_deref(), summaries.len())?;
+        Ok(Some(summaries))
+    }
+
+    /// Counts item writes, so callers can tell when `get_vault_statistics` may
+    /// have changed without recomputing it
+    pub fn item_changes(&self) -> u64 {
+        self.item_changes.load(Ordering::SeqCst)
+    }
+
+    fn stats_repo(&self) -> AppResult<&Arc<dyn StatsRepository>> {
+        self.stats_repo.as_ref().ok_or_else(|| {
+            AppError::Other("Security stats are not supported by this vault".to_string())
+        })
+    }
+
+    /// `decrypted_entries` without cards and identities: card numbers are not
+    /// passwords and identities have none, so they cannot be weak, reused or rotated
+    fn password_entries(&self) -> AppResult<Vec<(Credential, Secret)>> {
+        Ok(self
+            .decrypted_entries()?
+            .into_iter()
+            .filter(|(credential, _)| stats::has_password(credential.kind))
+            .collect())
+    }
+
+    /// Every credential paired with its decrypted secret
+    fn decrypted_entries(&self) -> AppResult<Vec<(Credential, Secret)>> {
+        self.credential_repo
+            .list_credentials(None)?
+            .into_iter()
+            .map(|credential| {
+                let secret = self.open_secret(&credential)?;
+                Ok((credential, secret))
+            })
+            .collect()
+    }
+
+    /// Replaces a credential's password, keeping everything else.
+    /// The old password goes to the history and the breach state is reset.
+    pub fn apply_remediation(&self, uuid: &str, new_password: &str) -> AppResult<Credential> {
+        self.ensure_unlocked()?;
+        self.replace_password(uuid, new_password, "audit.remediated")
+    }
+
+    /// Saves `new_password` for an item in one transaction with an `audit_key` entry
+    fn replace_password(
+        &self,
+        uuid: &str,
+        new_password: &str,
+        audit_key: &str,
+    ) -> AppResult<Credential> {
+        if new_password.is_empty() {
+            return Err(AppError::InvalidInput(
+                "New password must not be empty".to_string(),
+            ));
+        }
+
+        let credential = self.credential_repo.get_credential(uuid)?;
+        let mut secret = self.open_secret(&credential)?;
+        if secret.password == new_password {
+            return Err(AppError::InvalidInput(
+                "New password must differ from the current one".to_string(),
+            ));
+        }
+        secret.password = new_password.to_string();
+        let updated = self.updated_credential(
+            uuid,
+            &credential.site,
+            &credential.username,
+            secret,
+            credential.tags.clone(),
+            credential.expires_at,
+        )?;
+        self.with_transaction(|txn| {
+            txn.update_credential(&updated)?;
+            self.audit_txn(
+                txn,
+                Message::new(audit_key).with_param("site", &credential.site),
+                Some(uuid),
+            )?;
+            txn.get_credential(uuid)
+        })
+    }
+
+    /// Starts rotating the passwords of every item matching `filter`, replacing any
+    /// session in progress. Needs the master password, since each step reveals a password.
+    pub fn start_rotation(
+        &self,
+        filter: Option<CredentialFilter>,
+        options: GeneratorOptions,
+        master_password: &str,
+    ) -> AppResult<RotationStatus> {
+        self.reauthenticate(master_password)?;
+        let uuids: Vec<String> = self
+            .credential_repo
+            .list_credentials(filter)?
+            .into_iter()
+            .map(|credential| credential.uuid)
+            .collect();
+        let session = RotationSession::new(uuids, options);
+        let status = session.status();
+        *self.rotation.lock().unwrap() = Some(session);
+
+        self.audit(
+            Message::new("audit.rotation_started").with_param("count", status.total),
+            None,
+        )?;
+        Ok(status)
+    }
+
+    /// The item to rotate now, with a new password generated within its site's
+    /// constraints. Asking again before answering returns the same item.
+    /// None once every item has been confirmed or skipped.
+    pub fn next_rotation_item(&self) -> AppResult<Option<RotationItem>> {
         self.ensure_unlocked()?;
-        self.credential_repo.get_credential(uuid)
+        let mut guard = self.rotation.lock().unwrap();
+        let session = guard.as_mut().ok_or_else(no_rotation_session)?;
+        if let Some(item) = session.current() {
+            return Ok(Some(item.clone()));
+        }
+
+        while let Some(uuid) = session.next_uuid() {
+            let credential = match self.credential_repo.get_credential(&uuid) {
+                Ok(credential) => credential,
+                // Deleted since the session started
+                Err(AppError::NotFound(_)) => {
+                    session.drop_missing();
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            // Showing the current password of a high-security item takes the
+            // master password each time, so those are left for manual rotation
+            let secret = match self.decrypt_secret(&credential) {
+                Ok(secret) => secret,
+                Err(AppError::ReauthRequired) => {
+                    self.audit(
+                        Message::new("audit.rotation_skipped").with_param("site", &credential.site),
+                        Some(&credential.uuid),
+                    )?;
+                    session.record_skipped(RotationEntry::from(&credential));
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let new_password = generator::generate_constrained(
+                session.options(),
+                &credential.generator_constraints.clone().unwrap_or_default(),
+            )?;
+            let item = RotationItem {
+                uuid: credential.uuid,
+                site: credential.site,
+                username: credential.username,
+                current_password: secret.password,
+                new_password,
+                position: session.position(),
+                total: session.status().total,
+            };
+            session.set_current(item.clone());
+            return Ok(Some(item));
+        }
+        Ok(None)
     }
 
-    /// Lists credentials matching the filter criteria
-    pub fn list_credentials(&self, filter: Option<CredentialFilter>) -> AppResult<Vec<Credential>> {
+    /// Saves the new password of the current item, once it was changed on the website
+    pub fn confirm_rotated(&self, uuid: &str) -> AppResult<RotationStatus> {
         self.ensure_unlocked()?;
-        self.credential_repo.list_credentials(filter)
+        let mut guard = self.rotation.lock().unwrap();
+        let session = guard.as_mut().ok_or_else(no_rotation_session)?;
+        let item = session.take_current(uuid)?;
+        let credential =
+            match self.replace_password(uuid, &item.new_password, "audit.password_rotated") {
+                Ok(credential) => credential,
+                Err(e) => {
+                    // Leave the item up so the user can retry or skip it
+                    session.set_current(item);
+                    return Err(e);
+                }
+            };
+        session.record_rotated(RotationEntry::from(&credential));
+        Ok(session.status())
     }
 
-    /// Decrypts the secret data from a credential
-    pub fn decrypt_secret(&self, credential: &Credential) -> AppResult<Secret> {
+    /// Leaves the current item unchanged and records that its password still
+    /// has to be changed on the website
+    pub fn skip_rotation_item(&self, uuid: &str) -> AppResult<RotationStatus> {
         self.ensure_unlocked()?;
+        let mut guard = self.rotation.lock().unwrap();
+        let session = guard.as_mut().ok_or_else(no_rotation_session)?;
+        let item = session.take_current(uuid)?;
+        self.audit(
+            Message::new("audit.rotation_skipped").with_param("site", &item.site),
+            Some(uuid),
+        )?;
+        session.record_skipped(RotationEntry {
+            uuid: item.uuid,
+            site: item.site,
+            username: item.username,
+        });
+        Ok(session.status())
+    }
 
-        let crypto = self.crypto.lock().unwrap();
-        let plaintext = crypto.decrypt(
-            &credential.secret_enc,
-            format!("{}:{}", credential.site, credential.username).as_bytes(), // AAD
+    /// Progress of the current session, or None if none was started since unlocking
+    pub fn rotation_status(&self) -> AppResult<Option<RotationStatus>> {
+        self.ensure_unlocked()?;
+        Ok(self
+            .rotation
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(RotationSession::status))
+    }
+
+    /// Writes the whole audit log, hash-chained and signed with a key derived from the vault key
+    pub fn export_signed_audit_log(&self, path: &Path) -> AppResult<usize> {
+        self.ensure_unlocked()?;
+        let path = &self.resolve_path(path);
+        let mut entries = self.audit_logger.get_logs(Some(i64::MAX))?;
+        entries.reverse();
+
+        let public_key = self.crypto.lock().unwrap().audit_public_key()?;
+        let export = audit_export::build(
+            entries,
+            &public_key,
+            |payload| self.crypto.lock().unwrap().sign_audit(payload),
+            Utc::now(),
         )?;
+        export::write_export_file(path, &serde_json::to_vec_pretty(&export)?)?;
 
-        let secret: Secret = serde_json::from_slice(&plaintext).map_err(AppError::Serialization)?;
-        Ok(secret)
+        self.audit(
+            Message::new("audit.exported_audit_log").with_param("count", export.entries.len()),
+            None,
+        )?;
+        Ok(export.entries.len())
     }
 
-    /// Updates the breach state for a credential
-    pub fn update_breach_state(&self, uuid: &str, state: BreachState) -> AppResult<()> {
+    /// Checks a signed audit export against `public_key` (base64), such as one
+    /// the user noted from `audit_public_key`, or else against the unlocked
+    /// vault's own key. The key written in the export itself is not trusted.
+    pub fn verify_signed_audit_export(
+        &self,
+        path: &Path,
+        public_key: Option<&str>,
+    ) -> AppResult<AuditVerification> {
+        let export: SignedAuditExport = serde_json::from_slice(&std::fs::read(path)?)
+            .map_err(|e| AppError::InvalidInput(format!("Not a signed audit export: {}", e)))?;
+        let vault_key = if self.is_unlocked {
+            Some(self.crypto.lock().unwrap().audit_public_key()?)
+        } else {
+            None
+        };
+        let trusted_key = match public_key.filter(|key| !key.trim().is_empty()) {
+            Some(encoded) => {
+                Some(audit_export::decode_public_key(encoded).ok_or_else(|| {
+                    AppError::InvalidInput("Not an Ed25519 public key".to_string())
+                })?)
+            }
+            None => vault_key,
+        };
+        let is_vault_key = trusted_key.is_some() && trusted_key == vault_key;
+        Ok(audit_export::verify(
+            &export,
+            trusted_key.as_ref(),
+            is_vault_key,
+        ))
+    }
+
+    /// Base64 public key that verifies this vault's signed audit exports
+    pub fn audit_public_key(&self) -> AppResult<String> {
         self.ensure_unlocked()?;
-        // Update using the CredentialRepository trait
-        // The repository handles the audit log internally
-        self.credential_repo.update_breach_state(uuid, state)
+        Ok(audit_export::encode_public_key(
+            &self.crypto.lock().unwrap().audit_public_key()?,
+        ))
     }
 
-    /// Gets the app settings, decrypting them first.
-    pub fn get_settings(&self) -> AppResult<AppSettings> {
+    /// This vault's X25519 public key, to be shared with collection owners
+    pub fn member_public_key(&self) -> AppResult<String> {
         self.ensure_unlocked()?;
+        Ok(collections::encode_public_key(
+            &self.crypto.lock().unwrap().member_public_key()?,
+        ))
+    }
 
-        // Fetch encrypted settings and nonce from the SettingsRepository
-        let encrypted_data = self.settings_repo.get_encrypted_settings()?;
+    /// Creates a shared collection with a fresh key; this vault is its first member
+    pub fn create_collection(&self, name: &str) -> AppResult<Collection> {
+        self.ensure_unlocked()?;
+        let repo = self.collection_repo()?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::InvalidInput(
+                "Collection name must not be empty".to_string(),
+            ));
+        }
 
-        if let Some((nonce, encrypted_settings)) = encrypted_data {
-            // Decrypt using CryptoService
+        let id = Uuid::new_v4().to_string();
+        let (own_key, wrapped_key) = {
             let crypto = self.crypto.lock().unwrap();
-            let settings_json =
-                crypto.decrypt_with_nonce(&encrypted_settings, b"app_settings", &nonce)?;
-            drop(crypto);
+            (crypto.member_public_key()?, crypto.new_collection_key(&id)?)
+        };
+        let collection = Collection {
+            id: id.clone(),
+            name: name.to_string(),
+            created_at: Utc::now(),
+            members: vec![CollectionMember {
+                public_key: collections::encode_public_key(&own_key),
+                label: OWNER_LABEL.to_string(),
+                added_at: Utc::now(),
+                wrapped_key,
+            }],
+        };
+        repo.create_collection(&collection)?;
 
-            // Deserialize
-            serde_json::from_slice(&settings_json).map_err(AppError::Serialization)
-        } else {
-            // If no settings saved yet, return default
-            Ok(AppSettings::default())
+        self.audit(
+            Message::new("audit.collection_created").with_param("name", name),
+            None,
+        )?;
+        Ok(collection)
+    }
+
+    /// Lists all collections this vault knows about
+    pub fn list_collections(&self) -> AppResult<Vec<Collection>> {
+        self.ensure_unlocked()?;
+        self.collection_repo()?.list_collections()
+    }
+
+    /// Grants another member access by wrapping the collection key for their public key
+    pub fn add_collection_member(
+        &self,
+        collection_id: &str,
+        public_key: &str,
+        label: &str,
+    ) -> AppResult<Collection> {
+        self.ensure_unlocked()?;
+        let repo = self.collection_repo()?;
+        let recipient = collections::decode_public_key(public_key)?;
+        let own_wrapped = self.own_wrapped_key(collection_id)?;
+        let wrapped_key = self.crypto.lock().unwrap().rewrap_collection_key(
+            &own_wrapped,
+            collection_id,
+            &recipient,
+        )?;
+
+        let member = CollectionMember {
+            public_key: collections::encode_public_key(&recipient),
+            label: label.trim().to_string(),
+            added_at: Utc::now(),
+            wrapped_key,
+        };
+        repo.save_member(collection_id, &member)?;
+
+        let collection = repo.get_collection(collection_id)?;
+        self.audit(
+            Message::new("audit.collection_member_added")
+                .with_param("name", &collection.name)
+                .with_param("member", &member.label),
+            None,
+        )?;
+        Ok(collection)
+    }
+
+    /// Revokes a member and rotates the collection key, re-encrypting its items,
+    /// so the removed member cannot read future changes
+    pub fn remove_collection_member(
+        &self,
+        collection_id: &str,
+        public_key: &str,
+    ) -> AppResult<Collection> {
+        self.ensure_unlocked()?;
+        let repo = self.collection_repo()?;
+        if public_key == self.member_public_key()? {
+            return Err(AppError::InvalidInput(
+                "You cannot remove yourself; delete the collection instead".to_string(),
+            ));
         }
+
+        let removed_label = repo
+            .get_collection(collection_id)?
+            .members
+            .into_iter()
+            .find(|member| member.public_key == public_key)
+            .map(|member| member.label)
+            .ok_or_else(|| AppError::NotFound(public_key.to_string()))?;
+
+        // Journal both keys first; once members are rewrapped the old key is otherwise lost
+        let old_key = self.own_wrapped_key(collection_id)?;
+        let new_key = self
+            .crypto
+            .lock()
+            .unwrap()
+            .new_collection_key(collection_id)?;
+        let entry = self.begin_operation(PendingOperation::CollectionKeyRotation {
+            collection_id: collection_id.to_string(),
+            removed_member: public_key.to_string(),
+            old_key: old_key.clone(),
+            new_key: new_key.clone(),
+        })?;
+        self.finish_key_rotation(collection_id, public_key, &old_key, &new_key)?;
+        self.complete_operation(entry)?;
+
+        let collection = repo.get_collection(collection_id)?;
+        self.audit(
+            Message::new("audit.collection_member_removed")
+                .with_param("name", &collection.name)
+                .with_param("member", removed_label),
+            None,
+        )?;
+        Ok(collection)
     }
 
-    /// Saves app settings, encrypting them first.
-    pub fn save_settings(&self, settings: &AppSettings) -> AppResult<()> {
+    /// Moves a credential into a collection, or back to the personal vault with `None`,
+    /// re-encrypting its secret with the matching key
+    pub fn move_to_collection(
+        &self,
+        uuid: &str,
+        collection_id: Option<&str>,
+    ) -> AppResult<Credential> {
         self.ensure_unlocked()?;
+        let mut credential = self.credential_repo.get_credential(uuid)?;
+        if credential.collection_id.as_deref() == collection_id {
+            return Ok(credential);
+        }
 
-        let settings_json = serde_json::to_vec(settings).map_err(AppError::Serialization)?;
+        let secret = self.open_secret(&credential)?;
+        credential.secret_enc = self.encrypt_secret(
+            &secret,
+            &credential.site,
+            &credential.username,
+            collection_id,
+        )?;
+        credential.collection_id = collection_id.map(str::to_string);
+        self.credential_repo.update_credential(&credential)?;
 
-        // Encrypt the settings using CryptoService
-        let crypto = self.crypto.lock().unwrap();
-        // Use a method that returns nonce and ciphertext separately
-        let (nonce, encrypted_settings) =
-            crypto.encrypt_return_nonce(&settings_json, b"app_settings")?;
-        drop(crypto); // Explicitly drop the crypto lock
+        let message = match collection_id {
+            Some(id) => Message::new("audit.moved_to_collection")
+                .with_param("site", &credential.site)
+                .with_param("name", &self.collection_repo()?.get_collection(id)?.name),
+            None => Message::new("audit.moved_to_personal").with_param("site", &credential.site),
+        };
+        self.audit(message, Some(uuid))?;
+        self.credential_repo.get_credential(uuid)
+    }
 
-        // Save nonce and encrypted data using SettingsRepository
-        self.settings_repo
-            .save_encrypted_settings(&nonce, &encrypted_settings)?;
+    /// Deletes a collection after moving its items back to the personal vault
+    pub fn delete_collection(&self, collection_id: &str) -> AppResult<()> {
+        self.ensure_unlocked()?;
+        let collection = self.collection_repo()?.get_collection(collection_id)?;
+        let entry = self.begin_operation(PendingOperation::CollectionDeletion {
+            collection_id: collection_id.to_string(),
+        })?;
+        self.finish_collection_deletion(collection_id)?;
+        self.complete_operation(entry)?;
 
-        // Update crypto service's in-memory settings (KDF params)
-        let mut crypto = self.crypto.lock().unwrap();
-        crypto.update_kdf_settings(settings.clone());
-        drop(crypto);
+        self.audit(
+            Message::new("audit.collection_deleted").with_param("name", &collection.name),
+            None,
+        )?;
+        Ok(())
+    }
 
-        // Log the action using AuditLogger
-        self.audit_logger.add_log("Updated app settings", None)?;
+    /// Removes a member, wraps `new_key` for everyone left and re-encrypts the items
+    /// still under `old_key`; safe to repeat. Both keys are wrapped for this vault.
+    fn finish_key_rotation(
+        &self,
+        collection_id: &str,
+        removed_member: &str,
+        old_key: &WrappedKey,
+        new_key: &WrappedKey,
+    ) -> AppResult<()> {
+        let repo = self.collection_repo()?;
+        let collection = repo.get_collection(collection_id)?;
+        if collection
+            .members
+            .iter()
+            .any(|member| member.public_key == removed_member)
+        {
+            repo.remove_member(collection_id, removed_member)?;
+        }
+
+        for mut member in repo.get_collection(collection_id)?.members {
+            let recipient = collections::decode_public_key(&member.public_key)?;
+            member.wrapped_key = self.crypto.lock().unwrap().rewrap_collection_key(
+                new_key,
+                collection_id,
+                &recipient,
+            )?;
+            repo.save_member(collection_id, &member)?;
+        }
+        for mut credential in self.credential_repo.list_credentials(None)? {
+            if credential.collection_id.as_deref() != Some(collection_id) {
+                continue;
+            }
+            let aad = format!("{}:{}", credential.site, credential.username);
+            let recrypted = self.crypto.lock().unwrap().recrypt_for_collection(
+                old_key,
+                new_key,
+                collection_id,
+                &credential.secret_enc,
+                aad.as_bytes(),
+            )?;
+            let Some(secret_enc) = recrypted else {
+                continue;
+            };
+            credential.secret_enc = secret_enc;
+            self.credential_repo.update_credential(&credential)?;
+        }
+        Ok(())
+    }
+
+    /// Moves the remaining items out of a collection and deletes it; safe to repeat
+    fn finish_collection_deletion(&self, collection_id: &str) -> AppResult<()> {
+        for credential in self.credential_repo.list_credentials(None)? {
+            if credential.collection_id.as_deref() == Some(collection_id) {
+                self.move_to_collection(&credential.uuid, None)?;
+            }
+        }
+        self.collection_repo()?.delete_collection(collection_id)
+    }
+
+    fn collection_repo(&self) -> AppResult<&Arc<dyn CollectionRepository>> {
+        self.collection_repo.as_ref().ok_or_else(|| {
+            AppError::Other("Collections are not supported by this vault".to_string())
+        })
+    }
+
+    /// The collection key as wrapped for this vault's member key
+    fn own_wrapped_key(&self, collection_id: &str) -> AppResult<WrappedKey> {
+        let collection = self.collection_repo()?.get_collection(collection_id)?;
+        let own_key =
+            collections::encode_public_key(&self.crypto.lock().unwrap().member_public_key()?);
+        collection
+            .members
+            .into_iter()
+            .find(|member| member.public_key == own_key)
+            .map(|member| member.wrapped_key)
+            .ok_or_else(|| {
+                AppError::InvalidInput(format!(
+                    "This vault is not a member of collection {}",
+                    collection.name
+                ))
+            })
+    }
+
+    /// Encrypts a credential for a one-time share. Nothing is stored until
+    /// `record_share` is called after the envelope has been published.
+    pub fn prepare_share(
+        &self,
+        uuid: &str,
+        expires_in_secs: u64,
+        max_views: u32,
+    ) -> AppResult<PreparedShare> {
+        self.ensure_unlocked()?;
+        let credential = self.credential_repo.get_credential(uuid)?;
+        let secret = self.decrypt_secret(&credential)?;
+        let shared = SharedSecret {
+            site: credential.site.clone(),
+            username: credential.username.clone(),
+            password: secret.password,
+            notes: secret.notes,
+        };
+        let (envelope, key) = shares::seal(&shared, expires_in_secs, max_views, Utc::now())?;
+        Ok(PreparedShare {
+            envelope,
+            key,
+            item_uuid: credential.uuid,
+            site: credential.site,
+        })
+    }
+
+    /// Remembers a published share and returns the link for the recipient
+    pub fn record_share(
+        &self,
+        prepared: PreparedShare,
+        destination: ShareDestination,
+        location: &str,
+        revoke_token: &str,
+        link: Option<String>,
+    ) -> AppResult<ShareLink> {
+        self.ensure_unlocked()?;
+        let record = ShareRecord {
+            id: prepared.envelope.id,
+            item_uuid: prepared.item_uuid,
+            site: prepared.site,
+            destination,
+            location: location.to_string(),
+            revoke_token: revoke_token.to_string(),
+            created_at: Utc::now(),
+            expires_at: prepared.envelope.expires_at,
+            max_views: prepared.envelope.max_views,
+            revoked_at: None,
+        };
+        self.share_repo()?.save_share(&record)?;
+
+        self.audit(
+            Message::new("audit.share_created")
+                .with_param("site", &record.site)
+                .with_param("destination", destination.as_str()),
+            Some(&record.item_uuid),
+        )?;
+        let key = shares::encode_key(&prepared.key);
+        Ok(ShareLink {
+            share: record,
+            link: link.map(|link| format!("{}#{}", link, key)),
+            key,
+        })
+    }
+
+    /// Writes a self-contained encrypted share file to `path`
+    pub fn create_file_share(
+        &self,
+        uuid: &str,
+        expires_in_secs: u64,
+        max_views: u32,
+        path: &Path,
+    ) -> AppResult<ShareLink> {
+        // Fail before anything is written if sharing is unsupported
+        self.share_repo()?;
+        let prepared = self.prepare_share(uuid, expires_in_secs, max_views)?;
+        export::write_export_file(path, &serde_json::to_vec_pretty(&prepared.envelope)?)?;
+        self.record_share(
+            prepared,
+            ShareDestination::File,
+            &path.display().to_string(),
+            "",
+            None,
+        )
+    }
+
+    /// Lists shares created from this vault, newest first
+    pub fn list_shares(&self) -> AppResult<Vec<ShareRecord>> {
+        self.ensure_unlocked()?;
+        self.share_repo()?.list_shares()
+    }
+
+    /// Gets a share record, including its relay revoke token
+    pub fn get_share(&self, id: &str) -> AppResult<ShareRecord> {
+        self.ensure_unlocked()?;
+        self.share_repo()?.get_share(id)
+    }
+
+    /// Marks a share revoked and deletes its file for file shares.
+    /// Relay copies must be deleted by the caller first.
+    pub fn revoke_share(&self, id: &str) -> AppResult<ShareRecord> {
+        self.ensure_unlocked()?;
+        let repo = self.share_repo()?;
+        let share = repo.get_share(id)?;
+        if share.revoked_at.is_some() {
+            return Ok(share);
+        }
+        if share.destination == ShareDestination::File {
+            match std::fs::remove_file(&share.location) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        repo.mark_share_revoked(id, Utc::now())?;
+        let share = repo.get_share(id)?;
+
+        self.audit(
+            Message::new("audit.share_revoked").with_param("site", &share.site),
+            Some(&share.item_uuid),
+        )?;
+        Ok(share)
+    }
+
+    fn share_repo(&self) -> AppResult<&Arc<dyn ShareRepository>> {
+        self.share_repo
+            .as_ref()
+            .ok_or_else(|| AppError::Other("Sharing is not supported by this vault".to_string()))
+    }
+
+    /// Designates an emergency contact and wraps the vault key for their public key.
+    /// The wrapped key stays valid only until the master password changes. It is
+    /// stored right away, so `wait_days` holds only while the contact goes through
+    /// the app; see the `emergency` module.
+    pub fn add_emergency_contact(
+        &self,
+        label: &str,
+        public_key: &str,
+        wait_days: u32,
+    ) -> AppResult<EmergencyContact> {
+        self.ensure_unlocked()?;
+        let repo = self.emergency_repo()?;
+        let recipient = collections::decode_public_key(public_key)?;
+        let mut contact = EmergencyContact::new(
+            label,
+            collections::encode_public_key(&recipient),
+            wait_days,
+            Utc::now(),
+        )?;
+        let vault_key = self.crypto.lock().unwrap().master_key_bytes()?;
+        contact.wrapped_key = Some(collections::wrap_key(&vault_key, &recipient, &contact.id)?);
+        repo.save_contact(&contact)?;
+
+        self.audit(
+            Message::new("audit.emergency_contact_added")
+                .with_param("contact", &contact.label)
+                .with_param("days", contact.wait_days),
+            None,
+        )?;
+        Ok(contact)
+    }
+
+    /// Lists emergency contacts, granting requests whose waiting period has passed
+    pub fn list_emergency_contacts(&self) -> AppResult<Vec<EmergencyContact>> {
+        self.ensure_unlocked()?;
+        let ids: Vec<String> = self
+            .emergency_repo()?
+            .list_contacts()?
+            .into_iter()
+            .map(|contact| contact.id)
+            .collect();
+        ids.iter().map(|id| self.emergency_contact(id)).collect()
+    }
+
+    /// Starts the waiting period on behalf of a contact. Works while locked,
+    /// since the contact does not know the master password.
+    pub fn request_emergency_access(&self, id: &str) -> AppResult<EmergencyContact> {
+        let mut contact = self.emergency_contact(id)?;
+        contact.request_recovery(Utc::now())?;
+        self.save_emergency_change(&contact, "audit.emergency_access_requested")?;
+        Ok(contact)
+    }
+
+    /// Grants a pending request without waiting
+    pub fn approve_emergency_access(&self, id: &str) -> AppResult<EmergencyContact> {
+        self.ensure_unlocked()?;
+        let mut contact = self.emergency_contact(id)?;
+        contact.approve(Utc::now())?;
+        self.save_emergency_change(&contact, "audit.emergency_access_approved")?;
+        Ok(contact)
+    }
+
+    /// Vetoes a pending request; the contact stays designated
+    pub fn reject_emergency_access(&self, id: &str) -> AppResult<EmergencyContact> {
+        self.ensure_unlocked()?;
+        let mut contact = self.emergency_contact(id)?;
+        contact.reject(Utc::now())?;
+        self.save_emergency_change(&contact, "audit.emergency_access_rejected")?;
+        Ok(contact)
+    }
+
+    /// Withdraws emergency access and discards the wrapped key
+    pub fn revoke_emergency_contact(&self, id: &str) -> AppResult<EmergencyContact> {
+        self.ensure_unlocked()?;
+        let mut contact = self.emergency_contact(id)?;
+        contact.revoke(Utc::now());
+        self.save_emergency_change(&contact, "audit.emergency_contact_revoked")?;
+        Ok(contact)
+    }
+
+    /// Hands out the wrapped vault key once access was granted. Works while locked.
+    /// The contact unwraps it with `collections::unwrap_key`, using the contact id.
+    pub fn release_emergency_key(&self, id: &str) -> AppResult<WrappedKey> {
+        let contact = self.emergency_contact(id)?;
+        let wrapped = contact.release()?.clone();
+        self.audit(
+            Message::new("audit.emergency_key_released").with_param("contact", &contact.label),
+            None,
+        )?;
+        Ok(wrapped)
+    }
 
+    /// Loads a contact, persisting an automatic grant if the waiting period has passed
+    fn emergency_contact(&self, id: &str) -> AppResult<EmergencyContact> {
+        let mut contact = self.emergency_repo()?.get_contact(id)?;
+        if contact.refresh(Utc::now()) {
+            self.save_emergency_change(&contact, "audit.emergency_access_granted")?;
+        }
+        Ok(contact)
+    }
+
+    fn save_emergency_change(&self, contact: &EmergencyContact, audit_key: &str) -> AppResult<()> {
+        self.emergency_repo()?.save_contact(contact)?;
+        self.audit(
+            Message::new(audit_key).with_param("contact", &contact.label),
+            None,
+        )?;
         Ok(())
     }
 
+    /// ID of the device this manager runs on
+    pub fn current_device_id(&self) -> Option<&str> {
+        self.devices
+            .as_ref()
+            .map(|(_, identity)| identity.id.as_str())
+    }
+
+    /// Lists the devices that have opened the vault, most recently seen first
+    pub fn list_devices(&self) -> AppResult<Vec<Device>> {
+        self.ensure_unlocked()?;
+        self.device_repo()?.list_devices()
+    }
+
+    /// Stops a device from unlocking the vault. The current device cannot be revoked.
+    pub fn revoke_device(&self, id: &str) -> AppResult<Device> {
+        self.ensure_unlocked()?;
+        let repo = self.device_repo()?;
+        if self.current_device_id() == Some(id) {
+            return Err(AppError::InvalidInput(
+                "The current device cannot be revoked".to_string(),
+            ));
+        }
+        repo.revoke_device(id, Utc::now())?;
+        let device = repo.get_device(id)?;
+
+        self.audit(
+            Message::new("audit.device_revoked").with_param("device", &device.name),
+            None,
+        )?;
+        Ok(device)
+    }
+
+    fn device_repo(&self) -> AppResult<&Arc<dyn DeviceRepository>> {
+        self.devices.as_ref().map(|(repo, _)| repo).ok_or_else(|| {
+            AppError::Other("Device management is not supported by this vault".to_string())
+        })
+    }
+
+    fn emergency_repo(&self) -> AppResult<&Arc<dyn EmergencyRepository>> {
+        self.emergency_repo.as_ref().ok_or_else(|| {
+            AppError::Other("Emergency access is not supported by this vault".to_string())
+        })
+    }
+
+    /// Hides every credential without one of `allowed_tags`, and the audit entries
+    /// naming it, archived ones included, by encrypting them under a fresh travel
+    /// key. The key is not stored; it is only in the returned recovery blob.
+    pub fn enable_travel_mode(&self, allowed_tags: &[String]) -> AppResult<TravelActivation> {
+        self.ensure_unlocked()?;
+        let repo = self.travel_repo()?;
+        if repo.travel_mode_since()?.is_some() {
+            return Err(AppError::InvalidInput(
+                "Travel mode is already enabled".to_string(),
+            ));
+        }
+
+        let hidden: Vec<Credential> = self
+            .credential_repo
+            .list_credentials(None)?
+            .into_iter()
+            .filter(|credential| !travel::is_allowed(credential, allowed_tags))
+            .collect();
+        let key = CryptoService::generate_key();
+        let now = Utc::now();
+        let recovery_blob = travel::encode_recovery(&key, hidden.len(), now)?;
+        let uuids: Vec<String> = hidden.iter().map(|c| c.uuid.clone()).collect();
+        let mut audit = repo.item_audit_entries(&uuids)?;
+        let archives = self.strip_audit_archives(&uuids, &mut audit)?;
+        repo.enter_travel_mode(&travel::stash(&hidden, &audit, &key)?, &archives, now)?;
+
+        self.audit(
+            Message::new("audit.travel_mode_enabled").with_param("count", hidden.len()),
+            None,
+        )?;
+        Ok(TravelActivation {
+            hidden_count: hidden.len(),
+            recovery_blob,
+        })
+    }
+
+    /// Restores the credentials hidden by travel mode. Returns how many were restored.
+    /// Their audit entries go back to the audit table, archived ones too, so the
+    /// next `archive_audit_log` archives those again.
+    pub fn disable_travel_mode(&self, recovery_blob: &str) -> AppResult<usize> {
+        self.ensure_unlocked()?;
+        let repo = self.travel_repo()?;
+        if repo.travel_mode_since()?.is_none() {
+            return Err(AppError::InvalidInput(
+                "Travel mode is not enabled".to_string(),
+            ));
+        }
+
+        let key = travel::decode_recovery(recovery_blob)?;
+        let (restored, audit) = travel::restore(&repo.list_stashed()?, &key)?;
+        repo.leave_travel_mode(&restored, &audit)?;
+
+        self.audit(
+            Message::new("audit.travel_mode_disabled").with_param("count", restored.len()),
+            None,
+        )?;
+        Ok(restored.len())
+    }
+
+    /// Whether travel mode is on and how many credentials it hides
+    pub fn travel_mode_status(&self) -> AppResult<TravelModeStatus> {
+        self.ensure_unlocked()?;
+        let repo = self.travel_repo()?;
+        let enabled_at = repo.travel_mode_since()?;
+        Ok(TravelModeStatus {
+            active: enabled_at.is_some(),
+            enabled_at,
+            hidden_count: repo.list_stashed()?.len(),
+        })
+    }
+
+    /// Moves the entries about `uuids` out of the audit archive segments into
+    /// `audit`. Returns the segments that changed, re-encrypted; one left without
+    /// entries is to be deleted.
+    fn strip_audit_archives(
+        &self,
+        uuids: &[String],
+        audit: &mut Vec<AuditLogEntry>,
+    ) -> AppResult<Vec<StoredAuditArchive>> {
+        let Some(archive_repo) = &self.audit_archive_repo else {
+            return Ok(Vec::new());
+        };
+        let mut changed = Vec::new();
+        for archive in archive_repo.list_audit_archives()? {
+            let crypto = self.crypto.lock().unwrap();
+            let packed = crypto.decrypt(&archive.data_enc, AUDIT_ARCHIVE_AAD)?;
+            let (hidden, kept): (Vec<_>, Vec<_>) = audit_archive::unpack(&packed)?
+                .into_iter()
+                .partition(|entry| {
+                    entry
+                        .item_uuid
+                        .as_ref()
+                        .is_some_and(|uuid| uuids.contains(uuid))
+                });
+            if hidden.is_empty() {
+                continue;
+            }
+            changed.push(match (kept.first(), kept.last()) {
+                (Some(first), Some(last)) => StoredAuditArchive {
+                    id: archive.id,
+                    first_at: first.timestamp,
+                    last_at: last.timestamp,
+                    entries: kept.len(),
+                    data_enc: crypto.encrypt(&audit_archive::pack(&kept)?, AUDIT_ARCHIVE_AAD)?,
+                },
+                _ => StoredAuditArchive {
+                    entries: 0,
+                    data_enc: String::new(),
+                    ..archive
+                },
+            });
+            audit.extend(hidden);
+        }
+        audit.sort_by_key(|entry| entry.id);
+        Ok(changed)
+    }
+
+    fn travel_repo(&self) -> AppResult<&Arc<dyn TravelRepository>> {
+        self.travel_repo.as_ref().ok_or_else(|| {
+            AppError::Other("Travel mode is not supported by this vault".to_string())
+        })
+    }
+
+    /// Journals an operation before its first step (no-op without a journal)
+    fn begin_operation(&self, operation: PendingOperation) -> AppResult<Option<JournalEntry>> {
+        let Some(repo) = &self.journal_repo else {
+            return Ok(None);
+        };
+        let entry = JournalEntry::new(operation, Utc::now());
+        repo.begin_operation(&entry)?;
+        Ok(Some(entry))
+    }
+
+    /// Removes a finished operation from the journal
+    fn complete_operation(&self, entry: Option<JournalEntry>) -> AppResult<()> {
+        match (&self.journal_repo, entry) {
+            (Some(repo), Some(entry)) => repo.complete_operation(&entry.id),
+            _ => Ok(()),
+        }
+    }
+
+    /// Lets the next unlock go ahead without recovering interrupted operations,
+    /// once the user has confirmed after a failed recovery. The operations stay
+    /// in the journal and are tried again on the unlock after.
+    pub fn skip_next_recovery(&mut self) {
+        self.skip_recovery = true;
+    }
+
+    /// `recover_pending_operations`, unless `skip_next_recovery` asked to skip
+    /// it. A failure is reported as `AppError::RecoveryFailed`, for the UI to
+    /// offer opening the vault anyway.
+    fn recover_on_unlock(&mut self) -> AppResult<()> {
+        if std::mem::take(&mut self.skip_recovery) {
+            let pending = match &self.journal_repo {
+                Some(repo) => repo.list_pending_operations()?.len(),
+                None => 0,
+            };
+            if pending > 0 {
+                warn!(
+                    pending,
+                    "vault unlocked without recovering interrupted operations"
+                );
+                self.audit(
+                    Message::new("audit.recovery_skipped").with_param("count", pending),
+                    None,
+                )?;
+            }
+            return Ok(());
+        }
+        self.recover_pending_operations()
+            .map(|_| ())
+            .map_err(|e| AppError::RecoveryFailed(e.to_string()))
+    }
+
+    /// Rolls back or finishes operations interrupted by a crash. Runs on every unlock.
+    pub fn recover_pending_operations(&self) -> AppResult<RecoveryReport> {
+        self.ensure_unlocked()?;
+        let mut report = RecoveryReport::default();
+        let Some(repo) = &self.journal_repo else {
+            return Ok(report);
+        };
+
+        for entry in repo.list_pending_operations()? {
+            let action = match &entry.operation {
+                PendingOperation::Import { credential_uuids } => {
+                    for uuid in credential_uuids {
+                        if self.credential_repo.credential_exists(uuid)? {
+                            self.credential_repo.delete_credential(uuid)?;
+                        }
+                    }
+                    report.rolled_back += 1;
+                    "audit.operation_rolled_back"
+                }
+                PendingOperation::CollectionDeletion { collection_id } => {
+                    // The collection row goes last, so its absence means only the journal is stale
+                    match self.collection_repo()?.get_collection(collection_id) {
+                        Ok(_) => self.finish_collection_deletion(collection_id)?,
+                        Err(AppError::NotFound(_)) => {}
+                        Err(e) => return Err(e),
+                    }
+                    report.resumed += 1;
+                    "audit.operation_resumed"
+                }
+                PendingOperation::CollectionKeyRotation {
+                    collection_id,
+                    removed_member,
+                    old_key,
+                    new_key,
+                } => {
+                    match self.finish_key_rotation(collection_id, removed_member, old_key, new_key)
+                    {
+                        Ok(()) | Err(AppError::NotFound(_)) => {}
+                        Err(e) => return Err(e),
+                    }
+                    report.resumed += 1;
+                    "audit.operation_resumed"
+                }
+            };
+            repo.complete_operation(&entry.id)?;
+            self.audit(
+                Message::new(action).with_param("operation", entry.operation.name()),
+                None,
+            )?;
+        }
+        Ok(report)
+    }
+
     /// Gets audit log entries
     pub fn get_audit_log(&self, limit: Option<i64>) -> AppResult<Vec<AuditLogEntry>> {
         self.ensure_unlocked()?;
@@ -277,6 +5096,86 @@ impl VaultManager {
         self.audit_logger.get_logs(limit)
     }
 
+    /// Rolls audit entries older than `older_than_days` into compressed,
+    /// encrypted segments and removes them from the audit table
+    pub fn archive_audit_log(
+        &self,
+        older_than_days: u32,
+        now: DateTime<Utc>,
+    ) -> AppResult<AuditArchiveReport> {
+        self.ensure_unlocked()?;
+        let repo = self.audit_archive_repo()?;
+        let cutoff = now - chrono::Duration::days(i64::from(older_than_days));
+        let mut report = AuditArchiveReport::default();
+        loop {
+            let entries = repo.audit_entries_before(cutoff, AUDIT_ARCHIVE_SEGMENT_ENTRIES)?;
+            let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+                break;
+            };
+            let data_enc = self
+                .crypto
+                .lock()
+                .unwrap()
+                .encrypt(&audit_archive::pack(&entries)?, AUDIT_ARCHIVE_AAD)?;
+            let archive = StoredAuditArchive {
+                id: 0,
+                first_at: first.timestamp,
+                last_at: last.timestamp,
+                entries: entries.len(),
+                data_enc,
+            };
+            let ids: Vec<i64> = entries.iter().map(|entry| entry.id).collect();
+            repo.add_audit_archive(&archive, &ids)?;
+            report.archived += entries.len();
+            report.segments += 1;
+        }
+        if report.archived > 0 {
+            debug!(entries = report.archived, "audit entries archived");
+            self.audit(
+                Message::new("audit.audit_archived").with_param("count", report.archived),
+                None,
+            )?;
+        }
+        Ok(report)
+    }
+
+    /// Archived audit entries within `range`, newest first, like `get_audit_log`.
+    /// Only the segments overlapping the range are decrypted.
+    pub fn search_audit_archives(
+        &self,
+        range: &AuditArchiveRange,
+        limit: Option<usize>,
+    ) -> AppResult<Vec<AuditLogEntry>> {
+        self.ensure_unlocked()?;
+        let mut found = Vec::new();
+        for archive in self.audit_archive_repo()?.list_audit_archives()? {
+            if !range.overlaps(archive.first_at, archive.last_at) {
+                continue;
+            }
+            let packed = self
+                .crypto
+                .lock()
+                .unwrap()
+                .decrypt(&archive.data_enc, AUDIT_ARCHIVE_AAD)?;
+            found.extend(
+                audit_archive::unpack(&packed)?
+                    .into_iter()
+                    .filter(|entry| range.contains(entry.timestamp)),
+            );
+        }
+        found.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(b.id.cmp(&a.id)));
+        if let Some(limit) = limit {
+            found.truncate(limit);
+        }
+        Ok(found)
+    }
+
+    fn audit_archive_repo(&self) -> AppResult<&Arc<dyn AuditArchiveRepository>> {
+        self.audit_archive_repo.as_ref().ok_or_else(|| {
+            AppError::Other("Audit archives are not supported by this vault".to_string())
+        })
+    }
+
     /// Helper method to ensure the vault is unlocked
     fn ensure_unlocked(&self) -> AppResult<()> {
         if !self.is_unlocked {
@@ -286,3 +5185,7 @@ impl VaultManager {
         }
     }
 }
+
+fn no_rotation_session() -> AppError {
+    AppError::InvalidInput("No password rotation is in progress".to_string())
+}
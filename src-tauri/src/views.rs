@@ -0,0 +1,141 @@
+//! Saved views of the item list.
+//!
+//! A view is a named filter, sort order and set of columns. Views are kept in
+//! an encrypted metadata entry, since search terms and tags can say as much
+//! about the vault as its items do. Applying a view happens here rather than in
+//! the frontend, so every window lists a view the same way.
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::models::{BreachState, CredentialSummary};
+use crate::text_match::MatchMode;
+use crate::vault::{CredentialFilter, SmartFilters};
+
+/// Metadata entry holding the saved views
+pub const VIEWS_META_KEY: &str = "views";
+/// Longest view name, in characters
+pub const MAX_VIEW_NAME_CHARS: usize = 64;
+
+/// The stored form of a `CredentialFilter`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewFilter {
+    pub search_term: Option<String>,
+    pub tag: Option<String>,
+    pub min_strength: Option<u8>,
+    pub breach_state: Option<BreachState>,
+    pub password_older_than_days: Option<u32>,
+    pub match_mode: MatchMode,
+    pub smart: SmartFilters,
+}
+
+impl ViewFilter {
+    pub fn to_filter(&self) -> CredentialFilter {
+        CredentialFilter {
+            search_term: self.search_term.clone(),
+            tag: self.tag.clone(),
+            min_strength: self.min_strength,
+            breach_state: self.breach_state,
+            password_older_than_days: self.password_older_than_days,
+            match_mode: self.match_mode,
+            smart: self.smart.clone(),
+            ..CredentialFilter::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    /// The order items are listed in without a view
+    #[default]
+    Site,
+    Username,
+    CreatedAt,
+    UpdatedAt,
+    PasswordChangedAt,
+    Strength,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewSort {
+    pub field: SortField,
+    pub direction: SortDirection,
+}
+
+/// What a view shows
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewConfig {
+    pub filter: ViewFilter,
+    pub sort: ViewSort,
+    /// Columns to show, in order. Named by the frontend; stored as given.
+    pub columns: Vec<String>,
+}
+
+/// A view as returned by `list_views`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedView {
+    pub name: String,
+    pub config: ViewConfig,
+}
+
+/// Trims a view name and checks it is usable
+pub fn normalize_name(name: &str) -> AppResult<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("A view needs a name".to_string()));
+    }
+    if name.chars().count() > MAX_VIEW_NAME_CHARS {
+        return Err(AppError::InvalidInput(format!(
+            "View names are limited to {} characters",
+            MAX_VIEW_NAME_CHARS
+        )));
+    }
+    Ok(name.to_string())
+}
+
+/// Adds `view` to `views`, replacing a view of the same name, and keeps them
+/// ordered by name
+pub fn upsert(views: &mut Vec<SavedView>, view: SavedView) {
+    match views.iter_mut().find(|saved| saved.name == view.name) {
+        Some(saved) => *saved = view,
+        None => views.push(view),
+    }
+    views.sort_by_key(|view| view.name.to_lowercase());
+}
+
+/// Sorts `items` by `sort`, breaking ties by site and then username
+pub fn sort_summaries(items: &mut [CredentialSummary], sort: ViewSort) {
+    let by_site = |a: &CredentialSummary, b: &CredentialSummary| {
+        (a.site.to_lowercase(), a.username.to_lowercase())
+            .cmp(&(b.site.to_lowercase(), b.username.to_lowercase()))
+    };
+    items.sort_by(|a, b| {
+        let order = match sort.field {
+            SortField::Site => Ordering::Equal,
+            SortField::Username => a.username.to_lowercase().cmp(&b.username.to_lowercase()),
+            SortField::CreatedAt => a.created_at.cmp(&b.created_at),
+            SortField::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+            SortField::PasswordChangedAt => a.password_changed_at.cmp(&b.password_changed_at),
+            SortField::Strength => a.strength.cmp(&b.strength),
+        }
+        .then_with(|| by_site(a, b));
+        match sort.direction {
+            SortDirection::Ascending => order,
+            SortDirection::Descending => order.reverse(),
+        }
+    });
+}
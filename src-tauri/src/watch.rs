@@ -0,0 +1,83 @@
+//! Detecting changes other programs make to the vault database.
+//!
+//! A sync client either rewrites the file in place or replaces it with a new
+//! one. SQLite notices the first through `PRAGMA data_version`, which changes
+//! only when another connection commits. The second leaves the open connection
+//! reading the old, unlinked file, so the path's file identity is compared too.
+
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// How the vault database was changed from outside the app
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalChange {
+    /// Another program wrote to the open database
+    Modified,
+    /// A different file now sits at the vault path
+    Replaced,
+    /// Nothing is left at the vault path
+    Removed,
+}
+
+/// Identifies a file independently of its contents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileIdentity {
+    device: u64,
+    inode: u64,
+}
+
+impl FileIdentity {
+    /// Identity of the file at `path`, or None if there is none
+    pub fn of(path: &Path) -> io::Result<Option<Self>> {
+        match std::fs::metadata(path) {
+            Ok(metadata) => Ok(Some(Self::from_metadata(&metadata))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    #[cfg(unix)]
+    fn from_metadata(metadata: &std::fs::Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+        Self {
+            device: metadata.dev(),
+            inode: metadata.ino(),
+        }
+    }
+
+    // Without inode numbers, a replaced file shows up as a new creation time
+    #[cfg(not(unix))]
+    fn from_metadata(metadata: &std::fs::Metadata) -> Self {
+        let created = metadata
+            .created()
+            .ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        Self {
+            device: created.as_secs(),
+            inode: u64::from(created.subsec_nanos()),
+        }
+    }
+}
+
+/// What the database looked like the last time it was checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchBaseline {
+    pub identity: Option<FileIdentity>,
+    pub data_version: i64,
+}
+
+impl WatchBaseline {
+    /// The change between this baseline and `current`, if any
+    pub fn compare(&self, current: &WatchBaseline) -> Option<ExternalChange> {
+        match current.identity {
+            None if self.identity.is_some() => Some(ExternalChange::Removed),
+            identity if identity != self.identity => Some(ExternalChange::Replaced),
+            _ if current.data_version != self.data_version => Some(ExternalChange::Modified),
+            _ => None,
+        }
+    }
+}
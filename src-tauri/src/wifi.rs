@@ -0,0 +1,198 @@
+//! Wi-Fi network items and their QR codes.
+//!
+//! A network is an item of kind `Wifi`: the passphrase is its password and the
+//! SSID its site, with the security type in `WifiDetails`. `qr_code` encodes
+//! the `WIFI:` payload phone cameras understand and renders it here, so the
+//! passphrase never passes through a frontend QR library.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use qrcode::render::svg;
+use qrcode::{Color, EcLevel, QrCode};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+const MAX_SSID_BYTES: usize = 32;
+/// Pixels per QR module in the PNG
+const PNG_MODULE_PIXELS: usize = 8;
+/// Light modules around the code, as the QR specification asks for
+const QUIET_ZONE_MODULES: usize = 4;
+
+/// How the network is secured, as the `T:` field of the payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WifiSecurity {
+    /// WPA, WPA2 or WPA3 personal
+    Wpa,
+    Wep,
+    /// No passphrase
+    Open,
+}
+
+impl WifiSecurity {
+    fn payload_type(&self) -> &'static str {
+        match self {
+            WifiSecurity::Wpa => "WPA",
+            WifiSecurity::Wep => "WEP",
+            WifiSecurity::Open => "nopass",
+        }
+    }
+}
+
+/// Unencrypted details of a Wi-Fi item
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WifiDetails {
+    pub ssid: String,
+    pub security: WifiSecurity,
+    /// The network does not broadcast its SSID
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+/// A network as entered by the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WifiInput {
+    pub ssid: String,
+    pub security: WifiSecurity,
+    #[serde(default)]
+    pub password: String,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl WifiInput {
+    /// The details lists show. Rejects passphrases the security type does not allow.
+    pub fn validate(&self) -> AppResult<WifiDetails> {
+        if self.ssid.is_empty() || self.ssid.len() > MAX_SSID_BYTES {
+            return Err(AppError::InvalidInput(format!(
+                "An SSID must be 1 to {} bytes",
+                MAX_SSID_BYTES
+            )));
+        }
+        let password = self.password.as_str();
+        let is_hex = |len: usize| password.len() == len && is_hex(password);
+        let valid = match self.security {
+            // 8-63 printable characters, or the 64-digit hex key itself
+            WifiSecurity::Wpa => {
+                (8..=63).contains(&password.len()) && password.is_ascii() || is_hex(64)
+            }
+            // 40- or 104-bit keys, as ASCII or hex
+            WifiSecurity::Wep => {
+                [5, 13].contains(&password.len()) && password.is_ascii() || is_hex(10) || is_hex(26)
+            }
+            WifiSecurity::Open => password.is_empty(),
+        };
+        if !valid {
+            let message = match self.security {
+                WifiSecurity::Wpa => "A WPA passphrase must be 8 to 63 characters",
+                WifiSecurity::Wep => "A WEP key must be 5 or 13 characters, or 10 or 26 hex digits",
+                WifiSecurity::Open => "An open network has no passphrase",
+            };
+            return Err(AppError::InvalidInput(message.to_string()));
+        }
+        Ok(WifiDetails {
+            ssid: self.ssid.clone(),
+            security: self.security,
+            hidden: self.hidden,
+        })
+    }
+}
+
+/// A network's QR code, for guests to scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WifiQr {
+    pub ssid: String,
+    /// Scales to any size
+    pub svg: String,
+    /// Base64 PNG, for saving or printing
+    pub png: String,
+}
+
+fn is_hex(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Escapes a payload field; values that look like hex are quoted so scanners
+/// do not read them as hex-encoded bytes
+fn escape_field(value: &str) -> String {
+    let escaped: String = value
+        .chars()
+        .flat_map(|c| match c {
+            '\\' | ';' | ',' | ':' | '"' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect();
+    if is_hex(value) {
+        format!("\"{}\"", escaped)
+    } else {
+        escaped
+    }
+}
+
+/// The `WIFI:` payload, e.g. `WIFI:T:WPA;S:home;P:secret;;`
+pub fn payload(details: &WifiDetails, password: &str) -> String {
+    let mut payload = format!(
+        "WIFI:T:{};S:{};",
+        details.security.payload_type(),
+        escape_field(&details.ssid)
+    );
+    if details.security != WifiSecurity::Open {
+        payload.push_str(&format!("P:{};", escape_field(password)));
+    }
+    if details.hidden {
+        payload.push_str("H:true;");
+    }
+    payload.push(';');
+    payload
+}
+
+/// Grayscale PNG of the code's modules
+fn render_png(code: &QrCode) -> AppResult<Vec<u8>> {
+    let modules = code.width();
+    let colors = code.to_colors();
+    let side = (modules + 2 * QUIET_ZONE_MODULES) * PNG_MODULE_PIXELS;
+    let mut pixels = vec![255u8; side * side];
+    for (index, color) in colors.iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+        let x = (index % modules + QUIET_ZONE_MODULES) * PNG_MODULE_PIXELS;
+        let y = (index / modules + QUIET_ZONE_MODULES) * PNG_MODULE_PIXELS;
+        for row in y..y + PNG_MODULE_PIXELS {
+            pixels[row * side + x..row * side + x + PNG_MODULE_PIXELS].fill(0);
+        }
+    }
+
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, side as u32, side as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let to_error =
+        |e: png::EncodingError| AppError::Other(format!("Failed to encode QR code: {}", e));
+    let mut writer = encoder.write_header().map_err(to_error)?;
+    writer.write_image_data(&pixels).map_err(to_error)?;
+    writer.finish().map_err(to_error)?;
+    Ok(bytes)
+}
+
+/// Renders `payload` as a QR code, returning the SVG and the base64 PNG
+pub fn render_qr(payload: &str) -> AppResult<(String, String)> {
+    let code = QrCode::with_error_correction_level(payload, EcLevel::M)
+        .map_err(|e| AppError::InvalidInput(format!("Failed to encode QR code: {}", e)))?;
+    let svg = code.render::<svg::Color>().min_dimensions(256, 256).build();
+    Ok((svg, BASE64.encode(render_png(&code)?)))
+}
+
+/// Renders the network's payload as SVG and PNG
+pub fn qr_code(details: &WifiDetails, password: &str) -> AppResult<WifiQr> {
+    let (svg, png) = render_qr(&payload(details, password))?;
+    Ok(WifiQr {
+        ssid: details.ssid.clone(),
+        svg,
+        png,
+    })
+}
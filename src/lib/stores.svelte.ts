@@ -1,5 +1,67 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { BreachState, Credential, Secret } from './types';
+import type {
+	ApiKeyInput,
+	ApiKeyPreset,
+	AppStatus,
+	AutofillProfile,
+	AutotypeRequest,
+	BreachCheckResult,
+	BreachListImport,
+	BreachListSource,
+	BreachNotification,
+	BulkEdit,
+	BulkSession,
+	CardInput,
+	CardNumberCheck,
+	CommandError,
+	ConflictResolution,
+	Credential,
+	CredentialQr,
+	CredentialSummary,
+	DataLocation,
+	DeepLinkRequest,
+	DetectedSecret,
+	DiagnosticsReport,
+	DomainInfo,
+	DownloadedUpdate,
+	Feature,
+	FeatureState,
+	GeneratorConstraints,
+	GeneratorOptions,
+	HardeningStatus,
+	IdentityInput,
+	ImportConflict,
+	LocalBreachMatch,
+	LogEntry,
+	LogLevel,
+	MaintenanceReport,
+	MasterPasswordReminder,
+	MatchMode,
+	NonceAuditReport,
+	Notification,
+	OperationSummary,
+	PendingSave,
+	QuickSearchHit,
+	RotationItem,
+	RotationStatus,
+	SavedView,
+	Secret,
+	SecretField,
+	SettingsProfileImport,
+	SmartFilters,
+	TagSuggestion,
+	TotpValidation,
+	UndoStatus,
+	UpdateCheck,
+	UrlMatch,
+	VaultSetup,
+	VaultSetupOptions,
+	VaultStatistics,
+	VaultStatus,
+	ViewConfig,
+	WifiInput,
+	WifiQr
+} from './types';
 
 // App state management
 // Following proper Svelte 5 patterns for sharing state between modules
@@ -13,15 +75,17 @@ export const vault = $state({
 
 // Current credentials list
 export const credentialStore = $state({
-	items: [] as Credential[]
+	items: [] as CredentialSummary[]
 });
 
 // Search and filter state
 export const filters = $state({
-	searchTerm: '',
+	searchTerm: '', // free text and filters such as `tag:work strength:<50`, see the backend's `query` module
 	selectedTag: null as string | null,
 	minStrength: null as number | null,
-	breachFilter: null as number | null
+	breachFilter: null as number | null,
+	matchMode: 'substring' as MatchMode,
+	smart: {} as SmartFilters
 });
 
 // Loading state
@@ -53,7 +117,7 @@ export async function initializeApp() {
 
 		return vaultExists;
 	} catch (error) {
-		setError(`Failed to initialize app: ${error}`);
+		setError(`Failed to initialize app: ${errorText(error)}`);
 		return false;
 	} finally {
 		ui.isLoading = false;
@@ -72,20 +136,120 @@ export async function createVault(masterPassword: string) {
 
 		return true;
 	} catch (error) {
-		setError(`Failed to create vault: ${error}`);
+		setError(`Failed to create vault: ${errorText(error)}`);
 		return false;
 	} finally {
 		ui.isLoading = false;
 	}
 }
 
-// Unlock the vault
-export async function unlockVault(masterPassword: string) {
+// Whether the vault has been created and is unlocked
+export async function getVaultStatus() {
+	try {
+		return await invoke<VaultStatus>('get_vault_status');
+	} catch (error) {
+		setError(`Failed to get vault status: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Version, vault status, features, unread notifications and last backup and sync in one call
+export async function getAppStatus() {
+	try {
+		return await invoke<AppStatus>('get_app_status');
+	} catch (error) {
+		setError(`Failed to get app status: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Where the vault is kept; relative backup and export paths are taken from there
+export async function getDataLocation() {
+	try {
+		return await invoke<DataLocation>('get_data_location');
+	} catch (error) {
+		setError(`Failed to get data location: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Which memory protections are in effect
+export async function getHardeningStatus() {
+	try {
+		return await invoke<HardeningStatus>('get_hardening_status');
+	} catch (error) {
+		setError(`Failed to get hardening status: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Whether the vault key is held by a separate process that ends on lock
+export async function inEphemeralSession() {
+	try {
+		return await invoke<boolean>('in_ephemeral_session');
+	} catch (error) {
+		setError(`Failed to get session mode: ${errorText(error)}`);
+		return false;
+	}
+}
+
+// Write the settings, without secrets, to a file for another machine
+export async function exportSettingsProfile(path: string) {
+	try {
+		await invoke('export_settings_profile', { path });
+		return true;
+	} catch (error) {
+		setError(`Failed to export settings profile: ${errorText(error)}`);
+		return false;
+	}
+}
+
+// Apply a settings profile; if `kdf_differs` comes back, confirm and call again with includeKdf
+export async function importSettingsProfile(path: string, includeKdf = false) {
+	try {
+		return await invoke<SettingsProfileImport>('import_settings_profile', { path, includeKdf });
+	} catch (error) {
+		setError(`Failed to import settings profile: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Create the vault from the setup wizard; returns the recovery kit and KDF parameters
+export async function createVaultWithOptions(
+	masterPassword: string,
+	confirm: string,
+	options: VaultSetupOptions
+) {
+	try {
+		ui.isLoading = true;
+		ui.loadingMessage = 'Creating vault...';
+
+		const setup = await invoke<VaultSetup>('create_vault_v2', {
+			masterPassword,
+			confirm,
+			options
+		});
+		vault.isLocked = false;
+		vault.isInitialized = true;
+
+		return setup;
+	} catch (error) {
+		setError(`Failed to create vault: ${errorText(error)}`);
+		return null;
+	} finally {
+		ui.isLoading = false;
+	}
+}
+
+// Unlock the vault; `unlock-progress` events report the key derivation.
+// `skipRecovery` opens the vault without recovering interrupted operations,
+// which is only offered after recovery failed.
+export async function unlockVault(masterPassword: string, skipRecovery = false): Promise<boolean> {
 	try {
 		ui.isLoading = true;
 		ui.loadingMessage = 'Unlocking vault...';
 
-		const success = await invoke<boolean>('unlock_vault', { masterPassword });
+		const success = await invoke<boolean>('unlock_vault', { masterPassword, skipRecovery });
 
 		if (success) {
 			vault.isLocked = false;
@@ -96,13 +260,47 @@ export async function unlockVault(masterPassword: string) {
 			return false;
 		}
 	} catch (error) {
-		setError(`Failed to unlock vault: ${error}`);
+		const cause = (error as CommandError)?.cause?.key;
+		if (
+			cause === 'error.recovery_failed' &&
+			!skipRecovery &&
+			window.confirm(
+				`${errorText(error)}\n\nOpen the vault anyway? Items from the interrupted operation may be missing or incomplete until recovery succeeds.`
+			)
+		) {
+			return unlockVault(masterPassword, true);
+		}
+		// Cancelled with `cancelUnlock`; nothing went wrong
+		if (cause !== 'error.cancelled') {
+			setError(`Failed to unlock vault: ${errorText(error)}`);
+		}
 		return false;
 	} finally {
 		ui.isLoading = false;
 	}
 }
 
+// Abandon an unlock whose key derivation is still running
+export async function cancelUnlock() {
+	await invoke('cancel_unlock').catch(() => {});
+}
+
+// Report input in the app window; auto-lock falls back to this where the OS
+// cannot report idle time
+export async function recordActivity() {
+	await invoke('record_activity').catch(() => {});
+}
+
+// Whether changing the master password is suggested; also sent as `master-password-reminder`
+export async function getMasterPasswordReminder() {
+	try {
+		return await invoke<MasterPasswordReminder | null>('get_master_password_reminder');
+	} catch (error) {
+		setError(`Failed to check the master password: ${errorText(error)}`);
+		return null;
+	}
+}
+
 // Lock the vault
 export async function lockVault() {
 	try {
@@ -115,50 +313,285 @@ export async function lockVault() {
 
 		return true;
 	} catch (error) {
-		setError(`Failed to lock vault: ${error}`);
+		setError(`Failed to lock vault: ${errorText(error)}`);
 		return false;
 	} finally {
 		ui.isLoading = false;
 	}
 }
 
+// Text the app last put on the clipboard, so only that is ever cleared
+let copiedText: string | null = null;
+
+export async function copyText(text: string) {
+	await navigator.clipboard.writeText(text);
+	copiedText = text;
+}
+
+// Clears the clipboard if it still holds what the app copied. If it cannot be
+// read, it is cleared anyway; losing a foreign clipboard beats leaking a secret.
+export async function clearCopiedText() {
+	if (copiedText === null) return;
+	try {
+		const current = await navigator.clipboard.readText().catch(() => copiedText);
+		if (current === copiedText) await navigator.clipboard.writeText('');
+	} catch (error) {
+		console.error('Failed to clear clipboard:', error);
+	} finally {
+		copiedText = null;
+	}
+}
+
+// Handler for the `vault-panic-locked` event, which every window receives
+export async function onPanicLocked() {
+	vault.isLocked = true;
+	credentialStore.items = [];
+	await clearCopiedText();
+}
+
+// For the panic hotkey: locks without a loading state and clears the clipboard
+export async function panicLock() {
+	try {
+		await invoke('panic_lock');
+		return true;
+	} catch (error) {
+		setError(`Failed to panic lock: ${errorText(error)}`);
+		return false;
+	} finally {
+		await onPanicLocked();
+	}
+}
+
 // Load credentials with optional filters
 export async function loadCredentials() {
 	try {
 		ui.isLoading = true;
 		ui.loadingMessage = 'Loading credentials...';
 
-		credentialStore.items = await invoke<Credential[]>('search_credentials', {
+		credentialStore.items = await invoke<CredentialSummary[]>('search_credentials', {
 			searchTerm: filters.searchTerm || undefined,
 			tag: filters.selectedTag || undefined,
 			minStrength: filters.minStrength || undefined,
-			breachState: filters.breachFilter
+			breachState: filters.breachFilter,
+			matchMode: filters.matchMode,
+			smart: filters.smart
+		});
+
+		return credentialStore.items;
+	} catch (error) {
+		setError(`Failed to load credentials: ${errorText(error)}`);
+		return [];
+	} finally {
+		ui.isLoading = false;
+	}
+}
+
+// Saved views, ordered by name
+export async function listViews() {
+	try {
+		return await invoke<SavedView[]>('list_views');
+	} catch (error) {
+		setError(`Failed to load views: ${errorText(error)}`);
+		return [];
+	}
+}
+
+// Saves a view, replacing one with the same name
+export async function saveView(name: string, config: ViewConfig) {
+	try {
+		return await invoke<SavedView>('save_view', { name, config });
+	} catch (error) {
+		setError(`Failed to save view: ${errorText(error)}`);
+		return null;
+	}
+}
+
+export async function deleteView(name: string) {
+	try {
+		await invoke('delete_view', { name });
+		return true;
+	} catch (error) {
+		setError(`Failed to delete view: ${errorText(error)}`);
+		return false;
+	}
+}
+
+// Load the credentials of a saved view, filtered and sorted by the backend
+export async function loadCredentialsForView(name: string) {
+	try {
+		ui.isLoading = true;
+		ui.loadingMessage = 'Loading credentials...';
+
+		credentialStore.items = await invoke<CredentialSummary[]>('list_credentials_for_view', {
+			name
 		});
 
 		return credentialStore.items;
 	} catch (error) {
-		setError(`Failed to load credentials: ${error}`);
+		setError(`Failed to load credentials: ${errorText(error)}`);
 		return [];
 	} finally {
 		ui.isLoading = false;
 	}
 }
 
-// Get a credential's secret
-export async function getCredentialSecret(uuid: string): Promise<Secret | null> {
+// Get a credential's secret, or only `fields` of it; high-security items need the master password
+export async function getCredentialSecret(
+	uuid: string,
+	masterPassword?: string,
+	fields?: SecretField[]
+): Promise<Secret | null> {
 	try {
 		ui.isLoading = true;
 		ui.loadingMessage = 'Loading secret...';
 
-		return await invoke<Secret>('get_credential_secret', { uuid });
+		return await invoke<Secret>('get_credential_secret', { uuid, masterPassword, fields });
 	} catch (error) {
-		setError(`Failed to get credential secret: ${error}`);
+		setError(`Failed to get credential secret: ${errorText(error)}`);
 		return null;
 	} finally {
 		ui.isLoading = false;
 	}
 }
 
+// Prefix search for the quick search overlay; cheap enough to run on every keystroke
+export async function quickSearch(term: string, limit?: number) {
+	try {
+		return await invoke<QuickSearchHit[]>('quick_search', { term, limit });
+	} catch (error) {
+		setError(`Quick search failed: ${errorText(error)}`);
+		return [];
+	}
+}
+
+// Runs every maintenance task now instead of waiting for the user to be idle
+export async function runMaintenanceNow() {
+	try {
+		return await invoke<MaintenanceReport>('run_maintenance_now');
+	} catch (error) {
+		setError(`Maintenance failed: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Password rules and the change-password page known for a site or URL
+export async function getDomainInfo(domain: string) {
+	try {
+		return await invoke<DomainInfo>('get_domain_info', { domain });
+	} catch (error) {
+		setError(`Failed to look up ${domain}: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Tags in use that start with `prefix`, the most used first
+export async function suggestTags(prefix: string, limit?: number) {
+	try {
+		return await invoke<TagSuggestion[]>('suggest_tags', { prefix, limit });
+	} catch (error) {
+		setError(`Failed to suggest tags: ${errorText(error)}`);
+		return [];
+	}
+}
+
+// Credentials saved for the site of `url`, or a warning if the URL imitates one
+export async function findCredentialsForUrl(url: string) {
+	try {
+		return await invoke<UrlMatch>('find_credentials_for_url', { url });
+	} catch (error) {
+		setError(`Failed to find credentials for this page: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Logins captured in the browser, newest first
+export async function listPendingSaves() {
+	try {
+		return await invoke<PendingSave[]>('list_pending_saves');
+	} catch (error) {
+		setError(`Failed to list logins waiting to be saved: ${errorText(error)}`);
+		return [];
+	}
+}
+
+// Save a captured login (or discard it) and refresh the list
+export async function answerPendingSave(id: string, approve: boolean) {
+	try {
+		if (approve) {
+			await invoke<Credential>('approve_pending_save', { id });
+			await loadCredentials();
+		} else {
+			await invoke('discard_pending_save', { id });
+		}
+		return true;
+	} catch (error) {
+		setError(`Failed to save the captured login: ${errorText(error)}`);
+		return false;
+	}
+}
+
+// Links waiting for confirmation; needs the vault unlocked
+export async function listPendingDeepLinks() {
+	try {
+		return await invoke<DeepLinkRequest[]>('list_pending_deep_links');
+	} catch (error) {
+		setError(`Failed to list received links: ${errorText(error)}`);
+		return [];
+	}
+}
+
+// Act on a link (a TOTP link goes to `targetUuid`, or a new item) or dismiss it.
+// Returns the item to open.
+export async function answerDeepLink(id: string, accept: boolean, targetUuid: string | null = null) {
+	try {
+		if (!accept) {
+			await invoke('reject_deep_link', { id });
+			return null;
+		}
+		const credential = await invoke<Credential>('accept_deep_link', { id, targetUuid });
+		await loadCredentials();
+		return credential;
+	} catch (error) {
+		setError(`Failed to open the link: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Find items for the focused window; the answer also arrives as `autotype-requested`
+export async function prepareAutotype() {
+	try {
+		return await invoke<AutotypeRequest>('prepare_autotype');
+	} catch (error) {
+		setError(`Auto-type failed: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Type item `uuid` into the window of request `requestId`, or cancel when uuid is null
+export async function answerAutotype(requestId: string, uuid: string | null) {
+	try {
+		if (uuid === null) {
+			await invoke('cancel_autotype');
+		} else {
+			await invoke('confirm_autotype', { requestId, uuid });
+		}
+		return true;
+	} catch (error) {
+		setError(`Auto-type failed: ${errorText(error)}`);
+		return false;
+	}
+}
+
+// Set the keys auto-type sends for an item; null restores the default sequence
+export async function setAutotypeSequence(uuid: string, sequence: string | null) {
+	try {
+		return await invoke<Credential>('set_autotype_sequence', { uuid, sequence });
+	} catch (error) {
+		setError(`Failed to set auto-type sequence: ${errorText(error)}`);
+		return null;
+	}
+}
+
 // Add a new credential
 export async function addCredential(
 	site: string,
@@ -167,7 +600,9 @@ export async function addCredential(
 	notes?: string,
 	totp?: string,
 	customFields?: Record<string, string>,
-	tags?: string[]
+	tags?: string[],
+	// Set after the user confirmed an `error.password_too_weak` / `error.password_compromised` refusal
+	saveAnyway?: boolean
 ) {
 	try {
 		ui.isLoading = true;
@@ -180,7 +615,8 @@ export async function addCredential(
 			notes,
 			totp,
 			customFields: customFields ? JSON.stringify(customFields) : undefined,
-			tags
+			tags,
+			saveAnyway
 		});
 
 		// Refresh the credentials list
@@ -188,77 +624,661 @@ export async function addCredential(
 
 		return newCredential;
 	} catch (error) {
-		setError(`Failed to add credential: ${error}`);
+		setError(`Failed to add credential: ${errorText(error)}`);
 		return null;
 	} finally {
 		ui.isLoading = false;
 	}
 }
 
-// Delete a credential
-export async function deleteCredential(uuid: string) {
+// Providers the API key form offers, with their sites, labels and scopes
+export async function getApiKeyPresets() {
 	try {
-		ui.isLoading = true;
-		ui.loadingMessage = 'Deleting credential...';
-
-		await invoke('delete_credential', { uuid });
+		return await invoke<ApiKeyPreset[]>('get_api_key_presets');
+	} catch (error) {
+		setError(`Failed to load API key presets: ${errorText(error)}`);
+		return [];
+	}
+}
 
-		// Refresh the credentials list
+// Add an API key item, or replace one when `uuid` is given
+export async function saveApiKey(key: ApiKeyInput, uuid?: string) {
+	try {
+		const credential = uuid
+			? await invoke<Credential>('update_api_key', { uuid, key })
+			: await invoke<Credential>('add_api_key', { key });
 		await loadCredentials();
-
-		return true;
+		return credential;
 	} catch (error) {
-		setError(`Failed to delete credential: ${error}`);
-		return false;
-	} finally {
-		ui.isLoading = false;
+		setError(`Failed to save API key: ${errorText(error)}`);
+		return null;
 	}
 }
 
-// Check if a password has been breached
-export async function checkPasswordBreach(uuid: string) {
+// Brand and validity of a card number, checked in the backend as it is typed
+export async function checkCardNumber(number: string) {
 	try {
-		ui.isLoading = true;
-		ui.loadingMessage = 'Checking for breaches...';
-
-		const breachState = await invoke<BreachState>('check_password_breach', { uuid });
+		return await invoke<CardNumberCheck>('check_card_number', { number });
+	} catch {
+		return null; // not a number yet; the form shows its own hint
+	}
+}
 
-		// Refresh the credentials list to get updated breach state
+// Add a card, or replace one when `uuid` is given
+export async function saveCard(card: CardInput, uuid?: string) {
+	try {
+		const credential = uuid
+			? await invoke<Credential>('update_card', { uuid, card })
+			: await invoke<Credential>('add_card', { card });
 		await loadCredentials();
-
-		return breachState;
+		return credential;
 	} catch (error) {
-		setError(`Failed to check password breach: ${error}`);
+		setError(`Failed to save card: ${errorText(error)}`);
 		return null;
-	} finally {
-		ui.isLoading = false;
 	}
 }
 
-// Generate a password
-export async function generatePassword(
-	length: number = 16,
-	useUppercase: boolean = true,
-	useLowercase: boolean = true,
-	useNumbers: boolean = true,
-	useSymbols: boolean = true,
-	excludeSimilar: boolean = false
-) {
+// Add an identity, or replace one when `uuid` is given
+export async function saveIdentity(identity: IdentityInput, uuid?: string) {
 	try {
-		return await invoke<string>('generate_password', {
-			length,
-			useUppercase,
-			useLowercase,
-			useNumbers,
-			useSymbols,
-			excludeSimilar
-		});
+		const credential = uuid
+			? await invoke<Credential>('update_identity', { uuid, identity })
+			: await invoke<Credential>('add_identity', { identity });
+		await loadCredentials();
+		return credential;
 	} catch (error) {
-		setError(`Failed to generate password: ${error}`);
+		setError(`Failed to save identity: ${errorText(error)}`);
 		return null;
 	}
 }
 
+// The normalized profile the browser extension fills forms with
+export async function exportAutofillProfile(uuid: string) {
+	try {
+		return await invoke<AutofillProfile>('export_autofill_profile', { uuid });
+	} catch (error) {
+		setError(`Failed to export autofill profile: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Add a Wi-Fi network, or replace one when `uuid` is given
+export async function saveWifi(wifi: WifiInput, uuid?: string) {
+	try {
+		const credential = uuid
+			? await invoke<Credential>('update_wifi', { uuid, wifi })
+			: await invoke<Credential>('add_wifi', { wifi });
+		await loadCredentials();
+		return credential;
+	} catch (error) {
+		setError(`Failed to save Wi-Fi network: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// The network's QR code for guests to scan, rendered by the backend
+export async function generateWifiQr(uuid: string) {
+	try {
+		return await invoke<WifiQr>('generate_wifi_qr', { uuid });
+	} catch (error) {
+		setError(`Failed to create QR code: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// One item as a QR code for another device, encrypted under a PIN of 10+
+// characters that is hard to guess
+export async function exportCredentialQr(uuid: string, pin: string) {
+	try {
+		return await invoke<CredentialQr>('export_credential_qr', { uuid, pin });
+	} catch (error) {
+		setError(`Failed to create transfer code: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Adds the item from a scanned or pasted transfer code
+export async function importCredentialQr(payload: string, pin: string) {
+	try {
+		const credential = await invoke<Credential>('import_credential_qr', { payload, pin });
+		await loadCredentials();
+		return credential;
+	} catch (error) {
+		setError(`Failed to import transfer code: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Replaces an item's recovery codes with those pasted from the site
+export async function setRecoveryCodes(uuid: string, codes: string) {
+	try {
+		return await invoke<Credential>('set_recovery_codes', { uuid, codes });
+	} catch (error) {
+		setError(`Failed to save recovery codes: ${errorText(error)}`);
+		return null;
+	}
+}
+
+export async function markRecoveryCodeUsed(uuid: string, index: number) {
+	try {
+		return await invoke<Credential>('mark_recovery_code_used', { uuid, index });
+	} catch (error) {
+		setError(`Failed to mark recovery code as used: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Fresh numbers for the dashboard; later changes arrive as `vault-stats-changed`
+export async function getVaultStatistics() {
+	try {
+		return await invoke<VaultStatistics>('get_vault_statistics');
+	} catch (error) {
+		setError(`Failed to load vault statistics: ${errorText(error)}`);
+		return null;
+	}
+}
+
+export async function listFeatures() {
+	try {
+		return await invoke<FeatureState[]>('list_features');
+	} catch (error) {
+		setError(`Failed to load features: ${errorText(error)}`);
+		return [];
+	}
+}
+
+export async function setFeature(name: Feature, enabled: boolean) {
+	try {
+		return await invoke<FeatureState>('set_feature', { name, enabled });
+	} catch (error) {
+		setError(`Failed to change feature: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Stops or resumes every network feature at once, including work under way
+export async function setOfflineMode(offline: boolean) {
+	try {
+		await invoke('set_offline_mode', { offline });
+		return true;
+	} catch (error) {
+		setError(`Failed to save offline mode: ${errorText(error)}`);
+		return false;
+	}
+}
+
+// Whether a newer version is available; respects the offline switch and update permission
+export async function checkForUpdates() {
+	try {
+		return await invoke<UpdateCheck>('check_for_updates');
+	} catch (error) {
+		setError(`Failed to check for updates: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Downloads and verifies the latest version; progress arrives as `update-download-progress` events
+export async function downloadUpdate() {
+	try {
+		return await invoke<DownloadedUpdate>('download_update');
+	} catch (error) {
+		setError(`Failed to download update: ${errorText(error)}`);
+		return null;
+	}
+}
+
+export async function listImportConflicts() {
+	try {
+		return await invoke<ImportConflict[]>('list_import_conflicts');
+	} catch (error) {
+		setError(`Failed to load import conflicts: ${errorText(error)}`);
+		return [];
+	}
+}
+
+// Returns the item written, null for 'keep_existing'
+export async function resolveImportConflict(id: number, resolution: ConflictResolution) {
+	try {
+		const written = await invoke<Credential | null>('resolve_import_conflict', {
+			id,
+			resolution
+		});
+		await loadCredentials();
+		return written;
+	} catch (error) {
+		setError(`Failed to resolve import conflict: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Items expiring within `withinDays` (default: the reminder setting) or already expired.
+// The scheduler also sends them once a day as `credentials-expiring`.
+export async function getExpiringCredentials(withinDays?: number) {
+	try {
+		return await invoke<CredentialSummary[]>('get_expiring_credentials', { withinDays });
+	} catch (error) {
+		setError(`Failed to list expiring items: ${errorText(error)}`);
+		return [];
+	}
+}
+
+// Find API keys, database URLs and secret variables in pasted .env, JSON or YAML text
+export async function scanPastedSecrets(text: string) {
+	try {
+		return await invoke<DetectedSecret[]>('scan_pasted_secrets', { text });
+	} catch (error) {
+		setError(`Failed to scan the pasted text: ${errorText(error)}`);
+		return [];
+	}
+}
+
+// Save the chosen proposals of `scanPastedSecrets` on the same text as API key items
+export async function savePastedSecrets(text: string, ids: number[], tags?: string[]) {
+	try {
+		const saved = await invoke<CredentialSummary[]>('save_pasted_secrets', { text, ids, tags });
+		await loadCredentials();
+		return saved;
+	} catch (error) {
+		setError(`Failed to save the secrets: ${errorText(error)}`);
+		return [];
+	}
+}
+
+// Start rescoring every stored password in the background; false if a run is already going.
+// Reload credentials on `strength-recalc-complete` to see the new scores.
+export async function recalculateAllStrengths() {
+	try {
+		return await invoke<boolean>('recalculate_all_strengths');
+	} catch (error) {
+		setError(`Failed to recalculate password strengths: ${errorText(error)}`);
+		return false;
+	}
+}
+
+// Recent diagnostic logs, newest first; `minLevel` keeps that level and anything more severe
+export async function getRecentLogs(limit?: number, minLevel?: LogLevel) {
+	try {
+		return await invoke<LogEntry[]>('get_recent_logs', { limit, minLevel });
+	} catch (error) {
+		setError(`Failed to load logs: ${errorText(error)}`);
+		return [];
+	}
+}
+
+// Write a zip of versions, sanitized settings, database health and recent logs for a bug report
+export async function createDiagnosticsBundle(path: string) {
+	try {
+		return await invoke<DiagnosticsReport>('create_diagnostics_bundle', { path });
+	} catch (error) {
+		setError(`Failed to create diagnostics bundle: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Look for nonces used twice with one key, which would mean the RNG has failed
+export async function verifyNoNonceReuse() {
+	try {
+		return await invoke<NonceAuditReport>('verify_no_nonce_reuse');
+	} catch (error) {
+		setError(`Failed to check for nonce reuse: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Check a code from the user's authenticator against an item's stored TOTP secret
+export async function validateTotpInput(uuid: string, code: string) {
+	try {
+		return await invoke<TotpValidation>('validate_totp_input', { uuid, code });
+	} catch (error) {
+		setError(`Failed to check TOTP code: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Start rotating every password with `tag` (all items when omitted); needs the master password
+export async function startRotation(
+	masterPassword: string,
+	tag?: string,
+	options?: GeneratorOptions
+) {
+	try {
+		return await invoke<RotationStatus>('start_rotation', { masterPassword, tag, options });
+	} catch (error) {
+		setError(`Failed to start rotation: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// The item to rotate now, or null when the session is done
+export async function nextRotationItem() {
+	try {
+		return await invoke<RotationItem | null>('next_rotation_item');
+	} catch (error) {
+		setError(`Password rotation failed: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Save the new password once it was changed on the website, or skip the item for now
+export async function answerRotationItem(uuid: string, rotated: boolean) {
+	try {
+		return await invoke<RotationStatus>(rotated ? 'confirm_rotated' : 'skip_rotation_item', {
+			uuid
+		});
+	} catch (error) {
+		setError(`Password rotation failed: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Check the monitored email addresses now instead of waiting for the schedule
+export async function checkMonitoredEmails() {
+	try {
+		return await invoke<BreachNotification[]>('check_monitored_emails');
+	} catch (error) {
+		setError(`Failed to check monitored email addresses: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Breach notifications, newest first
+export async function listBreachNotifications(includeDismissed = false) {
+	try {
+		return await invoke<BreachNotification[]>('list_breach_notifications', { includeDismissed });
+	} catch (error) {
+		setError(`Failed to list breach notifications: ${errorText(error)}`);
+		return [];
+	}
+}
+
+export async function dismissBreachNotification(id: string) {
+	try {
+		return await invoke<BreachNotification>('dismiss_breach_notification', { id });
+	} catch (error) {
+		setError(`Failed to dismiss breach notification: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Backend notifications, newest first, including those not shown
+export async function listNotifications() {
+	try {
+		return await invoke<Notification[]>('list_notifications');
+	} catch (error) {
+		setError(`Failed to list notifications: ${errorText(error)}`);
+		return [];
+	}
+}
+
+export async function markNotificationsRead() {
+	try {
+		await invoke('mark_notifications_read');
+		return true;
+	} catch (error) {
+		setError(`Failed to mark notifications as read: ${errorText(error)}`);
+		return false;
+	}
+}
+
+export async function clearNotifications() {
+	try {
+		await invoke('clear_notifications');
+		return true;
+	} catch (error) {
+		setError(`Failed to clear notifications: ${errorText(error)}`);
+		return false;
+	}
+}
+
+// Add a credential with a password generated in the backend; the password is never sent here
+export async function addGeneratedCredential(
+	site: string,
+	username: string,
+	generatorOptions: GeneratorOptions,
+	tags?: string[]
+) {
+	try {
+		ui.isLoading = true;
+		ui.loadingMessage = 'Adding credential...';
+
+		const newCredential = await invoke<Credential>('add_generated_credential', {
+			site,
+			username,
+			generatorOptions,
+			tags
+		});
+
+		await loadCredentials();
+
+		return newCredential;
+	} catch (error) {
+		setError(`Failed to add credential: ${errorText(error)}`);
+		return null;
+	} finally {
+		ui.isLoading = false;
+	}
+}
+
+// Delete a credential
+export async function deleteCredential(uuid: string) {
+	try {
+		ui.isLoading = true;
+		ui.loadingMessage = 'Deleting credential...';
+
+		await invoke('delete_credential', { uuid });
+
+		// Refresh the credentials list
+		await loadCredentials();
+
+		return true;
+	} catch (error) {
+		setError(`Failed to delete credential: ${errorText(error)}`);
+		return false;
+	} finally {
+		ui.isLoading = false;
+	}
+}
+
+export async function bulkEdit(uuids: string[], edit: BulkEdit) {
+	try {
+		const edited = await invoke<CredentialSummary[]>('bulk_edit', { uuids, edit });
+		await loadCredentials();
+		return edited;
+	} catch (error) {
+		setError(`Failed to edit items: ${errorText(error)}`);
+		return null;
+	}
+}
+
+export async function renameTag(oldTag: string, newTag: string) {
+	try {
+		const edited = await invoke<CredentialSummary[]>('rename_tag', { old: oldTag, new: newTag });
+		await loadCredentials();
+		return edited;
+	} catch (error) {
+		setError(`Failed to rename tag: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Reverts the latest delete, bulk edit or tag rename; null if there was nothing to undo
+export async function undoLastOperation() {
+	try {
+		const undone = await invoke<OperationSummary | null>('undo_last_operation');
+		await loadCredentials();
+		return undone;
+	} catch (error) {
+		setError(`Failed to undo: ${errorText(error)}`);
+		return null;
+	}
+}
+
+export async function redoOperation() {
+	try {
+		const redone = await invoke<OperationSummary | null>('redo');
+		await loadCredentials();
+		return redone;
+	} catch (error) {
+		setError(`Failed to redo: ${errorText(error)}`);
+		return null;
+	}
+}
+
+export async function getUndoStatus() {
+	try {
+		return await invoke<UndoStatus>('get_undo_status');
+	} catch (error) {
+		setError(`Failed to load undo history: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Check if a password has been breached
+export async function checkPasswordBreach(uuid: string) {
+	try {
+		ui.isLoading = true;
+		ui.loadingMessage = 'Checking for breaches...';
+
+		const result = await invoke<BreachCheckResult>('check_password_breach', { uuid });
+
+		// Refresh the credentials list to get updated breach state
+		await loadCredentials();
+
+		return result;
+	} catch (error) {
+		setError(`Failed to check password breach: ${errorText(error)}`);
+		return null;
+	} finally {
+		ui.isLoading = false;
+	}
+}
+
+// Import a file of compromised passwords, one per line or as SHA-1 hashes
+export async function importBreachList(path: string, source: string) {
+	try {
+		return await invoke<BreachListImport>('import_breach_list', { path, source });
+	} catch (error) {
+		setError(`Failed to import breach list: ${errorText(error)}`);
+		return null;
+	}
+}
+
+export async function listBreachLists() {
+	try {
+		return await invoke<BreachListSource[]>('list_breach_lists');
+	} catch (error) {
+		setError(`Failed to load breach lists: ${errorText(error)}`);
+		return [];
+	}
+}
+
+export async function removeBreachList(source: string) {
+	try {
+		await invoke<number>('remove_breach_list', { source });
+		return true;
+	} catch (error) {
+		setError(`Failed to remove breach list: ${errorText(error)}`);
+		return false;
+	}
+}
+
+// Confirm with the master password before a report that reads every secret
+export async function beginBulkSession(purpose: string, masterPassword: string) {
+	try {
+		return await invoke<BulkSession>('begin_bulk_session', { purpose, masterPassword });
+	} catch (error) {
+		setError(`Failed to start bulk session: ${errorText(error)}`);
+		return null;
+	}
+}
+
+export async function endBulkSession() {
+	try {
+		await invoke('end_bulk_session');
+		return true;
+	} catch (error) {
+		setError(`Failed to end bulk session: ${errorText(error)}`);
+		return false;
+	}
+}
+
+// The open bulk session, or null once it has expired
+export async function getBulkSession() {
+	try {
+		return await invoke<BulkSession | null>('get_bulk_session');
+	} catch (error) {
+		setError(`Failed to load bulk session: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Check every password against the imported lists; matches are marked compromised
+export async function checkLocalBreaches() {
+	try {
+		const matches = await invoke<LocalBreachMatch[]>('check_local_breaches');
+		await loadCredentials();
+		return matches;
+	} catch (error) {
+		setError(`Failed to check imported breach lists: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Generate a password
+export async function generatePassword(
+	length: number = 16,
+	useUppercase: boolean = true,
+	useLowercase: boolean = true,
+	useNumbers: boolean = true,
+	useSymbols: boolean = true,
+	excludeSimilar: boolean = false
+) {
+	try {
+		return await invoke<string>('generate_password', {
+			length,
+			useUppercase,
+			useLowercase,
+			useNumbers,
+			useSymbols,
+			excludeSimilar
+		});
+	} catch (error) {
+		setError(`Failed to generate password: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Generate a new password for an item that meets the site's stored rules
+export async function generatePasswordFor(uuid: string, options?: GeneratorOptions) {
+	try {
+		return await invoke<string>('generate_password_for', { uuid, options });
+	} catch (error) {
+		setError(`Failed to generate password: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Store the site's password rules on an item, or clear them with null
+export async function setGeneratorConstraints(
+	uuid: string,
+	constraints: GeneratorConstraints | null
+) {
+	try {
+		return await invoke<Credential>('set_generator_constraints', { uuid, constraints });
+	} catch (error) {
+		setError(`Failed to save password rules: ${errorText(error)}`);
+		return null;
+	}
+}
+
+// Commands reject with a CommandError; fall back to String() for anything else
+export function errorText(error: unknown): string {
+	if (error && typeof error === 'object' && 'message' in error) {
+		return String((error as CommandError).message);
+	}
+	return String(error);
+}
+
 // Error handling helper
 function setError(message: string) {
 	ui.errorMessage = message;
@@ -5,18 +5,181 @@ export enum BreachState {
 	Compromised = 2
 }
 
-// Credential type definition matching Rust model
-export interface Credential {
+// Result of check_password_breach: the new state, or "offline" if HIBP was unreachable
+export type BreachCheckResult = { checked: 'Unknown' | 'Safe' | 'Compromised' } | 'offline';
+
+// Credential as returned by list commands such as `search_credentials`: no ciphertext
+export interface CredentialSummary {
 	uuid: string;
 	site: string;
 	username: string;
-	secret_enc: string;
 	tags: string[];
 	created_at: number;
 	updated_at: number;
 	expires_at: number | null;
-	strength: number;
+	strength: number; // 0-100, calibrated so zxcvbn scores 1-4 start at 20, 40, 60 and 80
+	strength_source?: 'Analyzed' | 'Generated'; // Generated = scored from exact entropy
+	entropy_bits?: number | null; // set for generated passwords
 	breach_state: BreachState;
+	breach_checked_at: number | null;
+	password_changed_at: number;
+	collection_id?: string | null; // shared collection, null = personal vault
+	high_security?: boolean; // decrypting needs the master password again
+	revision?: number; // bumped on every write; stale updates are rejected
+	generator_constraints?: GeneratorConstraints | null; // the site's password rules
+	kind?: 'Login' | 'ApiKey' | 'CreditCard' | 'Identity' | 'Wifi'; // ApiKey, CreditCard and Wifi items keep the key, number or passphrase in `password`; Identity items keep theirs in `Secret.identity`
+	api_key?: ApiKeyDetails | null; // set for ApiKey items
+	card?: CardDetails | null; // set for CreditCard items
+	wifi?: WifiDetails | null; // set for Wifi items
+	has_totp?: boolean; // the secret holds a TOTP seed
+	last_used_at?: number | null; // last reveal or auto-type, null = never
+}
+
+export type CardBrand =
+	| 'visa'
+	| 'mastercard'
+	| 'amex'
+	| 'discover'
+	| 'jcb'
+	| 'diners_club'
+	| 'union_pay'
+	| 'maestro'
+	| 'unknown';
+
+// Unencrypted details of a card; `expires_at` of the item is the end of the expiry month
+export interface CardDetails {
+	brand: CardBrand;
+	masked_number: string; // e.g. •••• 4242
+	expiry: { month: number; year: number };
+}
+
+export type WifiSecurity = 'wpa' | 'wep' | 'open'; // wpa covers WPA, WPA2 and WPA3 personal
+
+export interface WifiDetails {
+	ssid: string;
+	security: WifiSecurity;
+	hidden?: boolean; // the network does not broadcast its SSID
+}
+
+// A network as entered in the add/edit form; the passphrase is checked against the security type
+export interface WifiInput {
+	ssid: string;
+	security: WifiSecurity;
+	password?: string; // empty for open networks
+	hidden?: boolean;
+	notes?: string | null;
+	tags?: string[];
+}
+
+// Result of `generate_wifi_qr`
+export interface WifiQr {
+	ssid: string;
+	svg: string;
+	png: string; // base64, e.g. for `data:image/png;base64,...`
+}
+
+// Result of `export_credential_qr`: one item encrypted under a PIN
+export interface CredentialQr {
+	site: string;
+	payload: string; // the encoded text, for pasting when there is no camera
+	svg: string;
+	png: string; // base64
+	expires_at: string; // the code cannot be imported after this
+}
+
+export interface Address {
+	label?: string; // e.g. Home or Work
+	lines?: string[]; // street, building and unit
+	city?: string;
+	region?: string; // state, province or county
+	postal_code?: string;
+	country?: string; // a name or ISO 3166 code
+}
+
+// The canonical data of an identity item, stored encrypted; the first address,
+// phone and email are the ones forms are filled with
+export interface Identity {
+	title?: string;
+	first_name?: string;
+	middle_name?: string;
+	last_name?: string;
+	company?: string;
+	addresses?: Address[];
+	phones?: string[];
+	emails?: string[];
+}
+
+export interface IdentityInput {
+	name?: string | null; // defaults to the person's or company's name
+	identity: Identity;
+	notes?: string | null;
+	tags?: string[];
+}
+
+// What the browser extension fills forms with, keyed by HTML autocomplete tokens
+// (given-name, email, tel, address-line1, postal-code, country, ...)
+export interface AutofillProfile {
+	uuid: string;
+	name: string;
+	fields: Record<string, string>;
+	emails: string[];
+	phones: string[];
+	addresses: { label: string; fields: Record<string, string> }[];
+}
+
+// Result of `check_card_number`, for feedback while a number is typed
+export interface CardNumberCheck {
+	brand: CardBrand;
+	valid: boolean; // Luhn checksum and a length the brand issues
+	masked_number: string;
+}
+
+// A card as entered in the add/edit form; the number is validated in the backend
+export interface CardInput {
+	name?: string | null; // defaults to e.g. "Visa •••• 4242"
+	cardholder?: string;
+	number: string;
+	expiry_month: number;
+	expiry_year: number; // two-digit years are accepted
+	cvv?: string | null;
+	notes?: string | null;
+	tags?: string[];
+}
+
+// Unencrypted details of an API key item; the expiry is the item's `expires_at`
+export interface ApiKeyDetails {
+	provider: string; // a preset id or any other name
+	key_id?: string | null; // public half of the key, e.g. an AWS access key ID
+	scopes: string[];
+	secret_preview: string; // e.g. ••••9f3a
+}
+
+// An API key as entered in the add/edit form
+export interface ApiKeyInput {
+	provider: string;
+	site?: string | null; // defaults to the preset's site
+	username?: string;
+	key_id?: string | null;
+	secret: string;
+	scopes?: string[];
+	expires_at?: string | null;
+	notes?: string | null;
+	tags?: string[];
+}
+
+export interface ApiKeyPreset {
+	id: string;
+	name: string;
+	site: string;
+	key_id_label: string | null;
+	secret_label: string;
+	scopes: string[];
+	suggested_lifetime_days: number | null;
+}
+
+// Credential type definition matching Rust model, returned for a single item
+export interface Credential extends CredentialSummary {
+	secret_enc: string;
 }
 
 // Secret type definition matching Rust model
@@ -25,18 +188,682 @@ export interface Secret {
 	notes?: string;
 	totp?: string;
 	custom_fields: Record<string, string>;
+	password_history?: { password: string; replaced_at: string }[];
+	autotype_sequence?: string | null; // e.g. '{USERNAME}{TAB}{PASSWORD}{ENTER}', the default
+	identity?: Identity | null; // set for Identity items
+	recovery_codes?: RecoveryCode[]; // two-factor recovery codes, in the site's order
+}
+
+export interface RecoveryCode {
+	code: string;
+	used_at?: string | null; // when it was used; null while it is still good
+}
+
+// Passed as `fields` to `get_credential_secret`; fields not asked for come back empty
+export type SecretField =
+	| 'password'
+	| 'notes'
+	| 'totp'
+	| 'password_history'
+	| 'identity'
+	| 'recovery_codes'
+	| { custom_field: string };
+
+// Result of `validate_totp_input`; drift_steps is -1/1 when the code matched a neighbouring time step
+export interface TotpValidation {
+	valid: boolean;
+	drift_steps: number | null;
 }
 
 // App settings type definition
 export interface AppSettings {
 	theme: 'light' | 'dark' | 'system';
 	auto_lock_timeout: number; // minutes, 0 = never
-	password_gen_defaults: {
-		length: number;
-		use_uppercase: boolean;
-		use_lowercase: boolean;
-		use_numbers: boolean;
-		use_symbols: boolean;
-		exclude_similar: boolean;
+	idle_detection?: 'system' | 'app'; // what counts as activity for auto-lock
+	share_relay_url?: string | null; // one-time share relay, null = file shares only
+	update_manifest_url?: string | null; // null = the project's releases
+	network?: NetworkSettings;
+	breach_api?: {
+		base_url?: string | null;
+		pinned_certificates?: string[]; // hex SHA-256 of accepted server public keys (SPKI)
+	};
+	check_breach_on_save?: boolean; // results arrive as `breach-precheck-complete` events
+	log_level?: LogLevel;
+	audit_level?: AuditLevel;
+	email_monitor?: EmailMonitorSettings;
+	expiry_reminder_days?: number; // `credentials-expiring` is sent this many days ahead, 0 = off
+	min_password_score?: number; // 0-4; weaker new passwords are refused unless saved anyway, 0 = off
+	block_compromised_passwords?: boolean; // refuse passwords on an imported breach list
+	master_password_min_score?: number; // 0-4; a weaker master password is reminded of after unlocking
+	master_password_max_age_days?: number; // remind to change the master password after this long, 0 = never
+	performance_mode?: 'auto' | 'on' | 'off'; // large-vault optimizations; 'auto' from 5000 items
+	ephemeral_session?: boolean; // keep the vault key in a separate process ended on lock; turning it off applies at the next unlock
+	notifications?: NotificationSettings; // which desktop notifications are shown
+	password_gen_defaults: GeneratorOptions;
+}
+
+// Choices made in the vault creation wizard, see `create_vault_v2`
+export interface VaultSetupOptions {
+	kdf_target_ms?: number; // calibrate Argon2 to unlock in about this long
+	settings?: AppSettings;
+	user_inputs?: string[]; // username, email, ... the master password should not contain
+}
+
+export interface KdfCalibration {
+	memory_kb: number;
+	iterations: number;
+	parallelism: number;
+	estimated_ms: number;
+}
+
+export interface VaultSetup {
+	kdf: KdfCalibration | null;
+}
+
+// Decided by the stored master password hash, not by the database file
+export type VaultStatus = 'NotInitialized' | 'Created' | 'Unlocked' | 'Locked';
+
+// Addresses checked against the breached-account API; needs an API key
+export interface EmailMonitorSettings {
+	emails: string[];
+	api_key?: string | null;
+	base_url?: string | null;
+	interval_hours: number; // 0 = only when checked by hand
+}
+
+export type LogLevel = 'error' | 'warn' | 'info' | 'debug' | 'trace';
+
+// Minimal records security events only; verbose adds searches and listings
+export type AuditLevel = 'minimal' | 'standard' | 'verbose';
+
+// A diagnostic log event from `get_recent_logs`; secrets are already scrubbed
+export interface LogEntry {
+	timestamp: string;
+	level: LogLevel;
+	target: string;
+	message: string;
+	fields: Record<string, string>;
+}
+
+// Where a vault keeps its credentials; a remote server only receives ciphertext
+export type StorageProfile =
+	| { backend: 'local' }
+	| { backend: 'remote'; url: string; token: string };
+
+// Payload of the `vault-externally-modified` event; call `reload_vault` afterwards
+export type ExternalChange = 'modified' | 'replaced' | 'removed';
+
+// A newer version than the running one, from the signed release manifest
+export interface AvailableUpdate {
+	version: string;
+	published_at: string;
+	notes: string;
+	size: number; // bytes
+}
+
+// Returned by `check_for_updates`; `update` is null when this is the latest version
+export interface UpdateCheck {
+	current_version: string;
+	update: AvailableUpdate | null;
+}
+
+// Payload of the `master-password-reminder` event sent after unlocking
+export interface MasterPasswordReminder {
+	reasons: ('weak' | 'old')[];
+	score: number; // 0-4, recorded when the password was set
+	min_score: number;
+	set_at: string;
+	age_days: number;
+}
+
+// An item that failed the integrity check after unlock
+export interface DamagedItem {
+	uuid: string;
+	site: string;
+	username: string;
+	kind: 'decryption' | 'malformed'; // decryption = wrong key or site/username changed outside the app
+}
+
+// Payload of the `integrity-warning` event, sent only when sampled items fail to decrypt
+export interface IntegrityReport {
+	total: number;
+	checked: number;
+	skipped: number; // e.g. shared collections this device left
+	damaged: DamagedItem[];
+}
+
+// From `get_data_location`; portable when a `portable.txt` next to the executable moves the data beside it
+export interface DataLocation {
+	dir: string;
+	portable: boolean;
+}
+
+// What a backend notification is about
+export type NotificationCategory = 'breach' | 'expiry' | 'sync_conflict' | 'backup_failed';
+
+// Categories shown as desktop notifications; the history records all of them
+export interface NotificationSettings {
+	breach: boolean;
+	expiry: boolean;
+	sync_conflict: boolean;
+	backup_failed: boolean;
+}
+
+// An entry of `list_notifications`, also the payload of the `notification-added` event
+export interface Notification {
+	id: string;
+	category: NotificationCategory;
+	title: Message;
+	body: Message;
+	created_at: string;
+	shown: boolean; // false if its category is turned off or showing it failed
+	read?: boolean;
+}
+
+// From `get_app_status`; features and the backup time are empty while locked
+export interface AppStatus {
+	version: string;
+	vault_status: VaultStatus;
+	features: Feature[]; // enabled for this vault
+	pending_notifications: number; // unread
+	last_backup_at: string | null;
+	last_sync_at: string | null; // remote vaults only
+}
+
+// From `import_settings_profile`; secrets are never part of a profile
+export interface SettingsProfileImport {
+	changed: string[]; // settings fields that changed
+	kdf_differs: boolean; // the profile's KDF parameters differ from this machine's
+	kdf_applied: boolean;
+}
+
+// Whether a memory protection is in effect; 'inactive' = not applied yet or nothing to protect
+export type MitigationState = 'active' | 'failed' | 'unsupported' | 'inactive';
+
+// From `get_hardening_status`; process-wide protections are applied at the first unlock
+export interface HardeningStatus {
+	core_dumps_disabled: MitigationState;
+	debugger_blocked: MitigationState;
+	injection_blocked: MitigationState; // Windows only
+	key_memory_locked: MitigationState; // the vault key kept out of swap
+}
+
+// Payload of the `unlock-progress` event
+export type UnlockProgress = 'derivation_started' | 'derivation_finished' | 'cancelled';
+
+// Payload of the `update-download-progress` event
+export interface UpdateProgress {
+	downloaded: number;
+	total: number;
+}
+
+// Returned by `download_update` once the artifact's signature has been verified
+export interface DownloadedUpdate {
+	version: string;
+	path: string;
+}
+
+// Proxy and offline mode for every network feature
+export interface NetworkSettings {
+	offline: boolean;
+	proxy: { mode: 'system' } | { mode: 'direct' } | { mode: 'manual'; url: string };
+	// Per-feature network permissions, all on by default
+	allow_hibp?: boolean; // password and email breach lookups
+	allow_icons?: boolean;
+	allow_sync?: boolean;
+	allow_update_checks?: boolean;
+}
+
+// Unencrypted UI preferences, readable at the lock screen
+export interface UiPreferences {
+	theme: 'light' | 'dark' | 'system';
+	locale?: string | null;
+	auto_lock_timeout: number; // minutes, mirrors AppSettings
+}
+
+// A translatable backend message: catalog key plus named params
+export interface Message {
+	key: string;
+	params?: Record<string, string>;
+}
+
+// Error payload of every command; `message` is already rendered in the backend locale
+export interface CommandError extends Message {
+	message: string;
+	cause?: Message;
+}
+
+// Aggregate vault health at one point in time, see `get_security_trend`
+export interface SecuritySnapshot {
+	taken_at: string;
+	total: number;
+	weak: number;
+	compromised: number;
+	reused: number;
+	strength_histogram: [number, number, number, number, number]; // index = strength bucket (strength / 20, capped at 4)
+}
+
+export type TrendRange = 'month' | 'quarter' | 'year' | 'all';
+
+// Item counts and password health, from `get_vault_statistics` and the
+// `vault-stats-changed` event sent shortly after items change
+export interface VaultStatistics {
+	total: number;
+	logins: number;
+	api_keys: number;
+	cards: number;
+	identities: number;
+	wifi: number;
+	// The rest count only items with a password (logins, API keys and Wi-Fi)
+	weak: number;
+	compromised: number;
+	reused: number | null; // null outside a bulk session, which comparing passwords needs
+	strength_histogram: [number, number, number, number, number];
+	performance_mode_active: boolean; // large-vault optimizations in use, see `performance_mode`
+}
+
+export type Feature = 'browser_bridge' | 'ssh_agent';
+
+// A per-vault feature flag; experimental features are unavailable outside
+// builds made with the `experimental` Cargo feature
+export interface FeatureState {
+	feature: Feature;
+	enabled: boolean;
+	default_enabled: boolean;
+	experimental: boolean;
+	available: boolean;
+}
+
+// Result of `verify_signed_audit_export`, checked against the given public key
+// or the unlocked vault's own (`get_audit_public_key`)
+export interface AuditVerification {
+	valid: boolean;
+	signed_by_this_vault: boolean;
+	entry_count: number;
+	exported_at: string;
+	problem?: Message | null;
+}
+
+// A shared collection; its key is wrapped for every member's X25519 public key
+export interface Collection {
+	id: string;
+	name: string;
+	created_at: string;
+	members: CollectionMember[];
+}
+
+export interface CollectionMember {
+	public_key: string; // Base64 X25519
+	label: string;
+	added_at: string;
+	wrapped_key: { ephemeral_public_key: string; ciphertext: string };
+}
+
+// Local record of a one-time share (never contains the key)
+export interface ShareRecord {
+	id: string;
+	item_uuid: string;
+	site: string;
+	destination: 'relay' | 'file';
+	location: string; // relay base URL or file path
+	created_at: string;
+	expires_at: string;
+	max_views: number;
+	revoked_at: string | null;
+}
+
+// Trusted contact who can recover the vault after a waiting period
+export interface EmergencyContact {
+	id: string;
+	label: string;
+	public_key: string; // Base64 X25519
+	wait_days: number; // Enforced by the app, not cryptographically: a contact with the database file skips it
+	state: 'active' | 'recovery_requested' | 'recovery_granted' | 'revoked';
+	created_at: string;
+	requested_at: string | null;
+	state_changed_at: string;
+}
+
+// An installation that has opened the vault; revoked devices can no longer unlock it
+export interface Device {
+	id: string;
+	name: string;
+	platform: string;
+	public_key: string; // Base64 Ed25519
+	first_seen: string;
+	last_seen: string;
+	revoked_at: string | null;
+}
+
+// Returned by `compact_vault`; progress arrives as `compact-progress` events
+export interface CompactReport {
+	size_before: number;
+	size_after: number;
+	freed_pages: number;
+	wiped: boolean;
+}
+
+export type MaintenanceTask = 'wal_checkpoint' | 'optimize' | 'audit_archive' | 'share_cleanup';
+
+// Returned by `run_maintenance_now`, and sent as `maintenance-completed` after idle-time runs
+export interface MaintenanceReport {
+	ran: MaintenanceTask[];
+	checkpointed_pages: number | null; // null when the database has no write-ahead log
+	audit_entries_archived: number;
+	shares_removed: number;
+	errors: string[]; // failed tasks are retried on the next run
+	last_run: Partial<Record<MaintenanceTask, string>>;
+}
+
+// Result of `create_diagnostics_bundle`; the zip holds no secrets, paths or URLs
+export interface DiagnosticsReport {
+	path: string;
+	files: string[]; // summary.json, settings.json (only while unlocked), logs.txt
+	integrity_ok?: boolean | null; // null when the vault has no local database
+	nonces_ok?: boolean | null; // false if a nonce was reused; null while locked
+}
+
+// A nonce used more than once with one key: a sign of a failed random number generator
+export interface NonceReuse {
+	key_epoch: string; // 'vault', 'travel' or 'collection:<id>'
+	nonce: string;
+	locations: string[];
+}
+
+// Result of `verify_no_nonce_reuse`
+export interface NonceAuditReport {
+	containers_scanned: number;
+	reused: NonceReuse[]; // empty for a healthy vault
+	unreadable: string[];
+}
+
+export interface CompactProgress {
+	stage: 'vacuuming' | 'wiping' | 'restoring';
+	done: number;
+	total: number;
+}
+
+// Returned once by `enable_travel_mode`; the blob is the only copy of the travel key
+export interface TravelActivation {
+	hidden_count: number;
+	recovery_blob: string;
+}
+
+export interface TravelModeStatus {
+	active: boolean;
+	enabled_at: string | null;
+	hidden_count: number;
+}
+
+// Returned once by `create_share`
+export interface ShareLink {
+	share: ShareRecord;
+	key: string; // Base64url; send separately for file shares
+	link: string | null; // relay link with the key in the fragment
+}
+
+// Character sets and length for generated passwords
+export interface GeneratorOptions {
+	length: number;
+	use_uppercase: boolean;
+	use_lowercase: boolean;
+	use_numbers: boolean;
+	use_symbols: boolean;
+	exclude_similar: boolean;
+}
+
+export type CharacterClass = 'uppercase' | 'lowercase' | 'numbers' | 'symbols';
+
+// Password rules of a site, honored by `generate_password_for`
+export interface GeneratorConstraints {
+	max_length?: number | null;
+	forbidden_characters?: string;
+	required_classes?: CharacterClass[];
+}
+
+// Password rules and the change-password page known for a site
+export interface DomainInfo {
+	domain: string; // registrable domain the site was matched by
+	known: boolean; // the built-in dataset has an entry for it
+	password_rules: string | null; // in the `password-rules` syntax
+	min_length: number | null;
+	constraints: GeneratorConstraints | null;
+	change_password_url: string; // falls back to /.well-known/change-password
+}
+
+export type ConflictResolution = 'keep_existing' | 'overwrite' | 'keep_both' | 'merge_fields';
+
+// An imported row matching an existing item by site and username, waiting for a decision
+export interface ImportConflict {
+	id: number;
+	existing: CredentialSummary | null; // null if the item was deleted since the import
+	site: string;
+	username: string;
+	tags: string[];
+	differences: string[]; // field names such as 'password' or 'custom_fields.pin', never values
+	created_at: string;
+}
+
+// A personal list of compromised passwords imported with `import_breach_list`
+export interface BreachListSource {
+	source: string;
+	entries: number;
+	imported_at: string;
+}
+
+export interface BreachListImport {
+	source: string;
+	read: number; // distinct entries in the file
+	added: number; // entries the list did not have yet
+}
+
+// Elevation needed by reports that read every secret, e.g. reuse detection and
+// breach checks of the whole vault; see `begin_bulk_session`
+export interface BulkSession {
+	purpose: string;
+	started_at: string;
+	expires_at: string; // a few minutes after it started
+}
+
+// An item whose password is on an imported list; it is marked Compromised
+export interface LocalBreachMatch {
+	uuid: string;
+	site: string;
+	username: string;
+	sources: string[];
+}
+
+// Payload of the `breach-precheck-complete` event
+export interface BreachPrecheck {
+	uuid: string;
+	result: BreachCheckResult | null;
+	error: CommandError | null;
+}
+
+// Emitted as `strength-recalc-progress` after each batch and `strength-recalc-complete` at the end
+export interface StrengthRecalcProgress {
+	processed: number;
+	total: number;
+	changed: number;
+	failed: number; // could not be decrypted; the job runs again on the next unlock
+}
+
+// User-loaded word lists, stored encrypted; `set_wordlists` returns them normalized
+export interface Wordlists {
+	banned_passwords: string[]; // score 0 and fail the master password policy
+	dictionary: string[]; // extra words treated as easy to guess
+}
+
+// An account a rotation session has dealt with
+export interface RotationEntry {
+	uuid: string;
+	site: string;
+	username: string;
+}
+
+// Returned by `next_rotation_item`: change the password on the site, then confirm or skip
+export interface RotationItem extends RotationEntry {
+	current_password: string;
+	new_password: string; // saved only by `confirm_rotated`
+	position: number; // 1-based
+	total: number;
+}
+
+export interface RotationStatus {
+	session_id: string;
+	total: number;
+	remaining: number;
+	rotated: RotationEntry[];
+	needs_manual_rotation: RotationEntry[]; // skipped; still to be changed on the website
+	finished: boolean;
+}
+
+// Changes `bulk_edit` makes to every selected item
+export interface BulkEdit {
+	add_tags?: string[];
+	remove_tags?: string[];
+}
+
+export type OperationKind = 'delete' | 'bulk_edit' | 'tag_rename';
+
+export interface OperationSummary {
+	kind: OperationKind;
+	items: number;
+}
+
+// What undo and redo would do next; the history ends when the vault locks
+export interface UndoStatus {
+	undo: OperationSummary | null;
+	redo: OperationSummary | null;
+}
+
+export interface AccountBreach {
+	Name: string;
+	Title: string;
+	Domain: string;
+	BreachDate: string | null;
+	AddedDate: string;
+	DataClasses: string[];
+}
+
+// A breach affecting a monitored address; also sent as `email-breaches-found` events
+export interface BreachNotification {
+	id: string;
+	email: string;
+	breach: AccountBreach;
+	credential_uuids: string[]; // items logging in with this address
+	created_at: string;
+	dismissed_at: string | null;
+}
+
+export type LookalikeReason = 'homograph' | 'similar_spelling' | 'embedded_domain';
+
+// The page imitates a saved site; nothing may be filled in on it
+export interface LookalikeWarning {
+	domain: string; // as shown to the user, in Unicode
+	resembles: string;
+	reason: LookalikeReason;
+	credential_uuids: string[];
+}
+
+// Returned by `find_credentials_for_url`; `warning` is only set when nothing matched
+export interface UrlMatch {
+	domain: string | null;
+	matches: CredentialSummary[];
+	warning: LookalikeWarning | null;
+}
+
+// A secret found by `scan_pasted_secrets` and the item proposed for it; the value stays in the backend
+export interface DetectedSecret {
+	id: number; // pass to `save_pasted_secrets` with the same text
+	kind: 'api_key' | 'database_url' | 'variable';
+	name: string | null; // the variable it was assigned to
+	provider: string | null; // e.g. GitHub, for keys in a known format
+	site: string;
+	username: string;
+	preview: string; // mostly hidden, e.g. `ghp_…Xy9z`
+	line: number;
+}
+
+// A login captured by the browser extension, waiting for approval; also sent as
+// `pending-save-added` events. The password stays in the backend.
+export interface PendingSave {
+	id: string;
+	url: string;
+	site: string;
+	username: string;
+	existing_uuid: string | null; // approving replaces this item's password
+	warning: LookalikeWarning | null; // the page only resembles a saved site
+	captured_at: string;
+}
+
+// An otpauth:// or secretplan:// link the app was opened with; `deep-link-received` announces new ones
+export interface DeepLinkRequest {
+	id: string;
+	kind: 'totp' | 'open_credential';
+	issuer: string | null;
+	account: string | null;
+	credential_uuid: string | null; // the item an open_credential link points to
+	candidates: CredentialSummary[]; // items a TOTP setup probably belongs to, best first
+	received_at: string;
+}
+
+// Sent as `autotype-requested` when the shortcut fires; confirm or cancel it
+export interface AutotypeRequest {
+	id: string;
+	window_title: string;
+	candidates: { uuid: string; site: string; username: string }[]; // empty: let the user pick
+}
+
+// A `quick_search` result; higher scores are better matches
+export interface QuickSearchHit {
+	credential: CredentialSummary;
+	score: number;
+}
+
+// A tag in use, with the number of items carrying it
+export interface TagSuggestion {
+	tag: string;
+	count: number;
+}
+
+// How the free text of a search is matched: as written, allowing typos, or as a regular expression
+export type MatchMode = 'substring' | 'fuzzy' | 'regex';
+
+// Built-in filters for tidying up a vault; all that are set must hold
+export interface SmartFilters {
+	modified_within_days?: number | null;
+	created_within_days?: number | null;
+	never_used?: boolean; // never revealed or auto-typed
+	no_totp?: boolean;
+	no_tags?: boolean;
+}
+
+export type ViewSortField =
+	| 'site'
+	| 'username'
+	| 'created_at'
+	| 'updated_at'
+	| 'password_changed_at'
+	| 'strength';
+
+// A saved view's filter, sort order and columns; missing fields take their defaults
+export interface ViewConfig {
+	filter: {
+		search_term?: string | null;
+		tag?: string | null;
+		min_strength?: number | null;
+		breach_state?: 'Unknown' | 'Safe' | 'Compromised' | null;
+		password_older_than_days?: number | null;
+		match_mode?: MatchMode;
+		smart?: SmartFilters;
 	};
+	sort: { field: ViewSortField; direction: 'ascending' | 'descending' };
+	columns: string[]; // stored as given
+}
+
+// From `list_views`, ordered by name
+export interface SavedView {
+	name: string;
+	config: ViewConfig;
 }